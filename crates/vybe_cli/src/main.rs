@@ -1,19 +1,89 @@
 use std::env;
 use std::path::PathBuf;
 
+use vybe_runtime::{Capability, SandboxPolicy};
+
+const USAGE: &str = "\
+Usage: vybe [sandbox options] <filename.vb|filename.vbp|filename.vbproj> [args...]
+       vybe --check <filename.vb|filename.vbp|filename.vbproj>
+
+Sandbox options (later ones override earlier ones):
+  --sandbox                    deny everything not allowed by another option
+  --allow-fs[=DIR]             allow file access, only inside DIR if given (repeatable)
+  --deny-fs                    deny file access
+  --allow-net, --deny-net      sockets and HTTP
+  --allow-process, --deny-process
+  --allow-env, --deny-env      environment variables
+  --allow-db, --deny-db        database connections
+  --allow-dialogs, --deny-dialogs";
+
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: vybe <filename.vb|filename.vbp|filename.vbproj> [args...]");
+
+    // `--check` reports syntax errors without running anything (for CI).
+    let mut check_only = false;
+    let mut sandbox = SandboxPolicy::default();
+    let mut rest = args.iter().skip(1);
+    let file_arg = loop {
+        match rest.next() {
+            Some(flag) if flag == "--check" => check_only = true,
+            Some(flag) if flag.starts_with("--") => {
+                if !apply_sandbox_flag(&mut sandbox, flag) {
+                    eprintln!("Unknown option: {flag}");
+                    eprintln!("{USAGE}");
+                    std::process::exit(1);
+                }
+            }
+            other => break other,
+        }
+    };
+    let Some(file_arg) = file_arg else {
+        eprintln!("{USAGE}");
         std::process::exit(1);
-    }
+    };
 
-    let file_path = PathBuf::from(&args[1]);
+    let file_path = PathBuf::from(file_arg);
     if !file_path.exists() {
         eprintln!("Error: file not found: {}", file_path.display());
         std::process::exit(1);
     }
 
-    let extra_args: Vec<String> = args[2..].to_vec();
-    vybe_ui::run(&file_path, &extra_args);
+    if check_only {
+        std::process::exit(if vybe_ui::check(&file_path) { 0 } else { 1 });
+    }
+
+    let extra_args: Vec<String> = rest.cloned().collect();
+    vybe_ui::run(&file_path, &extra_args, &sandbox);
+}
+
+/// Apply one sandbox option to `policy`; false if `flag` isn't one.
+fn apply_sandbox_flag(policy: &mut SandboxPolicy, flag: &str) -> bool {
+    if flag == "--sandbox" {
+        *policy = SandboxPolicy::deny_all();
+        return true;
+    }
+    if let Some(root) = flag.strip_prefix("--allow-fs=") {
+        policy.allow_files_within(root);
+        return true;
+    }
+    let (name, allowed) = if let Some(name) = flag.strip_prefix("--allow-") {
+        (name, true)
+    } else if let Some(name) = flag.strip_prefix("--deny-") {
+        (name, false)
+    } else {
+        return false;
+    };
+    let capabilities: &[Capability] = match name {
+        "fs" => &[Capability::Files],
+        "net" => &[Capability::Sockets, Capability::Http],
+        "process" => &[Capability::Processes],
+        "env" => &[Capability::Environment],
+        "db" => &[Capability::Databases],
+        "dialogs" => &[Capability::Dialogs],
+        _ => return false,
+    };
+    for &capability in capabilities {
+        policy.allow(capability, allowed);
+    }
+    true
 }
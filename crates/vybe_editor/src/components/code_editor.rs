@@ -1,6 +1,10 @@
 use dioxus::prelude::*;
 use crate::app_state::{AppState, ResourceTarget};
 use vybe_forms::EventType;
+use vybe_parser::{check_syntax_with, Severity};
+
+/// How long the code has to sit unchanged before it is syntax-checked.
+const SYNTAX_CHECK_DELAY_MS: u64 = 300;
 
 #[component]
 pub fn CodeEditor() -> Element {
@@ -135,6 +139,68 @@ pub fn CodeEditor() -> Element {
     // Track if Monaco is ready
     let mut monaco_ready = use_signal(|| false);
 
+    // Syntax errors for whatever Monaco is showing (user code or designer code).
+    // Recovering from errors can re-parse a broken file many times, so the check
+    // waits until typing pauses and runs on a worker thread; a newer edit bumps
+    // the generation and the stale result is dropped.
+    let mut diagnostics = use_signal(Vec::new);
+    let mut check_generation = use_signal(|| 0u64);
+    use_effect(move || {
+        let constants = state.project.read().as_ref()
+            .map(|p| p.define_constants.clone())
+            .unwrap_or_default();
+        let code = if *code_tab.read() == "designer" {
+            state.get_current_designer_code()
+        } else {
+            state.get_current_code()
+        };
+        let generation = *check_generation.peek() + 1;
+        check_generation.set(generation);
+        spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(SYNTAX_CHECK_DELAY_MS)).await;
+            if *check_generation.peek() != generation {
+                return;
+            }
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            std::thread::spawn(move || {
+                let _ = tx.send(check_syntax_with(&code, &constants));
+            });
+            if let Ok(found) = rx.await {
+                if *check_generation.peek() == generation {
+                    diagnostics.set(found);
+                }
+            }
+        });
+    });
+
+    // Mirror the diagnostics as Monaco markers (squiggles + hover messages)
+    use_effect(move || {
+        let diags = diagnostics.read();
+        if !*monaco_ready.read() {
+            return;
+        }
+        let markers: Vec<serde_json::Value> = diags
+            .iter()
+            .filter(|d| !d.span.is_unknown())
+            .map(|d| {
+                serde_json::json!({
+                    // monaco.MarkerSeverity: Warning = 4, Error = 8
+                    "severity": if d.severity == Severity::Error { 8 } else { 4 },
+                    "code": d.code,
+                    "message": d.message,
+                    "startLineNumber": d.span.start_line,
+                    "startColumn": d.span.start_col,
+                    "endLineNumber": d.span.end_line,
+                    "endColumn": d.span.end_col.max(d.span.start_col + 1),
+                })
+            })
+            .collect();
+        let _ = document::eval(&format!(
+            "if (window.monaco && window.monacoEditor) monaco.editor.setModelMarkers(window.monacoEditor.getModel(), 'vybe', {});",
+            serde_json::Value::Array(markers)
+        ));
+    });
+
     // use_effect runs AFTER DOM is committed, guaranteeing #monaco-container exists.
     // spawn() starts the async recv loop in the component's scope.
     // On remount (after run/stop), the component is fresh: effect runs again, new eval + spawn.
@@ -362,6 +428,30 @@ pub fn CodeEditor() -> Element {
                 style: "flex: 1; width: 100%; height: 100%; overflow: hidden;",
                 "Loading editor..."
             }
+
+            // Error list
+            if !diagnostics.read().is_empty() {
+                div {
+                    class: "error-list",
+                    style: "max-height: 120px; overflow-y: auto; border-top: 1px solid #ccc; background: #fff8f8; font-family: monospace; font-size: 12px;",
+                    for (label, span) in diagnostics.read().iter().map(|d| (d.to_string(), d.span)) {
+                        div {
+                            style: "padding: 2px 6px; cursor: pointer; color: #a00;",
+                            onclick: move |_| {
+                                if span.is_unknown() {
+                                    return;
+                                }
+                                let _ = document::eval(&format!(
+                                    "if (window.monacoEditor) {{ window.monacoEditor.revealLineInCenter({line}); window.monacoEditor.setPosition({{ lineNumber: {line}, column: {col} }}); window.monacoEditor.focus(); }}",
+                                    line = span.start_line,
+                                    col = span.start_col,
+                                ));
+                            },
+                            "{label}"
+                        }
+                    }
+                }
+            }
         }
     }
 }
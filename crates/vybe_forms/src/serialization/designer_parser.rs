@@ -1,7 +1,7 @@
 use crate::control::{Control, ControlType};
 use crate::events::{EventBinding, EventType};
 use crate::form::Form;
-use vybe_parser::{ClassDecl, Expression, Statement};
+use vybe_parser::{ClassDecl, ExprKind, Expression, Statement};
 
 /// Extracts the last component of a potentially dotted identifier.
 /// e.g. "System.Windows.Forms.Button" -> "Button", "Button" -> "Button"
@@ -181,11 +181,12 @@ impl ControlBuilder {
 /// so round-trip fidelity is preserved for complex expressions.
 fn expr_to_property_value(expr: &Expression) -> crate::properties::PropertyValue {
     use crate::properties::PropertyValue;
-    match expr {
-        Expression::StringLiteral(s) => PropertyValue::String(s.clone()),
-        Expression::IntegerLiteral(i) => PropertyValue::Integer(*i),
-        Expression::BooleanLiteral(b) => PropertyValue::Boolean(*b),
-        Expression::DoubleLiteral(d) => PropertyValue::Double(*d),
+    match &expr.kind {
+        ExprKind::StringLiteral(s) => PropertyValue::String(s.clone()),
+        ExprKind::IntegerLiteral(i) => PropertyValue::Integer(*i),
+        ExprKind::BooleanLiteral(b) => PropertyValue::Boolean(*b),
+        ExprKind::DoubleLiteral(d) => PropertyValue::Double(*d),
+        ExprKind::SingleLiteral(f) => PropertyValue::Double(*f as f64),
         _ => PropertyValue::Expression(expr_to_code(expr)),
     }
 }
@@ -193,28 +194,30 @@ fn expr_to_property_value(expr: &Expression) -> crate::properties::PropertyValue
 /// Reconstruct a VB.NET code string from an AST expression.
 /// Used for preserving complex property values (images, enums, etc.) verbatim.
 fn expr_to_code(expr: &Expression) -> String {
-    match expr {
-        Expression::Variable(id) => id.as_str().to_string(),
-        Expression::MemberAccess(obj, member) => {
+    match &expr.kind {
+        ExprKind::Variable(id) => id.as_str().to_string(),
+        ExprKind::MemberAccess(obj, member) => {
             format!("{}.{}", expr_to_code(obj), member.as_str())
         }
-        Expression::Call(func, args) => {
+        ExprKind::Call(func, args) => {
             let arg_strs: Vec<String> = args.iter().map(expr_to_code).collect();
             format!("{}({})", func.as_str(), arg_strs.join(", "))
         }
-        Expression::MethodCall(obj, method, args) => {
+        ExprKind::MethodCall(obj, method, args) => {
             let arg_strs: Vec<String> = args.iter().map(expr_to_code).collect();
             format!("{}.{}({})", expr_to_code(obj), method.as_str(), arg_strs.join(", "))
         }
-        Expression::New(type_name, args) => {
+        ExprKind::New(type_name, args) => {
             let arg_strs: Vec<String> = args.iter().map(expr_to_code).collect();
             format!("New {}({})", type_name.as_str(), arg_strs.join(", "))
         }
-        Expression::StringLiteral(s) => format!("\"{}\"", s.replace('"', "\"\"")),
-        Expression::IntegerLiteral(i) => i.to_string(),
-        Expression::BooleanLiteral(b) => if *b { "True".to_string() } else { "False".to_string() },
-        Expression::DoubleLiteral(d) => d.to_string(),
-        Expression::Me => "Me".to_string(),
+        ExprKind::StringLiteral(s) => format!("\"{}\"", s.replace('"', "\"\"")),
+        ExprKind::IntegerLiteral(i) => i.to_string(),
+        ExprKind::BooleanLiteral(b) => if *b { "True".to_string() } else { "False".to_string() },
+        ExprKind::DoubleLiteral(d) => d.to_string(),
+        ExprKind::SingleLiteral(f) => format!("{}F", f),
+        ExprKind::LongLiteral(l) => format!("{}L", l),
+        ExprKind::Me => "Me".to_string(),
         _ => "Nothing".to_string(),
     }
 }
@@ -262,12 +265,12 @@ pub fn extract_form_from_designer(class_decl: &ClassDecl) -> Option<Form> {
                         }
                     }
                     "text" => {
-                        if let Expression::StringLiteral(s) = value {
+                        if let ExprKind::StringLiteral(s) = &value.kind {
                             form.text = s.clone();
                         }
                     }
                     "name" => {
-                        if let Expression::StringLiteral(s) = value {
+                        if let ExprKind::StringLiteral(s) = &value.kind {
                             form.name = s.clone();
                         }
                     }
@@ -298,7 +301,7 @@ pub fn extract_form_from_designer(class_decl: &ClassDecl) -> Option<Form> {
                     | "minimumsize" | "maximumsize" | "transparencykey"
                     | "topmost" | "opacity" => {}
                     _ => {
-                        if let Expression::New(type_id, _) = value {
+                        if let ExprKind::New(type_id, _) = &value.kind {
                             // Me.X = New SomeType() → register as a control
                             if let Some(ct) = vbnet_type_to_control_type(type_id.as_str()) {
                                 if !builders.contains_key(member_name) {
@@ -332,10 +335,10 @@ pub fn extract_form_from_designer(class_decl: &ClassDecl) -> Option<Form> {
             }
 
             // ── ExpressionStatement method calls (DataBindings.Add, Controls.Add) ──
-            Statement::ExpressionStatement(Expression::MethodCall(obj, method, args)) => {
+            Statement::ExpressionStatement(Expression { kind: ExprKind::MethodCall(obj, method, args), .. }) => {
                 let method_lower = method.as_str().to_lowercase();
 
-                if let Expression::MemberAccess(inner, member_id) = obj.as_ref() {
+                if let ExprKind::MemberAccess(inner, member_id) = &obj.kind {
                     let member_str = member_id.as_str();
 
                     match method_lower.as_str() {
@@ -343,19 +346,19 @@ pub fn extract_form_from_designer(class_decl: &ClassDecl) -> Option<Form> {
                             // Me.ctrl.DataBindings.Add("PropName", Me.bs, "Column" [, ...])
                             if let Some((ctrl_name, true)) = extract_me_member_target(inner) {
                                 if args.len() >= 3 {
-                                    let prop_name = match &args[0] {
-                                        Expression::StringLiteral(s) => Some(s.clone()),
+                                    let prop_name = match &args[0].kind {
+                                        ExprKind::StringLiteral(s) => Some(s.clone()),
                                         _ => None,
                                     };
-                                    let bs_name = match &args[1] {
-                                        Expression::MemberAccess(inner2, m) if is_me(inner2) => {
+                                    let bs_name = match &args[1].kind {
+                                        ExprKind::MemberAccess(inner2, m) if is_me(inner2) => {
                                             Some(m.as_str().to_string())
                                         }
-                                        Expression::StringLiteral(s) => Some(s.clone()),
+                                        ExprKind::StringLiteral(s) => Some(s.clone()),
                                         _ => None,
                                     };
-                                    let col_name = match &args[2] {
-                                        Expression::StringLiteral(s) => Some(s.clone()),
+                                    let col_name = match &args[2].kind {
+                                        ExprKind::StringLiteral(s) => Some(s.clone()),
                                         _ => None,
                                     };
                                     if let (Some(prop), Some(bs), Some(col)) =
@@ -487,7 +490,7 @@ fn apply_control_property(
             }
         }
         "text" => {
-            if let Expression::StringLiteral(s) = value {
+            if let ExprKind::StringLiteral(s) = &value.kind {
                 builder.text = Some(s.clone());
             }
         }
@@ -523,26 +526,26 @@ fn apply_control_property(
             }
         }
         "name" => {
-            if let Expression::StringLiteral(s) = value {
+            if let ExprKind::StringLiteral(s) = &value.kind {
                 builder.explicit_name = Some(s.clone());
             }
         }
         "tag" => {
-            if let Expression::StringLiteral(s) = value {
+            if let ExprKind::StringLiteral(s) = &value.kind {
                 builder.tag = Some(s.clone());
             } else {
                 builder.extra_props.insert("Tag".to_string(), expr_to_property_value(value));
             }
         }
         "tabindex" => {
-            if let Expression::IntegerLiteral(n) = value {
+            if let ExprKind::IntegerLiteral(n) = &value.kind {
                 builder.tab_index = *n;
             }
         }
         // ── Data-source / binding properties ─────────────────────────────────
         "datasource" => {
-            let pv = match value {
-                Expression::MemberAccess(inner, m) if is_me(inner) => {
+            let pv = match &value.kind {
+                ExprKind::MemberAccess(inner, m) if is_me(inner) => {
                     crate::properties::PropertyValue::String(m.as_str().to_string())
                 }
                 _ => expr_to_property_value(value),
@@ -559,8 +562,8 @@ fn apply_control_property(
             builder.extra_props.insert("ValueMember".to_string(), expr_to_property_value(value));
         }
         "bindingsource" => {
-            let pv = match value {
-                Expression::MemberAccess(inner, m) if is_me(inner) => {
+            let pv = match &value.kind {
+                ExprKind::MemberAccess(inner, m) if is_me(inner) => {
                     crate::properties::PropertyValue::String(m.as_str().to_string())
                 }
                 _ => expr_to_property_value(value),
@@ -598,12 +601,12 @@ fn apply_control_property(
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
 fn is_me(expr: &Expression) -> bool {
-    matches!(expr, Expression::Me)
+    matches!(&expr.kind, ExprKind::Me)
 }
 
 /// Given `MemberAccess(Me, "Button1")` → `("Button1", true)`.
 fn extract_me_member_target(expr: &Expression) -> Option<(String, bool)> {
-    if let Expression::MemberAccess(inner, member) = expr {
+    if let ExprKind::MemberAccess(inner, member) = &expr.kind {
         if is_me(inner) {
             return Some((member.as_str().to_string(), true));
         }
@@ -613,7 +616,7 @@ fn extract_me_member_target(expr: &Expression) -> Option<(String, bool)> {
 
 /// Extract `(x, y)` from `New [System.Drawing.]Point(x, y)`.
 fn extract_point(expr: &Expression) -> Option<(i32, i32)> {
-    if let Expression::New(id, args) = expr {
+    if let ExprKind::New(id, args) = &expr.kind {
         if last_component(id.as_str()).eq_ignore_ascii_case("Point") && args.len() == 2 {
             let x = expr_to_i32(&args[0])?;
             let y = expr_to_i32(&args[1])?;
@@ -625,7 +628,7 @@ fn extract_point(expr: &Expression) -> Option<(i32, i32)> {
 
 /// Extract `(w, h)` from `New [System.Drawing.]Size(w, h)`.
 fn extract_size(expr: &Expression) -> Option<(i32, i32)> {
-    if let Expression::New(id, args) = expr {
+    if let ExprKind::New(id, args) = &expr.kind {
         if last_component(id.as_str()).eq_ignore_ascii_case("Size") && args.len() == 2 {
             let w = expr_to_i32(&args[0])?;
             let h = expr_to_i32(&args[1])?;
@@ -638,9 +641,9 @@ fn extract_size(expr: &Expression) -> Option<(i32, i32)> {
 /// Extract color as `#RRGGBB` from common VB.NET designer color expressions.
 /// Returns `None` for named/system colors so the caller can fall back to Expression storage.
 fn extract_color(expr: &Expression) -> Option<String> {
-    match expr {
-        Expression::Call(ident, args) => try_extract_color_call(ident.as_str(), args),
-        Expression::MethodCall(_, ident, args) => try_extract_color_call(ident.as_str(), args),
+    match &expr.kind {
+        ExprKind::Call(ident, args) => try_extract_color_call(ident.as_str(), args),
+        ExprKind::MethodCall(_, ident, args) => try_extract_color_call(ident.as_str(), args),
         _ => None,
     }
 }
@@ -648,7 +651,7 @@ fn extract_color(expr: &Expression) -> Option<String> {
 fn try_extract_color_call(name: &str, args: &[Expression]) -> Option<String> {
     match name.to_lowercase().as_str() {
         "fromhtml" if args.len() == 1 => {
-            if let Expression::StringLiteral(s) = &args[0] {
+            if let ExprKind::StringLiteral(s) = &args[0].kind {
                 return Some(s.clone());
             }
             None
@@ -677,13 +680,13 @@ fn try_extract_color_call(name: &str, args: &[Expression]) -> Option<String> {
 /// Extract font string `"Family, sizepx"` from `New [System.Drawing.]Font(family, size[, style])`.
 /// The optional style argument is preserved via extra_props by the caller if needed.
 fn extract_font(expr: &Expression) -> Option<String> {
-    if let Expression::New(id, args) = expr {
+    if let ExprKind::New(id, args) = &expr.kind {
         if last_component(id.as_str()).eq_ignore_ascii_case("Font") && args.len() >= 2 {
-            if let Expression::StringLiteral(fam) = &args[0] {
+            if let ExprKind::StringLiteral(fam) = &args[0].kind {
                 let size = match expr_to_i32(&args[1]) {
                     Some(i) => i as f32,
-                    None => match &args[1] {
-                        Expression::DoubleLiteral(d) => *d as f32,
+                    None => match &args[1].kind {
+                        ExprKind::DoubleLiteral(d) => *d as f32,
                         _ => 12.0,
                     },
                 };
@@ -700,9 +703,10 @@ fn extract_font(expr: &Expression) -> Option<String> {
 }
 
 fn expr_to_i32(expr: &Expression) -> Option<i32> {
-    match expr {
-        Expression::IntegerLiteral(n) => Some(*n),
-        Expression::DoubleLiteral(d) => Some(*d as i32),
+    match &expr.kind {
+        ExprKind::IntegerLiteral(n) => Some(*n),
+        ExprKind::DoubleLiteral(d) => Some(*d as i32),
+        ExprKind::SingleLiteral(f) => Some(*f as i32),
         _ => None,
     }
 }
@@ -27,25 +27,43 @@ impl std::fmt::Display for Identifier {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum VBType {
+    SByte,
+    Byte,
+    Short,
+    UShort,
     Integer,
+    UInteger,
     Long,
+    ULong,
     Single,
     Double,
+    Decimal,
     String,
     Boolean,
     Date,
     Object,
     Variant,
+    /// `String * 20` in a `Type` field: always exactly that many characters.
+    FixedString(usize),
     Custom(String),
 }
 
 impl VBType {
     pub fn from_str(s: &str) -> Self {
+        // A type_name span can end in the whitespace before a following `=`
+        let s = s.trim();
         match s.to_lowercase().as_str() {
+            "sbyte" => VBType::SByte,
+            "byte" => VBType::Byte,
+            "short" | "int16" => VBType::Short,
+            "ushort" | "uint16" => VBType::UShort,
             "integer" | "int32" => VBType::Integer,
+            "uinteger" | "uint32" => VBType::UInteger,
             "long" | "int64" => VBType::Long,
+            "ulong" | "uint64" => VBType::ULong,
             "single" | "float" => VBType::Single,
             "double" => VBType::Double,
+            "decimal" => VBType::Decimal,
             "string" => VBType::String,
             "boolean" | "bool" => VBType::Boolean,
             "date" | "datetime" => VBType::Date,
@@ -59,16 +77,60 @@ impl VBType {
 impl std::fmt::Display for VBType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            VBType::SByte => write!(f, "SByte"),
+            VBType::Byte => write!(f, "Byte"),
+            VBType::Short => write!(f, "Short"),
+            VBType::UShort => write!(f, "UShort"),
             VBType::Integer => write!(f, "Integer"),
+            VBType::UInteger => write!(f, "UInteger"),
             VBType::Long => write!(f, "Long"),
+            VBType::ULong => write!(f, "ULong"),
             VBType::Single => write!(f, "Single"),
             VBType::Double => write!(f, "Double"),
+            VBType::Decimal => write!(f, "Decimal"),
             VBType::String => write!(f, "String"),
             VBType::Boolean => write!(f, "Boolean"),
             VBType::Date => write!(f, "Date"),
             VBType::Object => write!(f, "Object"),
             VBType::Variant => write!(f, "Variant"),
+            VBType::FixedString(len) => write!(f, "String * {}", len),
             VBType::Custom(s) => write!(f, "{}", s),
         }
     }
 }
+
+/// A source range, 1-based line and column, as reported by pest.
+///
+/// Declarations, statements (through their [`Block`](super::Block)) and
+/// expressions carry one. A default (all-zero) span means the node was
+/// synthesized rather than parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Span {
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+}
+
+impl Span {
+    pub fn from_pest(span: pest::Span<'_>) -> Self {
+        let (start_line, start_col) = span.start_pos().line_col();
+        let (end_line, end_col) = span.end_pos().line_col();
+        Span { start_line, start_col, end_line, end_col }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.start_line == 0
+    }
+
+    /// From the start of `self` to the end of `end`.
+    pub fn to(self, end: Span) -> Span {
+        Span { end_line: end.end_line, end_col: end.end_col, ..self }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.start_line, self.start_col)
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Expression, Identifier, Statement, VBType};
+use super::{Block, Expression, Identifier, Span, VBType};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -49,6 +49,10 @@ pub struct VariableDecl {
     pub initializer: Option<Expression>,
     #[serde(default)]
     pub with_events: bool,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -57,14 +61,19 @@ pub struct ConstDecl {
     pub name: Identifier,
     pub const_type: VBType,
     pub value: Expression,
+    #[serde(default)]
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SubDecl {
     pub visibility: Visibility,
     pub name: Identifier,
+    /// `(Of T, ...)` type parameters; empty unless the Sub is generic.
+    #[serde(default)]
+    pub type_parameters: Vec<TypeParameter>,
     pub parameters: Vec<Parameter>,
-    pub body: Vec<Statement>,
+    pub body: Block,
     pub handles: Option<Vec<String>>,
     #[serde(default)]
     pub is_async: bool,
@@ -80,18 +89,32 @@ pub struct SubDecl {
     pub is_shared: bool,
     #[serde(default)]
     pub is_not_overridable: bool,
+    /// `<Serializable>`, `<Obsolete("...")>`, ... applied to the declaration
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    /// Where the declaration appears in its source file.
+    #[serde(default)]
+    pub span: Span,
+    /// Name of the file the declaration was parsed from, if known.
+    #[serde(default)]
+    pub source_file: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FunctionDecl {
     pub visibility: Visibility,
     pub name: Identifier,
+    #[serde(default)]
+    pub type_parameters: Vec<TypeParameter>,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<VBType>,
-    pub body: Vec<Statement>,
+    pub body: Block,
     pub handles: Option<Vec<String>>,
     #[serde(default)]
     pub is_async: bool,
+    /// `Iterator Function`: the body runs lazily, one `Yield` at a time
+    #[serde(default)]
+    pub is_iterator: bool,
     #[serde(default)]
     pub is_extension: bool,
     #[serde(default)]
@@ -104,12 +127,20 @@ pub struct FunctionDecl {
     pub is_shared: bool,
     #[serde(default)]
     pub is_not_overridable: bool,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
+    #[serde(default)]
+    pub source_file: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ClassDecl {
     pub visibility: Visibility,
     pub name: Identifier,
+    #[serde(default)]
+    pub type_parameters: Vec<TypeParameter>,
     pub is_partial: bool,
     pub inherits: Option<VBType>,
     pub implements: Vec<VBType>,
@@ -124,6 +155,14 @@ pub struct ClassDecl {
     pub nested_classes: Vec<ClassDecl>,
     #[serde(default)]
     pub nested_enums: Vec<EnumDecl>,
+    #[serde(default)]
+    pub operators: Vec<OperatorDecl>,
+    #[serde(default)]
+    pub events: Vec<EventDecl>,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -132,8 +171,40 @@ pub struct PropertyDecl {
     pub name: Identifier,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<VBType>,
-    pub getter: Option<Vec<Statement>>,
-    pub setter: Option<(Parameter, Vec<Statement>)>, // Setter has a value parameter and a body
+    pub getter: Option<Block>,
+    pub setter: Option<(Parameter, Block)>, // Setter has a value parameter and a body
+    /// `Default Property Item(index)`: `obj(i)` reads and writes it
+    #[serde(default)]
+    pub is_default: bool,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
+    #[serde(default)]
+    pub source_file: Option<String>,
+}
+
+/// `Shared Operator +(a As Money, b As Money) As Money`, or a conversion
+/// operator `Widening Operator CType(m As Money) As Double`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperatorDecl {
+    pub visibility: Visibility,
+    /// The operator as written: `+`, `<>`, `Mod`, `IsTrue`, `CType`, ...
+    pub symbol: String,
+    /// `Widening` / `Narrowing`, only on `CType` operators
+    #[serde(default)]
+    pub conversion: Option<ConversionKind>,
+    pub parameters: Vec<Parameter>,
+    pub return_type: Option<VBType>,
+    pub body: Block,
+    #[serde(default)]
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConversionKind {
+    Widening,
+    Narrowing,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -156,6 +227,68 @@ pub struct Parameter {
     /// ParamArray — last parameter receives remaining args as an array
     #[serde(default)]
     pub is_param_array: bool,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+}
+
+/// One attribute: `Obsolete("Use Foo")` in `<Obsolete("Use Foo"), Browsable(False)>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribute {
+    /// The name as written: `Obsolete`, `System.ObsoleteAttribute`, ...
+    pub name: String,
+    /// `Assembly` or `Module` in `<Assembly: AssemblyTitle("App")>`
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Positional arguments, then named ones as `ExprKind::NamedArgument`
+    pub arguments: Vec<Expression>,
+}
+
+impl Attribute {
+    /// The name without namespace or `Attribute` suffix: `Obsolete` for
+    /// `System.ObsoleteAttribute`.
+    pub fn short_name(&self) -> &str {
+        let last = self.name.rsplit('.').next().unwrap_or(&self.name);
+        match last.len().checked_sub("Attribute".len()) {
+            Some(n) if n > 0 && last[n..].eq_ignore_ascii_case("Attribute") => &last[..n],
+            _ => last,
+        }
+    }
+
+    /// Whether this is the attribute `name`, however it was qualified.
+    pub fn is(&self, name: &str) -> bool {
+        self.short_name().eq_ignore_ascii_case(name)
+    }
+}
+
+/// A type parameter of a generic declaration: `T` in `Class Box(Of T As {Class, New})`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypeParameter {
+    pub name: Identifier,
+    #[serde(default)]
+    pub constraints: Vec<TypeConstraint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TypeConstraint {
+    /// `Class` — the type argument must be a reference type
+    Class,
+    /// `Structure` — the type argument must be a value type
+    Structure,
+    /// `New` — the type argument must have a public parameterless constructor
+    New,
+    /// The type argument must be, inherit from or implement this type
+    Type(VBType),
+}
+
+impl std::fmt::Display for TypeConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeConstraint::Class => write!(f, "Class"),
+            TypeConstraint::Structure => write!(f, "Structure"),
+            TypeConstraint::New => write!(f, "New"),
+            TypeConstraint::Type(t) => write!(f, "{}", t),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -189,6 +322,10 @@ pub struct EnumDecl {
     pub visibility: Visibility,
     pub name: Identifier,
     pub members: Vec<EnumMember>,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
 }
 
 /// A VB.NET Interface declaration.
@@ -196,8 +333,14 @@ pub struct EnumDecl {
 pub struct InterfaceDecl {
     pub visibility: Visibility,
     pub name: Identifier,
+    #[serde(default)]
+    pub type_parameters: Vec<TypeParameter>,
     pub inherits: Vec<VBType>,
     pub methods: Vec<InterfaceMember>,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
 }
 
 /// A member declared inside an Interface block.
@@ -224,15 +367,24 @@ pub enum InterfaceMember {
     },
 }
 
-/// A VB.NET Structure declaration (value type — treated like a class).
+/// A VB.NET Structure declaration: registered like a class, but its
+/// instances are values, copied on assignment and ByVal passing.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StructureDecl {
     pub visibility: Visibility,
     pub name: Identifier,
+    #[serde(default)]
+    pub type_parameters: Vec<TypeParameter>,
     pub implements: Vec<VBType>,
     pub properties: Vec<PropertyDecl>,
     pub methods: Vec<MethodDecl>,
     pub fields: Vec<VariableDecl>,
+    #[serde(default)]
+    pub operators: Vec<OperatorDecl>,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
 }
 
 /// A VB.NET Delegate declaration.
@@ -240,9 +392,15 @@ pub struct StructureDecl {
 pub struct DelegateDecl {
     pub visibility: Visibility,
     pub name: Identifier,
+    #[serde(default)]
+    pub type_parameters: Vec<TypeParameter>,
     pub parameters: Vec<Parameter>,
     pub return_type: Option<VBType>,
     pub is_sub: bool,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
 }
 
 /// A VB.NET Event declaration (inside a class/module).
@@ -252,6 +410,23 @@ pub struct EventDecl {
     pub name: Identifier,
     pub parameters: Vec<Parameter>,
     pub event_type: Option<VBType>,
+    /// The accessor blocks of a `Custom Event`; None for an ordinary event,
+    /// whose handlers the runtime keeps itself.
+    #[serde(default)]
+    pub accessors: Option<Box<EventAccessors>>,
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+    #[serde(default)]
+    pub span: Span,
+}
+
+/// The `AddHandler(value)`, `RemoveHandler(value)` and `RaiseEvent(...)`
+/// blocks of a `Custom Event`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventAccessors {
+    pub add_handler: (Parameter, Block),
+    pub remove_handler: (Parameter, Block),
+    pub raise_event: (Vec<Parameter>, Block),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1,11 +1,51 @@
-use super::Identifier;
+use super::{Identifier, Span};
 use serde::{Deserialize, Serialize};
 
+/// An expression and the source range it was parsed from.
+///
+/// Equality ignores the span: the same expression written in two places is
+/// equal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expression {
+    pub kind: ExprKind,
+    #[serde(default)]
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+/// A synthesized expression, with an unknown span.
+impl From<ExprKind> for Expression {
+    fn from(kind: ExprKind) -> Self {
+        Expression { kind, span: Span::default() }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum Expression {
+pub enum ExprKind {
     // Literals
     IntegerLiteral(i32),
     DoubleLiteral(f64),
+    /// Literals whose type character or size makes them something other
+    /// than Integer or Double: `3000000000`, `&HFF00L`, `7S`, `2.5F`, `1.1@`
+    LongLiteral(i64),
+    ShortLiteral(i16),
+    UShortLiteral(u16),
+    UIntegerLiteral(u32),
+    ULongLiteral(u64),
+    SingleLiteral(f32),
+    /// Kept as its digits so no precision is lost before the runtime sees it
+    DecimalLiteral(String),
     StringLiteral(String),
     BooleanLiteral(bool),
     DateLiteral(String),
@@ -76,6 +116,15 @@ pub enum Expression {
     // Object initializer: New Type() With { .Prop = expr, ... }
     NewWithInitializer(Identifier, Vec<Expression>, Vec<(String, Expression)>),
 
+    // Anonymous type: New With { .Name = expr, Key .Id = expr, c.City }
+    NewAnonymous(Vec<(String, Expression)>),
+
+    // Named argument in a call: Title:="App"
+    NamedArgument(Identifier, Box<Expression>),
+
+    // Tuple: (Name:="Ann", Age:=30) or (x, y); names explicit or inferred
+    TupleLiteral(Vec<(Option<String>, Expression)>),
+
     // Lambda
     Lambda {
         params: Vec<super::decl::Parameter>,
@@ -112,6 +161,9 @@ pub enum Expression {
 
     // XML Literals
     XmlLiteral(Box<super::xml::XmlNode>),
+
+    // XML axis property: doc.<root>, doc...<price>, el.@id
+    XmlAxis(Box<Expression>, super::xml::XmlAxis, super::xml::XmlName),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -121,35 +173,67 @@ pub enum CastKind {
     TryCast,
 }
 
-impl Expression {
+impl ExprKind {
     pub fn binary(op: BinaryOp, left: Expression, right: Expression) -> Self {
         match op {
-            BinaryOp::Add => Expression::Add(Box::new(left), Box::new(right)),
-            BinaryOp::Subtract => Expression::Subtract(Box::new(left), Box::new(right)),
-            BinaryOp::Multiply => Expression::Multiply(Box::new(left), Box::new(right)),
-            BinaryOp::Divide => Expression::Divide(Box::new(left), Box::new(right)),
-            BinaryOp::IntegerDivide => Expression::IntegerDivide(Box::new(left), Box::new(right)),
-            BinaryOp::Modulo => Expression::Modulo(Box::new(left), Box::new(right)),
-            BinaryOp::Exponent => Expression::Exponent(Box::new(left), Box::new(right)),
-            BinaryOp::Concatenate => Expression::Concatenate(Box::new(left), Box::new(right)),
-            BinaryOp::Equal => Expression::Equal(Box::new(left), Box::new(right)),
-            BinaryOp::NotEqual => Expression::NotEqual(Box::new(left), Box::new(right)),
-            BinaryOp::LessThan => Expression::LessThan(Box::new(left), Box::new(right)),
-            BinaryOp::LessThanOrEqual => Expression::LessThanOrEqual(Box::new(left), Box::new(right)),
-            BinaryOp::GreaterThan => Expression::GreaterThan(Box::new(left), Box::new(right)),
-            BinaryOp::GreaterThanOrEqual => Expression::GreaterThanOrEqual(Box::new(left), Box::new(right)),
-            BinaryOp::And => Expression::And(Box::new(left), Box::new(right)),
-            BinaryOp::AndAlso => Expression::AndAlso(Box::new(left), Box::new(right)),
-            BinaryOp::Or => Expression::Or(Box::new(left), Box::new(right)),
-            BinaryOp::OrElse => Expression::OrElse(Box::new(left), Box::new(right)),
-            BinaryOp::Xor => Expression::Xor(Box::new(left), Box::new(right)),
-            BinaryOp::BitShiftLeft => Expression::BitShiftLeft(Box::new(left), Box::new(right)),
-            BinaryOp::BitShiftRight => Expression::BitShiftRight(Box::new(left), Box::new(right)),
-            BinaryOp::Is => Expression::Is(Box::new(left), Box::new(right)),
-            BinaryOp::IsNot => Expression::IsNot(Box::new(left), Box::new(right)),
-            BinaryOp::Like => Expression::Like(Box::new(left), Box::new(right)),
+            BinaryOp::Add => ExprKind::Add(Box::new(left), Box::new(right)),
+            BinaryOp::Subtract => ExprKind::Subtract(Box::new(left), Box::new(right)),
+            BinaryOp::Multiply => ExprKind::Multiply(Box::new(left), Box::new(right)),
+            BinaryOp::Divide => ExprKind::Divide(Box::new(left), Box::new(right)),
+            BinaryOp::IntegerDivide => ExprKind::IntegerDivide(Box::new(left), Box::new(right)),
+            BinaryOp::Modulo => ExprKind::Modulo(Box::new(left), Box::new(right)),
+            BinaryOp::Exponent => ExprKind::Exponent(Box::new(left), Box::new(right)),
+            BinaryOp::Concatenate => ExprKind::Concatenate(Box::new(left), Box::new(right)),
+            BinaryOp::Equal => ExprKind::Equal(Box::new(left), Box::new(right)),
+            BinaryOp::NotEqual => ExprKind::NotEqual(Box::new(left), Box::new(right)),
+            BinaryOp::LessThan => ExprKind::LessThan(Box::new(left), Box::new(right)),
+            BinaryOp::LessThanOrEqual => ExprKind::LessThanOrEqual(Box::new(left), Box::new(right)),
+            BinaryOp::GreaterThan => ExprKind::GreaterThan(Box::new(left), Box::new(right)),
+            BinaryOp::GreaterThanOrEqual => ExprKind::GreaterThanOrEqual(Box::new(left), Box::new(right)),
+            BinaryOp::And => ExprKind::And(Box::new(left), Box::new(right)),
+            BinaryOp::AndAlso => ExprKind::AndAlso(Box::new(left), Box::new(right)),
+            BinaryOp::Or => ExprKind::Or(Box::new(left), Box::new(right)),
+            BinaryOp::OrElse => ExprKind::OrElse(Box::new(left), Box::new(right)),
+            BinaryOp::Xor => ExprKind::Xor(Box::new(left), Box::new(right)),
+            BinaryOp::BitShiftLeft => ExprKind::BitShiftLeft(Box::new(left), Box::new(right)),
+            BinaryOp::BitShiftRight => ExprKind::BitShiftRight(Box::new(left), Box::new(right)),
+            BinaryOp::Is => ExprKind::Is(Box::new(left), Box::new(right)),
+            BinaryOp::IsNot => ExprKind::IsNot(Box::new(left), Box::new(right)),
+            BinaryOp::Like => ExprKind::Like(Box::new(left), Box::new(right)),
         }
     }
+
+    /// The operator and operands of a binary operation; the inverse of
+    /// [`ExprKind::binary`].
+    pub fn as_binary(&self) -> Option<(BinaryOp, &Expression, &Expression)> {
+        Some(match self {
+            ExprKind::Add(left, right) => (BinaryOp::Add, left.as_ref(), right.as_ref()),
+            ExprKind::Subtract(left, right) => (BinaryOp::Subtract, left.as_ref(), right.as_ref()),
+            ExprKind::Multiply(left, right) => (BinaryOp::Multiply, left.as_ref(), right.as_ref()),
+            ExprKind::Divide(left, right) => (BinaryOp::Divide, left.as_ref(), right.as_ref()),
+            ExprKind::IntegerDivide(left, right) => (BinaryOp::IntegerDivide, left.as_ref(), right.as_ref()),
+            ExprKind::Modulo(left, right) => (BinaryOp::Modulo, left.as_ref(), right.as_ref()),
+            ExprKind::Exponent(left, right) => (BinaryOp::Exponent, left.as_ref(), right.as_ref()),
+            ExprKind::Concatenate(left, right) => (BinaryOp::Concatenate, left.as_ref(), right.as_ref()),
+            ExprKind::Equal(left, right) => (BinaryOp::Equal, left.as_ref(), right.as_ref()),
+            ExprKind::NotEqual(left, right) => (BinaryOp::NotEqual, left.as_ref(), right.as_ref()),
+            ExprKind::LessThan(left, right) => (BinaryOp::LessThan, left.as_ref(), right.as_ref()),
+            ExprKind::LessThanOrEqual(left, right) => (BinaryOp::LessThanOrEqual, left.as_ref(), right.as_ref()),
+            ExprKind::GreaterThan(left, right) => (BinaryOp::GreaterThan, left.as_ref(), right.as_ref()),
+            ExprKind::GreaterThanOrEqual(left, right) => (BinaryOp::GreaterThanOrEqual, left.as_ref(), right.as_ref()),
+            ExprKind::And(left, right) => (BinaryOp::And, left.as_ref(), right.as_ref()),
+            ExprKind::AndAlso(left, right) => (BinaryOp::AndAlso, left.as_ref(), right.as_ref()),
+            ExprKind::Or(left, right) => (BinaryOp::Or, left.as_ref(), right.as_ref()),
+            ExprKind::OrElse(left, right) => (BinaryOp::OrElse, left.as_ref(), right.as_ref()),
+            ExprKind::Xor(left, right) => (BinaryOp::Xor, left.as_ref(), right.as_ref()),
+            ExprKind::BitShiftLeft(left, right) => (BinaryOp::BitShiftLeft, left.as_ref(), right.as_ref()),
+            ExprKind::BitShiftRight(left, right) => (BinaryOp::BitShiftRight, left.as_ref(), right.as_ref()),
+            ExprKind::Is(left, right) => (BinaryOp::Is, left.as_ref(), right.as_ref()),
+            ExprKind::IsNot(left, right) => (BinaryOp::IsNot, left.as_ref(), right.as_ref()),
+            ExprKind::Like(left, right) => (BinaryOp::Like, left.as_ref(), right.as_ref()),
+            _ => return None,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -183,5 +267,5 @@ pub enum BinaryOp {
 pub enum LambdaBody {
     Expression(Box<Expression>),
     Statement(Box<super::stmt::Statement>),
-    Block(Vec<super::stmt::Statement>),
+    Block(super::stmt::Block),
 }
@@ -17,5 +17,43 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Program {
     pub declarations: Vec<Declaration>,
-    pub statements: Vec<Statement>,
+    pub statements: Block,
+    /// Name of the file this program was parsed from, if known.
+    #[serde(default)]
+    pub source_file: Option<String>,
+    /// `Option` statements at the top of the file.
+    #[serde(default)]
+    pub options: OptionSettings,
+    /// `<Assembly: ...>` and `<Module: ...>` attributes.
+    #[serde(default)]
+    pub attributes: Vec<Attribute>,
+}
+
+/// `Option Explicit`, `Option Strict`, `Option Compare` and `Option Infer`,
+/// from the top of a file or a project's defaults. None where not set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OptionSettings {
+    pub explicit: Option<bool>,
+    pub strict: Option<bool>,
+    pub compare: Option<CompareMode>,
+    pub infer: Option<bool>,
+}
+
+impl OptionSettings {
+    /// These settings, with `defaults` filling in the ones left unset.
+    pub fn or(self, defaults: OptionSettings) -> OptionSettings {
+        OptionSettings {
+            explicit: self.explicit.or(defaults.explicit),
+            strict: self.strict.or(defaults.strict),
+            compare: self.compare.or(defaults.compare),
+            infer: self.infer.or(defaults.infer),
+        }
+    }
+}
+
+/// `Option Compare Binary` (ordinal) or `Option Compare Text` (case-insensitive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareMode {
+    Binary,
+    Text,
 }
@@ -1,6 +1,7 @@
 use crate::ast::expr::Expression;
 use serde::{Deserialize, Serialize};
 
+/// `From x In xs ...` or `Aggregate x In xs ... Into ...`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QueryExpression {
     pub from_clause: FromClause,
@@ -19,40 +20,95 @@ pub struct RangeVariable {
     pub type_name: Option<String>,
 }
 
+/// The clauses after the first `From`, applied in order.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct QueryBody {
     pub clauses: Vec<QueryClause>,
-    pub select_or_group: SelectOrGroupClause,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum QueryClause {
+    /// A further `From`: every combination with the ranges already in scope
+    From(Vec<RangeVariable>),
     Where(Expression),
     OrderBy(Vec<Ordering>),
     Let { name: String, value: Expression },
-    // Join not yet implemented
+    Select(Vec<QueryField>),
+    Distinct,
+    Skip(Expression),
+    Take(Expression),
+    SkipWhile(Expression),
+    TakeWhile(Expression),
+    Join(Box<JoinClause>),
+    GroupJoin(Box<GroupJoinClause>),
+    GroupBy(Box<GroupByClause>),
+    /// `Aggregate y In ys ... Into ...` inside a query: adds the aggregates to each row
+    Aggregate(Box<AggregateClause>),
+    /// The `Into` ending an `Aggregate` query: the result is the aggregate, not a sequence
+    Into(Vec<Aggregation>),
 }
 
+/// `name = expression` in `Select` and `Group ... By`. Without an explicit
+/// name the parser infers it from the expression (`c.City` is `City`);
+/// `None` when there is nothing to infer, which only a lone field allows.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Ordering {
+pub struct QueryField {
+    pub name: Option<String>,
     pub expression: Expression,
-    pub direction: OrderDirection,
 }
 
+/// `Join y In ys On x.Key Equals y.Key [And ...]`
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum OrderDirection {
-    Ascending,
-    Descending,
+pub struct JoinClause {
+    pub range: RangeVariable,
+    pub conditions: Vec<JoinCondition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinCondition {
+    pub left: Expression,
+    pub right: Expression,
+}
+
+/// `Group Join y In ys On x.Key Equals y.Key Into Items = Group, Count()`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupJoinClause {
+    pub range: RangeVariable,
+    pub conditions: Vec<JoinCondition>,
+    pub into: Vec<Aggregation>,
+}
+
+/// `Group [items] By keys Into aggregates`; no items groups the whole row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupByClause {
+    pub items: Vec<QueryField>,
+    pub keys: Vec<QueryField>,
+    pub into: Vec<Aggregation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum SelectOrGroupClause {
-    Select(Vec<Expression>), // Usually one, but can be multiple for anonymous types? In VB it's usually Select explicit_expr
-    Group(Box<GroupClause>),
+pub struct AggregateClause {
+    pub ranges: Vec<RangeVariable>,
+    pub clauses: Vec<QueryClause>,
+    pub into: Vec<Aggregation>,
+}
+
+/// `[name =] Function([argument])` or `[name =] Group` in an `Into` list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Aggregation {
+    pub name: Option<String>,
+    pub function: String,
+    pub argument: Option<Expression>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ordering {
+    pub expression: Expression,
+    pub direction: OrderDirection,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct GroupClause {
-    pub item: Expression,
-    pub key: Expression,
+pub enum OrderDirection {
+    Ascending,
+    Descending,
 }
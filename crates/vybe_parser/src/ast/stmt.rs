@@ -1,4 +1,4 @@
-use super::{Expression, Identifier, VBType};
+use super::{ExprKind, Expression, Identifier, Span, VBType};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -35,6 +35,21 @@ pub enum Statement {
         value: Expression,
     },
 
+    // Indexed member assignment (e.g., obj.Item(3) = value, Me.cells(2) = value)
+    IndexedAssignment {
+        object: Expression,
+        member: Identifier,
+        indices: Vec<Expression>,
+        value: Expression,
+    },
+
+    // XML attribute assignment (e.g., el.@id = value)
+    XmlAttributeAssignment {
+        element: Expression,
+        name: super::xml::XmlName,
+        value: Expression,
+    },
+
     // Array redimensioning
     ReDim {
         preserve: bool,
@@ -60,54 +75,59 @@ pub enum Statement {
     // Control flow
     If {
         condition: Expression,
-        then_branch: Vec<Statement>,
-        elseif_branches: Vec<(Expression, Vec<Statement>)>,
-        else_branch: Option<Vec<Statement>>,
+        then_branch: Block,
+        elseif_branches: Vec<(Expression, Block)>,
+        else_branch: Option<Block>,
     },
 
     For {
         variable: Identifier,
+        /// `For i As Integer = ...` declares the loop variable
+        #[serde(default)]
+        var_type: Option<VBType>,
         start: Expression,
         end: Expression,
         step: Option<Expression>,
-        body: Vec<Statement>,
+        body: Block,
     },
 
     While {
         condition: Expression,
-        body: Vec<Statement>,
+        body: Block,
     },
 
     DoLoop {
         pre_condition: Option<(LoopConditionType, Expression)>,
-        body: Vec<Statement>,
+        body: Block,
         post_condition: Option<(LoopConditionType, Expression)>,
     },
 
     Select {
         test_expr: Expression,
         cases: Vec<CaseBlock>,
-        else_block: Option<Vec<Statement>>,
+        else_block: Option<Block>,
     },
 
     // For Each
     ForEach {
         variable: Identifier,
+        #[serde(default)]
+        var_type: Option<VBType>,
         collection: Expression,
-        body: Vec<Statement>,
+        body: Block,
     },
 
     // With block
     With {
         object: Expression,
-        body: Vec<Statement>,
+        body: Block,
     },
 
     // Using block (resource disposal)
     Using {
         variable: Identifier,
         resource: Expression,
-        body: Vec<Statement>,
+        body: Block,
     },
 
     // Exit statements
@@ -123,6 +143,9 @@ pub enum Statement {
     // Return
     Return(Option<Expression>),
 
+    /// `Yield expr` in an Iterator Function
+    Yield(Expression),
+
     // Procedure call
     Call {
         name: Identifier,
@@ -134,9 +157,9 @@ pub enum Statement {
 
     // Exception handling
     Try {
-        body: Vec<Statement>,
+        body: Block,
         catches: Vec<CatchBlock>,
-        finally: Option<Vec<Statement>>,
+        finally: Option<Block>,
     },
 
     // Throw exception
@@ -167,17 +190,53 @@ pub enum Statement {
     // GoTo / Labels
     GoTo(String),
     Label(String),
+    /// `GoSub label`: jump to the label; a bare `Return` comes back to the
+    /// statement after the GoSub.
+    GoSub(String),
+    /// `On index GoTo a, b, c` (or `GoSub`): jump to the index-th label,
+    /// counting from 1. An index of 0 or past the list falls through.
+    OnGoTo {
+        selector: Expression,
+        labels: Vec<String>,
+        gosub: bool,
+    },
 
     // VB6/VB.NET error handling
     OnErrorResumeNext,
     OnErrorGoTo(String),    // Label name, or "0" to disable
     Resume(ResumeTarget),
 
+    // VB6 string and array statements
+    /// `Mid(s, start[, length]) = value`: overwrite characters of `s` in
+    /// place, never changing its length.
+    MidAssignment {
+        target: Expression,
+        start: Expression,
+        length: Option<Expression>,
+        value: Expression,
+    },
+    /// `LSet s = value`: `value` left-aligned in the length of `s`. Between
+    /// two user-defined types, copies the bytes of one record into the other.
+    LSet {
+        target: Expression,
+        value: Expression,
+    },
+    /// `RSet s = value`: `value` right-aligned in the length of `s`.
+    RSet {
+        target: Expression,
+        value: Expression,
+    },
+    /// `Erase a, b`: release the arrays, as assigning `Nothing` does.
+    Erase(Vec<Expression>),
+
     // VB6 File I/O
     Open {
         file_path: Expression,
         mode: FileOpenMode,
         file_number: Expression,
+        /// `Len = 64`: the record length of a `Random` file.
+        #[serde(default)]
+        record_length: Option<Expression>,
     },
     CloseFile {
         file_number: Option<Expression>,
@@ -199,14 +258,136 @@ pub enum Statement {
         file_number: Expression,
         variable: Identifier,
     },
+    /// `Get #1, [position], target`: read a record (`Random`) or the bytes
+    /// at a 1-based offset (`Binary`) into a variable.
+    GetFile {
+        file_number: Expression,
+        position: Option<Expression>,
+        target: Expression,
+    },
+    /// `Put #1, [position], value`: write a record or bytes.
+    PutFile {
+        file_number: Expression,
+        position: Option<Expression>,
+        value: Expression,
+    },
 
     // SyncLock
     SyncLock {
         lock_object: Expression,
-        body: Vec<Statement>,
+        body: Block,
     },
 }
 
+impl Statement {
+    /// The statement assigning `value` to `target`: a variable, a member,
+    /// an array element, an indexed property or an XML attribute (`el.@id`).
+    /// `None` for anything else.
+    pub fn assignment(target: Expression, value: Expression) -> Option<Statement> {
+        Some(match target.kind {
+            ExprKind::Variable(target) => Statement::Assignment { target, value },
+            ExprKind::MemberAccess(object, member) => Statement::MemberAssignment { object: *object, member, value },
+            ExprKind::ArrayAccess(array, indices) | ExprKind::Call(array, indices) => {
+                Statement::ArrayAssignment { array, indices, value }
+            }
+            ExprKind::MethodCall(object, member, indices) => {
+                Statement::IndexedAssignment { object: *object, member, indices, value }
+            }
+            ExprKind::XmlAxis(element, super::xml::XmlAxis::Attribute, name) => {
+                Statement::XmlAttributeAssignment { element: *element, name, value }
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// A sequence of statements together with the source span of each one.
+///
+/// Derefs to `Vec<Statement>`, so it can be iterated and indexed like a plain
+/// statement list. Statements are only added with a span, so the two stay
+/// parallel; blocks built by hand (e.g. synthesized by the runtime) get
+/// unknown spans. Expressions carry their own spans.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    statements: Vec<Statement>,
+    #[serde(default)]
+    spans: Vec<Span>,
+}
+
+impl Block {
+    pub fn new() -> Self {
+        Block::default()
+    }
+
+    /// Append a statement parsed from `span`.
+    pub fn push_spanned(&mut self, stmt: Statement, span: Span) {
+        // A deserialized block may have been saved without spans
+        self.spans.resize(self.statements.len(), Span::default());
+        self.statements.push(stmt);
+        self.spans.push(span);
+    }
+
+    /// Append all statements of `other`, keeping their spans.
+    pub fn extend_block(&mut self, other: Block) {
+        self.extend(other.into_spanned());
+    }
+
+    /// Span of the statement at `index`, or an unknown span if none was recorded.
+    pub fn span_at(&self, index: usize) -> Span {
+        self.spans.get(index).copied().unwrap_or_default()
+    }
+
+    /// Iterate over statements paired with their spans.
+    pub fn iter_spanned(&self) -> impl Iterator<Item = (&Statement, Span)> {
+        self.statements.iter().enumerate().map(|(i, s)| (s, self.span_at(i)))
+    }
+
+    /// The statements paired with their spans.
+    pub fn into_spanned(self) -> impl Iterator<Item = (Statement, Span)> {
+        let mut spans = self.spans;
+        spans.resize(self.statements.len(), Span::default());
+        self.statements.into_iter().zip(spans)
+    }
+}
+
+impl std::ops::Deref for Block {
+    type Target = Vec<Statement>;
+    fn deref(&self) -> &Vec<Statement> {
+        &self.statements
+    }
+}
+
+impl From<Vec<Statement>> for Block {
+    fn from(statements: Vec<Statement>) -> Self {
+        let spans = vec![Span::default(); statements.len()];
+        Block { statements, spans }
+    }
+}
+
+impl<'a> IntoIterator for &'a Block {
+    type Item = &'a Statement;
+    type IntoIter = std::slice::Iter<'a, Statement>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.statements.iter()
+    }
+}
+
+impl IntoIterator for Block {
+    type Item = Statement;
+    type IntoIter = std::vec::IntoIter<Statement>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.statements.into_iter()
+    }
+}
+
+impl Extend<(Statement, Span)> for Block {
+    fn extend<T: IntoIterator<Item = (Statement, Span)>>(&mut self, iter: T) {
+        for (stmt, span) in iter {
+            self.push_spanned(stmt, span);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum LoopConditionType {
     While,
@@ -216,7 +397,7 @@ pub enum LoopConditionType {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CaseBlock {
     pub conditions: Vec<CaseCondition>,
-    pub body: Vec<Statement>,
+    pub body: Block,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -230,7 +411,7 @@ pub enum CaseCondition {
 pub struct CatchBlock {
     pub variable: Option<(Identifier, Option<super::VBType>)>,
     pub when_clause: Option<Expression>,
-    pub body: Vec<Statement>,
+    pub body: Block,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
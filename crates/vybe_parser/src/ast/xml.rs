@@ -28,6 +28,21 @@ pub struct XmlName {
 pub struct XmlAttribute {
     pub name: XmlName,
     pub value: Vec<XmlNode>, // Attributes can contain embedded expressions too: name="<%= expr %>"
+    /// An embedded expression in place of the name: `<item <%= key %>="1"/>`.
+    /// With no value it gives the whole attribute: `<item <%= attr %>/>`.
+    #[serde(default)]
+    pub name_expression: Option<Expression>,
+}
+
+/// The XML axis properties: `x.<name>`, `x...<name>` and `x.@name`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum XmlAxis {
+    /// `x.<name>`: the child elements called name
+    Child,
+    /// `x...<name>`: the elements called name at any depth below x
+    Descendant,
+    /// `x.@name` or `x.@<name>`: the value of attribute name
+    Attribute,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -0,0 +1,1128 @@
+//! Syntax diagnostics: report every error in a file instead of stopping at
+//! the first one.
+//!
+//! pest gives up at the first failure, so recovery works on the source text:
+//!
+//! 1. A block pass matches `If`/`End If`, `For`/`Next`, `Sub`/`End Sub`, ...
+//!    line by line. Unclosed blocks get a synthesized terminator inserted where
+//!    the parent block ends; stray terminators and clauses are blanked out.
+//! 2. The repaired text is re-parsed. Each failure is recorded and the
+//!    offending line is replaced by a neutral equivalent (or blanked), then the
+//!    parse is retried until it succeeds or no further progress can be made.
+//!    Each procedure is re-parsed on its own, inside the headers of the
+//!    containers around it, so an error only costs a parse of its procedure;
+//!    everything outside procedures is re-parsed as one part, and the whole
+//!    file once more at the end. A procedure with more than
+//!    [`MAX_REPARSES`] errors is skipped from there on, which keeps the cost
+//!    of checking a file linear in its size.
+//!
+//! Every repaired line remembers which original line it came from, so the
+//! reported spans always point into the user's file.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use pest::error::{ErrorVariant, LineColLocation};
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{Program, Span};
+use crate::parser::{parse_program, ParseError, Rule};
+use crate::preprocessor::{preprocess, ConditionalConstants};
+
+/// Diagnostic codes reported by the parser.
+pub mod codes {
+    /// The parser could not make sense of a statement.
+    pub const SYNTAX_ERROR: &str = "VB1000";
+    /// A block (`If`, `For`, `Sub`, ...) is never closed.
+    pub const UNCLOSED_BLOCK: &str = "VB1001";
+    /// A block terminator (`End If`, `Next`, ...) has no matching opener.
+    pub const UNMATCHED_END: &str = "VB1002";
+    /// A clause (`Else`, `Case`, `Catch`, ...) appears outside its block.
+    pub const MISPLACED_CLAUSE: &str = "VB1003";
+    /// An `#If`, `#Const` or `#Region` directive is malformed or unbalanced.
+    pub const DIRECTIVE_ERROR: &str = "VB1004";
+    /// Checking stopped early, or skipped the rest of a procedure; errors past
+    /// this point are not reported.
+    pub const TOO_MANY_ERRORS: &str = "VB1005";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A single problem found in a source file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: String,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    fn error(code: &str, message: String, span: Span) -> Self {
+        Diagnostic { severity: Severity::Error, code: code.to_string(), message, span }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    /// `12:5: error[VB1001]: expected `End If` to close `If` on line 10`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.span.is_unknown() {
+            write!(f, "{}: ", self.span)?;
+        }
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+/// Parse `source`, collecting every syntax error instead of stopping at the
+/// first. Returns the program when the file is clean.
+pub fn parse_program_diagnostics(source: &str) -> Result<Program, Vec<Diagnostic>> {
+    parse_program_diagnostics_with(source, &ConditionalConstants::new())
+}
+
+/// [`parse_program_diagnostics`] with conditional compilation constants.
+/// Lines in inactive `#If` branches are not checked.
+pub fn parse_program_diagnostics_with(source: &str, constants: &ConditionalConstants) -> Result<Program, Vec<Diagnostic>> {
+    let source = source.trim_start_matches('\u{feff}');
+    let source = match preprocess(source, constants) {
+        Ok(text) => text,
+        Err(ParseError::Directive { message, span }) => {
+            return Err(vec![Diagnostic::error(codes::DIRECTIVE_ERROR, message, span)]);
+        }
+        Err(e) => return Err(vec![diagnostic_from_error(&e, &[])]),
+    };
+    let first_error = match parse_program(&source) {
+        Ok(program) => return Ok(program),
+        Err(e) => e,
+    };
+
+    let mut lines: Vec<SourceLine> = source
+        .split('\n')
+        .enumerate()
+        .map(|(i, l)| SourceLine::original(l.trim_end_matches('\r'), i))
+        .collect();
+
+    let mut diagnostics = Vec::new();
+    check_blocks(&mut lines, &mut diagnostics);
+    let units = procedure_units(&lines);
+    let mut in_unit = vec![false; lines.len()];
+    for unit in &units {
+        in_unit[unit.body.0..=unit.body.1].fill(true);
+    }
+    let outside: Vec<usize> = (0..lines.len()).filter(|&i| !in_unit[i]).collect();
+    let finished = match reparse_part(&mut lines, &outside, &[], &mut diagnostics) {
+        Ok(()) => true,
+        Err(span) => {
+            diagnostics.push(stopped(span));
+            false
+        }
+    };
+    for unit in units.iter().take_while(|_| finished) {
+        let mut indices = unit.headers.clone();
+        indices.extend(unit.body.0..=unit.body.1);
+        if let Err(span) = reparse_part(&mut lines, &indices, &unit.closers, &mut diagnostics) {
+            // Leave the procedure out rather than paying a parse per error
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                code: codes::TOO_MANY_ERRORS.to_string(),
+                message: format!("too many syntax errors; skipped the rest of this procedure after {}", MAX_REPARSES),
+                span,
+            });
+            for line in &mut lines[unit.body.0..=unit.body.1] {
+                line.blank();
+            }
+        }
+    }
+    if finished && let Err(span) = reparse_until_clean(&mut lines, &mut diagnostics) {
+        diagnostics.push(stopped(span));
+    }
+
+    if diagnostics.is_empty() {
+        diagnostics.push(diagnostic_from_error(&first_error, &[]));
+    }
+    diagnostics.sort_by_key(|d| (d.span.start_line, d.span.start_col));
+    Err(diagnostics)
+}
+
+/// Every syntax error in `source`; empty when it parses cleanly.
+pub fn check_syntax(source: &str) -> Vec<Diagnostic> {
+    parse_program_diagnostics(source).err().unwrap_or_default()
+}
+
+/// Every syntax error in `source` under `constants`.
+pub fn check_syntax_with(source: &str, constants: &ConditionalConstants) -> Vec<Diagnostic> {
+    parse_program_diagnostics_with(source, constants).err().unwrap_or_default()
+}
+
+/// Give up after this many re-parses of one part; each one fixes at most one
+/// line and costs a parse of the whole part.
+const MAX_REPARSES: usize = 30;
+
+/// The warning left where checking stopped.
+fn stopped(span: Span) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Warning,
+        code: codes::TOO_MANY_ERRORS.to_string(),
+        message: format!("too many syntax errors; stopped after {}", MAX_REPARSES),
+        span,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineState {
+    Original,
+    /// Replaced by (or synthesized as) a neutral line of the same shape.
+    Canonical,
+    Blank,
+}
+
+#[derive(Debug, Clone)]
+struct SourceLine {
+    text: String,
+    /// 0-based line in the user's file; `None` for synthesized lines.
+    origin: Option<usize>,
+    state: LineState,
+}
+
+impl SourceLine {
+    fn original(text: &str, origin: usize) -> Self {
+        SourceLine { text: text.to_string(), origin: Some(origin), state: LineState::Original }
+    }
+
+    fn synthesized(text: String) -> Self {
+        SourceLine { text, origin: None, state: LineState::Canonical }
+    }
+
+    fn blank(&mut self) {
+        self.text.clear();
+        self.state = LineState::Blank;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Block structure
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    If,
+    For,
+    While,
+    Do,
+    Select,
+    Try,
+    With,
+    Using,
+    SyncLock,
+    Sub,
+    Function,
+    Property,
+    Get,
+    Set,
+    Class,
+    Module,
+    Structure,
+    Interface,
+    Enum,
+    Namespace,
+    Event,
+    AddHandler,
+    RemoveHandler,
+    RaiseEvent,
+    Operator,
+}
+
+impl BlockKind {
+    fn from_end_keyword(word: &str) -> Option<Self> {
+        Some(match word {
+            "if" => BlockKind::If,
+            "while" => BlockKind::While,
+            "select" => BlockKind::Select,
+            "try" => BlockKind::Try,
+            "with" => BlockKind::With,
+            "using" => BlockKind::Using,
+            "synclock" => BlockKind::SyncLock,
+            "sub" => BlockKind::Sub,
+            "function" => BlockKind::Function,
+            "property" => BlockKind::Property,
+            "get" => BlockKind::Get,
+            "set" => BlockKind::Set,
+            "class" => BlockKind::Class,
+            "module" => BlockKind::Module,
+            "structure" => BlockKind::Structure,
+            "interface" => BlockKind::Interface,
+            "enum" => BlockKind::Enum,
+            "namespace" => BlockKind::Namespace,
+            "event" => BlockKind::Event,
+            "addhandler" => BlockKind::AddHandler,
+            "removehandler" => BlockKind::RemoveHandler,
+            "raiseevent" => BlockKind::RaiseEvent,
+            "operator" => BlockKind::Operator,
+            _ => return None,
+        })
+    }
+
+    /// Blocks that can hold declarations.
+    fn is_container(self) -> bool {
+        matches!(
+            self,
+            BlockKind::Class
+                | BlockKind::Module
+                | BlockKind::Structure
+                | BlockKind::Interface
+                | BlockKind::Namespace
+        )
+    }
+
+    /// Blocks that only appear at declaration level, never inside a procedure.
+    fn is_declaration(self) -> bool {
+        self.is_container()
+            || matches!(
+                self,
+                BlockKind::Sub
+                    | BlockKind::Function
+                    | BlockKind::Property
+                    | BlockKind::Enum
+                    | BlockKind::Event
+                    | BlockKind::Operator
+            )
+    }
+
+    /// The keyword as written at the start of the block.
+    fn name(self) -> &'static str {
+        match self {
+            BlockKind::If => "If",
+            BlockKind::For => "For",
+            BlockKind::While => "While",
+            BlockKind::Do => "Do",
+            BlockKind::Select => "Select Case",
+            BlockKind::Try => "Try",
+            BlockKind::With => "With",
+            BlockKind::Using => "Using",
+            BlockKind::SyncLock => "SyncLock",
+            BlockKind::Sub => "Sub",
+            BlockKind::Function => "Function",
+            BlockKind::Property => "Property",
+            BlockKind::Get => "Get",
+            BlockKind::Set => "Set",
+            BlockKind::Class => "Class",
+            BlockKind::Module => "Module",
+            BlockKind::Structure => "Structure",
+            BlockKind::Interface => "Interface",
+            BlockKind::Enum => "Enum",
+            BlockKind::Namespace => "Namespace",
+            BlockKind::Event => "Custom Event",
+            BlockKind::AddHandler => "AddHandler",
+            BlockKind::RemoveHandler => "RemoveHandler",
+            BlockKind::RaiseEvent => "RaiseEvent",
+            BlockKind::Operator => "Operator",
+        }
+    }
+
+    /// The statement that closes the block.
+    fn terminator(self) -> &'static str {
+        match self {
+            BlockKind::If => "End If",
+            BlockKind::For => "Next",
+            BlockKind::While => "End While",
+            BlockKind::Do => "Loop",
+            BlockKind::Select => "End Select",
+            BlockKind::Try => "End Try",
+            BlockKind::With => "End With",
+            BlockKind::Using => "End Using",
+            BlockKind::SyncLock => "End SyncLock",
+            BlockKind::Sub => "End Sub",
+            BlockKind::Function => "End Function",
+            BlockKind::Property => "End Property",
+            BlockKind::Get => "End Get",
+            BlockKind::Set => "End Set",
+            BlockKind::Class => "End Class",
+            BlockKind::Module => "End Module",
+            BlockKind::Structure => "End Structure",
+            BlockKind::Interface => "End Interface",
+            BlockKind::Enum => "End Enum",
+            BlockKind::Namespace => "End Namespace",
+            BlockKind::Event => "End Event",
+            BlockKind::AddHandler => "End AddHandler",
+            BlockKind::RemoveHandler => "End RemoveHandler",
+            BlockKind::RaiseEvent => "End RaiseEvent",
+            BlockKind::Operator => "End Operator",
+        }
+    }
+
+    /// A header for this block that the grammar accepts, used to stand in
+    /// for a header line that failed to parse.
+    fn neutral_header(self, lambda: Option<usize>) -> String {
+        if let Some(depth) = lambda {
+            // Keep the parens the terminator line (`End Sub)`) will close.
+            let keyword = if self == BlockKind::Sub { "Sub()" } else { "Function()" };
+            return match depth {
+                0 => format!("Dim recovered_ = {}", keyword),
+                n => format!("{}{}", "recovered_(".repeat(n), keyword),
+            };
+        }
+        match self {
+            BlockKind::If => "If True Then",
+            BlockKind::For => "For recovered_ = 0 To 0",
+            BlockKind::While => "While False",
+            BlockKind::Do => "Do",
+            BlockKind::Select => "Select Case 0",
+            BlockKind::Try => "Try",
+            BlockKind::With => "With Nothing",
+            BlockKind::Using => "Using recovered_ As Object = Nothing",
+            BlockKind::SyncLock => "SyncLock Nothing",
+            BlockKind::Sub => "Sub Recovered_()",
+            BlockKind::Function => "Function Recovered_()",
+            BlockKind::Property => "Property Recovered_() As Object",
+            BlockKind::Get => "Get",
+            BlockKind::Set => "Set(value As Object)",
+            BlockKind::Class => "Class Recovered_",
+            BlockKind::Module => "Module Recovered_",
+            BlockKind::Structure => "Structure Recovered_",
+            BlockKind::Interface => "Interface Recovered_",
+            BlockKind::Enum => "Enum Recovered_",
+            BlockKind::Namespace => "Namespace Recovered_",
+            BlockKind::Event => "Custom Event Recovered_ As EventHandler",
+            BlockKind::AddHandler => "AddHandler(value As EventHandler)",
+            BlockKind::RemoveHandler => "RemoveHandler(value As EventHandler)",
+            BlockKind::RaiseEvent => "RaiseEvent(sender As Object, e As EventArgs)",
+            BlockKind::Operator => "Operator +(a As Object, b As Object) As Object",
+        }
+        .to_string()
+    }
+}
+
+/// What a statement does to the block structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    /// `lambda` holds the number of parens left open before a multi-line
+    /// lambda header, `None` for ordinary block headers.
+    Open { kind: BlockKind, lambda: Option<usize> },
+    /// `Else`, `ElseIf`, `Case`, `Catch`, `Finally`.
+    Clause { kind: BlockKind, keyword: &'static str },
+    Close(BlockKind),
+}
+
+/// Context from the enclosing block that changes how a line is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    None,
+    /// Members are signatures without bodies.
+    Interface,
+    /// Only `End Enum` matters.
+    Enum,
+    /// `Get` / `Set` start accessor blocks.
+    Property,
+    /// `AddHandler` / `RemoveHandler` / `RaiseEvent` start accessor blocks.
+    CustomEvent,
+}
+
+impl Context {
+    fn of(top: Option<BlockKind>) -> Self {
+        match top {
+            Some(BlockKind::Interface) => Context::Interface,
+            Some(BlockKind::Enum) => Context::Enum,
+            Some(BlockKind::Property) => Context::Property,
+            Some(BlockKind::Event) => Context::CustomEvent,
+            _ => Context::None,
+        }
+    }
+}
+
+const MODIFIERS: &[&str] = &[
+    "public", "private", "protected", "friend", "shared", "overrides", "overridable",
+    "notoverridable", "overloads", "shadows", "partial", "readonly", "writeonly", "default",
+    "async", "iterator", "mustinherit", "notinheritable", "widening", "narrowing",
+];
+
+/// Strip a trailing comment, respecting string literals.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '\'' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    let trimmed = line.trim_start();
+    let is_rem = trimmed.get(..3).is_some_and(|w| w.eq_ignore_ascii_case("rem"))
+        && trimmed[3..].chars().next().is_none_or(char::is_whitespace);
+    if is_rem {
+        return "";
+    }
+    line
+}
+
+/// Split on `:` statement separators outside string literals (but not `:=`).
+fn split_statements(code: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+    let bytes = code.as_bytes();
+    for (i, c) in code.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ':' if !in_string && bytes.get(i + 1) != Some(&b'=') => {
+                parts.push(&code[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&code[start..]);
+    parts
+}
+
+/// Lowercased words of a statement, split on anything that is not part of an identifier.
+fn words(stmt: &str) -> Vec<String> {
+    stmt.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// Drop leading modifier keywords (`Private`, `Shared`, ...).
+fn strip_modifiers(mut stmt: &str) -> &str {
+    while let Some((word, rest)) = stmt.split_once(char::is_whitespace) {
+        if !MODIFIERS.iter().any(|m| m.eq_ignore_ascii_case(word)) {
+            break;
+        }
+        stmt = rest.trim_start();
+    }
+    stmt
+}
+
+/// Drop leading `<Attribute(...)>` blocks.
+fn strip_attributes(mut stmt: &str) -> &str {
+    while stmt.starts_with('<') {
+        match stmt.find('>') {
+            Some(end) if !stmt[end + 1..].trim().is_empty() => stmt = stmt[end + 1..].trim_start(),
+            _ => break,
+        }
+    }
+    stmt
+}
+
+/// Does `stmt` end with a `Sub(...)` / `Function(...)` lambda header whose
+/// body continues on the following lines? Also returns how many parens are
+/// open before the lambda.
+fn multiline_lambda(stmt: &str) -> Option<(BlockKind, usize)> {
+    let lower = stmt.to_lowercase();
+    for (keyword, kind) in [("function", BlockKind::Function), ("sub", BlockKind::Sub)] {
+        let mut search = 0;
+        while let Some(found) = lower[search..].find(keyword) {
+            let at = search + found;
+            search = at + keyword.len();
+            let before = lower[..at].chars().next_back();
+            if before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.') {
+                continue;
+            }
+            if lower[..at].trim_end().ends_with("end") {
+                continue;
+            }
+            let rest = lower[at + keyword.len()..].trim_start();
+            if !rest.starts_with('(') {
+                continue;
+            }
+            // Find the matching close paren of the parameter list.
+            let mut depth = 0;
+            let mut close = None;
+            for (i, c) in rest.char_indices() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            close = Some(i);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let Some(close) = close else { continue };
+            let tail = rest[close + 1..].trim();
+            let tail_words: Vec<&str> = tail.split_whitespace().collect();
+            if tail.is_empty() || (tail_words.len() == 2 && tail_words[0] == "as") {
+                let opened = lower[..at].matches('(').count();
+                let closed = lower[..at].matches(')').count();
+                return Some((kind, opened.saturating_sub(closed)));
+            }
+        }
+    }
+    None
+}
+
+/// Classify one statement (a `:`-separated piece of a logical line).
+/// `opens_accessors` tells whether a `Property` header is followed by `Get`/`Set`.
+fn classify(stmt: &str, context: Context, opens_accessors: bool) -> Option<Role> {
+    let stmt = strip_attributes(stmt.trim());
+    if stmt.is_empty() || stmt.starts_with('#') {
+        return None;
+    }
+    let all = words(stmt);
+    let mut idx = 0;
+    let mut must_override = false;
+    while idx < all.len() && (MODIFIERS.contains(&all[idx].as_str()) || all[idx] == "mustoverride") {
+        must_override |= all[idx] == "mustoverride";
+        idx += 1;
+    }
+    let w: Vec<&str> = all[idx..].iter().map(String::as_str).collect();
+    let first = w.first().copied().unwrap_or("");
+    let second = w.get(1).copied().unwrap_or("");
+
+    if context == Context::Enum {
+        return (first == "end" && second == "enum").then_some(Role::Close(BlockKind::Enum));
+    }
+    let open = |kind| Some(Role::Open { kind, lambda: None });
+    let accessor = context == Context::Property && is_accessor_header(strip_modifiers(stmt));
+
+    let role = match first {
+        "end" => BlockKind::from_end_keyword(second).map(Role::Close),
+        "wend" => Some(Role::Close(BlockKind::While)),
+        "next" => Some(Role::Close(BlockKind::For)),
+        "loop" => Some(Role::Close(BlockKind::Do)),
+        "if" => {
+            if w.last() == Some(&"then") && stmt.to_lowercase().trim_end().ends_with("then") {
+                open(BlockKind::If)
+            } else {
+                None
+            }
+        }
+        "elseif" => Some(Role::Clause { kind: BlockKind::If, keyword: "ElseIf" }),
+        "else" if second == "if" => Some(Role::Clause { kind: BlockKind::If, keyword: "ElseIf" }),
+        "else" => Some(Role::Clause { kind: BlockKind::If, keyword: "Else" }),
+        "for" => open(BlockKind::For),
+        "while" => open(BlockKind::While),
+        "do" => open(BlockKind::Do),
+        "select" => open(BlockKind::Select),
+        "case" => Some(Role::Clause { kind: BlockKind::Select, keyword: "Case" }),
+        "try" => open(BlockKind::Try),
+        "catch" => Some(Role::Clause { kind: BlockKind::Try, keyword: "Catch" }),
+        "finally" => Some(Role::Clause { kind: BlockKind::Try, keyword: "Finally" }),
+        "with" => open(BlockKind::With),
+        "using" => open(BlockKind::Using),
+        "synclock" => open(BlockKind::SyncLock),
+        "sub" | "function" | "operator" if context == Context::Interface || must_override => None,
+        "sub" => open(BlockKind::Sub),
+        "function" => open(BlockKind::Function),
+        "operator" => open(BlockKind::Operator),
+        "property" if context == Context::Interface || must_override || !opens_accessors => None,
+        "property" => open(BlockKind::Property),
+        "get" if accessor => open(BlockKind::Get),
+        "set" if accessor => open(BlockKind::Set),
+        "addhandler" if context == Context::CustomEvent => open(BlockKind::AddHandler),
+        "removehandler" if context == Context::CustomEvent => open(BlockKind::RemoveHandler),
+        "raiseevent" if context == Context::CustomEvent => open(BlockKind::RaiseEvent),
+        "custom" if second == "event" => open(BlockKind::Event),
+        "class" => open(BlockKind::Class),
+        "module" => open(BlockKind::Module),
+        "structure" => open(BlockKind::Structure),
+        "interface" => open(BlockKind::Interface),
+        "enum" => open(BlockKind::Enum),
+        "namespace" => open(BlockKind::Namespace),
+        _ => None,
+    };
+    role.or_else(|| {
+        multiline_lambda(stmt).map(|(kind, depth)| Role::Open { kind, lambda: Some(depth) })
+    })
+}
+
+/// Roles of every statement on a logical line, in order.
+fn line_roles(code: &str, context: Context, opens_accessors: bool) -> Vec<Role> {
+    let trimmed = code.trim();
+    let lower = trimmed.to_lowercase();
+    // A single-line `If ... Then ...` owns everything after `Then`.
+    let single_line_if = (lower.starts_with("if ") || lower.starts_with("if("))
+        && !lower.ends_with("then");
+    if single_line_if {
+        return Vec::new();
+    }
+    split_statements(trimmed)
+        .into_iter()
+        .filter_map(|stmt| classify(stmt, context, opens_accessors))
+        .collect()
+}
+
+/// Group physical lines joined by continuations: `(first, last)` indices.
+/// Besides explicit ` _`, a line ending in `(`, `{` or `,` (or followed by a
+/// line starting with `}`) continues implicitly.
+fn logical_lines(lines: &[SourceLine]) -> Vec<(usize, usize)> {
+    let mut groups = Vec::new();
+    let mut start = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let code = strip_comment(&line.text).trim_end();
+        let next_closes = lines
+            .get(i + 1)
+            .is_some_and(|next| strip_comment(&next.text).trim_start().starts_with('}'));
+        let continues = code.ends_with(" _")
+            || code == "_"
+            || code.ends_with(['(', '{', ','])
+            || (next_closes && !code.trim().is_empty());
+        if !continues || i + 1 == lines.len() {
+            groups.push((start, i));
+            start = i + 1;
+        }
+    }
+    groups
+}
+
+fn logical_code(lines: &[SourceLine], (first, last): (usize, usize)) -> String {
+    lines[first..=last]
+        .iter()
+        .map(|l| {
+            let code = strip_comment(&l.text).trim_end();
+            code.strip_suffix('_').unwrap_or(code).to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+struct OpenBlock {
+    kind: BlockKind,
+    /// 1-based line of the header in the user's file.
+    line: usize,
+}
+
+fn line_span(line: usize, text: &str) -> Span {
+    let indent = text.len() - text.trim_start().len();
+    let start_col = text[..indent].chars().count() + 1;
+    let end_col = text.chars().count() + 1;
+    Span { start_line: line, start_col, end_line: line, end_col }
+}
+
+fn span_for(line: &SourceLine, fallback_line: usize) -> Span {
+    line_span(line.origin.map_or(fallback_line, |o| o + 1), &line.text)
+}
+
+/// Report unclosed blocks `stack[from..]` at `here`, inserting their
+/// terminators into `out` so the rest of the file still parses.
+fn close_blocks(
+    stack: &mut Vec<OpenBlock>,
+    from: usize,
+    here: Span,
+    out: &mut Vec<SourceLine>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    while stack.len() > from {
+        let block = stack.pop().expect("stack is longer than `from`");
+        diagnostics.push(Diagnostic::error(
+            codes::UNCLOSED_BLOCK,
+            format!(
+                "expected `{}` to close `{}` on line {}",
+                block.kind.terminator(),
+                block.kind.name(),
+                block.line
+            ),
+            here,
+        ));
+        out.push(SourceLine::synthesized(block.kind.terminator().to_string()));
+    }
+}
+
+/// Whether the logical line after `codes[gi]` starts with `Get` or `Set`.
+fn opens_accessors(codes: &[String], gi: usize) -> bool {
+    let next_code = codes[gi + 1..].iter().find(|c| !c.trim().is_empty());
+    next_code.is_some_and(|c| {
+        let w = words(c);
+        let mut i = 0;
+        while i < w.len() && MODIFIERS.contains(&w[i].as_str()) {
+            i += 1;
+        }
+        matches!(w.get(i).map(String::as_str), Some("get") | Some("set"))
+    })
+}
+
+/// Pass 1: match block headers with their terminators.
+fn check_blocks(lines: &mut Vec<SourceLine>, diagnostics: &mut Vec<Diagnostic>) {
+    let groups = logical_lines(lines);
+    let codes: Vec<String> = groups.iter().map(|&g| logical_code(lines, g)).collect();
+    let mut out: Vec<SourceLine> = Vec::with_capacity(lines.len());
+    let mut stack: Vec<OpenBlock> = Vec::new();
+
+    for (gi, &(first, last)) in groups.iter().enumerate() {
+        let here = span_for(&lines[first], first + 1);
+        let opens_accessors = opens_accessors(&codes, gi);
+
+        let mut stray = false;
+        let mut context = Context::of(stack.last().map(|b| b.kind));
+        for role in line_roles(&codes[gi], context, opens_accessors) {
+            match role {
+                Role::Open { kind, lambda } => {
+                    // A declaration ends whatever procedure is still open.
+                    if lambda.is_none() && kind.is_declaration() {
+                        let container = stack.iter().rposition(|b| b.kind.is_container());
+                        let from = container.map_or(0, |pos| pos + 1);
+                        close_blocks(&mut stack, from, here, &mut out, diagnostics);
+                    }
+                    stack.push(OpenBlock { kind, line: here.start_line });
+                }
+                Role::Clause { kind, keyword } => {
+                    if let Some(pos) = stack.iter().rposition(|b| b.kind == kind) {
+                        close_blocks(&mut stack, pos + 1, here, &mut out, diagnostics);
+                    } else {
+                        diagnostics.push(Diagnostic::error(
+                            codes::MISPLACED_CLAUSE,
+                            format!("`{}` without a matching `{}`", keyword, kind.name()),
+                            here,
+                        ));
+                        stray = true;
+                    }
+                }
+                Role::Close(kind) => {
+                    if let Some(pos) = stack.iter().rposition(|b| b.kind == kind) {
+                        close_blocks(&mut stack, pos + 1, here, &mut out, diagnostics);
+                        stack.pop();
+                    } else {
+                        diagnostics.push(Diagnostic::error(
+                            codes::UNMATCHED_END,
+                            format!("`{}` without a matching `{}`", kind.terminator(), kind.name()),
+                            here,
+                        ));
+                        stray = true;
+                    }
+                }
+            }
+            context = Context::of(stack.last().map(|b| b.kind));
+        }
+
+        for line in &lines[first..=last] {
+            let mut line = line.clone();
+            if stray {
+                line.blank();
+            }
+            out.push(line);
+        }
+    }
+
+    let end_line = lines.iter().rev().find_map(|l| l.origin).map_or(1, |o| o + 1);
+    let eof = line_span(end_line, lines.last().map_or("", |l| l.text.as_str()));
+    close_blocks(&mut stack, 0, eof, &mut out, diagnostics);
+    *lines = out;
+}
+
+// ---------------------------------------------------------------------------
+// Re-parsing
+// ---------------------------------------------------------------------------
+
+/// A procedure-level block (`Sub`, `Property`, `Enum`, ...) that can be
+/// re-parsed apart from the rest of the file.
+struct Unit {
+    /// Lines of the enclosing container headers, outermost first.
+    headers: Vec<usize>,
+    /// First and last line of the block itself.
+    body: (usize, usize),
+    /// Terminators closing the containers, innermost first.
+    closers: Vec<&'static str>,
+}
+
+/// The procedure-level blocks of a file whose blocks are balanced.
+fn procedure_units(lines: &[SourceLine]) -> Vec<Unit> {
+    let groups = logical_lines(lines);
+    let codes: Vec<String> = groups.iter().map(|&g| logical_code(lines, g)).collect();
+    let mut stack: Vec<(BlockKind, usize)> = Vec::new();
+    // The group the current unit starts on, and the depth it was opened at.
+    let mut open: Option<(usize, usize)> = None;
+    let mut units = Vec::new();
+
+    for (gi, &(_, last)) in groups.iter().enumerate() {
+        let opens_accessors = opens_accessors(&codes, gi);
+        let mut context = Context::of(stack.last().map(|b| b.0));
+        for role in line_roles(&codes[gi], context, opens_accessors) {
+            match role {
+                Role::Open { kind, lambda } => {
+                    let declares = lambda.is_none() && kind.is_declaration() && !kind.is_container();
+                    if open.is_none() && declares {
+                        open = Some((gi, stack.len()));
+                    }
+                    stack.push((kind, gi));
+                }
+                Role::Clause { .. } => {}
+                Role::Close(kind) => {
+                    if let Some(pos) = stack.iter().rposition(|b| b.0 == kind) {
+                        stack.truncate(pos);
+                    }
+                }
+            }
+            context = Context::of(stack.last().map(|b| b.0));
+        }
+
+        let Some((start, depth)) = open else { continue };
+        if stack.len() > depth {
+            continue;
+        }
+        open = None;
+        let containers = &stack[..depth];
+        if containers.iter().any(|b| !b.0.is_container()) {
+            continue;
+        }
+        units.push(Unit {
+            headers: containers
+                .iter()
+                .flat_map(|&(_, g)| groups[g].0..=groups[g].1)
+                .collect(),
+            body: (groups[start].0, last),
+            closers: containers.iter().rev().map(|b| b.0.terminator()).collect(),
+        });
+    }
+    units
+}
+
+/// Re-parse the lines at `indices` on their own, followed by `closers`,
+/// and copy the repaired lines back. Fails like [`reparse_until_clean`].
+fn reparse_part(
+    lines: &mut [SourceLine],
+    indices: &[usize],
+    closers: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), Span> {
+    let mut part: Vec<SourceLine> = indices.iter().map(|&i| lines[i].clone()).collect();
+    part.extend(closers.iter().map(|c| SourceLine::synthesized(c.to_string())));
+    let result = reparse_until_clean(&mut part, diagnostics);
+    for (line, &i) in part.into_iter().zip(indices) {
+        lines[i] = line;
+    }
+    result
+}
+
+/// Pass 2: re-parse, recording each failure and neutralizing the failing line.
+/// Fails with the position of the next error when it gave up after
+/// [`MAX_REPARSES`].
+fn reparse_until_clean(lines: &mut [SourceLine], diagnostics: &mut Vec<Diagnostic>) -> Result<(), Span> {
+    let mut reported: HashSet<usize> = diagnostics.iter().map(|d| d.span.start_line).collect();
+
+    for pass in 0..=MAX_REPARSES {
+        let text = lines.iter().map(|l| l.text.as_str()).collect::<Vec<_>>().join("\n");
+        let error = match parse_program(&text) {
+            Ok(_) => return Ok(()),
+            Err(e) => e,
+        };
+        let ParseError::PestError(pest_error) = &error else {
+            // Errors raised while building the AST carry no position.
+            diagnostics.push(diagnostic_from_error(&error, lines));
+            return Ok(());
+        };
+        if pass == MAX_REPARSES {
+            return Err(diagnostic_from_error(&error, lines).span);
+        }
+
+        let (line_no, _) = pest_position(pest_error);
+        let index = line_no.saturating_sub(1).min(lines.len().saturating_sub(1));
+        let groups = logical_lines(lines);
+        let Some(&(first, last)) = groups.iter().find(|&&(f, l)| f <= index && index <= l) else {
+            return Ok(());
+        };
+
+        let diagnostic = diagnostic_from_error(&error, lines);
+        if reported.insert(diagnostic.span.start_line) {
+            diagnostics.push(diagnostic);
+        }
+
+        // An accessor that fails right after its header usually means the
+        // header itself was not understood; neutralize the header instead so
+        // the accessor bodies are not reported one line at a time.
+        let failing = logical_code(lines, (first, last));
+        let (first, last) = match groups.iter().position(|&g| g == (first, last)) {
+            Some(gi) if is_accessor_header(strip_modifiers(failing.trim())) => groups[..gi]
+                .iter()
+                .rev()
+                .find(|&&g| !logical_code(lines, g).trim().is_empty())
+                .filter(|&&(f, _)| lines[f].state == LineState::Original)
+                .copied()
+                .unwrap_or((first, last)),
+            _ => (first, last),
+        };
+        if !neutralize(lines, first, last) {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+/// Replace a failing logical line with something the grammar accepts:
+/// a neutral block header/terminator of the same kind, or nothing.
+/// Returns false when the line was already blank (no progress possible).
+fn neutralize(lines: &mut [SourceLine], first: usize, last: usize) -> bool {
+    match lines[first].state {
+        LineState::Blank => false,
+        LineState::Canonical => {
+            for line in &mut lines[first..=last] {
+                line.blank();
+            }
+            true
+        }
+        LineState::Original => {
+            let code = logical_code(lines, (first, last));
+            // Without the enclosing context, read accessor headers by shape.
+            let context = if is_accessor_header(strip_modifiers(code.trim())) {
+                Context::Property
+            } else {
+                Context::None
+            };
+            let replacement = line_roles(&code, context, true).first().map(|role| match *role {
+                Role::Open { kind, lambda } => kind.neutral_header(lambda),
+                Role::Clause { keyword: "ElseIf", .. } => "ElseIf True Then".to_string(),
+                Role::Clause { keyword: "Case", .. } => "Case 0".to_string(),
+                Role::Clause { keyword, .. } => keyword.to_string(),
+                Role::Close(kind) => kind.terminator().to_string(),
+            });
+            for line in &mut lines[first..=last] {
+                line.blank();
+            }
+            if let Some(text) = replacement {
+                lines[first].text = text;
+                lines[first].state = LineState::Canonical;
+            }
+            true
+        }
+    }
+}
+
+/// `Get` or `Set(value As T)`, judged by shape alone.
+fn is_accessor_header(code: &str) -> bool {
+    let lower = code.trim().to_lowercase();
+    lower == "get" || lower == "set" || lower.starts_with("set(") || lower.starts_with("set (")
+}
+
+fn pest_position(error: &pest::error::Error<Rule>) -> (usize, usize) {
+    match error.line_col {
+        LineColLocation::Pos(pos) => pos,
+        LineColLocation::Span(start, _) => start,
+    }
+}
+
+/// Turn a parse error into a diagnostic, mapping its position back through
+/// `lines` (empty when the error came from the untouched source).
+fn diagnostic_from_error(error: &ParseError, lines: &[SourceLine]) -> Diagnostic {
+    let ParseError::PestError(pest_error) = error else {
+        return Diagnostic::error(codes::SYNTAX_ERROR, error.to_string(), Span::default());
+    };
+    let (line_no, col) = pest_position(pest_error);
+    let index = line_no.saturating_sub(1);
+
+    let (line, text, original) = match lines.get(index) {
+        None if lines.is_empty() => (line_no, String::new(), true),
+        None => {
+            let last = lines.iter().rev().find_map(|l| l.origin).map_or(1, |o| o + 1);
+            (last, String::new(), false)
+        }
+        Some(l) => {
+            // Synthesized lines report at the next line from the user's file,
+            // or the last one when nothing follows.
+            let origin = l
+                .origin
+                .or_else(|| lines[index..].iter().find_map(|n| n.origin))
+                .or_else(|| lines[..index].iter().rev().find_map(|n| n.origin));
+            let line = origin.map_or(line_no, |o| o + 1);
+            (line, l.text.clone(), l.state == LineState::Original)
+        }
+    };
+
+    let mut span = line_span(line, &text);
+    if original {
+        span.start_col = col;
+    }
+    let found = found_token(&text, if original { col } else { span.start_col });
+    let message = match &pest_error.variant {
+        ErrorVariant::ParsingError { positives, .. } => expected_message(positives, &found),
+        ErrorVariant::CustomError { message } => message.clone(),
+    };
+    Diagnostic::error(codes::SYNTAX_ERROR, message, span)
+}
+
+/// The token at 1-based column `col` of `text`, for "found ..." messages.
+fn found_token(text: &str, col: usize) -> String {
+    let rest: String = text.chars().skip(col.saturating_sub(1)).collect();
+    let rest = rest.trim_start();
+    let Some(c) = rest.chars().next() else {
+        return "end of line".to_string();
+    };
+    let token: String = if c.is_alphanumeric() || c == '_' {
+        rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect()
+    } else {
+        c.to_string()
+    };
+    format!("`{}`", token)
+}
+
+/// Human-readable name for what the grammar expected, or `None` for rules
+/// that only add noise to the message.
+fn describe_rule(rule: &Rule) -> Option<&'static str> {
+    let name = format!("{:?}", rule);
+    Some(match name.as_str() {
+        "NEWLINE" | "EOI" => "end of statement",
+        "statement_line" | "statement" => "statement",
+        "expression" | "logical_xor" | "logical_or" | "logical_and" | "not_condition" | "equality"
+        | "comparison" | "bit_shift" | "additive" | "multiplicative" | "unary" | "exponent"
+        | "postfix" | "primary" | "literal" => "expression",
+        "xor_op" | "or_op" | "and_op" | "eq_op" | "comp_op" | "like_op" | "shift_op" | "add_op"
+        | "mult_op" | "exp_op" | "member_chain" | "member_chain_call" | "member_chain_access" => {
+            "operator"
+        }
+        "identifier" | "member_identifier" | "sub_name" => "identifier",
+        "type_name" | "dotted_identifier" => "type name",
+        "dim_new_keyword" => "`New`",
+        "argument_list" => "argument list",
+        "param_list" | "param" => "parameter",
+        "if_end" => "`End If`",
+        "elseif_block" => "`ElseIf`",
+        "else_block" => "`Else`",
+        "for_end" => "`Next`",
+        "while_end" => "`End While`",
+        "do_end" => "`Loop`",
+        "select_end" => "`End Select`",
+        "case_block" | "case_else" => "`Case`",
+        "try_end" => "`End Try`",
+        "catch_block" => "`Catch`",
+        "finally_block" => "`Finally`",
+        "with_end" => "`End With`",
+        "using_end" => "`End Using`",
+        "synclock_end" => "`End SyncLock`",
+        "sub_end" => "`End Sub`",
+        "func_end" => "`End Function`",
+        "property_end" => "`End Property`",
+        "get_end" => "`End Get`",
+        "set_end" => "`End Set`",
+        "class_end" => "`End Class`",
+        "module_end" => "`End Module`",
+        "structure_end" => "`End Structure`",
+        "interface_end" => "`End Interface`",
+        "enum_end" => "`End Enum`",
+        "namespace_end" => "`End Namespace`",
+        "dim_statement" | "sub_decl" | "function_decl" | "class_decl" | "enum_decl" | "field_decl"
+        | "auto_property_decl" | "property_decl" | "const_statement" => "declaration",
+        _ => return None,
+    })
+}
+
+fn expected_message(positives: &[Rule], found: &str) -> String {
+    let mut expected: Vec<&str> = Vec::new();
+    for rule in positives {
+        if let Some(desc) = describe_rule(rule).filter(|d| !expected.contains(d)) {
+            expected.push(desc);
+        }
+    }
+    match expected.len() {
+        0 => format!("syntax error: unexpected {}", found),
+        1 => format!("expected {}, found {}", expected[0], found),
+        n => format!(
+            "expected {} or {}, found {}",
+            expected[..n - 1].join(", "),
+            expected[n - 1],
+            found
+        ),
+    }
+}
@@ -1,5 +1,9 @@
 pub mod ast;
 pub mod parser;
+pub mod diagnostics;
+pub mod preprocessor;
 
 pub use ast::*;
-pub use parser::{parse_program, parse_expression_str, ParseError, ParseResult};
+pub use parser::{parse_program, parse_program_named, parse_program_with, parse_program_named_with, parse_expression_str, ParseError, ParseResult};
+pub use diagnostics::{check_syntax, check_syntax_with, parse_program_diagnostics, parse_program_diagnostics_with, Diagnostic, Severity};
+pub use preprocessor::{preprocess, ConditionalConstants, ConstValue};
@@ -2,6 +2,7 @@ use pest::Parser;
 use pest::iterators::Pair;
 use pest_derive::Parser;
 use crate::ast::*;
+use crate::preprocessor::{preprocess, ConditionalConstants};
 
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
@@ -17,22 +18,36 @@ pub enum ParseError {
 
     #[error("Parse error: {0}")]
     Custom(String),
+
+    /// A malformed or unbalanced `#If`, `#Const` or `#Region` directive.
+    #[error("{span}: {message}")]
+    Directive { message: String, span: Span },
 }
 
 pub type ParseResult<T> = Result<T, ParseError>;
 
 pub fn parse_program(source: &str) -> ParseResult<Program> {
+    parse_program_with(source, &ConditionalConstants::new())
+}
+
+/// Parse `source` with `constants` (a project's DEBUG, TRACE and
+/// `<DefineConstants>`) visible to its `#If` directives.
+pub fn parse_program_with(source: &str, constants: &ConditionalConstants) -> ParseResult<Program> {
     // Strip BOM from any source — single place for all callers
     let source = source.trim_start_matches('\u{feff}');
-    let pairs = VBParser::parse(Rule::program, source)?;
+    let source = preprocess(source, constants)?;
+    let pairs = VBParser::parse(Rule::program, &source)?;
     let mut declarations = Vec::new();
-    let mut statements = Vec::new();
+    let mut statements = Block::new();
+    let mut options = OptionSettings::default();
+    let mut attributes = Vec::new();
 
     for pair in pairs {
         match pair.as_rule() {
             Rule::program => {
                 for inner in pair.into_inner() {
                     match inner.as_rule() {
+                        Rule::option_statement => parse_option_statement(inner, &mut options),
                         Rule::imports_statement => {
                             declarations.push(parse_imports_statement(inner)?);
                         }
@@ -49,10 +64,11 @@ pub fn parse_program(source: &str) -> ParseResult<Program> {
                                 } else if let Some(decl) = try_parse_declaration(stmt_pair.clone())? {
                                     declarations.push(decl);
                                 } else {
-                                    statements.push(parse_statement(stmt_pair)?);
+                                    push_statement(&mut statements, stmt_pair)?;
                                 }
                             }
                         }
+                        Rule::attribute_block => attributes.extend(parse_attribute_block(inner)?),
                         Rule::NEWLINE | Rule::EOI => {}
                         _ => {}
                     }
@@ -65,9 +81,89 @@ pub fn parse_program(source: &str) -> ParseResult<Program> {
     Ok(Program {
         declarations,
         statements,
+        source_file: None,
+        options,
+        attributes,
     })
 }
 
+/// Parse `Option Explicit|Strict|Infer [On|Off]` or `Option Compare Binary|Text`
+fn parse_option_statement(pair: Pair<Rule>, options: &mut OptionSettings) {
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::option_compare => {
+                let text = p.into_inner().next().is_some_and(|m| m.as_str().eq_ignore_ascii_case("text"));
+                options.compare = Some(if text { CompareMode::Text } else { CompareMode::Binary });
+            }
+            Rule::option_switch => {
+                let mut inner = p.into_inner();
+                let name = inner.next().map(|n| n.as_str().to_lowercase()).unwrap_or_default();
+                // A bare `Option Strict` means On
+                let on = inner.next().is_none_or(|v| v.as_str().eq_ignore_ascii_case("on"));
+                match name.as_str() {
+                    "explicit" => options.explicit = Some(on),
+                    "strict" => options.strict = Some(on),
+                    "infer" => options.infer = Some(on),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Parse `source` and record `file_name` on the program and on every
+/// procedure and property in it, so runtime errors can name the file.
+pub fn parse_program_named(source: &str, file_name: &str) -> ParseResult<Program> {
+    parse_program_named_with(source, file_name, &ConditionalConstants::new())
+}
+
+/// [`parse_program_named`] with conditional compilation constants.
+pub fn parse_program_named_with(source: &str, file_name: &str, constants: &ConditionalConstants) -> ParseResult<Program> {
+    let mut program = parse_program_with(source, constants)?;
+    program.source_file = Some(file_name.to_string());
+    for decl in &mut program.declarations {
+        set_declaration_source(decl, file_name);
+    }
+    Ok(program)
+}
+
+fn set_declaration_source(decl: &mut Declaration, file_name: &str) {
+    match decl {
+        Declaration::Sub(s) => s.source_file = Some(file_name.to_string()),
+        Declaration::Function(f) => f.source_file = Some(file_name.to_string()),
+        Declaration::Class(c) => set_class_source(c, file_name),
+        Declaration::Structure(s) => {
+            set_members_source(&mut s.methods, &mut s.properties, file_name);
+        }
+        Declaration::Namespace(ns) => {
+            for inner in &mut ns.declarations {
+                set_declaration_source(inner, file_name);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn set_class_source(class: &mut ClassDecl, file_name: &str) {
+    set_members_source(&mut class.methods, &mut class.properties, file_name);
+    for nested in &mut class.nested_classes {
+        set_class_source(nested, file_name);
+    }
+}
+
+fn set_members_source(methods: &mut [MethodDecl], properties: &mut [PropertyDecl], file_name: &str) {
+    for method in methods {
+        match method {
+            MethodDecl::Sub(s) => s.source_file = Some(file_name.to_string()),
+            MethodDecl::Function(f) => f.source_file = Some(file_name.to_string()),
+        }
+    }
+    for prop in properties {
+        prop.source_file = Some(file_name.to_string());
+    }
+}
+
 pub fn parse_expression_str(source: &str) -> ParseResult<Expression> {
     let mut pairs = VBParser::parse(Rule::expression, source)?;
     let pair = pairs.next().ok_or_else(|| ParseError::Custom("No expression found".to_string()))?;
@@ -90,6 +186,7 @@ fn try_parse_declaration(pair: Pair<Rule>) -> ParseResult<Option<Declaration>> {
         Rule::imports_statement => Ok(Some(parse_imports_statement(pair)?)),
         Rule::interface_decl => Ok(Some(Declaration::Interface(parse_interface_decl(pair)?))),
         Rule::structure_decl => Ok(Some(Declaration::Structure(parse_structure_decl(pair)?))),
+        Rule::type_decl => Ok(Some(Declaration::Structure(parse_type_decl(pair)?))),
         Rule::delegate_sub_decl => Ok(Some(Declaration::Delegate(parse_delegate_decl(pair, true)?))),
         Rule::delegate_function_decl => Ok(Some(Declaration::Delegate(parse_delegate_decl(pair, false)?))),
         Rule::event_decl => Ok(Some(Declaration::Event(parse_event_decl(pair)?))),
@@ -98,6 +195,7 @@ fn try_parse_declaration(pair: Pair<Rule>) -> ParseResult<Option<Declaration>> {
 }
 
 fn parse_dim_variable(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut name = Identifier::new("");
     let mut var_type = None;
@@ -129,7 +227,7 @@ fn parse_dim_variable(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
             Rule::argument_list => {
                 // Constructor arguments for "As New Type(args)"
                 for arg_pair in p.into_inner() {
-                    if arg_pair.as_rule() == Rule::expression {
+                    if matches!(arg_pair.as_rule(), Rule::expression | Rule::named_argument) {
                         ctor_args.push(parse_expression(arg_pair)?);
                     }
                 }
@@ -167,11 +265,11 @@ fn parse_dim_variable(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
         if let Some(t) = &var_type {
             let class_id = Identifier::new(t.to_string());
             if let Some(elements) = from_init {
-                initializer = Some(Expression::NewFromInitializer(class_id, ctor_args, elements));
+                initializer = Some(ExprKind::NewFromInitializer(class_id, ctor_args, elements).into());
             } else if let Some(members) = with_init {
-                initializer = Some(Expression::NewWithInitializer(class_id, ctor_args, members));
+                initializer = Some(ExprKind::NewWithInitializer(class_id, ctor_args, members).into());
             } else {
-                initializer = Some(Expression::New(class_id, ctor_args));
+                initializer = Some(ExprKind::New(class_id, ctor_args).into());
             }
         }
     }
@@ -182,6 +280,8 @@ fn parse_dim_variable(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
         array_bounds,
         initializer,
         with_events: false,
+        attributes: Vec::new(),
+        span,
     })
 }
 
@@ -196,6 +296,7 @@ fn parse_dim_statement(pair: Pair<Rule>) -> ParseResult<Vec<VariableDecl>> {
 }
 
 fn parse_const_statement(pair: Pair<Rule>) -> ParseResult<ConstDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
@@ -232,6 +333,7 @@ fn parse_const_statement(pair: Pair<Rule>) -> ParseResult<ConstDecl> {
         name,
         const_type,
         value: value.ok_or_else(|| ParseError::Custom("Const must have a value".to_string()))?,
+        span,
     })
 }
 
@@ -239,7 +341,64 @@ fn parse_array_literal(pair: Pair<Rule>) -> ParseResult<Expression> {
     let elements: Vec<Expression> = pair.into_inner()
         .map(|p| parse_expression(p))
         .collect::<Result<_, _>>()?;
-    Ok(Expression::ArrayLiteral(elements))
+    Ok(ExprKind::ArrayLiteral(elements).into())
+}
+
+/// A numeric literal typed the way VB types it. Without a type character a
+/// whole number is Integer, or Long when it doesn't fit; `&H`, `&O` and `&B`
+/// literals are bit patterns, so `&HFFFFFFFF` is Integer -1. Anything with a
+/// fraction or exponent is Double unless its type character says otherwise.
+fn parse_numeric_literal(text: &str) -> ParseResult<ExprKind> {
+    let overflow = || ParseError::Custom(format!("Overflow in numeric literal '{}'", text));
+    let digits = text.replace('_', "");
+    let (radix, body) = match digits.get(..2).map(|p| p.to_ascii_uppercase()).as_deref() {
+        Some("&H") => (16, &digits[2..]),
+        Some("&O") => (8, &digits[2..]),
+        Some("&B") => (2, &digits[2..]),
+        _ => (10, &digits[..]),
+    };
+    // Hex digits include D and F, so only integer type characters follow a prefix
+    let type_chars: &[char] = if radix == 10 { &['S', 'I', 'L', 'U', 'F', 'R', 'D', '%', '&', '@', '!', '#'] } else { &['S', 'I', 'L', 'U', '%', '&'] };
+    let number = body.trim_end_matches(|c: char| type_chars.contains(&c.to_ascii_uppercase()));
+    let suffix = body[number.len()..].to_ascii_uppercase();
+
+    let floating = number.contains(['.', 'e', 'E']);
+    match suffix.as_str() {
+        "F" | "!" => {
+            let f: f32 = number.parse().map_err(|_| overflow())?;
+            return if f.is_finite() { Ok(ExprKind::SingleLiteral(f)) } else { Err(overflow()) };
+        }
+        "D" | "@" => return Ok(ExprKind::DecimalLiteral(number.to_string())),
+        "R" | "#" => {}
+        _ if floating => {}
+        _ => {
+            let bits = u64::from_str_radix(number, radix).map_err(|_| overflow())?;
+            let prefixed = radix != 10;
+            return match suffix.as_str() {
+                "" if prefixed => Ok(match u32::try_from(bits) {
+                    Ok(b) => ExprKind::IntegerLiteral(b as i32),
+                    Err(_) => ExprKind::LongLiteral(bits as i64),
+                }),
+                "" => match (i32::try_from(bits), i64::try_from(bits)) {
+                    (Ok(i), _) => Ok(ExprKind::IntegerLiteral(i)),
+                    (_, Ok(l)) => Ok(ExprKind::LongLiteral(l)),
+                    _ => Err(overflow()),
+                },
+                "S" if prefixed => u16::try_from(bits).map(|b| ExprKind::ShortLiteral(b as i16)).map_err(|_| overflow()),
+                "S" => i16::try_from(bits).map(ExprKind::ShortLiteral).map_err(|_| overflow()),
+                "US" => u16::try_from(bits).map(ExprKind::UShortLiteral).map_err(|_| overflow()),
+                "I" | "%" if prefixed => u32::try_from(bits).map(|b| ExprKind::IntegerLiteral(b as i32)).map_err(|_| overflow()),
+                "I" | "%" => i32::try_from(bits).map(ExprKind::IntegerLiteral).map_err(|_| overflow()),
+                "UI" => u32::try_from(bits).map(ExprKind::UIntegerLiteral).map_err(|_| overflow()),
+                "L" | "&" if prefixed => Ok(ExprKind::LongLiteral(bits as i64)),
+                "L" | "&" => i64::try_from(bits).map(ExprKind::LongLiteral).map_err(|_| overflow()),
+                "UL" => Ok(ExprKind::ULongLiteral(bits)),
+                _ => Err(ParseError::Custom(format!("Invalid type character in numeric literal '{}'", text))),
+            };
+        }
+    }
+    let d: f64 = number.parse().map_err(|_| overflow())?;
+    if d.is_finite() { Ok(ExprKind::DoubleLiteral(d)) } else { Err(overflow()) }
 }
 
 fn parse_redim_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
@@ -275,15 +434,43 @@ fn parse_redim_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
 
 
 
+/// `<Obsolete("Use Foo"), Browsable(False)>`: one Attribute per entry.
+fn parse_attribute_block(pair: Pair<Rule>) -> ParseResult<Vec<Attribute>> {
+    let mut attributes = Vec::new();
+    for attr in pair.into_inner() {
+        let mut name = String::new();
+        let mut target = None;
+        let mut arguments = Vec::new();
+        for p in attr.into_inner() {
+            match p.as_rule() {
+                Rule::attribute_target => target = Some(p.as_str().trim_end_matches(':').trim().to_string()),
+                Rule::dotted_identifier => name = p.as_str().to_string(),
+                Rule::argument_list => {
+                    for arg in p.into_inner() {
+                        if matches!(arg.as_rule(), Rule::expression | Rule::named_argument) {
+                            arguments.push(parse_expression(arg)?);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        attributes.push(Attribute { name, target, arguments });
+    }
+    Ok(attributes)
+}
+
 fn parse_sub_decl(pair: Pair<Rule>) -> ParseResult<SubDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
+    let mut type_parameters = Vec::new();
     let mut parameters = Vec::new();
-    let mut body = Vec::new();
+    let mut body = Block::new();
     let mut handles: Option<Vec<String>> = None;
     let mut is_async = false;
-    let mut is_extension = false;
+    let mut attributes = Vec::new();
     let mut is_overridable = false;
     let mut is_overrides = false;
     let mut is_must_override = false;
@@ -292,7 +479,7 @@ fn parse_sub_decl(pair: Pair<Rule>) -> ParseResult<SubDecl> {
 
     for p in inner {
         match p.as_rule() {
-            Rule::extension_attribute => is_extension = true,
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::visibility_modifier => {
                 let s = p.as_str().to_lowercase();
                 match s.as_str() {
@@ -316,17 +503,18 @@ fn parse_sub_decl(pair: Pair<Rule>) -> ParseResult<SubDecl> {
                 }
             }
             Rule::sub_name => name = Identifier::new(p.as_str()),
+            Rule::type_parameter_list => type_parameters = parse_type_parameter_list(p),
             Rule::param_list => parameters = parse_param_list(p)?,
             Rule::statement | Rule::statement_line => {
                 for stmt_pair in p.into_inner() {
                     if stmt_pair.as_rule() == Rule::NEWLINE || stmt_pair.as_rule() == Rule::EOI {
                         continue;
                     }
-                    body.push(parse_statement(stmt_pair)?);
+                    push_statement(&mut body, stmt_pair)?;
                 }
             }
             Rule::sub_block_body => {
-                body.extend(parse_block(p)?);
+                body.extend_block(parse_block(p)?);
             }
             Rule::sub_inline_body => {
                 for stmt_pair in p.into_inner() {
@@ -336,12 +524,12 @@ fn parse_sub_decl(pair: Pair<Rule>) -> ParseResult<SubDecl> {
                                 if inner.as_rule() == Rule::NEWLINE || inner.as_rule() == Rule::EOI {
                                     continue;
                                 }
-                                body.push(parse_statement(inner)?);
+                                push_statement(&mut body, inner)?;
                             }
                         }
                         Rule::sub_end | Rule::NEWLINE | Rule::EOI => {}
                         _ => {
-                            body.push(parse_statement(stmt_pair)?);
+                            push_statement(&mut body, stmt_pair)?;
                         }
                     }
                 }
@@ -364,29 +552,36 @@ fn parse_sub_decl(pair: Pair<Rule>) -> ParseResult<SubDecl> {
     Ok(SubDecl {
         visibility,
         name,
+        type_parameters,
         parameters,
         body,
         handles,
         is_async,
-        is_extension,
+        is_extension: attributes.iter().any(|a| a.is("Extension")),
         is_overridable,
         is_overrides,
         is_must_override,
         is_shared,
         is_not_overridable,
+        attributes,
+        span,
+        source_file: None,
     })
 }
 
 fn parse_function_decl(pair: Pair<Rule>) -> ParseResult<FunctionDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
+    let mut type_parameters = Vec::new();
     let mut parameters = Vec::new();
     let mut return_type = None;
-    let mut body = Vec::new();
+    let mut body = Block::new();
     let mut handles: Option<Vec<String>> = None;
     let mut is_async = false;
-    let mut is_extension = false;
+    let mut is_iterator = false;
+    let mut attributes = Vec::new();
     let mut is_overridable = false;
     let mut is_overrides = false;
     let mut is_must_override = false;
@@ -395,7 +590,7 @@ fn parse_function_decl(pair: Pair<Rule>) -> ParseResult<FunctionDecl> {
 
     for p in inner {
         match p.as_rule() {
-            Rule::extension_attribute => is_extension = true,
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::visibility_modifier => {
                 let s = p.as_str().to_lowercase();
                 match s.as_str() {
@@ -407,6 +602,7 @@ fn parse_function_decl(pair: Pair<Rule>) -> ParseResult<FunctionDecl> {
                 }
             }
             Rule::async_kw => is_async = true,
+            Rule::iterator_kw => is_iterator = true,
             Rule::sub_modifier_keyword => {
                 let kw = p.as_str().to_lowercase();
                 match kw.as_str() {
@@ -419,6 +615,7 @@ fn parse_function_decl(pair: Pair<Rule>) -> ParseResult<FunctionDecl> {
                 }
             },
             Rule::identifier => name = Identifier::new(p.as_str()),
+            Rule::type_parameter_list => type_parameters = parse_type_parameter_list(p),
             Rule::param_list => parameters = parse_param_list(p)?,
             Rule::type_name => return_type = Some(VBType::from_str(p.as_str())),
             Rule::statement | Rule::statement_line => {
@@ -426,11 +623,11 @@ fn parse_function_decl(pair: Pair<Rule>) -> ParseResult<FunctionDecl> {
                     if stmt_pair.as_rule() == Rule::NEWLINE || stmt_pair.as_rule() == Rule::EOI {
                         continue;
                     }
-                    body.push(parse_statement(stmt_pair)?);
+                    push_statement(&mut body, stmt_pair)?;
                 }
             }
             Rule::func_block_body => {
-                body.extend(parse_block(p)?);
+                body.extend_block(parse_block(p)?);
             }
             Rule::func_inline_body => {
                 for stmt_pair in p.into_inner() {
@@ -440,12 +637,12 @@ fn parse_function_decl(pair: Pair<Rule>) -> ParseResult<FunctionDecl> {
                                 if inner.as_rule() == Rule::NEWLINE || inner.as_rule() == Rule::EOI {
                                     continue;
                                 }
-                                body.push(parse_statement(inner)?);
+                                push_statement(&mut body, inner)?;
                             }
                         }
                         Rule::func_end | Rule::NEWLINE | Rule::EOI => {}
                         _ => {
-                            body.push(parse_statement(stmt_pair)?);
+                            push_statement(&mut body, stmt_pair)?;
                         }
                     }
                 }
@@ -468,20 +665,64 @@ fn parse_function_decl(pair: Pair<Rule>) -> ParseResult<FunctionDecl> {
     Ok(FunctionDecl {
         visibility,
         name,
+        type_parameters,
         parameters,
         return_type,
         body,
         is_async,
-        is_extension,
+        is_iterator,
+        is_extension: attributes.iter().any(|a| a.is("Extension")),
         is_overridable,
         is_overrides,
         is_must_override,
         is_shared,
         is_not_overridable,
         handles,
+        attributes,
+        span,
+        source_file: None,
     })
 }
 
+fn parse_operator_decl(pair: Pair<Rule>) -> ParseResult<OperatorDecl> {
+    let span = Span::from_pest(pair.as_span());
+    let mut visibility = Visibility::Public;
+    let mut symbol = String::new();
+    let mut conversion = None;
+    let mut parameters = Vec::new();
+    let mut return_type = None;
+    let mut body = Block::new();
+
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::visibility_modifier => {
+                let s = p.as_str().to_lowercase();
+                match s.as_str() {
+                    "public" => visibility = Visibility::Public,
+                    "private" => visibility = Visibility::Private,
+                    "protected" => visibility = Visibility::Protected,
+                    "friend" => visibility = Visibility::Friend,
+                    _ => {}
+                }
+            }
+            Rule::conversion_modifier => {
+                conversion = Some(if p.as_str().eq_ignore_ascii_case("widening") {
+                    ConversionKind::Widening
+                } else {
+                    ConversionKind::Narrowing
+                });
+            }
+            Rule::operator_symbol => symbol = p.as_str().to_string(),
+            Rule::param_list => parameters = parse_param_list(p)?,
+            Rule::type_name => return_type = Some(VBType::from_str(p.as_str())),
+            Rule::operator_block_body => body.extend_block(parse_block(p)?),
+            _ => {}
+        }
+    }
+
+    Ok(OperatorDecl { visibility, symbol, conversion, parameters, return_type, body, span })
+}
+
 fn parse_module_decl(pair: Pair<Rule>) -> ParseResult<Vec<Declaration>> {
     let mut declarations = Vec::new();
 
@@ -494,6 +735,8 @@ fn parse_module_decl(pair: Pair<Rule>) -> ParseResult<Vec<Declaration>> {
             Rule::field_decl => declarations.push(Declaration::Variable(vec![parse_field_decl(p)?])),
             Rule::class_decl => declarations.push(Declaration::Class(parse_class_decl(p)?)),
             Rule::enum_decl => declarations.push(Declaration::Enum(parse_enum_decl(p)?)),
+            Rule::structure_decl => declarations.push(Declaration::Structure(parse_structure_decl(p)?)),
+            Rule::type_decl => declarations.push(Declaration::Structure(parse_type_decl(p)?)),
             Rule::identifier | Rule::NEWLINE | Rule::module_end => {}
             _ => {}
         }
@@ -557,6 +800,9 @@ fn parse_namespace_decl(pair: Pair<Rule>) -> ParseResult<Declaration> {
             Rule::structure_decl => {
                 declarations.push(Declaration::Structure(parse_structure_decl(p)?));
             }
+            Rule::type_decl => {
+                declarations.push(Declaration::Structure(parse_type_decl(p)?));
+            }
             Rule::NEWLINE | Rule::namespace_end => {}
             _ => {}
         }
@@ -568,12 +814,15 @@ fn parse_namespace_decl(pair: Pair<Rule>) -> ParseResult<Declaration> {
 /// Parse an auto-implemented property (`Public Property Name As String = "default"`)
 /// into a VariableDecl (field), since it's syntactic sugar for a backing field.
 fn parse_auto_property_as_field(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
+    let span = Span::from_pest(pair.as_span());
     let mut name = Identifier::new("");
     let mut var_type = None;
     let mut initializer = None;
+    let mut attributes = Vec::new();
 
     for p in pair.into_inner() {
         match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::identifier => name = Identifier::new(p.as_str()),
             Rule::type_name => var_type = Some(VBType::from_str(p.as_str())),
             Rule::expression => initializer = Some(parse_expression(p)?),
@@ -588,12 +837,16 @@ fn parse_auto_property_as_field(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
         array_bounds: None,
         initializer,
         with_events: false,
+        attributes,
+        span,
     })
 }
 
 fn parse_class_decl(pair: Pair<Rule>) -> ParseResult<ClassDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut name = Identifier::new("");
+    let mut type_parameters = Vec::new();
     let mut is_partial = false;
     let mut visibility = Visibility::Public;
     let mut inherits = None;
@@ -605,9 +858,13 @@ fn parse_class_decl(pair: Pair<Rule>) -> ParseResult<ClassDecl> {
     let mut is_not_inheritable = false;
     let mut nested_classes = Vec::new();
     let mut nested_enums = Vec::new();
+    let mut operators = Vec::new();
+    let mut events = Vec::new();
+    let mut attributes = Vec::new();
 
     for p in inner {
         match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::partial_keyword => is_partial = true,
             Rule::visibility_modifier => {
                 let s = p.as_str().to_lowercase();
@@ -634,6 +891,7 @@ fn parse_class_decl(pair: Pair<Rule>) -> ParseResult<ClassDecl> {
                 }
             }
             Rule::identifier => name = Identifier::new(p.as_str()),
+            Rule::type_parameter_list => type_parameters = parse_type_parameter_list(p),
             Rule::property_decl => properties.push(parse_property_decl(p)?),
             Rule::auto_property_decl => {
                 // Auto-implemented property → treat as a field
@@ -641,6 +899,7 @@ fn parse_class_decl(pair: Pair<Rule>) -> ParseResult<ClassDecl> {
             }
             Rule::sub_decl => methods.push(MethodDecl::Sub(parse_sub_decl(p)?)),
             Rule::function_decl => methods.push(MethodDecl::Function(parse_function_decl(p)?)),
+            Rule::operator_decl => operators.push(parse_operator_decl(p)?),
         Rule::dim_statement => {
             if let Ok(crate::ast::stmt::Statement::Dim(decls)) = parse_statement(p) {
                 fields.extend(decls);
@@ -655,6 +914,8 @@ fn parse_class_decl(pair: Pair<Rule>) -> ParseResult<ClassDecl> {
             Rule::enum_decl => {
                 nested_enums.push(parse_enum_decl(p)?);
             }
+            Rule::event_decl => events.push(parse_event_decl(p)?),
+            Rule::custom_event_decl => events.push(parse_custom_event_decl(p)?),
             Rule::NEWLINE | Rule::class_end => {}
             _ => {}
         }
@@ -663,6 +924,7 @@ fn parse_class_decl(pair: Pair<Rule>) -> ParseResult<ClassDecl> {
     Ok(ClassDecl {
         visibility,
         name,
+        type_parameters,
         is_partial,
         inherits,
         implements,
@@ -673,10 +935,15 @@ fn parse_class_decl(pair: Pair<Rule>) -> ParseResult<ClassDecl> {
         is_not_inheritable,
         nested_classes,
         nested_enums,
+        operators,
+        events,
+        attributes,
+        span,
     })
 }
 
 fn parse_property_decl(pair: Pair<Rule>) -> ParseResult<PropertyDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
@@ -684,6 +951,8 @@ fn parse_property_decl(pair: Pair<Rule>) -> ParseResult<PropertyDecl> {
     let mut return_type = None;
     let mut getter = None;
     let mut setter = None;
+    let mut is_default = false;
+    let mut attributes = Vec::new();
 
     for p in inner {
         match p.as_str().to_lowercase().as_str() {
@@ -691,6 +960,8 @@ fn parse_property_decl(pair: Pair<Rule>) -> ParseResult<PropertyDecl> {
             "private" => visibility = Visibility::Private,
             _ => {
                 match p.as_rule() {
+                    Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
+                    Rule::default_keyword => is_default = true,
                     Rule::identifier => name = Identifier::new(p.as_str()),
                     Rule::param_list => parameters = parse_param_list(p)?,
                     Rule::type_name => return_type = Some(VBType::from_str(p.as_str())),
@@ -709,16 +980,20 @@ fn parse_property_decl(pair: Pair<Rule>) -> ParseResult<PropertyDecl> {
         return_type,
         getter,
         setter,
+        is_default,
+        attributes,
+        span,
+        source_file: None,
     })
 }
 
-fn parse_property_get(pair: Pair<Rule>) -> ParseResult<Vec<Statement>> {
-    let mut body = Vec::new();
+fn parse_property_get(pair: Pair<Rule>) -> ParseResult<Block> {
+    let mut body = Block::new();
     for stmt_pair in pair.into_inner() {
          if stmt_pair.as_rule() == Rule::statement_line {
              for s in stmt_pair.into_inner() {
                  if s.as_rule() != Rule::NEWLINE && s.as_rule() != Rule::EOI {
-                     body.push(parse_statement(s)?);
+                     push_statement(&mut body, s)?;
                  }
              }
          }
@@ -726,16 +1001,16 @@ fn parse_property_get(pair: Pair<Rule>) -> ParseResult<Vec<Statement>> {
     Ok(body)
 }
 
-fn parse_property_set(pair: Pair<Rule>) -> ParseResult<(Parameter, Vec<Statement>)> {
+fn parse_property_set(pair: Pair<Rule>) -> ParseResult<(Parameter, Block)> {
     let mut inner = pair.into_inner();
     let param = parse_parameter(inner.next().unwrap())?; // Set(ByVal value As Type)
     
-    let mut body = Vec::new();
+    let mut body = Block::new();
     for stmt_pair in inner {
          if stmt_pair.as_rule() == Rule::statement_line {
              for s in stmt_pair.into_inner() {
                  if s.as_rule() != Rule::NEWLINE && s.as_rule() != Rule::EOI {
-                     body.push(parse_statement(s)?);
+                     push_statement(&mut body, s)?;
                  }
              }
          }
@@ -744,11 +1019,38 @@ fn parse_property_set(pair: Pair<Rule>) -> ParseResult<(Parameter, Vec<Statement
     Ok((param, body))
 }
 
+fn parse_type_parameter_list(pair: Pair<Rule>) -> Vec<TypeParameter> {
+    let mut type_parameters = Vec::new();
+    for tp in pair.into_inner().filter(|p| p.as_rule() == Rule::type_parameter) {
+        let mut name = Identifier::new("");
+        let mut constraints = Vec::new();
+        for part in tp.into_inner() {
+            match part.as_rule() {
+                Rule::identifier => name = Identifier::new(part.as_str()),
+                Rule::type_constraint => {
+                    let Some(c) = part.into_inner().next() else { continue };
+                    constraints.push(match c.as_rule() {
+                        Rule::class_constraint => TypeConstraint::Class,
+                        Rule::structure_constraint => TypeConstraint::Structure,
+                        Rule::new_constraint => TypeConstraint::New,
+                        _ => TypeConstraint::Type(VBType::from_str(c.as_str())),
+                    });
+                }
+                _ => {}
+            }
+        }
+        type_parameters.push(TypeParameter { name, constraints });
+    }
+    type_parameters
+}
+
 fn parse_param_list(pair: Pair<Rule>) -> ParseResult<Vec<Parameter>> {
     pair.into_inner().map(parse_parameter).collect()
 }
 
 fn parse_parameter(pair: Pair<Rule>) -> ParseResult<Parameter> {
+    let text = pair.as_str();
+    let start = pair.as_span().start();
     let inner = pair.into_inner();
     let mut pass_type = ParameterPassType::ByRef;
     let mut name = Identifier::new("");
@@ -757,9 +1059,12 @@ fn parse_parameter(pair: Pair<Rule>) -> ParseResult<Parameter> {
     let mut default_value = None;
     let mut is_nullable = false;
     let mut is_param_array = false;
+    let mut is_array = false;
+    let mut attributes = Vec::new();
 
     for p in inner {
         match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::pass_type_keyword => {
                 let text = p.as_str().to_lowercase();
                 if text == "byval" {
@@ -777,6 +1082,8 @@ fn parse_parameter(pair: Pair<Rule>) -> ParseResult<Parameter> {
             }
             Rule::identifier => {
                 name = Identifier::new(p.as_str());
+                // `values() As Integer` declares an array, same as `values As Integer()`
+                is_array = text[p.as_span().end() - start..].trim_start().starts_with('(');
             }
             Rule::type_name => param_type = Some(VBType::from_str(p.as_str())),
             Rule::nullable_marker => is_nullable = true,
@@ -784,6 +1091,10 @@ fn parse_parameter(pair: Pair<Rule>) -> ParseResult<Parameter> {
             _ => {}
         }
     }
+    if is_array {
+        let element = param_type.map_or_else(|| "Object".to_string(), |t| t.to_string());
+        param_type = Some(VBType::from_str(&format!("{}()", element)));
+    }
 
     Ok(Parameter {
         pass_type,
@@ -793,6 +1104,7 @@ fn parse_parameter(pair: Pair<Rule>) -> ParseResult<Parameter> {
         default_value,
         is_nullable,
         is_param_array,
+        attributes,
     })
 }
 
@@ -814,10 +1126,12 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
             // .prop1.prop2 = value (inside With block)
             let inner = pair.into_inner();
             let mut members = Vec::new();
+            let mut indices = None;
             let mut value_expr = None;
             for p in inner {
                 match p.as_rule() {
                     Rule::identifier | Rule::member_identifier => members.push(Identifier::new(p.as_str())),
+                    Rule::argument_list => indices = Some(parse_argument_list(p)?),
                     Rule::expression => value_expr = Some(parse_expression(p)?),
                     _ => {}
                 }
@@ -827,25 +1141,23 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                 return Err(ParseError::Custom("dot_assign needs at least one member".to_string()));
             }
             let last = members.pop().unwrap();
-            let mut obj = Expression::WithTarget;
+            let mut obj: Expression = ExprKind::WithTarget.into();
             for m in members {
-                obj = Expression::MemberAccess(Box::new(obj), m);
+                obj = ExprKind::MemberAccess(Box::new(obj), m).into();
             }
-            Ok(Statement::MemberAssignment {
-                object: obj,
-                member: last,
-                value,
-            })
+            Ok(member_assignment(obj, last, indices, value))
         }
         Rule::me_assign_statement => {
             // Me.prop1.prop2 = value
             let mut inner = pair.into_inner();
             let _me = inner.next().unwrap(); // me_keyword
             let mut members = Vec::new();
+            let mut indices = None;
             let mut value_expr = None;
             for p in inner {
                 match p.as_rule() {
                     Rule::identifier | Rule::member_identifier => members.push(Identifier::new(p.as_str())),
+                    Rule::argument_list => indices = Some(parse_argument_list(p)?),
                     Rule::expression => value_expr = Some(parse_expression(p)?),
                     _ => {}
                 }
@@ -855,15 +1167,11 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                 return Err(ParseError::Custom("me_assign_statement needs at least one member".to_string()));
             }
             let last = members.pop().unwrap();
-            let mut obj: Expression = Expression::Me;
+            let mut obj: Expression = ExprKind::Me.into();
             for m in members {
-                obj = Expression::MemberAccess(Box::new(obj), m);
+                obj = ExprKind::MemberAccess(Box::new(obj), m).into();
             }
-            Ok(Statement::MemberAssignment {
-                object: obj,
-                member: last,
-                value,
-            })
+            Ok(member_assignment(obj, last, indices, value))
         }
         Rule::mybase_assign_statement => {
             // MyBase.prop = value
@@ -883,9 +1191,9 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                 return Err(ParseError::Custom("mybase_assign_statement needs at least one member".to_string()));
             }
             let last = members.pop().unwrap();
-            let mut obj: Expression = Expression::MyBase;
+            let mut obj: Expression = ExprKind::MyBase.into();
             for m in members {
-                obj = Expression::MemberAccess(Box::new(obj), m);
+                obj = ExprKind::MemberAccess(Box::new(obj), m).into();
             }
             Ok(Statement::MemberAssignment {
                 object: obj,
@@ -900,38 +1208,16 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
             let lhs_expr = parse_l_value_expression(lhs_pair)?;
             let value_expr = parse_expression(inner.next().unwrap())?;
 
-            match lhs_expr {
-                Expression::Variable(id) => {
-                    Ok(Statement::Assignment {
-                        target: id,
-                        value: value_expr,
-                    })
-                }
-                Expression::MemberAccess(obj, member) => {
-                    Ok(Statement::MemberAssignment {
-                        object: *obj,
-                        member,
-                        value: value_expr,
-                    })
-                }
-                Expression::ArrayAccess(arr, indices) => {
-                    Ok(Statement::ArrayAssignment {
-                        array: arr,
-                        indices,
-                        value: value_expr,
-                    })
-                }
-                Expression::Call(func, args) => {
-                    // Treat function call on LHS as array assignment (e.g. arr(i) = val)
-                    // The AST expects 'array' as identifier and 'indices' as expressions.
-                    Ok(Statement::ArrayAssignment {
-                        array: func,
-                        indices: args,
-                        value: value_expr,
-                    })
-                }
-                _ => Err(ParseError::Custom("Invalid assignment target".to_string())),
-            }
+            // A call on the left (`arr(i) = val`) assigns an array element
+            Statement::assignment(lhs_expr, value_expr)
+                .ok_or_else(|| ParseError::Custom("Invalid assignment target".to_string()))
+        }
+        Rule::xml_attribute_assign_statement => {
+            let mut inner = pair.into_inner();
+            let target = parse_expression(inner.next().unwrap())?;
+            let value = parse_expression(inner.next().unwrap())?;
+            Statement::assignment(target, value)
+                .ok_or_else(|| ParseError::Custom("Invalid assignment target".to_string()))
         }
         Rule::set_statement => {
             let mut inner = pair.into_inner();
@@ -965,8 +1251,8 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
             // Note: CompoundAssignment AST struct is rigid: target: Identifier, members: Vec<Identifier>, indices: Vec<Expression>
             // We need to decompose lhs_expr into these components if possible.
             
-            match lhs_expr {
-                Expression::Variable(id) => {
+            match lhs_expr.kind {
+                ExprKind::Variable(id) => {
                      Ok(Statement::CompoundAssignment {
                         target: id,
                         members: vec![],
@@ -975,7 +1261,7 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                         value,
                     })
                 }
-                Expression::MemberAccess(obj, member) => {
+                ExprKind::MemberAccess(obj, member) => {
                      // Decompose object... handling simple obj.prop or obj.prop1.prop2
                      // This is tricky if obj is complex expression.
                      // The AST Statement::CompoundAssignment handles target + list of members + optional indices at the end.
@@ -987,12 +1273,12 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                      let mut root = None;
                      
                      loop {
-                         match current {
-                             Expression::Variable(id) => {
+                         match current.kind {
+                             ExprKind::Variable(id) => {
                                  root = Some(id);
                                  break;
                              }
-                             Expression::MemberAccess(parent, m) => {
+                             ExprKind::MemberAccess(parent, m) => {
                                  parts.push(m);
                                  current = *parent;
                              }
@@ -1079,6 +1365,10 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
             let value = inner.next().map(parse_expression).transpose()?;
             Ok(Statement::Return(value))
         }
+        Rule::yield_statement => {
+            let expr = pair.into_inner().find(|p| p.as_rule() == Rule::expression).unwrap();
+            Ok(Statement::Yield(parse_expression(expr)?))
+        }
         Rule::call_statement => {
             let mut inner = pair.into_inner();
             let mut first = inner.next().unwrap();
@@ -1154,10 +1444,54 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
             let label = pair.into_inner().next().unwrap().as_str().to_string();
             Ok(Statement::GoTo(label))
         }
+        Rule::gosub_statement => {
+            let label = pair.into_inner().nth(1).unwrap().as_str().to_string();
+            Ok(Statement::GoSub(label))
+        }
+        Rule::on_goto_statement => {
+            let mut inner = pair.into_inner();
+            let selector = parse_expression(inner.next().unwrap())?;
+            let mut gosub = false;
+            let mut labels = Vec::new();
+            for p in inner {
+                match p.as_rule() {
+                    Rule::gosub_keyword => gosub = true,
+                    Rule::identifier => labels.push(p.as_str().to_string()),
+                    _ => {}
+                }
+            }
+            Ok(Statement::OnGoTo { selector, labels, gosub })
+        }
         Rule::label_statement => {
             let label = pair.into_inner().next().unwrap().as_str().to_string();
             Ok(Statement::Label(label))
         }
+        Rule::mid_assign_statement => {
+            let mut parts = pair.into_inner().map(|p| match p.as_rule() {
+                Rule::l_value_expression => parse_l_value_expression(p),
+                _ => parse_expression(p),
+            }).collect::<ParseResult<Vec<_>>>()?;
+            let value = parts.pop().unwrap();
+            let mut parts = parts.into_iter();
+            let target = parts.next().unwrap();
+            let start = parts.next().unwrap();
+            Ok(Statement::MidAssignment { target, start, length: parts.next(), value })
+        }
+        Rule::lset_statement => {
+            let mut inner = pair.into_inner();
+            let right = inner.next().unwrap().as_str().eq_ignore_ascii_case("rset");
+            let target = parse_l_value_expression(inner.next().unwrap())?;
+            let value = parse_expression(inner.next().unwrap())?;
+            Ok(if right { Statement::RSet { target, value } } else { Statement::LSet { target, value } })
+        }
+        Rule::erase_statement => {
+            let arrays = pair.into_inner()
+                .filter(|p| p.as_rule() == Rule::l_value_expression)
+                .map(parse_l_value_expression)
+                .collect::<ParseResult<Vec<_>>>()?;
+            Ok(Statement::Erase(arrays))
+        }
+        Rule::get_file_statement | Rule::put_file_statement => parse_record_statement(pair),
         Rule::on_error_statement => {
             let text = pair.as_str().to_lowercase();
             if text.contains("resume") && text.contains("next") {
@@ -1183,17 +1517,17 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
             }
         }
         // New declarations — parse gracefully as no-op statements for now
-        Rule::interface_decl | Rule::structure_decl |
+        Rule::interface_decl | Rule::structure_decl | Rule::type_decl |
         Rule::event_decl | Rule::delegate_sub_decl | Rule::delegate_function_decl => {
             // These are parsed by the grammar but the runtime doesn't execute them yet.
             // Return an expression statement with Nothing to avoid breaking parsing.
-            Ok(Statement::ExpressionStatement(Expression::Nothing))
+            Ok(Statement::ExpressionStatement(ExprKind::Nothing.into()))
         }
         Rule::namespace_decl => {
             // Namespace encountered as a statement (e.g. inside module or at top level).
             // We shouldn't get here normally — it's handled as a declaration in parse_program.
             // But if we do, just return Nothing to not break.
-            Ok(Statement::ExpressionStatement(Expression::Nothing))
+            Ok(Statement::ExpressionStatement(ExprKind::Nothing.into()))
         }
         Rule::synclock_statement => parse_synclock_statement(pair),
         _ => Err(ParseError::UnexpectedRule(pair.as_rule())),
@@ -1203,7 +1537,7 @@ fn parse_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
 fn parse_if_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let mut inner = pair.into_inner();
     let condition = parse_expression(inner.next().unwrap())?;
-    let mut then_branch = Vec::new();
+    let mut then_branch = Block::new();
     let mut elseif_branches = Vec::new();
     let mut else_branch = None;
 
@@ -1216,7 +1550,7 @@ fn parse_if_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
             }
             Rule::elseif_block => {
                 let mut elseif_condition = None;
-                let mut elseif_body = Vec::new();
+                let mut elseif_body = Block::new();
                 for p_inner in p.into_inner() {
                     match p_inner.as_rule() {
                         Rule::expression => elseif_condition = Some(parse_expression(p_inner)?),
@@ -1229,7 +1563,7 @@ fn parse_if_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                 }
             }
             Rule::else_block => {
-                let mut body = Vec::new();
+                let mut body = Block::new();
                 for p_inner in p.into_inner() {
                     if p_inner.as_rule() == Rule::if_body {
                         body = parse_block(p_inner)?;
@@ -1251,8 +1585,15 @@ fn parse_if_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     })
 }
 
-fn parse_block(pair: Pair<Rule>) -> ParseResult<Vec<Statement>> {
-    let mut statements = Vec::new();
+/// Parse a statement pair and append it to `block` along with its span.
+fn push_statement(block: &mut Block, pair: Pair<Rule>) -> ParseResult<()> {
+    let span = Span::from_pest(pair.as_span());
+    block.push_spanned(parse_statement(pair)?, span);
+    Ok(())
+}
+
+fn parse_block(pair: Pair<Rule>) -> ParseResult<Block> {
+    let mut statements = Block::new();
     for p in pair.into_inner() {
         match p.as_rule() {
             Rule::statement_line => {
@@ -1260,11 +1601,11 @@ fn parse_block(pair: Pair<Rule>) -> ParseResult<Vec<Statement>> {
                     if stmt_pair.as_rule() == Rule::NEWLINE || stmt_pair.as_rule() == Rule::EOI {
                         continue;
                     }
-                    statements.push(parse_statement(stmt_pair)?);
+                    push_statement(&mut statements, stmt_pair)?;
                 }
             }
             Rule::statement => {
-                statements.push(parse_statement(p)?);
+                push_statement(&mut statements, p)?;
             }
             Rule::NEWLINE | Rule::EOI => {}
             _ => {}
@@ -1277,16 +1618,17 @@ fn parse_for_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let mut inner = pair.into_inner();
     let variable = Identifier::new(inner.next().unwrap().as_str());
 
-    // Skip optional 'As type_name'
+    let mut var_type = None;
     let mut next = inner.next().unwrap();
     if next.as_rule() == Rule::type_name {
+        var_type = Some(VBType::from_str(next.as_str()));
         next = inner.next().unwrap();
     }
     let start = parse_expression(next)?;
     let end = parse_expression(inner.next().unwrap())?;
 
     let mut step = None;
-    let mut body = Vec::new();
+    let mut body = Block::new();
 
     for p in inner {
         match p.as_rule() {
@@ -1296,7 +1638,7 @@ fn parse_for_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                     if stmt_pair.as_rule() == Rule::NEWLINE || stmt_pair.as_rule() == Rule::EOI {
                         continue;
                     }
-                    body.push(parse_statement(stmt_pair)?);
+                    push_statement(&mut body, stmt_pair)?;
                 }
             }
             Rule::NEWLINE | Rule::for_end => {}
@@ -1306,6 +1648,7 @@ fn parse_for_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
 
     Ok(Statement::For {
         variable,
+        var_type,
         start,
         end,
         step,
@@ -1316,7 +1659,7 @@ fn parse_for_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
 fn parse_while_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let mut inner = pair.into_inner();
     let condition = parse_expression(inner.next().unwrap())?;
-    let mut body = Vec::new();
+    let mut body = Block::new();
 
     for p in inner {
         match p.as_rule() {
@@ -1325,7 +1668,7 @@ fn parse_while_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                     if stmt_pair.as_rule() == Rule::NEWLINE || stmt_pair.as_rule() == Rule::EOI {
                         continue;
                     }
-                    body.push(parse_statement(stmt_pair)?);
+                    push_statement(&mut body, stmt_pair)?;
                 }
             }
             Rule::NEWLINE | Rule::while_end => {}
@@ -1340,7 +1683,7 @@ fn parse_do_loop_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let inner = pair.into_inner();
     let mut pre_condition = None;
     let mut post_condition = None;
-    let mut body = Vec::new();
+    let mut body = Block::new();
     let mut current_loop_type = LoopConditionType::While;
 
     for p in inner {
@@ -1360,7 +1703,7 @@ fn parse_do_loop_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                     if stmt_pair.as_rule() == Rule::NEWLINE || stmt_pair.as_rule() == Rule::EOI {
                         continue;
                     }
-                    body.push(parse_statement(stmt_pair)?);
+                    push_statement(&mut body, stmt_pair)?;
                 }
             }
             Rule::do_end => {
@@ -1388,7 +1731,52 @@ fn parse_do_loop_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     })
 }
 
+/// The operand inside a run of single-child precedence levels. Each level
+/// from `expression` down to `postfix` wraps even a lone operand in a pair of
+/// its own; stepping through them here rather than recursing once per level
+/// keeps nested arguments and embedded expressions from exhausting the stack.
+fn lone_operand(mut pair: Pair<Rule>) -> Pair<Rule> {
+    while matches!(
+        pair.as_rule(),
+        Rule::expression | Rule::logical_xor | Rule::logical_or | Rule::logical_and | Rule::not_condition
+            | Rule::equality | Rule::comparison | Rule::bit_shift | Rule::additive | Rule::multiplicative
+            | Rule::unary | Rule::exponent | Rule::postfix
+    ) {
+        let mut inner = pair.clone().into_inner();
+        match (inner.next(), inner.next()) {
+            (Some(only), None) => pair = only,
+            _ => break,
+        }
+    }
+    pair
+}
+
 fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
+    let span = expression_span(&pair);
+    let mut expr = parse_expression_node(pair)?;
+    // Nodes built from part of the pair have placed themselves already
+    if expr.span.is_unknown() {
+        expr.span = span;
+    }
+    Ok(expr)
+}
+
+thread_local! {
+    /// Set while the holes of an interpolated string are parsed, on their
+    /// own: positions in that text aren't positions in the file.
+    static UNPLACED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// The span of an expression parsed from `pair`.
+fn expression_span(pair: &Pair<Rule>) -> Span {
+    if UNPLACED.get() {
+        return Span::default();
+    }
+    Span::from_pest(pair.as_span())
+}
+
+fn parse_expression_node(pair: Pair<Rule>) -> ParseResult<Expression> {
+    let pair = lone_operand(pair);
     match pair.as_rule() {
         Rule::expression | Rule::logical_xor | Rule::logical_or | Rule::logical_and |
         Rule::equality | Rule::comparison | Rule::bit_shift | Rule::additive |
@@ -1401,7 +1789,7 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             let first = inner.next().unwrap();
             if first.as_rule() == Rule::not_op {
                 let operand = parse_expression(inner.next().unwrap())?;
-                Ok(Expression::Not(Box::new(operand)))
+                Ok(ExprKind::Not(Box::new(operand)).into())
             } else {
                 parse_expression(first)
             }
@@ -1413,10 +1801,10 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             let mut inner = pair.into_inner();
             let expr = parse_expression(inner.next().unwrap())?;
             let type_name = inner.next().unwrap().as_str().trim().to_string();
-            Ok(Expression::TypeOf {
+            Ok(ExprKind::TypeOf {
                 expr: Box::new(expr),
                 type_name,
-            })
+            }.into())
         }
 
         Rule::unary => {
@@ -1426,20 +1814,24 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             parse_postfix_expression(pair)
         }
         Rule::call_expression => {
-            let mut inner = pair.into_inner();
-            let name = Identifier::new(inner.next().unwrap().as_str());
+            let mut inner = pair.into_inner().peekable();
+            let mut name = inner.next().unwrap().as_str().to_string();
+            // Explicit type arguments stay on the name, as for New: First(Of Integer)
+            if let Some(suffix) = inner.next_if(|p| p.as_rule() == Rule::generic_suffix) {
+                name.push_str(suffix.as_str());
+            }
             let arguments = inner.next()
                 .map(parse_argument_list)
                 .transpose()?
                 .unwrap_or_default();
 
-            Ok(Expression::Call(name, arguments))
+            Ok(ExprKind::Call(Identifier::new(&name), arguments).into())
         }
         Rule::member_call => {
             let mut inner = pair.into_inner();
             // First child is always the root identifier
             let first = inner.next().unwrap();
-            let mut expr = Expression::Variable(Identifier::new(first.as_str()));
+            let mut expr = Expression::new(ExprKind::Variable(Identifier::new(first.as_str())), expression_span(&first));
 
             // Remaining children are member_chain segments
             for chain in inner {
@@ -1450,17 +1842,19 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
         }
         Rule::member_access => {
             let mut inner = pair.into_inner();
-            let mut expr = Expression::Variable(Identifier::new(inner.next().unwrap().as_str()));
+            let root = inner.next().unwrap();
+            let mut expr = Expression::new(ExprKind::Variable(Identifier::new(root.as_str())), expression_span(&root));
 
             for p in inner {
-                expr = Expression::MemberAccess(Box::new(expr), Identifier::new(p.as_str()));
+                let span = expr.span.to(expression_span(&p));
+                expr = Expression::new(ExprKind::MemberAccess(Box::new(expr), Identifier::new(p.as_str())), span);
             }
 
             Ok(expr)
         }
         Rule::query_expression => parse_query_expression(pair),
         Rule::xml_literal => parse_xml_literal(pair),
-        Rule::identifier => Ok(Expression::Variable(Identifier::new(pair.as_str()))),
+        Rule::identifier => Ok(ExprKind::Variable(Identifier::new(pair.as_str())).into()),
         Rule::cast_expression => {
             let text = pair.as_str();
             let kind = if text[..5].eq_ignore_ascii_case("CType") {
@@ -1473,11 +1867,11 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             let mut inner = pair.into_inner();
             let expr = parse_expression(inner.next().unwrap())?;
             let type_name = inner.next().unwrap().as_str().to_string();
-            Ok(Expression::Cast {
+            Ok(ExprKind::Cast {
                 kind,
                 expr: Box::new(expr),
                 target_type: type_name,
-            })
+            }.into())
         }
         Rule::cast_member_call => {
             let mut inner = pair.into_inner();
@@ -1512,7 +1906,7 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
                     }
                     // Flush text so far
                     if !current_text.is_empty() {
-                        parts.push(Expression::StringLiteral(current_text.clone()));
+                        parts.push(ExprKind::StringLiteral(current_text.clone()).into());
                         current_text.clear();
                     }
                     // Collect expression until matching }
@@ -1525,7 +1919,12 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
                     }
                     // Parse the expression text as a VB expression
                     let expr_code = format!("Sub _Tmp()\nDim _x = {}\nEnd Sub", expr_text);
-                    match crate::parse_program(&expr_code) {
+                    // Positions in this text aren't the file's, so the hole is
+                    // placed at the string holding it
+                    let was_unplaced = UNPLACED.replace(true);
+                    let parsed = crate::parse_program(&expr_code);
+                    UNPLACED.set(was_unplaced);
+                    match parsed {
                         Ok(program) => {
                             // Extract the expression from Dim _x = <expr>
                             let mut found = false;
@@ -1543,12 +1942,12 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
                                 }
                             }
                             if !found {
-                                parts.push(Expression::Variable(Identifier::new(expr_text.trim())));
+                                parts.push(ExprKind::Variable(Identifier::new(expr_text.trim())).into());
                             }
                         }
                         Err(_) => {
                             // Fallback: treat as simple variable reference
-                            parts.push(Expression::Variable(Identifier::new(expr_text.trim())));
+                            parts.push(ExprKind::Variable(Identifier::new(expr_text.trim())).into());
                         }
                     }
                 } else if ch == '}' {
@@ -1563,21 +1962,21 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             }
             // Flush remaining text
             if !current_text.is_empty() {
-                parts.push(Expression::StringLiteral(current_text));
+                parts.push(ExprKind::StringLiteral(current_text).into());
             }
             
             // Build concatenation chain
             if parts.is_empty() {
-                Ok(Expression::StringLiteral(String::new()))
+                Ok(ExprKind::StringLiteral(String::new()).into())
             } else if parts.len() == 1 {
                 Ok(parts.into_iter().next().unwrap())
             } else {
                 let mut result = parts.remove(0);
                 for part in parts {
-                    result = Expression::Concatenate(
+                    result = ExprKind::Concatenate(
                         Box::new(result),
                         Box::new(part),
-                    );
+                    ).into();
                 }
                 Ok(result)
             }
@@ -1586,20 +1985,11 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             let s = pair.as_str();
             // Strip outer quotes, then unescape VB-style doubled quotes ("" -> ")
             let inner = s[1..s.len()-1].replace("\"\"", "\"");
-            Ok(Expression::StringLiteral(inner))
-        }
-        Rule::numeric_literal => {
-            let s = pair.as_str();
-            // Strip type suffixes: F, D, L, R, S, US, UI, UL, !, #, @, %
-            let s = s.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '!' || c == '#' || c == '@' || c == '%');
-            if s.contains('.') {
-                Ok(Expression::DoubleLiteral(s.parse().unwrap_or(0.0)))
-            } else {
-                Ok(Expression::IntegerLiteral(s.parse().unwrap_or(0)))
-            }
+            Ok(ExprKind::StringLiteral(inner).into())
         }
+        Rule::numeric_literal => parse_numeric_literal(pair.as_str()).map(Expression::from),
         Rule::boolean_literal => {
-            Ok(Expression::BooleanLiteral(pair.as_str().to_lowercase() == "true"))
+            Ok(ExprKind::BooleanLiteral(pair.as_str().to_lowercase() == "true").into())
         }
         Rule::array_literal => {
             parse_array_literal(pair)
@@ -1608,9 +1998,41 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             let s = pair.as_str();
             // Strip the surrounding # delimiters
             let inner = s[1..s.len()-1].trim().to_string();
-            Ok(Expression::DateLiteral(inner))
+            Ok(ExprKind::DateLiteral(inner).into())
+        }
+        Rule::nothing_literal => Ok(ExprKind::Nothing.into()),
+        Rule::anonymous_new => {
+            let mut members = Vec::new();
+            for member in pair.into_inner().filter(|p| p.as_rule() == Rule::anonymous_member) {
+                let p = member.into_inner().find(|p| p.as_rule() != Rule::anonymous_key).unwrap();
+                if p.as_rule() == Rule::member_initializer {
+                    let mut mi_inner = p.into_inner();
+                    let name = mi_inner.next().unwrap().as_str().to_string();
+                    members.push((name, parse_expression(mi_inner.next().unwrap())?));
+                } else {
+                    let value = parse_expression(p)?;
+                    let name = inferred_member_name(&value).ok_or_else(|| ParseError::Custom(
+                        "Anonymous type member name can be inferred only from a simple or qualified name".to_string()))?;
+                    members.push((name, value));
+                }
+            }
+            Ok(ExprKind::NewAnonymous(members).into())
+        }
+        Rule::named_argument => {
+            let mut inner = pair.into_inner();
+            let name = Identifier::new(inner.next().unwrap().as_str());
+            Ok(ExprKind::NamedArgument(name, Box::new(parse_expression(inner.next().unwrap())?)).into())
+        }
+        Rule::tuple_literal => {
+            let mut elements = Vec::new();
+            for element in pair.into_inner() {
+                let mut inner: Vec<_> = element.into_inner().collect();
+                let value = parse_expression(inner.pop().unwrap())?;
+                let name = inner.pop().map(|p| Identifier::new(p.as_str()).0);
+                elements.push((name, value));
+            }
+            Ok(ExprKind::TupleLiteral(tuple_element_names(elements)).into())
         }
-        Rule::nothing_literal => Ok(Expression::Nothing),
         Rule::new_expression => {
             let mut inner = pair.into_inner();
             let id_pair = inner.next().unwrap();
@@ -1639,11 +2061,11 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
                             .filter(|e| e.as_rule() == Rule::expression)
                             .map(|e| parse_expression(e))
                             .collect::<Result<Vec<_>, _>>()?;
-                        return Ok(Expression::NewFromInitializer(
+                        return Ok(ExprKind::NewFromInitializer(
                             Identifier::new(&class_name),
                             args,
                             elements,
-                        ));
+                        ).into());
                     }
                     Rule::with_initializer => {
                         // New Type() With { .Prop = expr, ... }
@@ -1655,20 +2077,20 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
                             let prop_expr = parse_expression(mi_inner.next().unwrap())?;
                             members.push((prop_name, prop_expr));
                         }
-                        return Ok(Expression::NewWithInitializer(
+                        return Ok(ExprKind::NewWithInitializer(
                             Identifier::new(&class_name),
                             args,
                             members,
-                        ));
+                        ).into());
                     }
                     _ => {}
                 }
             }
             // If there's an array initializer, return an ArrayLiteral instead of New
             if let Some(elements) = array_init {
-                Ok(Expression::ArrayLiteral(elements))
+                Ok(ExprKind::ArrayLiteral(elements).into())
             } else {
-                Ok(Expression::New(Identifier::new(&class_name), args))
+                Ok(ExprKind::New(Identifier::new(&class_name), args).into())
             }
         }
         Rule::if_expression => {
@@ -1676,11 +2098,11 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             let first = parse_expression(inner.next().unwrap())?;
             let second = parse_expression(inner.next().unwrap())?;
             let third = inner.next().map(|p| parse_expression(p)).transpose()?;
-            Ok(Expression::IfExpression(
+            Ok(ExprKind::IfExpression(
                 Box::new(first),
                 Box::new(second),
                 third.map(Box::new),
-            ))
+            ).into())
         }
         Rule::addressof_expr => {
             let inner = pair.into_inner();
@@ -1690,10 +2112,10 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
                     name = p.as_str().to_string();
                 }
             }
-            Ok(Expression::AddressOf(name))
+            Ok(ExprKind::AddressOf(name).into())
         }
         Rule::me_keyword => {
-            Ok(Expression::Me)
+            Ok(ExprKind::Me.into())
         }
         Rule::dot_call_statement => {
             // .Method(args) or .obj.Method(args) inside With block
@@ -1711,19 +2133,19 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
                 return Err(ParseError::Custom("dot_call needs at least one identifier".to_string()));
             }
             let method_name = Identifier::new(identifiers.last().unwrap().clone());
-            let mut expr = Expression::WithTarget;
+            let mut expr: Expression = ExprKind::WithTarget.into();
             for i in 0..identifiers.len() - 1 {
-                expr = Expression::MemberAccess(Box::new(expr), Identifier::new(identifiers[i].clone()));
+                expr = ExprKind::MemberAccess(Box::new(expr), Identifier::new(identifiers[i].clone())).into();
             }
-            Ok(Expression::MethodCall(Box::new(expr), method_name, arguments))
+            Ok(ExprKind::MethodCall(Box::new(expr), method_name, arguments).into())
         }
         Rule::dot_member_access => {
             // .prop or .obj.prop inside With block
             let inner = pair.into_inner();
-            let mut expr = Expression::WithTarget;
+            let mut expr: Expression = ExprKind::WithTarget.into();
             for p in inner {
                 if p.as_rule() == Rule::identifier || p.as_rule() == Rule::member_identifier {
-                    expr = Expression::MemberAccess(Box::new(expr), Identifier::new(p.as_str()));
+                    expr = ExprKind::MemberAccess(Box::new(expr), Identifier::new(p.as_str())).into();
                 }
             }
             Ok(expr)
@@ -1731,10 +2153,10 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
         Rule::me_member_access => {
             let mut inner = pair.into_inner();
             let _me = inner.next().unwrap(); // me_keyword
-            let mut expr = Expression::Me;
+            let mut expr: Expression = ExprKind::Me.into();
             for p in inner {
                 if p.as_rule() == Rule::identifier || p.as_rule() == Rule::member_identifier {
-                    expr = Expression::MemberAccess(Box::new(expr), Identifier::new(p.as_str()));
+                    expr = ExprKind::MemberAccess(Box::new(expr), Identifier::new(p.as_str())).into();
                 }
             }
             Ok(expr)
@@ -1743,10 +2165,10 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             // MyBase.Property
             let mut inner = pair.into_inner();
             let _mybase = inner.next().unwrap(); // mybase_keyword
-            let mut expr = Expression::MyBase;
+            let mut expr: Expression = ExprKind::MyBase.into();
             for p in inner {
                 if p.as_rule() == Rule::identifier || p.as_rule() == Rule::member_identifier {
-                    expr = Expression::MemberAccess(Box::new(expr), Identifier::new(p.as_str()));
+                    expr = ExprKind::MemberAccess(Box::new(expr), Identifier::new(p.as_str())).into();
                 }
             }
             Ok(expr)
@@ -1772,12 +2194,12 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             let method_name = Identifier::new(identifiers.last().unwrap().clone());
 
             // Build object expression: Me.a.b... (all except last)
-            let mut expr = Expression::Me;
+            let mut expr: Expression = ExprKind::Me.into();
             for i in 0..identifiers.len() - 1 {
-                expr = Expression::MemberAccess(Box::new(expr), Identifier::new(identifiers[i].clone()));
+                expr = ExprKind::MemberAccess(Box::new(expr), Identifier::new(identifiers[i].clone())).into();
             }
 
-            Ok(Expression::MethodCall(Box::new(expr), method_name, arguments))
+            Ok(ExprKind::MethodCall(Box::new(expr), method_name, arguments).into())
         }
         Rule::mybase_member_call => {
             // MyBase.Method()
@@ -1798,12 +2220,12 @@ fn parse_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
             }
 
             let method_name = Identifier::new(identifiers.last().unwrap().clone());
-            let mut expr = Expression::MyBase;
+            let mut expr: Expression = ExprKind::MyBase.into();
             for i in 0..identifiers.len() - 1 {
-                expr = Expression::MemberAccess(Box::new(expr), Identifier::new(identifiers[i].clone()));
+                expr = ExprKind::MemberAccess(Box::new(expr), Identifier::new(identifiers[i].clone())).into();
             }
 
-            Ok(Expression::MethodCall(Box::new(expr), method_name, arguments))
+            Ok(ExprKind::MethodCall(Box::new(expr), method_name, arguments).into())
         }
         _ => Err(ParseError::UnexpectedRule(pair.as_rule())),
     }
@@ -1850,7 +2272,8 @@ fn parse_binary_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
 
         let right_pair = inner.next().unwrap();
         let right = parse_expression(right_pair)?;
-        left = Expression::binary(op, left, right);
+        let span = left.span.to(right.span);
+        left = Expression::new(ExprKind::binary(op, left, right), span);
     }
 
     Ok(left)
@@ -1863,15 +2286,15 @@ fn parse_unary_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
     match first.as_rule() {
         Rule::not_op => {
             let operand = parse_expression(inner.next().unwrap())?;
-            Ok(Expression::Not(Box::new(operand)))
+            Ok(ExprKind::Not(Box::new(operand)).into())
         }
         Rule::neg_op => {
             let operand = parse_expression(inner.next().unwrap())?;
-            Ok(Expression::Negate(Box::new(operand)))
+            Ok(ExprKind::Negate(Box::new(operand)).into())
         }
         Rule::await_op => {
             let operand = parse_expression(inner.next().unwrap())?;
-            Ok(Expression::Await(Box::new(operand)))
+            Ok(ExprKind::Await(Box::new(operand)).into())
         }
         Rule::postfix => {
             parse_postfix_expression(first)
@@ -1897,25 +2320,58 @@ fn parse_postfix_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
 }
 
 fn parse_member_chain_node(chain: Pair<Rule>, expr: Expression) -> ParseResult<Expression> {
+    // From the start of the object to the end of this member
+    let span = expr.span.to(expression_span(&chain));
+    let mut node = parse_member_chain_kind(chain, expr)?;
+    if node.span.is_unknown() {
+        node.span = span;
+    }
+    Ok(node)
+}
+
+fn parse_member_chain_kind(chain: Pair<Rule>, expr: Expression) -> ParseResult<Expression> {
     match chain.as_rule() {
         Rule::member_chain_call => {
-            let mut chain_inner = chain.into_inner();
-            let name = chain_inner.next().unwrap().as_str();
+            let mut chain_inner = chain.into_inner().peekable();
+            let mut name = chain_inner.next().unwrap().as_str().to_string();
+            if let Some(suffix) = chain_inner.next_if(|p| p.as_rule() == Rule::generic_suffix) {
+                name.push_str(suffix.as_str());
+            }
             let arguments = if let Some(arg_list) = chain_inner.next() {
                 parse_argument_list(arg_list)?
             } else {
                 vec![]
             };
-            Ok(Expression::MethodCall(Box::new(expr), Identifier::new(name), arguments))
+            Ok(ExprKind::MethodCall(Box::new(expr), Identifier::new(&name), arguments).into())
         }
         Rule::member_chain_access => {
             let name = chain.into_inner().next().unwrap().as_str();
-            Ok(Expression::MemberAccess(Box::new(expr), Identifier::new(name)))
+            Ok(ExprKind::MemberAccess(Box::new(expr), Identifier::new(name)).into())
         }
         Rule::member_chain => {
             let inner_chain = chain.into_inner().next().unwrap();
             parse_member_chain_node(inner_chain, expr)
         }
+        Rule::xml_axis => {
+            use crate::ast::xml::XmlAxis;
+            let axis_pair = chain.into_inner().next().unwrap();
+            let axis = match axis_pair.as_rule() {
+                Rule::xml_descendant_axis => XmlAxis::Descendant,
+                Rule::xml_child_axis => XmlAxis::Child,
+                _ => XmlAxis::Attribute,
+            };
+            let mut inner = axis_pair.into_inner();
+            let name = parse_xml_name(inner.next().unwrap());
+            let expr: Expression = ExprKind::XmlAxis(Box::new(expr), axis, name).into();
+            match inner.next() {
+                // doc...<item>(1) is the second item, or Nothing
+                Some(index) => {
+                    let index = parse_expression(index.into_inner().next().unwrap())?;
+                    Ok(ExprKind::MethodCall(Box::new(expr), Identifier::new("ElementAtOrDefault"), vec![index]).into())
+                }
+                None => Ok(expr),
+            }
+        }
         _ => Ok(expr),
     }
 }
@@ -1927,7 +2383,7 @@ fn parse_argument_list(pair: Pair<Rule>) -> ParseResult<Vec<Expression>> {
 fn parse_try_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let inner = pair.into_inner();
     
-    let mut body = Vec::new();
+    let mut body = Block::new();
     let mut catches = Vec::new();
     let mut finally = None;
     
@@ -1961,7 +2417,7 @@ fn parse_catch_block(pair: Pair<Rule>) -> ParseResult<CatchBlock> {
     let inner = pair.into_inner();
     let mut variable = None;
     let mut when_clause = None;
-    let mut body = Vec::new();
+    let mut body = Block::new();
 
     for p in inner {
         match p.as_rule() {
@@ -2022,26 +2478,27 @@ fn parse_lambda_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
         Rule::expression => LambdaBody::Expression(Box::new(parse_expression(next_pair)?)),
         Rule::NEWLINE => {
             // Multiline block
-            let mut body_stmts = Vec::new();
+            let mut body_stmts = Block::new();
             for item in inner {
                 match item.as_rule() {
                     Rule::statement_line => {
                         for stmt_pair in item.into_inner() {
                             if stmt_pair.as_rule() != Rule::NEWLINE && stmt_pair.as_rule() != Rule::EOI {
-                                body_stmts.push(parse_statement(stmt_pair)?);
+                                push_statement(&mut body_stmts, stmt_pair)?;
                             }
                         }
                     }
                     _ => {
                         // try_parse_declaration or other rules that might appear
+                        let span = Span::from_pest(item.as_span());
                         if let Some(decl) = try_parse_declaration(item.clone())? {
                             match decl {
                                 Declaration::Variable(vars) => {
                                     for v in vars {
-                                        body_stmts.push(Statement::Dim(vec![v]));
+                                        body_stmts.push_spanned(Statement::Dim(vec![v]), span);
                                     }
                                 }
-                                Declaration::Constant(c) => body_stmts.push(Statement::Const(c)),
+                                Declaration::Constant(c) => body_stmts.push_spanned(Statement::Const(c), span),
                                 _ => {}
                             }
                         }
@@ -2057,19 +2514,19 @@ fn parse_lambda_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
         }
     };
 
-    Ok(Expression::Lambda {
+    Ok(ExprKind::Lambda {
         params,
         body: Box::new(body),
-    })
+    }.into())
 }
 
-fn parse_block_body(pair: Pair<Rule>) -> ParseResult<Vec<Statement>> {
-    let mut body = Vec::new();
+fn parse_block_body(pair: Pair<Rule>) -> ParseResult<Block> {
+    let mut body = Block::new();
     for stmt_pair in pair.into_inner() {
          if stmt_pair.as_rule() == Rule::statement_line {
               for s in stmt_pair.into_inner() {
                    if s.as_rule() != Rule::NEWLINE && s.as_rule() != Rule::EOI {
-                        body.push(parse_statement(s)?);
+                        push_statement(&mut body, s)?;
                    }
               }
          }
@@ -2080,12 +2537,13 @@ fn parse_block_body(pair: Pair<Rule>) -> ParseResult<Vec<Statement>> {
 fn parse_for_each_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let mut inner = pair.into_inner();
     let variable = Identifier::new(inner.next().unwrap().as_str());
+    let mut var_type = None;
     let mut collection = None;
-    let mut body = Vec::new();
+    let mut body = Block::new();
 
     for p in inner {
         match p.as_rule() {
-            Rule::type_name => {} // Skip "As Type" — we don't store it in ForEach AST
+            Rule::type_name => var_type = Some(VBType::from_str(p.as_str())),
             Rule::expression => {
                 if collection.is_none() {
                     collection = Some(parse_expression(p)?);
@@ -2094,7 +2552,7 @@ fn parse_for_each_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
             Rule::statement_line => {
                 for stmt_pair in p.into_inner() {
                     if stmt_pair.as_rule() != Rule::NEWLINE && stmt_pair.as_rule() != Rule::EOI {
-                        body.push(parse_statement(stmt_pair)?);
+                        push_statement(&mut body, stmt_pair)?;
                     }
                 }
             }
@@ -2105,6 +2563,7 @@ fn parse_for_each_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
 
     Ok(Statement::ForEach {
         variable,
+        var_type,
         collection: collection.ok_or_else(|| ParseError::Custom("For Each missing collection".to_string()))?,
         body,
     })
@@ -2113,14 +2572,14 @@ fn parse_for_each_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
 fn parse_with_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let mut inner = pair.into_inner();
     let object = parse_expression(inner.next().unwrap())?;
-    let mut body = Vec::new();
+    let mut body = Block::new();
 
     for p in inner {
         match p.as_rule() {
             Rule::statement_line => {
                 for stmt_pair in p.into_inner() {
                     if stmt_pair.as_rule() != Rule::NEWLINE && stmt_pair.as_rule() != Rule::EOI {
-                        body.push(parse_statement(stmt_pair)?);
+                        push_statement(&mut body, stmt_pair)?;
                     }
                 }
             }
@@ -2166,13 +2625,13 @@ fn parse_using_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let resource = resource_expr.ok_or_else(|| ParseError::Custom("Using statement missing resource expression".to_string()))?;
     
     // Parse body statements
-    let mut body = Vec::new();
+    let mut body = Block::new();
     for p in remaining.iter().skip(body_start_idx) {
         match p.as_rule() {
             Rule::statement_line => {
                 for stmt_pair in p.clone().into_inner() {
                     if stmt_pair.as_rule() != Rule::NEWLINE && stmt_pair.as_rule() != Rule::EOI {
-                        body.push(parse_statement(stmt_pair)?);
+                        push_statement(&mut body, stmt_pair)?;
                     }
                 }
             }
@@ -2185,13 +2644,16 @@ fn parse_using_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
 }
 
 fn parse_enum_decl(pair: Pair<Rule>) -> ParseResult<EnumDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
     let mut members = Vec::new();
+    let mut attributes = Vec::new();
 
     for p in inner {
         match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::identifier => {
                 let text = p.as_str().to_lowercase();
                 match text.as_str() {
@@ -2216,7 +2678,7 @@ fn parse_enum_decl(pair: Pair<Rule>) -> ParseResult<EnumDecl> {
         }
     }
 
-    Ok(EnumDecl { visibility, name, members })
+    Ok(EnumDecl { visibility, name, members, attributes, span })
 }
 
 fn parse_single_line_if(pair: Pair<Rule>) -> ParseResult<Statement> {
@@ -2225,16 +2687,16 @@ fn parse_single_line_if(pair: Pair<Rule>) -> ParseResult<Statement> {
 
     // Parse then body
     let then_body_pair = inner.next().unwrap(); // single_line_then_body
-    let mut then_branch = Vec::new();
+    let mut then_branch = Block::new();
     for stmt_pair in then_body_pair.into_inner() {
-        then_branch.push(parse_statement(stmt_pair)?);
+        push_statement(&mut then_branch, stmt_pair)?;
     }
 
     // Parse optional else body
     let else_branch = if let Some(else_body_pair) = inner.next() {
-        let mut else_stmts = Vec::new();
+        let mut else_stmts = Block::new();
         for stmt_pair in else_body_pair.into_inner() {
-            else_stmts.push(parse_statement(stmt_pair)?);
+            push_statement(&mut else_stmts, stmt_pair)?;
         }
         Some(else_stmts)
     } else {
@@ -2250,6 +2712,7 @@ fn parse_single_line_if(pair: Pair<Rule>) -> ParseResult<Statement> {
 }
 
 fn parse_field_decl(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
+    let span = Span::from_pest(pair.as_span());
     let mut field_name = Identifier::new("");
     let mut field_type = None;
     let mut field_init = None;
@@ -2258,9 +2721,11 @@ fn parse_field_decl(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
     let mut ctor_args: Vec<Expression> = Vec::new();
     
     let mut is_with_events = false;
+    let mut attributes = Vec::new();
     
     for fp in pair.into_inner() {
         match fp.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(fp)?),
             Rule::withevents_keyword => { is_with_events = true; } 
             Rule::visibility_modifier | Rule::partial_keyword => {} // modifiers handled by caller
             Rule::dim_new_keyword => { is_new = true; }
@@ -2274,7 +2739,7 @@ fn parse_field_decl(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
             }
             Rule::argument_list => {
                 for arg_pair in fp.into_inner() {
-                    if arg_pair.as_rule() == Rule::expression {
+                    if matches!(arg_pair.as_rule(), Rule::expression | Rule::named_argument) {
                         ctor_args.push(parse_expression(arg_pair)?);
                     }
                 }
@@ -2288,7 +2753,7 @@ fn parse_field_decl(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
     // Handle "As New Type" syntax
     if is_new && field_init.is_none() {
         if let Some(t) = &field_type {
-            field_init = Some(Expression::New(Identifier::new(t.to_string()), ctor_args));
+            field_init = Some(ExprKind::New(Identifier::new(t.to_string()), ctor_args).into());
         }
     }
     
@@ -2298,6 +2763,8 @@ fn parse_field_decl(pair: Pair<Rule>) -> ParseResult<VariableDecl> {
         array_bounds: field_bounds,
         initializer: field_init,
         with_events: is_with_events,
+        attributes,
+        span,
     })
 }
 
@@ -2314,7 +2781,8 @@ fn parse_open_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
         _ => return Err(ParseError::Custom(format!("Unknown file mode: {}", mode_pair.as_str()))),
     };
     let file_number = parse_expression(inner.next().unwrap())?;
-    Ok(Statement::Open { file_path, mode, file_number })
+    let record_length = inner.next().map(parse_expression).transpose()?;
+    Ok(Statement::Open { file_path, mode, file_number, record_length })
 }
 
 fn parse_close_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
@@ -2362,6 +2830,21 @@ fn parse_line_input_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let variable = Identifier::new(inner.next().unwrap().as_str());
     Ok(Statement::LineInput { file_number, variable })
 }
+
+/// `Get #1, [position], target` or `Put #1, [position], value`.
+fn parse_record_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
+    let is_get = pair.as_rule() == Rule::get_file_statement;
+    let mut inner = pair.into_inner();
+    let file_number = parse_expression(inner.next().unwrap())?;
+    let position = inner.next().unwrap().into_inner().next().map(parse_expression).transpose()?;
+    let data = inner.next().unwrap();
+    Ok(if is_get {
+        Statement::GetFile { file_number, position, target: parse_l_value_expression(data)? }
+    } else {
+        Statement::PutFile { file_number, position, value: parse_expression(data)? }
+    })
+}
+
 fn parse_select_statement(pair: Pair<Rule>) -> Result<Statement, ParseError> {
     let mut inner = pair.into_inner();
     let test_expr = parse_expression(inner.next().unwrap())?;
@@ -2419,12 +2902,12 @@ fn parse_select_statement(pair: Pair<Rule>) -> Result<Statement, ParseError> {
                     conditions.push(condition);
                 }
 
-                let mut body = Vec::new();
+                let mut body = Block::new();
                 for stmt_pair in case_inner {
                     if stmt_pair.as_rule() == Rule::statement_line {
                         for inner in stmt_pair.into_inner() {
                             if inner.as_rule() != Rule::NEWLINE && inner.as_rule() != Rule::EOI {
-                                body.push(parse_statement(inner)?);
+                                push_statement(&mut body, inner)?;
                             }
                         }
                     }
@@ -2432,12 +2915,12 @@ fn parse_select_statement(pair: Pair<Rule>) -> Result<Statement, ParseError> {
                 cases.push(CaseBlock { conditions, body });
             }
             Rule::case_else => {
-                let mut body = Vec::new();
+                let mut body = Block::new();
                 for stmt_pair in p.into_inner() {
                     if stmt_pair.as_rule() == Rule::statement_line {
                         for inner in stmt_pair.into_inner() {
                             if inner.as_rule() != Rule::NEWLINE && inner.as_rule() != Rule::EOI {
-                                body.push(parse_statement(inner)?);
+                                push_statement(&mut body, inner)?;
                             }
                         }
                     }
@@ -2461,15 +2944,20 @@ fn parse_select_statement(pair: Pair<Rule>) -> Result<Statement, ParseError> {
 // ---------------------------------------------------------------------------
 
 fn parse_interface_decl(pair: Pair<Rule>) -> ParseResult<InterfaceDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
+    let mut type_parameters = Vec::new();
     let mut inherits = Vec::new();
     let mut methods = Vec::new();
+    let mut attributes = Vec::new();
 
     for p in inner {
         match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::identifier => name = Identifier::new(p.as_str()),
+            Rule::type_parameter_list => type_parameters = parse_type_parameter_list(p),
             Rule::inherits_statement => {
                 for tp in p.into_inner() {
                     if tp.as_rule() == Rule::type_name {
@@ -2546,21 +3034,27 @@ fn parse_interface_decl(pair: Pair<Rule>) -> ParseResult<InterfaceDecl> {
         }
     }
 
-    Ok(InterfaceDecl { visibility, name, inherits, methods })
+    Ok(InterfaceDecl { visibility, name, type_parameters, inherits, methods, attributes, span })
 }
 
 fn parse_structure_decl(pair: Pair<Rule>) -> ParseResult<StructureDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
+    let mut type_parameters = Vec::new();
     let mut implements = Vec::new();
     let mut properties = Vec::new();
     let mut methods = Vec::new();
     let mut fields = Vec::new();
+    let mut operators = Vec::new();
+    let mut attributes = Vec::new();
 
     for p in inner {
         match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::identifier => name = Identifier::new(p.as_str()),
+            Rule::type_parameter_list => type_parameters = parse_type_parameter_list(p),
             Rule::implements_statement => {
                 for tp in p.into_inner() {
                     if tp.as_rule() == Rule::type_name {
@@ -2574,6 +3068,7 @@ fn parse_structure_decl(pair: Pair<Rule>) -> ParseResult<StructureDecl> {
             }
             Rule::sub_decl => methods.push(MethodDecl::Sub(parse_sub_decl(p)?)),
             Rule::function_decl => methods.push(MethodDecl::Function(parse_function_decl(p)?)),
+            Rule::operator_decl => operators.push(parse_operator_decl(p)?),
             Rule::dim_statement => {
                 if let Ok(crate::ast::stmt::Statement::Dim(decls)) = parse_statement(p) {
                     fields.extend(decls);
@@ -2597,19 +3092,83 @@ fn parse_structure_decl(pair: Pair<Rule>) -> ParseResult<StructureDecl> {
         }
     }
 
-    Ok(StructureDecl { visibility, name, implements, properties, methods, fields })
+    Ok(StructureDecl { visibility, name, type_parameters, implements, properties, methods, fields, operators, attributes, span })
+}
+
+/// A VB6 `Type ... End Type`, declared as a Structure with only fields.
+fn parse_type_decl(pair: Pair<Rule>) -> ParseResult<StructureDecl> {
+    let span = Span::from_pest(pair.as_span());
+    let mut visibility = Visibility::Public;
+    let mut name = Identifier::new("");
+    let mut fields = Vec::new();
+
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::visibility_modifier if p.as_str().eq_ignore_ascii_case("private") => {
+                visibility = Visibility::Private;
+            }
+            Rule::identifier => name = Identifier::new(p.as_str()),
+            Rule::type_member => {
+                let member_span = Span::from_pest(p.as_span());
+                let mut member = VariableDecl {
+                    name: Identifier::new(""),
+                    var_type: None,
+                    array_bounds: None,
+                    initializer: None,
+                    with_events: false,
+                    attributes: Vec::new(),
+                    span: member_span,
+                };
+                for mp in p.into_inner() {
+                    match mp.as_rule() {
+                        Rule::identifier => member.name = Identifier::new(mp.as_str()),
+                        Rule::array_bounds => {
+                            member.array_bounds = Some(mp.into_inner().map(parse_expression).collect::<ParseResult<_>>()?);
+                        }
+                        Rule::type_name => member.var_type = Some(VBType::from_str(mp.as_str())),
+                        Rule::fixed_length => {
+                            let len = mp.as_str().parse()
+                                .map_err(|_| ParseError::Custom(format!("Invalid string length: {}", mp.as_str())))?;
+                            member.var_type = Some(VBType::FixedString(len));
+                        }
+                        _ => {}
+                    }
+                }
+                fields.push(member);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(StructureDecl {
+        visibility,
+        name,
+        type_parameters: Vec::new(),
+        implements: Vec::new(),
+        properties: Vec::new(),
+        methods: Vec::new(),
+        fields,
+        operators: Vec::new(),
+        attributes: Vec::new(),
+        span,
+    })
 }
 
 fn parse_delegate_decl(pair: Pair<Rule>, is_sub: bool) -> ParseResult<DelegateDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
+    let mut type_parameters = Vec::new();
     let mut parameters = Vec::new();
     let mut return_type = None;
+    let mut attributes = Vec::new();
 
     for p in inner {
         match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::identifier => name = Identifier::new(p.as_str()),
+            Rule::type_parameter_list => type_parameters = parse_type_parameter_list(p),
             Rule::param_list => parameters = parse_param_list(p)?,
             Rule::type_name => return_type = Some(VBType::from_str(p.as_str())),
             Rule::visibility_modifier => {
@@ -2626,18 +3185,21 @@ fn parse_delegate_decl(pair: Pair<Rule>, is_sub: bool) -> ParseResult<DelegateDe
         }
     }
 
-    Ok(DelegateDecl { visibility, name, parameters, return_type, is_sub })
+    Ok(DelegateDecl { visibility, name, type_parameters, parameters, return_type, is_sub, attributes, span })
 }
 
 fn parse_event_decl(pair: Pair<Rule>) -> ParseResult<EventDecl> {
+    let span = Span::from_pest(pair.as_span());
     let inner = pair.into_inner();
     let mut visibility = Visibility::Public;
     let mut name = Identifier::new("");
     let mut parameters = Vec::new();
     let mut event_type = None;
+    let mut attributes = Vec::new();
 
     for p in inner {
         match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
             Rule::identifier => name = Identifier::new(p.as_str()),
             Rule::param_list => parameters = parse_param_list(p)?,
             Rule::type_name => event_type = Some(VBType::from_str(p.as_str())),
@@ -2655,7 +3217,63 @@ fn parse_event_decl(pair: Pair<Rule>) -> ParseResult<EventDecl> {
         }
     }
 
-    Ok(EventDecl { visibility, name, parameters, event_type })
+    Ok(EventDecl { visibility, name, parameters, event_type, accessors: None, attributes, span })
+}
+
+fn parse_custom_event_decl(pair: Pair<Rule>) -> ParseResult<EventDecl> {
+    let span = Span::from_pest(pair.as_span());
+    let mut visibility = Visibility::Public;
+    let mut name = Identifier::new("");
+    let mut event_type = None;
+    let mut add_handler = None;
+    let mut remove_handler = None;
+    let mut raise_event = None;
+    let mut attributes = Vec::new();
+
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::attribute_block => attributes.extend(parse_attribute_block(p)?),
+            Rule::identifier => name = Identifier::new(p.as_str()),
+            Rule::type_name => event_type = Some(VBType::from_str(p.as_str())),
+            Rule::visibility_modifier => {
+                visibility = match p.as_str().to_lowercase().as_str() {
+                    "private" => Visibility::Private,
+                    "protected" => Visibility::Protected,
+                    "friend" => Visibility::Friend,
+                    _ => Visibility::Public,
+                };
+            }
+            Rule::event_add_handler => add_handler = Some(parse_property_set(p)?),
+            Rule::event_remove_handler => remove_handler = Some(parse_property_set(p)?),
+            Rule::event_raise_event => {
+                let mut parameters = Vec::new();
+                let mut body = Block::new();
+                for part in p.into_inner() {
+                    match part.as_rule() {
+                        Rule::param_list => parameters = parse_param_list(part)?,
+                        Rule::statement_line => {
+                            for s in part.into_inner() {
+                                if s.as_rule() != Rule::NEWLINE && s.as_rule() != Rule::EOI {
+                                    push_statement(&mut body, s)?;
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                raise_event = Some((parameters, body));
+            }
+            _ => {}
+        }
+    }
+
+    let missing = |accessor: &str| ParseError::Custom(format!("'{}' definition missing for event '{}'", accessor, name));
+    let accessors = EventAccessors {
+        add_handler: add_handler.ok_or_else(|| missing("AddHandler"))?,
+        remove_handler: remove_handler.ok_or_else(|| missing("RemoveHandler"))?,
+        raise_event: raise_event.ok_or_else(|| missing("RaiseEvent"))?,
+    };
+    Ok(EventDecl { visibility, name, parameters: Vec::new(), event_type, accessors: Some(Box::new(accessors)), attributes, span })
 }
 
 // ── Syntax Extensions Implementation ──
@@ -2663,7 +3281,7 @@ fn parse_event_decl(pair: Pair<Rule>) -> ParseResult<EventDecl> {
 fn parse_synclock_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
     let mut inner = pair.into_inner();
     let lock_object = parse_expression(inner.next().unwrap())?;
-    let mut body = Vec::new();
+    let mut body = Block::new();
 
     for p in inner {
         match p.as_rule() {
@@ -2672,7 +3290,7 @@ fn parse_synclock_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
                     if stmt_pair.as_rule() == Rule::NEWLINE || stmt_pair.as_rule() == Rule::EOI {
                         continue;
                     }
-                    body.push(parse_statement(stmt_pair)?);
+                    push_statement(&mut body, stmt_pair)?;
                 }
             }
             Rule::synclock_end | Rule::NEWLINE => {}
@@ -2683,116 +3301,233 @@ fn parse_synclock_statement(pair: Pair<Rule>) -> ParseResult<Statement> {
 }
 
 fn parse_query_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
-    let mut inner = pair.into_inner();
-    let from_clause_pair = inner.next().unwrap();
-    
-    // Parse From clause
-    let mut from_inner = from_clause_pair.into_inner();
+    use crate::ast::query::*;
+
+    let mut inner = pair.into_inner().filter(|p| p.as_rule() != Rule::NEWLINE);
+    let first = inner.next().ok_or_else(|| ParseError::Custom("Query missing From clause".to_string()))?;
+    let (ranges, mut clauses) = match first.as_rule() {
+        Rule::aggregate_query => {
+            let (ranges, mut clauses, into) = parse_aggregate_parts(first)?;
+            clauses.push(QueryClause::Into(into));
+            (ranges, clauses)
+        }
+        _ => (parse_range_variables(first)?, Vec::new()),
+    };
+    for op in inner {
+        clauses.extend(parse_query_operator(op)?);
+    }
+
+    Ok(ExprKind::Query(Box::new(QueryExpression {
+        from_clause: FromClause { ranges },
+        body: QueryBody { clauses },
+    })).into())
+}
+
+/// The `range_variable`s of a `From` clause (or of `Aggregate`/`Join`).
+fn parse_range_variables(pair: Pair<Rule>) -> ParseResult<Vec<crate::ast::query::RangeVariable>> {
+    pair.into_inner()
+        .filter(|p| p.as_rule() == Rule::range_variable)
+        .map(parse_range_variable)
+        .collect()
+}
+
+fn parse_range_variable(pair: Pair<Rule>) -> ParseResult<crate::ast::query::RangeVariable> {
+    let mut name = String::new();
+    let mut type_name = None;
+    let mut collection = None;
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::identifier => name = p.as_str().to_string(),
+            Rule::type_name => type_name = Some(p.as_str().trim().to_string()),
+            Rule::expression => collection = Some(parse_expression(p)?),
+            _ => {}
+        }
+    }
+    let collection = collection.ok_or_else(|| ParseError::Custom("Range variable missing collection".to_string()))?;
+    Ok(crate::ast::query::RangeVariable { name, collection, type_name })
+}
+
+/// Ranges, inner clauses and `Into` list of `Aggregate ... Into ...`.
+#[allow(clippy::type_complexity)]
+fn parse_aggregate_parts(pair: Pair<Rule>) -> ParseResult<(Vec<crate::ast::query::RangeVariable>, Vec<crate::ast::query::QueryClause>, Vec<crate::ast::query::Aggregation>)> {
     let mut ranges = Vec::new();
-    
-    while let Some(id_pair) = from_inner.next() {
-        if id_pair.as_rule() == Rule::identifier {
-            let name = id_pair.as_str().to_string();
-            let mut collection_expr = None;
-            let mut type_name = None;
-            
-            while let Some(p) = from_inner.next() {
+    let mut clauses = Vec::new();
+    let mut into = Vec::new();
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::range_variable => ranges.push(parse_range_variable(p)?),
+            Rule::query_operator => clauses.extend(parse_query_operator(p)?),
+            Rule::aggregation_list => into = parse_aggregation_list(p)?,
+            _ => {}
+        }
+    }
+    Ok((ranges, clauses, into))
+}
+
+/// The clauses of one query operator; usually one, but `Let a = x, b = y` gives two.
+fn parse_query_operator(pair: Pair<Rule>) -> ParseResult<Vec<crate::ast::query::QueryClause>> {
+    use crate::ast::query::*;
+
+    let op = pair.into_inner().next().ok_or_else(|| ParseError::Custom("Empty query operator".to_string()))?;
+    let rule = op.as_rule();
+    // The clause's first expression, for the single-expression clauses
+    let expression = |op: Pair<Rule>| -> ParseResult<Expression> {
+        let expr = op.into_inner().find(|p| p.as_rule() == Rule::expression)
+            .ok_or_else(|| ParseError::Custom(format!("{:?} missing expression", rule)))?;
+        parse_expression(expr)
+    };
+    Ok(vec![match rule {
+        Rule::from_clause => QueryClause::From(parse_range_variables(op)?),
+        Rule::where_clause => QueryClause::Where(expression(op)?),
+        Rule::skip_clause => QueryClause::Skip(expression(op)?),
+        Rule::take_clause => QueryClause::Take(expression(op)?),
+        Rule::skip_while_clause => QueryClause::SkipWhile(expression(op)?),
+        Rule::take_while_clause => QueryClause::TakeWhile(expression(op)?),
+        Rule::distinct_clause => QueryClause::Distinct,
+        Rule::order_by_clause => {
+            let mut orderings = Vec::new();
+            for ord in op.into_inner().filter(|p| p.as_rule() == Rule::ordering) {
+                let mut ord_inner = ord.into_inner();
+                let expr = parse_expression(ord_inner.next().unwrap())?;
+                let direction = match ord_inner.next().map(|x| x.as_str().to_lowercase()).as_deref() {
+                    Some("descending") => OrderDirection::Descending,
+                    _ => OrderDirection::Ascending,
+                };
+                orderings.push(Ordering { expression: expr, direction });
+            }
+            QueryClause::OrderBy(orderings)
+        }
+        Rule::let_clause => {
+            // `Let a = x, b = y` becomes one Let per binding
+            return op.into_inner().filter(|p| p.as_rule() == Rule::let_binding).map(|b| {
+                let mut b_inner = b.into_inner();
+                let name = b_inner.next().unwrap().as_str().to_string();
+                Ok(QueryClause::Let { name, value: parse_expression(b_inner.next().unwrap())? })
+            }).collect();
+        }
+        Rule::select_clause => QueryClause::Select(parse_query_fields(op)?),
+        Rule::join_clause => {
+            let (range, conditions) = parse_join_parts(op.clone())?;
+            QueryClause::Join(Box::new(JoinClause { range, conditions }))
+        }
+        Rule::group_join_clause => {
+            let into = match op.clone().into_inner().find(|p| p.as_rule() == Rule::aggregation_list) {
+                Some(list) => parse_aggregation_list(list)?,
+                None => Vec::new(),
+            };
+            let (range, conditions) = parse_join_parts(op)?;
+            QueryClause::GroupJoin(Box::new(GroupJoinClause { range, conditions, into }))
+        }
+        Rule::group_by_clause => {
+            let mut items = Vec::new();
+            let mut keys = Vec::new();
+            let mut into = Vec::new();
+            for p in op.into_inner() {
                 match p.as_rule() {
-                    Rule::expression => { collection_expr = Some(parse_expression(p)?); break; }
-                    Rule::type_name => type_name = Some(p.as_str().to_string()),
+                    Rule::group_items => items = parse_query_fields(p)?,
+                    Rule::group_keys => keys = parse_query_fields(p)?,
+                    Rule::aggregation_list => into = parse_aggregation_list(p)?,
                     _ => {}
                 }
             }
-             
-            if let Some(collection) = collection_expr {
-                ranges.push(crate::ast::query::RangeVariable {
-                    name,
-                    collection,
-                    type_name,
-                });
+            if into.is_empty() {
+                into.push(Aggregation { name: None, function: "Group".to_string(), argument: None });
             }
+            QueryClause::GroupBy(Box::new(GroupByClause { items, keys, into }))
         }
-    }
-    
-    let query_body_pair = inner.next().unwrap();
-    let body_inner = query_body_pair.into_inner();
-    let mut clauses = Vec::new();
-    let mut select_or_group = None;
-    
-    for p in body_inner {
+        Rule::aggregate_clause => {
+            let (ranges, clauses, into) = parse_aggregate_parts(op)?;
+            QueryClause::Aggregate(Box::new(AggregateClause { ranges, clauses, into }))
+        }
+        other => return Err(ParseError::UnexpectedRule(other)),
+    }])
+}
+
+fn parse_join_parts(pair: Pair<Rule>) -> ParseResult<(crate::ast::query::RangeVariable, Vec<crate::ast::query::JoinCondition>)> {
+    let mut range = None;
+    let mut conditions = Vec::new();
+    for p in pair.into_inner() {
         match p.as_rule() {
-            Rule::query_operator => {
-                let op = p.into_inner().next().unwrap();
-                match op.as_rule() {
-                    Rule::where_clause => {
-                        let expr = parse_expression(op.into_inner().next().unwrap())?;
-                        clauses.push(crate::ast::query::QueryClause::Where(expr));
-                    }
-                    Rule::order_by_clause => {
-                        let mut orderings = Vec::new();
-                        for ord in op.into_inner() {
-                            let mut ord_inner = ord.into_inner();
-                            let expr = parse_expression(ord_inner.next().unwrap())?;
-                            let direction = match ord_inner.next().map(|x| x.as_str().to_lowercase()).as_deref() {
-                                Some("descending") => crate::ast::query::OrderDirection::Descending,
-                                _ => crate::ast::query::OrderDirection::Ascending,
-                            };
-                            orderings.push(crate::ast::query::Ordering { expression: expr, direction });
-                        }
-                        clauses.push(crate::ast::query::QueryClause::OrderBy(orderings));
-                    }
-                    Rule::let_clause => {
-                        let mut let_inner = op.into_inner();
-                        let name = let_inner.next().unwrap().as_str().to_string();
-                        let value = parse_expression(let_inner.next().unwrap())?;
-                        clauses.push(crate::ast::query::QueryClause::Let { name, value });
-                    }
-                    _ => {}
-                }
-            }
-            Rule::select_or_group_clause => {
-                let inner_sg = p.into_inner().next().unwrap();
-                match inner_sg.as_rule() {
-                    Rule::select_clause => {
-                        let exprs = inner_sg.into_inner().map(|x| parse_expression(x)).collect::<Result<Vec<_>,_>>()?;
-                        select_or_group = Some(crate::ast::query::SelectOrGroupClause::Select(exprs));
-                    }
-                    Rule::group_clause => {
-                        let mut exprs = Vec::new();
-                        for x in inner_sg.into_inner() {
-                             if x.as_rule() == Rule::expression {
-                                 exprs.push(parse_expression(x)?);
-                             }
-                        }
-                        if exprs.len() >= 2 {
-                             let key = exprs.pop().unwrap();
-                             let item = exprs.pop().unwrap();
-                             select_or_group = Some(crate::ast::query::SelectOrGroupClause::Group(Box::new(crate::ast::query::GroupClause { item, key })));
-                        } else if !exprs.is_empty() {
-                             let key = exprs.pop().unwrap();
-                             let item = Expression::Nothing;
-                             select_or_group = Some(crate::ast::query::SelectOrGroupClause::Group(Box::new(crate::ast::query::GroupClause { item, key })));
-                        }
-                    }
-                    _ => {}
-                }
+            Rule::range_variable => range = Some(parse_range_variable(p)?),
+            Rule::join_condition => {
+                let mut sides = p.into_inner().filter(|s| s.as_rule() == Rule::comparison);
+                let left = parse_expression(sides.next().unwrap())?;
+                let right = parse_expression(sides.next().unwrap())?;
+                conditions.push(crate::ast::query::JoinCondition { left, right });
             }
             _ => {}
         }
     }
-    
-    Ok(Expression::Query(Box::new(crate::ast::query::QueryExpression {
-        from_clause: crate::ast::query::FromClause { ranges },
-        body: crate::ast::query::QueryBody {
-            clauses,
-            select_or_group: select_or_group.unwrap_or(crate::ast::query::SelectOrGroupClause::Select(vec![Expression::Nothing])), 
-        },
-    })))
+    let range = range.ok_or_else(|| ParseError::Custom("Join missing range variable".to_string()))?;
+    Ok((range, conditions))
+}
+
+/// `[name =] expression` fields of `Select` and `Group ... By`.
+fn parse_query_fields(pair: Pair<Rule>) -> ParseResult<Vec<crate::ast::query::QueryField>> {
+    pair.into_inner().filter(|p| p.as_rule() == Rule::query_field).map(|field| {
+        let mut name = None;
+        let mut expression = None;
+        for p in field.into_inner() {
+            match p.as_rule() {
+                Rule::identifier => name = Some(p.as_str().to_string()),
+                Rule::expression => expression = Some(parse_expression(p)?),
+                _ => {}
+            }
+        }
+        let expression = expression.ok_or_else(|| ParseError::Custom("Query field missing expression".to_string()))?;
+        let name = name.or_else(|| inferred_member_name(&expression));
+        Ok(crate::ast::query::QueryField { name, expression })
+    }).collect()
+}
+
+fn parse_aggregation_list(pair: Pair<Rule>) -> ParseResult<Vec<crate::ast::query::Aggregation>> {
+    pair.into_inner().filter(|p| p.as_rule() == Rule::aggregation).map(|agg| {
+        let mut identifiers = Vec::new();
+        let mut argument = None;
+        for p in agg.into_inner() {
+            match p.as_rule() {
+                Rule::identifier => identifiers.push(p.as_str().to_string()),
+                Rule::expression => argument = Some(parse_expression(p)?),
+                _ => {}
+            }
+        }
+        let function = identifiers.pop().unwrap_or_default();
+        Ok(crate::ast::query::Aggregation { name: identifiers.pop(), function, argument })
+    }).collect()
+}
+
+/// The name VB infers for an anonymous type member or query field:
+/// the last identifier of `x` or `a.b.x`.
+fn inferred_member_name(expr: &Expression) -> Option<String> {
+    match &expr.kind {
+        ExprKind::Variable(id) => Some(id.as_str().to_string()),
+        ExprKind::MemberAccess(_, member) => Some(member.as_str().to_string()),
+        _ => None,
+    }
+}
+
+/// Tuple elements with the names VB gives them: an explicit `Name:=`, or a
+/// name inferred from `x` or `p.Name` unless it is repeated, taken
+/// explicitly, or looks like `ItemN`.
+fn tuple_element_names(elements: Vec<(Option<String>, Expression)>) -> Vec<(Option<String>, Expression)> {
+    let inferred: Vec<Option<String>> = elements.iter()
+        .map(|(name, value)| if name.is_none() { inferred_member_name(value) } else { None })
+        .collect();
+    let usable = |name: &str| {
+        let lower = name.to_lowercase();
+        inferred.iter().flatten().filter(|n| n.eq_ignore_ascii_case(name)).count() == 1
+            && !elements.iter().any(|(n, _)| n.as_ref().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+            && lower.strip_prefix("item").is_none_or(|n| n.parse::<usize>().is_err())
+    };
+    let names: Vec<Option<String>> = elements.iter().zip(&inferred)
+        .map(|((name, _), inferred)| name.clone().or_else(|| inferred.clone().filter(|n| usable(n))))
+        .collect();
+    names.into_iter().zip(elements).map(|(name, (_, value))| (name, value)).collect()
 }
 
 fn parse_xml_literal(pair: Pair<Rule>) -> ParseResult<Expression> {
     let top = parse_xml_node(pair)?;
-    Ok(Expression::XmlLiteral(Box::new(top)))
+    Ok(ExprKind::XmlLiteral(Box::new(top)).into())
 }
 
 fn parse_xml_node(pair: Pair<Rule>) -> ParseResult<crate::ast::xml::XmlNode> {
@@ -2818,19 +3553,7 @@ fn parse_xml_node(pair: Pair<Rule>) -> ParseResult<crate::ast::xml::XmlNode> {
             
             for p in inner {
                 match p.as_rule() {
-                    Rule::xml_attribute => {
-                        let mut attr_inner = p.into_inner();
-                        let attr_name = parse_xml_name(attr_inner.next().unwrap());
-                        let val_pair = attr_inner.next().unwrap();
-                        let val_node = if val_pair.as_rule() == Rule::xml_embedded_expression {
-                            crate::ast::xml::XmlNode::EmbeddedExpression(parse_expression(val_pair.into_inner().next().unwrap())?)
-                        } else {
-                             let s = val_pair.as_str().trim_matches('"').to_string();
-                             let s = s.replace("&quot;", "\"").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
-                             crate::ast::xml::XmlNode::Text(s)
-                        };
-                        attributes.push(crate::ast::xml::XmlAttribute { name: attr_name, value: vec![val_node] });
-                    }
+                    Rule::xml_attribute => attributes.push(parse_xml_attribute(p)?),
                     Rule::xml_content => {
                          let content = p.into_inner().next().unwrap();
                          match content.as_rule() {
@@ -2862,6 +3585,36 @@ fn parse_xml_node(pair: Pair<Rule>) -> ParseResult<crate::ast::xml::XmlNode> {
     }
 }
 
+/// `name="text"` or `name=<%= expr %>`; the name may itself be an embedded
+/// expression, or a lone `<%= expr %>` give the whole attribute.
+fn parse_xml_attribute(pair: Pair<Rule>) -> ParseResult<crate::ast::xml::XmlAttribute> {
+    use crate::ast::xml::{XmlAttribute, XmlName, XmlNode};
+    let mut attribute = XmlAttribute {
+        name: XmlName { prefix: None, local: String::new() },
+        value: Vec::new(),
+        name_expression: None,
+    };
+    for (i, p) in pair.into_inner().enumerate() {
+        match p.as_rule() {
+            Rule::xml_name => attribute.name = parse_xml_name(p),
+            Rule::xml_embedded_expression => {
+                let expr = parse_expression(p.into_inner().next().unwrap())?;
+                if i == 0 {
+                    attribute.name_expression = Some(expr);
+                } else {
+                    attribute.value.push(XmlNode::EmbeddedExpression(expr));
+                }
+            }
+            _ => {
+                let s = p.as_str().trim_matches('"').to_string();
+                let s = s.replace("&quot;", "\"").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
+                attribute.value.push(XmlNode::Text(s));
+            }
+        }
+    }
+    Ok(attribute)
+}
+
 fn parse_xml_name(pair: Pair<Rule>) -> crate::ast::xml::XmlName {
     let s = pair.as_str();
     if let Some(idx) = s.find(':') {
@@ -2874,6 +3627,15 @@ fn parse_xml_name(pair: Pair<Rule>) -> crate::ast::xml::XmlName {
     }
 }
 
+/// `obj.member = value`, or `obj.member(indices) = value` when the target
+/// is indexed.
+fn member_assignment(object: Expression, member: Identifier, indices: Option<Vec<Expression>>, value: Expression) -> Statement {
+    match indices {
+        Some(indices) => Statement::IndexedAssignment { object, member, indices, value },
+        None => Statement::MemberAssignment { object, member, value },
+    }
+}
+
 fn parse_l_value_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
     let mut inner = pair.into_inner();
     let primary = inner.next().unwrap();
@@ -2881,26 +3643,22 @@ fn parse_l_value_expression(pair: Pair<Rule>) -> ParseResult<Expression> {
 
     // Handle postfix operations (member access, array/method calls)
     for part in inner {
+        let span = expr.span.to(expression_span(&part));
         match part.as_rule() {
              Rule::member_identifier => {
                  let name = part.as_str();
-                 expr = Expression::MemberAccess(Box::new(expr), Identifier::new(name));
+                 expr = Expression::new(ExprKind::MemberAccess(Box::new(expr), Identifier::new(name)), span);
              }
              _ => {
                  // It's (arg_list) -> parse arguments
+                 let args = parse_argument_list(part)?;
                  
-                 let args = if let Some(arg_list_pair) = part.into_inner().next() {
-                     parse_argument_list(arg_list_pair)?
-                 } else {
-                     vec![]
-                 };
-                 
-                 match expr {
-                     Expression::Variable(id) => {
-                         expr = Expression::Call(id, args);
+                 match expr.kind {
+                     ExprKind::Variable(id) => {
+                         expr = Expression::new(ExprKind::Call(id, args), span);
                      }
-                     Expression::MemberAccess(obj, member) => {
-                         expr = Expression::MethodCall(obj, member, args);
+                     ExprKind::MemberAccess(obj, member) => {
+                         expr = Expression::new(ExprKind::MethodCall(obj, member, args), span);
                      }
                      _ => {
                          return Err(ParseError::Custom("Complex nested calls not supported yet".to_string()));
@@ -0,0 +1,553 @@
+//! Conditional compilation: `#Const`, `#If`/`#ElseIf`/`#Else`/`#End If` and
+//! `#Region`.
+//!
+//! The preprocessor runs on the source text before pest sees it. Directive
+//! lines and the lines of inactive branches are blanked rather than removed,
+//! so every remaining line keeps its number and the spans reported by the
+//! parser, the diagnostics and the runtime still point into the user's file.
+//!
+//! `#If` conditions see the caller's [`ConditionalConstants`] (a project's
+//! DEBUG, TRACE and `<DefineConstants>`) plus the file's own `#Const`s.
+//! Undefined names are `Nothing`, which is false.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::Span;
+use crate::parser::{ParseError, ParseResult};
+
+/// The value of a conditional compilation constant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConstValue {
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+    Nothing,
+}
+
+impl ConstValue {
+    fn is_true(&self) -> bool {
+        match self {
+            ConstValue::Boolean(b) => *b,
+            ConstValue::Integer(i) => *i != 0,
+            ConstValue::String(s) => !s.is_empty(),
+            ConstValue::Nothing => false,
+        }
+    }
+
+    /// Numeric value for arithmetic and comparisons; True is -1 as in VB.
+    fn as_integer(&self) -> Option<i64> {
+        match self {
+            ConstValue::Boolean(b) => Some(if *b { -1 } else { 0 }),
+            ConstValue::Integer(i) => Some(*i),
+            ConstValue::String(s) => s.trim().parse().ok(),
+            ConstValue::Nothing => Some(0),
+        }
+    }
+
+    fn as_string(&self) -> String {
+        match self {
+            ConstValue::Boolean(b) => if *b { "True" } else { "False" }.to_string(),
+            ConstValue::Integer(i) => i.to_string(),
+            ConstValue::String(s) => s.clone(),
+            ConstValue::Nothing => String::new(),
+        }
+    }
+}
+
+impl fmt::Display for ConstValue {
+    /// The value as it is written in `<DefineConstants>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::String(s) => write!(f, "\"{}\"", s),
+            ConstValue::Nothing => write!(f, "Nothing"),
+            other => write!(f, "{}", other.as_string()),
+        }
+    }
+}
+
+/// Named constants visible to `#If`, compared case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConditionalConstants {
+    constants: Vec<(String, ConstValue)>,
+}
+
+impl ConditionalConstants {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// DEBUG and TRACE, as a project's Debug configuration defines them.
+    pub fn project_defaults() -> Self {
+        let mut constants = Self::new();
+        constants.define("DEBUG", ConstValue::Boolean(true));
+        constants.define("TRACE", ConstValue::Boolean(true));
+        constants
+    }
+
+    pub fn define(&mut self, name: &str, value: ConstValue) {
+        match self.constants.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(entry) => entry.1 = value,
+            None => self.constants.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.constants.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConstValue> {
+        self.constants.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ConstValue)> {
+        self.constants.iter().map(|(n, v)| (n.as_str(), v))
+    }
+
+    /// Add the constants of a `<DefineConstants>` value such as
+    /// `DEBUG,TRACE,Edition="Pro",Level=2`. A bare name is True; MSBuild
+    /// references like `$(DefineConstants)` are skipped.
+    pub fn define_list(&mut self, list: &str) {
+        for item in list.split([',', ';']).map(str::trim) {
+            if item.is_empty() || item.starts_with("$(") {
+                continue;
+            }
+            let (name, value) = match item.split_once('=') {
+                Some((name, value)) => {
+                    let value = evaluate(value.trim(), &ConditionalConstants::new()).unwrap_or(ConstValue::Nothing);
+                    (name.trim(), value)
+                }
+                None => (item, ConstValue::Boolean(true)),
+            };
+            if is_identifier(name) {
+                self.define(name, value);
+            }
+        }
+    }
+}
+
+/// Apply `#Const`, `#If` and `#Region` directives to `source`, blanking the
+/// directive lines and every line in an inactive branch.
+pub fn preprocess<'a>(source: &'a str, constants: &ConditionalConstants) -> ParseResult<Cow<'a, str>> {
+    if !source.lines().any(|line| directive(line).is_some()) {
+        return Ok(Cow::Borrowed(source));
+    }
+
+    let mut constants = constants.clone();
+    let mut branches: Vec<Branch> = Vec::new();
+    let mut regions: Vec<usize> = Vec::new();
+    let mut output = String::with_capacity(source.len());
+
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        let line_no = index + 1;
+        let active = branches.last().is_none_or(|b| b.active);
+        let Some((keyword, rest)) = directive(line) else {
+            if active {
+                output.push_str(line);
+            } else {
+                output.push_str(line_ending(line));
+            }
+            continue;
+        };
+        output.push_str(line_ending(line));
+
+        let error = |message: String| ParseError::Directive { message, span: line_span(line_no, line) };
+        let rest = strip_comment(rest);
+        match keyword.as_str() {
+            "const" if active => {
+                let (name, value) = rest.split_once('=')
+                    .ok_or_else(|| error("'#Const' needs a name and a value: #Const Name = value".to_string()))?;
+                let name = name.trim();
+                if !is_identifier(name) {
+                    return Err(error(format!("'{}' is not a valid constant name", name)));
+                }
+                let value = evaluate(value.trim(), &constants).map_err(&error)?;
+                constants.define(name, value);
+            }
+            "if" => {
+                let taken = active && condition(rest, &constants).map_err(&error)?;
+                branches.push(Branch { line: line_no, parent_active: active, active: taken, taken, seen_else: false });
+            }
+            "elseif" | "else" => {
+                let Some(branch) = branches.last_mut() else {
+                    return Err(error(format!("'#{}' must be preceded by a matching '#If'", directive_name(&keyword))));
+                };
+                if branch.seen_else {
+                    return Err(error(format!("'#{}' cannot follow '#Else'", directive_name(&keyword))));
+                }
+                let take = if keyword == "else" {
+                    branch.seen_else = true;
+                    branch.parent_active && !branch.taken
+                } else {
+                    branch.parent_active && !branch.taken && condition(rest, &constants).map_err(&error)?
+                };
+                branch.active = take;
+                branch.taken |= take;
+            }
+            "endif" => {
+                branches.pop().ok_or_else(|| error("'#End If' must be preceded by a matching '#If'".to_string()))?;
+            }
+            "region" => regions.push(line_no),
+            "endregion" => {
+                regions.pop().ok_or_else(|| error("'#End Region' must be preceded by a matching '#Region'".to_string()))?;
+            }
+            // #Const in an inactive branch, #ExternalSource, #Disable/#Enable Warning
+            _ => {}
+        }
+    }
+
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    if let Some(branch) = branches.last() {
+        return Err(ParseError::Directive {
+            message: "'#If' must end with a matching '#End If'".to_string(),
+            span: line_span(branch.line, lines[branch.line - 1]),
+        });
+    }
+    if let Some(&line) = regions.last() {
+        return Err(ParseError::Directive {
+            message: "'#Region' must end with a matching '#End Region'".to_string(),
+            span: line_span(line, lines[line - 1]),
+        });
+    }
+    Ok(Cow::Owned(output))
+}
+
+/// One `#If` ... `#End If` being read.
+struct Branch {
+    /// Line of the `#If`, for the unclosed-block error.
+    line: usize,
+    /// The enclosing code is active, so some branch of this one can be.
+    parent_active: bool,
+    /// Lines are kept in the current branch.
+    active: bool,
+    /// Some branch was taken, so the later ones aren't.
+    taken: bool,
+    seen_else: bool,
+}
+
+const DIRECTIVES: &[&str] = &["const", "if", "elseif", "else", "end", "endif", "region", "externalsource", "disable", "enable"];
+
+/// The lowercased directive keyword of a `#...` line and the text after it.
+/// `#End If` and `#End Region` come back as `endif` and `endregion`.
+fn directive(line: &str) -> Option<(String, &str)> {
+    let text = line.trim_start().strip_prefix('#')?.trim_start();
+    let word_len = text.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(text.len());
+    let keyword = text[..word_len].to_ascii_lowercase();
+    if !DIRECTIVES.contains(&keyword.as_str()) {
+        return None;
+    }
+    let rest = &text[word_len..];
+    if keyword != "end" {
+        return Some((keyword, rest));
+    }
+    let rest = rest.trim_start();
+    let next_len = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+    let block = rest[..next_len].to_ascii_lowercase();
+    Some((format!("end{}", block), &rest[next_len..]))
+}
+
+fn directive_name(keyword: &str) -> &'static str {
+    match keyword {
+        "elseif" => "ElseIf",
+        _ => "Else",
+    }
+}
+
+/// The `\n` or `\r\n` that ends `line`, if any.
+fn line_ending(line: &str) -> &'static str {
+    if line.ends_with("\r\n") {
+        "\r\n"
+    } else if line.ends_with('\n') {
+        "\n"
+    } else {
+        ""
+    }
+}
+
+fn line_span(line_no: usize, line: &str) -> Span {
+    let text = line.trim_end_matches(['\r', '\n']);
+    let start_col = text.len() - text.trim_start().len() + 1;
+    Span { start_line: line_no, start_col, end_line: line_no, end_col: text.chars().count() + 1 }
+}
+
+/// `text` without a trailing `'` comment (quotes inside strings don't count).
+fn strip_comment(text: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '\'' if !in_string => return &text[..i],
+            _ => {}
+        }
+    }
+    text
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Evaluate an `#If`/`#ElseIf` condition, with its optional `Then`.
+fn condition(text: &str, constants: &ConditionalConstants) -> Result<bool, String> {
+    let text = text.trim_end();
+    let text = match text.len().checked_sub(4) {
+        Some(at) if text.is_char_boundary(at)
+            && text[at..].eq_ignore_ascii_case("then")
+            && (at == 0 || !text[..at].ends_with(|c: char| c.is_ascii_alphanumeric() || c == '_')) => &text[..at],
+        _ => text,
+    };
+    Ok(evaluate(text, constants)?.is_true())
+}
+
+// ---------------------------------------------------------------------------
+// Constant expressions
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Str(String),
+    Word(String),
+    Symbol(&'static str),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            tokens.push(Token::Number(digits.parse().map_err(|_| format!("number '{}' is too large", digits))?));
+        } else if c == '"' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err("string constant is missing its closing quote".to_string()),
+                    Some('"') if chars.get(i + 1) == Some(&'"') => {
+                        s.push('"');
+                        i += 2;
+                    }
+                    Some('"') => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&ch) => {
+                        s.push(ch);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(Token::Str(s));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            let symbol = ["<>", "<=", ">="].into_iter().find(|s| *s == two)
+                .or_else(|| ["=", "<", ">", "+", "-", "*", "/", "&", "(", ")"].into_iter().find(|s| s.starts_with(c)))
+                .ok_or_else(|| format!("unexpected '{}' in constant expression", c))?;
+            i += symbol.len();
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Evaluate a constant expression: literals, constant names, `Not`, `And`,
+/// `Or`, `AndAlso`, `OrElse`, `Xor`, comparisons, `+ - * / &` and parentheses.
+fn evaluate(text: &str, constants: &ConditionalConstants) -> Result<ConstValue, String> {
+    let tokens = tokenize(text)?;
+    if tokens.is_empty() {
+        return Err("expected a constant expression".to_string());
+    }
+    let mut parser = ExprParser { tokens, pos: 0, constants };
+    let value = parser.or_expr()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(value),
+        Some(token) => Err(format!("unexpected {} in constant expression", describe(token))),
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(n) => format!("'{}'", n),
+        Token::Str(s) => format!("\"{}\"", s),
+        Token::Word(w) => format!("'{}'", w),
+        Token::Symbol(s) => format!("'{}'", s),
+    }
+}
+
+struct ExprParser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    constants: &'a ConditionalConstants,
+}
+
+impl ExprParser<'_> {
+    fn eat_word(&mut self, words: &[&str]) -> Option<String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Word(w)) if words.iter().any(|k| w.eq_ignore_ascii_case(k)) => {
+                self.pos += 1;
+                Some(w.to_ascii_lowercase())
+            }
+            _ => None,
+        }
+    }
+
+    fn eat_symbol(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Symbol(s)) if symbols.contains(s) => {
+                self.pos += 1;
+                Some(*s)
+            }
+            _ => None,
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<ConstValue, String> {
+        let mut left = self.and_expr()?;
+        while let Some(op) = self.eat_word(&["Or", "OrElse", "Xor"]) {
+            let right = self.and_expr()?;
+            left = match op.as_str() {
+                "xor" => logical(&left, &right, |a, b| a ^ b, |a, b| a != b),
+                _ => logical(&left, &right, |a, b| a | b, |a, b| a || b),
+            };
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<ConstValue, String> {
+        let mut left = self.not_expr()?;
+        while self.eat_word(&["And", "AndAlso"]).is_some() {
+            let right = self.not_expr()?;
+            left = logical(&left, &right, |a, b| a & b, |a, b| a && b);
+        }
+        Ok(left)
+    }
+
+    fn not_expr(&mut self) -> Result<ConstValue, String> {
+        if self.eat_word(&["Not"]).is_some() {
+            return Ok(match self.not_expr()? {
+                ConstValue::Integer(i) => ConstValue::Integer(!i),
+                other => ConstValue::Boolean(!other.is_true()),
+            });
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<ConstValue, String> {
+        let left = self.concat()?;
+        let Some(op) = self.eat_symbol(&["=", "<>", "<", "<=", ">", ">="]) else {
+            return Ok(left);
+        };
+        let right = self.concat()?;
+        let ordering = match (&left, &right) {
+            (ConstValue::String(_), _) | (_, ConstValue::String(_)) => left.as_string().cmp(&right.as_string()),
+            _ => left.as_integer().cmp(&right.as_integer()),
+        };
+        let result = match op {
+            "=" => ordering.is_eq(),
+            "<>" => ordering.is_ne(),
+            "<" => ordering.is_lt(),
+            "<=" => ordering.is_le(),
+            ">" => ordering.is_gt(),
+            _ => ordering.is_ge(),
+        };
+        Ok(ConstValue::Boolean(result))
+    }
+
+    fn concat(&mut self) -> Result<ConstValue, String> {
+        let mut left = self.additive()?;
+        while self.eat_symbol(&["&"]).is_some() {
+            let right = self.additive()?;
+            left = ConstValue::String(left.as_string() + &right.as_string());
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<ConstValue, String> {
+        let mut left = self.multiplicative()?;
+        while let Some(op) = self.eat_symbol(&["+", "-"]) {
+            let right = self.multiplicative()?;
+            left = match (op, &left, &right) {
+                ("+", ConstValue::String(a), ConstValue::String(b)) => ConstValue::String(format!("{}{}", a, b)),
+                _ => arithmetic(op, &left, &right)?,
+            };
+        }
+        Ok(left)
+    }
+
+    fn multiplicative(&mut self) -> Result<ConstValue, String> {
+        let mut left = self.unary()?;
+        while let Some(op) = self.eat_symbol(&["*", "/"]) {
+            let right = self.unary()?;
+            left = arithmetic(op, &left, &right)?;
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<ConstValue, String> {
+        if self.eat_symbol(&["-"]).is_some() {
+            let value = self.unary()?;
+            return arithmetic("-", &ConstValue::Integer(0), &value);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<ConstValue, String> {
+        let token = self.tokens.get(self.pos).cloned().ok_or("expression ends unexpectedly")?;
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(ConstValue::Integer(n)),
+            Token::Str(s) => Ok(ConstValue::String(s)),
+            Token::Symbol("(") => {
+                let value = self.or_expr()?;
+                self.eat_symbol(&[")"]).ok_or("expected ')'")?;
+                Ok(value)
+            }
+            Token::Word(w) if w.eq_ignore_ascii_case("True") => Ok(ConstValue::Boolean(true)),
+            Token::Word(w) if w.eq_ignore_ascii_case("False") => Ok(ConstValue::Boolean(false)),
+            Token::Word(w) if w.eq_ignore_ascii_case("Nothing") => Ok(ConstValue::Nothing),
+            Token::Word(w) => Ok(self.constants.get(&w).cloned().unwrap_or(ConstValue::Nothing)),
+            other => Err(format!("unexpected {} in constant expression", describe(&other))),
+        }
+    }
+}
+
+/// `And`/`Or`/`Xor`: bitwise on two integers, logical otherwise.
+fn logical(left: &ConstValue, right: &ConstValue, bits: fn(i64, i64) -> i64, bools: fn(bool, bool) -> bool) -> ConstValue {
+    match (left, right) {
+        (ConstValue::Integer(a), ConstValue::Integer(b)) => ConstValue::Integer(bits(*a, *b)),
+        _ => ConstValue::Boolean(bools(left.is_true(), right.is_true())),
+    }
+}
+
+fn arithmetic(op: &str, left: &ConstValue, right: &ConstValue) -> Result<ConstValue, String> {
+    let (Some(a), Some(b)) = (left.as_integer(), right.as_integer()) else {
+        return Err(format!("'{}' needs numeric operands", op));
+    };
+    let result = match op {
+        "+" => a.checked_add(b),
+        "-" => a.checked_sub(b),
+        "*" => a.checked_mul(b),
+        _ if b == 0 => return Err("division by zero in constant expression".to_string()),
+        _ => a.checked_div(b),
+    };
+    result.map(ConstValue::Integer).ok_or_else(|| "constant expression overflows".to_string())
+}
@@ -0,0 +1,186 @@
+//! Tests for multi-error syntax diagnostics.
+
+use vybe_parser::diagnostics::codes;
+use vybe_parser::{check_syntax, parse_program_diagnostics, Diagnostic, Severity};
+
+fn lines(diags: &[Diagnostic]) -> Vec<usize> {
+    diags.iter().map(|d| d.span.start_line).collect()
+}
+
+#[test]
+fn test_clean_source_has_no_diagnostics() {
+    let code = r#"
+Module Module1
+    Sub Main()
+        Dim x As Integer = 1
+        If x > 0 Then
+            Console.WriteLine("positive")
+        End If
+    End Sub
+End Module
+"#;
+    assert!(parse_program_diagnostics(code).is_ok());
+    assert!(check_syntax(code).is_empty());
+}
+
+#[test]
+fn test_reports_every_syntax_error() {
+    let code = r#"
+Sub Main()
+    Dim x = = 2
+    Console.WriteLine("fine")
+    Dim y As = 5
+    Console.WriteLine("also fine")
+    z = 3 +
+End Sub
+"#;
+    let diags = check_syntax(code);
+    assert_eq!(lines(&diags), vec![3, 5, 7], "{:#?}", diags);
+    assert!(diags.iter().all(|d| d.severity == Severity::Error && d.code == codes::SYNTAX_ERROR));
+    assert_eq!(diags[0].span.start_col, 13);
+    assert!(diags[0].message.contains("found `=`"), "{}", diags[0].message);
+}
+
+#[test]
+fn test_unclosed_block_names_the_opener() {
+    let code = r#"
+Sub Main()
+    Dim x As Integer = 1
+    If x > 0 Then
+        Console.WriteLine("positive")
+End Sub
+
+Sub Other()
+    Dim y = = 1
+End Sub
+"#;
+    let diags = check_syntax(code);
+    assert_eq!(lines(&diags), vec![6, 9], "{:#?}", diags);
+    assert_eq!(diags[0].code, codes::UNCLOSED_BLOCK);
+    assert_eq!(diags[0].message, "expected `End If` to close `If` on line 4");
+    assert_eq!(diags[0].to_string(), "6:1: error[VB1001]: expected `End If` to close `If` on line 4");
+}
+
+#[test]
+fn test_unmatched_terminator_and_misplaced_clause() {
+    let code = r#"
+Sub Main()
+    For i = 1 To 3
+        Console.WriteLine(i)
+    Next
+    Next
+    Else
+End Sub
+"#;
+    let diags = check_syntax(code);
+    assert_eq!(lines(&diags), vec![6, 7], "{:#?}", diags);
+    assert_eq!(diags[0].code, codes::UNMATCHED_END);
+    assert_eq!(diags[0].message, "`Next` without a matching `For`");
+    assert_eq!(diags[1].code, codes::MISPLACED_CLAUSE);
+}
+
+#[test]
+fn test_missing_end_at_end_of_file() {
+    let code = "Class Foo\n    Sub Bar()\n    End Sub\n";
+    let diags = check_syntax(code);
+    assert_eq!(diags.len(), 1, "{:#?}", diags);
+    assert_eq!(diags[0].message, "expected `End Class` to close `Class` on line 1");
+}
+
+#[test]
+fn test_structure_aware_of_properties_and_lambdas() {
+    // Only the bad statement inside the lambda should be reported; the
+    // property accessors and the multi-line lambda must not confuse the block matcher.
+    let code = r#"
+Class Foo
+    Private _v As Integer
+    Public Property V() As Integer
+        Get
+            Return _v
+        End Get
+        Set(value As Integer)
+            _v = value
+        End Set
+    End Property
+    Public Property Auto As String
+
+    Sub Run()
+        Dim f = Function(x As Integer)
+                    Return x * * 2
+                End Function
+        Dim g = Function(x) x + 1
+    End Sub
+End Class
+"#;
+    let diags = check_syntax(code);
+    assert_eq!(lines(&diags), vec![16], "{:#?}", diags);
+    assert_eq!(diags[0].code, codes::SYNTAX_ERROR);
+}
+
+#[test]
+fn test_bad_property_header_reported_once() {
+    let code = r#"
+Class Foo
+    Public Property As Integer
+        Get
+            Return 1
+        End Get
+    End Property
+End Class
+"#;
+    let diags = check_syntax(code);
+    assert_eq!(lines(&diags), vec![4], "{:#?}", diags);
+}
+
+#[test]
+fn test_many_errors_in_a_large_file() {
+    let mut code = String::from("Module Big\n    Dim total As Integer = 0\n\n");
+    let mut expected = Vec::new();
+    for i in 0..40 {
+        code.push_str(&format!("    Sub Step{}()\n", i));
+        for j in 0..14 {
+            if j == 3 || j == 9 {
+                expected.push(code.lines().count() + 1);
+                code.push_str("        Dim x = = 2\n");
+            } else {
+                code.push_str(&format!("        total += {}\n", j));
+            }
+        }
+        code.push_str("    End Sub\n\n");
+    }
+    code.push_str("End Module\n");
+
+    let diags = check_syntax(&code);
+    assert_eq!(lines(&diags), expected);
+}
+
+#[test]
+fn test_truncated_diagnostics_say_so() {
+    // A procedure with too many errors is skipped; the next is still checked
+    let mut code = String::from("Sub Main()\n");
+    for _ in 0..40 {
+        code.push_str("    Dim x = = 2\n");
+    }
+    code.push_str("End Sub\n\nSub Other()\n    Dim y = = 3\nEnd Sub\n");
+    let diags = check_syntax(&code);
+    let (skipped, errors) = diags.iter().partition::<Vec<_>, _>(|d| d.code == codes::TOO_MANY_ERRORS);
+    assert_eq!(errors.len(), 31);
+    assert!(errors.iter().all(|d| d.code == codes::SYNTAX_ERROR));
+    assert_eq!(errors.last().unwrap().span.start_line, 45);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].severity, Severity::Warning);
+    assert_eq!(skipped[0].span.start_line, 32);
+    assert!(skipped[0].message.contains("skipped the rest of this procedure"));
+
+    // Too many outside any procedure stops checking
+    let mut code = String::from("Module M\n");
+    for _ in 0..40 {
+        code.push_str("    Dim x = = 2\n");
+    }
+    code.push_str("    Sub Other()\n        Dim y = = 3\n    End Sub\nEnd Module\n");
+    let diags = check_syntax(&code);
+    let (last, errors) = diags.split_last().unwrap();
+    assert_eq!(errors.len(), 30);
+    assert_eq!(last.code, codes::TOO_MANY_ERRORS);
+    assert_eq!(last.span.start_line, 32);
+}
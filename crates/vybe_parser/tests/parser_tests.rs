@@ -1,7 +1,8 @@
 
 use vybe_parser::parser::parse_program;
 // use vybe_parser::ast::*;
-use vybe_parser::ast::Declaration;
+use vybe_parser::ast::{CompareMode, ConversionKind, Declaration, ExprKind, MethodDecl, OptionSettings, Statement, TypeConstraint, VBType};
+use vybe_parser::ast::query::QueryClause;
 
 // Test for single argument implicit call
 #[test]
@@ -265,3 +266,312 @@ fn test_non_extension_function() {
     }).expect("No function declaration found");
     assert!(!func.is_extension, "Normal function should NOT be marked as extension");
 }
+
+#[test]
+fn test_attributes_captured_on_declarations() {
+    let code = r#"
+        <Assembly: AssemblyTitle("App")>
+        <Serializable>
+        Public Class Widget
+            <Category("Layout"), DefaultValue(10)>
+            Public Property Width As Integer = 10
+            <Obsolete("Use Resize", IsError:=True)>
+            Public Sub Grow(<Out> ByRef amount As Integer)
+            End Sub
+        End Class
+    "#;
+    let prog = parse_program(code).expect("Failed to parse attributes");
+    assert_eq!(prog.attributes.len(), 1);
+    assert_eq!(prog.attributes[0].name, "AssemblyTitle");
+    assert_eq!(prog.attributes[0].target.as_deref(), Some("Assembly"));
+    let class = prog.declarations.iter().find_map(|d| {
+        if let Declaration::Class(c) = d { Some(c) } else { None }
+    }).expect("No class declaration found");
+    assert!(class.attributes[0].is("Serializable"));
+    let names: Vec<&str> = class.fields[0].attributes.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, vec!["Category", "DefaultValue"]);
+    let MethodDecl::Sub(grow) = &class.methods[0] else { panic!("Expected Sub") };
+    let obsolete = &grow.attributes[0];
+    assert!(obsolete.is("Obsolete"));
+    assert_eq!(obsolete.arguments[0].kind, ExprKind::StringLiteral("Use Resize".to_string()));
+    assert!(matches!(&obsolete.arguments[1].kind, ExprKind::NamedArgument(name, _) if name.as_str() == "IsError"));
+    assert!(!grow.is_extension);
+    assert!(grow.parameters[0].attributes[0].is("Out"));
+}
+
+// ===== Generic declaration tests =====
+
+#[test]
+fn test_generic_class_type_parameters_and_constraints() {
+    let code = r#"
+        Public Class Repository(Of TKey, T As {Class, New})
+            Public Function Find(key As TKey) As T
+                Return Nothing
+            End Function
+        End Class
+    "#;
+    let prog = parse_program(code).expect("Failed to parse generic class");
+    let class = prog.declarations.iter().find_map(|d| {
+        if let Declaration::Class(c) = d { Some(c) } else { None }
+    }).expect("No class declaration found");
+    assert_eq!(class.name.as_str(), "Repository");
+    let names: Vec<&str> = class.type_parameters.iter().map(|tp| tp.name.as_str()).collect();
+    assert_eq!(names, vec!["TKey", "T"]);
+    assert!(class.type_parameters[0].constraints.is_empty());
+    assert_eq!(class.type_parameters[1].constraints, vec![TypeConstraint::Class, TypeConstraint::New]);
+}
+
+#[test]
+fn test_generic_method_and_explicit_type_arguments() {
+    let code = r#"
+        Function First(Of T As IComparable(Of T))(items As List(Of T)) As T
+            Return items(0)
+        End Function
+
+        Sub Main()
+            Dim x = First(Of Integer)(nums)
+            Dim y = helper.Convert(Of String)(x)
+            Dim z = Offset(Offset)
+        End Sub
+    "#;
+    let prog = parse_program(code).expect("Failed to parse generic function");
+    let func = prog.declarations.iter().find_map(|d| {
+        if let Declaration::Function(f) = d { Some(f) } else { None }
+    }).expect("No function declaration found");
+    assert_eq!(func.name.as_str(), "First");
+    assert_eq!(func.type_parameters.len(), 1);
+    assert_eq!(func.type_parameters[0].constraints, vec![TypeConstraint::Type(VBType::from_str("IComparable(Of T)"))]);
+    assert_eq!(func.parameters[0].param_type, Some(VBType::from_str("List(Of T)")));
+}
+
+#[test]
+fn test_generic_interface_structure_and_delegate() {
+    let code = r#"
+        Public Interface IProducer(Of Out T)
+            Function Produce() As T
+        End Interface
+
+        Public Structure Pair(Of TKey As Structure, TValue)
+            Public Key As TKey
+            Public Value As TValue
+        End Structure
+
+        Public Delegate Function Transformer(Of TIn, TOut)(value As TIn) As TOut
+    "#;
+    let prog = parse_program(code).expect("Failed to parse generic declarations");
+    for decl in &prog.declarations {
+        match decl {
+            Declaration::Interface(i) => assert_eq!(i.type_parameters[0].name.as_str(), "T"),
+            Declaration::Structure(s) => {
+                assert_eq!(s.type_parameters.len(), 2);
+                assert_eq!(s.type_parameters[0].constraints, vec![TypeConstraint::Structure]);
+            }
+            Declaration::Delegate(d) => assert_eq!(d.type_parameters.len(), 2),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_operator_declarations() {
+    let code = r#"
+        Public Structure Money
+            Public Amount As Decimal
+
+            Public Shared Operator +(a As Money, b As Money) As Money
+                Return New Money()
+            End Operator
+
+            Public Shared Operator <>(a As Money, b As Money) As Boolean
+                Return False
+            End Operator
+
+            Public Shared Narrowing Operator CType(m As Money) As Integer
+                Return 0
+            End Operator
+
+            Public Shared Operator IsTrue(m As Money) As Boolean
+                Return True
+            End Operator
+        End Structure
+    "#;
+    let prog = parse_program(code).expect("Failed to parse operator declarations");
+    let Some(Declaration::Structure(s)) = prog.declarations.first() else {
+        panic!("No structure declaration found");
+    };
+    let symbols: Vec<&str> = s.operators.iter().map(|op| op.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["+", "<>", "CType", "IsTrue"]);
+    assert_eq!(s.operators[0].parameters.len(), 2);
+    assert_eq!(s.operators[0].return_type, Some(VBType::from_str("Money")));
+    assert_eq!(s.operators[2].conversion, Some(ConversionKind::Narrowing));
+    assert_eq!(s.operators[3].body.len(), 1);
+    assert!(s.fields.iter().any(|f| f.name.as_str() == "Amount"));
+}
+
+#[test]
+fn test_iterator_function_with_yield() {
+    let code = r#"
+        Public Iterator Function Evens(max As Integer) As IEnumerable(Of Integer)
+            For i As Integer = 0 To max Step 2
+                Yield i
+            Next
+            If max > 100 Then Yield -1
+        End Function
+    "#;
+    let prog = parse_program(code).expect("Failed to parse iterator function");
+    let func = prog.declarations.iter().find_map(|d| {
+        if let Declaration::Function(f) = d { Some(f) } else { None }
+    }).expect("No function declaration found");
+    assert!(func.is_iterator, "Iterator modifier should be recorded");
+    let Some(Statement::For { body, .. }) = func.body.iter().find(|s| matches!(s, Statement::For { .. })) else {
+        panic!("No For loop in iterator body");
+    };
+    assert!(matches!(body[0], Statement::Yield(_)));
+    assert!(func.body.iter().any(|s| matches!(s, Statement::If { then_branch, .. } if matches!(then_branch[0], Statement::Yield(_)))));
+}
+
+#[test]
+fn test_option_statements() {
+    let code = "Option Strict On\r\noption explicit\r\nOption Compare Text\r\nOption Infer Off\r\n\r\nModule M\r\n    Sub Main()\r\n        For i As Integer = 1 To 3\r\n        Next\r\n    End Sub\r\nEnd Module\r\n";
+    let prog = parse_program(code).expect("Failed to parse Option statements");
+    assert_eq!(prog.options, OptionSettings {
+        explicit: Some(true),
+        strict: Some(true),
+        compare: Some(CompareMode::Text),
+        infer: Some(false),
+    });
+    let main = prog.declarations.iter().find_map(|d| {
+        if let Declaration::Sub(s) = d { Some(s) } else { None }
+    }).expect("No Sub found");
+    assert!(matches!(&main.body[0], Statement::For { var_type: Some(VBType::Integer), .. }));
+
+    let plain = parse_program("Module M\nEnd Module\n").expect("Failed to parse module");
+    assert_eq!(plain.options, OptionSettings::default());
+}
+
+#[test]
+fn test_query_clauses() {
+    let code = r#"
+        Sub Test()
+            Dim q = From o In orders
+                    Join c In customers On o.CustomerId Equals c.Id
+                    Order By c.Name Descending
+                    Group o By c.Name Into Total = Sum(o.Total), Count()
+                    Skip While Total < 10
+                    Select Name, Total Distinct
+        End Sub
+    "#;
+    let prog = parse_program(code).expect("Failed to parse query expression");
+    let sub = prog.declarations.iter().find_map(|d| {
+        if let Declaration::Sub(s) = d { Some(s) } else { None }
+    }).expect("No Sub found");
+    let Statement::Dim(decls) = &sub.body[0] else { panic!("Expected Dim") };
+    let Some(ExprKind::Query(query)) = decls[0].initializer.as_ref().map(|e| &e.kind) else { panic!("Expected query") };
+    let clauses: Vec<_> = query.body.clauses.iter().map(|c| match c {
+        QueryClause::Join(_) => "Join",
+        QueryClause::OrderBy(_) => "OrderBy",
+        QueryClause::GroupBy(g) if g.into.len() == 2 && g.keys[0].name.as_deref() == Some("Name") => "GroupBy",
+        QueryClause::SkipWhile(_) => "SkipWhile",
+        QueryClause::Select(fields) if fields.len() == 2 => "Select",
+        QueryClause::Distinct => "Distinct",
+        other => panic!("Unexpected clause {:?}", other),
+    }).collect();
+    // `Order By` after an expression must not read as `c.Id Or der`
+    assert_eq!(clauses, vec!["Join", "OrderBy", "GroupBy", "SkipWhile", "Select", "Distinct"]);
+}
+
+#[test]
+fn test_numeric_literals() {
+    let literal = |text: &str| {
+        let prog = parse_program(&format!("Sub Test()\n    x = {}\nEnd Sub\n", text))?;
+        let Some(Declaration::Sub(sub)) = prog.declarations.first() else { panic!("No Sub found") };
+        let Statement::Assignment { value, .. } = &sub.body[0] else { panic!("Expected assignment") };
+        Ok::<_, vybe_parser::ParseError>(value.kind.clone())
+    };
+    assert_eq!(literal("&H7F").unwrap(), ExprKind::IntegerLiteral(127));
+    assert_eq!(literal("&HFFFFFFFF").unwrap(), ExprKind::IntegerLiteral(-1));
+    assert_eq!(literal("&HFFFFFFFF00L").unwrap(), ExprKind::LongLiteral(0xFF_FFFF_FF00));
+    assert_eq!(literal("&O7_7").unwrap(), ExprKind::IntegerLiteral(63));
+    assert_eq!(literal("&B1000_0000S").unwrap(), ExprKind::ShortLiteral(128));
+    assert_eq!(literal("&HFFFFS").unwrap(), ExprKind::ShortLiteral(-1));
+    assert_eq!(literal("65535US").unwrap(), ExprKind::UShortLiteral(65535));
+    assert_eq!(literal("4_000_000_000UI").unwrap(), ExprKind::UIntegerLiteral(4_000_000_000));
+    assert_eq!(literal("&HFFFFFFFFFFFFFFFFUL").unwrap(), ExprKind::ULongLiteral(u64::MAX));
+    assert_eq!(literal("3000000000").unwrap(), ExprKind::LongLiteral(3_000_000_000));
+    assert_eq!(literal("2.5F").unwrap(), ExprKind::SingleLiteral(2.5));
+    assert_eq!(literal("1.10@").unwrap(), ExprKind::DecimalLiteral("1.10".to_string()));
+    assert_eq!(literal("5D").unwrap(), ExprKind::DecimalLiteral("5".to_string()));
+    assert_eq!(literal("1.5E3").unwrap(), ExprKind::DoubleLiteral(1500.0));
+    assert_eq!(literal("7R").unwrap(), ExprKind::DoubleLiteral(7.0));
+
+    for text in ["40000S", "&H1FFFFS", "99999999999999999999", "3000000000I", "1E999"] {
+        let err = literal(text).unwrap_err();
+        assert!(err.to_string().contains("Overflow"), "{} should overflow, got {}", text, err);
+    }
+}
+
+#[test]
+fn test_legacy_statements() {
+    let code = r#"
+Type Person
+    Name As String * 20
+    Scores(3) As Integer
+End Type
+
+Module M
+    Sub Main()
+        GoSub Work
+        On n GoSub Work, Done
+        Mid$(s, 3, 2) = "xy"
+        LSet s = "a"
+        Erase a, b
+        Open f For Random As #1 Len = 24
+        Get #1, 2, p
+        Put #1, , p
+Work:
+        Return
+Done:
+    End Sub
+End Module
+"#;
+    let prog = parse_program(code).expect("Failed to parse legacy statements");
+    let Some(Declaration::Structure(person)) = prog.declarations.first() else { panic!("Expected Type") };
+    assert_eq!(person.fields[0].var_type, Some(VBType::FixedString(20)));
+    assert!(person.fields[1].array_bounds.is_some());
+    let sub = prog.declarations.iter().find_map(|d| {
+        if let Declaration::Sub(s) = d { Some(s) } else { None }
+    }).expect("No Sub found");
+    let body = &sub.body;
+    assert!(matches!(&body[0], Statement::GoSub(label) if label == "Work"));
+    assert!(matches!(&body[1], Statement::OnGoTo { labels, gosub: true, .. } if labels.len() == 2));
+    assert!(matches!(&body[2], Statement::MidAssignment { length: Some(_), .. }));
+    assert!(matches!(&body[3], Statement::LSet { .. }));
+    assert!(matches!(&body[4], Statement::Erase(arrays) if arrays.len() == 2));
+    assert!(matches!(&body[5], Statement::Open { record_length: Some(_), .. }));
+    assert!(matches!(&body[6], Statement::GetFile { position: Some(_), .. }));
+    assert!(matches!(&body[7], Statement::PutFile { position: None, .. }));
+}
+
+#[test]
+fn test_xml_axis_properties() {
+    use vybe_parser::ast::xml::XmlAxis;
+    let code = r#"
+        Sub Test()
+            x = doc.<root>...<price>(0).Value
+            el.@<xml:lang> = "en"
+            y = a.@id = "1"
+        End Sub
+    "#;
+    let prog = parse_program(code).expect("Failed to parse XML axis properties");
+    let Some(Declaration::Sub(sub)) = prog.declarations.first() else { panic!("No Sub found") };
+    let Statement::Assignment { value, .. } = &sub.body[0] else { panic!("Expected assignment") };
+    let ExprKind::MemberAccess(indexed, _) = &value.kind else { panic!("Expected .Value") };
+    let ExprKind::MethodCall(descendants, _, _) = &indexed.kind else { panic!("Expected index") };
+    let ExprKind::XmlAxis(child, XmlAxis::Descendant, price) = &descendants.kind else { panic!("Expected ...<price>") };
+    assert_eq!(price.local, "price");
+    assert!(matches!(&child.kind, ExprKind::XmlAxis(_, XmlAxis::Child, root) if root.local == "root"));
+    let Statement::XmlAttributeAssignment { name, .. } = &sub.body[1] else { panic!("Expected attribute assignment") };
+    assert_eq!((name.prefix.as_deref(), name.local.as_str()), (Some("xml"), "lang"));
+    // `=` after an attribute axis on the right is a comparison
+    assert!(matches!(&sub.body[2], Statement::Assignment { value, .. } if matches!(value.kind, ExprKind::Equal(..))));
+}
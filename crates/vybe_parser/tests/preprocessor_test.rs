@@ -0,0 +1,126 @@
+//! Tests for conditional compilation: `#Const`, `#If` and `#Region`.
+
+use vybe_parser::diagnostics::codes;
+use vybe_parser::{check_syntax_with, parse_program_with, preprocess, ConditionalConstants, ConstValue, ParseError};
+
+fn active_lines(source: &str, constants: &ConditionalConstants) -> Vec<String> {
+    preprocess(source, constants).expect("preprocess failed")
+        .lines()
+        .map(|l| l.trim().to_string())
+        .collect()
+}
+
+#[test]
+fn test_inactive_branches_are_blanked_in_place() {
+    let code = "#Const Verbose = True\n#If DEBUG Then\na\n#ElseIf Verbose Then\nb\n#Else\nc\n#End If\nd\n";
+    assert_eq!(active_lines(code, &ConditionalConstants::new()), vec!["", "", "", "", "b", "", "", "", "d"]);
+    assert_eq!(active_lines(code, &ConditionalConstants::project_defaults()), vec!["", "", "a", "", "", "", "", "", "d"]);
+
+    let crlf = code.replace('\n', "\r\n");
+    let output = preprocess(&crlf, &ConditionalConstants::new()).unwrap();
+    assert_eq!(output.matches("\r\n").count(), 9);
+}
+
+#[test]
+fn test_nested_blocks_and_constant_expressions() {
+    let code = r#"#Const Level = 2
+#Const Edition = "Pro"
+#If Level >= 2 AndAlso Edition = "Pro" Then
+#If Not TRACE Then
+untraced
+#Else
+traced
+#End If
+#ElseIf Level = 2 Then
+never
+#End If
+#If (Level * 3 - 1) / 5 = 1 Or Missing Then ' comment
+math
+#End If
+#If Edition & "!" = "Pro!" Then
+concat
+#End If
+"#;
+    let kept: Vec<String> = active_lines(code, &ConditionalConstants::project_defaults())
+        .into_iter().filter(|l| !l.is_empty()).collect();
+    assert_eq!(kept, vec!["traced", "math", "concat"]);
+}
+
+#[test]
+fn test_const_in_inactive_branch_is_ignored() {
+    let code = "#If False Then\n#Const Flag = True\n#End If\n#If Flag Then\nflag\n#End If\n";
+    assert!(active_lines(code, &ConditionalConstants::new()).iter().all(|l| l.is_empty()));
+}
+
+#[test]
+fn test_regions_and_date_literals_pass_through() {
+    let code = r#"
+#Region "Helpers"
+Module M
+    Sub Main()
+        Dim d As Date = #1/15/2024#
+        Console.WriteLine(d)
+    End Sub
+End Module
+#End Region
+"#;
+    let program = parse_program_with(code, &ConditionalConstants::new()).expect("Parse error");
+    assert_eq!(program.declarations.len(), 1);
+}
+
+#[test]
+fn test_unbalanced_directives_are_errors() {
+    let cases = [
+        ("x\n#End If\n", 2, "'#End If' must be preceded"),
+        ("#If A Then\n#Else\n#Else\n#End If\n", 3, "cannot follow '#Else'"),
+        ("#ElseIf A Then\n", 1, "'#ElseIf' must be preceded"),
+        ("\n#If A Then\nx\n", 2, "must end with a matching '#End If'"),
+        ("#Region \"R\"\n", 1, "must end with a matching '#End Region'"),
+        ("#Const = 1\n", 1, "not a valid constant name"),
+        ("#If 1 + Then\n#End If\n", 1, "expression ends unexpectedly"),
+    ];
+    for (code, line, message) in cases {
+        match preprocess(code, &ConditionalConstants::new()) {
+            Err(ParseError::Directive { message: m, span }) => {
+                assert_eq!(span.start_line, line, "{code:?}");
+                assert!(m.contains(message), "{code:?}: {m}");
+            }
+            other => panic!("{code:?}: expected a directive error, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_diagnostics_skip_inactive_code() {
+    let code = r#"
+Module M
+    Sub Main()
+#If LEGACY Then
+        Dim x As = 5
+#End If
+        Console.WriteLine("ok")
+    End Sub
+End Module
+"#;
+    let mut constants = ConditionalConstants::new();
+    assert!(check_syntax_with(code, &constants).is_empty());
+
+    constants.define("LEGACY", ConstValue::Boolean(true));
+    let diags = check_syntax_with(code, &constants);
+    assert_eq!(diags.len(), 1);
+    assert_eq!(diags[0].span.start_line, 5);
+
+    let diags = check_syntax_with("#If A Then\n", &constants);
+    assert_eq!(diags[0].code, codes::DIRECTIVE_ERROR);
+}
+
+#[test]
+fn test_define_constants_list() {
+    let mut constants = ConditionalConstants::new();
+    constants.define_list("$(DefineConstants);DEBUG,Edition=\"Pro\", Level=2 ,Flag=False");
+    assert_eq!(constants.get("debug"), Some(&ConstValue::Boolean(true)));
+    assert_eq!(constants.get("EDITION"), Some(&ConstValue::String("Pro".to_string())));
+    assert_eq!(constants.get("Level"), Some(&ConstValue::Integer(2)));
+    assert_eq!(constants.get("Flag"), Some(&ConstValue::Boolean(false)));
+    assert_eq!(constants.iter().count(), 4);
+}
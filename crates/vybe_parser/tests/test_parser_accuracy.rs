@@ -5,7 +5,7 @@
 use vybe_parser::parse_program;
 use vybe_parser::ast::decl::{Declaration, Visibility, Parameter};
 use vybe_parser::ast::stmt::{Statement, LoopConditionType, CompoundOp};
-use vybe_parser::ast::expr::Expression;
+use vybe_parser::ast::expr::ExprKind;
 
 // ============================================================
 // Helper to extract first Sub's body statements
@@ -14,7 +14,7 @@ fn parse_sub_body(code: &str) -> Vec<Statement> {
     let prog = parse_program(code).expect("Parse failed");
     for d in &prog.declarations {
         if let Declaration::Sub(s) = d {
-            return s.body.clone();
+            return s.body.to_vec();
         }
     }
     panic!("No Sub found in code");
@@ -693,10 +693,10 @@ End Sub
     let has_if = stmts.iter().any(|s| matches!(s, Statement::If { .. }));
     assert!(has_if, "Should have an If statement. Got: {:?}", stmts);
     if let Statement::If { condition, then_branch, .. } = &stmts[1] {
-        if let Expression::TypeOf { expr, type_name } = condition {
+        if let ExprKind::TypeOf { expr, type_name } = &condition.kind {
             assert_eq!(type_name, "String", "type_name should be 'String', got: {:?}", type_name);
             // Check the inner expression is the variable 's'
-            assert!(matches!(expr.as_ref(), Expression::Variable(_)),
+            assert!(matches!(expr.kind, ExprKind::Variable(_)),
                 "TypeOf inner expr should be a Variable. Got: {:?}", expr);
         } else {
             panic!("If condition should be TypeOf expression. Got: {:?}", condition);
@@ -714,3 +714,51 @@ End Sub
 "#);
     assert!(!stmts.is_empty(), "Should parse empty array literal");
 }
+
+// ============================================================
+// Source spans
+// ============================================================
+
+#[test]
+fn test_statement_and_declaration_spans() {
+    let sub = parse_first_sub(r#"
+Sub Main()
+    Dim x As Integer = 1
+    If x > 0 Then
+        x = 2
+    End If
+End Sub
+"#);
+    assert_eq!((sub.span.start_line, sub.span.start_col), (2, 1));
+    assert_eq!(sub.span.end_line, 7);
+    assert!(sub.body.iter_spanned().all(|(_, span)| span.start_line > 0));
+    assert_eq!(sub.body.span_at(0).to_string(), "3:5");
+    assert_eq!(sub.body.span_at(1).to_string(), "4:5");
+    if let Statement::If { then_branch, .. } = &sub.body[1] {
+        assert_eq!(then_branch.span_at(0).to_string(), "5:9");
+    } else {
+        panic!("Expected If statement. Got: {:?}", sub.body[1]);
+    }
+}
+
+#[test]
+fn test_parse_program_named_sets_source_file() {
+    let prog = vybe_parser::parse_program_named(r#"
+Public Class Foo
+    Public Sub Bar()
+    End Sub
+End Class
+"#, "Foo.vb").expect("Parse failed");
+    assert_eq!(prog.source_file.as_deref(), Some("Foo.vb"));
+    if let Declaration::Class(c) = &prog.declarations[0] {
+        assert_eq!(c.span.start_line, 2);
+        if let vybe_parser::ast::decl::MethodDecl::Sub(s) = &c.methods[0] {
+            assert_eq!(s.source_file.as_deref(), Some("Foo.vb"));
+            assert_eq!(s.span.start_line, 3);
+        } else {
+            panic!("Expected Sub method");
+        }
+    } else {
+        panic!("Expected Class declaration");
+    }
+}
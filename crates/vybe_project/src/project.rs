@@ -1,4 +1,5 @@
 use vybe_forms::Form;
+use vybe_parser::{ConditionalConstants, OptionSettings};
 use serde::{Deserialize, Serialize};
 use crate::resources::ResourceManager;
 
@@ -154,6 +155,12 @@ pub struct Project {
     /// Names of referenced sub-projects (from <ProjectReference> in .vbproj)
     #[serde(default)]
     pub project_references: Vec<String>,
+    /// Project-wide `Option Explicit/Strict/Compare/Infer` (`<OptionStrict>` etc. in .vbproj)
+    #[serde(default)]
+    pub options: OptionSettings,
+    /// Constants visible to `#If`: DEBUG, TRACE and `<DefineConstants>`
+    #[serde(default = "ConditionalConstants::project_defaults")]
+    pub define_constants: ConditionalConstants,
     /// Deprecated: single resource manager. Kept for backward compat deserialization.
     #[serde(skip_serializing)]
     pub resources: ResourceManager,
@@ -175,6 +182,8 @@ impl Project {
             code_files: Vec::new(),
             resource_files: Vec::new(),
             project_references: Vec::new(),
+            options: OptionSettings::default(),
+            define_constants: ConditionalConstants::project_defaults(),
             resources: ResourceManager::new(),
         }
     }
@@ -266,6 +275,10 @@ impl<'de> Deserialize<'de> for Project {
             #[serde(default)]
             resource_files: Vec<ResourceManager>,
             #[serde(default)]
+            options: OptionSettings,
+            #[serde(default = "ConditionalConstants::project_defaults")]
+            define_constants: ConditionalConstants,
+            #[serde(default)]
             resources: ResourceManager,
         }
 
@@ -305,6 +318,8 @@ impl<'de> Deserialize<'de> for Project {
             code_files,
             resource_files,
             project_references: Vec::new(),
+            options: helper.options,
+            define_constants: helper.define_constants,
             resources: helper.resources,
         })
     }
@@ -9,6 +9,7 @@ use std::fs;
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 use crate::encoding::read_text_file;
+use vybe_parser::{CompareMode, ConditionalConstants, ConstValue, OptionSettings};
 
 pub fn save_project_auto(project: &Project, path: impl AsRef<Path>) -> SaveResult<()> {
     let path = path.as_ref();
@@ -31,6 +32,37 @@ pub fn save_project_vbproj(project: &Project, path: impl AsRef<Path>) -> SaveRes
     xml.push_str(&format!("    <AssemblyName>{}</AssemblyName>\n", project.name));
     xml.push_str("    <TargetFramework>net6.0-windows</TargetFramework>\n");
     xml.push_str("    <UseWindowsForms>true</UseWindowsForms>\n");
+    let on_off = |on: bool| if on { "On" } else { "Off" };
+    if let Some(explicit) = project.options.explicit {
+        xml.push_str(&format!("    <OptionExplicit>{}</OptionExplicit>\n", on_off(explicit)));
+    }
+    if let Some(strict) = project.options.strict {
+        xml.push_str(&format!("    <OptionStrict>{}</OptionStrict>\n", on_off(strict)));
+    }
+    if let Some(compare) = project.options.compare {
+        let mode = match compare {
+            CompareMode::Binary => "Binary",
+            CompareMode::Text => "Text",
+        };
+        xml.push_str(&format!("    <OptionCompare>{}</OptionCompare>\n", mode));
+    }
+    if let Some(infer) = project.options.infer {
+        xml.push_str(&format!("    <OptionInfer>{}</OptionInfer>\n", on_off(infer)));
+    }
+    let defined = |name: &str| project.define_constants.get(name) == Some(&ConstValue::Boolean(true));
+    if !defined("DEBUG") {
+        xml.push_str("    <DefineDebug>false</DefineDebug>\n");
+    }
+    if !defined("TRACE") {
+        xml.push_str("    <DefineTrace>false</DefineTrace>\n");
+    }
+    let custom: Vec<String> = project.define_constants.iter()
+        .filter(|(name, _)| !(["DEBUG", "TRACE"].iter().any(|d| d.eq_ignore_ascii_case(name)) && defined(name)))
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    if !custom.is_empty() {
+        xml.push_str(&format!("    <DefineConstants>{}</DefineConstants>\n", quick_xml::escape::escape(&custom.join(","))));
+    }
     match &project.startup_object {
         crate::project::StartupObject::Form(form_name) => {
             xml.push_str(&format!("    <StartupObject>{}.{}</StartupObject>\n", project.name, form_name));
@@ -92,6 +124,15 @@ pub fn load_project_auto(path: impl AsRef<Path>) -> SaveResult<Project> {
     Err(SaveError::Parse("Only .vbproj files are supported".to_string()))
 }
 
+/// `On`/`Off` from an `<OptionStrict>`-style tag.
+fn parse_on_off(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
 pub fn load_project_vbproj(path: impl AsRef<Path>) -> SaveResult<Project> {
     let path = path.as_ref();
     let content = read_text_file(path)?;
@@ -111,6 +152,8 @@ pub fn load_project_vbproj(path: impl AsRef<Path>) -> SaveResult<Project> {
     let mut module_paths = Vec::new();
     let mut resource_paths: Vec<(String, Option<String>)> = Vec::new(); // (resx_path, dependent_upon)
     let mut project_ref_paths: Vec<String> = Vec::new(); // relative paths to referenced .vbproj files
+    let mut options = OptionSettings::default();
+    let mut define_constants = ConditionalConstants::project_defaults();
 
     // State tracking
     let mut current_file_path = String::new();
@@ -125,7 +168,8 @@ pub fn load_project_vbproj(path: impl AsRef<Path>) -> SaveResult<Project> {
         match reader.read_event() {
             Ok(Event::Start(ref e)) => {
                 let name = e.name().as_ref().to_vec();
-                if name == b"AssemblyName" || name == b"StartupObject" || name == b"SubType" || name == b"DependentUpon" {
+                if name == b"AssemblyName" || name == b"StartupObject" || name == b"SubType" || name == b"DependentUpon"
+                    || name.starts_with(b"Option") || name.starts_with(b"Define") {
                     capture_text = true;
                     current_tag = name.clone();
                 } else if name == b"Compile" {
@@ -233,6 +277,27 @@ pub fn load_project_vbproj(path: impl AsRef<Path>) -> SaveResult<Project> {
                                 // My.MyApplication — mark for Application.myapp lookup
                                 startup_object = Some("__MY_APPLICATION__".to_string());
                             }
+                        } else if current_tag == b"OptionExplicit" {
+                            options.explicit = parse_on_off(&txt);
+                        } else if current_tag == b"OptionStrict" {
+                            options.strict = parse_on_off(&txt);
+                        } else if current_tag == b"OptionInfer" {
+                            options.infer = parse_on_off(&txt);
+                        } else if current_tag == b"OptionCompare" {
+                            options.compare = match txt.to_lowercase().as_str() {
+                                "binary" => Some(CompareMode::Binary),
+                                "text" => Some(CompareMode::Text),
+                                _ => None,
+                            };
+                        } else if current_tag == b"DefineConstants" {
+                            define_constants.define_list(&txt);
+                        } else if current_tag == b"DefineDebug" || current_tag == b"DefineTrace" {
+                            let name = if current_tag == b"DefineDebug" { "DEBUG" } else { "TRACE" };
+                            if txt.trim().eq_ignore_ascii_case("true") {
+                                define_constants.define(name, ConstValue::Boolean(true));
+                            } else {
+                                define_constants.remove(name);
+                            }
                         } else if current_tag == b"SubType" && in_compile {
                             current_subtype = txt;
                         } else if current_tag == b"DependentUpon" && (in_compile || in_embedded_resource) {
@@ -244,7 +309,8 @@ pub fn load_project_vbproj(path: impl AsRef<Path>) -> SaveResult<Project> {
             Ok(Event::End(ref e)) => {
                 let qname = e.name();
                 let name = qname.as_ref();
-                if name == b"AssemblyName" || name == b"StartupObject" || name == b"SubType" || name == b"DependentUpon" {
+                if name == b"AssemblyName" || name == b"StartupObject" || name == b"SubType" || name == b"DependentUpon"
+                    || name.starts_with(b"Option") || name.starts_with(b"Define") {
                     capture_text = false;
                 } else if name == b"Compile" {
                     in_compile = false;
@@ -285,6 +351,8 @@ pub fn load_project_vbproj(path: impl AsRef<Path>) -> SaveResult<Project> {
     }
 
     let mut project = Project::new(&project_name);
+    project.options = options;
+    project.define_constants = define_constants;
     
     // Set startup object based on what was parsed
     if let Some(ref startup_str) = startup_object {
@@ -29,3 +29,58 @@ fn test_load_vbproj() {
     assert_eq!(project.code_files.len(), 1);
     assert_eq!(project.code_files[0].name, "Module1");
 }
+
+#[test]
+fn test_vbproj_option_defaults_round_trip() {
+    use vybe_parser::{CompareMode, OptionSettings};
+    use vybe_project::{load_project_vbproj, save_project_vbproj, Project};
+
+    let dir = std::env::temp_dir().join(format!("vybe_options_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Options.vbproj");
+
+    let mut project = Project::new("Options");
+    project.options = OptionSettings {
+        explicit: Some(true),
+        strict: Some(true),
+        compare: Some(CompareMode::Text),
+        infer: Some(false),
+    };
+    save_project_vbproj(&project, &path).expect("Failed to save project");
+
+    let xml = std::fs::read_to_string(&path).unwrap();
+    assert!(xml.contains("<OptionStrict>On</OptionStrict>"));
+    assert!(xml.contains("<OptionCompare>Text</OptionCompare>"));
+
+    let loaded = load_project_vbproj(&path).expect("Failed to load project");
+    assert_eq!(loaded.options, project.options);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_vbproj_define_constants_round_trip() {
+    use vybe_parser::{ConditionalConstants, ConstValue};
+    use vybe_project::{load_project_vbproj, save_project_vbproj, Project};
+
+    let dir = std::env::temp_dir().join(format!("vybe_defines_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Defines.vbproj");
+
+    let mut project = Project::new("Defines");
+    assert_eq!(project.define_constants, ConditionalConstants::project_defaults());
+    project.define_constants.remove("TRACE");
+    project.define_constants.define("Edition", ConstValue::String("Pro".to_string()));
+    project.define_constants.define("Level", ConstValue::Integer(2));
+    save_project_vbproj(&project, &path).expect("Failed to save project");
+
+    let xml = std::fs::read_to_string(&path).unwrap();
+    assert!(xml.contains("<DefineTrace>false</DefineTrace>"));
+    assert!(!xml.contains("<DefineDebug>"));
+
+    let loaded = load_project_vbproj(&path).expect("Failed to load project");
+    assert_eq!(loaded.define_constants.get("DEBUG"), Some(&ConstValue::Boolean(true)));
+    assert_eq!(loaded.define_constants.get("TRACE"), None);
+    assert_eq!(loaded.define_constants.get("Edition"), Some(&ConstValue::String("Pro".to_string())));
+    assert_eq!(loaded.define_constants.get("Level"), Some(&ConstValue::Integer(2)));
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,103 @@
+//! Timings for variable-heavy programs under both engines.
+//!
+//! Run with `cargo bench -p vybe_runtime`; pass a name to run only the
+//! programs containing it, e.g. `cargo bench -p vybe_runtime -- loops`.
+
+use std::time::{Duration, Instant};
+
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+use vybe_runtime::{Engine, Interpreter};
+
+const LOOPS: &str = r#"
+Module M
+    Sub Main()
+        Dim total As Long = 0
+        For i = 1 To 200000
+            If i Mod 3 = 0 OrElse i Mod 5 = 0 Then
+                total += i
+            End If
+        Next
+        Dim k = 0
+        Do While k < 100000
+            k = k + 1
+        Loop
+    End Sub
+End Module
+"#;
+
+const CALLS: &str = r#"
+Module M
+    Function Fib(n As Integer) As Integer
+        If n < 2 Then Return n
+        Return Fib(n - 1) + Fib(n - 2)
+    End Function
+
+    Sub Main()
+        Dim result = Fib(17)
+    End Sub
+End Module
+"#;
+
+const STRINGS: &str = r#"
+Module M
+    Sub Main()
+        Dim s = ""
+        Dim count = 0
+        For i = 1 To 20000
+            s = "item" & i
+            If s.Length > 8 Then count += 1
+        Next
+    End Sub
+End Module
+"#;
+
+const FIELDS: &str = r#"
+Class Counter
+    Private total As Integer
+    Private steps As Integer
+
+    Public Sub Run(count As Integer)
+        For i = 1 To count
+            total = total + i Mod 7
+            steps += 1
+        Next
+    End Sub
+End Class
+
+Module M
+    Sub Main()
+        Dim c As New Counter()
+        c.Run(100000)
+    End Sub
+End Module
+"#;
+
+const PROGRAMS: &[(&str, &str)] = &[("loops", LOOPS), ("calls", CALLS), ("strings", STRINGS), ("fields", FIELDS)];
+
+const RUNS: usize = 5;
+
+/// The fastest of a few runs of `Main`, leaving out parsing and setup.
+fn time(code: &str, engine: Engine) -> Duration {
+    let program = parse_program(code).expect("Parse error");
+    (0..RUNS).map(|_| {
+        let mut interp = Interpreter::new();
+        interp.engine = engine;
+        interp.run(&program).expect("run");
+        let start = Instant::now();
+        interp.call_procedure(&Identifier::new("Main"), &[]).expect("Main");
+        start.elapsed()
+    }).min().unwrap_or_default()
+}
+
+fn main() {
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    for (name, code) in PROGRAMS {
+        if filter.as_ref().is_some_and(|f| !name.contains(f.as_str())) {
+            continue;
+        }
+        for engine in [Engine::TreeWalker, Engine::Bytecode] {
+            println!("{:<8} {:<10} {:>10.2?}", name, format!("{:?}", engine), time(code, engine));
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Named arguments: `MsgBox("Saved", Title:="App")`, `OpenFile(path,
+//! readOnly:=True)`.
+//!
+//! A call that names any of its arguments is rearranged into plain
+//! positional form before the usual binding runs. Positional arguments go
+//! to the parameter in their position, named ones to the parameter of that
+//! name (case-insensitively). A positional argument may follow named ones
+//! only while every named argument so far sits in its own position, as in
+//! VB 15.5. The result lists the arguments in parameter order up to the
+//! last one supplied; a gap left by an omitted Optional parameter is filled
+//! with its default, and omitted trailing parameters are left to the callee.
+
+use vybe_parser::ast::decl::Parameter;
+use vybe_parser::{ExprKind, Expression};
+
+use crate::value::RuntimeError;
+
+/// A parameter as argument matching sees it: its name and whether it may
+/// be omitted.
+pub type ParamInfo<'a> = (&'a str, bool);
+
+/// Whether any argument of a call is named.
+pub fn has_named(args: &[Expression]) -> bool {
+    args.iter().any(|a| matches!(&a.kind, ExprKind::NamedArgument(..)))
+}
+
+/// The name of each argument of a call, None for positional ones.
+pub fn argument_names(args: &[Expression]) -> Vec<Option<String>> {
+    args.iter().map(|a| match &a.kind {
+        ExprKind::NamedArgument(name, _) => Some(name.as_str().to_string()),
+        _ => None,
+    }).collect()
+}
+
+/// The arguments of a call with the names taken off, paired with them.
+pub fn split(args: &[Expression]) -> Vec<(Option<String>, Expression)> {
+    args.iter().map(|a| match &a.kind {
+        ExprKind::NamedArgument(name, value) => (Some(name.as_str().to_string()), (**value).clone()),
+        _ => (None, a.clone()),
+    }).collect()
+}
+
+/// The parameters of a user procedure as argument matching sees them.
+pub fn user_params(params: &[Parameter]) -> Vec<ParamInfo<'_>> {
+    params.iter().map(|p| (p.name.as_str(), p.is_optional || p.is_param_array)).collect()
+}
+
+/// `args` put in parameter order for a call to `procedure`. `default`
+/// gives the value of an omitted Optional parameter that has to be filled
+/// in because a later one was supplied.
+pub fn arrange<T>(
+    procedure: &str,
+    params: &[ParamInfo],
+    args: Vec<(Option<String>, T)>,
+    mut default: impl FnMut(usize) -> Result<T, RuntimeError>,
+) -> Result<Vec<T>, RuntimeError> {
+    let mut slots: Vec<Option<T>> = params.iter().map(|_| None).collect();
+    let mut extra = Vec::new();
+    let mut out_of_position = false;
+    for (i, (name, value)) in args.into_iter().enumerate() {
+        let position = match name {
+            Some(name) => {
+                let Some(p) = params.iter().position(|(n, _)| n.eq_ignore_ascii_case(&name)) else {
+                    return Err(RuntimeError::Custom(format!("'{}' is not a parameter of '{}'.", name, procedure)));
+                };
+                out_of_position |= p != i;
+                p
+            }
+            None if out_of_position => {
+                return Err(RuntimeError::Custom("Named argument expected.".to_string()));
+            }
+            None if i >= params.len() => {
+                // The rest of a ParamArray
+                extra.push(value);
+                continue;
+            }
+            None => i,
+        };
+        if slots[position].is_some() {
+            return Err(RuntimeError::Custom(format!(
+                "Parameter '{}' already has a matching argument.", params[position].0)));
+        }
+        slots[position] = Some(value);
+    }
+    if !extra.is_empty() && !params.last().is_some_and(|(_, optional)| *optional) {
+        return Err(RuntimeError::Custom(format!("Too many arguments to '{}'.", procedure)));
+    }
+    if let Some(p) = params.iter().zip(&slots).position(|((_, optional), slot)| slot.is_none() && !optional) {
+        return Err(RuntimeError::Custom(format!(
+            "Argument not specified for parameter '{}' of '{}'.", params[p].0, procedure)));
+    }
+
+    let supplied = slots.iter().rposition(Option::is_some).map_or(0, |p| p + 1);
+    let mut arranged = Vec::with_capacity(supplied + extra.len());
+    for (p, slot) in slots.into_iter().take(supplied).enumerate() {
+        arranged.push(match slot {
+            Some(value) => value,
+            None => default(p)?,
+        });
+    }
+    arranged.extend(extra);
+    Ok(arranged)
+}
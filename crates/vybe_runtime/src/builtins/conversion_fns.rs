@@ -1,3 +1,4 @@
+use crate::integral::{convert, IntType};
 use crate::value::{RuntimeError, Value};
 use chrono::{NaiveDate, NaiveTime, NaiveDateTime};
 
@@ -50,8 +51,7 @@ pub fn cint_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CInt requires exactly one argument".to_string()));
     }
-    let val = args[0].as_integer()?;
-    Ok(Value::Integer(val))
+    convert(&args[0], IntType::Integer)
 }
 
 pub fn cdbl_fn(args: &[Value]) -> Result<Value, RuntimeError> {
@@ -75,16 +75,14 @@ pub fn clng_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CLng requires exactly one argument".to_string()));
     }
-    let val = args[0].as_long()?;
-    Ok(Value::Long(val))
+    convert(&args[0], IntType::Long)
 }
 
 pub fn cbyte_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CByte requires exactly one argument".to_string()));
     }
-    let val = args[0].as_byte()?;
-    Ok(Value::Byte(val))
+    convert(&args[0], IntType::Byte)
 }
 
 pub fn cchar_fn(args: &[Value]) -> Result<Value, RuntimeError> {
@@ -201,12 +199,12 @@ pub fn cdate_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
-/// CDec(expression) - Converts to Decimal (maps to Double)
+/// CDec(expression) - Converts to Decimal
 pub fn cdec_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CDec requires exactly one argument".to_string()));
     }
-    Ok(Value::Double(args[0].as_double()?))
+    Ok(Value::Decimal(crate::decimal::to_decimal(&args[0])?))
 }
 
 /// Asc(string) returns numeric code; ChrW(code) returns Unicode char
@@ -215,9 +213,8 @@ pub fn formatnumber_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.is_empty() || args.len() > 2 {
         return Err(RuntimeError::Custom("FormatNumber requires 1 or 2 arguments".to_string()));
     }
-    let d = args[0].as_double()?;
     let decimals = if args.len() >= 2 { args[1].as_integer()?.max(0) as usize } else { 2 };
-    Ok(Value::String(format!("{:.prec$}", d, prec = decimals)))
+    Ok(Value::String(crate::decimal::format_fixed(&args[0], decimals)?))
 }
 
 /// FormatCurrency(number[, decimals]) - Formats as currency
@@ -225,9 +222,8 @@ pub fn formatcurrency_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.is_empty() || args.len() > 2 {
         return Err(RuntimeError::Custom("FormatCurrency requires 1 or 2 arguments".to_string()));
     }
-    let d = args[0].as_double()?;
     let decimals = if args.len() >= 2 { args[1].as_integer()?.max(0) as usize } else { 2 };
-    Ok(Value::String(format!("${:.prec$}", d, prec = decimals)))
+    Ok(Value::String(format!("${}", crate::decimal::format_fixed(&args[0], decimals)?)))
 }
 
 /// FormatPercent(number[, decimals]) - Formats as percentage
@@ -254,8 +250,7 @@ pub fn cshort_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CShort requires exactly one argument".to_string()));
     }
-    let val = args[0].as_integer()? as i16;
-    Ok(Value::Integer(val as i32))
+    convert(&args[0], IntType::Short)
 }
 
 /// CUShort(expression) - Convert to UShort (16-bit unsigned integer)
@@ -263,8 +258,7 @@ pub fn cushort_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CUShort requires exactly one argument".to_string()));
     }
-    let val = args[0].as_integer()?.max(0) as u16;
-    Ok(Value::Integer(val as i32))
+    convert(&args[0], IntType::UShort)
 }
 
 /// CUInt(expression) - Convert to UInteger (32-bit unsigned integer)
@@ -272,8 +266,7 @@ pub fn cuint_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CUInt requires exactly one argument".to_string()));
     }
-    let val = args[0].as_integer()?.max(0) as u32;
-    Ok(Value::Long(val as i64))
+    convert(&args[0], IntType::UInteger)
 }
 
 /// CULng(expression) - Convert to ULong (64-bit unsigned integer)
@@ -281,8 +274,7 @@ pub fn culng_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CULng requires exactly one argument".to_string()));
     }
-    let val = args[0].as_long()?.max(0) as u64;
-    Ok(Value::Long(val as i64))
+    convert(&args[0], IntType::ULong)
 }
 
 /// CSByte(expression) - Convert to Signed Byte (-128 to 127)
@@ -290,11 +282,7 @@ pub fn csbyte_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("CSByte requires exactly one argument".to_string()));
     }
-    let val = args[0].as_integer()?;
-    if val < i8::MIN as i32 || val > i8::MAX as i32 {
-        return Err(RuntimeError::Custom("Overflow in CSByte conversion".to_string()));
-    }
-    Ok(Value::Integer(val))
+    convert(&args[0], IntType::SByte)
 }
 
 /// AscW(string) - Returns Unicode code point of first character
@@ -6,7 +6,8 @@ pub fn isnumeric_fn(args: &[Value]) -> Result<Value, RuntimeError> {
         return Err(RuntimeError::Custom("IsNumeric requires exactly one argument".to_string()));
     }
     let result = match &args[0] {
-        Value::Integer(_) | Value::Long(_) | Value::Single(_) | Value::Double(_) | Value::Byte(_) | Value::Date(_) => true,
+        Value::Integer(_) | Value::Long(_) | Value::Single(_) | Value::Double(_) | Value::Decimal(_) | Value::Byte(_) | Value::Date(_) => true,
+        Value::SByte(_) | Value::Short(_) | Value::UShort(_) | Value::UInteger(_) | Value::ULong(_) => true,
         Value::String(s) => s.trim().parse::<f64>().is_ok(),
         Value::Boolean(_) => true,
         _ => false,
@@ -69,16 +70,23 @@ pub fn typename_fn(args: &[Value]) -> Result<Value, RuntimeError> {
         Value::Long(_) => "Long",
         Value::Single(_) => "Single",
         Value::Double(_) => "Double",
+        Value::Decimal(_) => "Decimal",
         Value::Date(_) => "Date",
         Value::String(_) => "String",
         Value::Boolean(_) => "Boolean",
         Value::Byte(_) => "Byte",
+        Value::SByte(_) => "SByte",
+        Value::Short(_) => "Short",
+        Value::UShort(_) => "UShort",
+        Value::UInteger(_) => "UInteger",
+        Value::ULong(_) => "ULong",
         Value::Char(_) => "Char",
         Value::Array(_) => "Variant()",
         Value::Nothing => "Nothing",
         Value::Object(obj_ref) => {
             let borrowed = obj_ref.borrow();
-            return Ok(Value::String(borrowed.class_name.clone()));
+            let type_args = crate::generics::instance_type_args(&borrowed);
+            return Ok(Value::String(crate::generics::display_name(&borrowed.class_name, &type_args)));
         }
         Value::Collection(_) => "Collection",
         Value::Queue(_) => "Queue",
@@ -104,10 +112,13 @@ pub fn vartype_fn(args: &[Value]) -> Result<Value, RuntimeError> {
         Value::Long(_) => 3,    // vbLong
         Value::Single(_) => 4,  // vbSingle
         Value::Double(_) => 5,  // vbDouble
+        Value::Decimal(_) => 14, // vbDecimal
         Value::Date(_) => 7,    // vbDate
         Value::String(_) => 8,  // vbString
         Value::Boolean(_) => 11, // vbBoolean
         Value::Byte(_) => 17,   // vbByte
+        Value::Short(_) => 2,   // vbInteger is 16 bits
+        Value::SByte(_) | Value::UShort(_) | Value::UInteger(_) | Value::ULong(_) => 9, // no VB6 equivalent
         Value::Char(_) => 18,   // vbChar
         Value::Object(_) => 9,  // vbObject
         Value::Collection(_) => 9, // vbObject
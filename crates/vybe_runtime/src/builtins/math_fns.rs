@@ -1,3 +1,5 @@
+use crate::decimal::Rounding;
+use crate::integral::{int_value, IntType};
 use crate::value::{RuntimeError, Value};
 
 /// Abs(number) - Returns absolute value
@@ -5,11 +7,14 @@ pub fn abs_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("Abs requires exactly one argument".to_string()));
     }
+    if let (Some(ty), Some(n)) = (IntType::of(&args[0]), int_value(&args[0])) {
+        // Math.Abs(Integer.MinValue) overflows
+        return ty.make(n.abs());
+    }
     match &args[0] {
-        Value::Integer(i) => Ok(Value::Integer(i.abs())),
-        Value::Long(l) => Ok(Value::Long(l.abs())),
         Value::Single(f) => Ok(Value::Single(f.abs())),
         Value::Double(d) => Ok(Value::Double(d.abs())),
+        Value::Decimal(d) => Ok(Value::Decimal(d.abs())),
         _ => {
             let d = args[0].as_double()?;
             Ok(Value::Double(d.abs()))
@@ -22,6 +27,9 @@ pub fn int_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("Int requires exactly one argument".to_string()));
     }
+    if let Value::Decimal(d) = &args[0] {
+        return Ok(Value::Decimal(d.round(0, Rounding::Down)));
+    }
     let d = args[0].as_double()?;
     Ok(Value::Integer(d.floor() as i32))
 }
@@ -31,6 +39,9 @@ pub fn fix_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("Fix requires exactly one argument".to_string()));
     }
+    if let Value::Decimal(d) = &args[0] {
+        return Ok(Value::Decimal(d.round(0, Rounding::ToZero)));
+    }
     let d = args[0].as_double()?;
     Ok(Value::Integer(d.trunc() as i32))
 }
@@ -40,6 +51,9 @@ pub fn sgn_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("Sgn requires exactly one argument".to_string()));
     }
+    if let Value::Decimal(d) = &args[0] {
+        return Ok(Value::Integer(if d.is_zero() { 0 } else if d.is_negative() { -1 } else { 1 }));
+    }
     let d = args[0].as_double()?;
     Ok(Value::Integer(if d < 0.0 { -1 } else if d > 0.0 { 1 } else { 0 }))
 }
@@ -70,20 +84,38 @@ pub fn rnd_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     Ok(Value::Single(r as f32))
 }
 
-/// Round(number[, decimal_places]) - Rounds to specified decimal places
+/// Round(number[, decimal_places[, mode]]) - Rounds to specified decimal places.
+/// Halves go to the even neighbour unless `mode` (a `MidpointRounding`) says
+/// otherwise, so `Round(2.5)` is 2 and `Round(3.5)` is 4.
 pub fn round_fn(args: &[Value]) -> Result<Value, RuntimeError> {
-    if args.is_empty() || args.len() > 2 {
-        return Err(RuntimeError::Custom("Round requires 1 or 2 arguments".to_string()));
+    if args.is_empty() || args.len() > 3 {
+        return Err(RuntimeError::Custom("Round requires 1 to 3 arguments".to_string()));
     }
-    let d = args[0].as_double()?;
-    let places = if args.len() == 2 {
+    let places = if args.len() >= 2 {
         args[1].as_integer()?.max(0) as u32
     } else {
         0
     };
+    let mode = match args.get(2) {
+        Some(m) => Rounding::from_midpoint(m.as_integer()?)
+            .ok_or_else(|| RuntimeError::Custom(format!("'{}' is not a valid MidpointRounding value", m.as_string())))?,
+        None => Rounding::ToEven,
+    };
+
+    if let Value::Decimal(d) = &args[0] {
+        return Ok(Value::Decimal(d.round(places, mode)));
+    }
 
+    let d = args[0].as_double()?;
     let factor = 10f64.powi(places as i32);
-    let rounded = (d * factor).round() / factor;
+    let scaled = d * factor;
+    let rounded = match mode {
+        Rounding::ToEven => scaled.round_ties_even(),
+        Rounding::AwayFromZero => scaled.round(),
+        Rounding::ToZero => scaled.trunc(),
+        Rounding::Down => scaled.floor(),
+        Rounding::Up => scaled.ceil(),
+    } / factor;
 
     if places == 0 {
         Ok(Value::Integer(rounded as i32))
@@ -150,6 +182,13 @@ pub fn max_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 2 {
         return Err(RuntimeError::Custom("Max requires exactly 2 arguments".to_string()));
     }
+    let exact = match crate::decimal::binary_operation(">=", &args[0], &args[1])? {
+        Some(result) => Some(result),
+        None => crate::integral::binary_operation(">=", &args[0], &args[1])?,
+    };
+    if let Some(Value::Boolean(first)) = exact {
+        return Ok(args[if first { 0 } else { 1 }].clone());
+    }
     let a = args[0].as_double()?;
     let b = args[1].as_double()?;
     if a >= b {
@@ -164,6 +203,13 @@ pub fn min_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 2 {
         return Err(RuntimeError::Custom("Min requires exactly 2 arguments".to_string()));
     }
+    let exact = match crate::decimal::binary_operation("<=", &args[0], &args[1])? {
+        Some(result) => Some(result),
+        None => crate::integral::binary_operation("<=", &args[0], &args[1])?,
+    };
+    if let Some(Value::Boolean(first)) = exact {
+        return Ok(args[if first { 0 } else { 1 }].clone());
+    }
     let a = args[0].as_double()?;
     let b = args[1].as_double()?;
     if a <= b {
@@ -178,6 +224,9 @@ pub fn ceiling_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("Ceiling requires exactly one argument".to_string()));
     }
+    if let Value::Decimal(d) = &args[0] {
+        return Ok(Value::Decimal(d.round(0, Rounding::Up)));
+    }
     let d = args[0].as_double()?;
     Ok(Value::Integer(d.ceil() as i32))
 }
@@ -187,6 +236,9 @@ pub fn floor_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("Floor requires exactly one argument".to_string()));
     }
+    if let Value::Decimal(d) = &args[0] {
+        return Ok(Value::Decimal(d.round(0, Rounding::Down)));
+    }
     let d = args[0].as_double()?;
     Ok(Value::Integer(d.floor() as i32))
 }
@@ -17,6 +17,7 @@ pub mod cryptography_fns;
 pub mod drawing_fns;
 pub mod concurrent_collections;
 pub mod networking;
+pub mod parameters;
 
 pub use msgbox::*;
 pub use string_fns::*;
@@ -35,3 +36,4 @@ pub use text_fns::*;
 pub use cryptography_fns::*;
 pub use drawing_fns::*;
 pub use concurrent_collections::*;
+pub use parameters::*;
@@ -0,0 +1,47 @@
+use crate::value::Value;
+
+/// A builtin parameter: its name as VB documents it, and for an optional
+/// one the value it takes when a later argument is named but it is not.
+/// A trailing optional parameter is never filled in, so its value is
+/// `Nothing` and the builtin's own default applies.
+pub type BuiltinParameter = (&'static str, Option<Value>);
+
+/// The parameters of the builtin functions that accept named arguments.
+pub fn builtin_parameters(name: &str) -> Option<Vec<BuiltinParameter>> {
+    let required = |name| (name, None);
+    let optional = |name, default| (name, Some(default));
+    let params = match name {
+        "msgbox" => vec![required("Prompt"), optional("Buttons", Value::Integer(0)), optional("Title", Value::Nothing)],
+        "inputbox" => vec![
+            required("Prompt"),
+            optional("Title", Value::String(String::new())),
+            optional("DefaultResponse", Value::Nothing),
+        ],
+        "left" | "right" => vec![required("Str"), required("Length")],
+        "mid" => vec![required("Str"), required("Start"), optional("Length", Value::Nothing)],
+        "instr" => vec![optional("Start", Value::Integer(1)), required("String1"), required("String2")],
+        "replace" => vec![
+            required("Expression"),
+            required("Find"),
+            required("Replacement"),
+            optional("Start", Value::Integer(1)),
+            optional("Count", Value::Nothing),
+        ],
+        "split" => vec![required("Expression"), optional("Delimiter", Value::Nothing)],
+        "join" => vec![required("SourceArray"), optional("Delimiter", Value::Nothing)],
+        "strcomp" => vec![required("String1"), required("String2"), optional("Compare", Value::Nothing)],
+        "strdup" => vec![required("Number"), required("Character")],
+        "format" => vec![required("Expression"), optional("Style", Value::Nothing)],
+        "formatnumber" | "formatcurrency" | "formatpercent" => vec![required("Expression"), optional("NumDigitsAfterDecimal", Value::Nothing)],
+        "dateadd" => vec![required("Interval"), required("Number"), required("DateValue")],
+        "datediff" => vec![required("Interval"), required("Date1"), required("Date2")],
+        "dateserial" => vec![required("Year"), required("Month"), required("Day")],
+        "timeserial" => vec![required("Hour"), required("Minute"), required("Second")],
+        "iif" => vec![required("Expression"), required("TruePart"), required("FalsePart")],
+        "pmt" => vec![required("Rate"), required("NPer"), required("PV"), optional("FV", Value::Double(0.0)), optional("Due", Value::Nothing)],
+        "fv" => vec![required("Rate"), required("NPer"), required("Pmt"), optional("PV", Value::Double(0.0)), optional("Due", Value::Nothing)],
+        "pv" => vec![required("Rate"), required("NPer"), required("Pmt"), optional("FV", Value::Double(0.0)), optional("Due", Value::Nothing)],
+        _ => return None,
+    };
+    Some(params)
+}
@@ -1,3 +1,4 @@
+use crate::decimal::{format_fixed, Decimal};
 use crate::value::{RuntimeError, Value};
 
 pub fn len_fn(args: &[Value]) -> Result<Value, RuntimeError> {
@@ -45,6 +46,30 @@ pub fn mid_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     Ok(Value::String(result))
 }
 
+/// The `Mid` statement: `text` with the characters from `start` (1-based)
+/// replaced by those of `value`, at most `length` of them. The length of
+/// `text` never changes.
+pub fn mid_statement(text: &str, start: i32, length: Option<i32>, value: &str) -> Result<String, RuntimeError> {
+    let argument_error = |message: &str| RuntimeError::Exception("ArgumentException".to_string(), message.to_string(), None);
+    let mut chars: Vec<char> = text.chars().collect();
+    if start < 1 {
+        return Err(argument_error("Argument 'Start' must be greater than zero."));
+    }
+    if start as usize > chars.len() {
+        return Err(argument_error("Argument 'Start' is not a valid value."));
+    }
+    if length.is_some_and(|len| len < 0) {
+        return Err(argument_error("Argument 'Length' must be greater or equal to zero."));
+    }
+    let start = start as usize - 1;
+    let room = chars.len() - start;
+    let count = length.map_or(room, |len| len as usize).min(room);
+    for (slot, c) in chars[start..start + count].iter_mut().zip(value.chars()) {
+        *slot = c;
+    }
+    Ok(chars.into_iter().collect())
+}
+
 pub fn ucase_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.len() != 1 {
         return Err(RuntimeError::Custom("UCase requires exactly one argument".to_string()));
@@ -296,36 +321,31 @@ pub fn format_fn(args: &[Value]) -> Result<Value, RuntimeError> {
 
     let fmt_str = args[1].as_string().to_lowercase();
 
-    match fmt_str.as_str() {
-        "currency" | "c" => {
-            let val = args[0].as_double()?;
-            Ok(Value::String(format!("${:.2}", val)))
-        }
-        "fixed" | "f" => {
-            let val = args[0].as_double()?;
-            Ok(Value::String(format!("{:.2}", val)))
+    // "N2", "F0", "C3", "P1": a standard format with an explicit precision
+    let (kind, precision) = match fmt_str.char_indices().nth(1) {
+        Some((i, _)) if fmt_str[i..].chars().all(|c| c.is_ascii_digit()) && fmt_str.len() <= 3 => {
+            (&fmt_str[..i], fmt_str[i..].parse::<usize>().ok())
         }
-        "standard" | "n" => {
-            let val = args[0].as_double()?;
-            // Add thousands separator
-            let formatted = format!("{:.2}", val);
-            let parts: Vec<&str> = formatted.split('.').collect();
-            let int_part = parts[0];
-            let dec_part = parts.get(1).unwrap_or(&"00");
-            let chars: Vec<char> = int_part.chars().rev().collect();
-            let mut with_commas = String::new();
-            for (i, c) in chars.iter().enumerate() {
-                if i > 0 && i % 3 == 0 && *c != '-' {
-                    with_commas.push(',');
-                }
-                with_commas.push(*c);
-            }
-            let int_formatted: String = with_commas.chars().rev().collect();
-            Ok(Value::String(format!("{}.{}", int_formatted, dec_part)))
+        _ => (fmt_str.as_str(), None),
+    };
+    let places = precision.unwrap_or(2);
+
+    match kind {
+        "currency" | "c" => {
+            let formatted = format_fixed(&args[0], places)?;
+            Ok(Value::String(match formatted.strip_prefix('-') {
+                Some(abs) => format!("-${}", group_thousands(abs)),
+                None => format!("${}", group_thousands(&formatted)),
+            }))
         }
+        "fixed" | "f" => Ok(Value::String(format_fixed(&args[0], places)?)),
+        "standard" | "n" => Ok(Value::String(group_thousands(&format_fixed(&args[0], places)?))),
         "percent" | "p" => {
-            let val = args[0].as_double()?;
-            Ok(Value::String(format!("{:.2}%", val * 100.0)))
+            let val = match &args[0] {
+                Value::Decimal(d) => Value::Decimal(d.mul(&Decimal::from_i64(100))?),
+                other => Value::Double(other.as_double()? * 100.0),
+            };
+            Ok(Value::String(format!("{}%", format_fixed(&val, places)?)))
         }
         "yes/no" => {
             let val = args[0].as_bool()?;
@@ -344,8 +364,7 @@ pub fn format_fn(args: &[Value]) -> Result<Value, RuntimeError> {
             // Basic handling: count decimal places
             if fmt_str.contains('.') {
                 let dec_places = fmt_str.split('.').nth(1).map(|s| s.len()).unwrap_or(0);
-                let val = args[0].as_double()?;
-                Ok(Value::String(format!("{:.prec$}", val, prec = dec_places)))
+                Ok(Value::String(format_fixed(&args[0], dec_places)?))
             } else {
                 Ok(Value::String(args[0].as_string()))
             }
@@ -353,6 +372,29 @@ pub fn format_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+/// "1234567.50" -> "1,234,567.50"
+fn group_thousands(number: &str) -> String {
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", number),
+    };
+    let (int_part, dec_part) = match digits.split_once('.') {
+        Some((i, d)) => (i, Some(d)),
+        None => (digits, None),
+    };
+    let mut with_commas = String::new();
+    for (i, c) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            with_commas.push(',');
+        }
+        with_commas.push(c);
+    }
+    match dec_part {
+        Some(d) => format!("{}{}.{}", sign, with_commas, d),
+        None => format!("{}{}", sign, with_commas),
+    }
+}
+
 /// StrConv(string, conversion, [LCID]) - Convert string case/format
 pub fn strconv_fn(args: &[Value]) -> Result<Value, RuntimeError> {
     if args.is_empty() || args.len() > 3 {
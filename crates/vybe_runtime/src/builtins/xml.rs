@@ -3,6 +3,7 @@
 // with full VB.NET System.Xml.Linq-style API.
 
 use crate::value::{Value, ObjectData, RuntimeError};
+use vybe_parser::ast::xml::XmlAxis;
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -533,6 +534,60 @@ pub fn xml_method_call(obj: &Value, method: &str, args: &[Value]) -> Result<Valu
     }
 }
 
+// ─── Axis properties ────────────────────────────────────────────────────────
+
+/// `target.<name>`, `target...<name>` or `target.@name`. The target is a
+/// document, an element, or a sequence of them such as another axis gives.
+/// An element axis collects the matching elements below each of them; an
+/// attribute axis reads the first element's attribute, Nothing if missing.
+pub fn axis(target: &Value, axis: XmlAxis, name: &str) -> Value {
+    let sources = match target {
+        Value::Array(items) => items.clone(),
+        other => vec![other.clone()],
+    };
+    match axis {
+        XmlAxis::Child => Value::Array(
+            sources.iter()
+                .flat_map(get_children)
+                .filter(|c| is_xml_class(c, "XmlElement") && get_xml_name(c).eq_ignore_ascii_case(name))
+                .collect(),
+        ),
+        XmlAxis::Descendant => {
+            let name_filter = Some(name.to_string());
+            let mut result = Vec::new();
+            for source in &sources {
+                collect_descendants(source, &name_filter, &mut result);
+            }
+            Value::Array(result)
+        }
+        XmlAxis::Attribute => sources.first()
+            .map(get_attributes)
+            .and_then(|attrs| attrs.into_iter().find(|a| get_xml_name(a).eq_ignore_ascii_case(name)))
+            .and_then(|attr| if let Value::Object(o) = attr { o.borrow().fields.get("__value").cloned() } else { None })
+            .unwrap_or(Value::Nothing),
+    }
+}
+
+/// `target.@name = value`: set the attribute on the element, or on the first
+/// element of a sequence. Nothing removes it.
+pub fn set_attribute_axis(target: &Value, name: &str, value: Value) -> Result<(), RuntimeError> {
+    let element = match target {
+        Value::Array(items) => items.first().cloned().unwrap_or(Value::Nothing),
+        other => other.clone(),
+    };
+    if !is_xml_class(&element, "XmlElement") {
+        return Err(RuntimeError::Custom(format!("Cannot set attribute '{}': not an XML element", name)));
+    }
+    xml_method_call(&element, "setattributevalue", &[Value::String(name.to_string()), value])?;
+    Ok(())
+}
+
+/// `.Value` of a sequence of elements: the first one's value.
+pub fn sequence_value(items: &[Value]) -> Option<Value> {
+    let first = items.first().filter(|item| is_xml_class(item, "XmlElement"))?;
+    xml_property_access(first, "value").ok()
+}
+
 // ─── XML parsing ────────────────────────────────────────────────────────────
 
 /// Extract attributes from a BytesStart into owned (String, String) pairs
@@ -0,0 +1,526 @@
+//! Bytecode for Sub and Function bodies, run by the interpreter's VM as an
+//! alternative to walking the AST.
+//!
+//! [`compile`] turns a procedure body into a [`Chunk`]: a flat list of
+//! [`Op`]s for a stack machine, with jumps in place of nested `If`s and
+//! loops, so the VM runs them without recursing through the tree or
+//! re-matching statements. Variables, operators, assignments, `If`, the
+//! numeric `For`, `While` and `Do` loops and the `Exit`/`Continue`/`Return`
+//! statements are compiled. Every other statement and expression is kept as
+//! AST and handed to the tree walker when reached, so both engines share
+//! `Value`, the operator rules and all the builtins.
+//!
+//! Variables are resolved as the body compiles: each distinct name becomes a
+//! [`Variable`] holding its interned [`Symbol`] and the local slot it was last
+//! found in, so a loop reading the same local again goes straight to the
+//! slot instead of searching the scopes by name.
+//!
+//! Bodies with labels aren't compiled: `GoTo`, `GoSub` and `On Error GoTo`
+//! jump by statement index, which only the tree walker tracks. Nor are bodies
+//! without a compiled loop: run once, each statement costs the VM as much as
+//! the tree walker plus the dispatch, so calls to them stay on the tree walker.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use vybe_parser::ast::expr::BinaryOp;
+use vybe_parser::ast::stmt::{CompoundOp, ContinueType, LoopConditionType};
+use vybe_parser::{Block, ExprKind, Expression, Identifier, Span, Statement, VBType};
+
+use crate::environment::{Environment, Slot};
+use crate::evaluator::evaluate;
+use crate::symbol::Symbol;
+use crate::value::{ExitType, Value};
+
+/// Which engine runs Sub and Function bodies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Walk the AST of each statement as it runs.
+    #[default]
+    TreeWalker,
+    /// Compile each body with a loop to bytecode on its first call and run
+    /// that. Experimental: calls and member access aren't compiled yet.
+    Bytecode,
+}
+
+impl Engine {
+    /// The engine named by the `VYBE_ENGINE` environment variable
+    /// (`bytecode` or `tree`); the tree walker when it isn't set.
+    pub fn from_env() -> Engine {
+        match std::env::var("VYBE_ENGINE") {
+            Ok(name) if name.eq_ignore_ascii_case("bytecode") || name.eq_ignore_ascii_case("vm") => Engine::Bytecode,
+            _ => Engine::TreeWalker,
+        }
+    }
+}
+
+/// One instruction. Operands live on the VM's value stack; indexes refer to
+/// the tables of the [`Chunk`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    /// The statement at this span starts; errors until the next `At` are
+    /// reported there.
+    At(Span),
+    Const(Value),
+    /// Push `variables[i]`: a local from its slot, anything else through the
+    /// interpreter's name lookup.
+    Load(usize),
+    /// Push the value of `exprs[i]`, evaluated by the tree walker.
+    Eval(usize),
+    /// Pop the right and left operands and push the result.
+    Binary(BinaryOp),
+    Negate,
+    Not,
+    /// Pop the left operand of `AndAlso` (`and`) or `OrElse`. If it decides
+    /// the result, push that and jump to `end`; otherwise push it back with
+    /// a marker for [`Op::FinishShortCircuit`].
+    ShortCircuit { and: bool, end: usize },
+    /// Pop the right operand, the marker and the left operand; push the result.
+    FinishShortCircuit { and: bool },
+    /// Pop a value and assign it as `assignments[i]` does.
+    Assign(usize),
+    /// Pop the right operand and the variable's current value, and store
+    /// `current op rhs` as `compounds[i]` does.
+    Compound(usize),
+    /// Run `statements[i]` with the tree walker.
+    Exec(usize),
+    Jump(usize),
+    /// Pop a condition and jump if it is false.
+    JumpIfFalse(usize),
+    /// Pop a condition and jump if it is true.
+    JumpIfTrue(usize),
+    /// Leave `loops[i]`: `Exit For`, `Exit Do`, ...
+    Break(usize),
+    /// Start the next iteration of `loops[i]`: `Continue For`, ...
+    Next(usize),
+    /// `Option Explicit` check for the variable of `for_loops[i]`.
+    ForCheck(usize),
+    /// Pop a value and push it as an Integer, as `For` takes its bounds.
+    ToInteger,
+    /// With the counter, end and step on the stack: jump to `exit` when the
+    /// counter is past the end, otherwise store it in the variable of
+    /// `for_loops[var]`.
+    ForTest { var: usize, exit: usize },
+    /// Add the step to the counter and jump back to the `ForTest` at `head`.
+    ForStep(usize),
+    Pop(usize),
+    /// Leave the procedure with `Return`, with the popped value of
+    /// `returns[i]` if there is one.
+    Return(Option<usize>),
+    /// `Exit` out of something not compiled here (a Sub, a Select, ...).
+    Exit(ExitType),
+    /// `Continue` without an enclosing compiled loop of its kind.
+    Continue(ContinueType),
+}
+
+/// A compiled procedure body.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Chunk {
+    pub ops: Vec<Op>,
+    pub variables: Vec<Variable>,
+    pub exprs: Vec<Expression>,
+    pub statements: Vec<Fallback>,
+    /// `variables[target] = value`, with the value kept whole for the
+    /// `Option Strict` checks.
+    pub assignments: Vec<(usize, Expression)>,
+    pub compounds: Vec<(usize, CompoundOp)>,
+    /// The values of `Return` statements, kept whole for the `Option Strict`
+    /// checks.
+    pub returns: Vec<Expression>,
+    pub for_loops: Vec<(usize, Option<VBType>)>,
+    pub loops: Vec<Loop>,
+    /// Where each top-level statement starts, for `On Error Resume Next`.
+    pub statement_starts: Vec<usize>,
+}
+
+impl Chunk {
+    /// Where the top-level statement after the one holding op `pc` starts.
+    pub fn next_statement(&self, pc: usize) -> usize {
+        self.statement_starts.iter().copied().find(|&start| start > pc).unwrap_or(self.ops.len())
+    }
+}
+
+/// A variable named in a chunk, one per spelling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: Identifier,
+    pub symbol: Symbol,
+    /// The local slot found last, or `None` for a name that wasn't a local,
+    /// with the [`Environment::epoch`] it was found under.
+    slot: Cell<Option<(u64, Option<Slot>)>>,
+}
+
+impl Variable {
+    /// The slot of the local this names, if it is one. The scopes are only
+    /// searched again after a scope or variable came or went.
+    pub fn local(&self, env: &Environment) -> Option<Slot> {
+        match self.slot.get() {
+            Some((epoch, slot)) if epoch == env.epoch() => slot,
+            _ => {
+                let slot = env.resolve_local(self.symbol);
+                self.slot.set(Some((env.epoch(), slot)));
+                slot
+            }
+        }
+    }
+}
+
+/// A statement left to the tree walker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fallback {
+    pub statement: Statement,
+    /// The compiled loops around it, innermost last, for an `Exit` or
+    /// `Continue` it raises.
+    pub loops: Vec<usize>,
+}
+
+/// A compiled loop, as seen by `Exit` and `Continue`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loop {
+    pub kind: LoopKind,
+    /// Stack depth inside the loop; a `For` keeps its counter, end and step
+    /// on the stack.
+    pub depth: usize,
+    pub continue_at: usize,
+    pub exit_at: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopKind {
+    For,
+    While,
+    Do,
+}
+
+impl LoopKind {
+    /// Does `Exit exit` end a loop of this kind? As in the tree walker, a
+    /// `While` loop also ends on `Exit Do`.
+    pub fn exits_on(self, exit: &ExitType) -> bool {
+        matches!(
+            (self, exit),
+            (LoopKind::For, ExitType::For) | (LoopKind::While, ExitType::While | ExitType::Do) | (LoopKind::Do, ExitType::Do)
+        )
+    }
+
+    /// Does `Continue typ` go on with a loop of this kind?
+    pub fn continues_on(self, typ: &ContinueType) -> bool {
+        matches!(
+            (self, typ),
+            (LoopKind::For, ContinueType::For) | (LoopKind::While, ContinueType::While) | (LoopKind::Do, ContinueType::Do)
+        )
+    }
+}
+
+/// Compile a procedure body, or `None` if it has labels or no loop to
+/// compile.
+pub fn compile(body: &Block) -> Option<Chunk> {
+    if body.iter().any(|s| matches!(s, Statement::Label(_))) {
+        return None;
+    }
+    let mut compiler = Compiler::default();
+    for (stmt, span) in body.iter_spanned() {
+        compiler.chunk.statement_starts.push(compiler.chunk.ops.len());
+        compiler.statement(stmt, span);
+    }
+    (!compiler.chunk.loops.is_empty()).then_some(compiler.chunk)
+}
+
+/// Compiled bodies by the declaration they belong to and where the body
+/// itself starts, then the file and procedure name. A property's `Get` and
+/// `Set`, or a custom event's accessors, share a declaration but not a body.
+/// New code can put a different body at the same place, so the interpreter
+/// clears the cache whenever code is loaded.
+#[derive(Debug, Default)]
+pub struct ChunkCache {
+    entries: HashMap<(Span, Span), Vec<Rc<Compiled>>>,
+}
+
+#[derive(Debug)]
+pub struct Compiled {
+    source: Option<String>,
+    procedure: String,
+    /// `None` when the body can't be compiled.
+    pub chunk: Option<Chunk>,
+}
+
+impl ChunkCache {
+    /// The compiled form of `body`, declared at `span` as `procedure` in
+    /// file `source`.
+    pub fn get(&mut self, source: &Option<String>, procedure: &str, span: Span, body: &Block) -> Rc<Compiled> {
+        let bodies = self.entries.entry((span, body.span_at(0))).or_default();
+        if let Some(compiled) = bodies.iter().find(|c| c.source == *source && c.procedure == procedure) {
+            return compiled.clone();
+        }
+        let compiled = Rc::new(Compiled { source: source.clone(), procedure: procedure.to_string(), chunk: compile(body) });
+        bodies.push(compiled.clone());
+        compiled
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[derive(Default)]
+struct Compiler {
+    chunk: Chunk,
+    /// Loops being compiled, innermost last.
+    open_loops: Vec<usize>,
+    /// Values on the stack between statements.
+    depth: usize,
+    /// Indexes into the chunk's variables by name as written.
+    variables: HashMap<String, usize>,
+}
+
+impl Compiler {
+    fn emit(&mut self, op: Op) -> usize {
+        self.chunk.ops.push(op);
+        self.chunk.ops.len() - 1
+    }
+
+    fn here(&self) -> usize {
+        self.chunk.ops.len()
+    }
+
+    /// Point the jump at `at` to the next op.
+    fn patch(&mut self, at: usize) {
+        let target = self.here();
+        match &mut self.chunk.ops[at] {
+            Op::Jump(t) | Op::JumpIfFalse(t) | Op::JumpIfTrue(t) => *t = target,
+            Op::ShortCircuit { end, .. } => *end = target,
+            Op::ForTest { exit, .. } => *exit = target,
+            _ => {}
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        for (stmt, span) in block.iter_spanned() {
+            self.statement(stmt, span);
+        }
+    }
+
+    fn statement(&mut self, stmt: &Statement, span: Span) {
+        let start = self.emit(Op::At(span));
+        match stmt {
+            Statement::Assignment { target, value } => {
+                self.expression(value);
+                let target = self.variable(target);
+                self.chunk.assignments.push((target, value.clone()));
+                self.emit(Op::Assign(self.chunk.assignments.len() - 1));
+            }
+            Statement::CompoundAssignment { target, members, indices, operator, value }
+                if members.is_empty() && indices.is_empty() =>
+            {
+                self.load(target);
+                self.expression(value);
+                let target = self.variable(target);
+                self.chunk.compounds.push((target, *operator));
+                self.emit(Op::Compound(self.chunk.compounds.len() - 1));
+            }
+            Statement::If { condition, then_branch, elseif_branches, else_branch } => {
+                let mut ends = Vec::new();
+                self.expression(condition);
+                let mut skip = self.emit(Op::JumpIfFalse(0));
+                self.block(then_branch);
+                for (condition, branch) in elseif_branches {
+                    ends.push(self.emit(Op::Jump(0)));
+                    self.patch(skip);
+                    self.expression(condition);
+                    skip = self.emit(Op::JumpIfFalse(0));
+                    self.block(branch);
+                }
+                if let Some(branch) = else_branch {
+                    ends.push(self.emit(Op::Jump(0)));
+                    self.patch(skip);
+                    self.block(branch);
+                } else {
+                    self.patch(skip);
+                }
+                for end in ends {
+                    self.patch(end);
+                }
+            }
+            Statement::While { condition, body } => {
+                self.expression(condition);
+                let exit = self.emit(Op::JumpIfFalse(0));
+                let id = self.open_loop(LoopKind::While, start);
+                self.block(body);
+                self.emit(Op::Jump(start));
+                self.patch(exit);
+                self.close_loop(id);
+            }
+            Statement::DoLoop { pre_condition, body, post_condition } => {
+                let mut exits = Vec::new();
+                if let Some((kind, condition)) = pre_condition {
+                    exits.push(self.loop_condition(kind, condition));
+                }
+                let id = self.open_loop(LoopKind::Do, 0);
+                self.block(body);
+                self.chunk.loops[id].continue_at = self.emit(Op::At(span));
+                if let Some((kind, condition)) = post_condition {
+                    exits.push(self.loop_condition(kind, condition));
+                }
+                self.emit(Op::Jump(start));
+                for exit in exits {
+                    self.patch(exit);
+                }
+                self.close_loop(id);
+            }
+            Statement::For { variable, var_type, start: from, end, step, body } => {
+                let variable = self.variable(variable);
+                self.chunk.for_loops.push((variable, var_type.clone()));
+                let var = self.chunk.for_loops.len() - 1;
+                self.emit(Op::ForCheck(var));
+                for bound in [Some(from), Some(end), step.as_ref()] {
+                    match bound {
+                        Some(expr) => {
+                            self.expression(expr);
+                            self.emit(Op::ToInteger);
+                        }
+                        None => {
+                            self.emit(Op::Const(Value::Integer(1)));
+                        }
+                    }
+                }
+                self.depth += 3;
+                let head = self.emit(Op::At(span));
+                let test = self.emit(Op::ForTest { var, exit: 0 });
+                let id = self.open_loop(LoopKind::For, 0);
+                self.block(body);
+                self.chunk.loops[id].continue_at = self.emit(Op::ForStep(head));
+                self.patch(test);
+                self.close_loop(id);
+                self.depth -= 3;
+                self.emit(Op::Pop(3));
+            }
+            Statement::ExitFor => self.exit(ExitType::For),
+            Statement::ExitDo => self.exit(ExitType::Do),
+            Statement::ExitWhile => self.exit(ExitType::While),
+            Statement::ExitSub => {
+                self.emit(Op::Exit(ExitType::Sub));
+            }
+            Statement::ExitFunction => {
+                self.emit(Op::Exit(ExitType::Function));
+            }
+            Statement::Continue(typ) => {
+                let target = self.open_loops.iter().rev().copied().find(|&id| self.chunk.loops[id].kind.continues_on(typ));
+                match target {
+                    Some(id) => self.emit(Op::Next(id)),
+                    None => self.emit(Op::Continue(*typ)),
+                };
+            }
+            Statement::Return(value) => {
+                let returned = value.as_ref().map(|value| {
+                    self.expression(value);
+                    self.chunk.returns.push(value.clone());
+                    self.chunk.returns.len() - 1
+                });
+                self.emit(Op::Return(returned));
+            }
+            _ => {
+                self.chunk.statements.push(Fallback { statement: stmt.clone(), loops: self.open_loops.clone() });
+                self.emit(Op::Exec(self.chunk.statements.len() - 1));
+            }
+        }
+    }
+
+    /// The test of a `Do While`/`Do Until`; returns the jump out of the loop.
+    fn loop_condition(&mut self, kind: &LoopConditionType, condition: &Expression) -> usize {
+        self.expression(condition);
+        match kind {
+            LoopConditionType::While => self.emit(Op::JumpIfFalse(0)),
+            LoopConditionType::Until => self.emit(Op::JumpIfTrue(0)),
+        }
+    }
+
+    fn open_loop(&mut self, kind: LoopKind, continue_at: usize) -> usize {
+        self.chunk.loops.push(Loop { kind, depth: self.depth, continue_at, exit_at: 0 });
+        let id = self.chunk.loops.len() - 1;
+        self.open_loops.push(id);
+        id
+    }
+
+    /// End the loop `id` at the next op.
+    fn close_loop(&mut self, id: usize) {
+        self.chunk.loops[id].exit_at = self.here();
+        self.open_loops.pop();
+    }
+
+    fn exit(&mut self, exit: ExitType) {
+        let target = self.open_loops.iter().rev().copied().find(|&id| self.chunk.loops[id].kind.exits_on(&exit));
+        match target {
+            Some(id) => self.emit(Op::Break(id)),
+            None => self.emit(Op::Exit(exit)),
+        };
+    }
+
+    /// The index of the variable `name` in the chunk, added on first use.
+    fn variable(&mut self, name: &Identifier) -> usize {
+        if let Some(&index) = self.variables.get(name.as_str()) {
+            return index;
+        }
+        let symbol = Symbol::intern(name.as_str());
+        self.chunk.variables.push(Variable { name: name.clone(), symbol, slot: Cell::new(None) });
+        self.variables.insert(name.as_str().to_string(), self.chunk.variables.len() - 1);
+        self.chunk.variables.len() - 1
+    }
+
+    fn load(&mut self, name: &Identifier) {
+        let index = self.variable(name);
+        self.emit(Op::Load(index));
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        match &expr.kind {
+            ExprKind::IntegerLiteral(_)
+            | ExprKind::DoubleLiteral(_)
+            | ExprKind::LongLiteral(_)
+            | ExprKind::ShortLiteral(_)
+            | ExprKind::UShortLiteral(_)
+            | ExprKind::UIntegerLiteral(_)
+            | ExprKind::ULongLiteral(_)
+            | ExprKind::SingleLiteral(_)
+            | ExprKind::DecimalLiteral(_)
+            | ExprKind::StringLiteral(_)
+            | ExprKind::BooleanLiteral(_)
+            | ExprKind::Nothing => match evaluate(expr, &Environment::new()) {
+                Ok(value) => {
+                    self.emit(Op::Const(value));
+                }
+                // An out-of-range Decimal fails when it is reached
+                Err(_) => self.fallback(expr),
+            },
+            ExprKind::Variable(name) => self.load(name),
+            ExprKind::AndAlso(left, right) | ExprKind::OrElse(left, right) => {
+                let and = matches!(&expr.kind, ExprKind::AndAlso(..));
+                self.expression(left);
+                let decided = self.emit(Op::ShortCircuit { and, end: 0 });
+                self.expression(right);
+                self.emit(Op::FinishShortCircuit { and });
+                self.patch(decided);
+            }
+            ExprKind::Negate(operand) => {
+                self.expression(operand);
+                self.emit(Op::Negate);
+            }
+            ExprKind::Not(operand) => {
+                self.expression(operand);
+                self.emit(Op::Not);
+            }
+            _ => match expr.kind.as_binary() {
+                Some((op, left, right)) => {
+                    self.expression(left);
+                    self.expression(right);
+                    self.emit(Op::Binary(op));
+                }
+                None => self.fallback(expr),
+            },
+        }
+    }
+
+    fn fallback(&mut self, expr: &Expression) {
+        self.chunk.exprs.push(expr.clone());
+        self.emit(Op::Eval(self.chunk.exprs.len() - 1));
+    }
+}
@@ -83,7 +83,7 @@ impl ArrayList {
     }
 
     pub fn remove(&mut self, value: &Value) {
-        if let Some(pos) = self.items.iter().position(|x| x == value) {
+        if let Some(pos) = self.items.iter().position(|x| crate::delegates::items_equal(x, value)) {
             self.items.remove(pos);
             self.rebuild_key_indices_after_remove(pos);
         }
@@ -278,7 +278,7 @@ impl ArrayList {
     /// IndexOf with start index.
     pub fn index_of_from(&self, value: &Value, start: usize) -> i32 {
         for i in start..self.items.len() {
-            if self.items[i] == *value {
+            if crate::delegates::items_equal(&self.items[i], value) {
                 return i as i32;
             }
         }
@@ -289,7 +289,7 @@ impl ArrayList {
     pub fn index_of_range(&self, value: &Value, start: usize, count: usize) -> i32 {
         let end = (start + count).min(self.items.len());
         for i in start..end {
-            if self.items[i] == *value {
+            if crate::delegates::items_equal(&self.items[i], value) {
                 return i as i32;
             }
         }
@@ -300,7 +300,7 @@ impl ArrayList {
     pub fn last_index_of_from(&self, value: &Value, start: usize) -> i32 {
         let end = start.min(self.items.len().saturating_sub(1));
         for i in (0..=end).rev() {
-            if self.items[i] == *value {
+            if crate::delegates::items_equal(&self.items[i], value) {
                 return i as i32;
             }
         }
@@ -491,6 +491,9 @@ impl VBDictionary {
     fn keys_equal(a: &Value, b: &Value) -> bool {
         match (a, b) {
             (Value::String(sa), Value::String(sb)) => sa.eq_ignore_ascii_case(sb),
+            (Value::Object(oa), Value::Object(ob)) if crate::tuples::is_tuple(&oa.borrow()) => {
+                crate::tuples::tuple_equal(&oa.borrow(), &ob.borrow())
+            }
             _ => a == b,
         }
     }
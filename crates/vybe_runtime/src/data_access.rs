@@ -473,7 +473,14 @@ impl DataAccessManager {
                             }
                             let mut values = Vec::new();
                             for i in 0..raw_row.columns().len() {
-                                let val: String = raw_row.try_get::<String, _>(i)
+                                use sqlx::TypeInfo;
+                                // NUMERIC has no Rust type without extra features: decode it by hand
+                                let numeric = match raw_row.column(i).type_info().name() {
+                                    "NUMERIC" => raw_row.try_get_raw(i).ok().and_then(|v| pg_numeric_text(&v)),
+                                    _ => None,
+                                };
+                                let val: String = numeric.ok_or(())
+                                    .or_else(|_| raw_row.try_get::<String, _>(i))
                                     .or_else(|_| raw_row.try_get::<i64, _>(i).map(|v| v.to_string()))
                                     .or_else(|_| raw_row.try_get::<i32, _>(i).map(|v| v.to_string()))
                                     .or_else(|_| raw_row.try_get::<f64, _>(i).map(|v| v.to_string()))
@@ -503,8 +510,15 @@ impl DataAccessManager {
                             }
                             let mut values = Vec::new();
                             for i in 0..raw_row.columns().len() {
+                                use sqlx::TypeInfo;
+                                // DECIMAL arrives as text; read it as such rather than through f64
+                                let decimal = match raw_row.column(i).type_info().name() {
+                                    "DECIMAL" => raw_row.try_get_unchecked::<Option<String>, _>(i).ok().flatten(),
+                                    _ => None,
+                                };
                                 // MySQL native driver supports all MySQL types including TINYINT
-                                let val: String = raw_row.try_get::<String, _>(i)
+                                let val: String = decimal.ok_or(())
+                                    .or_else(|_| raw_row.try_get::<String, _>(i))
                                     .or_else(|_| raw_row.try_get::<i64, _>(i).map(|v| v.to_string()))
                                     .or_else(|_| raw_row.try_get::<i32, _>(i).map(|v| v.to_string()))
                                     .or_else(|_| raw_row.try_get::<i16, _>(i).map(|v| v.to_string()))
@@ -1129,6 +1143,56 @@ pub fn test_connection_and_list_tables(conn_str: &str) -> Result<Vec<String>, St
     Ok(tables)
 }
 
+/// The text of a Postgres NUMERIC value, or `None` for NULL and NaN.
+fn pg_numeric_text(value: &sqlx::postgres::PgValueRef<'_>) -> Option<String> {
+    use sqlx::ValueRef;
+    if value.is_null() {
+        return None;
+    }
+    match value.format() {
+        sqlx::postgres::PgValueFormat::Text => value.as_str().ok().map(str::to_string),
+        sqlx::postgres::PgValueFormat::Binary => decode_pg_numeric(value.as_bytes().ok()?),
+    }
+}
+
+/// Decode NUMERIC's binary form: digit count, weight, sign and display
+/// scale as 16-bit big-endian words, then the base-10000 digits, the first
+/// one multiplied by 10000^weight.
+fn decode_pg_numeric(bytes: &[u8]) -> Option<String> {
+    let word = |i: usize| bytes.get(i * 2..i * 2 + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    let ndigits = word(0)? as usize;
+    let weight = word(1)? as i16 as i64;
+    let sign = word(2)?;
+    let dscale = word(3)? as usize;
+    if sign == 0xC000 {
+        return None;
+    }
+    let digits: Vec<u16> = (0..ndigits).map(|k| word(4 + k)).collect::<Option<_>>()?;
+    // The digit at base-10000 exponent `e`
+    let digit = |e: i64| {
+        let k = weight - e;
+        if k >= 0 { digits.get(k as usize).copied().unwrap_or(0) } else { 0 }
+    };
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&digit(weight).to_string());
+        for e in (0..weight).rev() {
+            text.push_str(&format!("{:04}", digit(e)));
+        }
+    }
+    if dscale > 0 {
+        let fraction: String = (1..=dscale.div_ceil(4) as i64).map(|p| format!("{:04}", digit(-p))).collect();
+        text.push('.');
+        text.push_str(&fraction[..dscale]);
+    }
+    Some(text)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1165,6 +1229,18 @@ mod tests {
         assert!(url.starts_with("mysql://"));
     }
 
+    #[test]
+    fn test_decode_pg_numeric() {
+        let encode = |words: &[u16]| words.iter().flat_map(|w| w.to_be_bytes()).collect::<Vec<u8>>();
+        // 12345.678: digits 1, 2345, 6780 with weight 1, scale 3
+        assert_eq!(decode_pg_numeric(&encode(&[3, 1, 0, 3, 1, 2345, 6780])).unwrap(), "12345.678");
+        // -0.05: digit 500 at weight -1, scale 2
+        assert_eq!(decode_pg_numeric(&encode(&[1, 0xFFFF, 0x4000, 2, 500])).unwrap(), "-0.05");
+        // 20000 stored as the single digit 2 at weight 1
+        assert_eq!(decode_pg_numeric(&encode(&[1, 1, 0, 0, 2])).unwrap(), "20000");
+        assert_eq!(decode_pg_numeric(&encode(&[0, 0, 0xC000, 0])), None);
+    }
+
     #[test]
     fn test_parse_adodb_jet() {
         let (backend, _url) = DataAccessManager::parse_connection_string(
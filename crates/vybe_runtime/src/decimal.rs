@@ -0,0 +1,505 @@
+//! VB's `Decimal`: a 96-bit integer scaled by a power of ten from 0 to 28.
+//!
+//! Arithmetic is exact while the result fits; otherwise the least significant
+//! digits are dropped with banker's rounding, as .NET does, and a result too
+//! large for 96 bits at scale 0 is an `OverflowException`. The scale is kept
+//! through `+`, `-` and `*`, so `1.10D` prints as `1.10` and `1.5D * 2` as
+//! `3.0`; division gives the shortest exact quotient, or 28-29 significant
+//! digits when there is none.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::value::{RuntimeError, Value};
+
+/// Largest scale (digits after the point) a Decimal can have.
+pub const MAX_SCALE: u32 = 28;
+
+/// Largest magnitude of the 96-bit integer part.
+const MAX_MANTISSA: u128 = (1 << 96) - 1;
+
+/// How to round away the digits a result can't keep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rounding {
+    /// Banker's rounding: halves go to the even neighbour. VB's default.
+    ToEven,
+    AwayFromZero,
+    ToZero,
+    Down,
+    Up,
+}
+
+impl Rounding {
+    /// The `MidpointRounding` member with value `mode`.
+    pub fn from_midpoint(mode: i32) -> Option<Self> {
+        Some(match mode {
+            0 => Rounding::ToEven,
+            1 => Rounding::AwayFromZero,
+            2 => Rounding::ToZero,
+            3 => Rounding::Down,
+            4 => Rounding::Up,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    negative: bool,
+    mantissa: u128,
+    scale: u32,
+}
+
+/// `OverflowException` for a result outside the Decimal range.
+pub fn overflow() -> RuntimeError {
+    RuntimeError::Exception(
+        "OverflowException".to_string(),
+        "Value was either too large or too small for a Decimal.".to_string(),
+        None,
+    )
+}
+
+fn pow10(n: u32) -> u128 {
+    10u128.pow(n)
+}
+
+/// A 256-bit unsigned integer, most significant limb first, wide enough for
+/// the product of two mantissas or one mantissa scaled by `10^28`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Wide([u64; 4]);
+
+impl Wide {
+    fn from_u128(v: u128) -> Self {
+        Wide([0, 0, (v >> 64) as u64, v as u64])
+    }
+
+    fn to_u128(self) -> Option<u128> {
+        (self.0[0] == 0 && self.0[1] == 0).then(|| ((self.0[2] as u128) << 64) | self.0[3] as u128)
+    }
+
+    fn mul_u64(self, m: u64) -> Self {
+        let mut out = [0u64; 4];
+        let mut carry = 0u128;
+        for i in (0..4).rev() {
+            let v = self.0[i] as u128 * m as u128 + carry;
+            out[i] = v as u64;
+            carry = v >> 64;
+        }
+        Wide(out)
+    }
+
+    fn mul_u128(self, m: u128) -> Self {
+        let low = self.mul_u64(m as u64);
+        let high = self.mul_u64((m >> 64) as u64);
+        // Shift `high` one limb up before adding
+        low.add(Wide([high.0[1], high.0[2], high.0[3], 0]))
+    }
+
+    fn mul_pow10(self, n: u32) -> Self {
+        (0..n).fold(self, |w, _| w.mul_u64(10))
+    }
+
+    fn add(self, other: Self) -> Self {
+        let mut out = [0u64; 4];
+        let mut carry = 0u128;
+        for i in (0..4).rev() {
+            let v = self.0[i] as u128 + other.0[i] as u128 + carry;
+            out[i] = v as u64;
+            carry = v >> 64;
+        }
+        Wide(out)
+    }
+
+    /// `self - other`, for `self >= other`.
+    fn sub(self, other: Self) -> Self {
+        let mut out = [0u64; 4];
+        let mut borrow = false;
+        for i in (0..4).rev() {
+            let (v, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (v, b2) = v.overflowing_sub(borrow as u64);
+            out[i] = v;
+            borrow = b1 || b2;
+        }
+        Wide(out)
+    }
+
+    fn divrem_u64(self, d: u64) -> (Self, u64) {
+        let mut out = [0u64; 4];
+        let mut rem = 0u128;
+        for (limb, q) in self.0.iter().zip(out.iter_mut()) {
+            let cur = (rem << 64) | *limb as u128;
+            *q = (cur / d as u128) as u64;
+            rem = cur % d as u128;
+        }
+        (Wide(out), rem as u64)
+    }
+}
+
+/// Drop digits from `magnitude` until it fits 96 bits at a scale of at most
+/// 28, rounding the dropped digits with `mode`.
+fn fit(negative: bool, mut magnitude: Wide, mut scale: u32, mode: Rounding) -> Result<Decimal, RuntimeError> {
+    let max = Wide::from_u128(MAX_MANTISSA);
+    let (mut last, mut sticky) = (0u64, false);
+    while scale > MAX_SCALE || magnitude > max {
+        if scale == 0 {
+            return Err(overflow());
+        }
+        sticky |= last != 0;
+        let (q, r) = magnitude.divrem_u64(10);
+        magnitude = q;
+        last = r;
+        scale -= 1;
+    }
+    let mut mantissa = magnitude.to_u128().ok_or_else(overflow)?;
+    if round_up(mode, negative, mantissa, last, 10, sticky) {
+        mantissa += 1;
+        if mantissa > MAX_MANTISSA {
+            // 999...9 rounded up: one digit longer, so drop another
+            if scale == 0 {
+                return Err(overflow());
+            }
+            mantissa /= 10;
+            scale -= 1;
+        }
+    }
+    Ok(Decimal { negative: negative && mantissa != 0, mantissa, scale })
+}
+
+/// Should a truncated `quotient` be bumped by one, given the `remainder` of
+/// division by `divisor` and whether digits past it were nonzero (`sticky`)?
+fn round_up(mode: Rounding, negative: bool, quotient: u128, remainder: u64, divisor: u128, sticky: bool) -> bool {
+    round_up_wide(mode, negative, quotient, remainder as u128, divisor, sticky)
+}
+
+fn round_up_wide(mode: Rounding, negative: bool, quotient: u128, remainder: u128, divisor: u128, sticky: bool) -> bool {
+    if remainder == 0 && !sticky {
+        return false;
+    }
+    let twice = remainder * 2;
+    let above_half = twice > divisor || (twice == divisor && sticky);
+    let half = twice == divisor && !sticky;
+    match mode {
+        Rounding::ToEven => above_half || (half && !quotient.is_multiple_of(2)),
+        Rounding::AwayFromZero => above_half || half,
+        Rounding::ToZero => false,
+        Rounding::Down => negative,
+        Rounding::Up => !negative,
+    }
+}
+
+impl Decimal {
+    pub const ZERO: Decimal = Decimal { negative: false, mantissa: 0, scale: 0 };
+    pub const MAX: Decimal = Decimal { negative: false, mantissa: MAX_MANTISSA, scale: 0 };
+    pub const MIN: Decimal = Decimal { negative: true, mantissa: MAX_MANTISSA, scale: 0 };
+
+    pub fn from_i64(v: i64) -> Self {
+        Decimal { negative: v < 0, mantissa: v.unsigned_abs() as u128, scale: 0 }
+    }
+
+    /// An integer too wide for `i64`, such as a `ULong` or the sum of a
+    /// `Long` and a `ULong`.
+    pub fn from_i128(v: i128) -> Result<Self, RuntimeError> {
+        let mantissa = v.unsigned_abs();
+        if mantissa > MAX_MANTISSA {
+            return Err(overflow());
+        }
+        Ok(Decimal { negative: v < 0, mantissa, scale: 0 })
+    }
+
+    /// A Double as .NET converts it: rounded to 15 significant digits, so
+    /// `0.1 + 0.2` becomes exactly `0.3`.
+    pub fn from_f64(v: f64) -> Result<Self, RuntimeError> {
+        Self::from_float_digits(v, 15)
+    }
+
+    /// A Single, rounded to 7 significant digits.
+    pub fn from_f32(v: f32) -> Result<Self, RuntimeError> {
+        Self::from_float_digits(v as f64, 7)
+    }
+
+    fn from_float_digits(v: f64, digits: usize) -> Result<Self, RuntimeError> {
+        if !v.is_finite() {
+            return Err(overflow());
+        }
+        let text = format!("{:.*e}", digits - 1, v);
+        Ok(Self::parse(&text).ok_or_else(overflow)?.normalize())
+    }
+
+    /// Parse `[-]digits[.digits][E[+-]n]`, as written in VB source or read
+    /// back from a database. Excess digits are rounded off.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (negative, rest) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (number, exponent) = match rest.find(['e', 'E']) {
+            Some(i) => (&rest[..i], rest[i + 1..].parse::<i32>().ok()?),
+            None => (rest, 0),
+        };
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        let mut magnitude = Wide::from_u128(0);
+        for c in whole.chars().chain(fraction.chars()) {
+            let digit = c.to_digit(10)?;
+            if magnitude > Wide::from_u128(u128::MAX) {
+                return None;
+            }
+            magnitude = magnitude.mul_u64(10).add(Wide::from_u128(digit as u128));
+        }
+        let mut scale = fraction.len() as i32 - exponent;
+        if scale < 0 {
+            if scale < -(MAX_SCALE as i32 + 1) {
+                return None;
+            }
+            magnitude = magnitude.mul_pow10(scale.unsigned_abs());
+            scale = 0;
+        }
+        // Far more fraction digits than a Decimal holds: drop them first
+        while scale > 2 * MAX_SCALE as i32 {
+            magnitude = magnitude.divrem_u64(10).0;
+            scale -= 1;
+        }
+        fit(negative, magnitude, scale as u32, Rounding::ToEven).ok()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Remove trailing zeros after the point: `1.500` becomes `1.5`.
+    pub fn normalize(self) -> Self {
+        let mut d = self;
+        while d.scale > 0 && d.mantissa.is_multiple_of(10) {
+            d.mantissa /= 10;
+            d.scale -= 1;
+        }
+        d
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(0.0)
+    }
+
+    /// The integral part, truncated toward zero, if it fits an i64.
+    pub fn to_i64(&self) -> Option<i64> {
+        let whole = self.mantissa / pow10(self.scale);
+        let whole = i64::try_from(whole).ok()?;
+        Some(if self.negative { -whole } else { whole })
+    }
+
+    /// The integral part, truncated toward zero.
+    pub fn to_i128(&self) -> i128 {
+        let whole = (self.mantissa / pow10(self.scale)) as i128;
+        if self.negative { -whole } else { whole }
+    }
+
+    pub fn negate(self) -> Self {
+        Decimal { negative: !self.negative && self.mantissa != 0, ..self }
+    }
+
+    pub fn abs(self) -> Self {
+        Decimal { negative: false, ..self }
+    }
+
+    /// Round to at most `places` digits after the point.
+    pub fn round(self, places: u32, mode: Rounding) -> Self {
+        if self.scale <= places {
+            return self;
+        }
+        let divisor = pow10(self.scale - places);
+        let (q, r) = (self.mantissa / divisor, self.mantissa % divisor);
+        let q = if round_up_wide(mode, self.negative, q, r, divisor, false) { q + 1 } else { q };
+        Decimal { negative: self.negative && q != 0, mantissa: q, scale: places }
+    }
+
+    /// Round to exactly `places` digits after the point, padding with zeros.
+    pub fn rescale(self, places: u32, mode: Rounding) -> Result<Self, RuntimeError> {
+        let places = places.min(MAX_SCALE);
+        let d = self.round(places, mode);
+        let mantissa = d.mantissa.checked_mul(pow10(places - d.scale)).filter(|m| *m <= MAX_MANTISSA).ok_or_else(overflow)?;
+        Ok(Decimal { mantissa, scale: places, ..d })
+    }
+
+    /// The magnitudes of `self` and `other` brought to a common scale.
+    fn aligned(&self, other: &Decimal) -> (Wide, Wide, u32) {
+        let scale = self.scale.max(other.scale);
+        let a = Wide::from_u128(self.mantissa).mul_pow10(scale - self.scale);
+        let b = Wide::from_u128(other.mantissa).mul_pow10(scale - other.scale);
+        (a, b, scale)
+    }
+
+    pub fn add(&self, other: &Decimal) -> Result<Decimal, RuntimeError> {
+        let (a, b, scale) = self.aligned(other);
+        if self.negative == other.negative {
+            return fit(self.negative, a.add(b), scale, Rounding::ToEven);
+        }
+        match a.cmp(&b) {
+            Ordering::Less => fit(other.negative, b.sub(a), scale, Rounding::ToEven),
+            _ => fit(self.negative, a.sub(b), scale, Rounding::ToEven),
+        }
+    }
+
+    pub fn sub(&self, other: &Decimal) -> Result<Decimal, RuntimeError> {
+        self.add(&other.negate())
+    }
+
+    pub fn mul(&self, other: &Decimal) -> Result<Decimal, RuntimeError> {
+        let product = Wide::from_u128(self.mantissa).mul_u128(other.mantissa);
+        fit(self.negative != other.negative, product, self.scale + other.scale, Rounding::ToEven)
+    }
+
+    pub fn div(&self, other: &Decimal) -> Result<Decimal, RuntimeError> {
+        if other.is_zero() {
+            return Err(RuntimeError::DivisionByZero);
+        }
+        let negative = self.negative != other.negative;
+        let divisor = other.mantissa;
+        let (mut q, mut r) = (self.mantissa / divisor, self.mantissa % divisor);
+        let mut scale = self.scale as i32 - other.scale as i32;
+        // A negative scale can't be stored: scale the quotient up to zero
+        while scale < 0 {
+            q = q.checked_mul(10).map(|q| q + r * 10 / divisor).filter(|q| *q <= MAX_MANTISSA).ok_or_else(overflow)?;
+            r = r * 10 % divisor;
+            scale += 1;
+        }
+        // Add digits until the division is exact or no more fit
+        while r != 0 && scale < MAX_SCALE as i32 {
+            let next = q * 10 + r * 10 / divisor;
+            if next > MAX_MANTISSA {
+                break;
+            }
+            q = next;
+            r = r * 10 % divisor;
+            scale += 1;
+        }
+        if round_up_wide(Rounding::ToEven, negative, q, r, divisor, false) {
+            q += 1;
+        }
+        fit(negative, Wide::from_u128(q), scale as u32, Rounding::ToEven)
+    }
+
+    /// VB's `Mod`: the remainder with the sign of the dividend.
+    pub fn rem(&self, other: &Decimal) -> Result<Decimal, RuntimeError> {
+        if other.is_zero() {
+            return Err(RuntimeError::DivisionByZero);
+        }
+        let (a, b, scale) = self.aligned(other);
+        let (a, b) = (a.to_u128().ok_or_else(overflow)?, b.to_u128().ok_or_else(overflow)?);
+        fit(self.negative, Wide::from_u128(a % b), scale, Rounding::ToEven)
+    }
+
+    /// Fixed-point text with exactly `places` digits after the point, halves
+    /// rounded away from zero as .NET's `F`/`N` formats do.
+    pub fn to_fixed(&self, places: u32) -> String {
+        match self.rescale(places, Rounding::AwayFromZero) {
+            Ok(d) => d.to_string(),
+            Err(_) => self.to_string(),
+        }
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, _) = self.aligned(other);
+        match (self.negative && !self.is_zero(), other.negative && !other.is_zero()) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => a.cmp(&b),
+            (true, true) => b.cmp(&a),
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = format!("{:0>width$}", self.mantissa, width = self.scale as usize + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - self.scale as usize);
+        let sign = if self.negative { "-" } else { "" };
+        if fraction.is_empty() {
+            write!(f, "{}{}", sign, whole)
+        } else {
+            write!(f, "{}{}.{}", sign, whole, fraction)
+        }
+    }
+}
+
+/// `value` as a Decimal, for `CDec` and arithmetic with a Decimal operand.
+pub fn to_decimal(value: &Value) -> Result<Decimal, RuntimeError> {
+    match value {
+        Value::Decimal(d) => Ok(*d),
+        Value::Boolean(_) | Value::SByte(_) | Value::Byte(_) | Value::Short(_) | Value::UShort(_)
+        | Value::Integer(_) | Value::UInteger(_) | Value::Long(_) | Value::ULong(_) => {
+            Decimal::from_i128(crate::integral::int_value(value).unwrap_or(0))
+        }
+        Value::Single(f) => Decimal::from_f32(*f),
+        Value::Double(d) => Decimal::from_f64(*d),
+        Value::Nothing => Ok(Decimal::ZERO),
+        Value::String(s) => Decimal::parse(&s.replace(',', "")).ok_or_else(|| RuntimeError::Exception(
+            "InvalidCastException".to_string(),
+            format!("Conversion from string \"{}\" to type 'Decimal' is not valid.", s),
+            None,
+        )),
+        other => Err(RuntimeError::TypeError { expected: "Decimal".to_string(), got: format!("{:?}", other) }),
+    }
+}
+
+/// `l op r` when either side is a Decimal and the other is an integral type
+/// or a Decimal, which VB evaluates as Decimal. `None` leaves the operation to
+/// the caller: with a Single, Double or String operand the result is a
+/// floating-point number, and `\` and `^` never produce a Decimal.
+pub fn binary_operation(symbol: &str, l: &Value, r: &Value) -> Result<Option<Value>, RuntimeError> {
+    let exact = |v: &Value| matches!(v, Value::Decimal(_) | Value::Nothing) || crate::integral::int_value(v).is_some();
+    if !matches!(l, Value::Decimal(_)) && !matches!(r, Value::Decimal(_)) || !exact(l) || !exact(r) {
+        return Ok(None);
+    }
+    let (a, b) = (to_decimal(l)?, to_decimal(r)?);
+    let result = match symbol {
+        "+" => Value::Decimal(a.add(&b)?),
+        "-" => Value::Decimal(a.sub(&b)?),
+        "*" => Value::Decimal(a.mul(&b)?),
+        "/" => Value::Decimal(a.div(&b)?),
+        "Mod" => Value::Decimal(a.rem(&b)?),
+        "=" => Value::Boolean(a == b),
+        "<>" => Value::Boolean(a != b),
+        "<" => Value::Boolean(a < b),
+        "<=" => Value::Boolean(a <= b),
+        ">" => Value::Boolean(a > b),
+        ">=" => Value::Boolean(a >= b),
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
+
+/// `value` with `places` fixed decimals: a Decimal exactly, anything else
+/// through its Double value.
+pub fn format_fixed(value: &Value, places: usize) -> Result<String, RuntimeError> {
+    match value {
+        Value::Decimal(d) => Ok(d.to_fixed(places as u32)),
+        other => Ok(format!("{:.prec$}", other.as_double()?, prec = places)),
+    }
+}
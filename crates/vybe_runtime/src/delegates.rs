@@ -0,0 +1,116 @@
+//! Delegates and the handler lists of events.
+//!
+//! `AddressOf obj.OnChanged` makes an object of class [`DELEGATE_CLASS`]
+//! naming the method and the instance it is called on (`Nothing` for a
+//! module procedure). Two delegates are equal when they name the same method
+//! on the same instance, so removing the handler of one instance leaves the
+//! same method of another instance subscribed. Lambdas serve as delegates
+//! too.
+//!
+//! An ordinary `Event` keeps its handlers in a field of the instance that
+//! raises it, so each instance has its own subscribers. A `Custom Event`
+//! keeps them wherever its `AddHandler` accessor puts them.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::value::{ObjectData, Value};
+
+/// Class name of delegate instances.
+pub const DELEGATE_CLASS: &str = "Delegate";
+
+const METHOD_FIELD: &str = "__method";
+const TARGET_FIELD: &str = "__target";
+
+/// A delegate calling `method` on `target`, or the module procedure
+/// `method` when there is no target.
+pub fn delegate_object(method: &str, target: Option<Rc<RefCell<ObjectData>>>) -> Value {
+    let mut fields = HashMap::new();
+    fields.insert(METHOD_FIELD.to_string(), Value::String(method.to_string()));
+    fields.insert(TARGET_FIELD.to_string(), target.map_or(Value::Nothing, Value::Object));
+    Value::Object(Rc::new(RefCell::new(ObjectData {
+        class_name: DELEGATE_CLASS.to_string(),
+        fields,
+        drawing_commands: Vec::new(),
+    })))
+}
+
+pub fn is_delegate(obj: &ObjectData) -> bool {
+    obj.class_name == DELEGATE_CLASS
+}
+
+/// Whether `value` can be invoked as a delegate.
+pub fn is_callable(value: &Value) -> bool {
+    match value {
+        Value::Lambda { .. } => true,
+        Value::Object(obj) => is_delegate(&obj.borrow()),
+        _ => false,
+    }
+}
+
+/// The method a delegate calls, as written after `AddressOf`.
+pub fn method(obj: &ObjectData) -> String {
+    obj.fields.get(METHOD_FIELD).map(|m| m.as_string()).unwrap_or_default()
+}
+
+/// The instance a delegate calls its method on.
+pub fn target(obj: &ObjectData) -> Option<Rc<RefCell<ObjectData>>> {
+    match obj.fields.get(TARGET_FIELD) {
+        Some(Value::Object(target)) => Some(target.clone()),
+        _ => None,
+    }
+}
+
+/// Two delegates for the same method on the same instance.
+pub fn delegate_equal(a: &ObjectData, b: &ObjectData) -> bool {
+    if !is_delegate(a) || !is_delegate(b) || !method(a).eq_ignore_ascii_case(&method(b)) {
+        return false;
+    }
+    match (target(a), target(b)) {
+        (Some(x), Some(y)) => Rc::ptr_eq(&x, &y),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Equality of collection items: delegates as [`delegate_equal`], without
+/// comparing the instances they hold field by field.
+pub fn items_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Object(x), Value::Object(y)) if is_delegate(&x.borrow()) || is_delegate(&y.borrow()) => {
+            Rc::ptr_eq(x, y) || delegate_equal(&x.borrow(), &y.borrow())
+        }
+        _ => a == b,
+    }
+}
+
+/// Field of the raising instance holding the handlers of an ordinary event.
+fn handlers_field(event: &str) -> String {
+    format!("__event_{}", event.to_lowercase())
+}
+
+/// The handlers subscribed to `event` on `obj`, in subscription order.
+pub fn handlers(obj: &ObjectData, event: &str) -> Vec<Value> {
+    match obj.fields.get(&handlers_field(event)) {
+        Some(Value::Array(handlers)) => handlers.clone(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn add_handler(obj: &mut ObjectData, event: &str, handler: Value) {
+    let list = obj.fields.entry(handlers_field(event)).or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(handlers) = list {
+        handlers.push(handler);
+    }
+}
+
+/// Unsubscribes the first handler equal to `handler`, as VB does when the
+/// same handler was added more than once.
+pub fn remove_handler(obj: &mut ObjectData, event: &str, handler: &Value) {
+    if let Some(Value::Array(handlers)) = obj.fields.get_mut(&handlers_field(event))
+        && let Some(pos) = handlers.iter().position(|h| items_equal(h, handler))
+    {
+        handlers.remove(pos);
+    }
+}
@@ -1,86 +1,309 @@
+use crate::symbol::{NameHasher, Symbol, SymbolMap};
 use crate::value::{RuntimeError, Value};
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasherDefault;
+use vybe_parser::{Span, VBType};
 
-#[derive(Debug, Clone, PartialEq)]
+/// Where a variable lives: its scope and its index in that scope. Valid while
+/// [`Environment::epoch`] is unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    scope: usize,
+    index: usize,
+}
+
+/// The variables the tree walker has looked up, by where they are written:
+/// its counterpart of the bytecode's [`Variable`](crate::bytecode::Variable).
+/// A name read or assigned at the same place again keeps its symbol, and its
+/// slot while the [`Environment::epoch`] is unchanged, so running a line a
+/// second time neither interns the name nor searches the scopes.
+#[derive(Debug, Default)]
+pub struct Resolutions {
+    entries: HashMap<Span, Resolution, BuildHasherDefault<NameHasher>>,
+}
+
+#[derive(Debug)]
+struct Resolution {
+    /// The name as written; another file can have a different one at the
+    /// same span.
+    name: String,
+    symbol: Symbol,
+    /// The local slot found last, or `None` for a name that wasn't a local,
+    /// with the epoch it was found under.
+    slot: Option<(u64, Option<Slot>)>,
+}
+
+impl Resolution {
+    fn new(name: &str) -> Self {
+        Resolution { name: name.to_string(), symbol: Symbol::intern(name), slot: None }
+    }
+}
+
+impl Resolutions {
+    /// The symbol of `name` written at `span`, and its slot if it names a
+    /// local in `env`.
+    pub fn local(&mut self, span: Span, name: &str, env: &Environment) -> (Symbol, Option<Slot>) {
+        if span.is_unknown() {
+            let symbol = Symbol::intern(name);
+            return (symbol, env.resolve_local(symbol));
+        }
+        let entry = self.entries.entry(span).or_insert_with(|| Resolution::new(name));
+        if entry.name != name {
+            *entry = Resolution::new(name);
+        }
+        match entry.slot {
+            Some((epoch, slot)) if epoch == env.epoch() => (entry.symbol, slot),
+            _ => {
+                let slot = env.resolve_local(entry.symbol);
+                entry.slot = Some((env.epoch(), slot));
+                (entry.symbol, slot)
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// The variables of one scope, each in a slot that keeps its index for the
+/// life of the scope. A scope taken out of an environment keeps its symbols,
+/// so putting it back (a suspended iterator resuming) doesn't intern the
+/// names again.
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    slots: SymbolMap<usize>,
+    values: Vec<Value>,
+    types: SymbolMap<VBType>, // Declared `As` types
+}
+
+impl Scope {
+    fn get(&self, symbol: Symbol) -> Option<&Value> {
+        self.slots.get(&symbol).map(|&index| &self.values[index])
+    }
+
+    fn contains(&self, symbol: Symbol) -> bool {
+        self.slots.contains_key(&symbol)
+    }
+
+    /// Store `value` under `symbol`; true when that added a new slot.
+    fn insert(&mut self, symbol: Symbol, value: Value) -> bool {
+        match self.slots.get(&symbol) {
+            Some(&index) => {
+                self.values[index] = value;
+                false
+            }
+            None => {
+                self.slots.insert(symbol, self.values.len());
+                self.values.push(value);
+                true
+            }
+        }
+    }
+
+    fn variables(&self) -> impl Iterator<Item = (Symbol, &Value)> {
+        self.slots.iter().map(|(&symbol, &index)| (symbol, &self.values[index]))
+    }
+
+    /// The variable called `name`, in any case.
+    pub fn value(&self, name: &str) -> Option<&Value> {
+        self.get(Symbol::intern(name))
+    }
+
+    pub fn from_variables(vars: impl IntoIterator<Item = (String, Value)>) -> Scope {
+        let mut scope = Scope::default();
+        for (name, value) in vars {
+            scope.insert(Symbol::intern(&name), value);
+        }
+        scope
+    }
+}
+
+impl PartialEq for Scope {
+    fn eq(&self, other: &Self) -> bool {
+        self.slots.len() == other.slots.len()
+            && self.variables().all(|(symbol, value)| other.get(symbol) == Some(value))
+            && self.types == other.types
+    }
+}
+
+thread_local! {
+    static NEXT_EPOCH: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A number no environment on this thread has had before.
+fn fresh_epoch() -> u64 {
+    NEXT_EPOCH.with(|next| {
+        let epoch = next.get() + 1;
+        next.set(epoch);
+        epoch
+    })
+}
+
+#[derive(Debug, Clone)]
 pub struct Environment {
-    scopes: Vec<HashMap<String, Value>>,
-    constants: HashSet<String>, // Track constant names across all scopes
+    scopes: Vec<Scope>,
+    constants: HashSet<Symbol>, // Track constant names across all scopes
+    /// Changes whenever a scope or a variable is added or removed, so a
+    /// [`Slot`] found under one epoch can be reused until the next.
+    epoch: u64,
+}
+
+impl PartialEq for Environment {
+    fn eq(&self, other: &Self) -> bool {
+        self.scopes == other.scopes && self.constants == other.constants
+    }
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
-            scopes: vec![HashMap::new()],
+            scopes: vec![Scope::default()],
             constants: HashSet::new(),
+            epoch: fresh_epoch(),
         }
     }
 
     pub fn push_scope(&mut self) {
-        self.scopes.push(HashMap::new());
+        self.scopes.push(Scope::default());
+        self.epoch = fresh_epoch();
     }
 
     pub fn pop_scope(&mut self) {
         if self.scopes.len() > 1 {
             self.scopes.pop();
+            self.epoch = fresh_epoch();
         }
     }
 
+    /// Push `scope`, e.g. the saved locals of a suspended iterator.
+    pub fn push_scope_with(&mut self, scope: Scope) {
+        self.scopes.push(scope);
+        self.epoch = fresh_epoch();
+    }
+
+    /// Pop the innermost scope and return it.
+    pub fn take_scope(&mut self) -> Scope {
+        if self.scopes.len() > 1 {
+            self.epoch = fresh_epoch();
+            self.scopes.pop().unwrap_or_default()
+        } else {
+            Scope::default()
+        }
+    }
+
+    /// The stamp [`Slot`]s found by [`Self::resolve`] are valid under.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// The slot of the innermost variable `symbol`.
+    pub fn resolve(&self, symbol: Symbol) -> Option<Slot> {
+        self.find(symbol, 0)
+    }
+
+    /// The slot of the innermost variable `symbol` in any scope but the
+    /// global one.
+    pub fn resolve_local(&self, symbol: Symbol) -> Option<Slot> {
+        self.find(symbol, 1)
+    }
+
+    fn find(&self, symbol: Symbol, outermost: usize) -> Option<Slot> {
+        self.scopes.iter().enumerate().skip(outermost).rev()
+            .find_map(|(scope, s)| s.slots.get(&symbol).map(|&index| Slot { scope, index }))
+    }
+
+    /// The value in `slot`.
+    pub fn slot(&self, slot: Slot) -> &Value {
+        &self.scopes[slot.scope].values[slot.index]
+    }
+
+    /// Replace the value in `slot`; constants are the caller's to check.
+    pub fn store(&mut self, slot: Slot, value: Value) {
+        self.scopes[slot.scope].values[slot.index] = value;
+    }
+
+    /// Is `symbol` a constant?
+    pub fn is_const(&self, symbol: Symbol) -> bool {
+        self.constants.contains(&symbol)
+    }
+
+    /// Record the declared type of `name` in the scope that holds it.
+    pub fn declare_type(&mut self, name: &str, ty: VBType) {
+        let symbol = Symbol::intern(name);
+        let index = self.scopes.iter().rposition(|s| s.contains(symbol))
+            .unwrap_or(self.scopes.len() - 1);
+        if let Some(scope) = self.scopes.get_mut(index) {
+            scope.types.insert(symbol, ty);
+        }
+    }
+
+    /// The declared type of the innermost variable called `name`, if it was declared with one.
+    pub fn declared_type(&self, name: &str) -> Option<&VBType> {
+        self.declared_type_of(Symbol::intern(name))
+    }
+
+    /// [`Self::declared_type`] by symbol.
+    pub fn declared_type_of(&self, symbol: Symbol) -> Option<&VBType> {
+        let scope = self.scopes.iter().rev().find(|s| s.contains(symbol))?;
+        scope.types.get(&symbol)
+    }
+
     pub fn define(&mut self, name: impl Into<String>, value: Value) {
-        let name = name.into().to_lowercase();
-        if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name, value);
+        self.define_symbol(Symbol::intern(&name.into()), value);
+    }
+
+    /// [`Self::define`] by symbol.
+    pub fn define_symbol(&mut self, symbol: Symbol, value: Value) {
+        if let Some(scope) = self.scopes.last_mut() && scope.insert(symbol, value) {
+            self.epoch = fresh_epoch();
         }
     }
 
     pub fn define_global(&mut self, name: impl Into<String>, value: Value) {
-        let name = name.into().to_lowercase();
-        if let Some(scope) = self.scopes.first_mut() {
-            scope.insert(name, value);
+        let symbol = Symbol::intern(&name.into());
+        if let Some(scope) = self.scopes.first_mut() && scope.insert(symbol, value) {
+            self.epoch = fresh_epoch();
         }
     }
 
     pub fn define_const(&mut self, name: impl Into<String>, value: Value) {
-        let name = name.into().to_lowercase();
-        self.constants.insert(name.clone());
-        if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name, value);
-        }
+        let symbol = Symbol::intern(&name.into());
+        self.constants.insert(symbol);
+        self.define_symbol(symbol, value);
     }
 
     pub fn set(&mut self, name: &str, value: Value) -> Result<(), RuntimeError> {
-        let name_lower = name.to_lowercase();
+        let symbol = Symbol::intern(name);
 
         // Check if this is a constant
-        if self.constants.contains(&name_lower) {
+        if self.constants.contains(&symbol) {
             return Err(RuntimeError::Custom(format!(
                 "Cannot assign to constant '{}'",
                 name
             )));
         }
 
-        // Search from innermost to outermost scope
-        for scope in self.scopes.iter_mut().rev() {
-            if scope.contains_key(&name_lower) {
-                scope.insert(name_lower, value);
-                return Ok(());
-            }
+        // Search from innermost to outermost scope; if not found, define in
+        // current scope
+        match self.resolve(symbol) {
+            Some(slot) => self.store(slot, value),
+            None => self.define_symbol(symbol, value),
         }
-
-        // If not found, define in current scope
-        self.define(name_lower, value);
         Ok(())
     }
 
     pub fn get(&self, name: &str) -> Result<Value, RuntimeError> {
-        let name_lower = name.to_lowercase();
-        // Search from innermost to outermost scope
-        for scope in self.scopes.iter().rev() {
-            if let Some(value) = scope.get(&name_lower) {
-                return Ok(value.clone());
-            }
+        match self.resolve(Symbol::intern(name)) {
+            Some(slot) => Ok(self.slot(slot).clone()),
+            None => Err(RuntimeError::UndefinedVariable(name.to_string())),
         }
+    }
 
-        Err(RuntimeError::UndefinedVariable(name.to_string()))
+    /// Is `name` declared in any scope?
+    pub fn is_defined(&self, name: &str) -> bool {
+        self.resolve(Symbol::intern(name)).is_some()
     }
 
     pub fn get_or_nothing(&self, name: &str) -> Value {
@@ -88,62 +311,47 @@ impl Environment {
     }
 
     pub fn exists_in_current_scope(&self, name: &str) -> bool {
-        let name_lower = name.to_lowercase();
-        if let Some(scope) = self.scopes.last() {
-            scope.contains_key(&name_lower)
-        } else {
-            false
-        }
+        let symbol = Symbol::intern(name);
+        self.scopes.last().is_some_and(|scope| scope.contains(symbol))
     }
 
     /// Check if a variable exists in any scope EXCEPT the global scope (index 0)
     pub fn has_local(&self, name: &str) -> bool {
-        let name_lower = name.to_lowercase();
-        if self.scopes.len() <= 1 {
-            return false;
-        }
-        for scope in self.scopes.iter().skip(1) {
-            if scope.contains_key(&name_lower) {
-                return true;
-            }
-        }
-        false
+        self.resolve_local(Symbol::intern(name)).is_some()
+    }
+
+    /// The innermost variable `symbol` in any scope but the global one.
+    pub fn local(&self, symbol: Symbol) -> Option<&Value> {
+        self.resolve_local(symbol).map(|slot| self.slot(slot))
     }
 
     /// Get a variable from the global scope ONLY
     pub fn get_global(&self, name: &str) -> Option<Value> {
-        let name_lower = name.to_lowercase();
-        self.scopes.first().and_then(|s| s.get(&name_lower)).cloned()
+        self.scopes.first().and_then(|s| s.get(Symbol::intern(name))).cloned()
     }
 
     /// Deep clone the environment for snapshot threading.
     pub fn deep_clone(&self) -> Self {
-        let new_scopes = self.scopes.iter().map(|scope| {
-            let mut new_scope = HashMap::new();
-            for (k, v) in scope {
-                new_scope.insert(k.clone(), v.deep_clone());
-            }
-            new_scope
+        let new_scopes = self.scopes.iter().map(|scope| Scope {
+            values: scope.values.iter().map(|v| v.deep_clone()).collect(),
+            ..scope.clone()
         }).collect();
-        
+
         Self {
             scopes: new_scopes,
             constants: self.constants.clone(),
+            epoch: fresh_epoch(),
         }
     }
 
     pub fn to_shared(&self) -> crate::value::SharedEnvironment {
         let new_scopes = self.scopes.iter().map(|scope| {
-            let mut new_scope = HashMap::new();
-            for (k, v) in scope {
-                new_scope.insert(k.clone(), v.to_shared());
-            }
-            new_scope
+            scope.variables().map(|(symbol, v)| (symbol.name().to_string(), v.to_shared())).collect()
         }).collect();
-        
+
         crate::value::SharedEnvironment {
             scopes: new_scopes,
-            constants: self.constants.clone(),
+            constants: self.constants.iter().map(|c| c.name().to_string()).collect(),
         }
     }
 }
@@ -151,16 +359,13 @@ impl Environment {
 impl crate::value::SharedEnvironment {
     pub fn to_environment(&self) -> Environment {
         let new_scopes = self.scopes.iter().map(|scope| {
-            let mut new_scope = HashMap::new();
-            for (k, v) in scope {
-                new_scope.insert(k.clone(), v.to_value());
-            }
-            new_scope
-        }).collect();
-        
+            Scope::from_variables(scope.iter().map(|(k, v)| (k.clone(), v.to_value())))
+        }).collect::<Vec<_>>();
+
         Environment {
             scopes: new_scopes,
-            constants: self.constants.clone(),
+            constants: self.constants.iter().map(|c| Symbol::intern(c)).collect(),
+            epoch: fresh_epoch(),
         }
     }
 }
@@ -176,8 +381,8 @@ impl Environment {
     pub fn all_variables(&self) -> Vec<(String, Value)> {
         let mut result = Vec::new();
         for scope in &self.scopes {
-            for (name, value) in scope {
-                result.push((name.clone(), value.clone()));
+            for (symbol, value) in scope.variables() {
+                result.push((symbol.name().to_string(), value.clone()));
             }
         }
         result
@@ -1,23 +1,30 @@
 use crate::environment::Environment;
 use crate::value::{RuntimeError, Value};
-use vybe_parser::Expression;
+use vybe_parser::{ExprKind, Expression};
 
 pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeError> {
-    match expr {
-        Expression::IntegerLiteral(i) => Ok(Value::Integer(*i)),
-        Expression::DoubleLiteral(d) => Ok(Value::Double(*d)),
-        Expression::StringLiteral(s) => Ok(Value::String(s.clone())),
-        Expression::BooleanLiteral(b) => Ok(Value::Boolean(*b)),
-        Expression::DateLiteral(s) => {
+    match &expr.kind {
+        ExprKind::IntegerLiteral(i) => Ok(Value::Integer(*i)),
+        ExprKind::DoubleLiteral(d) => Ok(Value::Double(*d)),
+        ExprKind::LongLiteral(l) => Ok(Value::Long(*l)),
+        ExprKind::SingleLiteral(f) => Ok(Value::Single(*f)),
+        ExprKind::ShortLiteral(s) => Ok(Value::Short(*s)),
+        ExprKind::UShortLiteral(u) => Ok(Value::UShort(*u)),
+        ExprKind::UIntegerLiteral(u) => Ok(Value::UInteger(*u)),
+        ExprKind::ULongLiteral(u) => Ok(Value::ULong(*u)),
+        ExprKind::DecimalLiteral(s) => crate::decimal::Decimal::parse(s).map(Value::Decimal).ok_or_else(crate::decimal::overflow),
+        ExprKind::StringLiteral(s) => Ok(Value::String(s.clone())),
+        ExprKind::BooleanLiteral(b) => Ok(Value::Boolean(*b)),
+        ExprKind::DateLiteral(s) => {
             // Parse the date string from #...# literal and convert to OLE date
             crate::builtins::cdate_fn(&[Value::String(s.clone())])
         }
-        Expression::Nothing => Ok(Value::Nothing),
+        ExprKind::Nothing => Ok(Value::Nothing),
 
-        Expression::Variable(name) => env.get(name.as_str()),
+        ExprKind::Variable(name) => env.get(name.as_str()),
 
-        Expression::MemberAccess(obj, member) => {
-            if let Expression::Variable(name) = obj.as_ref() {
+        ExprKind::MemberAccess(obj, member) => {
+            if let ExprKind::Variable(name) = &obj.kind {
                 let flat_key = format!("{}.{}", name.as_str(), member.as_str());
                 if let Ok(val) = env.get(&flat_key) {
                     return Ok(val);
@@ -38,19 +45,21 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             }
         }
 
-        Expression::Add(left, right) => {
+        ExprKind::Add(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::decimal::binary_operation("+", &l, &r)? {
+                return Ok(result);
+            }
+            if let Some(result) = crate::integral::binary_operation("+", &l, &r)? {
+                return Ok(result);
+            }
 
             match (&l, &r) {
-                (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a + b)),
                 (Value::Date(d), Value::Double(n)) => Ok(Value::Date(d + n)),
                 (Value::Double(n), Value::Date(d)) => Ok(Value::Date(d + n)),
                 (Value::Date(d), Value::Integer(n)) => Ok(Value::Date(d + *n as f64)),
                 (Value::Integer(n), Value::Date(d)) => Ok(Value::Date(d + *n as f64)),
-                (Value::Long(a), Value::Long(b)) => Ok(Value::Long(a + b)),
-                (Value::Integer(a), Value::Long(b)) => Ok(Value::Long(*a as i64 + *b)),
-                (Value::Long(a), Value::Integer(b)) => Ok(Value::Long(*a + *b as i64)),
                 _ => {
                     let a = l.as_double()?;
                     let b = r.as_double()?;
@@ -58,18 +67,20 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
                 }
             }
         }
-        Expression::Subtract(left, right) => {
+        ExprKind::Subtract(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::decimal::binary_operation("-", &l, &r)? {
+                return Ok(result);
+            }
+            if let Some(result) = crate::integral::binary_operation("-", &l, &r)? {
+                return Ok(result);
+            }
 
             let res = match (&l, &r) {
-                (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a - b)),
                 (Value::Date(d), Value::Double(n)) => Ok(Value::Date(d - n)),
                 (Value::Date(d1), Value::Date(d2)) => Ok(Value::Double(d1 - d2)),
                 (Value::Date(d), Value::Integer(n)) => Ok(Value::Date(d - *n as f64)),
-                (Value::Long(a), Value::Long(b)) => Ok(Value::Long(a - b)),
-                (Value::Integer(a), Value::Long(b)) => Ok(Value::Long(*a as i64 - *b)),
-                (Value::Long(a), Value::Integer(b)) => Ok(Value::Long(*a - *b as i64)),
                 _ => {
                     let a = l.as_double()?;
                     let b = r.as_double()?;
@@ -79,26 +90,27 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             res
         }
 
-        Expression::Multiply(left, right) => {
+        ExprKind::Multiply(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
-
-            match (&l, &r) {
-                (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a * b)),
-                (Value::Long(a), Value::Long(b)) => Ok(Value::Long(a * b)),
-                (Value::Integer(a), Value::Long(b)) => Ok(Value::Long(*a as i64 * *b)),
-                (Value::Long(a), Value::Integer(b)) => Ok(Value::Long(*a * *b as i64)),
-                _ => {
-                    let a = l.as_double()?;
-                    let b = r.as_double()?;
-                    Ok(Value::Double(a * b))
-                }
+            if let Some(result) = crate::decimal::binary_operation("*", &l, &r)? {
+                return Ok(result);
             }
+            if let Some(result) = crate::integral::binary_operation("*", &l, &r)? {
+                return Ok(result);
+            }
+
+            let a = l.as_double()?;
+            let b = r.as_double()?;
+            Ok(Value::Double(a * b))
         }
 
-        Expression::Divide(left, right) => {
+        ExprKind::Divide(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::decimal::binary_operation("/", &l, &r)? {
+                return Ok(result);
+            }
 
             let a = l.as_double()?;
             let b = r.as_double()?;
@@ -110,59 +122,74 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Double(a / b))
         }
 
-        Expression::IntegerDivide(left, right) => {
+        ExprKind::IntegerDivide(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::integral::binary_operation("\\", &l, &r)? {
+                return Ok(result);
+            }
             let a = l.as_integer()?;
             let b = r.as_integer()?;
             if b == 0 { return Err(RuntimeError::DivisionByZero); }
             Ok(Value::Integer(a / b))
         }
 
-        Expression::Exponent(left, right) => {
+        ExprKind::Exponent(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
             Ok(Value::Double(l.as_double()?.powf(r.as_double()?)))
         }
 
-        Expression::Modulo(left, right) => {
+        ExprKind::Modulo(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::decimal::binary_operation("Mod", &l, &r)? {
+                return Ok(result);
+            }
+            if let Some(result) = crate::integral::binary_operation("Mod", &l, &r)? {
+                return Ok(result);
+            }
 
-            let a = l.as_integer()?;
-            let b = r.as_integer()?;
+            let a = l.as_double()?;
+            let b = r.as_double()?;
 
-            if b == 0 {
+            if b == 0.0 {
                 return Err(RuntimeError::DivisionByZero);
             }
 
-            Ok(Value::Integer(a % b))
+            Ok(Value::Double(a % b))
         }
 
-        Expression::Concatenate(left, right) => {
+        ExprKind::Concatenate(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
 
             Ok(Value::String(format!("{}{}", l.as_string(), r.as_string())))
         }
 
-        Expression::Equal(left, right) => {
+        ExprKind::Equal(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
 
             Ok(Value::Boolean(values_equal(&l, &r)))
         }
 
-        Expression::NotEqual(left, right) => {
+        ExprKind::NotEqual(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
 
             Ok(Value::Boolean(!values_equal(&l, &r)))
         }
 
-        Expression::LessThan(left, right) => {
+        ExprKind::LessThan(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::decimal::binary_operation("<", &l, &r)? {
+                return Ok(result);
+            }
+            if let Some(result) = crate::integral::binary_operation("<", &l, &r)? {
+                return Ok(result);
+            }
 
             let result = match (&l, &r) {
                 (Value::Integer(a), Value::Integer(b)) => a < b,
@@ -173,9 +200,15 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(result))
         }
 
-        Expression::LessThanOrEqual(left, right) => {
+        ExprKind::LessThanOrEqual(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::decimal::binary_operation("<=", &l, &r)? {
+                return Ok(result);
+            }
+            if let Some(result) = crate::integral::binary_operation("<=", &l, &r)? {
+                return Ok(result);
+            }
 
             let result = match (&l, &r) {
                 (Value::Integer(a), Value::Integer(b)) => a <= b,
@@ -186,9 +219,15 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(result))
         }
 
-        Expression::GreaterThan(left, right) => {
+        ExprKind::GreaterThan(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::decimal::binary_operation(">", &l, &r)? {
+                return Ok(result);
+            }
+            if let Some(result) = crate::integral::binary_operation(">", &l, &r)? {
+                return Ok(result);
+            }
 
             let result = match (&l, &r) {
                 (Value::Integer(a), Value::Integer(b)) => a > b,
@@ -199,9 +238,15 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(result))
         }
 
-        Expression::GreaterThanOrEqual(left, right) => {
+        ExprKind::GreaterThanOrEqual(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::decimal::binary_operation(">=", &l, &r)? {
+                return Ok(result);
+            }
+            if let Some(result) = crate::integral::binary_operation(">=", &l, &r)? {
+                return Ok(result);
+            }
 
             let result = match (&l, &r) {
                 (Value::Integer(a), Value::Integer(b)) => a >= b,
@@ -212,9 +257,12 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(result))
         }
 
-        Expression::And(left, right) => {
+        ExprKind::And(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::integral::binary_operation("And", &l, &r)? {
+                return Ok(result);
+            }
             match (&l, &r) {
                 (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(*a && *b)),
                 _ => {
@@ -225,9 +273,12 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             }
         }
 
-        Expression::Or(left, right) => {
+        ExprKind::Or(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::integral::binary_operation("Or", &l, &r)? {
+                return Ok(result);
+            }
             match (&l, &r) {
                 (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(*a || *b)),
                 _ => {
@@ -238,9 +289,12 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             }
         }
 
-        Expression::Xor(left, right) => {
+        ExprKind::Xor(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
+            if let Some(result) = crate::integral::binary_operation("Xor", &l, &r)? {
+                return Ok(result);
+            }
             match (&l, &r) {
                 (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(*a ^ *b)),
                 _ => {
@@ -251,7 +305,7 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             }
         }
 
-        Expression::AndAlso(left, right) => {
+        ExprKind::AndAlso(left, right) => {
             let l = evaluate(left, env)?;
             if !l.as_bool()? {
                 return Ok(Value::Boolean(false));
@@ -260,7 +314,7 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(r.as_bool()?))
         }
 
-        Expression::OrElse(left, right) => {
+        ExprKind::OrElse(left, right) => {
             let l = evaluate(left, env)?;
             if l.as_bool()? {
                 return Ok(Value::Boolean(true));
@@ -269,7 +323,7 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(r.as_bool()?))
         }
 
-        Expression::Is(left, right) => {
+        ExprKind::Is(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
             let result = match (&l, &r) {
@@ -280,7 +334,7 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(result))
         }
 
-        Expression::IsNot(left, right) => {
+        ExprKind::IsNot(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
             let result = match (&l, &r) {
@@ -291,7 +345,7 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(result))
         }
 
-        Expression::Like(left, right) => {
+        ExprKind::Like(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
             let text = l.as_string();
@@ -299,30 +353,27 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Boolean(vb_like_match(&text, &pattern)))
         }
 
-        Expression::TypeOf { .. } => {
+        ExprKind::TypeOf { .. } => {
             Err(RuntimeError::Custom("TypeOf cannot be evaluated in constant expressions".to_string()))
         }
 
-        Expression::BitShiftLeft(left, right) => {
+        ExprKind::BitShiftLeft(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
-            let val = l.as_long()?;
-            let shift = r.as_integer()?;
-            let shift_masked = shift & 63;
-            Ok(Value::Long(val << shift_masked))
+            crate::integral::shift("<<", &l, &r)
         }
 
-        Expression::BitShiftRight(left, right) => {
+        ExprKind::BitShiftRight(left, right) => {
             let l = evaluate(left, env)?;
             let r = evaluate(right, env)?;
-            let val = l.as_long()?;
-            let shift = r.as_integer()?;
-            let shift_masked = shift & 63;
-            Ok(Value::Long(val >> shift_masked))
+            crate::integral::shift(">>", &l, &r)
         }
 
-        Expression::Not(operand) => {
+        ExprKind::Not(operand) => {
             let val = evaluate(operand, env)?;
+            if let Some(result) = crate::integral::not(&val)? {
+                return Ok(result);
+            }
             match val {
                 Value::Boolean(b) => Ok(Value::Boolean(!b)),
                 _ => {
@@ -332,11 +383,14 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             }
         }
 
-        Expression::Negate(operand) => {
+        ExprKind::Negate(operand) => {
             let val = evaluate(operand, env)?;
+            if let Some(result) = crate::integral::negate(&val)? {
+                return Ok(result);
+            }
             match val {
-                Value::Integer(i) => Ok(Value::Integer(-i)),
                 Value::Double(d) => Ok(Value::Double(-d)),
+                Value::Decimal(d) => Ok(Value::Decimal(d.negate())),
                 _ => {
                     let d = val.as_double()?;
                     Ok(Value::Double(-d))
@@ -344,7 +398,7 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             }
         }
 
-        Expression::ArrayLiteral(elements) => {
+        ExprKind::ArrayLiteral(elements) => {
             let vals: Result<Vec<Value>, RuntimeError> = elements
                 .iter()
                 .map(|e| evaluate(e, env))
@@ -352,20 +406,22 @@ pub fn evaluate(expr: &Expression, env: &Environment) -> Result<Value, RuntimeEr
             Ok(Value::Array(vals?))
         }
 
-        Expression::ArrayAccess(array, indices) => {
+        ExprKind::ArrayAccess(array, indices) => {
             let arr = env.get(array.as_str())?;
             let index = evaluate(&indices[0], env)?.as_integer()? as usize;
             arr.get_array_element(index)
         }
 
-        Expression::Call(_, _) | Expression::MethodCall(_, _, _) | Expression::New(_, _) | Expression::NewFromInitializer(_, _, _) | Expression::NewWithInitializer(_, _, _) | Expression::Me | Expression::MyBase | Expression::WithTarget | Expression::IfExpression(_, _, _) | Expression::AddressOf(_) | Expression::Cast { .. } | Expression::Query(_) | Expression::XmlLiteral(_) => {
+        ExprKind::NamedArgument(_, value) => evaluate(value, env),
+
+        ExprKind::Call(_, _) | ExprKind::MethodCall(_, _, _) | ExprKind::New(_, _) | ExprKind::NewFromInitializer(_, _, _) | ExprKind::NewWithInitializer(_, _, _) | ExprKind::NewAnonymous(_) | ExprKind::TupleLiteral(_) | ExprKind::Me | ExprKind::MyBase | ExprKind::WithTarget | ExprKind::IfExpression(_, _, _) | ExprKind::AddressOf(_) | ExprKind::Cast { .. } | ExprKind::Query(_) | ExprKind::XmlLiteral(_) | ExprKind::XmlAxis(..) => {
             // These are handled in the interpreter
             Err(RuntimeError::Custom("Expression must be evaluated in interpreter context".to_string()))
         }
-        Expression::Lambda { .. } => {
+        ExprKind::Lambda { .. } => {
             Err(RuntimeError::Custom("Lambdas cannot be evaluated in constant expressions".to_string()))
         }
-        Expression::Await(operand) => {
+        ExprKind::Await(operand) => {
             let val = evaluate(operand, env)?;
             
             // Check if it's a Task object
@@ -457,10 +513,18 @@ pub fn values_equal(l: &Value, r: &Value) -> bool {
         (Value::Long(a), Value::Long(b)) => a == b,
         (Value::Single(a), Value::Single(b)) => a == b,
         (Value::Double(a), Value::Double(b)) => a == b,
+        (Value::Decimal(a), Value::Decimal(b)) => a == b,
         (Value::Date(a), Value::Date(b)) => a == b,
+        _ if crate::integral::IntType::of(l).is_some() && crate::integral::IntType::of(r).is_some() => {
+            crate::integral::int_value(l) == crate::integral::int_value(r)
+        }
         (Value::String(a), Value::String(b)) => a.eq_ignore_ascii_case(b),
         (Value::Nothing, Value::Nothing) => true,
-        (Value::Object(a), Value::Object(b)) => std::rc::Rc::ptr_eq(a, b),
+        (Value::Object(a), Value::Object(b)) => {
+            std::rc::Rc::ptr_eq(a, b) || crate::query::anonymous_equal(&a.borrow(), &b.borrow())
+                || crate::tuples::tuple_equal(&a.borrow(), &b.borrow())
+                || crate::delegates::delegate_equal(&a.borrow(), &b.borrow())
+        }
         // Coercion
         (Value::String(s), Value::Nothing) | (Value::Nothing, Value::String(s)) => s.is_empty(),
         (Value::String(s), other) | (other, Value::String(s)) => {
@@ -1,5 +1,5 @@
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use crate::value::RuntimeError;
 
@@ -9,9 +9,14 @@ pub enum FileHandle {
     Output(BufWriter<File>),
     Append(BufWriter<File>),
     Binary(File),
+    /// A `Random` file and its record length.
+    Random(File, usize),
 }
 
-pub fn open_file(path: &str, mode: vybe_parser::ast::stmt::FileOpenMode) -> Result<FileHandle, RuntimeError> {
+/// Record length of a `Random` file opened without `Len =`.
+pub const DEFAULT_RECORD_LENGTH: usize = 128;
+
+pub fn open_file(path: &str, mode: vybe_parser::ast::stmt::FileOpenMode, record_length: Option<usize>) -> Result<FileHandle, RuntimeError> {
     let file_path = Path::new(path);
     
     match mode {
@@ -42,14 +47,14 @@ pub fn open_file(path: &str, mode: vybe_parser::ast::stmt::FileOpenMode) -> Resu
             Ok(FileHandle::Binary(file))
         }
         vybe_parser::ast::stmt::FileOpenMode::Random => {
-             // For now, treat Random as Binary or Error? Let's use Binary for flexibility
-             let file = OpenOptions::new()
+            let file = OpenOptions::new()
                 .read(true)
                 .write(true)
                 .create(true)
+                .truncate(false)
                 .open(file_path)
                 .map_err(|e| RuntimeError::Custom(format!("Failed to open file for Random: {}", e)))?;
-            Ok(FileHandle::Binary(file))
+            Ok(FileHandle::Random(file, record_length.unwrap_or(DEFAULT_RECORD_LENGTH)))
         }
     }
 }
@@ -96,3 +101,102 @@ pub fn read_line(handle: &mut FileHandle) -> Result<String, RuntimeError> {
         _ => Err(RuntimeError::Custom("File mode not valid for Input".to_string())),
     }
 }
+
+fn io_error(what: &str, e: std::io::Error) -> RuntimeError {
+    RuntimeError::Custom(format!("{} error: {}", what, e))
+}
+
+/// Move a `Random` file to the start of record `position` (from 1) or a
+/// `Binary` file to byte `position` (from 1); `None` stays where the last
+/// `Get` or `Put` left off.
+fn seek_to(file: &mut File, position: Option<i64>, unit: usize) -> Result<(), RuntimeError> {
+    if let Some(position) = position {
+        if position < 1 {
+            return Err(RuntimeError::Custom("Bad record number".to_string()));
+        }
+        let offset = (position as u64 - 1) * unit as u64;
+        file.seek(SeekFrom::Start(offset)).map_err(|e| io_error("Seek", e))?;
+    }
+    Ok(())
+}
+
+/// `Put`: write `bytes` as one record of a `Random` file, or at a byte
+/// position of a `Binary` file.
+pub fn put_bytes(handle: &mut FileHandle, position: Option<i64>, bytes: &[u8]) -> Result<(), RuntimeError> {
+    match handle {
+        FileHandle::Random(file, record_length) => {
+            if bytes.len() > *record_length {
+                return Err(RuntimeError::Custom("Bad record length".to_string()));
+            }
+            seek_to(file, position, *record_length)?;
+            let mut record = bytes.to_vec();
+            record.resize(*record_length, 0);
+            file.write_all(&record).map_err(|e| io_error("Put", e))
+        }
+        FileHandle::Binary(file) => {
+            seek_to(file, position, 1)?;
+            file.write_all(bytes).map_err(|e| io_error("Put", e))
+        }
+        _ => Err(RuntimeError::Custom("Bad file mode".to_string())),
+    }
+}
+
+/// `Get`: read one record of a `Random` file, or `count` bytes at a byte
+/// position of a `Binary` file. Reading past the end gives fewer bytes.
+pub fn get_bytes(handle: &mut FileHandle, position: Option<i64>, count: usize) -> Result<Vec<u8>, RuntimeError> {
+    let (file, count) = match handle {
+        FileHandle::Random(file, record_length) => {
+            seek_to(file, position, *record_length)?;
+            (file, *record_length)
+        }
+        FileHandle::Binary(file) => {
+            seek_to(file, position, 1)?;
+            (file, count)
+        }
+        _ => return Err(RuntimeError::Custom("Bad file mode".to_string())),
+    };
+    let mut bytes = Vec::with_capacity(count);
+    file.take(count as u64).read_to_end(&mut bytes).map_err(|e| io_error("Get", e))?;
+    Ok(bytes)
+}
+
+/// `LOF`: the length of the open file in bytes.
+pub fn length(handle: &mut FileHandle) -> Result<u64, RuntimeError> {
+    let metadata = match handle {
+        FileHandle::Input(reader) => reader.get_ref().metadata(),
+        FileHandle::Output(writer) | FileHandle::Append(writer) => {
+            writer.flush().map_err(|e| io_error("Write", e))?;
+            writer.get_ref().metadata()
+        }
+        FileHandle::Binary(file) | FileHandle::Random(file, _) => file.metadata(),
+    };
+    metadata.map(|m| m.len()).map_err(|e| io_error("LOF", e))
+}
+
+/// `EOF`: whether nothing is left to read.
+pub fn at_end(handle: &mut FileHandle) -> Result<bool, RuntimeError> {
+    match handle {
+        FileHandle::Input(reader) => Ok(reader.fill_buf().map_err(|e| io_error("EOF", e))?.is_empty()),
+        FileHandle::Output(_) | FileHandle::Append(_) => Ok(true),
+        FileHandle::Binary(file) | FileHandle::Random(file, _) => {
+            let position = file.stream_position().map_err(|e| io_error("EOF", e))?;
+            let len = file.metadata().map_err(|e| io_error("EOF", e))?.len();
+            Ok(position >= len)
+        }
+    }
+}
+
+/// `Loc`: the number of the last record read or written for a `Random`
+/// file, the byte position otherwise.
+pub fn location(handle: &mut FileHandle) -> Result<u64, RuntimeError> {
+    let position = match handle {
+        FileHandle::Input(reader) => reader.stream_position(),
+        FileHandle::Output(writer) | FileHandle::Append(writer) => writer.stream_position(),
+        FileHandle::Binary(file) | FileHandle::Random(file, _) => file.stream_position(),
+    }
+    .map_err(|e| io_error("Loc", e))?;
+    Ok(match handle {
+        FileHandle::Random(_, record_length) => position / *record_length as u64,
+        _ => position,
+    })
+}
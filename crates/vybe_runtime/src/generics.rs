@@ -0,0 +1,155 @@
+//! Runtime support for user-defined generic classes, structures and methods.
+//!
+//! Type arguments are kept as VB type names (`Integer`, `List(Of String)`).
+//! An instance of a generic class records them in its [`TYPE_ARGS_FIELD`],
+//! and a procedure runs with a [`TypeArgs`] map from its type parameter names
+//! to those types, so `Dim x As T`, `New T()`, `GetType(T)`, `TypeOf ... Is T`
+//! and casts all see the concrete type.
+
+use std::collections::HashMap;
+
+use crate::value::{ObjectData, Value};
+
+/// Lowercased type parameter name → bound type name.
+pub type TypeArgs = HashMap<String, String>;
+
+/// Field of a generic instance holding its type arguments as strings.
+pub const TYPE_ARGS_FIELD: &str = "__type_args";
+
+/// Split `Box(Of Integer, List(Of String))` into `("Box", ["Integer", "List(Of String)"])`.
+/// Returns `None` when the name has no `(Of ...)` suffix.
+pub fn split_type_arguments(name: &str) -> Option<(&str, Vec<String>)> {
+    let open = name.find('(')?;
+    let rest = name[open + 1..].trim_start();
+    let after_of = rest.get(..2).filter(|of| of.eq_ignore_ascii_case("of"))?;
+    let list = rest[after_of.len()..].strip_suffix(')').unwrap_or(&rest[after_of.len()..]);
+    if !list.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in list.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    args.push(current.trim().to_string());
+    Some((name[..open].trim_end(), args))
+}
+
+/// Replace type parameter names in `type_name` with their bound types:
+/// `List(Of T)` with `T = Integer` becomes `List(Of Integer)`.
+pub fn substitute(type_name: &str, args: &TypeArgs) -> String {
+    if args.is_empty() {
+        return type_name.to_string();
+    }
+    let mut out = String::with_capacity(type_name.len());
+    let mut word = String::new();
+    let mut after_dot = false;
+    let flush = |word: &mut String, out: &mut String, after_dot: bool| {
+        match args.get(&word.to_lowercase()) {
+            Some(bound) if !after_dot => out.push_str(bound),
+            _ => out.push_str(word),
+        }
+        word.clear();
+    };
+    for c in type_name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            word.push(c);
+        } else {
+            if !word.is_empty() {
+                flush(&mut word, &mut out, after_dot);
+            }
+            after_dot = c == '.';
+            out.push(c);
+        }
+    }
+    if !word.is_empty() {
+        flush(&mut word, &mut out, after_dot);
+    }
+    out
+}
+
+/// `Box(Of Integer, String)`; just the base name when there are no arguments.
+pub fn display_name(base: &str, args: &[String]) -> String {
+    if args.is_empty() {
+        base.to_string()
+    } else {
+        format!("{}(Of {})", base, args.join(", "))
+    }
+}
+
+/// The type arguments an instance was created with.
+pub fn instance_type_args(obj: &ObjectData) -> Vec<String> {
+    match obj.fields.get(TYPE_ARGS_FIELD) {
+        Some(Value::Array(args)) => args.iter().map(|a| a.as_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Do two type names denote the same type? Ignores case, a `System.` prefix
+/// and the CLR aliases of the primitive types, recursing into type arguments.
+pub fn same_type(a: &str, b: &str) -> bool {
+    match (split_type_arguments(a), split_type_arguments(b)) {
+        (Some((base_a, args_a)), Some((base_b, args_b))) => {
+            same_type(base_a, base_b)
+                && args_a.len() == args_b.len()
+                && args_a.iter().zip(&args_b).all(|(x, y)| same_type(x, y))
+        }
+        (None, None) => canonical(a) == canonical(b),
+        _ => false,
+    }
+}
+
+fn canonical(name: &str) -> String {
+    let lower = name.trim().to_lowercase();
+    let lower = lower.strip_prefix("system.").unwrap_or(&lower);
+    match lower {
+        "int32" => "integer",
+        "int64" => "long",
+        "int16" => "short",
+        "datetime" => "date",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Primitive value types and the .NET structures the runtime knows about.
+pub fn is_value_type_name(name: &str) -> bool {
+    matches!(
+        canonical(name).as_str(),
+        "integer" | "long" | "short" | "byte" | "sbyte" | "uinteger" | "ulong" | "ushort"
+            | "uint32" | "uint64" | "uint16" | "single" | "double" | "decimal" | "boolean"
+            | "date" | "char" | "timespan" | "guid"
+    )
+}
+
+/// The VB name of a value's type, used to infer a method's type arguments.
+pub fn value_type_name(value: &Value) -> String {
+    match value {
+        Value::Integer(_) => "Integer".to_string(),
+        Value::Long(_) => "Long".to_string(),
+        Value::Single(_) => "Single".to_string(),
+        Value::Double(_) => "Double".to_string(),
+        Value::Date(_) => "Date".to_string(),
+        Value::String(_) => "String".to_string(),
+        Value::Boolean(_) => "Boolean".to_string(),
+        Value::Byte(_) => "Byte".to_string(),
+        Value::Char(_) => "Char".to_string(),
+        Value::Object(obj) => {
+            let obj = obj.borrow();
+            display_name(&obj.class_name, &instance_type_args(&obj))
+        }
+        other => crate::integral::IntType::of(other).map_or("Object", |ty| ty.name()).to_string(),
+    }
+}
@@ -0,0 +1,357 @@
+//! VB's integral types — `SByte`, `Byte`, `Short`, `UShort`, `Integer`,
+//! `UInteger`, `Long` and `ULong` — and checked arithmetic on them.
+//!
+//! An operator on two integral operands works in the type VB's result-type
+//! table gives: the wider of two types of the same signedness, or for mixed
+//! signedness the signed type wide enough to hold both (`Integer + UInteger`
+//! is `Long`, and `Long + ULong` is `Decimal`). A result outside that type's
+//! range is an `OverflowException`, as with VB's default integer overflow
+//! checks, and so is a conversion (`CShort`, `CUInt`, ...) of a value out of
+//! range. Conversions from floating point round halves to even.
+//!
+//! Shifts are the exception: `<<` and `>>` keep the left operand's type
+//! (`Long` for a non-integral one) and mask the count to that type's width,
+//! `And 31` for `Integer` and `And 63` for `Long`, so they never overflow.
+
+use crate::decimal::{Decimal, Rounding};
+use crate::value::{RuntimeError, Value};
+
+/// One of the eight integral types, narrowest signed/unsigned pair first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntType {
+    SByte,
+    Byte,
+    Short,
+    UShort,
+    Integer,
+    UInteger,
+    Long,
+    ULong,
+}
+
+impl IntType {
+    /// The integral type of `value`, if it has one.
+    pub fn of(value: &Value) -> Option<Self> {
+        Some(match value {
+            Value::SByte(_) => IntType::SByte,
+            Value::Byte(_) => IntType::Byte,
+            Value::Short(_) => IntType::Short,
+            Value::UShort(_) => IntType::UShort,
+            Value::Integer(_) => IntType::Integer,
+            Value::UInteger(_) => IntType::UInteger,
+            Value::Long(_) => IntType::Long,
+            Value::ULong(_) => IntType::ULong,
+            _ => return None,
+        })
+    }
+
+    /// The integral type named `name` (`Short`, `Int16`, `UInteger`, ...).
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim();
+        let name = name.strip_prefix("System.").unwrap_or(name);
+        Some(match name.to_lowercase().as_str() {
+            "sbyte" => IntType::SByte,
+            "byte" => IntType::Byte,
+            "short" | "int16" => IntType::Short,
+            "ushort" | "uint16" => IntType::UShort,
+            "integer" | "int32" => IntType::Integer,
+            "uinteger" | "uint32" => IntType::UInteger,
+            "long" | "int64" => IntType::Long,
+            "ulong" | "uint64" => IntType::ULong,
+            _ => return None,
+        })
+    }
+
+    /// The integral type a declared type stands for.
+    pub fn from_vb_type(ty: &vybe_parser::VBType) -> Option<Self> {
+        use vybe_parser::VBType;
+        Some(match ty {
+            VBType::SByte => IntType::SByte,
+            VBType::Byte => IntType::Byte,
+            VBType::Short => IntType::Short,
+            VBType::UShort => IntType::UShort,
+            VBType::Integer => IntType::Integer,
+            VBType::UInteger => IntType::UInteger,
+            VBType::Long => IntType::Long,
+            VBType::ULong => IntType::ULong,
+            _ => return None,
+        })
+    }
+
+    /// The VB keyword for the type.
+    pub fn name(self) -> &'static str {
+        match self {
+            IntType::SByte => "SByte",
+            IntType::Byte => "Byte",
+            IntType::Short => "Short",
+            IntType::UShort => "UShort",
+            IntType::Integer => "Integer",
+            IntType::UInteger => "UInteger",
+            IntType::Long => "Long",
+            IntType::ULong => "ULong",
+        }
+    }
+
+    fn bits(self) -> u32 {
+        match self {
+            IntType::SByte | IntType::Byte => 8,
+            IntType::Short | IntType::UShort => 16,
+            IntType::Integer | IntType::UInteger => 32,
+            IntType::Long | IntType::ULong => 64,
+        }
+    }
+
+    fn is_signed(self) -> bool {
+        matches!(self, IntType::SByte | IntType::Short | IntType::Integer | IntType::Long)
+    }
+
+    fn signed_of_bits(bits: u32) -> Option<Self> {
+        Some(match bits {
+            8 => IntType::SByte,
+            16 => IntType::Short,
+            32 => IntType::Integer,
+            64 => IntType::Long,
+            _ => return None,
+        })
+    }
+
+    fn range(self) -> (i128, i128) {
+        let bits = self.bits();
+        if self.is_signed() {
+            (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+        } else {
+            (0, (1i128 << bits) - 1)
+        }
+    }
+
+    /// The type's default value, `0`.
+    pub fn zero(self) -> Value {
+        self.make(0).unwrap_or(Value::Integer(0))
+    }
+
+    /// Can every value of this type be stored in `to`?
+    pub fn widens_to(self, to: IntType) -> bool {
+        let ((min, max), (to_min, to_max)) = (self.range(), to.range());
+        to_min <= min && max <= to_max
+    }
+
+    /// The value whose two's-complement bits are `bits`, as
+    /// `Convert.ToInt16("FFFF", 16)` reads `-1`. Set bits above the type's
+    /// width are an overflow.
+    pub fn from_bits(self, bits: u64) -> Result<Value, RuntimeError> {
+        let width = self.bits();
+        if width < 64 && bits >> width != 0 {
+            return Err(overflow());
+        }
+        let n = bits as i128;
+        let n = if self.is_signed() && n > self.range().1 { n - (1i128 << width) } else { n };
+        self.make(n)
+    }
+
+    /// `n` as a value of this type, or `OverflowException` if it doesn't fit.
+    pub fn make(self, n: i128) -> Result<Value, RuntimeError> {
+        let (min, max) = self.range();
+        if n < min || n > max {
+            return Err(overflow());
+        }
+        Ok(match self {
+            IntType::SByte => Value::SByte(n as i8),
+            IntType::Byte => Value::Byte(n as u8),
+            IntType::Short => Value::Short(n as i16),
+            IntType::UShort => Value::UShort(n as u16),
+            IntType::Integer => Value::Integer(n as i32),
+            IntType::UInteger => Value::UInteger(n as u32),
+            IntType::Long => Value::Long(n as i64),
+            IntType::ULong => Value::ULong(n as u64),
+        })
+    }
+}
+
+/// `OverflowException` for an integral result or conversion out of range.
+pub fn overflow() -> RuntimeError {
+    RuntimeError::Exception(
+        "OverflowException".to_string(),
+        "Arithmetic operation resulted in an overflow.".to_string(),
+        None,
+    )
+}
+
+/// The numeric value of an integral operand; Boolean is `-1` or `0`.
+pub fn int_value(value: &Value) -> Option<i128> {
+    Some(match value {
+        Value::SByte(n) => *n as i128,
+        Value::Byte(n) => *n as i128,
+        Value::Short(n) => *n as i128,
+        Value::UShort(n) => *n as i128,
+        Value::Integer(n) => *n as i128,
+        Value::UInteger(n) => *n as i128,
+        Value::Long(n) => *n as i128,
+        Value::ULong(n) => *n as i128,
+        Value::Boolean(b) => if *b { -1 } else { 0 },
+        _ => return None,
+    })
+}
+
+/// The type VB evaluates `l op r` in when both are integral: `None` for
+/// `Decimal`, which `Long` and `ULong` widen to together. Boolean counts as
+/// `SByte`, except that two Booleans are added as `Short`.
+fn result_type(l: Option<IntType>, r: Option<IntType>) -> Option<IntType> {
+    let (a, b) = match (l, r) {
+        (None, None) => return Some(IntType::Short),
+        (a, b) => (a.unwrap_or(IntType::SByte), b.unwrap_or(IntType::SByte)),
+    };
+    if a.is_signed() == b.is_signed() {
+        return Some(if a.bits() >= b.bits() { a } else { b });
+    }
+    let (signed, unsigned) = if a.is_signed() { (a, b) } else { (b, a) };
+    if signed.bits() > unsigned.bits() {
+        Some(signed)
+    } else {
+        IntType::signed_of_bits(unsigned.bits() * 2)
+    }
+}
+
+/// `l op r` when both sides are integral. A Boolean operand takes part in
+/// arithmetic as `-1` or `0` but is left to the caller for comparisons and
+/// `And`/`Or`/`Xor`. `None` also means the caller handles it: `/` and `^`
+/// always work in Double.
+pub fn binary_operation(symbol: &str, l: &Value, r: &Value) -> Result<Option<Value>, RuntimeError> {
+    let (lt, rt) = (IntType::of(l), IntType::of(r));
+    let arithmetic = matches!(symbol, "+" | "-" | "*" | "\\" | "Mod");
+    if !arithmetic && (lt.is_none() || rt.is_none()) {
+        return Ok(None);
+    }
+    let (Some(a), Some(b)) = (int_value(l), int_value(r)) else {
+        return Ok(None);
+    };
+    let result = match symbol {
+        "=" => return Ok(Some(Value::Boolean(a == b))),
+        "<>" => return Ok(Some(Value::Boolean(a != b))),
+        "<" => return Ok(Some(Value::Boolean(a < b))),
+        "<=" => return Ok(Some(Value::Boolean(a <= b))),
+        ">" => return Ok(Some(Value::Boolean(a > b))),
+        ">=" => return Ok(Some(Value::Boolean(a >= b))),
+        "+" => a + b,
+        "-" => a - b,
+        "*" => a * b,
+        "\\" | "Mod" => {
+            if b == 0 {
+                return Err(RuntimeError::DivisionByZero);
+            }
+            if symbol == "Mod" { a % b } else { a / b }
+        }
+        "And" => a & b,
+        "Or" => a | b,
+        "Xor" => a ^ b,
+        _ => return Ok(None),
+    };
+    match result_type(lt, rt) {
+        Some(ty) => ty.make(result).map(Some),
+        None => Decimal::from_i128(result).map(|d| Some(Value::Decimal(d))),
+    }
+}
+
+/// `l << r` or `l >> r`. The result has the left operand's type — `Short`
+/// for a Boolean, `Long` for anything that isn't integral — and the count,
+/// converted to `Integer`, is masked to that type's width. Bits shifted out
+/// on the left are lost; `>>` is arithmetic on signed types and logical on
+/// unsigned ones.
+pub fn shift(symbol: &str, l: &Value, r: &Value) -> Result<Value, RuntimeError> {
+    let (ty, n) = match (l, IntType::of(l), int_value(l)) {
+        (Value::Boolean(_), _, Some(n)) => (IntType::Short, n),
+        (_, Some(ty), Some(n)) => (ty, n),
+        _ => (IntType::Long, int_value(&convert(l, IntType::Long)?).unwrap_or(0)),
+    };
+    let count = int_value(&convert(r, IntType::Integer)?).unwrap_or(0) as u32 & (ty.bits() - 1);
+    let result = if symbol == "<<" {
+        let bits = (n << count) & ((1i128 << ty.bits()) - 1);
+        if ty.is_signed() && bits > ty.range().1 { bits - (1i128 << ty.bits()) } else { bits }
+    } else {
+        n >> count
+    };
+    ty.make(result)
+}
+
+/// `-value` for an integral value: unsigned types widen to the next signed
+/// type (`ULong` to `Decimal`), and negating a signed type's MinValue is an
+/// overflow.
+pub fn negate(value: &Value) -> Result<Option<Value>, RuntimeError> {
+    let (Some(ty), Some(n)) = (IntType::of(value), int_value(value)) else {
+        return Ok(None);
+    };
+    let ty = if ty.is_signed() { Some(ty) } else { IntType::signed_of_bits(ty.bits() * 2) };
+    match ty {
+        Some(ty) => ty.make(-n).map(Some),
+        None => Decimal::from_i128(-n).map(|d| Some(Value::Decimal(d))),
+    }
+}
+
+/// `Not value` for an integral value, in its own type.
+pub fn not(value: &Value) -> Result<Option<Value>, RuntimeError> {
+    let (Some(ty), Some(n)) = (IntType::of(value), int_value(value)) else {
+        return Ok(None);
+    };
+    let result = if ty.is_signed() { !n } else { !n & ty.range().1 };
+    ty.make(result).map(Some)
+}
+
+/// Convert `value` to `ty` as `CInt`, `CUShort` and friends do: integers are
+/// range-checked, floating-point and Decimal values are rounded half to even
+/// first, and strings are parsed as numbers (including `&H`, `&O` and `&B`).
+pub fn convert(value: &Value, ty: IntType) -> Result<Value, RuntimeError> {
+    let n = match value {
+        Value::Single(f) => round_float(*f as f64)?,
+        Value::Double(d) => round_float(*d)?,
+        Value::Date(d) => round_float(*d)?,
+        Value::Decimal(d) => d.round(0, Rounding::ToEven).to_i128(),
+        Value::Char(c) => *c as i128,
+        Value::Nothing => 0,
+        Value::String(s) => parse_string(s).ok_or_else(|| RuntimeError::Exception(
+            "InvalidCastException".to_string(),
+            format!("Conversion from string \"{}\" to type '{}' is not valid.", s, ty.name()),
+            None,
+        ))?,
+        other => match int_value(other) {
+            Some(n) => n,
+            None => return Err(RuntimeError::TypeError { expected: ty.name().to_string(), got: format!("{:?}", other) }),
+        },
+    };
+    ty.make(n)
+}
+
+/// `value` converted to the declared integral type `ty`, for assignments to a
+/// variable declared `As Short`, `As ULong`, ... Values that aren't numbers
+/// (objects, arrays) are left for the caller to deal with.
+pub fn coerce(value: &Value, ty: IntType) -> Result<Value, RuntimeError> {
+    match value {
+        Value::String(_) | Value::Boolean(_) | Value::Single(_) | Value::Double(_) | Value::Decimal(_) | Value::Nothing => convert(value, ty),
+        v if IntType::of(v).is_some() => convert(value, ty),
+        _ => Ok(value.clone()),
+    }
+}
+
+fn round_float(d: f64) -> Result<i128, RuntimeError> {
+    if !d.is_finite() {
+        return Err(overflow());
+    }
+    // Saturates far out of range, which `make` then rejects
+    Ok(d.round_ties_even() as i128)
+}
+
+fn parse_string(s: &str) -> Option<i128> {
+    let t = s.trim();
+    let radix = |prefix: &str| t.get(..2).filter(|p| p.eq_ignore_ascii_case(prefix)).map(|_| &t[2..]);
+    if let Some(digits) = radix("&H") {
+        return u64::from_str_radix(digits, 16).ok().map(|n| n as i128);
+    }
+    if let Some(digits) = radix("&O") {
+        return u64::from_str_radix(digits, 8).ok().map(|n| n as i128);
+    }
+    if let Some(digits) = radix("&B") {
+        return u64::from_str_radix(digits, 2).ok().map(|n| n as i128);
+    }
+    if let Ok(n) = t.parse::<i128>() {
+        return Some(n);
+    }
+    t.replace(',', "").parse::<f64>().ok().and_then(|d| round_float(d).ok())
+}
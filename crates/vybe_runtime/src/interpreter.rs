@@ -1,20 +1,37 @@
 use crate::builtins::*;
-use crate::environment::Environment;
+use crate::bytecode::{Chunk, ChunkCache, Engine, Op};
+use crate::environment::{Environment, Resolutions, Scope, Slot};
 use crate::evaluator::{evaluate, values_equal, value_in_range, compare_values};
 use crate::event_system::EventSystem;
-use crate::value::{ExitType, RuntimeError, Value, ObjectData};
+use crate::symbol::Symbol;
+use crate::generics::TypeArgs;
+use crate::iterators::{self, BodyCursor, Cursor, Frame, Step};
+use crate::limits::{Budget, CancelHandle, ExecutionLimits};
+use crate::options;
+use crate::sandbox::{Capability, SandboxPolicy};
+use crate::query;
+use crate::overloads::{ClassMatch, OverloadTable, ResolvedOverload};
+use crate::value::{ExitType, RuntimeError, SourceLocation, Value, ObjectData};
 use crate::EventData;
 use std::collections::{HashMap, VecDeque};
 use std::io::BufRead;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::cell::RefCell;
 use std::sync::mpsc;
-use vybe_parser::{CaseCondition, Declaration, Expression, FunctionDecl, Identifier, Program, Statement, SubDecl};
+use vybe_parser::ast::decl::ConversionKind;
+use vybe_parser::ast::expr::BinaryOp;
+use vybe_parser::ast::stmt::{CaseBlock, CatchBlock, LoopConditionType};
+use vybe_parser::{Block, CaseCondition, Declaration, EventDecl, ExprKind, Expression, FunctionDecl, Identifier, MethodDecl, OptionSettings, Program, Span, Statement, SubDecl, TypeConstraint, TypeParameter};
 
 pub struct Interpreter {
     pub env: Environment,
-    pub functions: HashMap<String, FunctionDecl>,
-    pub subs: HashMap<String, SubDecl>,
+    /// Every signature declared under a key (`Overloads`), in declaration
+    /// order; lookups by key use the latest.
+    pub functions: HashMap<String, Vec<Arc<FunctionDecl>>>,
+    pub subs: HashMap<String, Vec<Arc<SubDecl>>>,
+    /// The keys each procedure name is declared under.
+    pub overloads: OverloadTable,
     pub classes: HashMap<String, vybe_parser::ClassDecl>,
     pub events: EventSystem,
     pub side_effects: VecDeque<crate::RuntimeSideEffect>,
@@ -55,8 +72,48 @@ pub struct Interpreter {
     pub static_locals: HashMap<String, Value>,
     /// Track which Sub/Function is currently executing (for static locals).
     current_procedure: Option<String>,
+    /// Return type of the executing Function, for the `Option Strict` check on `Return`.
+    return_type: Option<vybe_parser::VBType>,
+    /// Body of each executing procedure that has labels, for `GoSub`.
+    gosub_bodies: Vec<Option<Rc<Block>>>,
+    /// Source file of the code currently executing (for error locations).
+    current_source: Option<String>,
+    /// Project-level `Option` defaults for files that don't state their own.
+    pub options: OptionSettings,
+    /// `Option` statements of each loaded file, keyed by source file ("" when unnamed).
+    file_options: HashMap<String, OptionSettings>,
+    /// Options in effect for `current_source`.
+    active_options: OptionSettings,
+    /// Type arguments bound for the running procedure and its object.
+    type_args: TypeArgs,
+    /// Explicit `(Of ...)` arguments for the procedure about to be invoked.
+    pending_type_args: Option<(String, Vec<String>)>,
+    /// Which engine runs Sub and Function bodies; `VYBE_ENGINE` picks the
+    /// default.
+    pub engine: Engine,
+    /// Procedure bodies compiled for the bytecode engine.
+    chunks: ChunkCache,
+    /// Variables the tree walker has found, by where they are written.
+    resolutions: Resolutions,
+    /// How deeply procedure calls may nest before a `StackOverflowException`.
+    pub max_call_depth: usize,
+    /// The procedures being run, outermost first.
+    call_stack: Vec<String>,
+    /// The host's limits on this run and what it has used of them.
+    budget: Budget,
+    /// What the script may reach outside the interpreter.
+    sandbox: SandboxPolicy,
 }
 
+/// The default [`Interpreter::max_call_depth`].
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 5_000;
+
+/// Native stack the interpreter may use before it next checks for more. It
+/// checks on entry, at every procedure call, statement and expression.
+const STACK_RED_ZONE: usize = 2 * 1024 * 1024;
+/// Native stack added at a time when deep recursion runs low.
+const STACK_SEGMENT: usize = 16 * 1024 * 1024;
+
 /// An active Imports entry.
 #[derive(Debug, Clone)]
 pub struct ImportEntry {
@@ -94,6 +151,7 @@ impl Interpreter {
             env: Environment::new(),
             functions: HashMap::new(),
             subs: HashMap::new(),
+            overloads: OverloadTable::new(),
             classes: HashMap::new(),
             events: EventSystem::new(),
             side_effects: VecDeque::new(),
@@ -118,6 +176,21 @@ impl Interpreter {
             on_error_goto_label: None,
             static_locals: HashMap::new(),
             current_procedure: None,
+            return_type: None,
+            gosub_bodies: Vec::new(),
+            current_source: None,
+            options: OptionSettings::default(),
+            file_options: HashMap::new(),
+            active_options: OptionSettings::default(),
+            type_args: TypeArgs::new(),
+            pending_type_args: None,
+            engine: Engine::from_env(),
+            chunks: ChunkCache::default(),
+            resolutions: Resolutions::default(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            call_stack: Vec::new(),
+            budget: Budget::default(),
+            sandbox: SandboxPolicy::default(),
         };
         interp.register_builtin_constants();
         interp.init_namespaces();
@@ -140,7 +213,7 @@ impl Interpreter {
          
          let class_name = host_instance.borrow().class_name.clone();
          
-         if let Some(class_decl) = self.classes.get(&class_name.to_lowercase()) {
+         if let Some(class_decl) = self.classes.get(&*Symbol::intern(&class_name).name()) {
              use vybe_parser::MethodDecl;
              for method_enum in class_decl.methods.iter() {
                  let (method_name, handles) = match method_enum {
@@ -197,7 +270,7 @@ impl Interpreter {
          
          let class_name = host_instance.borrow().class_name.clone();
          
-         if let Some(class_decl) = self.classes.get(&class_name.to_lowercase()) {
+         if let Some(class_decl) = self.classes.get(&*Symbol::intern(&class_name).name()) {
              use vybe_parser::MethodDecl;
              for method_enum in class_decl.methods.iter() {
                  let (method_name, handles) = match method_enum {
@@ -238,19 +311,58 @@ impl Interpreter {
     }
 
     pub fn new_background(
-        functions: HashMap<String, vybe_parser::ast::decl::FunctionDecl>,
-        subs: HashMap<String, vybe_parser::ast::decl::SubDecl>,
+        functions: HashMap<String, Vec<Arc<FunctionDecl>>>,
+        subs: HashMap<String, Vec<Arc<SubDecl>>>,
+        overloads: OverloadTable,
         classes: HashMap<String, vybe_parser::ast::decl::ClassDecl>,
         namespace_map: HashMap<String, String>,
     ) -> Self {
         let mut interp = Self::new();
         interp.functions = functions;
         interp.subs = subs;
+        interp.overloads = overloads;
         interp.classes = classes;
         interp.namespace_map = namespace_map;
         interp
     }
 
+    /// Limit what the code run from now on may use. Each run the host
+    /// starts (`run`, `call_procedure`, an event handler...) gets the whole
+    /// budget: the clock for `timeout` starts when the run does.
+    pub fn set_limits(&mut self, limits: ExecutionLimits) {
+        self.budget = Budget::new(limits, self.budget.cancel_handle().clone());
+    }
+
+    /// Run `body` as one run of the budget, unless it is called from inside
+    /// one already.
+    fn as_run<T>(&mut self, body: impl FnOnce(&mut Self) -> Result<T, RuntimeError>) -> Result<T, RuntimeError> {
+        if !self.budget.begin() {
+            return body(self);
+        }
+        let result = body(self);
+        self.budget.end();
+        result
+    }
+
+    pub fn limits(&self) -> &ExecutionLimits {
+        self.budget.limits()
+    }
+
+    /// A handle that stops this interpreter's script from another thread.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.budget.cancel_handle().clone()
+    }
+
+    /// Restrict what the script may reach outside the interpreter. Threads
+    /// it starts from now on get the same policy.
+    pub fn set_sandbox(&mut self, policy: SandboxPolicy) {
+        self.sandbox = policy;
+    }
+
+    pub fn sandbox(&self) -> &SandboxPolicy {
+        &self.sandbox
+    }
+
     pub fn init_namespaces(&mut self) {
         // Create System.IO.File object
         let file_obj_data = ObjectData { drawing_commands: Vec::new(),
@@ -557,7 +669,7 @@ impl Interpreter {
         let mut fields = HashMap::new();
         
         let resolved = self.resolve_class_key(class_name);
-        if let Some(cls) = resolved.and_then(|k| self.classes.get(&k).cloned()) {
+        if let Some(cls) = resolved.as_ref().and_then(|k| self.classes.get(k).cloned()) {
              if let Some(parent_type) = &cls.inherits {
                  let parent_name = match parent_type {
                      vybe_parser::VBType::Custom(n) => Some(n.clone()),
@@ -573,17 +685,37 @@ impl Interpreter {
 
              // Add/Override with current class fields
              for field in &cls.fields {
+                 if let Some(bounds) = field.array_bounds.as_ref().filter(|b| !b.is_empty() && field.initializer.is_none()) {
+                     // Fixed-size array field: Scores(4) As Integer
+                     let dims: Vec<usize> = bounds.iter()
+                         .map(|b| self.evaluate_expr(b).and_then(|v| v.as_integer()).map(|i| (i + 1).max(0) as usize))
+                         .collect::<Result<_, _>>()
+                         .unwrap_or_default();
+                     let default_val = self.default_value(&field.var_type);
+                     let array = crate::structures::copy_elements(create_multi_dim_array(&dims, &default_val));
+                     fields.insert(field.name.as_str().to_lowercase(), array);
+                     continue;
+                 }
                  let init_val = if let Some(expr) = &field.initializer {
                      self.evaluate_expr(expr).unwrap_or(Value::Nothing) 
                  } else {
-                     match &field.var_type {
+                     match &self.resolve_var_type(&field.var_type) {
                          Some(t) => match t {
+                             vybe_parser::VBType::SByte => Value::SByte(0),
+                             vybe_parser::VBType::Byte => Value::Byte(0),
+                             vybe_parser::VBType::Short => Value::Short(0),
+                             vybe_parser::VBType::UShort => Value::UShort(0),
                              vybe_parser::VBType::Integer => Value::Integer(0),
+                             vybe_parser::VBType::UInteger => Value::UInteger(0),
                              vybe_parser::VBType::Long => Value::Long(0),
+                             vybe_parser::VBType::ULong => Value::ULong(0),
                              vybe_parser::VBType::Single => Value::Single(0.0),
                              vybe_parser::VBType::Double => Value::Double(0.0),
+                             vybe_parser::VBType::Decimal => Value::Decimal(crate::decimal::Decimal::ZERO),
                              vybe_parser::VBType::String => Value::String("".to_string()),
+                             vybe_parser::VBType::FixedString(len) => Value::String(" ".repeat(*len)),
                              vybe_parser::VBType::Boolean => Value::Boolean(false),
+                             vybe_parser::VBType::Custom(s) if self.is_structure_type(s) => self.structure_instance(s),
                              vybe_parser::VBType::Custom(s) => {
                                  let s_lower = s.to_lowercase();
                                  if s_lower.contains("system.windows.forms.") || 
@@ -607,6 +739,9 @@ impl Interpreter {
                  };
                  fields.insert(field.name.as_str().to_lowercase(), init_val);
              }
+             if resolved.as_ref().is_some_and(|k| self.structures.contains_key(k)) {
+                 fields.insert(crate::structures::VALUE_TYPE_FIELD.to_string(), Value::Boolean(true));
+             }
         } 
 
         // Always try to inject defaults for built-in types (whether they were found in classes or not)
@@ -687,7 +822,8 @@ impl Interpreter {
 
     /// Send console output through the channel (with colors), directly to stdout (CLI),
     /// or buffered in side_effects (tests/form mode).
-    fn send_console_output(&mut self, text: String) {
+    fn send_console_output(&mut self, text: String) -> Result<(), RuntimeError> {
+        self.budget.output(text.len())?;
         let (fg, bg) = self.get_console_colors();
         if let Some(tx) = &self.console_tx {
             let _ = tx.send(crate::ConsoleMessage::Output { text, fg, bg });
@@ -706,10 +842,12 @@ impl Interpreter {
             // Test/form mode: buffer for later inspection
             self.side_effects.push_back(crate::RuntimeSideEffect::ConsoleOutput(text));
         }
+        Ok(())
     }
 
     /// Send debug output (always uses default colors).
-    fn send_debug_output(&mut self, text: String) {
+    fn send_debug_output(&mut self, text: String) -> Result<(), RuntimeError> {
+        self.budget.output(text.len())?;
         if let Some(tx) = &self.console_tx {
             let _ = tx.send(crate::ConsoleMessage::Output { text, fg: 7, bg: 0 });
         } else if self.direct_console {
@@ -719,6 +857,7 @@ impl Interpreter {
         } else {
             self.side_effects.push_back(crate::RuntimeSideEffect::ConsoleOutput(text));
         }
+        Ok(())
     }
 
     // ── .NET-compatible EventArgs object factories ───────────────────────
@@ -1635,20 +1774,31 @@ impl Interpreter {
         self.env.define_const("stringcomparison.ordinalignorecase", Value::Integer(5));
         self.env.define_const("stringcomparison.currentculture", Value::Integer(0));
         self.env.define_const("stringcomparison.currentcultureignorecase", Value::Integer(1));
+
+        // MidpointRounding constants
+        self.env.define_const("midpointrounding.toeven", Value::Integer(0));
+        self.env.define_const("midpointrounding.awayfromzero", Value::Integer(1));
+        self.env.define_const("midpointrounding.tozero", Value::Integer(2));
+        self.env.define_const("midpointrounding.tonegativeinfinity", Value::Integer(3));
+        self.env.define_const("midpointrounding.topositiveinfinity", Value::Integer(4));
     }
 
     pub fn run(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        self.as_run(|this| stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || this.run_program(program)))
+    }
+
+    fn run_program(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        self.record_options(program);
         // First pass: collect all declarations
         for decl in &program.declarations {
             self.declare(decl)?;
         }
 
         // Second pass: execute statements
-        for stmt in &program.statements {
-            self.execute(stmt)?;
-        }
-
-        Ok(())
+        let prev_source = self.enter_source(&program.source_file);
+        let result = self.execute_block(&program.statements);
+        self.leave_source(prev_source);
+        result
     }
 
     /// Set the command-line arguments available to the VB program.
@@ -1661,6 +1811,11 @@ impl Interpreter {
     }
 
     pub fn load_module(&mut self, module_name: &str, program: &Program) -> Result<(), RuntimeError> {
+        self.as_run(|this| this.load_module_impl(module_name, program))
+    }
+
+    fn load_module_impl(&mut self, module_name: &str, program: &Program) -> Result<(), RuntimeError> {
+        self.record_options(program);
         // Define module as a namespace (string proxy) so Module.Member works
         self.env.define(module_name, Value::String(module_name.to_lowercase()));
 
@@ -1674,13 +1829,13 @@ impl Interpreter {
         }
 
         // Execute module-level statements if any
-        for stmt in &program.statements {
-            self.execute(stmt)?;
-        }
+        let prev_source = self.enter_source(&program.source_file);
+        let result = self.execute_block(&program.statements);
+        self.leave_source(prev_source);
 
         // Restore previous module
         self.current_module = prev_module;
-        Ok(())
+        result
     }
 
     /// Load a VB.NET code file into **global** scope.
@@ -1693,6 +1848,11 @@ impl Interpreter {
     /// Use this for `.vbproj` code files.  Use `load_module` when you need a
     /// named scope (VB6 `.bas` files, form code-behind, synthetic helpers).
     pub fn load_code_file(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        self.as_run(|this| this.load_code_file_impl(program))
+    }
+
+    fn load_code_file_impl(&mut self, program: &Program) -> Result<(), RuntimeError> {
+        self.record_options(program);
         // No current_module ⇒ declare() registers names without a prefix.
         let prev_module = self.current_module.take();
 
@@ -1700,15 +1860,18 @@ impl Interpreter {
             self.declare(decl)?;
         }
 
-        for stmt in &program.statements {
-            self.execute(stmt)?;
-        }
+        let prev_source = self.enter_source(&program.source_file);
+        let result = self.execute_block(&program.statements);
+        self.leave_source(prev_source);
 
         self.current_module = prev_module;
-        Ok(())
+        result
     }
 
     fn declare(&mut self, decl: &Declaration) -> Result<(), RuntimeError> {
+        // Bodies compiled so far may have been declared where this one is
+        self.chunks.clear();
+        self.resolutions.clear();
         match decl {
             Declaration::Variable(vars) => {
                 for var in vars {
@@ -1741,7 +1904,7 @@ impl Interpreter {
                 } else {
                     sub_decl.name.as_str().to_lowercase()
                 };
-                self.subs.insert(key, sub_decl.clone());
+                self.register_procedure(key, MethodDecl::Sub(sub_decl.clone()));
                 Ok(())
             }
             Declaration::Function(func_decl) => {
@@ -1750,7 +1913,7 @@ impl Interpreter {
                 } else {
                     func_decl.name.as_str().to_lowercase()
                 };
-                self.functions.insert(key, func_decl.clone());
+                self.register_procedure(key, MethodDecl::Function(func_decl.clone()));
                 Ok(())
             }
             Declaration::Class(class_decl) => {
@@ -1765,6 +1928,7 @@ impl Interpreter {
                     if existing.is_partial || class_decl.is_partial {
                         existing.fields.extend(class_decl.fields.clone());
                         existing.methods.extend(class_decl.methods.clone());
+                        existing.operators.extend(class_decl.operators.clone());
                         existing.properties.extend(class_decl.properties.clone());
                         if existing.inherits.is_none() {
                             existing.inherits = class_decl.inherits.clone();
@@ -1799,16 +1963,8 @@ impl Interpreter {
 
                 // Register class methods as subs so they can be called by event system
                 for method in &class_decl.methods {
-                    match method {
-                        vybe_parser::ast::MethodDecl::Sub(sub_decl) => {
-                            let sub_key = format!("{}.{}", key, sub_decl.name.as_str().to_lowercase());
-                            self.subs.insert(sub_key, sub_decl.clone());
-                        }
-                        vybe_parser::ast::MethodDecl::Function(func_decl) => {
-                            let func_key = format!("{}.{}", key, func_decl.name.as_str().to_lowercase());
-                            self.functions.insert(func_key, func_decl.clone());
-                        }
-                    }
+                    let method_key = format!("{}.{}", key, crate::overloads::method_name(method).to_lowercase());
+                    self.register_procedure(method_key, method.clone());
                 }
 
                 // Hoist nested classes — register them as top-level classes
@@ -1877,6 +2033,7 @@ impl Interpreter {
                 let class = vybe_parser::ClassDecl {
                     visibility: struct_decl.visibility.clone(),
                     name: struct_decl.name.clone(),
+                    type_parameters: struct_decl.type_parameters.clone(),
                     is_partial: false,
                     inherits: None,
                     implements: struct_decl.implements.clone(),
@@ -1887,6 +2044,10 @@ impl Interpreter {
                     is_not_inheritable: true, // structs can't be inherited
                     nested_classes: Vec::new(),
                     nested_enums: Vec::new(),
+                    operators: struct_decl.operators.clone(),
+                    events: Vec::new(),
+                    attributes: struct_decl.attributes.clone(),
+                    span: struct_decl.span,
                 };
                 self.classes.insert(key.clone(), class);
                 self.structures.insert(key, struct_decl.clone());
@@ -1938,6 +2099,7 @@ impl Interpreter {
                         if existing.is_partial || class_decl.is_partial {
                             existing.fields.extend(class_decl.fields.clone());
                             existing.methods.extend(class_decl.methods.clone());
+                            existing.operators.extend(class_decl.operators.clone());
                             existing.properties.extend(class_decl.properties.clone());
                             if existing.inherits.is_none() {
                                 existing.inherits = class_decl.inherits.clone();
@@ -1963,16 +2125,8 @@ impl Interpreter {
 
                     // Register class methods
                     for method in &class_decl.methods {
-                        match method {
-                            vybe_parser::ast::MethodDecl::Sub(sub_decl) => {
-                                let sub_key = format!("{}.{}", qualified_key, sub_decl.name.as_str().to_lowercase());
-                                self.subs.insert(sub_key, sub_decl.clone());
-                            }
-                            vybe_parser::ast::MethodDecl::Function(func_decl) => {
-                                let func_key = format!("{}.{}", qualified_key, func_decl.name.as_str().to_lowercase());
-                                self.functions.insert(func_key, func_decl.clone());
-                            }
-                        }
+                        let method_key = format!("{}.{}", qualified_key, crate::overloads::method_name(method).to_lowercase());
+                        self.register_procedure(method_key, method.clone());
                     }
                 }
                 other => {
@@ -1995,11 +2149,14 @@ impl Interpreter {
     ///   4. imports-qualified (`imported_ns.classname`)
     ///   5. any key whose last segment matches (`*.classname`)
     fn resolve_class_key(&self, class_name: &str) -> Option<String> {
-        let lower = class_name.to_lowercase();
+        // Box(Of Integer) is an instance of the Box class
+        let class_name = crate::generics::split_type_arguments(class_name).map_or(class_name, |(base, _)| base);
+        let lower_name = Symbol::intern(class_name).name();
+        let lower: &str = &lower_name;
 
         // 1. Exact match
-        if self.classes.contains_key(&lower) {
-            return Some(lower);
+        if self.classes.contains_key(lower) {
+            return Some(lower.to_string());
         }
 
         // 2. Try module/namespace-qualified
@@ -2011,7 +2168,7 @@ impl Interpreter {
         }
 
         // 3. Check namespace_map
-        if let Some(fq_key) = self.namespace_map.get(&lower) {
+        if let Some(fq_key) = self.namespace_map.get(lower) {
             if self.classes.contains_key(fq_key) {
                 return Some(fq_key.clone());
             }
@@ -2066,188 +2223,1324 @@ impl Interpreter {
         None
     }
 
-    // Helper to find a property in class hierarchy
-    fn find_property(&self, class_name: &str, prop_name: &str) -> Option<vybe_parser::ast::decl::PropertyDecl> {
-        let key = self.resolve_class_key(class_name).unwrap_or_else(|| class_name.to_lowercase());
-        if let Some(cls) = self.classes.get(&key) {
-             // Check current class
-            for prop in &cls.properties {
-                if prop.name.as_str().eq_ignore_ascii_case(prop_name) {
-                    return Some(prop.clone());
+    /// All overloads of `method_name` visible on `class_name`: its own
+    /// declarations plus base-class ones with other signatures. Constructors
+    /// aren't inherited, so `New` stops at the first class that declares one.
+    fn find_method_overloads(&self, class_name: &str, method_name: &str) -> Vec<MethodDecl> {
+        let mut found: Vec<MethodDecl> = Vec::new();
+        let mut current = Some(class_name.to_string());
+        let mut depth = 0;
+        while let Some(name) = current {
+            let Some(cls) = self.resolve_class_key(&name).and_then(|k| self.classes.get(&k)) else { break };
+            for method in &cls.methods {
+                let params = crate::overloads::method_parameters(method);
+                if crate::overloads::method_name(method).eq_ignore_ascii_case(method_name)
+                    && !found.iter().any(|m| crate::overloads::same_signature(crate::overloads::method_parameters(m), params))
+                {
+                    found.push(method.clone());
                 }
             }
-            
-            // Check base class
-             if let Some(parent_type) = &cls.inherits {
-                 let parent_name = match parent_type {
-                     vybe_parser::VBType::Custom(n) => Some(n.clone()),
-                     _ => None,
-                 };
-                 if let Some(p_name) = parent_name {
-                     return self.find_property(&p_name, prop_name);
-                 }
-             }
+            if method_name.eq_ignore_ascii_case("new") && !found.is_empty() {
+                break;
+            }
+            current = match &cls.inherits {
+                Some(vybe_parser::VBType::Custom(parent)) => Some(parent.clone()),
+                _ => None,
+            };
+            depth += 1;
+            if depth > 64 {
+                break;
+            }
         }
-        None
+        found
     }
 
-    /// Get the parent class name from a class's `Inherits` clause.
-    pub fn get_parent_class_name(&self, class_name: &str) -> Option<String> {
-        let key = self.resolve_class_key(class_name)?;
-        let cls = self.classes.get(&key)?;
-        match &cls.inherits {
-            Some(vybe_parser::VBType::Custom(n)) => Some(n.clone()),
-            _ => None,
+    /// Store a procedure under `key` in `subs` / `functions`, next to earlier
+    /// declarations with other signatures. Re-declaring a signature replaces it.
+    fn register_procedure(&mut self, key: String, method: MethodDecl) {
+        use crate::overloads::same_signature;
+        let name = crate::overloads::method_name(&method).to_lowercase();
+        let params = crate::overloads::method_parameters(&method);
+        let overloaded = self.overloads.keys(&name).iter()
+            .any(|k| self.signatures(k).any(|other| !same_signature(other, params)));
+        self.overloads.register(name, &key, overloaded);
+        // A Sub and a Function with one signature are the same overload
+        let params = params.to_vec();
+        if let Some(subs) = self.subs.get_mut(&key) {
+            subs.retain(|s| !same_signature(&s.parameters, &params));
+        }
+        if let Some(functions) = self.functions.get_mut(&key) {
+            functions.retain(|f| !same_signature(&f.parameters, &params));
+        }
+        match method {
+            MethodDecl::Sub(s) => self.subs.entry(key).or_default().push(Arc::new(s)),
+            MethodDecl::Function(f) => self.functions.entry(key).or_default().push(Arc::new(f)),
         }
     }
 
-    /// Find a method starting from the **parent** of the given class.
-    /// Used for `MyBase.Method()` — skips the derived class entirely.
-    pub fn find_method_in_base(&self, class_name: &str, method_name: &str) -> Option<vybe_parser::ast::decl::MethodDecl> {
-        if let Some(parent) = self.get_parent_class_name(class_name) {
-            self.find_method(&parent, method_name)
-        } else {
-            None
+    /// The latest Sub declared under `key`.
+    fn sub(&self, key: &str) -> Option<Arc<SubDecl>> {
+        self.subs.get(key).and_then(|subs| subs.last()).cloned()
+    }
+
+    /// The latest Function declared under `key`.
+    fn function(&self, key: &str) -> Option<Arc<FunctionDecl>> {
+        self.functions.get(key).and_then(|functions| functions.last()).cloned()
+    }
+
+    /// The parameters of every procedure declared under `key`.
+    fn signatures<'a>(&'a self, key: &str) -> impl Iterator<Item = &'a [vybe_parser::ast::decl::Parameter]> {
+        let subs = self.subs.get(key).into_iter().flatten().map(|s| s.parameters.as_slice());
+        let functions = self.functions.get(key).into_iter().flatten().map(|f| f.parameters.as_slice());
+        subs.chain(functions)
+    }
+
+    /// Every procedure declared under `key`, Subs first.
+    fn overload_set(&self, key: &str) -> Vec<MethodDecl> {
+        let subs = self.subs.get(key).into_iter().flatten().map(|s| MethodDecl::Sub(SubDecl::clone(s)));
+        let functions = self.functions.get(key).into_iter().flatten().map(|f| MethodDecl::Function(FunctionDecl::clone(f)));
+        subs.chain(functions).collect()
+    }
+
+    /// How `class_name` (the class of an argument, `None` for non-objects)
+    /// relates to the parameter type `target`, for overload scoring.
+    fn class_match(&self, class_name: Option<&str>, target: &str) -> ClassMatch {
+        if self.resolve_class_key(target).is_none() {
+            return ClassMatch::Unknown;
+        }
+        let mut current = class_name.map(str::to_string);
+        let mut distance = 0u8;
+        while let Some(name) = current {
+            let short = name.rsplit('.').next().unwrap_or(&name);
+            if name.eq_ignore_ascii_case(target) || short.eq_ignore_ascii_case(target) {
+                return ClassMatch::Distance(distance);
+            }
+            current = self.get_parent_class_name(&name);
+            distance = distance.saturating_add(1);
+            if distance > 64 {
+                break;
+            }
         }
+        ClassMatch::Unrelated
     }
 
-    /// Walk the inheritance chain to check if `class_name` is or inherits from `target`.
-    /// Used for `TypeOf x Is BaseClass`.
-    pub fn is_type_or_base(&self, class_name: &str, target: &str) -> bool {
-        if class_name.eq_ignore_ascii_case(target) {
-            return true;
+    /// `symbol` operators declared on `class_name` or its base classes, as
+    /// Shared Functions so they resolve and run like any other procedure.
+    fn find_operators(&self, class_name: &str, symbol: &str) -> Vec<FunctionDecl> {
+        self.operator_decls(class_name, symbol).into_iter().map(operator_function).collect()
+    }
+
+    /// The declarations behind [`Self::find_operators`].
+    fn operator_decls(&self, class_name: &str, symbol: &str) -> Vec<&vybe_parser::ast::decl::OperatorDecl> {
+        let mut found = Vec::new();
+        let mut current = Some(class_name.to_string());
+        let mut depth = 0;
+        while let Some(name) = current {
+            let Some(cls) = self.resolve_class_key(&name).and_then(|k| self.classes.get(&k)) else { break };
+            found.extend(cls.operators.iter().filter(|op| op.symbol.eq_ignore_ascii_case(symbol)));
+            current = match &cls.inherits {
+                Some(vybe_parser::VBType::Custom(parent)) => Some(parent.clone()),
+                _ => None,
+            };
+            depth += 1;
+            if depth > 64 {
+                break;
+            }
         }
-        // Also match against unqualified target (e.g. "Form" matches "System.Windows.Forms.Form")
-        let class_lower = class_name.to_lowercase();
-        let target_lower = target.to_lowercase();
-        if class_lower.ends_with(&format!(".{}", target_lower)) || target_lower.ends_with(&format!(".{}", class_lower)) {
-            return true;
+        found
+    }
+
+    /// Apply a user-defined operator when an operand is an instance of a class
+    /// or structure declaring `symbol`. `None` leaves the operation to the
+    /// built-in rules.
+    fn user_operator(&mut self, symbol: &str, operands: &[Value]) -> Result<Option<Value>, RuntimeError> {
+        if !operands.iter().any(|v| matches!(v, Value::Object(_))) {
+            return Ok(None);
         }
-        // Walk up the hierarchy
-        if let Some(parent) = self.get_parent_class_name(class_name) {
-            return self.is_type_or_base(&parent, target);
+        let mut candidates: Vec<FunctionDecl> = Vec::new();
+        let mut searched: Vec<String> = Vec::new();
+        for value in operands {
+            let Value::Object(obj) = value else { continue };
+            let class_name = obj.borrow().class_name.clone();
+            if searched.iter().any(|c| c.eq_ignore_ascii_case(&class_name)) {
+                continue;
+            }
+            for op in self.find_operators(&class_name, symbol) {
+                if op.parameters.len() == operands.len() && !candidates.contains(&op) {
+                    candidates.push(op);
+                }
+            }
+            searched.push(class_name);
         }
-        false
+        self.invoke_best_operator(symbol, candidates, operands)
     }
 
+    /// [`Self::user_operator`] for a binary operator, without copying the
+    /// operands unless one of them is an object.
+    fn binary_operator(&mut self, symbol: &str, l: &Value, r: &Value) -> Result<Option<Value>, RuntimeError> {
+        if !matches!(l, Value::Object(_)) && !matches!(r, Value::Object(_)) {
+            return Ok(None);
+        }
+        self.user_operator(symbol, &[l.clone(), r.clone()])
+    }
 
-    pub fn execute(&mut self, stmt: &Statement) -> Result<(), RuntimeError> {
-        match stmt {
-            Statement::Dim(decls) => {
-                for decl in decls {
-                    if let Some(bounds) = &decl.array_bounds {
-                        if bounds.len() == 1 {
-                            // 1-D array: Dim arr(10) As Integer
-                            let size = (self.evaluate_expr(&bounds[0])?.as_integer()? + 1) as usize;
-                            let default_val = default_value_for_type("", &decl.var_type);
-                            let arr = Value::Array(vec![default_val; size]);
-                            self.env.define(decl.name.as_str(), arr);
-                        } else {
-                            // Multi-dimensional: Dim arr(3, 4) → nested arrays
-                            let dims: Vec<usize> = bounds.iter()
-                                .map(|b| self.evaluate_expr(b).and_then(|v| v.as_integer()).map(|i| (i + 1) as usize))
-                                .collect::<Result<Vec<_>, _>>()?;
-                            let default_val = default_value_for_type("", &decl.var_type);
-                            let arr = create_multi_dim_array(&dims, &default_val);
-                            self.env.define(decl.name.as_str(), arr);
-                        }
-                    } else if let Some(init) = &decl.initializer {
-                        // Variable with initializer: Dim x As Integer = 10 or Dim arr() = {1,2,3}
-                        let val = self.evaluate_expr(init)?;
-                        self.env.define(decl.name.as_str(), val);
-                    } else {
-                        // Regular variable: Dim x As Integer
-                        let val = default_value_for_type(&decl.name.as_str(), &decl.var_type);
-                        self.env.define(decl.name.as_str(), val);
-                    }
+    /// The new value for `target op= rhs`. Like the plain operator, a
+    /// user-defined one on either operand's class comes first.
+    fn compound_operation(&mut self, operator: &vybe_parser::ast::stmt::CompoundOp, current: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = self.binary_operator(compound_symbol(operator), current, rhs)? {
+            return Ok(result);
+        }
+        let result = compound_value(operator, current, rhs)?;
+        self.budget.check_value(&result)?;
+        Ok(result)
+    }
+
+    /// `dict(key) = value`, which adds an entry when the key is new.
+    fn set_dictionary_item(&mut self, dict: &Rc<RefCell<crate::collections::VBDictionary>>, key: Value, value: Value) -> Result<(), RuntimeError> {
+        if !dict.borrow().contains_key(&key) {
+            self.budget.check_len(dict.borrow().count() as usize + 1)?;
+        }
+        dict.borrow_mut().set_item(key, value);
+        Ok(())
+    }
+
+    /// Convert `value` to `target` with a `Widening`/`Narrowing Operator CType`
+    /// declared on the value's class or on the target class, of one of `kinds`.
+    fn user_conversion(&mut self, value: &Value, target: &str, kinds: &[ConversionKind]) -> Result<Option<Value>, RuntimeError> {
+        let candidates = self.conversions(value, target, kinds);
+        self.invoke_best_operator("CType", candidates, std::slice::from_ref(value))
+    }
+
+    /// The `CType` operators of `kinds` that could convert `value` to `target`.
+    fn conversions(&self, value: &Value, target: &str, kinds: &[ConversionKind]) -> Vec<FunctionDecl> {
+        let mut owners = Vec::new();
+        if let Value::Object(obj) = value {
+            owners.push(obj.borrow().class_name.clone());
+        }
+        if self.resolve_class_key(target).is_some() {
+            owners.push(target.to_string());
+        }
+        // Widening all the way: the value mustn't narrow to the operand either
+        let widening = !kinds.contains(&ConversionKind::Narrowing);
+        let mut candidates: Vec<FunctionDecl> = Vec::new();
+        for owner in owners {
+            let ops = self.operator_decls(&owner, "CType").into_iter()
+                .filter(|op| op.conversion.is_none_or(|kind| kinds.contains(&kind)))
+                .filter(|op| !widening || !op.parameters.first().and_then(|p| p.param_type.as_ref())
+                    .is_some_and(|ty| options::is_narrowing(ty, value, false)))
+                .map(operator_function)
+                .collect::<Vec<_>>();
+            for op in ops {
+                let returns_target = op.return_type.as_ref().is_some_and(|t| crate::generics::same_type(&t.to_string(), target));
+                if op.parameters.len() == 1 && returns_target && !candidates.contains(&op) {
+                    candidates.push(op);
                 }
-                Ok(())
             }
-            Statement::Const(decl) => {
-                // Evaluate the constant expression
-                let val = self.evaluate_expr(&decl.value)?;
-                // Store as a constant (read-only)
-                self.env.define_const(decl.name.as_str(), val);
-                Ok(())
+        }
+        candidates
+    }
+
+    /// Does the class or structure `ty` names declare `CType` operators?
+    fn declares_conversions(&self, ty: &vybe_parser::VBType) -> bool {
+        matches!(ty, vybe_parser::VBType::Custom(name) if !self.operator_decls(name, "CType").is_empty())
+    }
+
+    /// `value` converted for storing as `target`, a class or structure it
+    /// isn't an instance of: through a user-defined `Widening Operator
+    /// CType`, or a `Narrowing` one with Option Strict Off. Unchanged when
+    /// there is none.
+    fn implicit_conversion(&mut self, target: &str, value: Value) -> Result<Value, RuntimeError> {
+        let target = self.resolve_type_name(target);
+        if self.is_instance_of(&value, &target) {
+            return Ok(value);
+        }
+        let strict = options::strict(&self.active_options);
+        let kinds = if strict { &[ConversionKind::Widening][..] } else { ALL_CONVERSIONS };
+        match self.user_conversion(&value, &target, kinds)? {
+            Some(converted) => Ok(converted),
+            // Only a narrowing conversion would do
+            None if strict && !self.conversions(&value, &target, ALL_CONVERSIONS).is_empty() => {
+                Err(options::narrowing_error(options::value_type_name(&value), &vybe_parser::VBType::Custom(target)))
+            }
+            None => Ok(value),
+        }
+    }
+
+    /// [`coerce_to_declared`] after any user-defined conversion to `ty`.
+    fn convert_to_declared(&mut self, ty: Option<&vybe_parser::VBType>, value: Value) -> Result<Value, RuntimeError> {
+        let value = match conversion_target(ty, &value) {
+            Some(target) => self.implicit_conversion(&target, value)?,
+            None => value,
+        };
+        coerce_to_declared(ty, value)
+    }
+
+    /// [`Self::convert_to_declared`] for a value stored in the variable `symbol`.
+    fn convert_to_variable(&mut self, symbol: Symbol, value: Value) -> Result<Value, RuntimeError> {
+        let value = match conversion_target(self.env.declared_type_of(symbol), &value) {
+            Some(target) => self.implicit_conversion(&target, value)?,
+            None => value,
+        };
+        coerce_to_declared(self.env.declared_type_of(symbol), value)
+    }
+
+    /// `IsTrue` on an object whose class declares it, otherwise the value's
+    /// own truthiness. Used for `If`, loop conditions and `AndAlso`/`OrElse`.
+    fn condition_is_true(&mut self, value: &Value) -> Result<bool, RuntimeError> {
+        match self.user_operator("IsTrue", std::slice::from_ref(value))? {
+            Some(result) => result.as_bool(),
+            None => Ok(value.is_truthy()),
+        }
+    }
+
+    /// The branch of an `If` whose condition holds, if any.
+    fn if_branch<'a>(
+        &mut self,
+        condition: &Expression,
+        then_branch: &'a Block,
+        elseif_branches: &'a [(Expression, Block)],
+        else_branch: &'a Option<Block>,
+    ) -> Result<Option<&'a Block>, RuntimeError> {
+        let cond_val = self.evaluate_expr(condition)?;
+        if self.condition_is_true(&cond_val)? {
+            return Ok(Some(then_branch));
+        }
+        for (elseif_cond, elseif_stmts) in elseif_branches {
+            let elseif_val = self.evaluate_expr(elseif_cond)?;
+            if self.condition_is_true(&elseif_val)? {
+                return Ok(Some(elseif_stmts));
             }
-            Statement::Assignment { target, value } => {
-                let val = self.evaluate_expr(value)?;
-                let target_str = target.as_str();
-                let target_lower = target_str.to_lowercase();
-                
-                // 1. Check if it's a local variable (exists in any scope except the global one)
-                if self.env.has_local(target_str) {
-                    self.env.set(target_str, val)?;
-                    return Ok(());
+        }
+        Ok(else_branch.as_ref())
+    }
+
+    /// The body of the first `Case` matching `test_expr`, or the `Case Else` block.
+    fn select_case_body<'a>(
+        &mut self,
+        test_expr: &Expression,
+        cases: &'a [CaseBlock],
+        else_block: &'a Option<Block>,
+    ) -> Result<Option<&'a Block>, RuntimeError> {
+        let test_val = self.evaluate_expr(test_expr)?;
+        let test_val = self.fold_case(test_val);
+        for case in cases {
+            for condition in &case.conditions {
+                let matches = match condition {
+                    CaseCondition::Value(expr) => {
+                        let case_val = self.evaluate_expr(expr)?;
+                        self.equal_values(&test_val, &case_val)
+                    }
+                    CaseCondition::Range { from, to } => {
+                        let from_val = self.evaluate_expr(from)?;
+                        let to_val = self.evaluate_expr(to)?;
+                        value_in_range(&test_val, &self.fold_case(from_val), &self.fold_case(to_val))
+                    }
+                    CaseCondition::Comparison { op, expr } => {
+                        let comp_val = self.evaluate_expr(expr)?;
+                        match op {
+                            vybe_parser::CompOp::Equal => self.equal_values(&test_val, &comp_val),
+                            vybe_parser::CompOp::NotEqual => !self.equal_values(&test_val, &comp_val),
+                            _ => compare_values(&test_val, op, &self.fold_case(comp_val))?,
+                        }
+                    }
+                };
+                if matches {
+                    return Ok(Some(&case.body));
                 }
+            }
+        }
+        Ok(else_block.as_ref())
+    }
 
-                // 2. Check if it's a field in the current object
-                if let Some(obj_rc) = &self.current_object {
-                    // Check if field is WithEvents before modification
-                    let (is_withevents, old_val) = {
-                        let obj = obj_rc.borrow();
-                        let class_name = obj.class_name.clone();
-                        let old = obj.fields.get(target_lower.as_str()).cloned();
-                        
-                        let is_we = if let Some(cls) = self.classes.get(&class_name.to_lowercase()) {
-                             cls.fields.iter().any(|f| f.name.as_str().eq_ignore_ascii_case(target_str) && f.with_events)
-                        } else { false };
-                        (is_we, old)
-                    };
+    /// Runs the first `Catch` block matching error `e`. None when `e` is control
+    /// flow (Exit, Return, Continue), a limit or cancellation, or no block
+    /// matches, so it keeps propagating.
+    fn run_catch(&mut self, e: &RuntimeError, catches: &[CatchBlock]) -> Option<Result<(), RuntimeError>> {
+        if matches!(e, RuntimeError::Exit(_) | RuntimeError::Return(_) | RuntimeError::Continue(_)) || e.is_abort() {
+            return None;
+        }
+        // Extract exception type from the error
+        let (ex_type, ex_msg, ex_inner) = match e.without_location() {
+            RuntimeError::Exception(t, m, inner) => (t.clone(), m.clone(), inner.clone()),
+            RuntimeError::Custom(m) => ("Exception".to_string(), m.clone(), None),
+            RuntimeError::TypeError { expected, got } => ("TypeMismatchException".to_string(), format!("Type error: expected {}, got {}", expected, got), None),
+            RuntimeError::UndefinedVariable(v) => ("NullReferenceException".to_string(), format!("Undefined variable: {}", v), None),
+            RuntimeError::UndefinedFunction(f) => ("MissingMethodException".to_string(), format!("Undefined function: {}", f), None),
+            RuntimeError::DivisionByZero => ("DivideByZeroException".to_string(), "Division by zero".to_string(), None),
+            RuntimeError::StackOverflow(_) => ("StackOverflowException".to_string(), crate::value::STACK_OVERFLOW_MESSAGE.to_string(), None),
+            other => ("Exception".to_string(), format!("{}", other), None),
+        };
+        let ex_trace = match (e.without_location(), e.location()) {
+            (RuntimeError::StackOverflow(chain), _) => crate::value::stack_trace(chain),
+            (_, Some(loc)) => format!(
+                "   at {} in {}",
+                loc.procedure.as_deref().unwrap_or("<module>"),
+                loc
+            ),
+            (_, None) => String::new(),
+        };
 
-                    {
-                        let mut obj = obj_rc.borrow_mut();
-                        // Create or update instance field
-                        obj.fields.insert(target_lower.clone(), val.clone());
-                    }
-                    
-                    if is_withevents {
-                        let host = obj_rc.clone();
-                         if let Some(old) = old_val {
-                             self.unwire_events(&old, target_str, host.clone());
-                         }
-                         self.wire_events(&val, target_str, host);
-                    }
+        for catch in catches {
+            // Check variable type match
+            let type_match = if let Some((_, Some(type_name))) = &catch.variable {
+                let catch_type = format!("{:?}", type_name).to_lowercase();
+                let catch_type = catch_type.trim_start_matches("custom(\"").trim_end_matches("\")");
+                let ex_lower = ex_type.to_lowercase();
+                catch_type == "exception" || catch_type == "system.exception"
+                    || ex_lower == catch_type
+                    || ex_lower.ends_with(&format!(".{}", catch_type))
+                    || catch_type.ends_with(&ex_lower)
+                    || ex_lower.ends_with("exception")
+            } else {
+                true // Catch All
+            };
+            if !type_match {
+                continue;
+            }
 
-                    return Ok(());
-                }
+            // Push scope for the catch variable to exist during 'When' evaluation and body execution
+            self.env.push_scope();
 
-                // 3. Fallback: set in environment (will check global scope or define as global)
-                self.env.set(target_str, val)?;
-                Ok(())
+            // Define the exception variable if present
+            if let Some((name, _)) = &catch.variable {
+                let mut ex_fields = HashMap::new();
+                ex_fields.insert("message".to_string(), Value::String(ex_msg.clone()));
+                // Basic stacktrace support: where the exception was raised
+                ex_fields.insert("stacktrace".to_string(), Value::String(ex_trace.clone()));
+                ex_fields.insert("source".to_string(), Value::String(String::new()));
+                ex_fields.insert("hresult".to_string(), Value::Integer(-2146233088));
+                let inner = match &ex_inner {
+                    Some(inner) => Value::String(inner.clone()),
+                    None => Value::Nothing,
+                };
+                ex_fields.insert("innerexception".to_string(), inner);
+
+                let ex_obj = ObjectData {
+                    drawing_commands: Vec::new(),
+                    class_name: ex_type.clone(),
+                    fields: ex_fields,
+                };
+                self.env.define(name.as_str(), Value::Object(Rc::new(RefCell::new(ex_obj))));
             }
 
-            Statement::SetAssignment { target, value } => {
-                let val = self.evaluate_expr(value)?;
-                self.env.set(target.as_str(), val)?;
-                Ok(())
+            let when_match = match &catch.when_clause {
+                // If evaluation fails (e.g. wrong property), treat as false match
+                Some(expr) => self.evaluate_expr(expr).map(|val| val.is_truthy()).unwrap_or(false),
+                None => true,
+            };
+
+            if when_match {
+                let result = self.execute_block(&catch.body);
+                self.env.pop_scope();
+                return Some(result);
             }
+            // Pop scope if we didn't match (remove the variable)
+            self.env.pop_scope();
+        }
+        None
+    }
 
-            Statement::SyncLock { lock_object, body } => {
-                let _lock = self.evaluate_expr(lock_object)?;
-                // Verify reference type if strict, but for now just execute body
-                // In a single-threaded interpreter, the lock is implicit
-                for stmt in body {
-                    self.execute(stmt)?;
-                }
-                Ok(())
+    fn invoke_best_operator(&mut self, symbol: &str, candidates: Vec<FunctionDecl>, operands: &[Value]) -> Result<Option<Value>, RuntimeError> {
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+        let class_match = |class: Option<&str>, target: &str| self.class_match(class, target);
+        let scores: Vec<_> = candidates
+            .iter()
+            .map(|op| crate::overloads::score_candidate(&op.parameters, operands, &class_match))
+            .collect();
+        match crate::overloads::select_best(&scores) {
+            Ok(best) => self.invoke_user_function(&candidates[best], Some(operands), None, None).map(Some),
+            Err(tied) if tied.is_empty() => Ok(None),
+            Err(tied) => {
+                let signatures: Vec<String> = tied
+                    .iter()
+                    .map(|&i| crate::overloads::signature_display(&MethodDecl::Function(candidates[i].clone())))
+                    .collect();
+                Err(RuntimeError::Exception(
+                    "AmbiguousMatchException".to_string(),
+                    format!("Overload resolution failed because no 'Operator {}' is most specific for these arguments:\n    {}", symbol, signatures.join("\n    ")),
+                    None,
+                ))
             }
+        }
+    }
 
-            Statement::MemberAssignment { object, member, value } => {
-                let val = self.evaluate_expr(value)?;
-                let obj_val = self.evaluate_expr(object)?;
-                let prop_name = member.as_str().to_string();
+    /// `name` with the type parameters in scope replaced by their arguments.
+    fn resolve_type_name(&self, name: &str) -> String {
+        crate::generics::substitute(name, &self.type_args)
+    }
 
-                match obj_val {
-                    Value::Object(obj_ref) => {
-                        let class_key = obj_ref.borrow().class_name.to_lowercase();
-                        if let Some(class_decl) = self.classes.get(&class_key).cloned() {
-                            for prop in &class_decl.properties {
-                                if prop.name.as_str().eq_ignore_ascii_case(&prop_name) {
-                                    if let Some((param, body)) = &prop.setter {
-                                        let sub = SubDecl {
+    /// A declared variable type, with a type parameter replaced by its argument.
+    /// The value a variable of `var_type` starts with: a zeroed instance
+    /// for a structure, the type's default otherwise.
+    fn default_value(&mut self, var_type: &Option<vybe_parser::VBType>) -> Value {
+        match self.resolve_var_type(var_type) {
+            Some(vybe_parser::VBType::Custom(name)) if self.is_structure_type(&name) => self.structure_instance(&name),
+            resolved => default_value_for_type("", &resolved),
+        }
+    }
+
+    fn is_structure_type(&self, type_name: &str) -> bool {
+        self.resolve_class_key(type_name).is_some_and(|key| self.structures.contains_key(&key))
+    }
+
+    /// A structure instance with every field at its default, as `Dim p As
+    /// Point` gives without calling a constructor.
+    fn structure_instance(&mut self, type_name: &str) -> Value {
+        let fields = self.collect_fields(type_name);
+        let class_name = self.resolve_class_key(type_name)
+            .and_then(|key| self.structures.get(&key))
+            .map_or_else(|| type_name.to_string(), |decl| decl.name.as_str().to_string());
+        Value::Object(Rc::new(RefCell::new(ObjectData { drawing_commands: Vec::new(), class_name, fields })))
+    }
+
+    fn resolve_var_type(&self, var_type: &Option<vybe_parser::VBType>) -> Option<vybe_parser::VBType> {
+        match var_type {
+            Some(vybe_parser::VBType::Custom(name)) if !self.type_args.is_empty() => {
+                Some(vybe_parser::VBType::from_str(&self.resolve_type_name(name)))
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// The class chain of an instance of `class_name(Of args)`, most derived
+    /// first, with the type arguments each level is instantiated with. A base
+    /// level gets its arguments from the `Inherits Base(Of ...)` clause.
+    fn generic_chain(&self, class_name: &str, args: Vec<String>) -> Vec<(String, Vec<TypeParameter>, Vec<String>)> {
+        let mut chain = Vec::new();
+        let mut current = Some((class_name.to_string(), args));
+        while let Some((name, args)) = current.take() {
+            let Some(cls) = self.resolve_class_key(&name).and_then(|k| self.classes.get(&k)) else { break };
+            let mut bindings = TypeArgs::new();
+            bind_type_args(&mut bindings, &cls.type_parameters, &args);
+            if let Some(vybe_parser::VBType::Custom(parent)) = &cls.inherits {
+                let parent = crate::generics::substitute(parent, &bindings);
+                current = Some(match crate::generics::split_type_arguments(&parent) {
+                    Some((base, parent_args)) => (base.to_string(), parent_args),
+                    None => (parent, Vec::new()),
+                });
+            }
+            chain.push((cls.name.as_str().to_string(), cls.type_parameters.clone(), args));
+            if chain.len() > 64 {
+                break;
+            }
+        }
+        chain
+    }
+
+    /// Type arguments of `obj` viewed as its class or the base class `target`.
+    fn type_args_as(&self, obj: &crate::value::ObjectData, target: &str) -> Option<Vec<String>> {
+        let target = target.rsplit('.').next().unwrap_or(target);
+        self.generic_chain(&obj.class_name, crate::generics::instance_type_args(obj))
+            .into_iter()
+            .find(|(name, _, _)| name.eq_ignore_ascii_case(target))
+            .map(|(_, _, args)| args)
+    }
+
+    /// Is `value` an instance of the (possibly generic) type `type_name`?
+    fn is_instance_of(&self, value: &Value, type_name: &str) -> bool {
+        let Value::Object(obj) = value else { return false };
+        let obj = obj.borrow();
+        match crate::generics::split_type_arguments(type_name) {
+            Some((base, args)) => {
+                self.is_type_or_base(&obj.class_name, base)
+                    && self.type_args_as(&obj, base).is_some_and(|actual| {
+                        actual.len() == args.len()
+                            && actual.iter().zip(&args).all(|(a, b)| crate::generics::same_type(a, b))
+                    })
+            }
+            None => self.is_type_or_base(&obj.class_name, type_name),
+        }
+    }
+
+    /// Type arguments in scope for a call to `name`: those of the object it
+    /// runs on, plus the procedure's own, given explicitly with `(Of ...)` or
+    /// inferred from the arguments already bound to `params`.
+    fn generic_bindings(
+        &mut self,
+        name: &str,
+        type_params: &[TypeParameter],
+        params: &[vybe_parser::ast::decl::Parameter],
+    ) -> Result<TypeArgs, RuntimeError> {
+        let mut bindings = TypeArgs::new();
+        if let Some(obj) = &self.current_object {
+            let obj = obj.borrow();
+            if obj.fields.contains_key(crate::generics::TYPE_ARGS_FIELD) {
+                let chain = self.generic_chain(&obj.class_name, crate::generics::instance_type_args(&obj));
+                for (_, level_params, level_args) in chain.iter().rev() {
+                    bind_type_args(&mut bindings, level_params, level_args);
+                }
+            }
+        }
+        if type_params.is_empty() {
+            return Ok(bindings);
+        }
+
+        let explicit = match self.pending_type_args.take() {
+            Some((target, args)) if target.eq_ignore_ascii_case(name) => Some(args),
+            other => {
+                self.pending_type_args = other;
+                None
+            }
+        };
+        let args: Vec<String> = match explicit {
+            Some(args) => {
+                if args.len() != type_params.len() {
+                    return Err(RuntimeError::Exception(
+                        "ArgumentException".to_string(),
+                        format!("Wrong number of type arguments to '{}': expected {}, got {}", name, type_params.len(), args.len()),
+                        None,
+                    ));
+                }
+                args
+            }
+            None => type_params
+                .iter()
+                .map(|tp| self.infer_type_argument(tp, params).unwrap_or_else(|| "Object".to_string()))
+                .collect(),
+        };
+        self.check_type_constraints(name, type_params, &args)?;
+        bind_type_args(&mut bindings, type_params, &args);
+        Ok(bindings)
+    }
+
+    /// Infer a method type argument from a parameter declared `As T`, `As T()`
+    /// or `As SomeGeneric(Of T)`, whose argument is bound in the current scope.
+    fn infer_type_argument(&self, type_param: &TypeParameter, params: &[vybe_parser::ast::decl::Parameter]) -> Option<String> {
+        let tp_name = type_param.name.as_str();
+        for param in params {
+            let Some(ty) = &param.param_type else { continue };
+            let ty = ty.to_string();
+            let Ok(value) = self.env.get(param.name.as_str()) else { continue };
+            if ty.eq_ignore_ascii_case(tp_name) {
+                if !matches!(value, Value::Nothing) {
+                    return Some(crate::generics::value_type_name(&value));
+                }
+            } else if ty.strip_suffix("()").is_some_and(|elem| elem.eq_ignore_ascii_case(tp_name)) {
+                let Value::Array(items) = &value else { continue };
+                if let Some(first) = items.iter().find(|v| !matches!(v, Value::Nothing)) {
+                    return Some(crate::generics::value_type_name(first));
+                }
+            } else if let Some((base, ty_args)) = crate::generics::split_type_arguments(&ty) {
+                let Some(pos) = ty_args.iter().position(|a| a.eq_ignore_ascii_case(tp_name)) else { continue };
+                let Value::Object(obj) = &value else { continue };
+                if let Some(arg) = self.type_args_as(&obj.borrow(), base).and_then(|actual| actual.get(pos).cloned()) {
+                    return Some(arg);
+                }
+            }
+        }
+        None
+    }
+
+    /// Check type arguments against the `As ...` constraints of `owner`'s type parameters.
+    fn check_type_constraints(&self, owner: &str, type_params: &[TypeParameter], args: &[String]) -> Result<(), RuntimeError> {
+        let mut bindings = TypeArgs::new();
+        bind_type_args(&mut bindings, type_params, args);
+        for (tp, arg) in type_params.iter().zip(args) {
+            for constraint in &tp.constraints {
+                let satisfied = match constraint {
+                    TypeConstraint::Class => !self.is_value_type(arg),
+                    TypeConstraint::Structure => self.is_value_type(arg),
+                    TypeConstraint::New => self.has_default_constructor(arg),
+                    TypeConstraint::Type(ty) => {
+                        self.satisfies_type_constraint(arg, &crate::generics::substitute(&ty.to_string(), &bindings))
+                    }
+                };
+                if !satisfied {
+                    return Err(RuntimeError::Exception(
+                        "ArgumentException".to_string(),
+                        format!(
+                            "Type argument '{}' does not satisfy the '{}' constraint for type parameter '{}' of '{}'",
+                            arg, constraint, tp.name.as_str(), owner
+                        ),
+                        None,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_value_type(&self, type_name: &str) -> bool {
+        let base = crate::generics::split_type_arguments(type_name).map_or(type_name, |(base, _)| base);
+        crate::generics::is_value_type_name(base)
+            || self.resolve_class_key(base).is_some_and(|key| self.structures.contains_key(&key))
+    }
+
+    fn has_default_constructor(&self, type_name: &str) -> bool {
+        if self.is_value_type(type_name) {
+            return true;
+        }
+        let base = crate::generics::split_type_arguments(type_name).map_or(type_name, |(base, _)| base);
+        if base.eq_ignore_ascii_case("String") || base.eq_ignore_ascii_case("System.String") {
+            return false;
+        }
+        match self.resolve_class_key(base).and_then(|k| self.classes.get(&k)) {
+            Some(cls) => {
+                let constructors: Vec<_> = cls
+                    .methods
+                    .iter()
+                    .filter(|m| crate::overloads::method_name(m).eq_ignore_ascii_case("new"))
+                    .collect();
+                !cls.is_must_inherit
+                    && (constructors.is_empty()
+                        || constructors.iter().any(|m| crate::overloads::accepts_arg_count(crate::overloads::method_parameters(m), 0)))
+            }
+            None => true,
+        }
+    }
+
+    /// Does `type_name` derive from or implement `target`? Types the runtime
+    /// doesn't know about (e.g. `IComparable` on Integer) are given the benefit of the doubt.
+    fn satisfies_type_constraint(&self, type_name: &str, target: &str) -> bool {
+        let base = crate::generics::split_type_arguments(type_name).map_or(type_name, |(base, _)| base);
+        let target_base = crate::generics::split_type_arguments(target).map_or(target, |(base, _)| base);
+        if self.resolve_class_key(base).is_none() {
+            return self.resolve_class_key(target_base).is_none() && !self.is_known_interface(target_base);
+        }
+        self.is_type_or_base(base, target_base) || self.class_implements(base, target_base)
+            || (self.resolve_class_key(target_base).is_none() && !self.is_known_interface(target_base))
+    }
+
+    fn is_known_interface(&self, name: &str) -> bool {
+        let short = name.rsplit('.').next().unwrap_or(name);
+        self.interfaces.values().any(|i| i.name.as_str().eq_ignore_ascii_case(short))
+    }
+
+    /// Does `class_name` or one of its base classes implement `interface`
+    /// (directly or through interface inheritance)?
+    fn class_implements(&self, class_name: &str, interface: &str) -> bool {
+        let target = interface.rsplit('.').next().unwrap_or(interface);
+        let mut pending: Vec<String> = Vec::new();
+        let mut current = Some(class_name.to_string());
+        let mut depth = 0;
+        while let Some(name) = current.take() {
+            let Some(cls) = self.resolve_class_key(&name).and_then(|k| self.classes.get(&k)) else { break };
+            pending.extend(cls.implements.iter().map(|t| t.to_string()));
+            if let Some(vybe_parser::VBType::Custom(parent)) = &cls.inherits {
+                current = Some(parent.clone());
+            }
+            depth += 1;
+            if depth > 64 {
+                break;
+            }
+        }
+        let mut seen = 0;
+        while let Some(iface) = pending.pop() {
+            let base = crate::generics::split_type_arguments(&iface).map_or(iface.as_str(), |(base, _)| base);
+            let short = base.rsplit('.').next().unwrap_or(base);
+            if short.eq_ignore_ascii_case(target) {
+                return true;
+            }
+            if let Some(decl) = self.interfaces.values().find(|i| i.name.as_str().eq_ignore_ascii_case(short)) {
+                pending.extend(decl.inherits.iter().map(|t| t.to_string()));
+            }
+            seen += 1;
+            if seen > 256 {
+                break;
+            }
+        }
+        false
+    }
+
+    // Helper to find a property in class hierarchy
+    fn find_property(&self, class_name: &str, prop_name: &str) -> Option<vybe_parser::ast::decl::PropertyDecl> {
+        let key = self.resolve_class_key(class_name).unwrap_or_else(|| class_name.to_lowercase());
+        if let Some(cls) = self.classes.get(&key) {
+             // Check current class
+            for prop in &cls.properties {
+                if prop.name.as_str().eq_ignore_ascii_case(prop_name) {
+                    return Some(prop.clone());
+                }
+            }
+            
+            // Check base class
+             if let Some(parent_type) = &cls.inherits {
+                 let parent_name = match parent_type {
+                     vybe_parser::VBType::Custom(n) => Some(n.clone()),
+                     _ => None,
+                 };
+                 if let Some(p_name) = parent_name {
+                     return self.find_property(&p_name, prop_name);
+                 }
+             }
+        }
+        None
+    }
+
+    /// A field declared by a class or its bases, auto-implemented properties
+    /// included.
+    fn find_field_decl(&self, class_name: &str, field_name: &str) -> Option<vybe_parser::VariableDecl> {
+        let key = self.resolve_class_key(class_name)?;
+        let cls = self.classes.get(&key)?;
+        if let Some(field) = cls.fields.iter().find(|f| f.name.as_str().eq_ignore_ascii_case(field_name)) {
+            return Some(field.clone());
+        }
+        match &cls.inherits {
+            Some(vybe_parser::VBType::Custom(parent)) => self.find_field_decl(parent, field_name),
+            _ => None,
+        }
+    }
+
+    /// The length of `field` when `obj` is a user-defined type declaring it
+    /// `String * n`.
+    fn fixed_length_field(&self, obj: &ObjectData, field: &str) -> Option<usize> {
+        if !crate::structures::is_structure(obj) {
+            return None;
+        }
+        match self.find_field_decl(&obj.class_name, field)?.var_type {
+            Some(vybe_parser::VBType::FixedString(len)) => Some(len),
+            _ => None,
+        }
+    }
+
+    /// Store `value` in the variable, field or element `target` names, as an
+    /// assignment statement would. Used by statements that write back to
+    /// their operand: `Mid(s, 1) = ...`, `LSet`, `Get` and `Erase`.
+    fn assign_to(&mut self, target: &Expression, value: Value) -> Result<(), RuntimeError> {
+        const ASSIGNED: &str = " assigned";
+        let stmt = Statement::assignment(target.clone(), ExprKind::Variable(Identifier::new(ASSIGNED)).into())
+            .ok_or_else(|| RuntimeError::Custom("Expression is not assignable".to_string()))?;
+        self.env.push_scope();
+        self.env.define(ASSIGNED, value);
+        let result = self.execute(&stmt);
+        self.env.pop_scope();
+        result
+    }
+
+    /// How `value` is laid out in a `Random` or `Binary` file: a
+    /// user-defined type by its declared fields, anything else by its type.
+    fn record_layout(&self, value: &Value) -> Result<crate::records::Layout, RuntimeError> {
+        use crate::records::Layout;
+        let Value::Object(obj) = value else {
+            return Layout::of_value(value).ok_or_else(|| {
+                RuntimeError::Custom(format!("Can't write {} to a file record", crate::generics::value_type_name(value)))
+            });
+        };
+        let obj = obj.borrow();
+        if !crate::structures::is_structure(&obj) {
+            return Err(RuntimeError::Custom(format!("Can't write {} to a file record", obj.class_name)));
+        }
+        let decls = self.resolve_class_key(&obj.class_name)
+            .and_then(|key| self.structures.get(&key))
+            .map(|decl| decl.fields.clone())
+            .unwrap_or_default();
+        let mut fields = Vec::new();
+        for decl in decls {
+            let name = decl.name.as_str().to_lowercase();
+            let current = obj.fields.get(&name).cloned().unwrap_or(Value::Nothing);
+            let declared = decl.var_type.as_ref().and_then(Layout::of_type);
+            let layout = match (&current, declared) {
+                (Value::Array(items), Some(element)) => Layout::Array(Box::new(element), items.len()),
+                (Value::Array(_), None) | (_, None) => self.record_layout(&current)?,
+                (_, Some(layout)) => layout,
+            };
+            fields.push((name, layout));
+        }
+        Ok(Layout::Record(fields))
+    }
+
+    /// `Len` of a user-defined type (its record size), and `EOF`, `LOF` and
+    /// `Loc` of a file opened with the `Open` statement.
+    fn record_builtin(&mut self, name: &str, args: &[Value]) -> Result<Option<Value>, RuntimeError> {
+        match (name, args) {
+            ("len", [record @ Value::Object(obj)]) if crate::structures::is_structure(&obj.borrow()) => {
+                Ok(Some(Value::Integer(self.record_layout(record)?.size() as i32)))
+            }
+            ("eof" | "lof" | "loc", [number]) => {
+                let Some(handle) = self.file_handles.get_mut(&number.as_integer()?) else {
+                    return Ok(None);
+                };
+                Ok(Some(match name {
+                    "eof" => Value::Boolean(crate::file_io::at_end(handle)?),
+                    "lof" => Value::Long(crate::file_io::length(handle)? as i64),
+                    _ => Value::Long(crate::file_io::location(handle)? as i64),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The attributes written on the type or member a reflection object
+    /// names. With `inherit`, a type's base classes contribute theirs.
+    fn declared_attributes(&self, obj: &ObjectData, inherit: bool) -> Vec<vybe_parser::Attribute> {
+        let name = obj.fields.get("name").map(|v| v.as_string()).unwrap_or_default();
+        let owner = obj.fields.get(crate::reflection::OWNER_FIELD).map(|v| v.as_string()).unwrap_or_default();
+        match obj.class_name.as_str() {
+            crate::reflection::TYPE_CLASS => {
+                let mut attributes = Vec::new();
+                let mut class = Some(name);
+                while let Some(class_name) = class.take() {
+                    let Some(decl) = self.resolve_class_key(&class_name).and_then(|key| self.classes.get(&key)) else { break };
+                    attributes.extend(decl.attributes.iter().cloned());
+                    if inherit && let Some(vybe_parser::VBType::Custom(parent)) = &decl.inherits {
+                        class = Some(parent.clone());
+                    }
+                }
+                attributes
+            }
+            "PropertyInfo" => self.find_property(&owner, &name).map(|p| p.attributes)
+                .or_else(|| self.find_field_decl(&owner, &name).map(|f| f.attributes))
+                .unwrap_or_default(),
+            "MethodInfo" => match self.find_method(&owner, &name) {
+                Some(MethodDecl::Sub(s)) => s.attributes,
+                Some(MethodDecl::Function(f)) => f.attributes,
+                None => Vec::new(),
+            },
+            _ => self.find_field_decl(&owner, &name).map(|f| f.attributes).unwrap_or_default(),
+        }
+    }
+
+    /// The runtime value of an attribute: an instance of the program's own
+    /// attribute class, or a stand-in for a framework attribute. Named
+    /// arguments set properties after construction.
+    fn attribute_object(&mut self, attr: &vybe_parser::Attribute) -> Result<Value, RuntimeError> {
+        let mut positional = Vec::new();
+        let mut named = Vec::new();
+        for arg in &attr.arguments {
+            match &arg.kind {
+                ExprKind::NamedArgument(name, value) => named.push((name.as_str().to_string(), self.evaluate_expr(value)?)),
+                _ => positional.push(arg.clone()),
+            }
+        }
+        let short = attr.short_name();
+        let class = [format!("{}Attribute", short), short.to_string()]
+            .into_iter()
+            .find(|c| self.resolve_class_key(c).is_some());
+        let Some(class) = class else {
+            let positional = positional.iter().map(|e| self.evaluate_expr(e)).collect::<Result<Vec<_>, _>>()?;
+            return Ok(crate::reflection::framework_attribute(short, positional, named));
+        };
+        let instance = self.evaluate_expr(&ExprKind::New(Identifier::new(&class), positional).into())?;
+        if let Value::Object(obj) = &instance {
+            for (name, value) in named {
+                match self.find_property(&class, &name).filter(|p| p.setter.is_some()) {
+                    Some(prop) => self.set_property(&prop, &[], value, obj.clone())?,
+                    None => {
+                        obj.borrow_mut().fields.insert(name.to_lowercase(), value);
+                    }
+                }
+            }
+        }
+        Ok(instance)
+    }
+
+    /// `GetCustomAttributes` and `IsDefined` on a `Type` or member object,
+    /// and `GetProperty`, `GetMethod` and `GetField` on a `Type`. None for
+    /// any other method.
+    fn reflection_method(&mut self, obj: &Rc<RefCell<ObjectData>>, method: &str, args: &[Value]) -> Result<Option<Value>, RuntimeError> {
+        let is_type = obj.borrow().class_name == crate::reflection::TYPE_CLASS;
+        match method {
+            "getcustomattributes" | "isdefined" => {
+                // (attributeType, inherit), (inherit) or ()
+                let filter = match args.first() {
+                    Some(Value::Object(t)) if t.borrow().class_name == crate::reflection::TYPE_CLASS => {
+                        Some(t.borrow().fields.get("name").map(|v| v.as_string()).unwrap_or_default())
+                    }
+                    _ => None,
+                };
+                let inherit = args.iter().any(|a| matches!(a, Value::Boolean(true)));
+                let attributes = self.declared_attributes(&obj.borrow(), inherit);
+                let mut found = Vec::new();
+                for attr in &attributes {
+                    let value = self.attribute_object(attr)?;
+                    let class = match &value {
+                        Value::Object(o) => o.borrow().class_name.clone(),
+                        _ => continue,
+                    };
+                    // Instances of a subclass of the requested type match too
+                    let matches = filter.as_ref().is_none_or(|type_name| {
+                        crate::reflection::attribute_key(&class) == crate::reflection::attribute_key(type_name)
+                            || self.is_type_or_base(&class, type_name)
+                    });
+                    if matches {
+                        found.push(value);
+                    }
+                }
+                Ok(Some(if method == "isdefined" { Value::Boolean(!found.is_empty()) } else { Value::Array(found) }))
+            }
+            "getproperty" | "getmethod" | "getfield" if is_type => {
+                let owner = obj.borrow().fields.get("name").map(|v| v.as_string()).unwrap_or_default();
+                let member = args.first().map(|v| v.as_string()).unwrap_or_default();
+                let (class, declared) = match method {
+                    "getproperty" => (
+                        crate::reflection::MEMBER_CLASSES[0],
+                        self.find_property(&owner, &member).map(|p| p.name)
+                            .or_else(|| self.find_field_decl(&owner, &member).map(|f| f.name)),
+                    ),
+                    "getmethod" => (
+                        crate::reflection::MEMBER_CLASSES[1],
+                        self.find_method(&owner, &member).map(|m| match m {
+                            MethodDecl::Sub(s) => s.name,
+                            MethodDecl::Function(f) => f.name,
+                        }),
+                    ),
+                    _ => (crate::reflection::MEMBER_CLASSES[2], self.find_field_decl(&owner, &member).map(|f| f.name)),
+                };
+                Ok(Some(match declared {
+                    Some(name) => crate::reflection::member_object(class, &owner, name.as_str()),
+                    None => Value::Nothing,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The `Default` property of a class or its bases.
+    fn find_default_property(&self, class_name: &str) -> Option<vybe_parser::ast::decl::PropertyDecl> {
+        let key = self.resolve_class_key(class_name)?;
+        let cls = self.classes.get(&key)?;
+        if let Some(prop) = cls.properties.iter().find(|p| p.is_default) {
+            return Some(prop.clone());
+        }
+        match &cls.inherits {
+            Some(vybe_parser::VBType::Custom(parent)) => self.find_default_property(parent),
+            _ => None,
+        }
+    }
+
+    /// The `Default` property of the instance `value`, if it has one.
+    fn default_property_of(&self, value: &Value) -> Option<(vybe_parser::ast::decl::PropertyDecl, Rc<RefCell<ObjectData>>)> {
+        match value {
+            Value::Object(obj) => {
+                let class_name = obj.borrow().class_name.clone();
+                self.find_default_property(&class_name).map(|prop| (prop, obj.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The items of an instance enumerated through its `Default` property:
+    /// `Item(0)` to `Item(Count - 1)`. None unless the class has a one-index
+    /// Default property and a `Count` property or field.
+    fn default_property_items(&mut self, value: &Value) -> Result<Option<Vec<Value>>, RuntimeError> {
+        let Some((prop, obj)) = self.default_property_of(value).filter(|(p, _)| p.parameters.len() == 1) else {
+            return Ok(None);
+        };
+        let class_name = obj.borrow().class_name.clone();
+        let count = match self.find_property(&class_name, "Count") {
+            Some(count_prop) if count_prop.parameters.is_empty() => self.get_property(&count_prop, &[], obj.clone())?,
+            _ => match obj.borrow().fields.get("count") {
+                Some(count) => count.clone(),
+                None => return Ok(None),
+            },
+        };
+        let mut items = Vec::new();
+        for index in 0..count.as_integer()?.max(0) {
+            items.push(self.get_property(&prop, &[Value::Integer(index)], obj.clone())?);
+        }
+        Ok(Some(items))
+    }
+
+    /// Runs the `Get` accessor of a (possibly parameterized) property.
+    fn get_property(&mut self, prop: &vybe_parser::ast::decl::PropertyDecl, args: &[Value], obj: Rc<RefCell<ObjectData>>) -> Result<Value, RuntimeError> {
+        let Some(body) = &prop.getter else {
+            return Err(RuntimeError::Custom(format!("Property '{}' is 'WriteOnly'.", prop.name.as_str())));
+        };
+        let func = FunctionDecl {
+            visibility: prop.visibility,
+            name: prop.name.clone(),
+            type_parameters: Vec::new(),
+            parameters: prop.parameters.clone(),
+            return_type: prop.return_type.clone(),
+            body: body.clone(),
+            is_async: false,
+            is_iterator: false,
+            is_extension: false,
+            is_overridable: false,
+            is_overrides: false,
+            is_must_override: false,
+            is_shared: false,
+            is_not_overridable: false,
+            handles: None,
+            attributes: Vec::new(),
+            span: prop.span,
+            source_file: prop.source_file.clone(),
+        };
+        self.call_user_function(&func, args, Some(obj))
+    }
+
+    /// Runs the `Set` accessor of a (possibly parameterized) property: the
+    /// index arguments come first, then the value.
+    fn set_property(&mut self, prop: &vybe_parser::ast::decl::PropertyDecl, args: &[Value], value: Value, obj: Rc<RefCell<ObjectData>>) -> Result<(), RuntimeError> {
+        let Some((param, body)) = &prop.setter else {
+            return Err(RuntimeError::Custom(format!("Property '{}' is 'ReadOnly'.", prop.name.as_str())));
+        };
+        let mut parameters = prop.parameters.clone();
+        parameters.push(param.clone());
+        let sub = SubDecl {
+            visibility: prop.visibility,
+            name: prop.name.clone(),
+            type_parameters: Vec::new(),
+            parameters,
+            body: body.clone(),
+            handles: None,
+            is_async: false,
+            is_extension: false,
+            is_overridable: false,
+            is_overrides: false,
+            is_must_override: false,
+            is_shared: false,
+            is_not_overridable: false,
+            attributes: Vec::new(),
+            span: prop.span,
+            source_file: prop.source_file.clone(),
+        };
+        let mut values = args.to_vec();
+        values.push(value);
+        match self.call_user_sub(&sub, &values, Some(obj)) {
+            Ok(_) | Err(RuntimeError::Exit(ExitType::Sub)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the parent class name from a class's `Inherits` clause.
+    pub fn get_parent_class_name(&self, class_name: &str) -> Option<String> {
+        let key = self.resolve_class_key(class_name)?;
+        let cls = self.classes.get(&key)?;
+        match &cls.inherits {
+            Some(vybe_parser::VBType::Custom(n)) => Some(n.clone()),
+            _ => None,
+        }
+    }
+
+    /// Find a method starting from the **parent** of the given class.
+    /// Used for `MyBase.Method()` — skips the derived class entirely.
+    pub fn find_method_in_base(&self, class_name: &str, method_name: &str) -> Option<vybe_parser::ast::decl::MethodDecl> {
+        if let Some(parent) = self.get_parent_class_name(class_name) {
+            self.find_method(&parent, method_name)
+        } else {
+            None
+        }
+    }
+
+    /// Walk the inheritance chain to check if `class_name` is or inherits from `target`.
+    /// Used for `TypeOf x Is BaseClass`.
+    pub fn is_type_or_base(&self, class_name: &str, target: &str) -> bool {
+        // Type arguments are compared by is_instance_of; here Box(Of T) is Box
+        let class_name = crate::generics::split_type_arguments(class_name).map_or(class_name, |(base, _)| base);
+        let target = crate::generics::split_type_arguments(target).map_or(target, |(base, _)| base);
+        if class_name.eq_ignore_ascii_case(target) {
+            return true;
+        }
+        // Also match against unqualified target (e.g. "Form" matches "System.Windows.Forms.Form")
+        let class_lower = class_name.to_lowercase();
+        let target_lower = target.to_lowercase();
+        if class_lower.ends_with(&format!(".{}", target_lower)) || target_lower.ends_with(&format!(".{}", class_lower)) {
+            return true;
+        }
+        // Walk up the hierarchy
+        if let Some(parent) = self.get_parent_class_name(class_name) {
+            return self.is_type_or_base(&parent, target);
+        }
+        false
+    }
+
+
+    /// `target = value` once `value` is evaluated to `val`: a local
+    /// variable, a field of `Me`, or else a global.
+    fn assign_variable(&mut self, target: &Identifier, value: &Expression, val: Value) -> Result<(), RuntimeError> {
+        let symbol = Symbol::intern(target.as_str());
+        // 1. Check if it's a local variable (exists in any scope except the global one)
+        if let Some(slot) = self.env.resolve_local(symbol) {
+            return self.assign_local(slot, symbol, target, value, val);
+        }
+
+        let target_str = target.as_str();
+        self.check_assignment(target_str, value, &val)?;
+        let val = self.convert_to_variable(symbol, val)?;
+
+        // 2. Check if it's a field in the current object
+        if let Some(obj_rc) = &self.current_object {
+            // Check if field is WithEvents before modification
+            let (is_withevents, old_val) = {
+                let obj = obj_rc.borrow();
+                let class_name = obj.class_name.clone();
+                let old = obj.fields.get(&*symbol.name()).cloned();
+                
+                let is_we = if let Some(cls) = self.classes.get(&*Symbol::intern(&class_name).name()) {
+                     cls.fields.iter().any(|f| f.name.as_str().eq_ignore_ascii_case(target_str) && f.with_events)
+                } else { false };
+                (is_we, old)
+            };
+
+            {
+                let mut obj = obj_rc.borrow_mut();
+                // Create or update instance field
+                obj.set_field(&symbol.name(), val.clone());
+            }
+            
+            if is_withevents {
+                let host = obj_rc.clone();
+                 if let Some(old) = old_val {
+                     self.unwire_events(&old, target_str, host.clone());
+                     self.handle_user_events(&old, target_str, &host, false)?;
+                 }
+                 self.wire_events(&val, target_str, host.clone());
+                 self.handle_user_events(&val, target_str, &host, true)?;
+            }
+
+            return Ok(());
+        }
+
+        // 3. Fallback: set in environment (will check global scope or define as global)
+        self.env.set(target_str, val)?;
+        Ok(())
+    }
+
+    /// `target = val` for a local variable found in `slot`.
+    fn assign_local(&mut self, slot: Slot, symbol: Symbol, target: &Identifier, value: &Expression, val: Value) -> Result<(), RuntimeError> {
+        self.check_assignment(target.as_str(), value, &val)?;
+        let val = self.convert_to_variable(symbol, val)?;
+        self.store_local(slot, symbol, target, val)
+    }
+
+    /// Replace the value of the local in `slot`, unless it's a constant.
+    fn store_local(&mut self, slot: Slot, symbol: Symbol, target: &Identifier, val: Value) -> Result<(), RuntimeError> {
+        if self.env.is_const(symbol) {
+            return Err(RuntimeError::Custom(format!("Cannot assign to constant '{}'", target.as_str())));
+        }
+        self.env.store(slot, val);
+        Ok(())
+    }
+
+    /// Store the result of `target op= value` in a plain variable.
+    fn store_compound(&mut self, target: &Identifier, new_val: Value) -> Result<(), RuntimeError> {
+        let symbol = Symbol::intern(target.as_str());
+        self.store_compound_symbol(target, symbol, self.env.resolve_local(symbol), new_val)
+    }
+
+    /// [`Self::store_compound`] once `target` is interned as `symbol`, with
+    /// its slot if it's a local.
+    fn store_compound_symbol(&mut self, target: &Identifier, symbol: Symbol, slot: Option<Slot>, new_val: Value) -> Result<(), RuntimeError> {
+        let declared = self.env.declared_type_of(symbol);
+        if let Some(ty) = declared && options::strict(&self.active_options) && options::is_narrowing(ty, &new_val, false) {
+            return Err(options::narrowing_error(options::value_type_name(&new_val), ty));
+        }
+        let new_val = coerce_to_declared(declared, new_val)?;
+        if let Some(slot) = slot {
+            return self.store_local(slot, symbol, target, new_val);
+        }
+        // A field of the current object, as in `X += dx` inside a method
+        let field = symbol.name();
+        match &self.current_object {
+            Some(obj) if obj.borrow().fields.contains_key(&*field) => {
+                obj.borrow_mut().set_field(&field, new_val);
+            }
+            _ => self.env.set(target.as_str(), new_val)?,
+        }
+        Ok(())
+    }
+
+    pub fn execute(&mut self, stmt: &Statement) -> Result<(), RuntimeError> {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || self.execute_impl(stmt))
+    }
+
+    fn execute_impl(&mut self, stmt: &Statement) -> Result<(), RuntimeError> {
+        match stmt {
+            Statement::Dim(decls) => {
+                let strict = options::strict(&self.active_options);
+                for decl in decls {
+                    let inferred = decl.initializer.is_some() && decl.array_bounds.is_none()
+                        && options::infer(&self.active_options);
+                    if strict && decl.var_type.is_none() && !inferred {
+                        return Err(options::as_clause_error());
+                    }
+                    if let Some(bounds) = &decl.array_bounds {
+                        if bounds.len() == 1 {
+                            // 1-D array: Dim arr(10) As Integer
+                            let size = (self.evaluate_expr(&bounds[0])?.as_integer()? + 1) as usize;
+                            self.budget.check_len(size)?;
+                            let default_val = self.default_value(&decl.var_type);
+                            let arr = crate::structures::copy_elements(Value::Array(vec![default_val; size]));
+                            self.env.define(decl.name.as_str(), arr);
+                        } else {
+                            // Multi-dimensional: Dim arr(3, 4) → nested arrays
+                            let dims: Vec<usize> = bounds.iter()
+                                .map(|b| self.evaluate_expr(b).and_then(|v| v.as_integer()).map(|i| (i + 1) as usize))
+                                .collect::<Result<Vec<_>, _>>()?;
+                            self.budget.check_dims(&dims)?;
+                            let default_val = self.default_value(&decl.var_type);
+                            let arr = crate::structures::copy_elements(create_multi_dim_array(&dims, &default_val));
+                            self.env.define(decl.name.as_str(), arr);
+                        }
+                    } else if let Some(init) = &decl.initializer {
+                        // Variable with initializer: Dim x As Integer = 10 or Dim arr() = {1,2,3}
+                        let val = self.evaluate_expr(init)?;
+                        if let (true, Some(ty)) = (strict, &decl.var_type) {
+                            self.check_strict_conversion(ty, init, &val)?;
+                        }
+                        let val = self.convert_to_declared(decl.var_type.as_ref(), val)?;
+                        self.env.define(decl.name.as_str(), val);
+                    } else {
+                        // Regular variable: Dim x As Integer
+                        let val = self.default_value(&decl.var_type);
+                        self.env.define(decl.name.as_str(), val);
+                    }
+                    // Decimal and integral variables keep their type through
+                    // assignments, so `total = total * 1.1` stays exact and
+                    // `b += 1` on a Byte of 255 overflows; tuple variables
+                    // keep their element names and types, and values stored
+                    // in a class with CType operators go through them
+                    if strict || decl.var_type.as_ref().is_some_and(|ty| is_exact_numeric_type(ty) || is_tuple_type(ty) || self.declares_conversions(ty)) {
+                        let ty = match &decl.var_type {
+                            Some(ty) => ty.clone(),
+                            None => options::inferred_type(&self.env.get_or_nothing(decl.name.as_str())),
+                        };
+                        self.env.declare_type(decl.name.as_str(), ty);
+                    }
+                }
+                Ok(())
+            }
+            Statement::Const(decl) => {
+                // Evaluate the constant expression
+                let val = self.evaluate_expr(&decl.value)?;
+                // Store as a constant (read-only)
+                self.env.define_const(decl.name.as_str(), val);
+                Ok(())
+            }
+            Statement::Assignment { target, value } => {
+                let val = self.evaluate_expr(value)?;
+                self.assign_variable(target, value, val)
+            }
+
+            Statement::SetAssignment { target, value } => {
+                let val = self.evaluate_expr(value)?;
+                self.check_assignment(target.as_str(), value, &val)?;
+                self.env.set(target.as_str(), val)?;
+                Ok(())
+            }
+
+            Statement::SyncLock { lock_object, body } => {
+                let _lock = self.evaluate_expr(lock_object)?;
+                // Verify reference type if strict, but for now just execute body
+                // In a single-threaded interpreter, the lock is implicit
+                self.execute_block(body)?;
+                Ok(())
+            }
+
+            Statement::MemberAssignment { object, member, value } => {
+                self.check_late_binding(object, member.as_str())?;
+                let val = crate::structures::copy_value(self.evaluate_expr(value)?);
+                let obj_val = self.evaluate_expr(object)?;
+                let prop_name = member.as_str().to_string();
+
+                match obj_val {
+                    Value::Object(obj_ref) => {
+                        let val = match self.fixed_length_field(&obj_ref.borrow(), &prop_name) {
+                            Some(len) => Value::String(crate::records::fit(&val.as_string(), len)),
+                            None => val,
+                        };
+                        let class_key = Symbol::intern(&obj_ref.borrow().class_name).name();
+                        if let Some(class_decl) = self.classes.get(&*class_key).cloned() {
+                            for prop in &class_decl.properties {
+                                if prop.name.as_str().eq_ignore_ascii_case(&prop_name) {
+                                    if let Some((param, body)) = &prop.setter {
+                                        let sub = SubDecl {
                                             visibility: prop.visibility,
                                             name: prop.name.clone(),
+                                            type_parameters: Vec::new(),
                                             parameters: vec![param.clone()],
                                             body: body.clone(),
                                             handles: None,
@@ -2258,6 +3551,9 @@ impl Interpreter {
                                             is_must_override: false,
                                             is_shared: false,
                                             is_not_overridable: false,
+                                            attributes: Vec::new(),
+                                            span: prop.span,
+                                            source_file: prop.source_file.clone(),
                                         };
                                         return match self.call_user_sub(&sub, &[val], Some(obj_ref.clone())) {
                                             Ok(_) => Ok(()),
@@ -2269,8 +3565,14 @@ impl Interpreter {
                             }
                         }
                         
+                        if crate::tuples::is_tuple(&obj_ref.borrow())
+                            && crate::tuples::set_element(&mut obj_ref.borrow_mut(), &prop_name, val.clone()) {
+                            return Ok(());
+                        }
+
                         // Fallback: Set field directly
-                        let member_lower = prop_name.to_lowercase();
+                        let member_name = Symbol::intern(&prop_name).name();
+                        let member_lower: &str = &member_name;
                         if val == Value::Nothing {
                         }
 
@@ -2294,7 +3596,7 @@ impl Interpreter {
                                     .unwrap_or_default();
                                 let bs_val = Value::Object(obj_ref.clone());
                                 for ctrl_name in bound {
-                                    let (columns, rows) = self.get_datasource_table_data(&bs_val);
+                                    let (columns, rows) = self.get_datasource_table_data(&bs_val)?;
                                     self.side_effects.push_back(crate::RuntimeSideEffect::DataSourceChanged {
                                         control_name: ctrl_name,
                                         columns,
@@ -2306,13 +3608,13 @@ impl Interpreter {
                             }
                             // Store other BindingSource properties and trigger appropriate side effects
                             if member_lower == "datamember" || member_lower == "filter" || member_lower == "sort" || member_lower == "position" {
-                                obj_ref.borrow_mut().fields.insert(member_lower.clone(), val.clone());
+                                obj_ref.borrow_mut().set_field(member_lower, val.clone());
 
                                 if member_lower == "position" {
                                     // Position changed → refresh all bound controls
                                     let ds = obj_ref.borrow().fields.get("__datasource").cloned().unwrap_or(Value::Nothing);
                                     let new_pos = val.as_integer().unwrap_or(0);
-                                    let count = self.binding_source_row_count_filtered(&Value::Object(obj_ref.clone()));
+                                    let count = self.binding_source_row_count_filtered(&Value::Object(obj_ref.clone()))?;
                                     let bs_name = obj_ref.borrow().fields.get("name")
                                         .map(|v| v.as_string()).unwrap_or_default();
                                     self.side_effects.push_back(crate::RuntimeSideEffect::BindingPositionChanged {
@@ -2320,7 +3622,7 @@ impl Interpreter {
                                         position: new_pos,
                                         count,
                                     });
-                                    self.refresh_bindings_filtered(&obj_ref, &ds, new_pos);
+                                    self.refresh_bindings_filtered(&obj_ref, &ds, new_pos)?;
                                 } else if member_lower == "filter" || member_lower == "sort" {
                                     // Filter/Sort changed → re-emit DataSourceChanged for all bound controls + refresh bindings
                                     let bound: Vec<String> = obj_ref.borrow()
@@ -2331,7 +3633,7 @@ impl Interpreter {
                                         .unwrap_or_default();
                                     let bs_val = Value::Object(obj_ref.clone());
                                     for ctrl_name in bound {
-                                        let (columns, rows) = self.get_datasource_table_data_filtered(&bs_val);
+                                        let (columns, rows) = self.get_datasource_table_data_filtered(&bs_val)?;
                                         self.side_effects.push_back(crate::RuntimeSideEffect::DataSourceChanged {
                                             control_name: ctrl_name,
                                             columns,
@@ -2341,7 +3643,7 @@ impl Interpreter {
                                     // Reset position to 0 and refresh bindings
                                     obj_ref.borrow_mut().fields.insert("position".to_string(), Value::Integer(0));
                                     let ds = obj_ref.borrow().fields.get("__datasource").cloned().unwrap_or(Value::Nothing);
-                                    let count = self.binding_source_row_count_filtered(&Value::Object(obj_ref.clone()));
+                                    let count = self.binding_source_row_count_filtered(&Value::Object(obj_ref.clone()))?;
                                     let bs_name = obj_ref.borrow().fields.get("name")
                                         .map(|v| v.as_string()).unwrap_or_default();
                                     self.side_effects.push_back(crate::RuntimeSideEffect::BindingPositionChanged {
@@ -2349,7 +3651,7 @@ impl Interpreter {
                                         position: 0,
                                         count,
                                     });
-                                    self.refresh_bindings_filtered(&obj_ref, &ds, 0);
+                                    self.refresh_bindings_filtered(&obj_ref, &ds, 0)?;
                                 }
                                 return Ok(());
                             }
@@ -2366,8 +3668,8 @@ impl Interpreter {
                             // when Me.ctrl.Name hasn't been set yet (designer sets DataSource before Name)
                             // e.g. `Me.dgv1.DataSource = Me.bs1` → infer "dgv1" from MemberAccess(Me, "dgv1")
                             if obj_name.is_none() {
-                                if let Expression::MemberAccess(inner, ctrl_member) = object {
-                                    if matches!(inner.as_ref(), Expression::Me) {
+                                if let ExprKind::MemberAccess(inner, ctrl_member) = &object.kind {
+                                    if matches!(&inner.kind, ExprKind::Me) {
                                         let inferred = ctrl_member.as_str().to_string();
                                         // Store on the object so later assignments can find the name
                                         obj_ref.borrow_mut().fields.insert("name".to_string(), Value::String(inferred.clone()));
@@ -2395,7 +3697,7 @@ impl Interpreter {
                                 }
                             }
                             if let Some(oname) = &obj_name {
-                                let (columns, rows) = self.get_datasource_table_data(&val);
+                                let (columns, rows) = self.get_datasource_table_data(&val)?;
                                 self.side_effects.push_back(crate::RuntimeSideEffect::DataSourceChanged {
                                     control_name: oname.clone(),
                                     columns,
@@ -2444,21 +3746,23 @@ impl Interpreter {
                         let (is_withevents, old_val) = {
                              let obj = obj_ref.borrow();
                              let class_name = obj.class_name.clone();
-                             let old = obj.fields.get(&member_lower).cloned();
-                             let is_we = if let Some(cls) = self.classes.get(&class_name.to_lowercase()) {
+                             let old = obj.fields.get(member_lower).cloned();
+                             let is_we = if let Some(cls) = self.classes.get(&*Symbol::intern(&class_name).name()) {
                                   cls.fields.iter().any(|f| f.name.as_str().eq_ignore_ascii_case(&prop_name) && f.with_events)
                              } else { false };
                              (is_we, old)
                         };
 
-                        obj_ref.borrow_mut().fields.insert(member_lower.clone(), store_val.clone());
+                        obj_ref.borrow_mut().set_field(member_lower, store_val.clone());
                         
                         if is_withevents {
                             let host = obj_ref.clone();
                              if let Some(old) = old_val {
                                  self.unwire_events(&old, &prop_name, host.clone());
+                                 self.handle_user_events(&old, &prop_name, &host, false)?;
                              }
-                             self.wire_events(&store_val, &prop_name, host);
+                             self.wire_events(&store_val, &prop_name, host.clone());
+                             self.handle_user_events(&store_val, &prop_name, &host, true)?;
                         }
 
                         // StringBuilder.Length setter: truncate or pad the buffer
@@ -2481,7 +3785,7 @@ impl Interpreter {
                         let is_control = obj_ref.borrow().fields.get("__is_control")
                             .map(|v| v.as_bool().unwrap_or(false)).unwrap_or(false);
                         if is_control {
-                            match member_lower.as_str() {
+                            match member_lower {
                                 "location" => {
                                     // Extract X/Y from Point object and set Left/Top
                                     if let Value::Object(pt) = &val {
@@ -2544,7 +3848,7 @@ impl Interpreter {
                             let ds_key = format!("{}.__datasource", obj_name);
                             self.env.define_global(&ds_key, val.clone());
                             // Resolve table data and push side-effect
-                            let (columns, rows) = self.get_datasource_table_data(&val);
+                            let (columns, rows) = self.get_datasource_table_data(&val)?;
                             self.side_effects.push_back(crate::RuntimeSideEffect::DataSourceChanged {
                                 control_name: obj_name,
                                 columns,
@@ -2586,17 +3890,72 @@ impl Interpreter {
                 }
             }
 
+            Statement::IndexedAssignment { object, member, indices, value } => {
+                let val = crate::structures::copy_value(self.evaluate_expr(value)?);
+                let args = indices.iter().map(|e| self.evaluate_expr(e)).collect::<Result<Vec<_>, _>>()?;
+                let Value::Object(obj_ref) = self.evaluate_expr(object)? else {
+                    return Err(RuntimeError::Custom(format!("'{}' cannot be indexed here.", member.as_str())));
+                };
+                let class_name = obj_ref.borrow().class_name.clone();
+                if let Some(prop) = self.find_property(&class_name, member.as_str()).filter(|p| !p.parameters.is_empty()) {
+                    return self.set_property(&prop, &args, val, obj_ref);
+                }
+                let member_name = Symbol::intern(member.as_str()).name();
+                let field = obj_ref.borrow().fields.get(&*member_name).cloned();
+                match field {
+                    Some(Value::Dictionary(dict)) if args.len() == 1 => {
+                        self.set_dictionary_item(&dict, args[0].clone(), val)
+                    }
+                    Some(Value::Array(_) | Value::Collection(_)) => {
+                        let idx_vals = args.iter().map(|a| a.as_integer().map(|i| i as usize)).collect::<Result<Vec<_>, _>>()?;
+                        let mut obj = obj_ref.borrow_mut();
+                        let Some(arr_val) = obj.fields.get_mut(&*member_name) else { return Ok(()) };
+                        if let [index] = idx_vals[..] {
+                            arr_val.set_array_element(index, val)
+                        } else {
+                            set_multi_dim_element(arr_val, &idx_vals, val)
+                        }
+                    }
+                    Some(other) => match self.default_property_of(&other) {
+                        Some((prop, inner)) => self.set_property(&prop, &args, val, inner),
+                        None => Err(RuntimeError::Custom(format!("'{}' cannot be indexed.", member.as_str()))),
+                    },
+                    None => Err(RuntimeError::Custom(format!("'{}' is not a member of '{}'.", member.as_str(), class_name))),
+                }
+            }
+
             Statement::ArrayAssignment { array, indices, value } => {
-                let val = self.evaluate_expr(value)?;
-                let array_lower = array.as_str().to_lowercase();
+                let val = crate::structures::copy_value(self.evaluate_expr(value)?);
+                let array_name = Symbol::intern(array.as_str()).name();
+                let field = self.current_object.as_ref().and_then(|o| o.borrow().fields.get(&*array_name).cloned());
+                let target = field.or_else(|| self.env.get(array.as_str()).ok());
+
+                // Default property: table("key") = value
+                if let Some((prop, obj)) = target.as_ref().and_then(|t| self.default_property_of(t)) {
+                    let args = indices.iter().map(|e| self.evaluate_expr(e)).collect::<Result<Vec<_>, _>>()?;
+                    return self.set_property(&prop, &args, val, obj);
+                }
+                // Parameterized property of the current instance: Item(i) = value
+                if target.is_none() && let Some(obj_rc) = self.current_object.clone() {
+                    let class_name = obj_rc.borrow().class_name.clone();
+                    if let Some(prop) = self.find_property(&class_name, array.as_str()).filter(|p| !p.parameters.is_empty()) {
+                        let args = indices.iter().map(|e| self.evaluate_expr(e)).collect::<Result<Vec<_>, _>>()?;
+                        return self.set_property(&prop, &args, val, obj_rc);
+                    }
+                }
 
                 if indices.len() == 1 {
+                    let key = self.evaluate_expr(&indices[0])?;
+                    // Dictionary indexer: d(key) = value
+                    if let Some(Value::Dictionary(dict)) = target {
+                        return self.set_dictionary_item(&dict, key, val);
+                    }
                     // 1-D array assignment
-                    let index = self.evaluate_expr(&indices[0])?.as_integer()? as usize;
+                    let index = key.as_integer()? as usize;
                     // Try current object fields first
                     if let Some(obj_rc) = &self.current_object {
                         let mut obj = obj_rc.borrow_mut();
-                        if let Some(arr_val) = obj.fields.get_mut(&array_lower) {
+                        if let Some(arr_val) = obj.fields.get_mut(&*array_name) {
                             arr_val.set_array_element(index, val.clone())?;
                             return Ok(());
                         }
@@ -2613,7 +3972,7 @@ impl Interpreter {
                     // Try current object fields first
                     if let Some(obj_rc) = &self.current_object {
                         let mut obj = obj_rc.borrow_mut();
-                        if let Some(arr_val) = obj.fields.get_mut(&array_lower) {
+                        if let Some(arr_val) = obj.fields.get_mut(&*array_name) {
                             set_multi_dim_element(arr_val, &idx_vals, val)?;
                             return Ok(());
                         }
@@ -2626,8 +3985,27 @@ impl Interpreter {
                 Ok(())
             }
 
+            Statement::XmlAttributeAssignment { element, name, value } => {
+                let element = self.evaluate_expr(element)?;
+                let value = self.evaluate_expr(value)?;
+                crate::builtins::xml::set_attribute_axis(&element, &xml_name(name), value)
+            }
+
             Statement::ReDim { preserve, array, bounds } => {
-                let new_size = (self.evaluate_expr(&bounds[0])?.as_integer()? + 1) as usize;
+                let dims: Vec<usize> = bounds.iter()
+                    .map(|b| self.evaluate_expr(b).and_then(|v| v.as_integer()).map(|i| (i + 1) as usize))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.budget.check_dims(&dims)?;
+                let new_size = dims[0];
+                // An array of structures is filled with zeroed instances
+                let structure_name = match self.env.get(array.as_str()) {
+                    Ok(Value::Array(vec)) => vec.iter().find_map(|v| match v {
+                        Value::Object(obj) if crate::structures::is_structure(&obj.borrow()) => Some(obj.borrow().class_name.clone()),
+                        _ => None,
+                    }),
+                    _ => None,
+                };
+                let structure_default = structure_name.map(|name| self.structure_instance(&name));
 
                 if *preserve {
                     // Get existing array and resize preserving data
@@ -2645,7 +4023,8 @@ impl Interpreter {
                                 _ => Value::Nothing,
                             })
                             .unwrap_or(Value::Integer(0));
-                        vec.resize(new_size, default_val);
+                        vec.resize(new_size, structure_default.clone().unwrap_or(default_val));
+                        let arr = if structure_default.is_some() { crate::structures::copy_elements(arr) } else { arr };
                         self.env.set(array.as_str(), arr)?;
                     } else {
                         return Err(RuntimeError::Custom(format!("{} is not an array", array.as_str())));
@@ -2666,58 +4045,22 @@ impl Interpreter {
                             _ => Value::Integer(0),
                         })
                         .unwrap_or(Value::Integer(0));
-                    let new_arr = Value::Array(vec![default_val; new_size]);
+                    let new_arr = match structure_default {
+                        Some(instance) => crate::structures::copy_elements(Value::Array(vec![instance; new_size])),
+                        None => Value::Array(vec![default_val; new_size]),
+                    };
                     self.env.set(array.as_str(), new_arr)?;
                 }
                 Ok(())
             }
 
             Statement::Select { test_expr, cases, else_block } => {
-                let test_val = self.evaluate_expr(test_expr)?;
-                let mut matched = false;
-
-                for case in cases {
-                    // Check if any condition matches
-                    for condition in &case.conditions {
-                        let matches = match condition {
-                            CaseCondition::Value(expr) => {
-                                let case_val = self.evaluate_expr(expr)?;
-                                values_equal(&test_val, &case_val)
-                            }
-                            CaseCondition::Range { from, to } => {
-                                let from_val = self.evaluate_expr(from)?;
-                                let to_val = self.evaluate_expr(to)?;
-                                value_in_range(&test_val, &from_val, &to_val)
-                            }
-                            CaseCondition::Comparison { op, expr } => {
-                                let comp_val = self.evaluate_expr(expr)?;
-                                compare_values(&test_val, op, &comp_val)?
-                            }
-                        };
-
-                        if matches {
-                            matched = true;
-                            break;
-                        }
-                    }
-
-                    if matched {
-                        // Execute case body
-                        match self.execute_block(&case.body) {
-                            Ok(_) => return Ok(()),
-                            Err(RuntimeError::Exit(ExitType::Select)) => return Ok(()),
-                            Err(e) => return Err(e),
-                        }
-                    }
-                }
-
-                // If no case matched, execute else block
-                if !matched {
-                    if let Some(else_stmts) = else_block {
-                        self.execute_block(else_stmts)?;
+                if let Some(body) = self.select_case_body(test_expr, cases, else_block)? {
+                    match self.execute_block(body) {
+                        Ok(_) | Err(RuntimeError::Exit(ExitType::Select)) => {}
+                        Err(e) => return Err(e),
                     }
                 }
-
                 Ok(())
             }
 
@@ -2727,44 +4070,21 @@ impl Interpreter {
                 elseif_branches,
                 else_branch,
             } => {
-                let cond_val = self.evaluate_expr(condition)?;
-
-                if cond_val.is_truthy() {
-                    for stmt in then_branch {
-                        self.execute(stmt)?;
-                    }
-                } else {
-                    let mut executed = false;
-                    for (elseif_cond, elseif_stmts) in elseif_branches {
-                        let elseif_val = self.evaluate_expr(elseif_cond)?;
-                        if elseif_val.is_truthy() {
-                            for stmt in elseif_stmts {
-                                self.execute(stmt)?;
-                            }
-                            executed = true;
-                            break;
-                        }
-                    }
-
-                    if !executed {
-                        if let Some(else_stmts) = else_branch {
-                            for stmt in else_stmts {
-                                self.execute(stmt)?;
-                            }
-                        }
-                    }
+                if let Some(branch) = self.if_branch(condition, then_branch, elseif_branches, else_branch)? {
+                    self.execute_block(branch)?;
                 }
-
                 Ok(())
             }
 
             Statement::For {
                 variable,
+                var_type,
                 start,
                 end,
                 step,
                 body,
             } => {
+                self.check_loop_variable(variable, var_type)?;
                 let start_val = self.evaluate_expr(start)?.as_integer()?;
                 let end_val = self.evaluate_expr(end)?.as_integer()?;
                 let step_val = if let Some(s) = step {
@@ -2774,6 +4094,9 @@ impl Interpreter {
                 };
 
                 let mut i = start_val;
+                // The counter's slot, found on the first pass
+                let symbol = Symbol::intern(variable.as_str());
+                let mut slot = None;
 
                 loop {
                     // Check loop condition
@@ -2781,11 +4104,19 @@ impl Interpreter {
                         break;
                     }
 
-                    self.env.set(variable.as_str(), Value::Integer(i))?;
+                    // `For i As Short` counts in its declared type
+                    let value = coerce_to_declared(var_type.as_ref(), Value::Integer(i))?;
+                    match slot {
+                        Some((epoch, s)) if epoch == self.env.epoch() => self.store_local(s, symbol, variable, value)?,
+                        _ => {
+                            self.env.set(variable.as_str(), value)?;
+                            slot = self.env.resolve(symbol).map(|s| (self.env.epoch(), s));
+                        }
+                    }
 
                     // Execute body
-                    for stmt in body {
-                        match self.execute(stmt) {
+                    for (stmt, span) in body.iter_spanned() {
+                        match self.execute_at(stmt, span) {
                             Err(RuntimeError::Exit(ExitType::For)) => return Ok(()),
                             Err(RuntimeError::Continue(vybe_parser::ast::stmt::ContinueType::For)) => break,
                             Err(e) => return Err(e),
@@ -2794,6 +4125,7 @@ impl Interpreter {
                     }
 
                     i += step_val;
+                    self.budget.step()?;
                 }
 
                 Ok(())
@@ -2802,12 +4134,12 @@ impl Interpreter {
             Statement::While { condition, body } => {
                 loop {
                     let cond_val = self.evaluate_expr(condition)?;
-                    if !cond_val.is_truthy() {
+                    if !self.condition_is_true(&cond_val)? {
                         break;
                     }
 
-                    for stmt in body {
-                        match self.execute(stmt) {
+                    for (stmt, span) in body.iter_spanned() {
+                        match self.execute_at(stmt, span) {
                             Err(RuntimeError::Exit(ExitType::Do) | RuntimeError::Exit(ExitType::While)) => return Ok(()),
                             // Parser Check: Exit While -> Statement::ExitDo (if mapped) or ExitWhile?
                             // Parser map: "while" -> ???
@@ -2822,6 +4154,7 @@ impl Interpreter {
                             Ok(_) => {}
                         }
                     }
+                    self.budget.step()?;
                 }
 
                 Ok(())
@@ -2840,12 +4173,12 @@ impl Interpreter {
                         let val = self.evaluate_expr(expr)?;
                         match cond_type {
                             LoopConditionType::While => {
-                                if !val.is_truthy() {
+                                if !self.condition_is_true(&val)? {
                                     break;
                                 }
                             }
                             LoopConditionType::Until => {
-                                if val.is_truthy() {
+                                if self.condition_is_true(&val)? {
                                     break;
                                 }
                             }
@@ -2853,8 +4186,8 @@ impl Interpreter {
                     }
 
                     // Execute body
-                    for stmt in body {
-                        match self.execute(stmt) {
+                    for (stmt, span) in body.iter_spanned() {
+                        match self.execute_at(stmt, span) {
                             Err(RuntimeError::Exit(ExitType::Do)) => return Ok(()),
                             Err(RuntimeError::Continue(vybe_parser::ast::stmt::ContinueType::Do)) => break,
                             Err(e) => return Err(e),
@@ -2867,12 +4200,12 @@ impl Interpreter {
                         let val = self.evaluate_expr(expr)?;
                         match cond_type {
                             LoopConditionType::While => {
-                                if !val.is_truthy() {
+                                if !self.condition_is_true(&val)? {
                                     break;
                                 }
                             }
                             LoopConditionType::Until => {
-                                if val.is_truthy() {
+                                if self.condition_is_true(&val)? {
                                     break;
                                 }
                             }
@@ -2881,6 +4214,7 @@ impl Interpreter {
                         // Infinite loop without condition
                         // In practice, there should be an Exit Do
                     }
+                    self.budget.step()?;
                 }
 
                 Ok(())
@@ -2896,6 +4230,12 @@ impl Interpreter {
             
             Statement::Continue(typ) => return Err(RuntimeError::Continue(typ.clone())),
 
+            // An Iterator Function's body is stepped through by resume_iterator,
+            // which handles its Yields itself
+            Statement::Yield(_) => Err(RuntimeError::Custom(
+                "'Yield' can only be used in an Iterator Function, outside 'Catch' and 'Finally' blocks".to_string(),
+            )),
+
             Statement::Throw(expr) => {
                 if let Some(ex) = expr {
                     let val = self.evaluate_expr(ex)?;
@@ -2919,6 +4259,9 @@ impl Interpreter {
             }
 
             Statement::AddHandler { event_target, handler } => {
+                if self.subscribe(event_target, handler, true)? {
+                    return Ok(());
+                }
                 // event_target = "Button1.Click" or "Me.Button1.Click"
                 // Strip "Me." prefix if present
                 let target = if event_target.to_lowercase().starts_with("me.") {
@@ -2969,6 +4312,9 @@ impl Interpreter {
             }
 
             Statement::RemoveHandler { event_target, handler } => {
+                if self.subscribe(event_target, handler, false)? {
+                    return Ok(());
+                }
                 let parts: Vec<&str> = event_target.splitn(2, '.').collect();
                 if parts.len() == 2 {
                     let control = parts[0].to_string();
@@ -2978,108 +4324,23 @@ impl Interpreter {
                     } else {
                         handler.clone()
                     };
-                    if let Some(event_type) = vybe_forms::EventType::from_name(&event) {
-                        self.events.remove_handler(&control, &event_type, &handler_name);
-                    }
-                }
-                Ok(())
-            }
-
-            Statement::Try { body, catches, finally } => {
-                let result = self.execute_block(body);
-                
-                let mut flow_result = result;
-                
-                if let Err(e) = &flow_result {
-                    // Check if it's a runtime error (not Exit/Continue/Return unless handled?)
-                    // VB.NET catches Exceptions. Exit/Return are control flow, usually not caught by Catch (unless catch all?).
-                    // But implemented as Result::Err in Rust.
-                    
-                    let mut handled = false;
-                    
-                    // Only catch actual value errors, not control flow (Exit, Return, Continue)
-                    let is_error = match e {
-                        RuntimeError::Exit(_) | RuntimeError::Return(_) | RuntimeError::Continue(_) => false,
-                        _ => true,
-                    };
-
-                    if is_error {
-                        // Extract exception type from the error
-                        let (ex_type, ex_msg, ex_inner) = match e {
-                            RuntimeError::Exception(t, m, inner) => (t.clone(), m.clone(), inner.clone()),
-                            RuntimeError::Custom(m) => ("Exception".to_string(), m.clone(), None),
-                            RuntimeError::TypeError { expected, got } => ("TypeMismatchException".to_string(), format!("Type error: expected {}, got {}", expected, got), None),
-                            RuntimeError::UndefinedVariable(v) => ("NullReferenceException".to_string(), format!("Undefined variable: {}", v), None),
-                            RuntimeError::UndefinedFunction(f) => ("MissingMethodException".to_string(), format!("Undefined function: {}", f), None),
-                            RuntimeError::DivisionByZero => ("DivideByZeroException".to_string(), "Division by zero".to_string(), None),
-                            _ => ("Exception".to_string(), format!("{}", e), None),
-                        };
+                    if let Some(event_type) = vybe_forms::EventType::from_name(&event) {
+                        self.events.remove_handler(&control, &event_type, &handler_name);
+                    }
+                }
+                Ok(())
+            }
 
-                        for catch in catches {
-                             // Check variable type match
-                             let type_match = if let Some((_, Some(type_name))) = &catch.variable {
-                                 let catch_type = format!("{:?}", type_name).to_lowercase();
-                                 let catch_type = catch_type.trim_start_matches("custom(\"").trim_end_matches("\")");
-                                 let ex_lower = ex_type.to_lowercase();
-                                 catch_type == "exception" || catch_type == "system.exception"
-                                     || ex_lower == catch_type
-                                     || ex_lower.ends_with(&format!(".{}", catch_type))
-                                     || catch_type.ends_with(&ex_lower)
-                                     || ex_lower.ends_with("exception")
-                             } else {
-                                 true // Catch All
-                             };
-                             
-                             if type_match {
-                                 // Push scope for the catch variable to exist during 'When' evaluation and body execution
-                                 self.env.push_scope();
-                                 
-                                 // Define the exception variable if present
-                                 if let Some((name, _)) = &catch.variable {
-                                     let mut ex_fields = std::collections::HashMap::new();
-                                     ex_fields.insert("message".to_string(), Value::String(ex_msg.clone()));
-                                     // Basic stacktrace support
-                                     ex_fields.insert("stacktrace".to_string(), Value::String(String::new()));
-                                     ex_fields.insert("source".to_string(), Value::String(String::new()));
-                                     ex_fields.insert("hresult".to_string(), Value::Integer(-2146233088));
-                                     if let Some(ref inner) = ex_inner {
-                                         ex_fields.insert("innerexception".to_string(), Value::String(inner.clone()));
-                                     } else {
-                                          ex_fields.insert("innerexception".to_string(), Value::Nothing);
-                                     }
-                                     
-                                     let ex_obj = crate::value::ObjectData { drawing_commands: Vec::new(),
-                                         class_name: ex_type.clone(),
-                                         fields: ex_fields,
-                                     };
-                                     self.env.define(name.as_str(), Value::Object(std::rc::Rc::new(std::cell::RefCell::new(ex_obj))));
-                                 }
+            Statement::Try { body, catches, finally } => {
+                let mut flow_result = self.execute_block(body);
 
-                                 let when_match = if let Some(expr) = &catch.when_clause {
-                                      match self.evaluate_expr(expr) {
-                                          Ok(val) => val.is_truthy(),
-                                          Err(_) => false, // If evaluation fails (e.g. wrong property), treat as false match
-                                      }
-                                 } else {
-                                      true
-                                 };
-                                 
-                                 if when_match {
-                                     flow_result = self.execute_block(&catch.body);
-                                     self.env.pop_scope();
-                                     handled = true;
-                                     break;
-                                 } else {
-                                     // Pop scope if we didn't match (remove the variable)
-                                     self.env.pop_scope();
-                                 }
-                             }
-                        }
-                        
-                        let _ = handled;
+                // Only actual errors are caught, not control flow (Exit, Return, Continue)
+                if let Err(e) = &flow_result {
+                    if let Some(handled) = self.run_catch(e, catches) {
+                        flow_result = handled;
                     }
                 }
-                
+
                 // Finally block
                 if let Some(final_stmts) = finally {
                      let final_res = self.execute_block(final_stmts);
@@ -3093,7 +4354,9 @@ impl Interpreter {
 
             Statement::Return(value) => {
                 let val = if let Some(expr) = value {
-                    Some(self.evaluate_expr(expr)?)
+                    let val = self.evaluate_expr(expr)?;
+                    self.check_return(expr, &val)?;
+                    Some(val)
                 } else {
                     None
                 };
@@ -3104,23 +4367,23 @@ impl Interpreter {
                 // If the call name contains dots (e.g. "Me.txt1.DataBindings.Add"),
                 // the parser failed to produce an expression chain. Build and evaluate
                 // a synthetic MethodCall expression to handle it correctly.
-                if name.as_str().contains('.') {
+                if name.as_str().split('(').next().is_some_and(|callee| callee.contains('.')) {
                     let parts: Vec<&str> = name.as_str().split('.').collect();
                     if parts.len() >= 2 {
                         let mut expr: Expression = if parts[0].eq_ignore_ascii_case("me") {
-                            Expression::Me
+                            ExprKind::Me.into()
                         } else {
-                            Expression::Variable(Identifier::new(parts[0]))
+                            ExprKind::Variable(Identifier::new(parts[0])).into()
                         };
                         for part in &parts[1..parts.len()-1] {
-                            expr = Expression::MemberAccess(Box::new(expr), Identifier::new(*part));
+                            expr = ExprKind::MemberAccess(Box::new(expr), Identifier::new(*part)).into();
                         }
                         let method_name = Identifier::new(parts[parts.len()-1]);
-                        let call_expr = Expression::MethodCall(
+                        let call_expr: Expression = ExprKind::MethodCall(
                             Box::new(expr),
                             method_name,
                             arguments.to_vec(),
-                        );
+                        ).into();
                         self.evaluate_expr(&call_expr)?;
                         return Ok(());
                     }
@@ -3134,33 +4397,42 @@ impl Interpreter {
                 Ok(())
             }
 
-            Statement::ForEach { variable, collection, body } => {
+            Statement::ForEach { variable, var_type, collection, body } => {
+                self.check_loop_variable(variable, var_type)?;
                 let coll_val = self.evaluate_expr(collection)?;
-                let items = coll_val.to_iterable()?;
+                let mut cursor = self.open_cursor(&coll_val)?;
                 self.env.define(variable.as_str(), Value::Nothing);
-                for item in &items {
-                    self.env.set(variable.as_str(), item.clone())?;
-                    let mut should_exit = false;
-                    for s in body {
-                        match self.execute(s) {
-                            Err(RuntimeError::Exit(ExitType::For)) => { should_exit = true; break; }
+                let result = 'items: loop {
+                    let item = match self.cursor_next(&mut cursor) {
+                        Ok(Some(item)) => item,
+                        Ok(None) => break Ok(()),
+                        Err(e) => break Err(e),
+                    };
+                    if let Err(e) = self.env.set(variable.as_str(), crate::structures::copy_value(item)) {
+                        break Err(e);
+                    }
+                    for (s, span) in body.iter_spanned() {
+                        match self.execute_at(s, span) {
+                            Err(RuntimeError::Exit(ExitType::For)) => break 'items Ok(()),
                             Err(RuntimeError::Continue(vybe_parser::ast::stmt::ContinueType::For)) => break,
-                            Err(e) => return Err(e),
+                            Err(e) => break 'items Err(e),
                             Ok(()) => {}
                         }
                     }
-                    if should_exit { break; }
-                }
-                Ok(())
+                    if let Err(e) = self.budget.step() {
+                        break Err(e);
+                    }
+                };
+                // Leaving early stops an iterator, running its pending Finally blocks
+                self.close_cursor(cursor)?;
+                result
             }
 
             Statement::With { object, body } => {
                 let obj_val = self.evaluate_expr(object)?;
                 let prev_with = self.with_object.take();
                 self.with_object = Some(obj_val);
-                for s in body {
-                    self.execute(s)?;
-                }
+                self.execute_block(body)?;
                 self.with_object = prev_with;
                 Ok(())
             }
@@ -3170,29 +4442,21 @@ impl Interpreter {
                 let res_val = self.evaluate_expr(resource)?;
                 self.env.define(variable.as_str(), res_val.clone());
 
-                for s in body {
-                    self.execute(s)?;
-                }
-
-                // Best-effort dispose: if the object has a Dispose method, invoke it.
-                if let Value::Object(obj_ref) = res_val.clone() {
-                    let class_name = obj_ref.borrow().class_name.clone();
-                    if let Some(method) = self.find_method(&class_name, "Dispose") {
-                        if let vybe_parser::ast::decl::MethodDecl::Sub(s) = method {
-                            let _ = self.call_user_sub(&s, &[], Some(obj_ref.clone()));
-                        }
-                    }
-                }
-
-                // Clear the variable after leaving the Using scope.
-                let _ = self.env.set(variable.as_str(), Value::Nothing);
+                self.execute_block(body)?;
+                self.end_using(variable.as_str(), &res_val);
                 Ok(())
             }
 
-            Statement::Open { file_path, mode, file_number } => {
+            Statement::Open { file_path, mode, file_number, record_length } => {
                 let path = self.evaluate_expr(file_path)?.as_string();
+                self.sandbox.check_path(&path)?;
                 let fn_val = self.evaluate_expr(file_number)?.as_integer()?;
-                let handle = crate::file_io::open_file(&path, *mode)?;
+                let record_length = match record_length {
+                    Some(len) => Some(usize::try_from(self.evaluate_expr(len)?.as_integer()?)
+                        .map_err(|_| RuntimeError::Custom("Bad record length".to_string()))?),
+                    None => None,
+                };
+                let handle = crate::file_io::open_file(&path, *mode, record_length)?;
                 self.file_handles.insert(fn_val, handle);
                 Ok(())
             }
@@ -3293,6 +4557,100 @@ impl Interpreter {
                 self.env.set(variable.as_str(), Value::String(line))?;
                 Ok(())
             }
+
+            Statement::PutFile { file_number, position, value } => {
+                let fn_val = self.evaluate_expr(file_number)?.as_integer()?;
+                let position = match position {
+                    Some(p) => Some(self.evaluate_expr(p)?.as_long()?),
+                    None => None,
+                };
+                let value = self.evaluate_expr(value)?;
+                let layout = self.record_layout(&value)?;
+                let Some(handle) = self.file_handles.get_mut(&fn_val) else {
+                    return Err(RuntimeError::Custom(format!("File number {} not open", fn_val)));
+                };
+                // A Binary file holds a string's characters alone
+                let layout = match (&*handle, layout) {
+                    (crate::file_io::FileHandle::Binary(_), crate::records::Layout::String) => {
+                        crate::records::Layout::FixedString(value.as_string().chars().count())
+                    }
+                    (_, layout) => layout,
+                };
+                let mut bytes = Vec::new();
+                crate::records::encode(&value, &layout, &mut bytes)?;
+                crate::file_io::put_bytes(handle, position, &bytes)
+            }
+
+            Statement::GetFile { file_number, position, target } => {
+                let fn_val = self.evaluate_expr(file_number)?.as_integer()?;
+                let position = match position {
+                    Some(p) => Some(self.evaluate_expr(p)?.as_long()?),
+                    None => None,
+                };
+                // The variable's current value gives the type to read
+                let current = self.evaluate_expr(target)?;
+                let layout = self.record_layout(&current)?;
+                let Some(handle) = self.file_handles.get_mut(&fn_val) else {
+                    return Err(RuntimeError::Custom(format!("File number {} not open", fn_val)));
+                };
+                let layout = match (&*handle, layout) {
+                    (crate::file_io::FileHandle::Binary(_), crate::records::Layout::String) => {
+                        crate::records::Layout::FixedString(current.as_string().chars().count())
+                    }
+                    (_, layout) => layout,
+                };
+                let bytes = crate::file_io::get_bytes(handle, position, layout.size())?;
+                let value = crate::records::decode(&mut bytes.as_slice(), &layout, &current)?;
+                self.assign_to(target, value)
+            }
+
+            Statement::MidAssignment { target, start, length, value } => {
+                let text = self.evaluate_expr(target)?.as_string();
+                let start = self.evaluate_expr(start)?.as_integer()?;
+                let length = match length {
+                    Some(len) => Some(self.evaluate_expr(len)?.as_integer()?),
+                    None => None,
+                };
+                let value = self.evaluate_expr(value)?.as_string();
+                let replaced = mid_statement(&text, start, length, &value)?;
+                self.assign_to(target, Value::String(replaced))
+            }
+
+            Statement::LSet { target, value } => {
+                let current = self.evaluate_expr(target)?;
+                let value = self.evaluate_expr(value)?;
+                let is_record = |v: &Value| matches!(v, Value::Object(o) if crate::structures::is_structure(&o.borrow()));
+                let result = if is_record(&current) && is_record(&value) {
+                    // One user-defined type into another: copy the bytes
+                    let mut bytes = Vec::new();
+                    let source_layout = self.record_layout(&value)?;
+                    crate::records::encode(&value, &source_layout, &mut bytes)?;
+                    let target_layout = self.record_layout(&current)?;
+                    crate::records::decode(&mut bytes.as_slice(), &target_layout, &current)?
+                } else {
+                    Value::String(crate::records::fit(&value.as_string(), current.as_string().chars().count()))
+                };
+                self.assign_to(target, result)
+            }
+
+            Statement::RSet { target, value } => {
+                let len = self.evaluate_expr(target)?.as_string().chars().count();
+                let value = self.evaluate_expr(value)?.as_string();
+                self.assign_to(target, Value::String(crate::records::fit_right(&value, len)))
+            }
+
+            Statement::Erase(arrays) => {
+                for array in arrays {
+                    match &array.kind {
+                        // Not coerced to the declared element type
+                        ExprKind::Variable(name) if self.env.has_local(name.as_str()) => {
+                            self.env.set(name.as_str(), Value::Nothing)?;
+                        }
+                        _ => self.assign_to(array, Value::Nothing)?,
+                    }
+                }
+                Ok(())
+            }
             Statement::ExitProperty => {
                  return Err(RuntimeError::Exit(ExitType::Property));
             }
@@ -3300,65 +4658,20 @@ impl Interpreter {
                 // Get current value
                 let current = if !members.is_empty() {
                     // Build member access expression
-                    let mut obj = Expression::Variable(target.clone());
+                    let mut obj: Expression = ExprKind::Variable(target.clone()).into();
                     for m in members.iter() {
-                        obj = Expression::MemberAccess(Box::new(obj), m.clone());
+                        obj = ExprKind::MemberAccess(Box::new(obj), m.clone()).into();
                     }
                     self.evaluate_expr(&obj)?
                 } else if !indices.is_empty() {
-                    let arr_expr = Expression::ArrayAccess(target.clone(), indices.clone());
+                    let arr_expr = ExprKind::ArrayAccess(target.clone(), indices.clone()).into();
                     self.evaluate_expr(&arr_expr)?
                 } else {
-                    self.evaluate_expr(&Expression::Variable(target.clone()))?
+                    self.evaluate_expr(&ExprKind::Variable(target.clone()).into())?
                 };
 
                 let rhs = self.evaluate_expr(value)?;
-                use vybe_parser::ast::stmt::CompoundOp;
-                let new_val = match operator {
-                    CompoundOp::AddAssign => {
-                        match (&current, &rhs) {
-                            (Value::Integer(a), Value::Integer(b)) => Value::Integer(a + b),
-                            (Value::String(a), _) => Value::String(format!("{}{}", a, rhs.as_string())),
-                            _ => Value::Double(current.as_double()? + rhs.as_double()?),
-                        }
-                    }
-                    CompoundOp::SubtractAssign => {
-                        match (&current, &rhs) {
-                            (Value::Integer(a), Value::Integer(b)) => Value::Integer(a - b),
-                            _ => Value::Double(current.as_double()? - rhs.as_double()?),
-                        }
-                    }
-                    CompoundOp::MultiplyAssign => {
-                        match (&current, &rhs) {
-                            (Value::Integer(a), Value::Integer(b)) => Value::Integer(a * b),
-                            _ => Value::Double(current.as_double()? * rhs.as_double()?),
-                        }
-                    }
-                    CompoundOp::DivideAssign => {
-                        let a = current.as_double()?;
-                        let b = rhs.as_double()?;
-                        if b == 0.0 { return Err(RuntimeError::DivisionByZero); }
-                        Value::Double(a / b)
-                    }
-                    CompoundOp::IntDivideAssign => {
-                        let a = current.as_integer()?;
-                        let b = rhs.as_integer()?;
-                        if b == 0 { return Err(RuntimeError::DivisionByZero); }
-                        Value::Integer(a / b)
-                    }
-                    CompoundOp::ConcatAssign => {
-                        Value::String(format!("{}{}", current.as_string(), rhs.as_string()))
-                    }
-                    CompoundOp::ExponentAssign => {
-                        Value::Double(current.as_double()?.powf(rhs.as_double()?))
-                    }
-                    CompoundOp::ShiftLeftAssign => {
-                        Value::Long(current.as_long()? << rhs.as_integer()? as u32)
-                    }
-                    CompoundOp::ShiftRightAssign => {
-                        Value::Long(current.as_long()? >> rhs.as_integer()? as u32)
-                    }
-                };
+                let new_val = self.compound_operation(operator, &current, &rhs)?;
 
                 // Assign back
                 if !members.is_empty() {
@@ -3377,11 +4690,19 @@ impl Interpreter {
                         }
                     }
                 } else {
-                    self.env.set(target.as_str(), new_val)?;
+                    self.store_compound(target, new_val)?;
                 }
                 Ok(())
             }
             Statement::RaiseEvent { event_name, arguments } => {
+                // An event of the class raising it
+                if let Some(source) = self.current_object.clone() {
+                    let class_name = source.borrow().class_name.clone();
+                    if let Some(event) = self.find_event(&class_name, event_name.as_str()) {
+                        let args: Vec<Value> = arguments.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_, _>>()?;
+                        return self.raise_event(&event, source, &args);
+                    }
+                }
                 // RaiseEvent fires events registered via AddHandler
                 // Look up handler in the event system
                 let event_str = event_name.as_str();
@@ -3433,6 +4754,23 @@ impl Interpreter {
             Statement::GoTo(label) => {
                 Err(RuntimeError::GoTo(label.clone()))
             }
+            Statement::GoSub(label) => self.go_sub(label),
+            Statement::OnGoTo { selector, labels, gosub } => {
+                let index = crate::integral::convert(&self.evaluate_expr(selector)?, crate::integral::IntType::Integer)?.as_integer()?;
+                match index {
+                    // Past the list (or 0): fall through to the next statement
+                    0..=255 if index as usize > labels.len() || index == 0 => Ok(()),
+                    1..=255 => {
+                        let label = &labels[index as usize - 1];
+                        if *gosub { self.go_sub(label) } else { Err(RuntimeError::GoTo(label.clone())) }
+                    }
+                    _ => Err(RuntimeError::Exception(
+                        "ArgumentException".to_string(),
+                        "Procedure call or argument is not valid.".to_string(),
+                        None,
+                    )),
+                }
+            }
             Statement::Label(_label) => {
                 // Labels are markers — execution just passes through them.
                 Ok(())
@@ -3468,22 +4806,43 @@ impl Interpreter {
     /// Execute a block of statements with GoTo jump support and On Error handling.
     /// When a GoTo is encountered, finds the target Label in the body and resumes
     /// execution from there. Also handles On Error Resume Next (swallow errors).
-    fn execute_body_with_goto(&mut self, body: &[Statement]) -> Result<(), RuntimeError> {
-        let mut pc = 0; // program counter — index into body
+    /// `span` is where the procedure owning the body was declared; the
+    /// bytecode engine files the compiled body under it.
+    fn execute_body_with_goto(&mut self, body: &Block, span: Span) -> Result<(), RuntimeError> {
+        // Synthesized procedures and bodies have no span to tell them apart by
+        if self.engine == Engine::Bytecode && !body.is_empty() && !span.is_unknown() && !body.span_at(0).is_unknown() {
+            let procedure = self.current_procedure.as_deref().unwrap_or_default();
+            let compiled = self.chunks.get(&self.current_source, procedure, span, body);
+            if let Some(chunk) = &compiled.chunk {
+                // A compiled body has no labels to GoSub to
+                self.gosub_bodies.push(None);
+                let result = self.run_chunk(chunk, body);
+                self.gosub_bodies.pop();
+                return result;
+            }
+        }
+        // Only bodies with labels can be the target of a GoSub
+        let labelled = body.iter().any(|s| matches!(s, Statement::Label(_)));
+        self.gosub_bodies.push(labelled.then(|| Rc::new(body.clone())));
+        let result = self.execute_body_from(body, 0);
+        self.gosub_bodies.pop();
+        result
+    }
+
+    /// Run `body` from statement `pc` to its end, following GoTo jumps.
+    fn execute_body_from(&mut self, body: &Block, mut pc: usize) -> Result<(), RuntimeError> {
         while pc < body.len() {
             let stmt = &body[pc];
-            match self.execute(stmt) {
+            match self.execute_at(stmt, body.span_at(pc)) {
                 Ok(_) => {
                     pc += 1;
                 }
                 Err(RuntimeError::GoTo(label)) => {
                     // Find the label in the body and jump to it
-                    if let Some(idx) = body.iter().position(|s| matches!(s, Statement::Label(l) if l.eq_ignore_ascii_case(&label))) {
-                        pc = idx + 1; // resume after the label
-                    } else {
-                        return Err(RuntimeError::Custom(format!("Label '{}' not found", label)));
-                    }
+                    pc = find_label(body, &label)? + 1; // resume after the label
+                    self.budget.step()?;
                 }
+                Err(e) if e.is_abort() => return Err(e),
                 Err(e) => {
                     // On Error Resume Next: swallow the error and continue
                     if self.on_error_resume_next {
@@ -3492,7 +4851,7 @@ impl Interpreter {
                     }
                     // On Error GoTo <label>: jump to error handler label
                     if let Some(ref lbl) = self.on_error_goto_label.clone() {
-                        if let Some(idx) = body.iter().position(|s| matches!(s, Statement::Label(l) if l.eq_ignore_ascii_case(lbl))) {
+                        if let Ok(idx) = find_label(body, lbl) {
                             // Disable error handling to avoid infinite loops in the handler
                             self.on_error_goto_label = None;
                             pc = idx + 1;
@@ -3507,188 +4866,262 @@ impl Interpreter {
         Ok(())
     }
 
-    /// Persist any static local variables from the current scope back into
-    /// `self.static_locals` so they survive across calls.
-    fn persist_static_locals(&mut self) {
-        let module = self.current_module.clone().unwrap_or_else(|| "__global__".to_string());
-        let proc = self.current_procedure.clone().unwrap_or_else(|| "__main__".to_string());
-        let prefix = format!("{}.{}.", module, proc).to_lowercase();
-        let keys: Vec<String> = self.static_locals.keys()
-            .filter(|k| k.starts_with(&prefix))
-            .cloned()
-            .collect();
-        for key in keys {
-            // Extract var name from key
-            let var_name = &key[prefix.len()..];
-            if let Ok(val) = self.env.get(var_name) {
-                self.static_locals.insert(key, val);
-            }
+    /// `GoSub label`: run the current procedure from `label` until a bare
+    /// `Return`, then carry on after the GoSub. Reaching the end of the
+    /// procedure first ends the procedure, as in VB6.
+    fn go_sub(&mut self, label: &str) -> Result<(), RuntimeError> {
+        let body = self.gosub_bodies.last().cloned().flatten()
+            .ok_or_else(|| RuntimeError::Custom(format!("Label '{}' not found", label)))?;
+        let start = find_label(&body, label)? + 1;
+        match self.execute_body_from(&body, start) {
+            Err(RuntimeError::Return(None)) => Ok(()),
+            Ok(()) => Err(RuntimeError::Return(None)),
+            Err(e) => Err(e),
         }
     }
 
-    pub fn evaluate_expr(&mut self, expr: &Expression) -> Result<Value, RuntimeError> {
-        match expr {
-            Expression::Lambda { params, body, .. } => {
-                Ok(Value::Lambda {
-                    params: params.clone(),
-                    body: body.clone(),
-                    env: Rc::new(RefCell::new(self.env.clone())),
-                })
+    /// Run a procedure body compiled to bytecode: the VM counterpart of
+    /// [`Self::execute_body_from`], with the same `On Error` handling of
+    /// errors out of the body's top-level statements.
+    fn run_chunk(&mut self, chunk: &Chunk, body: &Block) -> Result<(), RuntimeError> {
+        let mut stack = Vec::new();
+        let mut pc = 0;
+        loop {
+            let error = match self.run_ops(chunk, &mut stack, &mut pc) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            if let RuntimeError::GoTo(label) = &error {
+                // A compiled body has no labels, so this reports the missing one
+                find_label(body, label)?;
             }
-            Expression::Call(name, args) => {
-                // 1. Check if name refers to a variable holding a Lambda or Array
-                // NOTE: We check the environment directly instead of using evaluate_expr(Variable(...))
-                // because Variable evaluation has an implicit function-call fallback that would
-                // execute the sub/function a first time, then call_function below would execute it again.
-                let var_name = name.as_str();
-                if let Ok(val) = self.env.get(var_name) {
-                   match val {
-                       Value::Lambda { .. } => {
-                           let arg_values: Result<Vec<_>, _> = args.iter().map(|e| self.evaluate_expr(e)).collect();
-                           return self.call_lambda(val, &arg_values?);
-                       }
-                       Value::Array(arr) => {
-                           // Array access via Call syntax — supports multi-dimensional
-                           if args.len() == 1 {
-                               let index = self.evaluate_expr(&args[0])?.as_integer()? as usize;
-                               return arr.get(index).cloned().ok_or_else(|| RuntimeError::Custom("Array index out of bounds".to_string()));
-                           } else {
-                               // Multi-dim: drill into nested arrays
-                               let mut current = Value::Array(arr);
-                               for idx_expr in args.iter() {
-                                   let idx = self.evaluate_expr(idx_expr)?.as_integer()? as usize;
-                                   current = match current {
-                                       Value::Array(inner) => inner.get(idx).cloned().ok_or_else(|| {
-                                           RuntimeError::Custom("Array index out of bounds".to_string())
-                                       })?,
-                                       _ => return Err(RuntimeError::Custom("Cannot index non-array dimension".to_string())),
-                                   };
-                               }
-                               return Ok(current);
-                           }
-                       }
-                       Value::Dictionary(dict) => {
-                           // Dictionary access via Call syntax (e.g. dict("key"))
-                           if args.len() != 1 {
-                               return Err(RuntimeError::Custom("Dictionary index must be 1 key".to_string()));
-                           }
-                           let key = self.evaluate_expr(&args[0])?;
-                           return dict.borrow().item(&key);
-                       }
-                       Value::Collection(col) => {
-                           // Collection access via Call syntax (e.g. col("key") or col(0))
-                           if args.len() == 1 {
-                               let idx_val = self.evaluate_expr(&args[0])?;
-                               return match &idx_val {
-                                   Value::String(key) => col.borrow().item_by_key(key),
-                                   _ => {
-                                       let idx = idx_val.as_integer()? as usize;
-                                       col.borrow().item(idx)
-                                   }
-                               };
-                           }
-                       }
-                       _ => {} // Not a callable value, proceed to function lookup
-                   }
-                }
-                
-                // 2. Standard function lookup
-                self.call_function(name, args)
+            if self.on_error_resume_next && !error.is_abort() {
+                stack.clear();
+                pc = chunk.next_statement(pc);
+                continue;
             }
-            Expression::Await(operand) => {
-                // Await evaluates the operand; if it's a Task, return its Result
-                let val = self.evaluate_expr(operand)?;
-                if let Value::Object(ref obj) = val {
-                    let b = obj.borrow();
-                    if b.class_name == "Task" {
-                        // Check if faulted
-                        if let Some(Value::Boolean(true)) = b.fields.get("isfaulted") {
-                            let msg = b.fields.get("exception").map(|v| v.as_string()).unwrap_or_else(|| "Task faulted".to_string());
-                            return Err(RuntimeError::Exception("AggregateException".to_string(), msg, None));
-                        }
-                        return Ok(b.fields.get("result").cloned().unwrap_or(Value::Nothing));
-                    }
+            return Err(error);
+        }
+    }
+
+    /// Run `chunk` from op `pc` to its end. On an error, `pc` is left at the
+    /// op that raised it.
+    fn run_ops(&mut self, chunk: &Chunk, stack: &mut Vec<Value>, pc: &mut usize) -> Result<(), RuntimeError> {
+        let mut span = Span::default();
+        while let Some(op) = chunk.ops.get(*pc) {
+            let mut next = *pc + 1;
+            let result = match op {
+                Op::At(at) => {
+                    span = *at;
+                    Ok(())
                 }
-                Ok(val)
-            }
-            Expression::MethodCall(obj, method, args) => {
-                match self.call_method(obj, method, args) {
-                    Ok(val) => Ok(val),
-                    Err(e) => {
-                        // Fallback: try extension methods
-                        if let Ok(result) = self.try_extension_method(obj, method, args) {
-                            Ok(result)
-                        } else {
-                            Err(e)
+                Op::Const(value) => {
+                    stack.push(value.clone());
+                    Ok(())
+                }
+                Op::Load(i) => {
+                    let variable = &chunk.variables[*i];
+                    match variable.local(&self.env) {
+                        Some(slot) => {
+                            stack.push(self.env.slot(slot).clone());
+                            Ok(())
                         }
+                        None => self.load_nonlocal(&variable.name, variable.symbol).map(|v| stack.push(v)),
                     }
                 }
-            }
-            Expression::ArrayAccess(array, indices) => {
-                let arr_val = self.env.get(array.as_str())?;
-                match arr_val {
-                    Value::Array(arr) => {
-                        if indices.len() == 1 {
-                            let index = self.evaluate_expr(&indices[0])?.as_integer()? as usize;
-                            arr.get(index).cloned().ok_or_else(|| RuntimeError::Custom("Array index out of bounds".to_string()))
-                        } else {
-                            // Multi-dimensional: drill into nested arrays
-                            let mut current = Value::Array(arr);
-                            for idx_expr in indices {
-                                let idx = self.evaluate_expr(idx_expr)?.as_integer()? as usize;
-                                current = match current {
-                                    Value::Array(inner) => inner.get(idx).cloned().ok_or_else(|| {
-                                        RuntimeError::Custom("Array index out of bounds".to_string())
-                                    })?,
-                                    _ => return Err(RuntimeError::Custom("Cannot index non-array dimension".to_string())),
-                                };
-                            }
-                            Ok(current)
+                Op::Eval(i) => self.evaluate_expr(&chunk.exprs[*i]).map(|v| stack.push(v)),
+                Op::Binary(op) => {
+                    let r = pop(stack);
+                    let l = pop(stack);
+                    self.binary_operation(*op, l, r).map(|v| stack.push(v))
+                }
+                Op::Negate => {
+                    let val = pop(stack);
+                    self.negate(val).map(|v| stack.push(v))
+                }
+                Op::Not => {
+                    let val = pop(stack);
+                    self.not(val).map(|v| stack.push(v))
+                }
+                Op::ShortCircuit { and, end } => {
+                    let l = pop(stack);
+                    self.short_circuit(*and, &l).map(|pending| match pending {
+                        ShortCircuit::Done(result) => {
+                            stack.push(result);
+                            next = *end;
                         }
-                    }
-                    Value::Collection(col) => {
-                        let idx_val = self.evaluate_expr(&indices[0])?;
-                        match &idx_val {
-                            Value::String(key) => {
-                                col.borrow().item_by_key(key)
-                            }
-                            _ => {
-                                // VB.NET Collection is 1-based, but ArrayList is 0-based.
-                                // We use 0-based here since our Collection wraps ArrayList.
-                                let index = idx_val.as_integer()? as usize;
-                                col.borrow().item(index)
-                            }
+                        pending => {
+                            stack.push(l);
+                            stack.push(Value::Boolean(matches!(pending, ShortCircuit::Operator)));
+                        }
+                    })
+                }
+                Op::FinishShortCircuit { and } => {
+                    let r = pop(stack);
+                    let pending = match pop(stack) {
+                        Value::Boolean(true) => ShortCircuit::Operator,
+                        _ => ShortCircuit::Right,
+                    };
+                    let l = pop(stack);
+                    self.finish_short_circuit(*and, pending, &l, &r).map(|v| stack.push(v))
+                }
+                Op::Assign(i) => {
+                    let (target, value) = &chunk.assignments[*i];
+                    let variable = &chunk.variables[*target];
+                    let val = pop(stack);
+                    match variable.local(&self.env) {
+                        Some(slot) => self.assign_local(slot, variable.symbol, &variable.name, value, val),
+                        None => self.assign_variable(&variable.name, value, val),
+                    }
+                }
+                Op::Compound(i) => {
+                    let (target, operator) = &chunk.compounds[*i];
+                    let target = &chunk.variables[*target].name;
+                    let rhs = pop(stack);
+                    let current = pop(stack);
+                    self.compound_operation(operator, &current, &rhs).and_then(|v| self.store_compound(target, v))
+                }
+                Op::Exec(i) => {
+                    let fallback = &chunk.statements[*i];
+                    let result = self.execute(&fallback.statement);
+                    // An Exit or Continue for a compiled loop around the statement
+                    let enclosing = |matches: &dyn Fn(crate::bytecode::LoopKind) -> bool| {
+                        fallback.loops.iter().rev().copied().find(|&id| matches(chunk.loops[id].kind))
+                    };
+                    let caught = match &result {
+                        Err(RuntimeError::Exit(exit)) => enclosing(&|kind| kind.exits_on(exit)).map(|id| (id, false)),
+                        Err(RuntimeError::Continue(typ)) => enclosing(&|kind| kind.continues_on(typ)).map(|id| (id, true)),
+                        _ => None,
+                    };
+                    match caught {
+                        Some((id, go_on)) => {
+                            next = leave_loop(chunk, id, stack, go_on);
+                            Ok(())
                         }
+                        None => result,
                     }
-                    Value::Dictionary(dict) => {
-                        if indices.len() != 1 {
-                            return Err(RuntimeError::Custom("Dictionary index must be 1 key".to_string()));
+                }
+                Op::Jump(target) => {
+                    next = *target;
+                    Ok(())
+                }
+                Op::JumpIfFalse(target) | Op::JumpIfTrue(target) => {
+                    let cond = pop(stack);
+                    self.condition_is_true(&cond).map(|holds| {
+                        if holds == matches!(op, Op::JumpIfTrue(_)) {
+                            next = *target;
                         }
-                        let key = self.evaluate_expr(&indices[0])?;
-                        return dict.borrow().item(&key);
+                    })
+                }
+                Op::Break(id) => {
+                    next = leave_loop(chunk, *id, stack, false);
+                    Ok(())
+                }
+                Op::Next(id) => {
+                    next = leave_loop(chunk, *id, stack, true);
+                    Ok(())
+                }
+                Op::ForCheck(i) => {
+                    let (variable, var_type) = &chunk.for_loops[*i];
+                    self.check_loop_variable(&chunk.variables[*variable].name, var_type)
+                }
+                Op::ToInteger => {
+                    let val = pop(stack);
+                    val.as_integer().map(|i| stack.push(Value::Integer(i)))
+                }
+                Op::ForTest { var, exit } => {
+                    let (i, end, step) = for_counter(stack);
+                    if (step > 0 && i > end) || (step < 0 && i < end) {
+                        next = *exit;
+                        Ok(())
+                    } else {
+                        // `For i As Short` counts in its declared type
+                        let (variable, var_type) = &chunk.for_loops[*var];
+                        let variable = &chunk.variables[*variable];
+                        coerce_to_declared(var_type.as_ref(), Value::Integer(i)).and_then(|v| match variable.local(&self.env) {
+                            Some(slot) => self.store_local(slot, variable.symbol, &variable.name, v),
+                            None => self.env.set(variable.name.as_str(), v),
+                        })
                     }
-                    _ => Err(RuntimeError::Custom(format!("Type is not indexable: {:?}", arr_val))),
                 }
+                Op::ForStep(head) => {
+                    let (i, _, step) = for_counter(stack);
+                    let at = stack.len() - 3;
+                    stack[at] = Value::Integer(i + step);
+                    next = *head;
+                    Ok(())
+                }
+                Op::Pop(n) => {
+                    stack.truncate(stack.len().saturating_sub(*n));
+                    Ok(())
+                }
+                Op::Return(None) => Err(RuntimeError::Return(None)),
+                Op::Return(Some(i)) => {
+                    let value = pop(stack);
+                    self.check_return(&chunk.returns[*i], &value).and(Err(RuntimeError::Return(Some(value))))
+                }
+                Op::Exit(exit) => Err(RuntimeError::Exit(exit.clone())),
+                Op::Continue(typ) => Err(RuntimeError::Continue(*typ)),
+            };
+            // Every loop goes round by jumping back
+            let result = match result {
+                Ok(()) if next <= *pc => self.budget.step(),
+                result => result,
+            };
+            if let Err(e) = result {
+                return Err(self.locate_error(e, span));
             }
-            Expression::ArrayLiteral(elements) => {
-                let vals: Result<Vec<Value>, RuntimeError> = elements
-                    .iter()
-                    .map(|e| self.evaluate_expr(e))
-                    .collect();
-                Ok(Value::Array(vals?))
+            *pc = next;
+        }
+        Ok(())
+    }
+
+    /// Persist any static local variables from the current scope back into
+    /// `self.static_locals` so they survive across calls.
+    fn persist_static_locals(&mut self) {
+        let module = self.current_module.clone().unwrap_or_else(|| "__global__".to_string());
+        let proc = self.current_procedure.clone().unwrap_or_else(|| "__main__".to_string());
+        let prefix = format!("{}.{}.", module, proc).to_lowercase();
+        let keys: Vec<String> = self.static_locals.keys()
+            .filter(|k| k.starts_with(&prefix))
+            .cloned()
+            .collect();
+        for key in keys {
+            // Extract var name from key
+            let var_name = &key[prefix.len()..];
+            if let Ok(val) = self.env.get(var_name) {
+                self.static_locals.insert(key, val);
             }
-            Expression::Query(query) => self.execute_query(query),
-            Expression::XmlLiteral(node) => self.construct_xml(node),
-            // For all other expressions that might contain nested calls, evaluate through interpreter
-            Expression::Add(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+        }
+    }
+
+    /// Evaluate both operands of a binary operator, then apply it.
+    fn evaluate_binary(&mut self, op: BinaryOp, left: &Expression, right: &Expression) -> Result<Value, RuntimeError> {
+        let l = self.evaluate_expr(left)?;
+        let r = self.evaluate_expr(right)?;
+        self.binary_operation(op, l, r)
+    }
+
+    /// `l op r` for a binary operator. `AndAlso` and `OrElse` get here only
+    /// with both operands already evaluated; [`Self::short_circuit`] is
+    /// what skips the right one.
+    pub fn binary_operation(&mut self, op: BinaryOp, l: Value, r: Value) -> Result<Value, RuntimeError> {
+        match op {
+            BinaryOp::Add => {
+                if let Some(result) = self.binary_operator("+", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("+", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("+", &l, &r)? {
+                    return Ok(result);
+                }
                 match (&l, &r) {
                     (Value::Date(d), Value::Double(n)) => Ok(Value::Date(d + n)),
                     (Value::Double(n), Value::Date(d)) => Ok(Value::Date(d + n)),
                     (Value::Date(d), Value::Integer(n)) => Ok(Value::Date(d + *n as f64)),
                     (Value::Integer(n), Value::Date(d)) => Ok(Value::Date(d + *n as f64)),
-                    (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a + b)),
                     _ => {
                         let a = l.as_double()?;
                         let b = r.as_double()?;
@@ -3696,14 +5129,20 @@ impl Interpreter {
                     }
                 }
             }
-            Expression::Subtract(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+            BinaryOp::Subtract => {
+                if let Some(result) = self.binary_operator("-", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("-", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("-", &l, &r)? {
+                    return Ok(result);
+                }
                 match (&l, &r) {
                     (Value::Date(d), Value::Double(n)) => Ok(Value::Date(d - n)),
                     (Value::Date(d1), Value::Date(d2)) => Ok(Value::Double(d1 - d2)),
                     (Value::Date(d), Value::Integer(n)) => Ok(Value::Date(d - *n as f64)),
-                    (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a - b)),
                     _ => {
                         let a = l.as_double()?;
                         let b = r.as_double()?;
@@ -3711,21 +5150,27 @@ impl Interpreter {
                     }
                 }
             }
-            Expression::Multiply(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                match (&l, &r) {
-                    (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a * b)),
-                    _ => {
-                        let a = l.as_double()?;
-                        let b = r.as_double()?;
-                        Ok(Value::Double(a * b))
-                    }
+            BinaryOp::Multiply => {
+                if let Some(result) = self.binary_operator("*", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("*", &l, &r)? {
+                    return Ok(result);
                 }
+                if let Some(result) = crate::integral::binary_operation("*", &l, &r)? {
+                    return Ok(result);
+                }
+                let a = l.as_double()?;
+                let b = r.as_double()?;
+                Ok(Value::Double(a * b))
             }
-            Expression::Divide(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+            BinaryOp::Divide => {
+                if let Some(result) = self.binary_operator("/", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("/", &l, &r)? {
+                    return Ok(result);
+                }
                 let a = l.as_double()?;
                 let b = r.as_double()?;
                 if b == 0.0 {
@@ -3733,9 +5178,13 @@ impl Interpreter {
                 }
                 Ok(Value::Double(a / b))
             }
-            Expression::IntegerDivide(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+            BinaryOp::IntegerDivide => {
+                if let Some(result) = self.binary_operator("\\", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("\\", &l, &r)? {
+                    return Ok(result);
+                }
                 let a = l.as_integer()?;
                 let b = r.as_integer()?;
                 if b == 0 {
@@ -3743,52 +5192,70 @@ impl Interpreter {
                 }
                 Ok(Value::Integer(a / b))
             }
-            Expression::Exponent(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                Ok(Value::Double(l.as_double()?.powf(r.as_double()?)))
-            }
-            Expression::Concatenate(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                Ok(Value::String(format!("{}{}", l.as_string(), r.as_string())))
+            BinaryOp::Modulo => {
+                if let Some(result) = self.binary_operator("Mod", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("Mod", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("Mod", &l, &r)? {
+                    return Ok(result);
+                }
+                let a = l.as_double()?;
+                let b = r.as_double()?;
+                if b == 0.0 { return Err(RuntimeError::DivisionByZero); }
+                Ok(Value::Double(a % b))
             }
-            Expression::Negate(operand) => {
-                let val = self.evaluate_expr(operand)?;
-                match val {
-                    Value::Integer(i) => Ok(Value::Integer(-i)),
-                    Value::Double(d) => Ok(Value::Double(-d)),
-                    _ => {
-                        let d = val.as_double()?;
-                        Ok(Value::Double(-d))
-                    }
+            BinaryOp::Exponent => {
+                if let Some(result) = self.binary_operator("^", &l, &r)? {
+                    return Ok(result);
                 }
+                Ok(Value::Double(l.as_double()?.powf(r.as_double()?)))
             }
-            Expression::Not(operand) => {
-                let val = self.evaluate_expr(operand)?;
-                match val {
-                    Value::Boolean(b) => Ok(Value::Boolean(!b)),
-                    _ => {
-                        let i = val.as_long()?;
-                        Ok(Value::Long(!i))
-                    }
+            BinaryOp::Concatenate => {
+                if let Some(result) = self.binary_operator("&", &l, &r)? {
+                    return Ok(result);
                 }
+                let text = format!("{}{}", l.as_string(), r.as_string());
+                self.budget.check_string(&text)?;
+                Ok(Value::String(text))
             }
-            // Comparison operators - must be handled here so inner expressions 
-            // (like obj field accesses) resolve via the interpreter context
-            Expression::Equal(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                Ok(Value::Boolean(values_equal(&l, &r)))
+            BinaryOp::Equal => {
+                if let Some(result) = self.binary_operator("=", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("=", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("=", &l, &r)? {
+                    return Ok(result);
+                }
+                Ok(Value::Boolean(self.equal_values(&l, &r)))
             }
-            Expression::NotEqual(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                Ok(Value::Boolean(!values_equal(&l, &r)))
+            BinaryOp::NotEqual => {
+                if let Some(result) = self.binary_operator("<>", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("<>", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("<>", &l, &r)? {
+                    return Ok(result);
+                }
+                Ok(Value::Boolean(!self.equal_values(&l, &r)))
             }
-            Expression::LessThan(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+            BinaryOp::LessThan => {
+                if let Some(result) = self.binary_operator("<", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("<", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("<", &l, &r)? {
+                    return Ok(result);
+                }
+                let (l, r) = (self.fold_case(l), self.fold_case(r));
                 let result = match (&l, &r) {
                     (Value::Integer(a), Value::Integer(b)) => a < b,
                     (Value::String(a), Value::String(b)) => a < b,
@@ -3797,9 +5264,17 @@ impl Interpreter {
                 };
                 Ok(Value::Boolean(result))
             }
-            Expression::LessThanOrEqual(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+            BinaryOp::LessThanOrEqual => {
+                if let Some(result) = self.binary_operator("<=", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation("<=", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("<=", &l, &r)? {
+                    return Ok(result);
+                }
+                let (l, r) = (self.fold_case(l), self.fold_case(r));
                 let result = match (&l, &r) {
                     (Value::Integer(a), Value::Integer(b)) => a <= b,
                     (Value::String(a), Value::String(b)) => a <= b,
@@ -3808,9 +5283,17 @@ impl Interpreter {
                 };
                 Ok(Value::Boolean(result))
             }
-            Expression::GreaterThan(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+            BinaryOp::GreaterThan => {
+                if let Some(result) = self.binary_operator(">", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation(">", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation(">", &l, &r)? {
+                    return Ok(result);
+                }
+                let (l, r) = (self.fold_case(l), self.fold_case(r));
                 let result = match (&l, &r) {
                     (Value::Integer(a), Value::Integer(b)) => a > b,
                     (Value::String(a), Value::String(b)) => a > b,
@@ -3819,9 +5302,17 @@ impl Interpreter {
                 };
                 Ok(Value::Boolean(result))
             }
-            Expression::GreaterThanOrEqual(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+            BinaryOp::GreaterThanOrEqual => {
+                if let Some(result) = self.binary_operator(">=", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::decimal::binary_operation(">=", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation(">=", &l, &r)? {
+                    return Ok(result);
+                }
+                let (l, r) = (self.fold_case(l), self.fold_case(r));
                 let result = match (&l, &r) {
                     (Value::Integer(a), Value::Integer(b)) => a >= b,
                     (Value::String(a), Value::String(b)) => a >= b,
@@ -3830,9 +5321,13 @@ impl Interpreter {
                 };
                 Ok(Value::Boolean(result))
             }
-            Expression::And(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
+            BinaryOp::And => {
+                if let Some(result) = self.binary_operator("And", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("And", &l, &r)? {
+                    return Ok(result);
+                }
                 match (&l, &r) {
                     (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(*a && *b)),
                     _ => {
@@ -3842,139 +5337,402 @@ impl Interpreter {
                     }
                 }
             }
-            Expression::Or(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                match (&l, &r) {
-                    (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(*a || *b)),
-                    _ => {
-                        let i_l = l.as_long()?;
-                        let i_r = r.as_long()?;
-                        Ok(Value::Long(i_l | i_r))
+            BinaryOp::Or => {
+                if let Some(result) = self.binary_operator("Or", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("Or", &l, &r)? {
+                    return Ok(result);
+                }
+                match (&l, &r) {
+                    (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(*a || *b)),
+                    _ => {
+                        let i_l = l.as_long()?;
+                        let i_r = r.as_long()?;
+                        Ok(Value::Long(i_l | i_r))
+                    }
+                }
+            }
+            BinaryOp::Xor => {
+                if let Some(result) = self.binary_operator("Xor", &l, &r)? {
+                    return Ok(result);
+                }
+                if let Some(result) = crate::integral::binary_operation("Xor", &l, &r)? {
+                    return Ok(result);
+                }
+                match (&l, &r) {
+                    (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(*a ^ *b)),
+                    _ => {
+                        let i_l = l.as_long()?;
+                        let i_r = r.as_long()?;
+                        Ok(Value::Long(i_l ^ i_r))
+                    }
+                }
+            }
+            BinaryOp::BitShiftLeft => {
+                if let Some(result) = self.binary_operator("<<", &l, &r)? {
+                    return Ok(result);
+                }
+                crate::integral::shift("<<", &l, &r)
+            }
+            BinaryOp::BitShiftRight => {
+                if let Some(result) = self.binary_operator(">>", &l, &r)? {
+                    return Ok(result);
+                }
+                crate::integral::shift(">>", &l, &r)
+            }
+            BinaryOp::Is => {
+                // Reference equality — for Nothing comparison and object identity
+                let result = match (&l, &r) {
+                    (Value::Nothing, Value::Nothing) => true,
+                    (Value::Nothing, _) | (_, Value::Nothing) => {
+                        matches!((&l, &r), (Value::Nothing, Value::Nothing))
+                    }
+                    (Value::Object(a), Value::Object(b)) => std::ptr::eq(&*a.borrow(), &*b.borrow()),
+                    _ => l == r,
+                };
+                Ok(Value::Boolean(result))
+            }
+            BinaryOp::IsNot => {
+                let result = match (&l, &r) {
+                    (Value::Nothing, Value::Nothing) => false,
+                    (Value::Nothing, _) | (_, Value::Nothing) => true,
+                    (Value::Object(a), Value::Object(b)) => !std::ptr::eq(&*a.borrow(), &*b.borrow()),
+                    _ => l != r,
+                };
+                Ok(Value::Boolean(result))
+            }
+            BinaryOp::Like => {
+                // VB.NET Like operator — basic pattern matching with *, ?, #, [charlist]
+                if let Some(result) = self.binary_operator("Like", &l, &r)? {
+                    return Ok(result);
+                }
+                let text = self.fold_case(l).as_string();
+                let pattern = self.fold_case(r).as_string();
+                let ignore_case = options::ignore_case(&self.active_options) != Some(false);
+                let result = vb_like_match(&text, &pattern, ignore_case);
+                Ok(Value::Boolean(result))
+            }
+            BinaryOp::AndAlso | BinaryOp::OrElse => {
+                let and = op == BinaryOp::AndAlso;
+                match self.short_circuit(and, &l)? {
+                    ShortCircuit::Done(result) => Ok(result),
+                    pending => self.finish_short_circuit(and, pending, &l, &r),
+                }
+            }
+        }
+    }
+
+    /// Unary `-`.
+    pub fn negate(&mut self, val: Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = self.user_operator("-", std::slice::from_ref(&val))? {
+            return Ok(result);
+        }
+        if let Some(result) = crate::integral::negate(&val)? {
+            return Ok(result);
+        }
+        match val {
+            Value::Double(d) => Ok(Value::Double(-d)),
+            Value::Decimal(d) => Ok(Value::Decimal(d.negate())),
+            _ => {
+                let d = val.as_double()?;
+                Ok(Value::Double(-d))
+            }
+        }
+    }
+
+    /// `Not`: logical on Booleans, bitwise on numbers.
+    pub fn not(&mut self, val: Value) -> Result<Value, RuntimeError> {
+        if let Some(result) = self.user_operator("Not", std::slice::from_ref(&val))? {
+            return Ok(result);
+        }
+        if let Some(result) = crate::integral::not(&val)? {
+            return Ok(result);
+        }
+        match val {
+            Value::Boolean(b) => Ok(Value::Boolean(!b)),
+            _ => {
+                let i = val.as_long()?;
+                Ok(Value::Long(!i))
+            }
+        }
+    }
+
+    /// `AndAlso` (`and`) or `OrElse` after its left operand `l`: whether `l`
+    /// decides the result alone or how the right operand combines with it.
+    fn short_circuit(&mut self, and: bool, l: &Value) -> Result<ShortCircuit, RuntimeError> {
+        // A type with IsFalse and And: x AndAlso y is x when IsFalse(x), else x And y
+        let test = if and { "IsFalse" } else { "IsTrue" };
+        if let Some(decided) = self.user_operator(test, std::slice::from_ref(l))? {
+            return Ok(if decided.as_bool()? { ShortCircuit::Done(l.clone()) } else { ShortCircuit::Operator });
+        }
+        if self.condition_is_true(l)? != and {
+            return Ok(ShortCircuit::Done(Value::Boolean(!and)));
+        }
+        Ok(ShortCircuit::Right)
+    }
+
+    /// The result of `l AndAlso r` / `l OrElse r` when `l` didn't decide it.
+    fn finish_short_circuit(&mut self, and: bool, pending: ShortCircuit, l: &Value, r: &Value) -> Result<Value, RuntimeError> {
+        if let ShortCircuit::Operator = pending
+            && let Some(result) = self.binary_operator(if and { "And" } else { "Or" }, l, r)?
+        {
+            return Ok(result);
+        }
+        Ok(Value::Boolean(self.condition_is_true(r)?))
+    }
+
+    pub fn evaluate_expr(&mut self, expr: &Expression) -> Result<Value, RuntimeError> {
+        let result = match &expr.kind {
+            // Most of what runs, and nothing nests inside them; a call they
+            // turn into checks the stack itself
+            ExprKind::Variable(_) | ExprKind::IntegerLiteral(_) | ExprKind::StringLiteral(_)
+            | ExprKind::DoubleLiteral(_) | ExprKind::BooleanLiteral(_) | ExprKind::Nothing => {
+                self.evaluate_expr_impl(expr)
+            }
+            _ => stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || self.evaluate_expr_impl(expr)),
+        };
+        // The innermost expression that failed places the error
+        result.map_err(|e| self.locate_error(e, expr.span))
+    }
+
+    fn evaluate_expr_impl(&mut self, expr: &Expression) -> Result<Value, RuntimeError> {
+        match &expr.kind {
+            ExprKind::Lambda { params, body, .. } => {
+                Ok(Value::Lambda {
+                    params: params.clone(),
+                    body: body.clone(),
+                    env: Rc::new(RefCell::new(self.env.clone())),
+                })
+            }
+            ExprKind::Call(name, args) => {
+                // 1. Check if name refers to a variable holding a Lambda or Array
+                // NOTE: We check the environment directly instead of using evaluate_expr(Variable(...))
+                // because Variable evaluation has an implicit function-call fallback that would
+                // execute the sub/function a first time, then call_function below would execute it again.
+                let var_name = name.as_str();
+                if let Ok(val) = self.env.get(var_name) {
+                   match val {
+                       Value::Lambda { .. } => {
+                           let arg_values: Result<Vec<_>, _> = args.iter().map(|e| self.evaluate_expr(e)).collect();
+                           return self.call_lambda(val, &arg_values?);
+                       }
+                       Value::Object(ref obj) if crate::delegates::is_delegate(&obj.borrow()) => {
+                           let arg_values: Result<Vec<_>, _> = args.iter().map(|e| self.evaluate_expr(e)).collect();
+                           return self.invoke_delegate(&val, &arg_values?);
+                       }
+                       Value::Array(arr) => {
+                           // Array access via Call syntax — supports multi-dimensional
+                           if args.len() == 1 {
+                               let index = self.evaluate_expr(&args[0])?.as_integer()? as usize;
+                               return arr.get(index).cloned().ok_or_else(|| RuntimeError::Custom("Array index out of bounds".to_string()));
+                           } else {
+                               // Multi-dim: drill into nested arrays
+                               let mut current = Value::Array(arr);
+                               for idx_expr in args.iter() {
+                                   let idx = self.evaluate_expr(idx_expr)?.as_integer()? as usize;
+                                   current = match current {
+                                       Value::Array(inner) => inner.get(idx).cloned().ok_or_else(|| {
+                                           RuntimeError::Custom("Array index out of bounds".to_string())
+                                       })?,
+                                       _ => return Err(RuntimeError::Custom("Cannot index non-array dimension".to_string())),
+                                   };
+                               }
+                               return Ok(current);
+                           }
+                       }
+                       Value::Dictionary(dict) => {
+                           // Dictionary access via Call syntax (e.g. dict("key"))
+                           if args.len() != 1 {
+                               return Err(RuntimeError::Custom("Dictionary index must be 1 key".to_string()));
+                           }
+                           let key = self.evaluate_expr(&args[0])?;
+                           return dict.borrow().item(&key);
+                       }
+                       Value::Collection(col) => {
+                           // Collection access via Call syntax (e.g. col("key") or col(0))
+                           if args.len() == 1 {
+                               let idx_val = self.evaluate_expr(&args[0])?;
+                               return match &idx_val {
+                                   Value::String(key) => col.borrow().item_by_key(key),
+                                   _ => {
+                                       let idx = idx_val.as_integer()? as usize;
+                                       col.borrow().item(idx)
+                                   }
+                               };
+                           }
+                       }
+                       _ => {} // Not a callable value, proceed to function lookup
+                   }
+                }
+                
+                // 2. Standard function lookup
+                let value = self.call_function(name, args)?;
+                self.budget.check_value(&value)?;
+                Ok(value)
+            }
+            ExprKind::Await(operand) => {
+                // Await evaluates the operand; if it's a Task, return its Result
+                let val = self.evaluate_expr(operand)?;
+                if let Value::Object(ref obj) = val {
+                    let b = obj.borrow();
+                    if b.class_name == "Task" {
+                        // Check if faulted
+                        if let Some(Value::Boolean(true)) = b.fields.get("isfaulted") {
+                            let msg = b.fields.get("exception").map(|v| v.as_string()).unwrap_or_else(|| "Task faulted".to_string());
+                            return Err(RuntimeError::Exception("AggregateException".to_string(), msg, None));
+                        }
+                        return Ok(b.fields.get("result").cloned().unwrap_or(Value::Nothing));
+                    }
+                }
+                Ok(val)
+            }
+            ExprKind::MethodCall(obj, method, args) => {
+                let value = match self.call_method(obj, method, args) {
+                    Ok(val) => val,
+                    Err(e) => {
+                        // Fallback: try extension methods
+                        if let Ok(result) = self.try_extension_method(obj, method, args) {
+                            result
+                        } else {
+                            return Err(e);
+                        }
                     }
-                }
+                };
+                self.budget.check_value(&value)?;
+                Ok(value)
             }
-            Expression::Xor(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                match (&l, &r) {
-                    (Value::Boolean(a), Value::Boolean(b)) => Ok(Value::Boolean(*a ^ *b)),
-                    _ => {
-                        let i_l = l.as_long()?;
-                        let i_r = r.as_long()?;
-                        Ok(Value::Long(i_l ^ i_r))
+            ExprKind::ArrayAccess(array, indices) => {
+                let arr_val = self.env.get(array.as_str())?;
+                match arr_val {
+                    Value::Array(arr) => {
+                        if indices.len() == 1 {
+                            let index = self.evaluate_expr(&indices[0])?.as_integer()? as usize;
+                            arr.get(index).cloned().ok_or_else(|| RuntimeError::Custom("Array index out of bounds".to_string()))
+                        } else {
+                            // Multi-dimensional: drill into nested arrays
+                            let mut current = Value::Array(arr);
+                            for idx_expr in indices {
+                                let idx = self.evaluate_expr(idx_expr)?.as_integer()? as usize;
+                                current = match current {
+                                    Value::Array(inner) => inner.get(idx).cloned().ok_or_else(|| {
+                                        RuntimeError::Custom("Array index out of bounds".to_string())
+                                    })?,
+                                    _ => return Err(RuntimeError::Custom("Cannot index non-array dimension".to_string())),
+                                };
+                            }
+                            Ok(current)
+                        }
+                    }
+                    Value::Collection(col) => {
+                        let idx_val = self.evaluate_expr(&indices[0])?;
+                        match &idx_val {
+                            Value::String(key) => {
+                                col.borrow().item_by_key(key)
+                            }
+                            _ => {
+                                // VB.NET Collection is 1-based, but ArrayList is 0-based.
+                                // We use 0-based here since our Collection wraps ArrayList.
+                                let index = idx_val.as_integer()? as usize;
+                                col.borrow().item(index)
+                            }
+                        }
+                    }
+                    Value::Dictionary(dict) => {
+                        if indices.len() != 1 {
+                            return Err(RuntimeError::Custom("Dictionary index must be 1 key".to_string()));
+                        }
+                        let key = self.evaluate_expr(&indices[0])?;
+                        return dict.borrow().item(&key);
                     }
+                    _ => Err(RuntimeError::Custom(format!("Type is not indexable: {:?}", arr_val))),
                 }
             }
-            Expression::AndAlso(left, right) => {
-                // Short-circuit: if left is false, don't evaluate right
-                let l = self.evaluate_expr(left)?;
-                if !l.as_bool()? {
-                    return Ok(Value::Boolean(false));
-                }
-                let r = self.evaluate_expr(right)?;
-                Ok(Value::Boolean(r.as_bool()?))
+            ExprKind::ArrayLiteral(elements) => {
+                let vals: Result<Vec<Value>, RuntimeError> = elements
+                    .iter()
+                    .map(|e| self.evaluate_expr(e))
+                    .collect();
+                Ok(Value::Array(vals?))
             }
-            Expression::OrElse(left, right) => {
-                // Short-circuit: if left is true, don't evaluate right
-                let l = self.evaluate_expr(left)?;
-                if l.as_bool()? {
-                    return Ok(Value::Boolean(true));
-                }
-                let r = self.evaluate_expr(right)?;
-                Ok(Value::Boolean(r.as_bool()?))
+            ExprKind::Query(query) => self.execute_query(query),
+            ExprKind::XmlLiteral(node) => self.construct_xml(node),
+            ExprKind::XmlAxis(target, axis, name) => {
+                let target = self.evaluate_expr(target)?;
+                Ok(crate::builtins::xml::axis(&target, *axis, &xml_name(name)))
             }
-            Expression::Is(left, right) => {
-                // Reference equality — for Nothing comparison and object identity
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                let result = match (&l, &r) {
-                    (Value::Nothing, Value::Nothing) => true,
-                    (Value::Nothing, _) | (_, Value::Nothing) => {
-                        matches!((&l, &r), (Value::Nothing, Value::Nothing))
-                    }
-                    (Value::Object(a), Value::Object(b)) => std::ptr::eq(&*a.borrow(), &*b.borrow()),
-                    _ => l == r,
-                };
-                Ok(Value::Boolean(result))
+            // For all other expressions that might contain nested calls, evaluate through interpreter
+            ExprKind::Add(left, right) => self.evaluate_binary(BinaryOp::Add, left, right),
+            ExprKind::Subtract(left, right) => self.evaluate_binary(BinaryOp::Subtract, left, right),
+            ExprKind::Multiply(left, right) => self.evaluate_binary(BinaryOp::Multiply, left, right),
+            ExprKind::Divide(left, right) => self.evaluate_binary(BinaryOp::Divide, left, right),
+            ExprKind::IntegerDivide(left, right) => self.evaluate_binary(BinaryOp::IntegerDivide, left, right),
+            ExprKind::Exponent(left, right) => self.evaluate_binary(BinaryOp::Exponent, left, right),
+            ExprKind::Concatenate(left, right) => self.evaluate_binary(BinaryOp::Concatenate, left, right),
+            ExprKind::Negate(operand) => {
+                let val = self.evaluate_expr(operand)?;
+                self.negate(val)
             }
-            Expression::IsNot(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                let result = match (&l, &r) {
-                    (Value::Nothing, Value::Nothing) => false,
-                    (Value::Nothing, _) | (_, Value::Nothing) => true,
-                    (Value::Object(a), Value::Object(b)) => !std::ptr::eq(&*a.borrow(), &*b.borrow()),
-                    _ => l != r,
-                };
-                Ok(Value::Boolean(result))
+            ExprKind::Not(operand) => {
+                let val = self.evaluate_expr(operand)?;
+                self.not(val)
             }
-            Expression::Like(left, right) => {
-                // VB.NET Like operator — basic pattern matching with *, ?, #, [charlist]
+            // Comparison operators - must be handled here so inner expressions 
+            // (like obj field accesses) resolve via the interpreter context
+            ExprKind::Equal(left, right) => self.evaluate_binary(BinaryOp::Equal, left, right),
+            ExprKind::NotEqual(left, right) => self.evaluate_binary(BinaryOp::NotEqual, left, right),
+            ExprKind::LessThan(left, right) => self.evaluate_binary(BinaryOp::LessThan, left, right),
+            ExprKind::LessThanOrEqual(left, right) => self.evaluate_binary(BinaryOp::LessThanOrEqual, left, right),
+            ExprKind::GreaterThan(left, right) => self.evaluate_binary(BinaryOp::GreaterThan, left, right),
+            ExprKind::GreaterThanOrEqual(left, right) => self.evaluate_binary(BinaryOp::GreaterThanOrEqual, left, right),
+            ExprKind::And(left, right) => self.evaluate_binary(BinaryOp::And, left, right),
+            ExprKind::Or(left, right) => self.evaluate_binary(BinaryOp::Or, left, right),
+            ExprKind::Xor(left, right) => self.evaluate_binary(BinaryOp::Xor, left, right),
+            ExprKind::AndAlso(left, right) | ExprKind::OrElse(left, right) => {
+                // Short-circuit: the right operand is evaluated only if the left doesn't decide
+                let and = matches!(&expr.kind, ExprKind::AndAlso(..));
                 let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                let text = l.as_string();
-                let pattern = r.as_string();
-                let result = vb_like_match(&text, &pattern);
-                Ok(Value::Boolean(result))
+                match self.short_circuit(and, &l)? {
+                    ShortCircuit::Done(result) => Ok(result),
+                    pending => {
+                        let r = self.evaluate_expr(right)?;
+                        self.finish_short_circuit(and, pending, &l, &r)
+                    }
+                }
             }
-            Expression::TypeOf { expr, type_name } => {
+            ExprKind::Is(left, right) => self.evaluate_binary(BinaryOp::Is, left, right),
+            ExprKind::IsNot(left, right) => self.evaluate_binary(BinaryOp::IsNot, left, right),
+            ExprKind::Like(left, right) => self.evaluate_binary(BinaryOp::Like, left, right),
+            ExprKind::TypeOf { expr, type_name } => {
                 let val = self.evaluate_expr(expr)?;
-                let tn = type_name.trim();
+                let tn = self.resolve_type_name(type_name.trim());
+                let tn = tn.as_str();
                 let result = match &val {
-                    Value::Object(obj) => {
-                        let b = obj.borrow();
-                        self.is_type_or_base(&b.class_name, tn)
-                    }
+                    Value::Object(_) => self.is_instance_of(&val, tn),
                     Value::String(_) => tn.eq_ignore_ascii_case("String"),
-                    Value::Integer(_) => tn.eq_ignore_ascii_case("Integer") || tn.eq_ignore_ascii_case("Int32"),
-                    Value::Long(_) => tn.eq_ignore_ascii_case("Long") || tn.eq_ignore_ascii_case("Int64"),
+                    v if crate::integral::IntType::of(v).is_some() => crate::integral::IntType::of(v) == crate::integral::IntType::from_name(tn),
                     Value::Double(_) => tn.eq_ignore_ascii_case("Double"),
+                    Value::Decimal(_) => tn.eq_ignore_ascii_case("Decimal"),
                     Value::Boolean(_) => tn.eq_ignore_ascii_case("Boolean"),
                     Value::Nothing => false,
                     _ => false,
                 };
                 Ok(Value::Boolean(result))
             }
-            Expression::BitShiftLeft(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                let val = l.as_long()?;
-                let shift = r.as_integer()?;
-                // Rust panics if shift > 64, need to handle?
-                // VB wraps or uses mask? VB: "The shift amount is masked to the size of the data type"
-                // Long: count And 63. Integer: count And 31.
-                // We treat everything as Long here basically.
-                let shift_masked = shift & 63;
-                Ok(Value::Long(val << shift_masked))
-            }
-            Expression::BitShiftRight(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                let val = l.as_long()?;
-                let shift = r.as_integer()?;
-                let shift_masked = shift & 63;
-                Ok(Value::Long(val >> shift_masked))
-            }
-            Expression::Modulo(left, right) => {
-                let l = self.evaluate_expr(left)?;
-                let r = self.evaluate_expr(right)?;
-                let a = l.as_integer()?;
-                let b = r.as_integer()?;
-                if b == 0 { return Err(RuntimeError::DivisionByZero); }
-                Ok(Value::Integer(a % b))
-            }
-            Expression::Me => {
+            ExprKind::BitShiftLeft(left, right) => self.evaluate_binary(BinaryOp::BitShiftLeft, left, right),
+            ExprKind::BitShiftRight(left, right) => self.evaluate_binary(BinaryOp::BitShiftRight, left, right),
+            ExprKind::Modulo(left, right) => self.evaluate_binary(BinaryOp::Modulo, left, right),
+            ExprKind::Me => {
                 if let Some(obj_rc) = &self.current_object {
                     Ok(Value::Object(obj_rc.clone()))
                 } else {
                     Err(RuntimeError::Custom("'Me' used outside of object context".to_string()))
                 }
             }
-            Expression::MyBase => {
+            ExprKind::MyBase => {
                 // MyBase refers to the same object as Me, but method
                 // dispatch starts from the parent class.  We return the
-                // same Rc; call_method checks for Expression::MyBase to
+                // same Rc; call_method checks for ExprKind::MyBase to
                 // resolve the method from the base class.
                 if let Some(obj_rc) = &self.current_object {
                     Ok(Value::Object(obj_rc.clone()))
@@ -3982,19 +5740,45 @@ impl Interpreter {
                     Err(RuntimeError::Custom("'MyBase' used outside of object context".to_string()))
                 }
             }
-            Expression::WithTarget => {
+            ExprKind::WithTarget => {
                 if let Some(val) = &self.with_object {
                     Ok(val.clone())
                 } else {
                     Err(RuntimeError::Custom("'.' used outside of With block".to_string()))
                 }
             }
-            Expression::Cast { expr, .. } => {
-                // CType/DirectCast/TryCast — in our dynamically typed interpreter,
-                // just evaluate the inner expression (the cast is a no-op at runtime).
-                self.evaluate_expr(expr)
+            ExprKind::Cast { kind, expr, target_type } => {
+                // CType/DirectCast/TryCast — in our dynamically typed interpreter the
+                // cast is a no-op, except that an object cast to a user class must be
+                // an instance of it (including its type arguments, for generics).
+                // CType also applies a user-defined conversion operator.
+                let val = self.evaluate_expr(expr)?;
+                if *kind == vybe_parser::ast::expr::CastKind::CType {
+                    let target = self.resolve_type_name(target_type.trim());
+                    let converted = if self.is_instance_of(&val, &target) { None } else { self.user_conversion(&val, &target, ALL_CONVERSIONS)? };
+                    if let Some(converted) = converted {
+                        return Ok(converted);
+                    }
+                }
+                if let Value::Object(obj) = &val {
+                    let target = self.resolve_type_name(target_type.trim());
+                    let target_base = crate::generics::split_type_arguments(&target).map_or(target.as_str(), |(base, _)| base);
+                    let obj_is_user_class = self.resolve_class_key(&obj.borrow().class_name).is_some();
+                    if obj_is_user_class && self.resolve_class_key(target_base).is_some() && !self.is_instance_of(&val, &target) {
+                        if *kind == vybe_parser::ast::expr::CastKind::TryCast {
+                            return Ok(Value::Nothing);
+                        }
+                        let from = crate::generics::value_type_name(&val);
+                        return Err(RuntimeError::Exception(
+                            "InvalidCastException".to_string(),
+                            format!("Unable to cast object of type '{}' to type '{}'.", from, target),
+                            None,
+                        ));
+                    }
+                }
+                Ok(val)
             }
-            Expression::IfExpression(first, second, third) => {
+            ExprKind::IfExpression(first, second, third) => {
                 if let Some(false_expr) = third {
                     // Ternary: If(condition, trueValue, falseValue)
                     let cond = self.evaluate_expr(first)?;
@@ -4012,11 +5796,19 @@ impl Interpreter {
                     }
                 }
             }
-            Expression::AddressOf(name) => {
-                // For now, store as a string value — delegates are not fully supported
-                Ok(Value::String(format!("AddressOf:{}", name)))
-            }
-            Expression::New(class_id, ctor_args) => {
+            ExprKind::AddressOf(name) => self.delegate_for(name),
+            ExprKind::New(class_id, ctor_args) => {
+                // Inside generic code, New T() / New List(Of T) use the bound types
+                let resolved_id;
+                let class_id = if self.type_args.is_empty() {
+                    class_id
+                } else {
+                    resolved_id = Identifier::new(self.resolve_type_name(class_id.as_str()));
+                    if crate::generics::is_value_type_name(resolved_id.as_str()) {
+                        return Ok(default_value_for_type("", &Some(vybe_parser::VBType::from_str(resolved_id.as_str()))));
+                    }
+                    &resolved_id
+                };
                 // Strip generic suffix: "List(Of String)" → "list"
                 let class_name_full = class_id.as_str().to_lowercase();
                 let class_name = if let Some(idx) = class_name_full.find("(of ") {
@@ -4026,6 +5818,11 @@ impl Interpreter {
                 } else {
                     class_name_full
                 };
+                // A program's own Point or Size structure shadows System.Drawing's
+                let user_declared = self.resolve_class_key(&class_name).is_some();
+                if !user_declared {
+                    self.sandbox.check_new(&class_name)?;
+                }
 
                 // ===== XML CONSTRUCTORS: XDocument, XElement, XAttribute =====
                 if class_name == "xelement" || class_name == "system.xml.linq.xelement" {
@@ -4273,6 +6070,7 @@ impl Interpreter {
                 if class_name == "filestream" || class_name == "system.io.filestream" {
                     let arg_values: Result<Vec<_>, _> = ctor_args.iter().map(|e| self.evaluate_expr(e)).collect();
                     let arg_values = arg_values?;
+                    self.sandbox.check_paths(&arg_values, &[0])?;
                     let path = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                     let mode = arg_values.get(1).and_then(|v| v.as_integer().ok()).unwrap_or(3); // 3=OpenOrCreate
                     let access = arg_values.get(2).and_then(|v| v.as_integer().ok()).unwrap_or(3); // 3=ReadWrite
@@ -4519,7 +6317,7 @@ impl Interpreter {
                 }
 
                 // ===== SYSTEM.DRAWING.POINT =====
-                if class_name == "point" || class_name == "system.drawing.point" {
+                if (class_name == "point" && !user_declared) || class_name == "system.drawing.point" {
                     let arg_values: Result<Vec<_>, _> = ctor_args.iter().map(|e| self.evaluate_expr(e)).collect();
                     let arg_values = arg_values?;
                     let x = arg_values.get(0).and_then(|v| v.as_integer().ok()).unwrap_or(0);
@@ -4534,7 +6332,7 @@ impl Interpreter {
                 }
 
                 // ===== SYSTEM.DRAWING.SIZE =====
-                if class_name == "size" || class_name == "system.drawing.size" {
+                if (class_name == "size" && !user_declared) || class_name == "system.drawing.size" {
                     let arg_values: Result<Vec<_>, _> = ctor_args.iter().map(|e| self.evaluate_expr(e)).collect();
                     let arg_values = arg_values?;
                     let w = arg_values.get(0).and_then(|v| v.as_integer().ok()).unwrap_or(0);
@@ -4549,7 +6347,7 @@ impl Interpreter {
                 }
 
                 // ===== SYSTEM.DRAWING.RECTANGLE =====
-                if class_name == "rectangle" || class_name == "system.drawing.rectangle" {
+                if (class_name == "rectangle" && !user_declared) || class_name == "system.drawing.rectangle" {
                     let arg_values: Result<Vec<_>, _> = ctor_args.iter().map(|e| self.evaluate_expr(e)).collect();
                     let arg_values = arg_values?;
                     let x = arg_values.get(0).and_then(|v| v.as_integer().ok()).unwrap_or(0);
@@ -4593,7 +6391,7 @@ impl Interpreter {
                 }
 
                 // ===== SYSTEM.DRAWING.COLOR =====
-                if class_name == "color" || class_name == "system.drawing.color" {
+                if (class_name == "color" && !user_declared) || class_name == "system.drawing.color" {
                     // Color.FromArgb is handled separately; New Color() returns Empty
                     let mut fields = std::collections::HashMap::new();
                     fields.insert("__type".to_string(), Value::String("Color".to_string()));
@@ -4913,6 +6711,7 @@ impl Interpreter {
                 if class_name == "fileinfo" || class_name == "system.io.fileinfo" {
                     let arg_values: Result<Vec<_>, _> = ctor_args.iter().map(|e| self.evaluate_expr(e)).collect();
                     let arg_values = arg_values?;
+                    self.sandbox.check_paths(&arg_values, &[0])?;
                     let path = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                     let p = std::path::Path::new(&path);
                     let meta = std::fs::metadata(&path);
@@ -4938,6 +6737,7 @@ impl Interpreter {
                 if class_name == "directoryinfo" || class_name == "system.io.directoryinfo" {
                     let arg_values: Result<Vec<_>, _> = ctor_args.iter().map(|e| self.evaluate_expr(e)).collect();
                     let arg_values = arg_values?;
+                    self.sandbox.check_paths(&arg_values, &[0])?;
                     let path = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                     let p = std::path::Path::new(&path);
                     let mut fields = std::collections::HashMap::new();
@@ -5018,6 +6818,7 @@ impl Interpreter {
                     }
                     // File-backed StreamReader
                     let path = arg0.as_string();
+                    self.sandbox.check_path(&path)?;
                     let content = std::fs::read_to_string(&path)
                         .map_err(|e| RuntimeError::Custom(format!("StreamReader: {}", e)))?;
                     fields.insert("__content".to_string(), Value::String(content));
@@ -5051,6 +6852,7 @@ impl Interpreter {
                     }
                     // File-backed StreamWriter
                     let path = arg0.as_string();
+                    self.sandbox.check_path(&path)?;
                     let append = if ctor_args.len() >= 2 {
                         self.evaluate_expr(&ctor_args[1])?.as_bool()?
                     } else {
@@ -5260,8 +7062,36 @@ impl Interpreter {
                         )));
                     }
 
-                    // Collect fields from hierarchy
-                    let fields = self.collect_fields(&class_name);
+                    // Type arguments of a generic class: New Box(Of Integer)(...)
+                    let type_args = crate::generics::split_type_arguments(class_id.as_str())
+                        .map(|(_, args)| args)
+                        .unwrap_or_default();
+                    if type_args.len() != class_decl.type_parameters.len() {
+                        return Err(RuntimeError::Exception(
+                            "ArgumentException".to_string(),
+                            format!(
+                                "Wrong number of type arguments to '{}': expected {}, got {}",
+                                class_decl.name.as_str(), class_decl.type_parameters.len(), type_args.len()
+                            ),
+                            None,
+                        ));
+                    }
+                    self.check_type_constraints(class_decl.name.as_str(), &class_decl.type_parameters, &type_args)?;
+                    let chain = self.generic_chain(class_decl.name.as_str(), type_args.clone());
+                    let is_generic = chain.iter().any(|(_, params, _)| !params.is_empty());
+                    let mut instance_type_args = TypeArgs::new();
+                    for (_, params, args) in chain.iter().rev() {
+                        bind_type_args(&mut instance_type_args, params, args);
+                    }
+
+                    // Collect fields from hierarchy (As T fields default per the type arguments)
+                    let prev_type_args = std::mem::replace(&mut self.type_args, instance_type_args);
+                    let mut fields = self.collect_fields(&class_name);
+                    self.type_args = prev_type_args;
+                    if is_generic {
+                        let args = type_args.into_iter().map(Value::String).collect();
+                        fields.insert(crate::generics::TYPE_ARGS_FIELD.to_string(), Value::Array(args));
+                    }
 
                     let obj_data = crate::value::ObjectData { drawing_commands: Vec::new(),
                         class_name: class_decl.name.as_str().to_string(),
@@ -5299,12 +7129,15 @@ impl Interpreter {
                             }
                         }
 
-                        match method {
-                            vybe_parser::ast::decl::MethodDecl::Sub(s) => {
-                                self.call_user_sub(&s, &arg_values, Some(obj_ref.clone()))?;
-                            }
-                            vybe_parser::ast::decl::MethodDecl::Function(f) => {
-                                self.call_user_function(&f, &arg_values, Some(obj_ref.clone()))?;
+                        if self.call_named_overload(&method, ctor_args, &arg_values, Some(obj_ref.clone()))?.is_none() {
+                            let arg_values = self.arrange_values(&method, ctor_args, arg_values)?;
+                            match method {
+                                vybe_parser::ast::decl::MethodDecl::Sub(s) => {
+                                    self.call_user_sub(&s, &arg_values, Some(obj_ref.clone()))?;
+                                }
+                                vybe_parser::ast::decl::MethodDecl::Function(f) => {
+                                    self.call_user_function(&f, &arg_values, Some(obj_ref.clone()))?;
+                                }
                             }
                         }
                     } else {
@@ -5334,16 +7167,16 @@ impl Interpreter {
 
                     Ok(Value::Object(obj_ref))
                 } else {
-                    println!("DEBUG: Expression::New failed to resolve class '{}'. Available classes keys: {:?}", class_name, self.classes.keys().collect::<Vec<_>>());
+                    println!("DEBUG: ExprKind::New failed to resolve class '{}'. Available classes keys: {:?}", class_name, self.classes.keys().collect::<Vec<_>>());
                     // If the class is unknown, return Nothing instead of error to keep VB code running
                     return Ok(Value::Nothing);
                 }
             }
 
-            Expression::NewFromInitializer(class_id, ctor_args, init_elements) => {
+            ExprKind::NewFromInitializer(class_id, ctor_args, init_elements) => {
                 // New List(Of T) From { expr, expr, ... }
                 // Create the collection then add each element
-                let obj = self.evaluate_expr(&Expression::New(class_id.clone(), ctor_args.clone()))?;
+                let obj = self.evaluate_expr(&ExprKind::New(class_id.clone(), ctor_args.clone()).into())?;
                 for elem_expr in init_elements {
                     let elem_val = self.evaluate_expr(elem_expr)?;
                     // Add to the collection
@@ -5369,20 +7202,43 @@ impl Interpreter {
                 Ok(obj)
             }
 
-            Expression::NewWithInitializer(class_id, ctor_args, members) => {
+            ExprKind::NewWithInitializer(class_id, ctor_args, members) => {
                 // New Type() With { .Prop = expr, ... }
                 // Create the object then set each property
-                let obj = self.evaluate_expr(&Expression::New(class_id.clone(), ctor_args.clone()))?;
+                let obj = self.evaluate_expr(&ExprKind::New(class_id.clone(), ctor_args.clone()).into())?;
                 if let Value::Object(obj_ref) = &obj {
                     for (prop_name, prop_expr) in members {
                         let val = self.evaluate_expr(prop_expr)?;
-                        obj_ref.borrow_mut().fields.insert(prop_name.to_lowercase(), val);
+                        obj_ref.borrow_mut().set_field(&Symbol::intern(prop_name).name(), val);
                     }
                 }
                 Ok(obj)
             }
             
-            Expression::MemberAccess(obj, member) => {
+            ExprKind::NewAnonymous(members) => {
+                let mut fields = Vec::with_capacity(members.len());
+                for (name, expr) in members {
+                    fields.push((name.clone(), self.evaluate_expr(expr)?));
+                }
+                Ok(query::anonymous_object(fields))
+            }
+
+            // A named argument where its call could not place it by name
+            ExprKind::NamedArgument(_, value) => self.evaluate_expr(value),
+
+            ExprKind::TupleLiteral(elements) => {
+                let mut values = Vec::with_capacity(elements.len());
+                for (name, expr) in elements {
+                    values.push((name.clone(), crate::tuples::copy(self.evaluate_expr(expr)?)));
+                }
+                Ok(crate::tuples::tuple_object(values))
+            }
+
+            ExprKind::MemberAccess(obj, member) => {
+                self.check_late_binding(obj, member.as_str())?;
+                // Interned, so the lowercase name isn't allocated again on every access
+                let member_name = Symbol::intern(member.as_str()).name();
+                let member_lower: &str = &member_name;
                 // Handle known WinForms enum/namespace values from designer code
                 let full_path = format!("{}.{}", self.expr_to_string(obj), member.as_str()).to_lowercase();
                 if full_path.contains("system.windows.forms.") 
@@ -5465,11 +7321,23 @@ impl Interpreter {
                     "string.empty" | "system.string.empty" => return Ok(Value::String(String::new())),
                     // EventArgs.Empty
                     "eventargs.empty" | "system.eventargs.empty" => return Ok(Self::make_event_args()),
-                    // Int32/Double limits
+                    // Integral and floating-point limits
                     "integer.maxvalue" | "int32.maxvalue" | "system.int32.maxvalue" => return Ok(Value::Integer(i32::MAX)),
                     "integer.minvalue" | "int32.minvalue" | "system.int32.minvalue" => return Ok(Value::Integer(i32::MIN)),
                     "long.maxvalue" | "int64.maxvalue" | "system.int64.maxvalue" => return Ok(Value::Long(i64::MAX)),
                     "long.minvalue" | "int64.minvalue" | "system.int64.minvalue" => return Ok(Value::Long(i64::MIN)),
+                    "sbyte.maxvalue" | "system.sbyte.maxvalue" => return Ok(Value::SByte(i8::MAX)),
+                    "sbyte.minvalue" | "system.sbyte.minvalue" => return Ok(Value::SByte(i8::MIN)),
+                    "byte.maxvalue" | "system.byte.maxvalue" => return Ok(Value::Byte(u8::MAX)),
+                    "byte.minvalue" | "system.byte.minvalue" => return Ok(Value::Byte(u8::MIN)),
+                    "short.maxvalue" | "int16.maxvalue" | "system.int16.maxvalue" => return Ok(Value::Short(i16::MAX)),
+                    "short.minvalue" | "int16.minvalue" | "system.int16.minvalue" => return Ok(Value::Short(i16::MIN)),
+                    "ushort.maxvalue" | "uint16.maxvalue" | "system.uint16.maxvalue" => return Ok(Value::UShort(u16::MAX)),
+                    "ushort.minvalue" | "uint16.minvalue" | "system.uint16.minvalue" => return Ok(Value::UShort(u16::MIN)),
+                    "uinteger.maxvalue" | "uint32.maxvalue" | "system.uint32.maxvalue" => return Ok(Value::UInteger(u32::MAX)),
+                    "uinteger.minvalue" | "uint32.minvalue" | "system.uint32.minvalue" => return Ok(Value::UInteger(u32::MIN)),
+                    "ulong.maxvalue" | "uint64.maxvalue" | "system.uint64.maxvalue" => return Ok(Value::ULong(u64::MAX)),
+                    "ulong.minvalue" | "uint64.minvalue" | "system.uint64.minvalue" => return Ok(Value::ULong(u64::MIN)),
                     "double.maxvalue" | "system.double.maxvalue" => return Ok(Value::Double(f64::MAX)),
                     "double.minvalue" | "system.double.minvalue" => return Ok(Value::Double(f64::MIN)),
                     "double.nan" | "system.double.nan" => return Ok(Value::Double(f64::NAN)),
@@ -5477,6 +7345,10 @@ impl Interpreter {
                     "double.negativeinfinity" | "system.double.negativeinfinity" => return Ok(Value::Double(f64::NEG_INFINITY)),
                     "single.maxvalue" | "system.single.maxvalue" => return Ok(Value::Single(f32::MAX)),
                     "single.minvalue" | "system.single.minvalue" => return Ok(Value::Single(f32::MIN)),
+                    "decimal.maxvalue" | "system.decimal.maxvalue" => return Ok(Value::Decimal(crate::decimal::Decimal::MAX)),
+                    "decimal.minvalue" | "system.decimal.minvalue" => return Ok(Value::Decimal(crate::decimal::Decimal::MIN)),
+                    "decimal.zero" | "system.decimal.zero" => return Ok(Value::Decimal(crate::decimal::Decimal::ZERO)),
+                    "decimal.one" | "system.decimal.one" => return Ok(Value::Decimal(crate::decimal::Decimal::from_i64(1))),
                     // TimeSpan.Zero
                     "timespan.zero" | "system.timespan.zero" => {
                         let obj_data = crate::value::ObjectData { drawing_commands: Vec::new(),
@@ -5618,8 +7490,7 @@ impl Interpreter {
                 
                 // Collection Properties
                 if let Value::Collection(col_rc) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    match m.as_str() {
+                    match member_lower {
                         "count" => return Ok(Value::Integer(col_rc.borrow().count())),
                         "capacity" => return Ok(Value::Integer(col_rc.borrow().capacity())),
                         "isfixedsize" | "isreadonly" => return Ok(Value::Boolean(false)),
@@ -5629,27 +7500,17 @@ impl Interpreter {
                 }
 
                 // Queue Properties
-                if let Value::Queue(q) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    if m == "count" { return Ok(Value::Integer(q.borrow().count())); }
-                }
+                if let Value::Queue(q) = &obj_val && member_lower == "count" { return Ok(Value::Integer(q.borrow().count())); }
 
                 // Stack Properties
-                if let Value::Stack(s) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    if m == "count" { return Ok(Value::Integer(s.borrow().count())); }
-                }
+                if let Value::Stack(s) = &obj_val && member_lower == "count" { return Ok(Value::Integer(s.borrow().count())); }
 
                 // HashSet Properties
-                if let Value::HashSet(h) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    if m == "count" { return Ok(Value::Integer(h.borrow().count())); }
-                }
+                if let Value::HashSet(h) = &obj_val && member_lower == "count" { return Ok(Value::Integer(h.borrow().count())); }
 
                 // Dictionary Properties
                 if let Value::Dictionary(d) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    match m.as_str() {
+                    match member_lower {
                         "count" => return Ok(Value::Integer(d.borrow().count())),
                         "keys" => return Ok(Value::Array(d.borrow().keys())),
                         "values" => return Ok(Value::Array(d.borrow().values())),
@@ -5659,8 +7520,7 @@ impl Interpreter {
 
                 // ConcurrentDictionary Properties
                 if let Value::ConcurrentDictionary(d) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    match m.as_str() {
+                    match member_lower {
                         "count" => return Ok(Value::Integer(d.count())),
                         "keys" => return Ok(Value::Array(d.keys().into_iter().map(Value::String).collect())),
                         "values" => return Ok(Value::Array(d.values())),
@@ -5669,39 +7529,26 @@ impl Interpreter {
                 }
 
                 // ConcurrentQueue Properties
-                if let Value::ConcurrentQueue(q) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    if m == "count" { return Ok(Value::Integer(q.count())); }
-                }
+                if let Value::ConcurrentQueue(q) = &obj_val && member_lower == "count" { return Ok(Value::Integer(q.count())); }
 
                 // ConcurrentStack Properties
-                if let Value::ConcurrentStack(s) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    if m == "count" { return Ok(Value::Integer(s.count())); }
-                }
+                if let Value::ConcurrentStack(s) = &obj_val && member_lower == "count" { return Ok(Value::Integer(s.count())); }
 
                 // Array Properties (Length, Count)
-                if let Value::Array(arr) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    if m == "length" || m == "count" {
-                        return Ok(Value::Integer(arr.len() as i32));
-                    }
+                if let Value::Array(arr) = &obj_val && (member_lower == "length" || member_lower == "count") {
+                    return Ok(Value::Integer(arr.len() as i32));
                 }
 
                 // String Properties (Length, Chars)
-                if let Value::String(s) = &obj_val {
-                    let m = member.as_str().to_lowercase();
-                    if m == "length" {
-                        return Ok(Value::Integer(s.len() as i32));
-                    }
+                if let Value::String(s) = &obj_val && member_lower == "length" {
+                    return Ok(Value::Integer(s.len() as i32));
                 }
 
                 // DateTime Properties (Year, Month, Day, etc.) — OLE f64
                 if let Value::Date(ole_val) = &obj_val {
                     let ndt = ole_to_dt(*ole_val);
                     use chrono::{Datelike, Timelike, NaiveDate};
-                    let m = member.as_str().to_lowercase();
-                    match m.as_str() {
+                    match member_lower {
                         "year" => return Ok(Value::Integer(ndt.year())),
                         "month" => return Ok(Value::Integer(ndt.month() as i32)),
                         "day" => return Ok(Value::Integer(ndt.day() as i32)),
@@ -5747,6 +7594,13 @@ impl Interpreter {
                 if crate::builtins::xml::is_xml_object(&obj_val) {
                     return crate::builtins::xml::xml_property_access(&obj_val, member.as_str());
                 }
+                // doc...<price>.Value reads the first element's
+                if let Value::Array(items) = &obj_val
+                    && member.as_str().eq_ignore_ascii_case("value")
+                    && let Some(value) = crate::builtins::xml::sequence_value(items)
+                {
+                    return Ok(value);
+                }
 
                 if let Value::Object(obj_ref) = &obj_val {
                     let class_name_str;
@@ -5793,11 +7647,10 @@ impl Interpreter {
 
                         // DbRecordset properties (ADODB)
                         if db_type == "DbRecordset" {
-                            let m = member.as_str().to_lowercase();
                             let rs_id = obj_data.fields.get("__rs_id")
                                 .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
                                 .unwrap_or(0);
-                            match m.as_str() {
+                            match member_lower {
                                 "eof" => {
                                     let dam = crate::data_access::get_global_dam();
                                     let is_eof = dam.lock().unwrap().recordsets.get(&rs_id)
@@ -5822,12 +7675,11 @@ impl Interpreter {
 
                         // Task properties
                         if db_type == "Task" {
-                            let m = member.as_str().to_lowercase();
                             let handle_id = obj_data.fields.get("__handle").map(|v| v.as_string()).unwrap_or_default();
                             if !handle_id.is_empty() {
                                 let shared_obj_opt = get_registry().lock().unwrap().shared_objects.get(&handle_id).cloned();
                                 if let Some(shared_obj) = shared_obj_opt {
-                                    match m.as_str() {
+                                    match member_lower {
                                         "result" => {
                                             loop {
                                                 {
@@ -5836,7 +7688,7 @@ impl Interpreter {
                                                         break;
                                                     }
                                                 }
-                                                std::thread::sleep(std::time::Duration::from_millis(10));
+                                                self.budget.sleep(std::time::Duration::from_millis(10))?;
                                             }
                                             let lock = shared_obj.lock().unwrap();
                                             return Ok(lock.fields.get("result").cloned().unwrap_or(crate::value::SharedValue::Nothing).to_value());
@@ -5857,12 +7709,11 @@ impl Interpreter {
 
                         // Thread properties
                         if class_name_str == "Thread" {
-                            let m = member.as_str().to_lowercase();
                             let handle_id = obj_data.fields.get("__handle").map(|v| v.as_string()).unwrap_or_default();
                             if !handle_id.is_empty() {
                                 let shared_obj_opt = get_registry().lock().unwrap().shared_objects.get(&handle_id).cloned();
                                 if let Some(shared_obj) = shared_obj_opt {
-                                    match m.as_str() {
+                                    match member_lower {
                                         "isalive" => {
                                             let lock = shared_obj.lock().unwrap();
                                             return Ok(lock.fields.get("isalive").cloned().unwrap_or(crate::value::SharedValue::Boolean(false)).to_value());
@@ -5878,22 +7729,20 @@ impl Interpreter {
 
                         // Process properties (basic)
                         if class_name_str == "Process" {
-                            let m = member.as_str().to_lowercase();
-                            if m == "exitcode" {
+                            if member_lower == "exitcode" {
                                 return Ok(obj_data.fields.get("exitcode").cloned().unwrap_or(Value::Integer(0)));
                             }
-                            if m == "hasexited" {
+                            if member_lower == "hasexited" {
                                 return Ok(obj_data.fields.get("hasexited").cloned().unwrap_or(Value::Boolean(false)));
                             }
                         }
 
                         // DbReader properties (ADO.NET)
                         if db_type == "DbReader" {
-                            let m = member.as_str().to_lowercase();
                             let rs_id = obj_data.fields.get("__rs_id")
                                 .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
                                 .unwrap_or(0);
-                            match m.as_str() {
+                            match member_lower {
                                 "hasrows" => {
                                     let dam = crate::data_access::get_global_dam();
                                     let has = dam.lock().unwrap().recordsets.get(&rs_id)
@@ -5917,8 +7766,7 @@ impl Interpreter {
 
                         // DbConnection properties
                         if db_type == "DbConnection" {
-                            let m = member.as_str().to_lowercase();
-                            match m.as_str() {
+                            match member_lower {
                                 "state" => {
                                     let state = obj_data.fields.get("state")
                                         .cloned().unwrap_or(Value::Integer(0));
@@ -5973,8 +7821,7 @@ impl Interpreter {
 
                         // DbCommand properties
                         if db_type == "DbCommand" {
-                            let m = member.as_str().to_lowercase();
-                            if m == "parameters" {
+                            if member_lower == "parameters" {
                                 // Return a DbParameters proxy object
                                 let obj_rc = if let Value::Object(r) = &obj_val { r.clone() } else { unreachable!() };
                                 let mut fields = std::collections::HashMap::new();
@@ -5983,21 +7830,21 @@ impl Interpreter {
                                 let obj = crate::value::ObjectData { drawing_commands: Vec::new(), class_name: "DbParameters".to_string(), fields };
                                 return Ok(Value::Object(std::rc::Rc::new(std::cell::RefCell::new(obj))));
                             }
-                            if m == "connection" {
+                            if member_lower == "connection" {
                                 // Return connection object ref if stored, else Nothing
                                 return Ok(Value::Nothing);
                             }
-                            if m == "commandtimeout" {
+                            if member_lower == "commandtimeout" {
                                 let timeout = obj_data.fields.get("commandtimeout")
                                     .cloned().unwrap_or(Value::Integer(30));
                                 return Ok(timeout);
                             }
-                            if m == "commandtype" {
+                            if member_lower == "commandtype" {
                                 let ct = obj_data.fields.get("commandtype")
                                     .cloned().unwrap_or(Value::Integer(1));
                                 return Ok(ct);
                             }
-                            if m == "commandtext" {
+                            if member_lower == "commandtext" {
                                 let ct = obj_data.fields.get("commandtext")
                                     .cloned().unwrap_or(Value::String(String::new()));
                                 return Ok(ct);
@@ -6006,11 +7853,10 @@ impl Interpreter {
 
                         // DataTable properties
                         if db_type == "DataTable" {
-                            let m = member.as_str().to_lowercase();
                             let rs_id = obj_data.fields.get("__rs_id")
                                 .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
                                 .unwrap_or(0);
-                            match m.as_str() {
+                            match member_lower {
                                 "rows" => {
                                     // Return a DataRowCollection proxy
                                     let dam = crate::data_access::get_global_dam();
@@ -6055,8 +7901,7 @@ impl Interpreter {
 
                         // DataSet properties
                         if db_type == "DataSet" {
-                            let m = member.as_str().to_lowercase();
-                            match m.as_str() {
+                            match member_lower {
                                 "tables" => {
                                     let tables = obj_data.fields.get("__tables")
                                         .cloned().unwrap_or(Value::Array(Vec::new()));
@@ -6072,8 +7917,7 @@ impl Interpreter {
 
                         // BindingSource properties
                         if db_type == "BindingSource" {
-                            let m = member.as_str().to_lowercase();
-                            match m.as_str() {
+                            match member_lower {
                                 "datasource" => {
                                     return Ok(obj_data.fields.get("__datasource")
                                         .cloned().unwrap_or(Value::Nothing));
@@ -6090,7 +7934,7 @@ impl Interpreter {
                                     // Get row count from underlying data source
                                     let ds = obj_data.fields.get("__datasource").cloned().unwrap_or(Value::Nothing);
                                     drop(obj_data);
-                                    let count = self.binding_source_row_count(&ds);
+                                    let count = self.binding_source_row_count(&ds)?;
                                     return Ok(Value::Integer(count));
                                 }
                                 "current" => {
@@ -6100,7 +7944,7 @@ impl Interpreter {
                                         .and_then(|v| if let Value::Integer(i) = v { Some(*i) } else { None })
                                         .unwrap_or(0);
                                     drop(obj_data);
-                                    let row = self.binding_source_get_row(&ds, pos);
+                                    let row = self.binding_source_get_row(&ds, pos)?;
                                     return Ok(row);
                                 }
                                 "filter" => {
@@ -6129,48 +7973,38 @@ impl Interpreter {
                         }
 
                         // DbParameter properties
-                        if db_type == "DbParameter" {
-                            let m = member.as_str().to_lowercase();
-                            if let Some(val) = obj_data.fields.get(&m) {
-                                return Ok(val.clone());
-                            }
+                        if db_type == "DbParameter" && let Some(val) = obj_data.fields.get(member_lower) {
+                            return Ok(val.clone());
                         }
 
                         // DbParameters collection properties
-                        if db_type == "DbParameters" {
-                            let m = member.as_str().to_lowercase();
-                            if m == "count" {
-                                let parent_cmd = obj_data.fields.get("__parent_cmd").cloned();
-                                drop(obj_data); // must drop borrow before new borrows
-                                if let Some(Value::Object(cmd_ref)) = parent_cmd {
-                                    let cmd_borrow = cmd_ref.borrow();
-                                    if let Some(Value::Collection(coll_rc)) = cmd_borrow.fields.get("__parameters") {
-                                        return Ok(Value::Integer(coll_rc.borrow().items.len() as i32));
-                                    }
+                        if db_type == "DbParameters" && member_lower == "count" {
+                            let parent_cmd = obj_data.fields.get("__parent_cmd").cloned();
+                            drop(obj_data); // must drop borrow before new borrows
+                            if let Some(Value::Object(cmd_ref)) = parent_cmd {
+                                let cmd_borrow = cmd_ref.borrow();
+                                if let Some(Value::Collection(coll_rc)) = cmd_borrow.fields.get("__parameters") {
+                                    return Ok(Value::Integer(coll_rc.borrow().items.len() as i32));
                                 }
-                                return Ok(Value::Integer(0));
                             }
+                            return Ok(Value::Integer(0));
                         }
                         
                         // 1. Check if it's a field in the map (case-insensitive)
                         // Skip "controls" — it has a dedicated dynamic accessor below
-                        let member_lc = member.as_str().to_lowercase();
-                        if member_lc != "controls" {
-                            if let Some(val) = obj_data.fields.get(&member_lc) {
-                                return Ok(val.clone());
-                            }
+                        if member_lower != "controls" && let Some(val) = obj_data.fields.get(member_lower) {
+                            return Ok(val.clone());
                         }
                     } // Drop borrow
 
                     // Handle WinForms infrastructure properties that don't exist as real fields
-                    let member_lower = member.as_str().to_lowercase();
                     
                     // Computed properties for control objects
                     {
                         let is_ctrl = obj_ref.borrow().fields.get("__is_control")
                             .map(|v| v.as_bool().unwrap_or(false)).unwrap_or(false);
                         if is_ctrl {
-                            match member_lower.as_str() {
+                            match member_lower {
                                 "location" => {
                                     let b = obj_ref.borrow();
                                     let x = b.fields.get("left").and_then(|v| v.as_integer().ok()).unwrap_or(0);
@@ -6224,7 +8058,7 @@ impl Interpreter {
                         }
                     }
 
-                    match member_lower.as_str() {
+                    match member_lower {
                         "controls" => {
                             // Build the controls collection dynamically from all __is_control fields
                             let b = obj_ref.borrow();
@@ -6293,10 +8127,12 @@ impl Interpreter {
                              let func = FunctionDecl {
                                  visibility: prop.visibility,
                                  name: prop.name.clone(),
+                                 type_parameters: Vec::new(),
                                  parameters: prop.parameters.clone(),
                                  return_type: prop.return_type.clone(),
                                  body: body.clone(),
                                  is_async: false,
+                                 is_iterator: false,
                                  is_extension: false,
                                  is_overridable: false,
                                  is_overrides: false,
@@ -6304,6 +8140,9 @@ impl Interpreter {
                                  is_shared: false,
                                  is_not_overridable: false,
                                  handles: None,
+                                 attributes: Vec::new(),
+                                 span: prop.span,
+                                 source_file: prop.source_file.clone(),
                              };
                              
                              // Execute Property Get
@@ -6313,7 +8152,6 @@ impl Interpreter {
                 }
 
                 if let Value::String(obj_name) = &obj_val {
-                    let member_lower = member.as_str().to_lowercase();
                     // DataBindings proxy for string-proxy controls
                     if member_lower == "databindings" {
                         let mut flds = std::collections::HashMap::new();
@@ -6379,94 +8217,103 @@ impl Interpreter {
                  evaluate(expr, &self.env)
             }
 
-            Expression::Variable(name) => {
-                let var_name = name.as_str();
-                let var_lower = var_name.to_lowercase();
-                
-                // 1. Check local scopes
-                if self.env.has_local(var_name) {
-                    if let Ok(val) = self.env.get(var_name) {
-                        return Ok(val);
-                    }
-                }
+            ExprKind::Variable(name) => match self.resolutions.local(expr.span, name.as_str(), &self.env) {
+                (_, Some(slot)) => Ok(self.env.slot(slot).clone()),
+                (symbol, None) => self.load_nonlocal(name, symbol),
+            },
 
-                // 2. Check current object fields (Me.field)
-                if let Some(obj_rc) = &self.current_object {
-                    let obj = obj_rc.borrow();
-                    if let Some(val) = obj.fields.get(&var_lower) {
-                        return Ok(val.clone());
-                    }
-                    drop(obj);
-                    
-                    // Before auto-creating a field, check global scope first.
-                    // Global variables (Console, Math, etc.) should NOT be shadowed
-                    // by auto-created Nothing fields on the object.
-                    if let Some(val) = self.env.get_global(var_name) {
-                        return Ok(val);
-                    }
-                    // Also check module-level variables
-                    if let Some(module) = &self.current_module {
-                        let module_key = format!("{}.{}", module, var_name).to_lowercase();
-                        if let Ok(val) = self.env.get(&module_key) {
-                            return Ok(val);
-                        }
-                    }
-                    
-                    // If field not present and not a global, create it as Nothing
-                    // to mimic VB's instance fields being default-initialized
-                    obj_rc.borrow_mut().fields.insert(var_lower.clone(), Value::Nothing);
-                    return Ok(Value::Nothing);
-                }
 
-                // 3. Check global scope
-                if let Some(val) = self.env.get_global(var_name) {
-                    return Ok(val);
-                }
+            // For simple expressions, use the standalone evaluator
+            _ => evaluate(expr, &self.env),
+        }
+    }
 
-                // 4. Try module-level variable if we are in a module
-                if let Some(module) = &self.current_module {
-                     let module_key = format!("{}.{}", module, var_name).to_lowercase();
-                     if let Ok(val) = self.env.get(&module_key) {
-                         return Ok(val);
-                     }
-                }
 
-                // 5. Check imports aliases: `Imports IO = System.IO` → IO resolves to the namespace
-                for imp in &self.imports {
-                    if let Some(alias) = &imp.alias {
-                        if alias.eq_ignore_ascii_case(var_name) {
-                            // Try to resolve the path as an env value (namespace object)
-                            if let Ok(val) = self.env.get(&imp.path.to_lowercase()) {
-                                return Ok(val);
-                            }
-                        }
-                    } else {
-                        // Unqualified import: `Imports System.Text` -> `Encoding` resolves to `System.Text.Encoding`
-                        let key = format!("{}.{}", imp.path, var_name).to_lowercase();
-                        if let Ok(val) = self.env.get(&key) {
-                            return Ok(val);
-                        }
-                    }
-                }
 
-                // 6. Fallback: implicit function call without parentheses (e.g. "Now", "Date")
-                match self.call_procedure(name, &[]) {
-                    Ok(val) => return Ok(val),
-                    Err(_) => return Err(RuntimeError::UndefinedVariable(var_name.to_string())),
+    /// The value of the variable `name`, interned as `symbol`, when it isn't a
+    /// local: a field of the current object, a global, a module variable or an
+    /// imported name, else a call of a procedure without arguments.
+    fn load_nonlocal(&mut self, name: &Identifier, symbol: Symbol) -> Result<Value, RuntimeError> {
+        let var_name = name.as_str();
+
+        // 1. Check current object fields (Me.field)
+        if let Some(obj_rc) = &self.current_object {
+            let obj = obj_rc.borrow();
+            if let Some(val) = obj.fields.get(&*symbol.name()) {
+                return Ok(val.clone());
+            }
+            drop(obj);
+            
+            // Before auto-creating a field, check global scope first.
+            // Global variables (Console, Math, etc.) should NOT be shadowed
+            // by auto-created Nothing fields on the object.
+            if let Some(val) = self.env.get_global(var_name) {
+                return Ok(val);
+            }
+            // Also check module-level variables
+            if let Some(module) = &self.current_module {
+                let module_key = format!("{}.{}", module, var_name).to_lowercase();
+                if let Ok(val) = self.env.get(&module_key) {
+                    return Ok(val);
                 }
             }
+            
+            // If field not present and not a global, create it as Nothing
+            // to mimic VB's instance fields being default-initialized
+            obj_rc.borrow_mut().fields.insert(symbol.name().to_string(), Value::Nothing);
+            return Ok(Value::Nothing);
+        }
 
+        // 2. Check global scope
+        if let Some(val) = self.env.get_global(var_name) {
+            return Ok(val);
+        }
 
-            // For simple expressions, use the standalone evaluator
-            _ => evaluate(expr, &self.env),
+        // 3. Try module-level variable if we are in a module
+        if let Some(module) = &self.current_module {
+             let module_key = format!("{}.{}", module, var_name).to_lowercase();
+             if let Ok(val) = self.env.get(&module_key) {
+                 return Ok(val);
+             }
         }
-    }
 
+        // 4. Check imports aliases: `Imports IO = System.IO` → IO resolves to the namespace
+        for imp in &self.imports {
+            if let Some(alias) = &imp.alias {
+                if alias.eq_ignore_ascii_case(var_name) {
+                    // Try to resolve the path as an env value (namespace object)
+                    if let Ok(val) = self.env.get(&imp.path.to_lowercase()) {
+                        return Ok(val);
+                    }
+                }
+            } else {
+                // Unqualified import: `Imports System.Text` -> `Encoding` resolves to `System.Text.Encoding`
+                let key = format!("{}.{}", imp.path, var_name).to_lowercase();
+                if let Ok(val) = self.env.get(&key) {
+                    return Ok(val);
+                }
+            }
+        }
 
+        // 5. Fallback: implicit function call without parentheses (e.g. "Now", "Date")
+        self.call_procedure(name, &[]).map_err(|_| RuntimeError::UndefinedVariable(var_name.to_string()))
+    }
 
     pub fn call_procedure(&mut self, name: &Identifier, args: &[Expression]) -> Result<Value, RuntimeError> {
-        let name_str = name.as_str().to_lowercase();
+        self.as_run(|this| stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || this.call_procedure_impl(name, args)))
+    }
 
+    fn call_procedure_impl(&mut self, name: &Identifier, args: &[Expression]) -> Result<Value, RuntimeError> {
+        // Explicit type arguments: First(Of Integer)(items)
+        if let Some((base, type_args)) = crate::generics::split_type_arguments(name.as_str()) {
+            let type_args = type_args.iter().map(|t| self.resolve_type_name(t)).collect();
+            let prev = self.pending_type_args.replace((base.to_string(), type_args));
+            let result = self.call_procedure(&Identifier::new(base), args);
+            self.pending_type_args = prev;
+            return result;
+        }
+        let lower_name = Symbol::intern(name.as_str()).name();
+        let name_str: &str = &lower_name;
 
         // First check if it's actually an array access (arrays and functions use same syntax in VB)
         // Check local variable shadowing first
@@ -6485,26 +8332,43 @@ impl Interpreter {
                      return val.get_array_element(index);
                  }
              }
-             if let Value::Lambda { .. } = &val {
+             if crate::delegates::is_callable(&val) {
                  // It's a lambda/delegate call (Sub or Function lambda invoked as statement)
                  let arg_values: Result<Vec<_>, _> = args.iter().map(|e| self.evaluate_expr(e)).collect();
-                 return self.call_lambda(val, &arg_values?);
+                 return self.invoke_delegate(&val, &arg_values?);
+             }
+             if let Some((prop, obj)) = self.default_property_of(&val) {
+                 // Default property: table("key") reads table.Item("key")
+                 let arg_values: Result<Vec<_>, _> = args.iter().map(|e| self.evaluate_expr(e)).collect();
+                 return self.get_property(&prop, &arg_values?, obj);
              }
         }
-        
+
         // Check implicit object array/field access
-        let current_obj_opt = self.current_object.clone();
-        if let Some(obj_rc) = current_obj_opt {
-             let obj = obj_rc.borrow();
-             if let Some(val) = obj.fields.get(name.as_str()) {
-                 if let Value::Array(_) = val {
-                     // Array access on field
-                     if args.len() == 1 {
-                         let index = self.evaluate_expr(&args[0])?.as_integer()? as usize;
-                         return val.get_array_element(index);
-                     }
+        let field = self.current_object.as_ref().and_then(|o| o.borrow().fields.get(name.as_str()).cloned());
+        if let Some(val) = field {
+             if let Value::Array(_) = val {
+                 // Array access on field
+                 if args.len() == 1 {
+                     let index = self.evaluate_expr(&args[0])?.as_integer()? as usize;
+                     return val.get_array_element(index);
                  }
              }
+             // List or dictionary field: items(i), table(key)
+             if let Value::Collection(col) = &val && args.len() == 1 {
+                 return match self.evaluate_expr(&args[0])? {
+                     Value::String(key) => col.borrow().item_by_key(&key),
+                     index => col.borrow().item(index.as_integer()? as usize),
+                 };
+             }
+             if let Value::Dictionary(dict) = &val && args.len() == 1 {
+                 let key = self.evaluate_expr(&args[0])?;
+                 return dict.borrow().item(&key);
+             }
+             if let Some((prop, obj)) = self.default_property_of(&val) {
+                 let arg_values: Result<Vec<_>, _> = args.iter().map(|e| self.evaluate_expr(e)).collect();
+                 return self.get_property(&prop, &arg_values?, obj);
+             }
         }
 
 
@@ -6524,17 +8388,22 @@ impl Interpreter {
                     }
                 }
             }
+            if let Some(prop) = self.find_property(&class_name, name.as_str()).filter(|p| !p.parameters.is_empty()) {
+                let obj_rc = obj_rc.clone();
+                let arg_values: Result<Vec<_>, _> = args.iter().map(|e| self.evaluate_expr(e)).collect();
+                return self.get_property(&prop, &arg_values?, obj_rc);
+            }
         }
 
         // Check user-defined functions with scope resolution
         // Try 1: Look for qualified name (as-is)
-        if let Some(func) = self.functions.get(&name_str).cloned() {
+        if let Some(func) = self.function(name_str) {
             return self.call_user_function_exprs(&func, args, None);
         }
         // Try 2: If in a module, try current_module.name
         if let Some(module) = &self.current_module {
             let qualified = format!("{}.{}", module.to_lowercase(), name_str);
-            if let Some(func) = self.functions.get(&qualified).cloned() {
+            if let Some(func) = self.function(&qualified) {
                 return self.call_user_function_exprs(&func, args, None);
             }
         }
@@ -6542,15 +8411,15 @@ impl Interpreter {
         for imp in &self.imports {
             if imp.alias.is_none() {
                 let qualified = format!("{}.{}", imp.path.to_lowercase(), name_str);
-                if let Some(func) = self.functions.get(&qualified).cloned() {
+                if let Some(func) = self.function(&qualified) {
                     return self.call_user_function_exprs(&func, args, None);
                 }
             }
         }
         // Try 4: Global search - look for any function with matching unqualified name
         // This makes BAS module functions globally accessible
-        for (key, func) in &self.functions {
-            if key.ends_with(&format!(".{}", name_str)) || key == &name_str {
+        for (key, funcs) in &self.functions {
+            if let Some(func) = funcs.last() && (key.ends_with(&format!(".{}", name_str)) || key == name_str) {
                 let func_clone = func.clone();
                 return self.call_user_function_exprs(&func_clone, args, None);
             }
@@ -6558,13 +8427,13 @@ impl Interpreter {
 
         // Also check if it's a Sub (in VB, both Subs and Functions can be called with parens)
         // Try 1: Look for qualified name (as-is)
-        if let Some(sub) = self.subs.get(&name_str).cloned() {
+        if let Some(sub) = self.sub(name_str) {
             return self.call_user_sub_exprs(&sub, args, None);
         }
         // Try 2: If in a module, try current_module.name
         if let Some(module) = &self.current_module {
             let qualified = format!("{}.{}", module.to_lowercase(), name_str);
-            if let Some(sub) = self.subs.get(&qualified).cloned() {
+            if let Some(sub) = self.sub(&qualified) {
                 return self.call_user_sub_exprs(&sub, args, None);
             }
         }
@@ -6572,28 +8441,54 @@ impl Interpreter {
         for imp in &self.imports {
             if imp.alias.is_none() {
                 let qualified = format!("{}.{}", imp.path.to_lowercase(), name_str);
-                if let Some(sub) = self.subs.get(&qualified).cloned() {
+                if let Some(sub) = self.sub(&qualified) {
                     return self.call_user_sub_exprs(&sub, args, None);
                 }
             }
         }
         // Try 4: Global search for subs
-        for (key, sub) in &self.subs {
-            if key.ends_with(&format!(".{}", name_str)) || key == &name_str {
+        for (key, subs) in &self.subs {
+            if let Some(sub) = subs.last() && (key.ends_with(&format!(".{}", name_str)) || key == name_str) {
                 return self.call_user_sub_exprs(&sub.clone(), args, None);
             }
         }
 
 
+        // GetType(T) names a type, not a value
+        if let ("gettype", [Expression { kind: ExprKind::Variable(type_id), .. }]) = (name_str, args) {
+            return Ok(type_object(&self.resolve_type_name(type_id.as_str())));
+        }
     
     // Fallback: Check built-in functions
     if let Ok(arg_values) = args.iter().map(|e| self.evaluate_expr(e)).collect::<Result<Vec<_>, _>>() {
+        let arg_values = if crate::arguments::has_named(args) {
+            let Some(params) = crate::builtins::builtin_parameters(name_str) else {
+                return Err(RuntimeError::Custom(format!("'{}' does not accept named arguments.", name.as_str())));
+            };
+            let infos: Vec<_> = params.iter().map(|(n, default)| (*n, default.is_some())).collect();
+            let named = crate::arguments::argument_names(args).into_iter().zip(arg_values).collect();
+            crate::arguments::arrange(name.as_str(), &infos, named, |p| Ok(params[p].1.clone().unwrap_or(Value::Nothing)))?
+        } else {
+            arg_values
+        };
+        // CDbl(m), CStr(m), ... on an object use its CType operators
+        let converted = match (conversion_function_target(name_str), arg_values.as_slice()) {
+            (Some(target), [arg @ Value::Object(_)]) => self.user_conversion(arg, target, ALL_CONVERSIONS)?,
+            _ => None,
+        };
+        if let Some(converted) = converted {
+            return Ok(converted);
+        }
+        self.sandbox.check_call(name_str, &arg_values)?;
+        if let Some(value) = self.record_builtin(name_str, &arg_values)? {
+            return Ok(value);
+        }
         // Try standard library first
-        if let Ok(val) = crate::std_lib::call_builtin(&name_str, &arg_values) {
+        if let Ok(val) = crate::std_lib::call_builtin(name_str, &arg_values) {
             return Ok(val);
         }
 
-        match name_str.as_str() {
+        match name_str {
             "msgbox" => {
                 let msg = if arg_values.is_empty() {
                     "".to_string()
@@ -6629,8 +8524,8 @@ impl Interpreter {
             "trim" | "trim$" => return trim_fn(&arg_values),
             "ltrim" | "ltrim$" => return ltrim_fn(&arg_values),
             "rtrim" | "rtrim$" => return rtrim_fn(&arg_values),
-            "instr" => return instr_fn(&arg_values),
-            "instrrev" => return instrrev_fn(&arg_values),
+            "instr" => return instr_fn(&self.fold_case_all(arg_values)),
+            "instrrev" => return instrrev_fn(&self.fold_case_all(arg_values)),
             "replace" => return replace_fn(&arg_values),
             "chr" | "chr$" => return chr_fn(&arg_values),
             "asc" => return asc_fn(&arg_values),
@@ -6865,7 +8760,7 @@ impl Interpreter {
             // Debug/Output
             "debug.print" => {
                 let msg = arg_values.iter().map(|v| v.as_string()).collect::<Vec<_>>().join(" ");
-                self.send_debug_output(format!("{}\n", msg));
+                self.send_debug_output(format!("{}\n", msg))?;
                 return Ok(Value::Nothing);
             }
             "console.writeline" => {
@@ -6969,16 +8864,25 @@ impl Interpreter {
     }
 
     fn call_method(&mut self, obj: &Expression, method: &Identifier, args: &[Expression]) -> Result<Value, RuntimeError> {
-        let method_name = method.as_str().to_lowercase();
+        if let Some((base, type_args)) = crate::generics::split_type_arguments(method.as_str()) {
+            let type_args = type_args.iter().map(|t| self.resolve_type_name(t)).collect();
+            let prev = self.pending_type_args.replace((base.to_string(), type_args));
+            let result = self.call_method(obj, &Identifier::new(base), args);
+            self.pending_type_args = prev;
+            return result;
+        }
+        self.check_late_binding(obj, method.as_str())?;
+        let lower_method = Symbol::intern(method.as_str()).name();
+        let method_name: &str = &lower_method;
 
         // ── Static Class Dispatch ───────────────────────────────────────
-        if let Expression::Variable(name) = obj {
-            let class_name = name.as_str().to_lowercase();
-            match class_name.as_str() {
+        if let ExprKind::Variable(name) = &obj.kind {
+            let class_name = Symbol::intern(name.as_str()).name();
+            match &*class_name {
                 "math" | "system.math" => {
                     let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_,_>>()?;
                     use crate::builtins::math_fns::*;
-                    match method_name.as_str() {
+                    match method_name {
                         "abs" => return abs_fn(&arg_values),
                         "ceiling" => return ceiling_fn(&arg_values),
                         "cos" => return cos_fn(&arg_values),
@@ -6988,12 +8892,15 @@ impl Interpreter {
                         "max" => return max_fn(&arg_values),
                         "min" => return min_fn(&arg_values),
                         "pow" => return pow_fn(&arg_values),
-                        "round" => return round_fn(&arg_values),
+                        "round" => return round_fn(&midpoint_rounding_args(args, arg_values)),
                         "sign" => return sgn_fn(&arg_values),
                         "sin" => return sin_fn(&arg_values),
                         "sqrt" => return sqr_fn(&arg_values),
                         "tan" => return tan_fn(&arg_values),
-                        "truncate" => return fix_fn(&arg_values), // Fix truncates
+                        "truncate" => match arg_values.first() {
+                            Some(Value::Decimal(d)) => return Ok(Value::Decimal(d.round(0, crate::decimal::Rounding::ToZero))),
+                            _ => return fix_fn(&arg_values), // Fix truncates
+                        },
                         "atan" => return atn_fn(&arg_values),
                         "atan2" => return atan2_fn(&arg_values),
                         _ => return Err(RuntimeError::UndefinedFunction(format!("Math.{}", method_name))),
@@ -7001,8 +8908,9 @@ impl Interpreter {
                 }
                 "file" | "system.io.file" => {
                     let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_,_>>()?;
+                    self.sandbox.check_call(&format!("file.{}", method_name), &arg_values)?;
                     use crate::builtins::file_fns::*;
-                    match method_name.as_str() {
+                    match method_name {
                         "readalltext" => return file_readalltext_fn(&arg_values),
                         "writealltext" => return file_writealltext_fn(&arg_values),
                         "appendalltext" => return file_appendalltext_fn(&arg_values),
@@ -7016,8 +8924,9 @@ impl Interpreter {
                 }
                 "directory" | "system.io.directory" => {
                     let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_,_>>()?;
+                    self.sandbox.check_call(&format!("directory.{}", method_name), &arg_values)?;
                     use crate::builtins::file_fns::*;
-                    match method_name.as_str() {
+                    match method_name {
                         "exists" => return directory_exists_fn(&arg_values),
                         "createdirectory" => return directory_createdirectory_fn(&arg_values),
                         "delete" => return directory_delete_fn(&arg_values),
@@ -7030,7 +8939,7 @@ impl Interpreter {
                 "path" | "system.io.path" => {
                     let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_,_>>()?;
                     use crate::builtins::file_fns::*;
-                    match method_name.as_str() {
+                    match method_name {
                         "combine" => return path_combine_fn(&arg_values),
                         "getfilename" => return path_getfilename_fn(&arg_values),
                         "getdirectoryname" => return path_getdirectoryname_fn(&arg_values),
@@ -7040,7 +8949,7 @@ impl Interpreter {
                     }
                 }
                 "md5" | "system.security.cryptography.md5" => {
-                    match method_name.as_str() {
+                    match method_name {
                         "create" => {
                             let mut fields = HashMap::new();
                             fields.insert("__type".to_string(), Value::String("MD5".to_string()));
@@ -7051,7 +8960,7 @@ impl Interpreter {
                     }
                 }
                 "sha256" | "system.security.cryptography.sha256" => {
-                    match method_name.as_str() {
+                    match method_name {
                         "create" => {
                             let mut fields = HashMap::new();
                             fields.insert("__type".to_string(), Value::String("SHA256".to_string()));
@@ -7063,7 +8972,8 @@ impl Interpreter {
                 }
                 "xdocument" | "system.xml.linq.xdocument" => {
                     let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_,_>>()?;
-                    match method_name.as_str() {
+                    self.sandbox.check_call(&format!("xdocument.{}", method_name), &arg_values)?;
+                    match method_name {
                         "parse" => return crate::builtins::xml::xdocument_parse(&arg_values),
                         "load" => return crate::builtins::xml::xdocument_load(&arg_values),
                         _ => return Err(RuntimeError::UndefinedFunction(format!("XDocument.{}", method_name))),
@@ -7071,7 +8981,7 @@ impl Interpreter {
                 }
                 "xelement" | "system.xml.linq.xelement" => {
                     let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_,_>>()?;
-                    match method_name.as_str() {
+                    match method_name {
                         "parse" => return crate::builtins::xml::xelement_parse(&arg_values),
                         _ => return Err(RuntimeError::UndefinedFunction(format!("XElement.{}", method_name))),
                     }
@@ -7083,7 +8993,7 @@ impl Interpreter {
         // ── MyBase dispatch ─────────────────────────────────────────────
         // MyBase.Method() dispatches to the parent class's method on the
         // same object instance (Me).
-        let is_mybase = matches!(obj, Expression::MyBase);
+        let is_mybase = matches!(&obj.kind, ExprKind::MyBase);
         if is_mybase {
             if let Some(obj_rc) = &self.current_object {
                 let obj_clone = obj_rc.clone();
@@ -7091,7 +9001,11 @@ impl Interpreter {
                 let arg_values: Vec<Value> = args.iter()
                     .map(|a| self.evaluate_expr(a))
                     .collect::<Result<Vec<_>, _>>()?;
-                if let Some(base_method) = self.find_method_in_base(&class_name, &method_name) {
+                if let Some(base_method) = self.find_method_in_base(&class_name, method_name) {
+                    if let Some(result) = self.call_named_overload(&base_method, args, &arg_values, Some(obj_clone.clone()))? {
+                        return Ok(result);
+                    }
+                    let arg_values = self.arrange_values(&base_method, args, arg_values)?;
                     match base_method {
                         vybe_parser::ast::decl::MethodDecl::Sub(s) => {
                             self.call_user_sub(&s, &arg_values, Some(obj_clone))?;
@@ -7110,7 +9024,7 @@ impl Interpreter {
         }
 
         // Handle WinForms designer no-op methods
-        match method_name.as_str() {
+        match method_name {
             "suspendlayout" | "resumelayout" | "performlayout" => return Ok(Value::Nothing),
             _ => {}
         }
@@ -7171,19 +9085,19 @@ impl Interpreter {
         }
 
         // Handle Controls.* methods — full ControlCollection API
-        if let Expression::MemberAccess(parent_expr, member) = obj {
+        if let ExprKind::MemberAccess(parent_expr, member) = &obj.kind {
             if member.as_str().eq_ignore_ascii_case("Controls") {
                 println!("[DEBUG] call_method intercepted Controls. Obj: {:?} Method: {}", parent_expr, method_name);
                 // Extract parent control name from parent_expr
                 // Me.pnlRoot.Controls → parent_name = "pnlRoot"
                 // Me.Controls → parent_name = "" (form-level)
-                let parent_ctrl_name = match parent_expr.as_ref() {
-                    Expression::MemberAccess(_, id) => {
+                let parent_ctrl_name = match &parent_expr.kind {
+                    ExprKind::MemberAccess(_, id) => {
                         let name = id.as_str();
                         // "Me" means form-level
                         if name.eq_ignore_ascii_case("me") { String::new() } else { name.to_string() }
                     }
-                    Expression::Variable(id) => {
+                    ExprKind::Variable(id) => {
                         let name = id.as_str();
                         if name.eq_ignore_ascii_case("me") || name.eq_ignore_ascii_case("mybase") {
                             String::new()
@@ -7194,7 +9108,7 @@ impl Interpreter {
                     _ => String::new(), // Variable("Me") or other → form-level
                 };
 
-                match method_name.as_str() {
+                match method_name {
                     "add" => {
                         println!("[DEBUG] Inside Controls.Add handler. Args: {}", args.len());
                         if !args.is_empty() {
@@ -7397,8 +9311,8 @@ impl Interpreter {
                         if !args.is_empty() {
                             let idx = self.evaluate_expr(&args[0])?.as_integer()? as usize;
                             // Get the controls list, find the control at index, remove it
-                            let controls_val = self.evaluate_expr(&Expression::MemberAccess(
-                                parent_expr.clone(), member.clone()))?;
+                            let controls_val = self.evaluate_expr(&ExprKind::MemberAccess(
+                                parent_expr.clone(), member.clone()).into())?;
                             if let Value::Collection(coll) = &controls_val {
                                 let items = coll.borrow().items.clone();
                                 if idx < items.len() {
@@ -7444,8 +9358,8 @@ impl Interpreter {
                         // Controls.ContainsKey(key) — check if a control with that name exists
                         if !args.is_empty() {
                             let key = self.evaluate_expr(&args[0])?.as_string();
-                            let controls_val = self.evaluate_expr(&Expression::MemberAccess(
-                                parent_expr.clone(), member.clone()))?;
+                            let controls_val = self.evaluate_expr(&ExprKind::MemberAccess(
+                                parent_expr.clone(), member.clone()).into())?;
                             if let Value::Collection(coll) = &controls_val {
                                 let found = coll.borrow().items.iter().any(|item| {
                                     if let Value::Object(o) = item {
@@ -7466,8 +9380,8 @@ impl Interpreter {
                             let _search_all = if args.len() > 1 {
                                 self.evaluate_expr(&args[1])?.as_bool().unwrap_or(false)
                             } else { false };
-                            let controls_val = self.evaluate_expr(&Expression::MemberAccess(
-                                parent_expr.clone(), member.clone()))?;
+                            let controls_val = self.evaluate_expr(&ExprKind::MemberAccess(
+                                parent_expr.clone(), member.clone()).into())?;
                             let mut result = Vec::new();
                             if let Value::Collection(coll) = &controls_val {
                                 for item in &coll.borrow().items {
@@ -7489,8 +9403,8 @@ impl Interpreter {
                         // Controls.IndexOfKey(key) — find index of control by name
                         if !args.is_empty() {
                             let key = self.evaluate_expr(&args[0])?.as_string();
-                            let controls_val = self.evaluate_expr(&Expression::MemberAccess(
-                                parent_expr.clone(), member.clone()))?;
+                            let controls_val = self.evaluate_expr(&ExprKind::MemberAccess(
+                                parent_expr.clone(), member.clone()).into())?;
                             if let Value::Collection(coll) = &controls_val {
                                 for (i, item) in coll.borrow().items.iter().enumerate() {
                                     if let Value::Object(o) = item {
@@ -7510,8 +9424,8 @@ impl Interpreter {
                         // Controls.Contains(ctrl) — check if collection contains this control ref
                         if !args.is_empty() {
                             let ctrl_val = self.evaluate_expr(&args[0])?;
-                            let controls_val = self.evaluate_expr(&Expression::MemberAccess(
-                                parent_expr.clone(), member.clone()))?;
+                            let controls_val = self.evaluate_expr(&ExprKind::MemberAccess(
+                                parent_expr.clone(), member.clone()).into())?;
                             if let Value::Collection(coll) = &controls_val {
                                 if let Value::Object(target) = &ctrl_val {
                                     let found = coll.borrow().items.iter().any(|item| {
@@ -7526,8 +9440,8 @@ impl Interpreter {
                         return Ok(Value::Boolean(false));
                     }
                     "count" => {
-                        let controls_val = self.evaluate_expr(&Expression::MemberAccess(
-                            parent_expr.clone(), member.clone()))?;
+                        let controls_val = self.evaluate_expr(&ExprKind::MemberAccess(
+                            parent_expr.clone(), member.clone()).into())?;
                         if let Value::Collection(coll) = &controls_val {
                             return Ok(Value::Integer(coll.borrow().items.len() as i32));
                         }
@@ -7545,9 +9459,9 @@ impl Interpreter {
         //  evaluate "System" as a variable)
         {
             fn expr_to_static_path(e: &Expression) -> Option<String> {
-                match e {
-                    Expression::Variable(id) => Some(id.as_str().to_lowercase()),
-                    Expression::MemberAccess(base, member) => {
+                match &e.kind {
+                    ExprKind::Variable(id) => Some(id.as_str().to_lowercase()),
+                    ExprKind::MemberAccess(base, member) => {
                         expr_to_static_path(base).map(|b| format!("{}.{}", b, member.as_str().to_lowercase()))
                     }
                     _ => None,
@@ -7557,7 +9471,7 @@ impl Interpreter {
                 match class_path.as_str() {
                     "colortranslator" | "system.drawing.colortranslator" => {
                         let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_,_>>()?;
-                        match method_name.as_str() {
+                        match method_name {
                             "fromhtml" => {
                                 // Return the HTML color string as-is (the interpreter treats colors as strings)
                                 let color_str = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
@@ -7576,10 +9490,24 @@ impl Interpreter {
 
         // Evaluate object to check if it's a Collection or Dialog
         let eval_result = self.evaluate_expr(obj);
+        // Lazy sequences handle the LINQ operators that don't need every value;
+        // for any other method they're enumerated and treated like an array
+        let eval_result = match eval_result {
+            Ok(seq) if iterators::is_enumerable(&seq) => match self.enumerable_method(&seq, method_name, args)? {
+                Some(result) => return Ok(result),
+                None => self.sequence_items(&seq).map(Value::Array),
+            },
+            other => other,
+        };
         // if method_name == "add" { ... }
         if let Ok(ref obj_val) = eval_result {
+            // handler.Invoke(sender, e) on a delegate or lambda
+            if method_name == "invoke" && crate::delegates::is_callable(obj_val) {
+                let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_, _>>()?;
+                return self.invoke_delegate(obj_val, &arg_values);
+            }
             // Universal value methods (works on any type: Integer, String, Double, Boolean, etc.)
-            match method_name.as_str() {
+            match method_name {
                 "tostring" => {
                     // For typed objects, check special ToString implementations
                     if let Value::Object(obj_ref) = &obj_val {
@@ -7610,6 +9538,11 @@ impl Interpreter {
                          let fmt = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                          return Ok(Value::String(format_ole_date(*ole, &fmt)));
                     }
+                    let numeric = crate::integral::IntType::of(obj_val).is_some() || matches!(obj_val, Value::Single(_) | Value::Double(_) | Value::Decimal(_));
+                    if numeric && !args.is_empty() {
+                        let fmt = self.evaluate_expr(&args[0])?;
+                        return crate::builtins::format_fn(&[obj_val.clone(), fmt]);
+                    }
                     return Ok(Value::String(obj_val.as_string()));
                 }
                 "gethashcode" => return Ok(Value::Integer(obj_val.as_string().len() as i32)),
@@ -7619,15 +9552,21 @@ impl Interpreter {
                         Value::Long(_) => ("Int64", "System.Int64"),
                         Value::Single(_) => ("Single", "System.Single"),
                         Value::Double(_) => ("Double", "System.Double"),
+                        Value::Decimal(_) => ("Decimal", "System.Decimal"),
                         Value::String(_) => ("String", "System.String"),
                         Value::Boolean(_) => ("Boolean", "System.Boolean"),
                         Value::Byte(_) => ("Byte", "System.Byte"),
+                        Value::SByte(_) => ("SByte", "System.SByte"),
+                        Value::Short(_) => ("Int16", "System.Int16"),
+                        Value::UShort(_) => ("UInt16", "System.UInt16"),
+                        Value::UInteger(_) => ("UInt32", "System.UInt32"),
+                        Value::ULong(_) => ("UInt64", "System.UInt64"),
                         Value::Char(_) => ("Char", "System.Char"),
                         Value::Date(_) => ("DateTime", "System.DateTime"),
                         Value::Array(_) => ("Array", "System.Array"),
                         Value::Nothing => ("Object", "System.Object"),
-                        Value::Object(rc) => {
-                            let cn = rc.borrow().class_name.clone();
+                        Value::Object(_) => {
+                            let cn = clr_type_name(&crate::generics::value_type_name(obj_val));
                             let full = format!("System.{}", cn);
                             let mut fields = std::collections::HashMap::new();
                             fields.insert("name".to_string(), Value::String(cn.clone()));
@@ -7671,6 +9610,10 @@ impl Interpreter {
                         .collect();
                     let arg_values = arg_values?;
                     let other = arg_values.get(0).cloned().unwrap_or(Value::Nothing);
+                    // Anonymous types are equal when their members are
+                    if matches!(&obj_val, Value::Object(o) if o.borrow().class_name == query::ANONYMOUS_CLASS) {
+                        return Ok(Value::Boolean(values_equal(obj_val, &other)));
+                    }
                     return Ok(Value::Boolean(obj_val.as_string() == other.as_string()));
                 }
                 "compareto" => {
@@ -7695,7 +9638,10 @@ impl Interpreter {
                     .map(|arg| self.evaluate_expr(arg))
                     .collect();
                 let arg_values = arg_values?;
-                return crate::builtins::xml::xml_method_call(&obj_val, &method_name, &arg_values);
+                if method_name == "save" {
+                    self.sandbox.check_paths(&arg_values, &[0])?;
+                }
+                return crate::builtins::xml::xml_method_call(&obj_val, method_name, &arg_values);
             }
 
             // Handle ResourceManager methods: GetString(key), GetObject(key)
@@ -7706,7 +9652,7 @@ impl Interpreter {
                         .map(|arg| self.evaluate_expr(arg))
                         .collect();
                     let arg_values = arg_values?;
-                    match method_name.as_str() {
+                    match method_name {
                         "getstring" => {
                             let key = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                             let lookup = format!("__res_{}", key.to_lowercase());
@@ -7736,7 +9682,7 @@ impl Interpreter {
                 let arg_values = arg_values?;
                 let ndt = ole_to_dt(*ole_val);
                 use chrono::{Datelike, Timelike, NaiveDate};
-                match method_name.as_str() {
+                match method_name {
                     // Properties (accessed as methods with no args)
                     "year" => return Ok(Value::Integer(ndt.year())),
                     "month" => return Ok(Value::Integer(ndt.month() as i32)),
@@ -7880,7 +9826,7 @@ impl Interpreter {
                     .map(|arg| self.evaluate_expr(arg))
                     .collect();
                 let arg_values = arg_values?;
-                match method_name.as_str() {
+                match method_name {
                     "tolower" | "tolowerinvariant" => return Ok(Value::String(s_val.to_lowercase())),
                     "toupper" | "toupperinvariant" => return Ok(Value::String(s_val.to_uppercase())),
                     "trim" => {
@@ -8075,7 +10021,7 @@ impl Interpreter {
                     .map(|arg| self.evaluate_expr(arg))
                     .collect();
                 let arg_values = arg_values?;
-                match method_name.as_str() {
+                match method_name {
                     "contains" => {
                         let needle = arg_values.get(0).cloned().unwrap_or(Value::Nothing);
                         let found = arr_val.iter().any(|v| v.as_string() == needle.as_string());
@@ -8101,10 +10047,10 @@ impl Interpreter {
                             let arg_values: Vec<Value> = args.iter()
                                 .map(|arg| self.evaluate_expr(arg))
                                 .collect::<Result<Vec<_>, _>>()?;
-                            let result = stringbuilder_method_fn(&method_name, &obj_val, &arg_values)?;
+                            let result = stringbuilder_method_fn(method_name, &obj_val, &arg_values)?;
                             // Write the result array back to the destination variable
                             if let Value::Array(_) = &result {
-                                if let Expression::Variable(dest_id) = &args[1] {
+                                if let ExprKind::Variable(dest_id) = &args[1].kind {
                                     self.env.set(dest_id.as_str(), result.clone())?;
                                 }
                             }
@@ -8113,7 +10059,7 @@ impl Interpreter {
                         let arg_values: Result<Vec<Value>, RuntimeError> = args.iter()
                             .map(|arg| self.evaluate_expr(arg))
                             .collect();
-                        return stringbuilder_method_fn(&method_name, &obj_val, &arg_values?);
+                        return stringbuilder_method_fn(method_name, &obj_val, &arg_values?);
                     }
                     if type_name == "WebClient" {
                         // Collect headers from the object for curl -H flags
@@ -8153,7 +10099,7 @@ impl Interpreter {
                             cmd.arg(url);
                             cmd
                         };
-                        match method_name.as_str() {
+                        match method_name {
                             "downloadstring" => {
                                 let url = self.evaluate_expr(&args[0])?.as_string();
                                 let output = build_curl(&[], &url).output()
@@ -8178,6 +10124,7 @@ impl Interpreter {
                             "downloadfile" => {
                                 let url = self.evaluate_expr(&args[0])?.as_string();
                                 let path = self.evaluate_expr(&args[1])?.as_string();
+                                self.sandbox.check_path(&path)?;
                                 let output = build_curl(&["-o", &path], &url).output()
                                     .map_err(|e| RuntimeError::Custom(format!("curl failed: {}", e)))?;
                                 if !output.status.success() {
@@ -8277,7 +10224,7 @@ impl Interpreter {
                             let obj = crate::value::ObjectData { drawing_commands: Vec::new(), class_name: "HttpResponseMessage".to_string(), fields };
                             Ok(Value::Object(std::rc::Rc::new(std::cell::RefCell::new(obj))))
                         };
-                        match method_name.as_str() {
+                        match method_name {
                             "getstringasync" => {
                                 let url = self.evaluate_expr(&args[0])?.as_string();
                                 let output = build_http_curl("GET", &url, None, None).output()
@@ -8338,7 +10285,7 @@ impl Interpreter {
                     }
                     // HttpResponseMessage.Content.ReadAsStringAsync()
                     if type_name == "HttpResponseMessage" {
-                        match method_name.as_str() {
+                        match method_name {
                             "readasstringasync" | "tostring" => {
                                 let content = obj_ref.borrow().fields.get("content").cloned()
                                     .unwrap_or(Value::String(String::new()));
@@ -8349,7 +10296,7 @@ impl Interpreter {
                     }
                     // HttpWebRequest methods
                     if type_name == "HttpWebRequest" {
-                        match method_name.as_str() {
+                        match method_name {
                             "getresponse" | "getresponseasync" => {
                                 let url = obj_ref.borrow().fields.get("url").cloned()
                                     .unwrap_or(Value::String(String::new())).as_string();
@@ -8399,7 +10346,7 @@ impl Interpreter {
                     }
                     // HttpWebResponse methods
                     if type_name == "HttpWebResponse" {
-                        match method_name.as_str() {
+                        match method_name {
                             "getresponsestream" => {
                                 // Return the body as a string (simulates reading a stream)
                                 let body = obj_ref.borrow().fields.get("body").cloned()
@@ -8417,7 +10364,7 @@ impl Interpreter {
                             .map(|arg| self.evaluate_expr(arg))
                             .collect();
                         let arg_values = arg_values?;
-                        match method_name.as_str() {
+                        match method_name {
                             "next" => {
                                 // Simple LCG random number generator
                                 let seed = obj_ref.borrow().fields.get("__seed").map(|v| {
@@ -8484,7 +10431,7 @@ impl Interpreter {
 
                     // Stopwatch instance methods
                     if type_name == "Stopwatch" {
-                        match method_name.as_str() {
+                        match method_name {
                             "start" => {
                                 let now_ms = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_millis() as i64;
                                 obj_ref.borrow_mut().fields.insert("__start_ms".to_string(), Value::Long(now_ms));
@@ -8530,7 +10477,7 @@ impl Interpreter {
                             .collect();
                         let arg_values = arg_values?;
                         let sender_val = Value::Object(obj_ref.clone());
-                        match method_name.as_str() {
+                        match method_name {
                             "runworkerasync" => {
                                 // Set IsBusy = True
                                 obj_ref.borrow_mut().fields.insert("isbusy".to_string(), Value::Boolean(true));
@@ -8554,7 +10501,7 @@ impl Interpreter {
                                 if let Value::String(handler_name) = &dowork_handler {
                                     if !handler_name.is_empty() {
                                         let handler_lower = handler_name.to_lowercase();
-                                        if let Some(sub) = self.subs.get(&handler_lower).cloned() {
+                                        if let Some(sub) = self.sub(&handler_lower) {
                                             let _ = self.call_user_sub(&sub, &[sender_val.clone(), dw_args.clone()], None);
                                         }
                                     }
@@ -8589,7 +10536,7 @@ impl Interpreter {
                                 if let Value::String(handler_name) = &completed_handler {
                                     if !handler_name.is_empty() {
                                         let handler_lower = handler_name.to_lowercase();
-                                        if let Some(sub) = self.subs.get(&handler_lower).cloned() {
+                                        if let Some(sub) = self.sub(&handler_lower) {
                                             let _ = self.call_user_sub(&sub, &[sender_val.clone(), rw_args], None);
                                         }
                                     }
@@ -8619,7 +10566,7 @@ impl Interpreter {
                                 if let Value::String(handler_name) = &progress_handler {
                                     if !handler_name.is_empty() {
                                         let handler_lower = handler_name.to_lowercase();
-                                        if let Some(sub) = self.subs.get(&handler_lower).cloned() {
+                                        if let Some(sub) = self.sub(&handler_lower) {
                                             let _ = self.call_user_sub(&sub, &[sender_val.clone(), pc_args], None);
                                         }
                                     }
@@ -8678,7 +10625,7 @@ impl Interpreter {
                                 } else { 0xFF000000 }
                             };
 
-                            match method_name.as_str() {
+                            match method_name {
                                 "clear" => {
                                     let color_val = arg_values.get(0).cloned().unwrap_or(Value::Nothing);
                                     let c = get_color(&color_val);
@@ -8801,7 +10748,7 @@ impl Interpreter {
 
                     // Process instance methods
                     if type_name.eq_ignore_ascii_case("Process") || type_name.eq_ignore_ascii_case("System.Diagnostics.Process") {
-                        match method_name.as_str() {
+                        match method_name {
                             "waitforexit" => {
                                 let handle_id = obj_ref.borrow().fields.get("__handle").map(|v| v.as_string()).unwrap_or_default();
                                 if !handle_id.is_empty() {
@@ -8829,6 +10776,7 @@ impl Interpreter {
                             }
                             "start" => {
                                 // Instance Start() — start process using StartInfo
+                                self.sandbox.check(Capability::Processes)?;
                                 let start_info = obj_ref.borrow().fields.get("startinfo").cloned().unwrap_or(Value::Nothing);
                                 if let Value::Object(si_ref) = start_info {
                                     let filename = si_ref.borrow().fields.get("filename").map(|v| v.as_string()).unwrap_or_default();
@@ -8870,7 +10818,7 @@ impl Interpreter {
 
                         if let Some(sid) = socket_id {
                             // Network-backed StreamReader
-                            match method_name.as_str() {
+                            match method_name {
                                 "readline" => {
                                     if let Some(handle) = self.net_handles.get_mut(&sid) {
                                         match handle.tcp_read_line() {
@@ -8912,7 +10860,7 @@ impl Interpreter {
                             }
                         } else {
                             // File-backed StreamReader (existing logic)
-                            match method_name.as_str() {
+                            match method_name {
                                 "readline" => {
                                     let content = obj_ref.borrow().fields.get("__content").map(|v| v.as_string()).unwrap_or_default();
                                     let pos = obj_ref.borrow().fields.get("__position").map(|v| if let Value::Integer(i) = v { *i as usize } else { 0 }).unwrap_or(0);
@@ -8966,7 +10914,7 @@ impl Interpreter {
 
                         if let Some(sid) = socket_id {
                             // Network-backed StreamWriter — write directly to socket
-                            match method_name.as_str() {
+                            match method_name {
                                 "write" => {
                                     let text = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                                     if let Some(handle) = self.net_handles.get_mut(&sid) {
@@ -8998,7 +10946,7 @@ impl Interpreter {
                             }
                         } else {
                             // File-backed StreamWriter (existing logic)
-                            match method_name.as_str() {
+                            match method_name {
                                 "write" => {
                                     let text = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                                     let mut buf = obj_ref.borrow().fields.get("__buffer").map(|v| v.as_string()).unwrap_or_default();
@@ -9066,7 +11014,7 @@ impl Interpreter {
                         } else {
                             pattern.clone()
                         };
-                        match method_name.as_str() {
+                        match method_name {
                             "ismatch" => {
                                 let input = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                                 match regex::Regex::new(&full_pattern) {
@@ -9112,7 +11060,7 @@ impl Interpreter {
 
                     // ===== Task instance methods =====
                     if type_name == "Task" {
-                        match method_name.as_str() {
+                        match method_name {
                             "wait" => { return Ok(Value::Nothing); } // Already completed
                             "getawaiter" => { return Ok(obj_val.clone()); } // Return self
                             "getresult" => {
@@ -9142,7 +11090,7 @@ impl Interpreter {
 
                     // ===== Mutex instance methods =====
                     if type_name == "Mutex" {
-                        match method_name.as_str() {
+                        match method_name {
                             "waitone" => {
                                 obj_ref.borrow_mut().fields.insert("__owned".to_string(), Value::Boolean(true));
                                 return Ok(Value::Boolean(true));
@@ -9158,7 +11106,7 @@ impl Interpreter {
 
                     // ===== Semaphore instance methods =====
                     if type_name == "Semaphore" {
-                        match method_name.as_str() {
+                        match method_name {
                             "wait" | "waitone" => {
                                 let count = obj_ref.borrow().fields.get("__count").and_then(|v| v.as_integer().ok()).unwrap_or(0);
                                 if count > 0 {
@@ -9186,7 +11134,7 @@ impl Interpreter {
 
                     // ===== Timer instance methods =====
                     if type_name == "Timer" {
-                        match method_name.as_str() {
+                        match method_name {
                             "start" => {
                                 obj_ref.borrow_mut().fields.insert("enabled".to_string(), Value::Boolean(true));
                                 return Ok(Value::Nothing);
@@ -9207,7 +11155,7 @@ impl Interpreter {
                     if type_name == "FileStream" {
                         let arg_values: Result<Vec<Value>, RuntimeError> = args.iter().map(|a| self.evaluate_expr(a)).collect();
                         let arg_values = arg_values?;
-                        match method_name.as_str() {
+                        match method_name {
                             "read" => {
                                 // Read(buffer, offset, count) -> int
                                 let data = obj_ref.borrow().fields.get("__data").cloned().unwrap_or(Value::Array(Vec::new()));
@@ -9283,6 +11231,7 @@ impl Interpreter {
                             "close" | "dispose" => {
                                 // Write data to file before closing
                                 let path = obj_ref.borrow().fields.get("__path").map(|v| v.as_string()).unwrap_or_default();
+                                self.sandbox.check_path(&path)?;
                                 let data = obj_ref.borrow().fields.get("__data").cloned().unwrap_or(Value::Array(Vec::new()));
                                 if let Value::Array(ref arr) = data {
                                     let bytes: Vec<u8> = arr.iter().map(|v| v.as_integer().unwrap_or(0) as u8).collect();
@@ -9303,7 +11252,7 @@ impl Interpreter {
                     if type_name == "MemoryStream" {
                         let arg_values: Result<Vec<Value>, RuntimeError> = args.iter().map(|a| self.evaluate_expr(a)).collect();
                         let arg_values = arg_values?;
-                        match method_name.as_str() {
+                        match method_name {
                             "read" => {
                                 let data = obj_ref.borrow().fields.get("__data").cloned().unwrap_or(Value::Array(Vec::new()));
                                 let pos = obj_ref.borrow().fields.get("position").and_then(|v| if let Value::Long(l) = v { Some(*l as usize) } else { None }).unwrap_or(0);
@@ -9385,7 +11334,7 @@ impl Interpreter {
 
                     // ===== BinaryReader instance methods =====
                     if type_name == "BinaryReader" {
-                        match method_name.as_str() {
+                        match method_name {
                             "readbyte" => {
                                 let data = obj_ref.borrow().fields.get("__data").cloned().unwrap_or(Value::Array(Vec::new()));
                                 let pos = obj_ref.borrow().fields.get("__position").and_then(|v| if let Value::Long(l) = v { Some(*l as usize) } else { None }).unwrap_or(0);
@@ -9499,7 +11448,7 @@ impl Interpreter {
                     if type_name == "BinaryWriter" {
                         let arg_values: Result<Vec<Value>, RuntimeError> = args.iter().map(|a| self.evaluate_expr(a)).collect();
                         let arg_values = arg_values?;
-                        match method_name.as_str() {
+                        match method_name {
                             "write" => {
                                 let val = arg_values.get(0).cloned().unwrap_or(Value::Nothing);
                                 let bytes: Vec<Value> = match &val {
@@ -9547,7 +11496,7 @@ impl Interpreter {
                     if type_name == "TcpClient" {
                         let arg_values: Result<Vec<Value>, RuntimeError> = args.iter().map(|a| self.evaluate_expr(a)).collect();
                         let arg_values = arg_values?;
-                        match method_name.as_str() {
+                        match method_name {
                             "connect" => {
                                 let host = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                                 let port = arg_values.get(1).and_then(|v| v.as_integer().ok()).unwrap_or(0);
@@ -9608,7 +11557,7 @@ impl Interpreter {
                         let socket_id = obj_ref.borrow().fields.get("__socket_id")
                             .and_then(|v| if let Value::Long(id) = v { Some(*id) } else { None })
                             .unwrap_or(0);
-                        match method_name.as_str() {
+                        match method_name {
                             "read" => {
                                 // Read(buffer, offset, count) → returns bytes read
                                 let count = arg_values.get(2).and_then(|v| v.as_integer().ok()).unwrap_or(4096) as usize;
@@ -9679,7 +11628,7 @@ impl Interpreter {
 
                     // ===== TcpListener instance methods =====
                     if type_name == "TcpListener" {
-                        match method_name.as_str() {
+                        match method_name {
                             "start" => {
                                 let addr = obj_ref.borrow().fields.get("__address")
                                     .map(|v| v.as_string()).unwrap_or_else(|| "0.0.0.0".to_string());
@@ -9766,7 +11715,7 @@ impl Interpreter {
                         let socket_id = obj_ref.borrow().fields.get("__socket_id")
                             .and_then(|v| if let Value::Long(id) = v { Some(*id) } else { None })
                             .unwrap_or(0);
-                        match method_name.as_str() {
+                        match method_name {
                             "send" => {
                                 // Send(bytes, length, hostname, port)
                                 let data: Vec<u8> = if let Some(Value::Array(arr)) = arg_values.get(0) {
@@ -9830,7 +11779,7 @@ impl Interpreter {
                     if type_name == "SmtpClient" {
                         let arg_values: Result<Vec<Value>, RuntimeError> = args.iter().map(|a| self.evaluate_expr(a)).collect();
                         let arg_values = arg_values?;
-                        match method_name.as_str() {
+                        match method_name {
                             "send" => {
                                 // SmtpClient.Send(MailMessage) — use curl SMTP
                                 if let Some(Value::Object(msg_ref)) = arg_values.get(0) {
@@ -9945,7 +11894,7 @@ impl Interpreter {
 
                     // ===== Nullable instance methods =====
                     if type_name == "Nullable" {
-                        match method_name.as_str() {
+                        match method_name {
                             "getvalueordefault" => {
                                 let has = obj_ref.borrow().fields.get("hasvalue").and_then(|v| v.as_bool().ok()).unwrap_or(false);
                                 if has {
@@ -9980,6 +11929,7 @@ impl Interpreter {
                 if let Value::Object(obj_ref) = &obj_val {
                     let has_dialog_type = obj_ref.borrow().fields.contains_key("_dialog_type");
                     if has_dialog_type {
+                        self.sandbox.check(Capability::Dialogs)?;
                         return crate::builtins::dialogs::dialog_showdialog(&obj_val);
                     }
                 }
@@ -10007,7 +11957,7 @@ impl Interpreter {
 
                 // --- DbConnection methods ---
                 if type_name == "DbConnection" {
-                    match method_name.as_str() {
+                    match method_name {
                         "open" => {
                             // Get or update connection string
                             let conn_str = if !arg_values.is_empty() {
@@ -10018,6 +11968,7 @@ impl Interpreter {
                                 obj_ref.borrow().fields.get("connectionstring")
                                     .map(|v| v.as_string()).unwrap_or_default()
                             };
+                            self.sandbox.check(Capability::Databases)?;
                             let dam = crate::data_access::get_global_dam();
                             let conn_id = dam.lock().unwrap().open_connection(&conn_str)
                                 .map_err(|e| RuntimeError::Custom(e))?;
@@ -10099,6 +12050,7 @@ impl Interpreter {
                                 obj_ref.borrow().fields.get("connectionstring")
                                     .map(|v| v.as_string()).unwrap_or_default()
                             };
+                            self.sandbox.check(Capability::Databases)?;
                             let dam = crate::data_access::get_global_dam();
                             let conn_id = dam.lock().unwrap().open_connection(&conn_str)
                                 .map_err(|e| RuntimeError::Custom(e))?;
@@ -10165,7 +12117,7 @@ impl Interpreter {
                         } else { Vec::new() }
                     };
 
-                    match method_name.as_str() {
+                    match method_name {
                         // --- Parameters.Add / AddWithValue ---
                         "addparameter" | "addwithvalue" => {
                             // cmd.Parameters.AddWithValue("@name", value)
@@ -10419,7 +12371,7 @@ impl Interpreter {
 
                 // --- DbRecordset methods (ADODB Recordset API) ---
                 if type_name == "DbRecordset" {
-                    match method_name.as_str() {
+                    match method_name {
                         "open" => {
                             // rs.Open sql, conn
                             let sql = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
@@ -10528,7 +12480,7 @@ impl Interpreter {
 
                 // --- DbReader methods (ADO.NET SqlDataReader API) ---
                 if type_name == "DbReader" {
-                    match method_name.as_str() {
+                    match method_name {
                         "read" => {
                             let rs_id = obj_ref.borrow().fields.get("__rs_id")
                                 .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
@@ -10586,7 +12538,23 @@ impl Interpreter {
                             }
                             return Ok(Value::Integer(0));
                         }
-                        "getdouble" | "getfloat" | "getdecimal" => {
+                        "getdecimal" => {
+                            let rs_id = obj_ref.borrow().fields.get("__rs_id")
+                                .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
+                                .unwrap_or(0);
+                            let dam = crate::data_access::get_global_dam();
+                            let dam_lock = dam.lock().unwrap();
+                            if let Some(rs) = dam_lock.recordsets.get(&rs_id)
+                                && let Some(row) = rs.current_row()
+                            {
+                                let idx = arg_values[0].as_integer().unwrap_or(0) as usize;
+                                let val_str = row.get_by_index(idx).unwrap_or("0");
+                                let val = crate::decimal::Decimal::parse(val_str).unwrap_or(crate::decimal::Decimal::ZERO);
+                                return Ok(Value::Decimal(val));
+                            }
+                            return Ok(Value::Decimal(crate::decimal::Decimal::ZERO));
+                        }
+                        "getdouble" | "getfloat" => {
                             let rs_id = obj_ref.borrow().fields.get("__rs_id")
                                 .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
                                 .unwrap_or(0);
@@ -10755,7 +12723,7 @@ impl Interpreter {
 
                 // --- DbTransaction methods ---
                 if type_name == "DbTransaction" {
-                    match method_name.as_str() {
+                    match method_name {
                         "commit" => {
                             let conn_id = obj_ref.borrow().fields.get("__conn_id")
                                 .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
@@ -10781,7 +12749,7 @@ impl Interpreter {
 
                 // --- DataAdapter methods ---
                 if type_name == "DataAdapter" {
-                    match method_name.as_str() {
+                    match method_name {
                         "fill" => {
                             // adapter.Fill(dataTable) or adapter.Fill(dataSet)
                             let da_borrow = obj_ref.borrow();
@@ -10831,7 +12799,7 @@ impl Interpreter {
 
                 // --- DbParameters collection (for cmd.Parameters.AddWithValue) ---
                 if type_name == "DbParameters" {
-                    match method_name.as_str() {
+                    match method_name {
                         "addwithvalue" | "add" => {
                             let p_name = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                             let p_val = arg_values.get(1).map(|v| v.as_string()).unwrap_or_default();
@@ -10941,7 +12909,7 @@ impl Interpreter {
 
                 // --- DataRow methods ---
                 if type_name == "DataRow" {
-                    match method_name.as_str() {
+                    match method_name {
                         "item" | "" => {
                             // row.Item("colname") or row.Item(index) or row("colname") default indexer
                             match &arg_values[0] {
@@ -10993,7 +12961,7 @@ impl Interpreter {
 
                 // --- DataTable methods ---
                 if type_name == "DataTable" {
-                    match method_name.as_str() {
+                    match method_name {
                         "select" => {
                             // dt.Select() — returns all rows as an array
                             // dt.Select(filter) — basic filtering (not implemented, returns all)
@@ -11055,7 +13023,7 @@ impl Interpreter {
 
                 // --- DataSet methods ---
                 if type_name == "DataSet" {
-                    match method_name.as_str() {
+                    match method_name {
                         "tables" => {
                             // ds.Tables(index) or ds.Tables("name")
                             let tables = obj_ref.borrow().fields.get("__tables")
@@ -11098,11 +13066,11 @@ impl Interpreter {
                 if type_name == "BindingSource" {
                     let bs_name = obj_ref.borrow().fields.get("name")
                         .map(|v| v.as_string()).unwrap_or_default();
-                    match method_name.as_str() {
+                    match method_name {
                         "movenext" => {
                             let bs_val = Value::Object(obj_ref.clone());
                             let ds = obj_ref.borrow().fields.get("__datasource").cloned().unwrap_or(Value::Nothing);
-                            let count = self.binding_source_row_count_filtered(&bs_val);
+                            let count = self.binding_source_row_count_filtered(&bs_val)?;
                             let pos = obj_ref.borrow().fields.get("position")
                                 .and_then(|v| if let Value::Integer(i) = v { Some(*i) } else { None })
                                 .unwrap_or(0);
@@ -11113,13 +13081,13 @@ impl Interpreter {
                                 position: new_pos,
                                 count,
                             });
-                            self.refresh_bindings_filtered(&obj_ref, &ds, new_pos);
+                            self.refresh_bindings_filtered(&obj_ref, &ds, new_pos)?;
                             return Ok(Value::Nothing);
                         }
                         "moveprevious" => {
                             let bs_val = Value::Object(obj_ref.clone());
                             let ds = obj_ref.borrow().fields.get("__datasource").cloned().unwrap_or(Value::Nothing);
-                            let count = self.binding_source_row_count_filtered(&bs_val);
+                            let count = self.binding_source_row_count_filtered(&bs_val)?;
                             let pos = obj_ref.borrow().fields.get("position")
                                 .and_then(|v| if let Value::Integer(i) = v { Some(*i) } else { None })
                                 .unwrap_or(0);
@@ -11130,26 +13098,26 @@ impl Interpreter {
                                 position: new_pos,
                                 count,
                             });
-                            self.refresh_bindings_filtered(&obj_ref, &ds, new_pos);
+                            self.refresh_bindings_filtered(&obj_ref, &ds, new_pos)?;
                             return Ok(Value::Nothing);
                         }
                         "movefirst" => {
                             let bs_val = Value::Object(obj_ref.clone());
                             let ds = obj_ref.borrow().fields.get("__datasource").cloned().unwrap_or(Value::Nothing);
-                            let count = self.binding_source_row_count_filtered(&bs_val);
+                            let count = self.binding_source_row_count_filtered(&bs_val)?;
                             obj_ref.borrow_mut().fields.insert("position".to_string(), Value::Integer(0));
                             self.side_effects.push_back(crate::RuntimeSideEffect::BindingPositionChanged {
                                 binding_source_name: bs_name.clone(),
                                 position: 0,
                                 count,
                             });
-                            self.refresh_bindings_filtered(&obj_ref, &ds, 0);
+                            self.refresh_bindings_filtered(&obj_ref, &ds, 0)?;
                             return Ok(Value::Nothing);
                         }
                         "movelast" => {
                             let bs_val = Value::Object(obj_ref.clone());
                             let ds = obj_ref.borrow().fields.get("__datasource").cloned().unwrap_or(Value::Nothing);
-                            let count = self.binding_source_row_count_filtered(&bs_val);
+                            let count = self.binding_source_row_count_filtered(&bs_val)?;
                             let last = if count > 0 { count - 1 } else { 0 };
                             obj_ref.borrow_mut().fields.insert("position".to_string(), Value::Integer(last));
                             self.side_effects.push_back(crate::RuntimeSideEffect::BindingPositionChanged {
@@ -11157,7 +13125,7 @@ impl Interpreter {
                                 position: last,
                                 count,
                             });
-                            self.refresh_bindings_filtered(&obj_ref, &ds, last);
+                            self.refresh_bindings_filtered(&obj_ref, &ds, last)?;
                             return Ok(Value::Nothing);
                         }
                         "removecurrent" => {
@@ -11176,7 +13144,7 @@ impl Interpreter {
 
                 // --- DataBindings methods ---
                 if type_name == "DataBindings" {
-                    match method_name.as_str() {
+                    match method_name {
                         "add" => {
                             // control.DataBindings.Add(propertyName, dataSource, dataMember)
                             let prop_name = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
@@ -11234,7 +13202,7 @@ impl Interpreter {
                                     let pos = ds_ref.borrow().fields.get("position")
                                         .and_then(|v| if let Value::Integer(i) = v { Some(*i) } else { None })
                                         .unwrap_or(0);
-                                    let row = self.binding_source_get_row(&ds_val, pos);
+                                    let row = self.binding_source_get_row(&ds_val, pos)?;
                                     if let Value::Object(row_ref) = &row {
                                         let member_lower = data_member.to_lowercase();
                                         let cell_val = row_ref.borrow().fields.get(&member_lower)
@@ -11250,7 +13218,7 @@ impl Interpreter {
                                     let bs_name = ds_ref.borrow().fields.get("name")
                                         .map(|v| v.as_string()).unwrap_or_default();
                                     if !bs_name.is_empty() {
-                                        let count = self.binding_source_row_count(&Value::Object(ds_ref.clone()));
+                                        let count = self.binding_source_row_count(&Value::Object(ds_ref.clone()))?;
                                         self.side_effects.push_back(crate::RuntimeSideEffect::BindingPositionChanged {
                                             binding_source_name: bs_name,
                                             position: pos,
@@ -11272,7 +13240,7 @@ impl Interpreter {
             
             // Array properties accessed as method calls
             if let Value::Array(arr) = &obj_val {
-                match method_name.as_str() {
+                match method_name {
                     "length" | "count" if args.is_empty() => {
                         return Ok(Value::Integer(arr.len() as i32));
                     }
@@ -11281,10 +13249,11 @@ impl Interpreter {
             }
 
             if let Value::Collection(col_rc) = &obj_val {
-                 match method_name.as_str() {
+                 match method_name {
                     "add" => {
                         // VB.NET Collection.Add(item [, key [, before | after]])
-                        let val = self.evaluate_expr(&args[0])?;
+                        let val = crate::structures::copy_value(self.evaluate_expr(&args[0])?);
+                        self.budget.check_len(col_rc.borrow().items.len() + 1)?;
                         if args.len() >= 2 {
                             let key_val = self.evaluate_expr(&args[1])?;
                             let key_str = key_val.as_string();
@@ -11368,7 +13337,8 @@ impl Interpreter {
                     }
                     "insert" => {
                         let idx = self.evaluate_expr(&args[0])?.as_integer()? as usize;
-                        let val = self.evaluate_expr(&args[1])?;
+                        let val = crate::structures::copy_value(self.evaluate_expr(&args[1])?);
+                        self.budget.check_len(col_rc.borrow().items.len() + 1)?;
                         let items = &mut col_rc.borrow_mut().items;
                         if idx <= items.len() {
                             items.insert(idx, val);
@@ -11379,14 +13349,20 @@ impl Interpreter {
                     }
                     "addrange" => {
                         let val = self.evaluate_expr(&args[0])?;
+                        let added = match &val {
+                            Value::Array(arr) => arr.len(),
+                            Value::Collection(other) => other.borrow().items.len(),
+                            _ => 0,
+                        };
+                        self.budget.check_len(col_rc.borrow().items.len() + added)?;
                         if let Value::Array(arr) = val {
                             for v in arr {
-                                col_rc.borrow_mut().add(v);
+                                col_rc.borrow_mut().add(crate::structures::copy_value(v));
                             }
                         } else if let Value::Collection(other) = val {
                             let items: Vec<Value> = other.borrow().items.clone();
                             for v in items {
-                                col_rc.borrow_mut().add(v);
+                                col_rc.borrow_mut().add(crate::structures::copy_value(v));
                             }
                         }
                         return Ok(Value::Nothing);
@@ -11505,11 +13481,12 @@ impl Interpreter {
                     "insertrange" => {
                         let idx = self.evaluate_expr(&args[0])?.as_integer()? as usize;
                         let val = self.evaluate_expr(&args[1])?;
-                        let items = match val {
+                        let items: Vec<Value> = match val {
                             Value::Array(arr) => arr,
                             Value::Collection(c) => c.borrow().items.clone(),
                             _ => vec![val],
-                        };
+                        }.into_iter().map(crate::structures::copy_value).collect();
+                        self.budget.check_len(col_rc.borrow().items.len() + items.len())?;
                         col_rc.borrow_mut().insert_range(idx, items);
                         return Ok(Value::Nothing);
                     }
@@ -11522,11 +13499,11 @@ impl Interpreter {
                     "setrange" => {
                         let idx = self.evaluate_expr(&args[0])?.as_integer()? as usize;
                         let val = self.evaluate_expr(&args[1])?;
-                        let items = match val {
+                        let items: Vec<Value> = match val {
                             Value::Array(arr) => arr,
                             Value::Collection(c) => c.borrow().items.clone(),
                             _ => vec![val],
-                        };
+                        }.into_iter().map(crate::structures::copy_value).collect();
                         col_rc.borrow_mut().set_range(idx, &items)?;
                         return Ok(Value::Nothing);
                     }
@@ -11605,9 +13582,10 @@ impl Interpreter {
             }
 
             if let Value::Queue(q) = &obj_val {
-                match method_name.as_str() {
+                match method_name {
                     "enqueue" => {
-                        let val = self.evaluate_expr(&args[0])?;
+                        let val = crate::structures::copy_value(self.evaluate_expr(&args[0])?);
+                        self.budget.check_len(q.borrow().count() as usize + 1)?;
                         q.borrow_mut().enqueue(val);
                         return Ok(Value::Nothing);
                     }
@@ -11637,9 +13615,10 @@ impl Interpreter {
 
             // Stack methods
             if let Value::Stack(s) = &obj_val {
-                match method_name.as_str() {
+                match method_name {
                     "push" => {
-                        let val = self.evaluate_expr(&args[0])?;
+                        let val = crate::structures::copy_value(self.evaluate_expr(&args[0])?);
+                        self.budget.check_len(s.borrow().count() as usize + 1)?;
                         s.borrow_mut().push(val);
                         return Ok(Value::Nothing);
                     }
@@ -11669,9 +13648,10 @@ impl Interpreter {
 
             // HashSet methods
             if let Value::HashSet(h) = &obj_val {
-                match method_name.as_str() {
+                match method_name {
                     "add" => {
-                        let val = self.evaluate_expr(&args[0])?;
+                        let val = crate::structures::copy_value(self.evaluate_expr(&args[0])?);
+                        self.budget.check_len(h.borrow().count() as usize + 1)?;
                         let was_new = h.borrow_mut().add(val);
                         return Ok(Value::Boolean(was_new));
                     }
@@ -11697,10 +13677,11 @@ impl Interpreter {
 
             // Dictionary methods
             if let Value::Dictionary(d) = &obj_val {
-                match method_name.as_str() {
+                match method_name {
                     "add" => {
                         let key = self.evaluate_expr(&args[0])?;
-                        let val = self.evaluate_expr(&args[1])?;
+                        let val = crate::structures::copy_value(self.evaluate_expr(&args[1])?);
+                        self.budget.check_len(d.borrow().count() as usize + 1)?;
                         d.borrow_mut().add(key, val)?;
                         return Ok(Value::Nothing);
                     }
@@ -11744,7 +13725,7 @@ impl Interpreter {
                                 // we return the value. The boolean success is the wrapper.
                                 // For simplicity: store value in the ByRef variable if it's a Variable expression
                                 if args.len() >= 2 {
-                                    if let Expression::Variable(var_name) = &args[1] {
+                                    if let ExprKind::Variable(var_name) = &args[1].kind {
                                         self.env.set(var_name.as_str(), val.clone()).ok();
                                     }
                                 }
@@ -11762,17 +13743,18 @@ impl Interpreter {
 
             // ConcurrentDictionary methods
             if let Value::ConcurrentDictionary(d) = &obj_val {
-                match method_name.as_str() {
+                match method_name {
                     "tryadd" => {
                         let key = self.evaluate_expr(&args[0])?;
                         let val = self.evaluate_expr(&args[1])?;
+                        self.budget.check_len(d.count() as usize + 1)?;
                         return Ok(Value::Boolean(d.try_add(&key.as_string(), val)));
                     }
                     "trygetvalue" => {
                         let key = self.evaluate_expr(&args[0])?;
                         if let Some(val) = d.try_get_value(&key.as_string()) {
                              if args.len() >= 2 {
-                                if let Expression::Variable(var_name) = &args[1] {
+                                if let ExprKind::Variable(var_name) = &args[1].kind {
                                     self.env.set(var_name.as_str(), val.clone()).ok();
                                 }
                             }
@@ -11785,7 +13767,7 @@ impl Interpreter {
                         let key = self.evaluate_expr(&args[0])?;
                         if let Some(val) = d.try_remove(&key.as_string()) {
                              if args.len() >= 2 {
-                                if let Expression::Variable(var_name) = &args[1] {
+                                if let ExprKind::Variable(var_name) = &args[1].kind {
                                     self.env.set(var_name.as_str(), val.clone()).ok();
                                 }
                             }
@@ -11834,16 +13816,17 @@ impl Interpreter {
 
             // ConcurrentQueue methods
             if let Value::ConcurrentQueue(q) = &obj_val {
-                match method_name.as_str() {
+                match method_name {
                    "enqueue" => {
                        let val = self.evaluate_expr(&args[0])?;
+                       self.budget.check_len(q.count() as usize + 1)?;
                        q.enqueue(val);
                        return Ok(Value::Nothing);
                    }
                    "trydequeue" => {
                        if let Some(val) = q.try_dequeue() {
                             if args.len() >= 1 {
-                                if let Expression::Variable(var_name) = &args[0] {
+                                if let ExprKind::Variable(var_name) = &args[0].kind {
                                     self.env.set(var_name.as_str(), val.clone()).ok();
                                 }
                             }
@@ -11855,7 +13838,7 @@ impl Interpreter {
                    "trypeek" => {
                        if let Some(val) = q.try_peek() {
                             if args.len() >= 1 {
-                                if let Expression::Variable(var_name) = &args[0] {
+                                if let ExprKind::Variable(var_name) = &args[0].kind {
                                     self.env.set(var_name.as_str(), val.clone()).ok();
                                 }
                             }
@@ -11873,16 +13856,17 @@ impl Interpreter {
 
             // ConcurrentStack methods
             if let Value::ConcurrentStack(s) = &obj_val {
-                match method_name.as_str() {
+                match method_name {
                    "push" => {
                        let val = self.evaluate_expr(&args[0])?;
+                       self.budget.check_len(s.count() as usize + 1)?;
                        s.push(val);
                        return Ok(Value::Nothing);
                    }
                    "trypop" => {
                        if let Some(val) = s.try_pop() {
                             if args.len() >= 1 {
-                                if let Expression::Variable(var_name) = &args[0] {
+                                if let ExprKind::Variable(var_name) = &args[0].kind {
                                     self.env.set(var_name.as_str(), val.clone()).ok();
                                 }
                             }
@@ -11894,7 +13878,7 @@ impl Interpreter {
                    "trypeek" => {
                        if let Some(val) = s.try_peek() {
                             if args.len() >= 1 {
-                                if let Expression::Variable(var_name) = &args[0] {
+                                if let ExprKind::Variable(var_name) = &args[0].kind {
                                     self.env.set(var_name.as_str(), val.clone()).ok();
                                 }
                             }
@@ -11913,9 +13897,21 @@ impl Interpreter {
 
 
 
+        // Indexing an anonymous type member that holds an array: g.People(0)
+        if args.len() == 1 && let Ok(Value::Object(obj_ref)) = self.evaluate_expr(obj) {
+            let member = {
+                let data = obj_ref.borrow();
+                if data.class_name == query::ANONYMOUS_CLASS { data.fields.get(method_name).cloned() } else { None }
+            };
+            if let Some(array @ Value::Array(_)) = member {
+                let index = self.evaluate_expr(&args[0])?.as_integer()?;
+                return array.get_array_element(index as usize);
+            }
+        }
+
         // If no arguments, first try to access as a property (e.g., txt1.Text)
         if args.is_empty() {
-            if let Expression::Variable(obj_name) = obj {
+            if let ExprKind::Variable(obj_name) = &obj.kind {
                 // Try direct name first (e.g., btn0.Caption)
                 let property_key = format!("{}.{}", obj_name.as_str(), method.as_str());
                 if let Ok(val) = self.env.get(&property_key) {
@@ -11937,20 +13933,22 @@ impl Interpreter {
                     {
                         let obj_data = obj_ref.borrow();
                         class_name_str = obj_data.class_name.clone();
-                        if let Some(val) = obj_data.fields.get(&method_name) {
+                        if let Some(val) = obj_data.fields.get(method_name) {
                             return Ok(val.clone());
                         }
                     } // Drop borrow
                     
-                    if let Some(prop) = self.find_property(&class_name_str, &method_name) {
+                    if let Some(prop) = self.find_property(&class_name_str, method_name) {
                          if let Some(body) = &prop.getter {
                               let func = FunctionDecl {
                                   visibility: prop.visibility,
                                   name: prop.name.clone(),
+                                  type_parameters: Vec::new(),
                                   parameters: prop.parameters.clone(),
                                   return_type: prop.return_type.clone(),
                                   body: body.clone(),
                                   is_async: false,
+                                  is_iterator: false,
                                   is_extension: false,
                                   is_overridable: false,
                                   is_overrides: false,
@@ -11958,6 +13956,9 @@ impl Interpreter {
                                   is_shared: false,
                                   is_not_overridable: false,
                                   handles: None,
+                                  attributes: Vec::new(),
+                                  span: prop.span,
+                                  source_file: prop.source_file.clone(),
                               };
                               return self.call_user_function(&func, &[], Some(obj_ref.clone()));
                          }
@@ -11971,19 +13972,29 @@ impl Interpreter {
         let arg_values = arg_values?;
 
         // Get the object name (for forms, it's usually just the identifier)
-        let object_name = match obj {
-            Expression::Variable(id) => id.as_str().to_string(),
-            Expression::MemberAccess(_, id) => id.as_str().to_string(),
+        let object_name = match &obj.kind {
+            ExprKind::Variable(id) => id.as_str().to_string(),
+            ExprKind::MemberAccess(_, id) => id.as_str().to_string(),
             _ => String::new(), // MethodCall or other expression (e.g. LINQ chaining)
         };
 
         // Check if this is a qualified procedure call (e.g., Form2.QuickTest)
         // Try looking up module.method as a Sub or Function
         let qualified_name = format!("{}.{}", object_name.to_lowercase(), method_name);
-        if let Some(sub) = self.subs.get(&qualified_name).cloned() {
+        if let Some(sub) = self.sub(&qualified_name) {
+            let method = MethodDecl::Sub(SubDecl::clone(&sub));
+            if let Some(result) = self.call_named_overload(&method, args, &arg_values, None)? {
+                return Ok(result);
+            }
+            let arg_values = self.arrange_values(&method, args, arg_values)?;
             return self.call_user_sub(&sub, &arg_values, None);
         }
-        if let Some(func) = self.functions.get(&qualified_name).cloned() {
+        if let Some(func) = self.function(&qualified_name) {
+            let method = MethodDecl::Function(FunctionDecl::clone(&func));
+            if let Some(result) = self.call_named_overload(&method, args, &arg_values, None)? {
+                return Ok(result);
+            }
+            let arg_values = self.arrange_values(&method, args, arg_values)?;
             return self.call_user_function(&func, &arg_values, None);
         }
 
@@ -12004,22 +14015,27 @@ impl Interpreter {
                 let class_name_str = obj_ref.borrow().class_name.clone();
                 let class_name_lower = class_name_str.to_lowercase();
 
+                if crate::reflection::is_reflection_object(&obj_ref.borrow())
+                    && let Some(result) = self.reflection_method(&obj_ref, method_name, &arg_values)?
+                {
+                    return Ok(result);
+                }
                 if class_name_lower == "system.io.file" {
-                    return self.dispatch_file_method(&method_name, &arg_values);
+                    return self.dispatch_file_method(method_name, &arg_values);
                 } else if class_name_lower == "system.io.directory" {
-                    return self.dispatch_directory_method(&method_name, &arg_values);
+                    return self.dispatch_directory_method(method_name, &arg_values);
                 } else if class_name_lower == "system.io.path" {
-                    return self.dispatch_path_method(&method_name, &arg_values);
+                    return self.dispatch_path_method(method_name, &arg_values);
                 } else if class_name_lower == "system.console" {
-                    return self.dispatch_console_method(&method_name, &arg_values);
+                    return self.dispatch_console_method(method_name, &arg_values);
                 } else if class_name_lower == "application" || class_name_lower == "my.application" {
-                    return self.dispatch_application_method(&method_name, &arg_values);
+                    return self.dispatch_application_method(method_name, &arg_values);
                 } else if class_name_lower == "system.drawing.color" || class_name_lower == "color" {
                     if method_name.eq_ignore_ascii_case("fromargb") {
                          return crate::builtins::drawing_fns::color_from_argb_fn(&arg_values);
                     }
                     // Standard colors
-                    let argb = match method_name.to_lowercase().as_str() {
+                    let argb = match method_name {
                         "red" => Some(0xFFFF0000),
                         "green" => Some(0xFF008000),
                         "blue" => Some(0xFF0000FF),
@@ -12033,10 +14049,10 @@ impl Interpreter {
                         _ => None,
                     };
                     if let Some(val) = argb {
-                        return Ok(crate::builtins::drawing_fns::create_color_obj(&method_name, val));
+                        return Ok(crate::builtins::drawing_fns::create_color_obj(method_name, val));
                     }
                 } else if class_name_lower == "system.math" {
-                    return self.dispatch_math_method(&method_name, &arg_values);
+                    return self.dispatch_math_method(method_name, &arg_values);
                 } else if class_name_lower == "utf8encoding" {
                     if method_name == "getbytes" {
                         let s = arg_values.first().map(|v| v.as_string()).unwrap_or_default();
@@ -12070,7 +14086,7 @@ impl Interpreter {
                         return Ok(Value::String(hex.join("-")));
                     }
                 } else if class_name_lower == "task" {
-                    match method_name.as_str() {
+                    match method_name {
                         "wait" => {
                             let handle_id = obj_ref.borrow().fields.get("__handle").map(|v| v.as_string()).unwrap_or_default();
                             if !handle_id.is_empty() {
@@ -12083,7 +14099,7 @@ impl Interpreter {
                                                 break;
                                             }
                                         }
-                                        std::thread::sleep(std::time::Duration::from_millis(10));
+                                        self.budget.sleep(std::time::Duration::from_millis(10))?;
                                     }
                                 }
                             }
@@ -12100,7 +14116,7 @@ impl Interpreter {
                         return crate::builtins::cryptography_fns::sha256_hash_fn(&arg_values);
                     }
                 } else if class_name_lower == "thread" {
-                    match method_name.as_str() {
+                    match method_name {
                         "start" => {
                             let task = obj_ref.borrow().fields.get("__task").cloned();
                             if let Some(lambda) = task {
@@ -12124,11 +14140,16 @@ impl Interpreter {
                                 // Clone static state for the thread
                                 let functions = self.functions.clone();
                                 let subs = self.subs.clone();
+                                let overloads = self.overloads.clone();
                                 let classes = self.classes.clone();
                                 let namespace_map = self.namespace_map.clone();
+                                let budget = self.budget.for_thread();
+                                let sandbox = self.sandbox.clone();
                                 
                                 let join_handle = std::thread::spawn(move || {
-                                    let mut bg_interpreter = Interpreter::new_background(functions, subs, classes, namespace_map);
+                                    let mut bg_interpreter = Interpreter::new_background(functions, subs, overloads, classes, namespace_map);
+                                    bg_interpreter.budget = budget;
+                                    bg_interpreter.sandbox = sandbox;
                                     let lambda_val = shared_lambda.to_value();
                                     let _ = bg_interpreter.call_lambda(lambda_val, &[]);
                                     
@@ -12163,7 +14184,11 @@ impl Interpreter {
                 }
                 
                 // Use helper to find method in hierarchy
-                if let Some(method) = self.find_method(&class_name_str, &method_name) {
+                if let Some(method) = self.find_method(&class_name_str, method_name) {
+                     if let Some(result) = self.call_named_overload(&method, args, &arg_values, Some(obj_ref.clone()))? {
+                         return Ok(result);
+                     }
+                     let arg_values = self.arrange_values(&method, args, arg_values)?;
                      match method {
                          vybe_parser::ast::decl::MethodDecl::Sub(s) => {
                              self.call_user_sub(&s, &arg_values, Some(obj_ref.clone()))?;
@@ -12174,17 +14199,54 @@ impl Interpreter {
                          }
                      }
                 }
+
+                // Parameterized property: table.Item("key")
+                if let Some(prop) = self.find_property(&class_name_str, method_name).filter(|p| !p.parameters.is_empty()) {
+                    return self.get_property(&prop, &arg_values, obj_ref.clone());
+                }
+
+                // A field holding an instance with a Default property: table.rows(2)
+                let field = obj_ref.borrow().fields.get(method_name).cloned();
+                // An array field: rec.Scores(1)
+                if let Some(Value::Array(items)) = &field && !arg_values.is_empty() {
+                    let mut current = Value::Array(items.clone());
+                    for index in &arg_values {
+                        let index = index.as_integer()?;
+                        current = match current {
+                            Value::Array(inner) => usize::try_from(index).ok()
+                                .and_then(|i| inner.get(i).cloned())
+                                .ok_or_else(|| RuntimeError::Custom("Array index out of bounds".to_string()))?,
+                            _ => return Err(RuntimeError::Custom("Cannot index non-array dimension".to_string())),
+                        };
+                    }
+                    return Ok(current);
+                }
+                if let Some((prop, inner)) = field.and_then(|f| self.default_property_of(&f)) {
+                    return self.get_property(&prop, &arg_values, inner);
+                }
             }
         }
 
         // Try dispatching as a builtin qualified function call (e.g., Console.WriteLine)
         let qualified_call_name = format!("{}.{}", object_name.to_lowercase(), method_name);
+        self.sandbox.check_call(&qualified_call_name, &arg_values)?;
         match qualified_call_name.as_str() {
             "debug.print" => {
                 let msg = arg_values.iter().map(|v| v.as_string()).collect::<Vec<_>>().join(" ");
-                self.send_debug_output(format!("{}\n", msg));
+                self.send_debug_output(format!("{}\n", msg))?;
                 return Ok(Value::Nothing);
             }
+            // Attribute.GetCustomAttribute(GetType(Widget), GetType(ObsoleteAttribute))
+            "attribute.getcustomattribute" | "attribute.isdefined" => {
+                if let [Value::Object(element), rest @ ..] = arg_values.as_slice() {
+                    let method = if method_name == "isdefined" { "isdefined" } else { "getcustomattributes" };
+                    match self.reflection_method(element, method, rest)? {
+                        Some(Value::Array(found)) => return Ok(found.into_iter().next().unwrap_or(Value::Nothing)),
+                        Some(result) => return Ok(result),
+                        None => {}
+                    }
+                }
+            }
             // ---- MessageBox.Show ----
             "messagebox.show" => {
                 let msg = if !arg_values.is_empty() { arg_values[0].as_string() } else { String::new() };
@@ -12207,21 +14269,27 @@ impl Interpreter {
             "convert.todatetime" => {
                 return crate::builtins::cdate_fn(&arg_values);
             }
-            "convert.toint32" | "convert.toint16" | "convert.toint64" => {
+            "convert.tosbyte" | "convert.toint16" | "convert.touint16" | "convert.toint32"
+            | "convert.touint32" | "convert.toint64" | "convert.touint64" => {
+                let target = crate::integral::IntType::from_name(&qualified_call_name["convert.to".len()..])
+                    .unwrap_or(crate::integral::IntType::Integer);
                 if arg_values.len() >= 2 {
                     // Convert.ToInt32(value, base) — e.g. Convert.ToInt32("FF", 16)
                     let val_str = arg_values[0].as_string();
                     let base = arg_values[1].as_integer().unwrap_or(10);
                     match i64::from_str_radix(val_str.trim().trim_start_matches("0x").trim_start_matches("0X").trim_start_matches("&H").trim_start_matches("&h"), base as u32) {
-                        Ok(n) => return Ok(Value::Integer(n as i32)),
+                        Ok(n) => return target.from_bits(n as u64),
                         Err(_) => return Err(RuntimeError::Custom(format!("Convert.ToInt32: cannot convert '{}' with base {}", val_str, base))),
                     }
                 }
-                return crate::builtins::cint_fn(&arg_values);
+                return crate::integral::convert(arg_values.first().unwrap_or(&Value::Nothing), target);
             }
-            "convert.todouble" | "convert.tosingle" | "convert.todecimal" => {
+            "convert.todouble" | "convert.tosingle" => {
                 return crate::builtins::cdbl_fn(&arg_values);
             }
+            "convert.todecimal" => {
+                return crate::builtins::cdec_fn(&arg_values);
+            }
             "convert.tostring" => {
                 if arg_values.len() >= 2 {
                     // Convert.ToString(value, base) — e.g. Convert.ToString(255, 16)
@@ -12416,7 +14484,7 @@ impl Interpreter {
             // ---- Thread.Sleep ----
             "thread.sleep" | "system.threading.thread.sleep" => {
                 let ms = arg_values.get(0).map(|v| v.as_integer().unwrap_or(0)).unwrap_or(0);
-                std::thread::sleep(std::time::Duration::from_millis(ms.max(0) as u64));
+                self.budget.sleep(std::time::Duration::from_millis(ms.max(0) as u64))?;
                 return Ok(Value::Nothing);
             }
 
@@ -12441,13 +14509,18 @@ impl Interpreter {
                 let shared_lambda = lambda.to_shared();
                 let functions = self.functions.clone();
                 let subs = self.subs.clone();
+                let overloads = self.overloads.clone();
                 let classes = self.classes.clone();
                 let namespace_map = self.namespace_map.clone();
                 
+                let budget = self.budget.for_thread();
+                let sandbox = self.sandbox.clone();
                 let task_clone = shared_task_obj.clone();
                 
                 let join_handle = std::thread::spawn(move || {
-                    let mut bg_interpreter = Interpreter::new_background(functions, subs, classes, namespace_map);
+                    let mut bg_interpreter = Interpreter::new_background(functions, subs, overloads, classes, namespace_map);
+                    bg_interpreter.budget = budget;
+                    bg_interpreter.sandbox = sandbox;
                     let result = match bg_interpreter.call_lambda(shared_lambda.to_value(), &[]) {
                         Ok(v) => v.to_shared(),
                         Err(e) => {
@@ -12560,11 +14633,16 @@ impl Interpreter {
                     
                     let functions = self.functions.clone();
                     let subs = self.subs.clone();
+                    let overloads = self.overloads.clone();
                     let classes = self.classes.clone();
                     let namespace_map = self.namespace_map.clone();
+                    let budget = self.budget.for_thread();
+                    let sandbox = self.sandbox.clone();
                     
                     std::thread::spawn(move || {
-                        let mut bg_interpreter = Interpreter::new_background(functions, subs, classes, namespace_map);
+                        let mut bg_interpreter = Interpreter::new_background(functions, subs, overloads, classes, namespace_map);
+                        bg_interpreter.budget = budget;
+                        bg_interpreter.sandbox = sandbox;
                         let _ = bg_interpreter.call_lambda(shared_lambda.to_value(), &[shared_state.to_value()]);
                     });
                     return Ok(Value::Boolean(true));
@@ -12583,7 +14661,7 @@ impl Interpreter {
                 // Interlocked.Increment(ByRef variable) — increment and return new value
                 // In vybe, args[0] is a Variable expression that was already evaluated
                 // We need the variable name to update it. Use a fallback: get the value, increment, return.
-                if let Some(Expression::Variable(var_name)) = args.get(0) {
+                if let Some(ExprKind::Variable(var_name)) = args.first().map(|e| &e.kind) {
                     let current = self.env.get(var_name.as_str()).unwrap_or(Value::Integer(0));
                     let new_val = match current {
                         Value::Integer(i) => Value::Integer(i + 1),
@@ -12598,7 +14676,7 @@ impl Interpreter {
                 return Ok(Value::Integer(val + 1));
             }
             "interlocked.decrement" | "system.threading.interlocked.decrement" => {
-                if let Some(Expression::Variable(var_name)) = args.get(0) {
+                if let Some(ExprKind::Variable(var_name)) = args.first().map(|e| &e.kind) {
                     let current = self.env.get(var_name.as_str()).unwrap_or(Value::Integer(0));
                     let new_val = match current {
                         Value::Integer(i) => Value::Integer(i - 1),
@@ -12613,7 +14691,7 @@ impl Interpreter {
             }
             "interlocked.exchange" | "system.threading.interlocked.exchange" => {
                 // Interlocked.Exchange(ByRef location, value) — sets location = value, returns old
-                if let Some(Expression::Variable(var_name)) = args.get(0) {
+                if let Some(ExprKind::Variable(var_name)) = args.first().map(|e| &e.kind) {
                     let old_val = self.env.get(var_name.as_str()).unwrap_or(Value::Nothing);
                     let new_val = arg_values.get(1).cloned().unwrap_or(Value::Nothing);
                     let _ = self.env.set(var_name.as_str(), new_val);
@@ -12624,7 +14702,7 @@ impl Interpreter {
             "interlocked.compareexchange" | "system.threading.interlocked.compareexchange" => {
                 // Interlocked.CompareExchange(ByRef location, value, comparand)
                 // If location == comparand, sets location = value. Returns old value of location.
-                if let Some(Expression::Variable(var_name)) = args.get(0) {
+                if let Some(ExprKind::Variable(var_name)) = args.first().map(|e| &e.kind) {
                     let old_val = self.env.get(var_name.as_str()).unwrap_or(Value::Nothing);
                     let new_val = arg_values.get(1).cloned().unwrap_or(Value::Nothing);
                     let comparand = arg_values.get(2).cloned().unwrap_or(Value::Nothing);
@@ -12735,17 +14813,17 @@ impl Interpreter {
                             // Check for a sub or function with name "classname.member"
                             let qualified = format!("{}.{}", class_name.to_lowercase(), member_lower);
                             let method_args: Vec<Value> = arg_values[3..].to_vec();
-                            if let Some(sub) = self.subs.get(&qualified).cloned() {
+                            if let Some(sub) = self.sub(&qualified) {
                                 return self.call_user_sub(&sub, &method_args, Some(obj_ref.clone()));
                             }
-                            if let Some(func) = self.functions.get(&qualified).cloned() {
+                            if let Some(func) = self.function(&qualified) {
                                 return self.call_user_function(&func, &method_args, Some(obj_ref.clone()));
                             }
                             // Try direct method name
-                            if let Some(sub) = self.subs.get(&member_lower).cloned() {
+                            if let Some(sub) = self.sub(&member_lower) {
                                 return self.call_user_sub(&sub, &method_args, Some(obj_ref.clone()));
                             }
-                            if let Some(func) = self.functions.get(&member_lower).cloned() {
+                            if let Some(func) = self.function(&member_lower) {
                                 return self.call_user_function(&func, &method_args, Some(obj_ref.clone()));
                             }
                             return Err(RuntimeError::Custom(format!("CallByName: method '{}' not found on '{}'", member, class_name)));
@@ -12776,7 +14854,8 @@ impl Interpreter {
             "zipfile.createfromdirectory" | "system.io.compression.zipfile.createfromdirectory" => {
                 let src_dir = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                 let dest_zip = arg_values.get(1).map(|v| v.as_string()).unwrap_or_default();
-                // Use system zip command
+                // Uses the system zip command, so this needs Processes as well as the paths
+                self.sandbox.check(Capability::Processes)?;
                 let status = std::process::Command::new("zip")
                     .args(&["-r", &dest_zip, "."])
                     .current_dir(&src_dir)
@@ -12789,6 +14868,7 @@ impl Interpreter {
             "zipfile.extracttodirectory" | "system.io.compression.zipfile.extracttodirectory" => {
                 let src_zip = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                 let dest_dir = arg_values.get(1).map(|v| v.as_string()).unwrap_or_default();
+                self.sandbox.check(Capability::Processes)?;
                 let _ = std::fs::create_dir_all(&dest_dir);
                 let status = std::process::Command::new("unzip")
                     .args(&["-o", &src_zip, "-d", &dest_dir])
@@ -12926,19 +15006,19 @@ impl Interpreter {
             // ---- Debug.Write / Debug.WriteLine / Debug.Assert ----
             "debug.write" | "system.diagnostics.debug.write" => {
                 let msg = arg_values.iter().map(|v| v.as_string()).collect::<Vec<_>>().join(" ");
-                self.send_debug_output(msg);
+                self.send_debug_output(msg)?;
                 return Ok(Value::Nothing);
             }
             "debug.writeline" | "system.diagnostics.debug.writeline" => {
                 let msg = arg_values.iter().map(|v| v.as_string()).collect::<Vec<_>>().join(" ");
-                self.send_debug_output(format!("{}\n", msg));
+                self.send_debug_output(format!("{}\n", msg))?;
                 return Ok(Value::Nothing);
             }
             "debug.assert" | "system.diagnostics.debug.assert" => {
                 let condition = arg_values.get(0).map(|v| v.is_truthy()).unwrap_or(true);
                 if !condition {
                     let msg = arg_values.get(1).map(|v| v.as_string()).unwrap_or_else(|| "Debug.Assert failed".to_string());
-                    self.send_debug_output(format!("ASSERT FAILED: {}\n", msg));
+                    self.send_debug_output(format!("ASSERT FAILED: {}\n", msg))?;
                 }
                 return Ok(Value::Nothing);
             }
@@ -12956,7 +15036,11 @@ impl Interpreter {
             "string.join" | "system.string.join" => {
                 // String.Join(separator, array_or_items...)
                 let sep = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
-                if let Some(Value::Array(arr)) = arg_values.get(1) {
+                if let Some(seq) = arg_values.get(1).filter(|v| iterators::is_enumerable(v)) {
+                    let items = self.sequence_items(seq)?;
+                    let joined = items.iter().map(|v| v.as_string()).collect::<Vec<_>>().join(&sep);
+                    return Ok(Value::String(joined));
+                } else if let Some(Value::Array(arr)) = arg_values.get(1) {
                     let joined = arr.iter().map(|v| v.as_string()).collect::<Vec<_>>().join(&sep);
                     return Ok(Value::String(joined));
                 } else {
@@ -13052,11 +15136,19 @@ impl Interpreter {
             }
             "decimal.parse" | "system.decimal.parse" => {
                 let s = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
-                match s.trim().parse::<f64>() {
-                    Ok(n) => return Ok(Value::Double(n)),
-                    Err(_) => return Err(RuntimeError::Custom(format!("Decimal.Parse: '{}' is not a valid decimal", s))),
+                match crate::decimal::Decimal::parse(&s.replace(',', "")) {
+                    Some(d) => return Ok(Value::Decimal(d)),
+                    None => return Err(RuntimeError::Exception(
+                        "FormatException".to_string(),
+                        format!("Decimal.Parse: '{}' is not a valid decimal", s),
+                        None,
+                    )),
                 }
             }
+            "decimal.tryparse" | "system.decimal.tryparse" => {
+                let s = arg_values.first().map(|v| v.as_string()).unwrap_or_default();
+                return Ok(Value::Boolean(crate::decimal::Decimal::parse(&s.replace(',', "")).is_some()));
+            }
             "boolean.parse" | "system.boolean.parse" => {
                 let s = arg_values.get(0).map(|v| v.as_string()).unwrap_or_default();
                 match s.trim().to_lowercase().as_str() {
@@ -13614,13 +15706,13 @@ impl Interpreter {
             _ => {
                 // Prefix-based dispatch for static class calls (Path.*, Math.*, File.*, Console.*)
                 if qualified_call_name.starts_with("path.") || qualified_call_name.starts_with("system.io.path.") {
-                    return self.dispatch_path_method(&method_name, &arg_values);
+                    return self.dispatch_path_method(method_name, &arg_values);
                 } else if qualified_call_name.starts_with("math.") || qualified_call_name.starts_with("system.math.") {
-                    return self.dispatch_math_method(&method_name, &arg_values);
+                    return self.dispatch_math_method(method_name, &arg_values);
                 } else if qualified_call_name.starts_with("file.") || qualified_call_name.starts_with("system.io.file.") {
-                    return self.dispatch_file_method(&method_name, &arg_values);
+                    return self.dispatch_file_method(method_name, &arg_values);
                 } else if qualified_call_name.starts_with("directory.") || qualified_call_name.starts_with("system.io.directory.") {
-                    return self.dispatch_directory_method(&method_name, &arg_values);
+                    return self.dispatch_directory_method(method_name, &arg_values);
                 } else if qualified_call_name.starts_with("messagebox.") || qualified_call_name.starts_with("system.windows.forms.messagebox.") {
                     if method_name.eq_ignore_ascii_case("show") {
                         return crate::builtins::msgbox::msgbox(&arg_values);
@@ -13640,7 +15732,7 @@ impl Interpreter {
         // (which breaks chaining like .Where().Select()).
         if let Ok(ref val) = eval_result {
             if let Ok(items) = val.to_iterable() {
-                match method_name.as_str() {
+                match method_name {
                     // .Select(Function(x) expr)
                     "select" => {
                         let selector = self.evaluate_expr(&args[0])?;
@@ -13956,7 +16048,7 @@ impl Interpreter {
                         let mut result = Vec::new();
                         for item in &items {
                             let sub = self.call_lambda(selector.clone(), &[item.clone()])?;
-                            if let Ok(sub_items) = sub.to_iterable() {
+                            if let Ok(sub_items) = self.sequence_items(&sub) {
                                 result.extend(sub_items);
                             } else {
                                 result.push(sub);
@@ -13967,7 +16059,7 @@ impl Interpreter {
                     // .Zip(other, Function(a, b) expr)
                     "zip" => {
                         let other_val = self.evaluate_expr(&args[0])?;
-                        let other_items = other_val.to_iterable()?;
+                        let other_items = self.sequence_items(&other_val)?;
                         let combiner = if args.len() > 1 { Some(self.evaluate_expr(&args[1])?) } else { None };
                         let mut result = Vec::new();
                         for (a, b) in items.iter().zip(other_items.iter()) {
@@ -14014,7 +16106,7 @@ impl Interpreter {
                     // .Union(other) — set union preserving order
                     "union" => {
                         let other = self.evaluate_expr(&args[0])?;
-                        let other_items = other.to_iterable().unwrap_or_default();
+                        let other_items = self.sequence_items(&other).unwrap_or_default();
                         let mut seen = Vec::new();
                         let mut result = Vec::new();
                         for item in items.iter().chain(other_items.iter()) {
@@ -14029,7 +16121,7 @@ impl Interpreter {
                     // .Intersect(other) — set intersection
                     "intersect" => {
                         let other = self.evaluate_expr(&args[0])?;
-                        let other_items = other.to_iterable().unwrap_or_default();
+                        let other_items = self.sequence_items(&other).unwrap_or_default();
                         let other_keys: Vec<String> = other_items.iter().map(|v| v.as_string()).collect();
                         let mut seen = Vec::new();
                         let mut result = Vec::new();
@@ -14045,7 +16137,7 @@ impl Interpreter {
                     // .Except(other) — set difference
                     "except" => {
                         let other = self.evaluate_expr(&args[0])?;
-                        let other_items = other.to_iterable().unwrap_or_default();
+                        let other_items = self.sequence_items(&other).unwrap_or_default();
                         let other_keys: Vec<String> = other_items.iter().map(|v| v.as_string()).collect();
                         let result: Vec<Value> = items.into_iter()
                             .filter(|v| !other_keys.contains(&v.as_string()))
@@ -14055,7 +16147,7 @@ impl Interpreter {
                     // .Concat(other) — concatenation (allows duplicates)
                     "concat" if !matches!(val, Value::String(_)) => {
                         let other = self.evaluate_expr(&args[0])?;
-                        let other_items = other.to_iterable().unwrap_or_default();
+                        let other_items = self.sequence_items(&other).unwrap_or_default();
                         let mut result = items;
                         result.extend(other_items);
                         return Ok(Value::Array(result));
@@ -14144,7 +16236,7 @@ impl Interpreter {
         }
 
         // Handle form methods (extracted to keep call_method stack frame small)
-        self.dispatch_form_control_method(obj, &method_name, &object_name, &arg_values)
+        self.dispatch_form_control_method(obj, method_name, &object_name, &arg_values)
     }
 
     /// Dispatch form/control methods like Show, Hide, Close, PerformStep, etc.
@@ -14602,7 +16694,7 @@ impl Interpreter {
         }
 
         // Search extension functions
-        for (_key, func) in &self.functions {
+        for func in self.functions.values().flatten() {
             if func.is_extension && func.name.as_str().to_lowercase() == method_lower {
                 let func_clone = func.clone();
                 return self.call_user_function(&func_clone, &arg_vals, None);
@@ -14610,7 +16702,7 @@ impl Interpreter {
         }
 
         // Search extension subs
-        for (_key, sub) in &self.subs {
+        for sub in self.subs.values().flatten() {
             if sub.is_extension && sub.name.as_str().to_lowercase() == method_lower {
                 let sub_clone = sub.clone();
                 self.call_user_sub(&sub_clone, &arg_vals, None)?;
@@ -14621,74 +16713,323 @@ impl Interpreter {
         Err(RuntimeError::Custom(format!("No extension method found: {}", method.as_str())))
     }
 
-    // Generic helper for subs
-    fn call_user_sub_impl(&mut self, sub: &SubDecl, args: Option<&[Value]>, arg_exprs: Option<&[Expression]>, context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
-        // Push new scope
-        self.env.push_scope();
+    /// Pick the overload of `name` that a call should run, if `name(params)`
+    /// is one of several signatures. Returns the chosen declaration and, when
+    /// the arguments had to be evaluated to score candidates, their values.
+    fn resolve_overload(
+        &mut self,
+        name: &str,
+        params: &[vybe_parser::ast::decl::Parameter],
+        origin: crate::overloads::Origin,
+        args: Option<&[Value]>,
+        arg_exprs: Option<&[Expression]>,
+        context: &Option<Rc<RefCell<ObjectData>>>,
+    ) -> Result<Option<ResolvedOverload>, RuntimeError> {
+        if !self.overloads.is_overloaded(name) {
+            return Ok(None);
+        }
 
-        // Save previous object context and set new one
-        let prev_object = self.current_object.take();
-        self.current_object = context;
-        let prev_procedure = self.current_procedure.take();
-        self.current_procedure = Some(sub.name.as_str().to_string());
+        let from_class = context.as_ref().map(|obj| {
+            let class_name = obj.borrow().class_name.clone();
+            self.find_method_overloads(&class_name, name)
+        });
+        let candidates: Vec<MethodDecl> = match from_class {
+            Some(set) if set.iter().any(|m| crate::overloads::is_declaration(m, name, params, origin)) => set,
+            _ => match self.overloads.keys(&name.to_lowercase()).iter()
+                .map(|key| self.overload_set(key))
+                .find(|set| set.iter().any(|m| crate::overloads::is_declaration(m, name, params, origin)))
+            {
+                Some(set) => set,
+                None => return Ok(None),
+            },
+        };
+        if candidates.len() < 2 {
+            return Ok(None);
+        }
 
-        let mut byref_writebacks = Vec::new();
+        let arg_count = args.map(|a| a.len()).or(arg_exprs.map(|e| e.len())).unwrap_or(0);
+        let applicable: Vec<&MethodDecl> = candidates
+            .iter()
+            .filter(|m| crate::overloads::accepts_arg_count(crate::overloads::method_parameters(m), arg_count))
+            .collect();
+        match applicable.len() {
+            0 => {
+                return Err(RuntimeError::Exception(
+                    "MissingMemberException".to_string(),
+                    format!("No overload of '{}' accepts {} argument(s)", name, arg_count),
+                    None,
+                ));
+            }
+            1 => return Ok(Some((applicable[0].clone(), None))),
+            _ => {}
+        }
 
-        // Bind parameters
-        for (i, param) in sub.parameters.iter().enumerate() {
-            let mut val = Value::Nothing;
-            
+        // Several signatures fit the argument count: score them on the values.
+        let values: Vec<Value> = match (args, arg_exprs) {
+            (Some(values), _) => values.to_vec(),
+            (None, Some(exprs)) => {
+                let mut values = Vec::with_capacity(exprs.len());
+                for expr in exprs {
+                    values.push(self.evaluate_expr(expr)?);
+                }
+                values
+            }
+            (None, None) => Vec::new(),
+        };
+        // Named arguments go to different positions in each candidate, which
+        // is then scored and bound with its own arrangement.
+        let named = arg_exprs.filter(|exprs| crate::arguments::has_named(exprs));
+        let mut arranged: Vec<Option<Vec<Value>>> = Vec::new();
+        if let Some(exprs) = named {
+            for m in &applicable {
+                arranged.push(match self.arrange_values(m, exprs, values.clone()) {
+                    Ok(values) => Some(values),
+                    Err(e) if e.is_abort() => return Err(e),
+                    Err(_) => None,
+                });
+            }
+        }
+        let class_match = |class: Option<&str>, target: &str| self.class_match(class, target);
+        let scores: Vec<_> = applicable
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let values = if named.is_some() { arranged[i].as_ref()? } else { &values };
+                crate::overloads::score_candidate(crate::overloads::method_parameters(m), values, &class_match)
+            })
+            .collect();
+        match crate::overloads::select_best(&scores) {
+            Ok(best) => {
+                let evaluated = if named.is_some() {
+                    arranged.swap_remove(best)
+                } else if args.is_some() {
+                    None
+                } else {
+                    Some(values)
+                };
+                Ok(Some((applicable[best].clone(), evaluated)))
+            }
+            Err(tied) if tied.is_empty() => Err(RuntimeError::Exception(
+                "MissingMemberException".to_string(),
+                format!("Overload resolution failed because no accessible '{}' can be called with these arguments", name),
+                None,
+            )),
+            Err(tied) => {
+                let signatures: Vec<String> = tied.iter().map(|&i| crate::overloads::signature_display(applicable[i])).collect();
+                Err(RuntimeError::Exception(
+                    "AmbiguousMatchException".to_string(),
+                    format!("Overload resolution failed because no '{}' is most specific for these arguments:\n    {}", name, signatures.join("\n    ")),
+                    None,
+                ))
+            }
+        }
+    }
+
+    /// Run the overload picked by [`Self::resolve_overload`]. A Sub reached
+    /// from a Function call site evaluates to Nothing.
+    fn invoke_overload(
+        &mut self,
+        chosen: MethodDecl,
+        args: Option<&[Value]>,
+        evaluated: Option<Vec<Value>>,
+        arg_exprs: Option<&[Expression]>,
+        context: Option<Rc<RefCell<ObjectData>>>,
+    ) -> Result<Value, RuntimeError> {
+        let args = evaluated.as_deref().or(args);
+        match &chosen {
+            MethodDecl::Sub(s) => self.invoke_user_sub(s, args, arg_exprs, context),
+            MethodDecl::Function(f) => self.invoke_user_function(f, args, arg_exprs, context),
+        }
+    }
+
+    /// Define `params` in the current scope from the call's arguments.
+    /// Already-evaluated `args` take priority; `arg_exprs` are evaluated
+    /// otherwise and also name the variables ByRef parameters write back to.
+    /// Returns `(caller variable, parameter)` pairs for that writeback.
+    fn bind_parameters(
+        &mut self,
+        params: &[vybe_parser::ast::decl::Parameter],
+        args: Option<&[Value]>,
+        arg_exprs: Option<&[Expression]>,
+    ) -> Result<Vec<(String, String)>, RuntimeError> {
+        let arranged;
+        let arg_exprs = match arg_exprs {
+            Some(exprs) if crate::arguments::has_named(exprs) => {
+                let procedure = self.current_procedure.clone().unwrap_or_default();
+                arranged = crate::arguments::arrange(&procedure, &crate::arguments::user_params(params), crate::arguments::split(exprs),
+                    |p| Ok(params[p].default_value.clone().unwrap_or(ExprKind::Nothing.into())))?;
+                Some(arranged.as_slice())
+            }
+            other => other,
+        };
+        let mut byref_writebacks = Vec::new();
+        for (i, param) in params.iter().enumerate() {
             // ParamArray: last parameter collects remaining args into an array
             if param.is_param_array {
-                if let Some(values) = args {
-                    let remaining: Vec<Value> = values[i..].to_vec();
-                    val = Value::Array(remaining);
-                } else if let Some(exprs) = arg_exprs {
-                    let mut remaining = Vec::new();
-                    for expr in &exprs[i..] {
-                        remaining.push(self.evaluate_expr(expr)?);
+                let mut remaining: Vec<Value> = match (args, arg_exprs) {
+                    (Some(values), _) => values.get(i..).unwrap_or(&[]).to_vec(),
+                    (None, Some(exprs)) => {
+                        let mut remaining = Vec::new();
+                        for expr in exprs.get(i..).unwrap_or(&[]) {
+                            remaining.push(self.evaluate_expr(expr)?);
+                        }
+                        remaining
                     }
-                    val = Value::Array(remaining);
-                }
+                    (None, None) => Vec::new(),
+                };
+                // An array passed on its own is the ParamArray itself
+                let val = match remaining.as_slice() {
+                    [Value::Array(_)] => remaining.remove(0),
+                    _ => Value::Array(remaining),
+                };
                 self.env.define(param.name.as_str(), val);
                 break; // ParamArray must be last parameter
             }
 
-            if let Some(values) = args {
-                if i < values.len() {
-                    val = values[i].clone();
-                } else if param.is_optional {
-                    if let Some(ref default) = param.default_value {
-                        val = self.evaluate_expr(default)?;
+            let supplied = match (args, arg_exprs) {
+                (Some(values), _) => values.get(i).cloned(),
+                (None, Some(exprs)) => match exprs.get(i) {
+                    Some(expr) => Some(self.evaluate_expr(expr)?),
+                    None => None,
+                },
+                (None, None) => None,
+            };
+            let val = match supplied {
+                Some(v) => {
+                    if let (Some(ty), Some(expr)) = (&param.param_type, arg_exprs.and_then(|e| e.get(i)))
+                        && options::strict(&self.active_options)
+                    {
+                        self.check_strict_conversion(ty, expr, &v)?;
                     }
-                }
-            } else if let Some(exprs) = arg_exprs {
-                if i < exprs.len() {
-                    val = self.evaluate_expr(&exprs[i])?;
-                    let is_byref = param.pass_type == vybe_parser::ast::decl::ParameterPassType::ByRef;
-                    if is_byref {
-                        // Check if argument is a variable we can write back to
-                        match &exprs[i] {
-                            Expression::Variable(name) => {
-                                byref_writebacks.push((name.as_str().to_string(), param.name.as_str().to_string()));
-                            }
-                            _ => {}
+                    if param.pass_type == vybe_parser::ast::decl::ParameterPassType::ByRef {
+                        // Only a plain variable argument can be written back to
+                        if let Some(ExprKind::Variable(name)) = arg_exprs.and_then(|e| e.get(i)).map(|e| &e.kind) {
+                            byref_writebacks.push((name.as_str().to_string(), param.name.as_str().to_string()));
                         }
+                        v
+                    } else {
+                        crate::structures::copy_value(v)
                     }
                 }
+                None => match &param.default_value {
+                    Some(default) if param.is_optional => self.evaluate_expr(default)?,
+                    _ => Value::Nothing,
+                },
+            };
+            // An argument of another type goes through the class's conversions
+            let val = match conversion_target(param.param_type.as_ref(), &val) {
+                Some(target) => self.implicit_conversion(&target, val)?,
+                None => val,
+            };
+            // A tuple parameter sees the element names of its declared type
+            if let Some(ty) = param.param_type.as_ref().filter(|ty| is_tuple_type(ty)) {
+                self.env.define(param.name.as_str(), coerce_to_declared(Some(ty), val)?);
+                self.env.declare_type(param.name.as_str(), ty.clone());
+                continue;
             }
-
             self.env.define(param.name.as_str(), val);
         }
+        Ok(byref_writebacks)
+    }
+
+    /// Run `method` for a call naming some of its `args`, evaluated in call
+    /// order as `values`, when it is overloaded: the names are matched
+    /// against every overload before one is picked. None otherwise; the
+    /// caller then arranges the values for `method` itself.
+    fn call_named_overload(
+        &mut self,
+        method: &MethodDecl,
+        args: &[Expression],
+        values: &[Value],
+        context: Option<Rc<RefCell<ObjectData>>>,
+    ) -> Result<Option<Value>, RuntimeError> {
+        if !crate::arguments::has_named(args) {
+            return Ok(None);
+        }
+        let (name, params, origin) = match method {
+            MethodDecl::Sub(s) => (s.name.as_str(), &s.parameters, (s.span, s.source_file.as_deref())),
+            MethodDecl::Function(f) => (f.name.as_str(), &f.parameters, (f.span, f.source_file.as_deref())),
+        };
+        let (chosen, arranged) = match self.resolve_overload(name, params, origin, Some(values), Some(args), &context)? {
+            Some((chosen, Some(arranged))) => (chosen, arranged),
+            Some((chosen, None)) => {
+                let arranged = self.arrange_values(&chosen, args, values.to_vec())?;
+                (chosen, arranged)
+            }
+            None => return Ok(None),
+        };
+        self.invoke_overload(chosen, Some(&arranged), None, None, context).map(Some)
+    }
+
+    /// `values`, evaluated from `args`, in the parameter order of `method`
+    /// when some of `args` are named.
+    fn arrange_values(&mut self, method: &MethodDecl, args: &[Expression], values: Vec<Value>) -> Result<Vec<Value>, RuntimeError> {
+        if !crate::arguments::has_named(args) {
+            return Ok(values);
+        }
+        let (name, params) = match method {
+            MethodDecl::Sub(s) => (s.name.as_str(), &s.parameters),
+            MethodDecl::Function(f) => (f.name.as_str(), &f.parameters),
+        };
+        let named = crate::arguments::argument_names(args).into_iter().zip(values).collect();
+        crate::arguments::arrange(name, &crate::arguments::user_params(params), named, |p| match &params[p].default_value {
+            Some(default) => self.evaluate_expr(default),
+            None => Ok(Value::Nothing),
+        })
+    }
+
+    // Generic helper for subs
+    fn call_user_sub_impl(&mut self, sub: &SubDecl, args: Option<&[Value]>, arg_exprs: Option<&[Expression]>, context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
+        match self.resolve_overload(sub.name.as_str(), &sub.parameters, (sub.span, sub.source_file.as_deref()), args, arg_exprs, &context)? {
+            Some((chosen, values)) => self.invoke_overload(chosen, args, values, arg_exprs, context),
+            None => self.invoke_user_sub(sub, args, arg_exprs, context),
+        }
+    }
+
+    fn invoke_user_sub(&mut self, sub: &SubDecl, args: Option<&[Value]>, arg_exprs: Option<&[Expression]>, context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
+        let frame = frame_name(sub.name.as_str(), &context);
+        self.in_call_frame(frame, |interp| interp.run_user_sub(sub, args, arg_exprs, context))
+    }
+
+    /// Run a Sub's body with its arguments bound.
+    fn run_user_sub(&mut self, sub: &SubDecl, args: Option<&[Value]>, arg_exprs: Option<&[Expression]>, context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
+        // Push new scope
+        self.env.push_scope();
+
+        // Save previous object context and set new one
+        let prev_object = self.current_object.take();
+        self.current_object = context;
+        let prev_procedure = self.current_procedure.take();
+        self.current_procedure = Some(sub.name.as_str().to_string());
+
+        let bound = match self.bind_parameters(&sub.parameters, args, arg_exprs) {
+            Ok(w) => self.generic_bindings(sub.name.as_str(), &sub.type_parameters, &sub.parameters).map(|t| (w, t)),
+            Err(e) => Err(e),
+        };
+        let (byref_writebacks, type_args) = match bound {
+            Ok(b) => b,
+            Err(e) => {
+                self.env.pop_scope();
+                self.current_object = prev_object;
+                self.current_procedure = prev_procedure;
+                return Err(e);
+            }
+        };
+        let prev_type_args = std::mem::replace(&mut self.type_args, type_args);
 
         // Execute body with GoTo and On Error support
-        let result = self.execute_body_with_goto(&sub.body);
+        let prev_source = self.enter_source(&sub.source_file);
+        let prev_return = self.return_type.take();
+        let result = self.execute_body_with_goto(&sub.body, sub.span);
+        self.return_type = prev_return;
+        self.leave_source(prev_source);
         match result {
             Err(RuntimeError::Exit(ExitType::Sub)) => {}
             Err(e) => {
                 self.env.pop_scope();
                 self.current_object = prev_object;
                 self.current_procedure = prev_procedure;
+                self.type_args = prev_type_args;
                 return Err(e);
             }
             Ok(_) => {}
@@ -14711,6 +17052,7 @@ impl Interpreter {
         self.env.pop_scope();
         self.current_object = prev_object;
         self.current_procedure = prev_procedure;
+        self.type_args = prev_type_args;
         
         // Apply writebacks in restored scope
         for (var_name, val) in final_writebacks {
@@ -14720,6 +17062,22 @@ impl Interpreter {
         Ok(Value::Nothing)
     }
 
+    /// Run `body` as a call of the procedure `frame`. Past `max_call_depth`
+    /// nested calls it fails with a `StackOverflowException` instead; the
+    /// native stack is extended as the calls nest, so that limit is reached
+    /// before the thread's own stack runs out.
+    fn in_call_frame<T>(&mut self, frame: String, body: impl FnOnce(&mut Self) -> Result<T, RuntimeError>) -> Result<T, RuntimeError> {
+        self.budget.step()?;
+        if self.call_stack.len() >= self.max_call_depth {
+            let chain = std::iter::once(frame).chain(self.call_stack.iter().rev().cloned()).collect();
+            return Err(RuntimeError::StackOverflow(chain));
+        }
+        self.call_stack.push(frame);
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || body(self));
+        self.call_stack.pop();
+        result
+    }
+
     fn call_user_sub(&mut self, sub: &SubDecl, args: &[Value], context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
         self.call_user_sub_impl(sub, Some(args), None, context)
     }
@@ -14730,69 +17088,57 @@ impl Interpreter {
 
     // Generic helper for functions
     fn call_user_function_impl(&mut self, func: &FunctionDecl, args: Option<&[Value]>, arg_exprs: Option<&[Expression]>, context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
+        match self.resolve_overload(func.name.as_str(), &func.parameters, (func.span, func.source_file.as_deref()), args, arg_exprs, &context)? {
+            Some((chosen, values)) => self.invoke_overload(chosen, args, values, arg_exprs, context),
+            None => self.invoke_user_function(func, args, arg_exprs, context),
+        }
+    }
+
+    fn invoke_user_function(&mut self, func: &FunctionDecl, args: Option<&[Value]>, arg_exprs: Option<&[Expression]>, context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
+        let frame = frame_name(func.name.as_str(), &context);
+        self.in_call_frame(frame, |interp| interp.run_user_function(func, args, arg_exprs, context))
+    }
+
+    /// Run a Function's body with its arguments bound, for its result.
+    fn run_user_function(&mut self, func: &FunctionDecl, args: Option<&[Value]>, arg_exprs: Option<&[Expression]>, context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
         self.env.push_scope();
         let prev_object = self.current_object.take();
         self.current_object = context;
         let prev_procedure = self.current_procedure.take();
         self.current_procedure = Some(func.name.as_str().to_string());
 
-        let mut byref_writebacks = Vec::new();
-
-        for (i, param) in func.parameters.iter().enumerate() {
-            let mut val = Value::Nothing;
-
-            // ParamArray: last parameter collects remaining args into an array
-            if param.is_param_array {
-                if let Some(values) = args {
-                    let remaining: Vec<Value> = values[i..].to_vec();
-                    val = Value::Array(remaining);
-                } else if let Some(exprs) = arg_exprs {
-                    let mut remaining = Vec::new();
-                    for expr in &exprs[i..] {
-                        remaining.push(self.evaluate_expr(expr)?);
-                    }
-                    val = Value::Array(remaining);
-                }
-                self.env.define(param.name.as_str(), val);
-                break; // ParamArray must be last parameter
-            }
-
-            if let Some(values) = args {
-                if i < values.len() {
-                    val = values[i].clone();
-                } else if param.is_optional {
-                    if let Some(ref default) = param.default_value {
-                        val = self.evaluate_expr(default)?;
-                    }
-                }
-            } else if let Some(exprs) = arg_exprs {
-                if i < exprs.len() {
-                    val = self.evaluate_expr(&exprs[i])?;
-                    let is_byref = param.pass_type == vybe_parser::ast::decl::ParameterPassType::ByRef;
-                    if is_byref {
-                        match &exprs[i] {
-                            Expression::Variable(name) => {
-                                byref_writebacks.push((name.as_str().to_string(), param.name.as_str().to_string()));
-                            }
-                            _ => {}
-                        }
-                    }
-                } else if param.is_optional {
-                    if let Some(ref default) = param.default_value {
-                        val = self.evaluate_expr(default)?;
-                    }
-                }
+        let bound = match self.bind_parameters(&func.parameters, args, arg_exprs) {
+            Ok(w) => self.generic_bindings(func.name.as_str(), &func.type_parameters, &func.parameters).map(|t| (w, t)),
+            Err(e) => Err(e),
+        };
+        let (byref_writebacks, type_args) = match bound {
+            Ok(b) => b,
+            Err(e) => {
+                self.env.pop_scope();
+                self.current_object = prev_object;
+                self.current_procedure = prev_procedure;
+                return Err(e);
             }
-            self.env.define(param.name.as_str(), val);
+        };
+        if func.is_iterator {
+            // The body runs later, as the returned sequence is enumerated
+            let args = self.env.take_scope();
+            let me = std::mem::replace(&mut self.current_object, prev_object);
+            self.current_procedure = prev_procedure;
+            return Ok(self.iterator_object(func, args, me));
         }
+        let prev_type_args = std::mem::replace(&mut self.type_args, type_args);
 
         self.env.define(func.name.as_str(), Value::Nothing);
         let mut explicit_return: Option<Value> = None;
 
         let mut result = Value::Nothing;
         // Execute body with GoTo and On Error support
-        let body = func.body.clone();
-        let exec_result = self.execute_body_with_goto(&body);
+        let prev_source = self.enter_source(&func.source_file);
+        let prev_return = std::mem::replace(&mut self.return_type, func.return_type.clone());
+        let exec_result = self.execute_body_with_goto(&func.body, func.span);
+        self.return_type = prev_return;
+        self.leave_source(prev_source);
         match exec_result {
             Err(RuntimeError::Exit(ExitType::Function)) => {}
             Err(RuntimeError::Return(val)) => {
@@ -14804,6 +17150,7 @@ impl Interpreter {
                 self.env.pop_scope();
                 self.current_object = prev_object;
                 self.current_procedure = prev_procedure;
+                self.type_args = prev_type_args;
                 return Err(e);
             }
             Ok(_) => {}
@@ -14831,13 +17178,21 @@ impl Interpreter {
         self.env.pop_scope();
         self.current_object = prev_object;
         self.current_procedure = prev_procedure;
+        self.type_args = prev_type_args;
 
         // Writeback
         for (var_name, val) in final_writebacks {
             let _ = self.env.set(&var_name, val);
         }
 
-        Ok(result)
+        // A tuple result takes the element names of the return type
+        if func.return_type.as_ref().is_some_and(is_tuple_type) {
+            return coerce_to_declared(func.return_type.as_ref(), result);
+        }
+        match conversion_target(func.return_type.as_ref(), &result) {
+            Some(target) => self.implicit_conversion(&target, result),
+            None => Ok(result),
+        }
     }
 
     fn call_user_function(&mut self, func: &FunctionDecl, args: &[Value], context: Option<Rc<RefCell<ObjectData>>>) -> Result<Value, RuntimeError> {
@@ -14849,6 +17204,10 @@ impl Interpreter {
     }
 
     pub fn call_event_handler(&mut self, handler_name: &str, args: &[Value]) -> Result<(), RuntimeError> {
+        self.as_run(|this| this.call_event_handler_impl(handler_name, args))
+    }
+
+    fn call_event_handler_impl(&mut self, handler_name: &str, args: &[Value]) -> Result<(), RuntimeError> {
         let handler_name_lower = handler_name.to_lowercase();
 
         // Try to find the sub with or without module prefix
@@ -14856,7 +17215,8 @@ impl Interpreter {
         let mut found_sub = None;
         let mut module_name = None;
 
-        for (key, sub) in &self.subs {
+        for (key, subs) in &self.subs {
+            let Some(sub) = subs.last() else { continue };
             if key == &handler_name_lower {
                 // Found unqualified
                 found_sub = Some(sub.clone());
@@ -14893,6 +17253,220 @@ impl Interpreter {
         }
     }
 
+    /// The event `event_name` declared by `class_name` or one of its base
+    /// classes.
+    fn find_event(&self, class_name: &str, event_name: &str) -> Option<EventDecl> {
+        let cls = self.classes.get(&self.resolve_class_key(class_name)?)?;
+        if let Some(event) = cls.events.iter().find(|e| e.name.as_str().eq_ignore_ascii_case(event_name)) {
+            return Some(event.clone());
+        }
+        match &cls.inherits {
+            Some(vybe_parser::VBType::Custom(parent)) => self.find_event(parent, event_name),
+            _ => None,
+        }
+    }
+
+    /// `AddHandler` (`add`) or `RemoveHandler` on an event declared by a
+    /// class of the program. False when `event_target` names no such event,
+    /// leaving the statement to form controls and BackgroundWorker.
+    fn subscribe(&mut self, event_target: &str, handler: &str, add: bool) -> Result<bool, RuntimeError> {
+        let (source, event_name) = match event_target.rsplit_once('.') {
+            Some((source, event_name)) => (self.evaluate_expr(&dotted_expression(source)).ok(), event_name),
+            None => (self.current_object.clone().map(Value::Object), event_target),
+        };
+        let Some(Value::Object(source)) = source else { return Ok(false) };
+        let class_name = source.borrow().class_name.clone();
+        let Some(event) = self.find_event(&class_name, event_name) else { return Ok(false) };
+        let delegate = self.delegate_for(handler)?;
+        self.subscribe_delegate(&event, source, delegate, add)?;
+        Ok(true)
+    }
+
+    /// Adds `delegate` to the handlers of `event` on `source`, or removes it,
+    /// through the accessors of a `Custom Event`.
+    fn subscribe_delegate(&mut self, event: &EventDecl, source: Rc<RefCell<ObjectData>>, delegate: Value, add: bool) -> Result<(), RuntimeError> {
+        match &event.accessors {
+            Some(accessors) => {
+                let (param, body) = if add { &accessors.add_handler } else { &accessors.remove_handler };
+                self.call_event_accessor(event, vec![param.clone()], body, &[delegate], source)
+            }
+            None if add => {
+                crate::delegates::add_handler(&mut source.borrow_mut(), event.name.as_str(), delegate);
+                Ok(())
+            }
+            None => {
+                crate::delegates::remove_handler(&mut source.borrow_mut(), event.name.as_str(), &delegate);
+                Ok(())
+            }
+        }
+    }
+
+    /// When the `WithEvents` field `field_name` of `host` takes `source` (or,
+    /// with `add` false, lets go of it), subscribes the methods of `host`
+    /// that `Handles field.Event` to the events the program's classes
+    /// declare. Form controls are wired by `wire_events`.
+    fn handle_user_events(&mut self, source: &Value, field_name: &str, host: &Rc<RefCell<ObjectData>>, add: bool) -> Result<(), RuntimeError> {
+        let Value::Object(source) = source else { return Ok(()) };
+        let source_class = source.borrow().class_name.clone();
+        let host_class = host.borrow().class_name.clone();
+        let Some(cls) = self.resolve_class_key(&host_class).and_then(|k| self.classes.get(&k)) else { return Ok(()) };
+        let mut subscriptions = Vec::new();
+        for method in &cls.methods {
+            let (name, handles) = match method {
+                MethodDecl::Sub(s) => (&s.name, &s.handles),
+                MethodDecl::Function(f) => (&f.name, &f.handles),
+            };
+            for handle in handles.iter().flatten() {
+                if let Some((field, event)) = handle.split_once('.')
+                    && field.eq_ignore_ascii_case(field_name)
+                {
+                    subscriptions.push((name.as_str().to_string(), event.to_string()));
+                }
+            }
+        }
+        for (method, event_name) in subscriptions {
+            if let Some(event) = self.find_event(&source_class, &event_name) {
+                let delegate = crate::delegates::delegate_object(&method, Some(host.clone()));
+                self.subscribe_delegate(&event, source.clone(), delegate, add)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `RaiseEvent` of an event declared by the class of `source`: the
+    /// `RaiseEvent` accessor of a `Custom Event`, otherwise each handler of
+    /// this instance in turn. Handlers added or removed meanwhile take effect
+    /// from the next raise.
+    fn raise_event(&mut self, event: &EventDecl, source: Rc<RefCell<ObjectData>>, args: &[Value]) -> Result<(), RuntimeError> {
+        if let Some(accessors) = &event.accessors {
+            let (params, body) = &accessors.raise_event;
+            return self.call_event_accessor(event, params.clone(), body, args, source);
+        }
+        let handlers = crate::delegates::handlers(&source.borrow(), event.name.as_str());
+        for handler in handlers {
+            self.invoke_delegate(&handler, args)?;
+        }
+        Ok(())
+    }
+
+    /// Runs an accessor block of a `Custom Event` with `source` as `Me`.
+    fn call_event_accessor(
+        &mut self,
+        event: &EventDecl,
+        parameters: Vec<vybe_parser::Parameter>,
+        body: &Block,
+        args: &[Value],
+        source: Rc<RefCell<ObjectData>>,
+    ) -> Result<(), RuntimeError> {
+        let sub = SubDecl {
+            visibility: event.visibility,
+            name: event.name.clone(),
+            type_parameters: Vec::new(),
+            parameters,
+            body: body.clone(),
+            handles: None,
+            is_async: false,
+            is_extension: false,
+            is_overridable: false,
+            is_overrides: false,
+            is_must_override: false,
+            is_shared: false,
+            is_not_overridable: false,
+            attributes: Vec::new(),
+            span: event.span,
+            source_file: None,
+        };
+        match self.call_user_sub(&sub, args, Some(source)) {
+            Ok(_) | Err(RuntimeError::Exit(ExitType::Sub)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The delegate `AddressOf name` makes: a method of the object `name` is
+    /// qualified with or of `Me`, otherwise a module procedure.
+    fn delegate_for(&mut self, name: &str) -> Result<Value, RuntimeError> {
+        if let Some((qualifier, method)) = name.rsplit_once('.') {
+            let target = match self.evaluate_expr(&dotted_expression(qualifier)) {
+                Ok(Value::Object(obj)) => Some(obj),
+                // Module1.Handler
+                _ => None,
+            };
+            return Ok(match target {
+                Some(obj) => crate::delegates::delegate_object(method, Some(obj)),
+                None => crate::delegates::delegate_object(name, None),
+            });
+        }
+        let target = self.current_object.clone().filter(|obj| {
+            let class_name = obj.borrow().class_name.clone();
+            self.find_method(&class_name, name).is_some()
+        });
+        Ok(crate::delegates::delegate_object(name, target))
+    }
+
+    /// The module procedure `name`, optionally module-qualified, with the
+    /// module it belongs to. One of the current module wins over others.
+    fn find_procedure(&self, name: &str) -> Option<(MethodDecl, Option<String>)> {
+        let name = name.to_lowercase();
+        let lookup = |key: &str| {
+            self.sub(key).map(|s| MethodDecl::Sub(SubDecl::clone(&s)))
+                .or_else(|| self.function(key).map(|f| MethodDecl::Function(FunctionDecl::clone(&f))))
+        };
+        let module_of = |key: &str| key.rsplit_once('.').map(|(module, _)| module.to_string());
+        let mut keys = vec![name.clone()];
+        if let Some(module) = &self.current_module {
+            keys.insert(0, format!("{}.{}", module.to_lowercase(), name));
+        }
+        let suffix = format!(".{}", name);
+        let mut others: Vec<&String> = self.subs.keys().chain(self.functions.keys()).filter(|k| k.ends_with(&suffix)).collect();
+        others.sort();
+        keys.extend(others.into_iter().cloned());
+        keys.iter().find_map(|key| lookup(key).map(|method| (method, module_of(key))))
+    }
+
+    /// Calls a delegate or lambda. A handler declared without parameters
+    /// may stand for one that takes some, as VB's relaxed delegates allow.
+    pub fn invoke_delegate(&mut self, delegate: &Value, args: &[Value]) -> Result<Value, RuntimeError> {
+        let obj = match delegate {
+            Value::Lambda { .. } => return self.call_lambda(delegate.clone(), args),
+            Value::Object(obj) if crate::delegates::is_delegate(&obj.borrow()) => obj.clone(),
+            Value::Nothing => {
+                return Err(RuntimeError::Exception(
+                    "NullReferenceException".to_string(),
+                    "Object reference not set to an instance of an object.".to_string(),
+                    None,
+                ));
+            }
+            other => {
+                return Err(RuntimeError::TypeError { expected: "Delegate".to_string(), got: other.as_string() });
+            }
+        };
+        let (method_name, target) = {
+            let obj = obj.borrow();
+            (crate::delegates::method(&obj), crate::delegates::target(&obj))
+        };
+        let (method, module) = match &target {
+            Some(target) => {
+                let class_name = target.borrow().class_name.clone();
+                (self.find_method(&class_name, &method_name), None)
+            }
+            None => self.find_procedure(&method_name).map_or((None, None), |(m, module)| (Some(m), module)),
+        };
+        let Some(method) = method else {
+            return Err(RuntimeError::UndefinedFunction(method_name));
+        };
+        let args = if crate::overloads::method_parameters(&method).is_empty() { &[] } else { args };
+        let prev_module = self.current_module.clone();
+        if module.is_some() {
+            self.current_module = module;
+        }
+        let result = match &method {
+            MethodDecl::Sub(s) => self.call_user_sub(s, args, target),
+            MethodDecl::Function(f) => self.call_user_function(f, args, target),
+        };
+        self.current_module = prev_module;
+        result
+    }
+
     pub fn call_instance_method(&mut self, instance_name: &str, method_name: &str, args: &[Value]) -> Result<(), RuntimeError> {
         let instance_val = self.env.get(instance_name)?;
         if let Value::Object(obj_ref) = instance_val {
@@ -14908,6 +17482,15 @@ impl Interpreter {
         obj_ref: &std::rc::Rc<std::cell::RefCell<crate::value::ObjectData>>,
         method_name: &str,
         args: &[Value],
+    ) -> Result<(), RuntimeError> {
+        self.as_run(|this| this.call_method_on_object_impl(obj_ref, method_name, args))
+    }
+
+    fn call_method_on_object_impl(
+        &mut self,
+        obj_ref: &std::rc::Rc<std::cell::RefCell<crate::value::ObjectData>>,
+        method_name: &str,
+        args: &[Value],
     ) -> Result<(), RuntimeError> {
         let class_name = obj_ref.borrow().class_name.clone();
         if let Some(method) = self.find_method(&class_name, method_name) {
@@ -15072,6 +17655,10 @@ impl Interpreter {
     }
 
     pub fn trigger_event(&mut self, control_name: &str, event_type: vybe_forms::EventType, index: Option<i32>) -> Result<(), RuntimeError> {
+        self.as_run(|this| this.trigger_event_impl(control_name, event_type, index))
+    }
+
+    fn trigger_event_impl(&mut self, control_name: &str, event_type: vybe_forms::EventType, index: Option<i32>) -> Result<(), RuntimeError> {
         let handlers = if let Some(h) = self.events.get_handlers(control_name, &event_type) {
             h.clone()
         } else {
@@ -15095,13 +17682,91 @@ impl Interpreter {
         Ok(())
     }
 
-    fn execute_block(&mut self, stmts: &[Statement]) -> Result<(), RuntimeError> {
-        for stmt in stmts {
-            self.execute(stmt)?;
+    fn execute_block(&mut self, stmts: &Block) -> Result<(), RuntimeError> {
+        for (stmt, span) in stmts.iter_spanned() {
+            self.execute_at(stmt, span)?;
         }
         Ok(())
     }
 
+    /// Execute a statement, tagging any error it raises with `span` unless a
+    /// nested statement already did.
+    fn execute_at(&mut self, stmt: &Statement, span: Span) -> Result<(), RuntimeError> {
+        let result = match stmt {
+            // A plain variable target is found by where the statement is written
+            Statement::Assignment { target, value } => self.evaluate_expr(value).and_then(|val| {
+                match self.resolutions.local(span, target.as_str(), &self.env) {
+                    (symbol, Some(slot)) => self.assign_local(slot, symbol, target, value, val),
+                    (_, None) => self.assign_variable(target, value, val),
+                }
+            }),
+            Statement::CompoundAssignment { target, members, indices, operator, value }
+                if members.is_empty() && indices.is_empty() => self.compound_assign_at(span, target, operator, value),
+            _ => self.execute(stmt),
+        };
+        result.map_err(|e| self.locate_error(e, span))
+    }
+
+    /// `target op= value` for a plain variable, written at `span`.
+    fn compound_assign_at(&mut self, span: Span, target: &Identifier, operator: &vybe_parser::ast::stmt::CompoundOp, value: &Expression) -> Result<(), RuntimeError> {
+        let current = match self.resolutions.local(span, target.as_str(), &self.env) {
+            (_, Some(slot)) => self.env.slot(slot).clone(),
+            (_, None) => self.evaluate_expr(&ExprKind::Variable(target.clone()).into())?,
+        };
+        let rhs = self.evaluate_expr(value)?;
+        let new_val = self.compound_operation(operator, &current, &rhs)?;
+        // The right-hand side may have declared variables of its own
+        let (symbol, slot) = self.resolutions.local(span, target.as_str(), &self.env);
+        self.store_compound_symbol(target, symbol, slot, new_val)
+    }
+
+    fn locate_error(&self, error: RuntimeError, span: Span) -> RuntimeError {
+        if span.is_unknown() || error.is_control_flow() || error.location().is_some() {
+            return error;
+        }
+        RuntimeError::Located {
+            error: Box::new(error),
+            location: Box::new(SourceLocation {
+                file: self.current_source.clone(),
+                span,
+                procedure: self.current_procedure.clone(),
+            }),
+        }
+    }
+
+    /// Switch `current_source` to `file` when it is known; returns the
+    /// previous value so the caller can restore it.
+    fn enter_source(&mut self, file: &Option<String>) -> Option<String> {
+        let prev = match file {
+            Some(f) => self.current_source.replace(f.clone()),
+            None => return self.current_source.clone(),
+        };
+        if prev != self.current_source {
+            self.refresh_options();
+        }
+        prev
+    }
+
+    fn leave_source(&mut self, prev: Option<String>) {
+        if prev != self.current_source {
+            self.current_source = prev;
+            self.refresh_options();
+        }
+    }
+
+    /// Remember a file's `Option` statements before running any of its code.
+    fn record_options(&mut self, program: &Program) {
+        let file = program.source_file.clone().unwrap_or_default();
+        self.file_options.insert(file, program.options);
+        self.refresh_options();
+    }
+
+    fn refresh_options(&mut self) {
+        let file = self.current_source.as_deref().unwrap_or("");
+        let stated = self.file_options.get(file).copied().unwrap_or_default();
+        self.active_options = stated.or(self.options);
+    }
+
     /// Refresh all controls bound to a BindingSource after the position has changed.
     /// Reads the __bindings array (entries: "controlName|propertyName|dataMember")
     /// and pushes PropertyChange side effects for each.
@@ -15111,7 +17776,7 @@ impl Interpreter {
         bs_ref: &std::rc::Rc<std::cell::RefCell<crate::value::ObjectData>>,
         datasource: &Value,
         position: i32,
-    ) {
+    ) -> Result<(), RuntimeError> {
         let bindings: Vec<String> = bs_ref.borrow()
             .fields.get("__bindings")
             .and_then(|v| if let Value::Array(arr) = v {
@@ -15119,9 +17784,9 @@ impl Interpreter {
             } else { None })
             .unwrap_or_default();
 
-        if bindings.is_empty() { return; }
+        if bindings.is_empty() { return Ok(()); }
 
-        let row = self.binding_source_get_row(datasource, position);
+        let row = self.binding_source_get_row(datasource, position)?;
         if let Value::Object(row_ref) = &row {
             for entry in &bindings {
                 let parts: Vec<&str> = entry.split('|').collect();
@@ -15139,6 +17804,7 @@ impl Interpreter {
                 }
             }
         }
+        Ok(())
     }
 
     /// Like `refresh_bindings` but applies filter/sort from the BindingSource.
@@ -15148,7 +17814,7 @@ impl Interpreter {
         bs_ref: &std::rc::Rc<std::cell::RefCell<crate::value::ObjectData>>,
         datasource: &Value,
         position: i32,
-    ) {
+    ) -> Result<(), RuntimeError> {
         let bindings: Vec<String> = bs_ref.borrow()
             .fields.get("__bindings")
             .and_then(|v| if let Value::Array(arr) = v {
@@ -15156,7 +17822,7 @@ impl Interpreter {
             } else { None })
             .unwrap_or_default();
 
-        if bindings.is_empty() { return; }
+        if bindings.is_empty() { return Ok(()); }
 
         let filter = bs_ref.borrow().fields.get("filter")
             .map(|v| v.as_string()).unwrap_or_default();
@@ -15166,10 +17832,10 @@ impl Interpreter {
         // Efficiently get the target row value
         let row_val = if filter.is_empty() && sort.is_empty() {
             // Direct access: avoids re-creating all rows as ObjectData
-            self.binding_source_get_row(datasource, position)
+            self.binding_source_get_row(datasource, position)?
         } else {
             // Filter/Sort active: unfortunately must process all rows
-            let all_rows = self.get_all_data_rows(datasource);
+            let all_rows = self.get_all_data_rows(datasource)?;
             let filtered = Self::apply_filter_sort(&all_rows, &filter, &sort);
             filtered.get(position as usize).cloned().unwrap_or(Value::Nothing)
         };
@@ -15193,10 +17859,11 @@ impl Interpreter {
                 }
             }
         }
+        Ok(())
     }
 
     /// Get the row count from a BindingSource, applying its filter.
-    pub fn binding_source_row_count_filtered(&self, datasource: &Value) -> i32 {
+    pub fn binding_source_row_count_filtered(&self, datasource: &Value) -> Result<i32, RuntimeError> {
         if let Value::Object(obj_ref) = datasource {
             let obj = obj_ref.borrow();
             let dt_type = obj.fields.get("__type")
@@ -15212,29 +17879,29 @@ impl Interpreter {
                 if filter.is_empty() && sort.is_empty() {
                     return self.binding_source_row_count(&inner_ds);
                 }
-                let all_rows = self.get_all_data_rows(&inner_ds);
+                let all_rows = self.get_all_data_rows(&inner_ds)?;
                 let filtered = Self::apply_filter_sort(&all_rows, &filter, &sort);
-                return filtered.len() as i32;
+                return Ok(filtered.len() as i32);
             }
         }
         self.binding_source_row_count(datasource)
     }
 
     /// Get all DataRows from a datasource as Value::Object(DataRow) objects.
-    fn get_all_data_rows(&self, datasource: &Value) -> Vec<Value> {
-        let count = self.binding_source_row_count(datasource);
+    fn get_all_data_rows(&self, datasource: &Value) -> Result<Vec<Value>, RuntimeError> {
+        let count = self.binding_source_row_count(datasource)?;
         let mut rows = Vec::new();
         for i in 0..count {
-            let row = self.binding_source_get_row(datasource, i);
+            let row = self.binding_source_get_row(datasource, i)?;
             if !matches!(row, Value::Nothing) {
                 rows.push(row);
             }
         }
-        rows
+        Ok(rows)
     }
 
     /// Get columns+rows for DataGridView, applying BindingSource filter/sort.
-    pub fn get_datasource_table_data_filtered(&self, datasource: &Value) -> (Vec<String>, Vec<Vec<String>>) {
+    pub fn get_datasource_table_data_filtered(&self, datasource: &Value) -> Result<(Vec<String>, Vec<Vec<String>>), RuntimeError> {
         if let Value::Object(obj_ref) = datasource {
             let obj = obj_ref.borrow();
             let dt_type = obj.fields.get("__type")
@@ -15253,12 +17920,12 @@ impl Interpreter {
                 }
 
                 // Get full data, filter, sort, return
-                let (columns, all_rows) = self.get_datasource_table_data(&inner_ds);
-                if columns.is_empty() { return (columns, all_rows); }
+                let (columns, all_rows) = self.get_datasource_table_data(&inner_ds)?;
+                if columns.is_empty() { return Ok((columns, all_rows)); }
 
                 let col_lower: Vec<String> = columns.iter().map(|c| c.to_lowercase()).collect();
                 let filtered_rows = Self::apply_filter_sort_raw(&columns, &col_lower, &all_rows, &filter, &sort);
-                return (columns, filtered_rows);
+                return Ok((columns, filtered_rows));
             }
         }
         self.get_datasource_table_data(datasource)
@@ -15488,12 +18155,13 @@ impl Interpreter {
     /// execute its SelectCommand, and store the resulting recordset ID on the adapter.
     /// This is called lazily the first time a BindingSource (or control) tries
     /// to read data through a DataAdapter that hasn't been filled yet.
-    fn auto_fill_data_adapter(da_ref: &std::rc::Rc<std::cell::RefCell<crate::value::ObjectData>>) {
+    fn auto_fill_data_adapter(&self, da_ref: &std::rc::Rc<std::cell::RefCell<crate::value::ObjectData>>) -> Result<(), RuntimeError> {
         // Already filled?
         let already = da_ref.borrow().fields.get("__rs_id")
             .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
             .unwrap_or(0);
-        if already > 0 { return; }
+        if already > 0 { return Ok(()); }
+        self.sandbox.check(Capability::Databases)?;
 
         let da = da_ref.borrow();
         // Get the SQL query — designer stores as "selectcommand", constructor as "selectcommandtext"
@@ -15512,7 +18180,7 @@ impl Interpreter {
 
         if conn_str.is_empty() && conn_id_existing == 0 {
             eprintln!("[auto_fill] DataAdapter has no ConnectionString and no connection");
-            return;
+            return Ok(());
         }
 
         let dam = crate::data_access::get_global_dam();
@@ -15529,16 +18197,16 @@ impl Interpreter {
                 }
                 Err(e) => {
                     eprintln!("[auto_fill] Failed to connect: {}", e);
-                    return;
+                    return Ok(());
                 }
             }
         } else {
-            return;
+            return Ok(());
         };
 
         if sql.is_empty() {
             eprintln!("[auto_fill] DataAdapter has no SelectCommand");
-            return;
+            return Ok(());
         }
 
         match dam_lock.execute_reader(conn_id, &sql) {
@@ -15552,11 +18220,12 @@ impl Interpreter {
                 eprintln!("[auto_fill] Query error: {}", e);
             }
         }
+        Ok(())
     }
 
     /// Get the row count from a DataSource (DataTable, DataSet, DataAdapter, or Array).
     /// If the DataSource is a DataAdapter that hasn't been filled yet, auto-fill it.
-    pub fn binding_source_row_count(&self, datasource: &Value) -> i32 {
+    pub fn binding_source_row_count(&self, datasource: &Value) -> Result<i32, RuntimeError> {
         let datasource = match datasource {
             Value::String(proxy_name) => &self.resolve_control_as_sender(proxy_name),
             _ => datasource,
@@ -15574,7 +18243,7 @@ impl Interpreter {
                     let dam = crate::data_access::get_global_dam();
                     let dam_lock = dam.lock().unwrap();
                     if let Some(rs) = dam_lock.recordsets.get(&rs_id) {
-                        return rs.record_count();
+                        return Ok(rs.record_count());
                     }
                 } else if dt_type == "DataSet" {
                     // Use first table if available
@@ -15589,13 +18258,13 @@ impl Interpreter {
                             let dam = crate::data_access::get_global_dam();
                             let dam_lock = dam.lock().unwrap();
                             if let Some(rs) = dam_lock.recordsets.get(&rs_id) {
-                                return rs.record_count();
+                                return Ok(rs.record_count());
                             }
                         }
                     }
                 } else if dt_type == "DataAdapter" {
                     drop(obj);
-                    Self::auto_fill_data_adapter(obj_ref);
+                    self.auto_fill_data_adapter(obj_ref)?;
                     let rs_id = obj_ref.borrow().fields.get("__rs_id")
                         .and_then(|v| if let Value::Long(l) = v { Some(*l as u64) } else { None })
                         .unwrap_or(0);
@@ -15603,7 +18272,7 @@ impl Interpreter {
                         let dam = crate::data_access::get_global_dam();
                         let dam_lock = dam.lock().unwrap();
                         if let Some(rs) = dam_lock.recordsets.get(&rs_id) {
-                            return rs.record_count();
+                            return Ok(rs.record_count());
                         }
                     }
                 } else if dt_type == "BindingSource" {
@@ -15614,15 +18283,15 @@ impl Interpreter {
                     Self::inject_select_from_data_member(&inner_ds, &data_member);
                     return self.binding_source_row_count(&inner_ds);
                 }
-                0
+                Ok(0)
             }
-            Value::Array(arr) => arr.len() as i32,
-            _ => 0,
+            Value::Array(arr) => Ok(arr.len() as i32),
+            _ => Ok(0),
         }
     }
 
     /// Get a DataRow from a DataSource at a given position.
-    fn binding_source_get_row(&self, datasource: &Value, position: i32) -> Value {
+    fn binding_source_get_row(&self, datasource: &Value, position: i32) -> Result<Value, RuntimeError> {
         let datasource = match datasource {
             Value::String(proxy_name) => &self.resolve_control_as_sender(proxy_name),
             _ => datasource,
@@ -15649,7 +18318,7 @@ impl Interpreter {
 
                 // DataAdapter: auto-fill if needed
                 if dt_type == "DataAdapter" {
-                    Self::auto_fill_data_adapter(obj_ref);
+                    self.auto_fill_data_adapter(obj_ref)?;
                 }
 
                 let rs_id = {
@@ -15683,26 +18352,26 @@ impl Interpreter {
                                 flds.insert(col.to_lowercase(), Value::String(v));
                             }
                             let obj = crate::value::ObjectData { drawing_commands: Vec::new(), class_name: "DataRow".to_string(), fields: flds };
-                            return Value::Object(std::rc::Rc::new(std::cell::RefCell::new(obj)));
+                            return Ok(Value::Object(std::rc::Rc::new(std::cell::RefCell::new(obj))));
                         }
                     }
                 }
-                Value::Nothing
+                Ok(Value::Nothing)
             }
             Value::Array(arr) => {
                 if let Some(val) = arr.get(position as usize) {
-                    val.clone()
+                    Ok(val.clone())
                 } else {
-                    Value::Nothing
+                    Ok(Value::Nothing)
                 }
             }
-            _ => Value::Nothing,
+            _ => Ok(Value::Nothing),
         }
     }
 
     /// Get all columns and rows from a DataSource for rendering in DataGridView.
-    fn get_datasource_table_data(&self, datasource: &Value) -> (Vec<String>, Vec<Vec<String>>) {
-        match datasource {
+    fn get_datasource_table_data(&self, datasource: &Value) -> Result<(Vec<String>, Vec<Vec<String>>), RuntimeError> {
+        Ok(match datasource {
             Value::Object(obj_ref) => {
                 let dt_type = {
                     let obj = obj_ref.borrow();
@@ -15724,7 +18393,7 @@ impl Interpreter {
 
                 // DataAdapter: auto-fill if needed
                 if dt_type == "DataAdapter" {
-                    Self::auto_fill_data_adapter(obj_ref);
+                    self.auto_fill_data_adapter(obj_ref)?;
                 }
 
                 let rs_id = {
@@ -15752,7 +18421,7 @@ impl Interpreter {
                         let rows: Vec<Vec<String>> = rs.rows.iter()
                             .map(|r| r.values.clone())
                             .collect();
-                        return (columns, rows);
+                        return Ok((columns, rows));
                     }
                 }
                 (Vec::new(), Vec::new())
@@ -15785,22 +18454,23 @@ impl Interpreter {
                 }
             }
             _ => (Vec::new(), Vec::new()),
-        }
+        })
     }
 
     fn expr_to_string(&self, expr: &Expression) -> String {
-        match expr {
-            Expression::Variable(name) => name.as_str().to_string(),
-            Expression::Me => "Me".to_string(),
-            Expression::MyBase => "MyBase".to_string(),
-            Expression::Cast { expr, .. } => self.expr_to_string(expr),
-            Expression::MemberAccess(obj, member) => {
+        match &expr.kind {
+            ExprKind::Variable(name) => name.as_str().to_string(),
+            ExprKind::Me => "Me".to_string(),
+            ExprKind::MyBase => "MyBase".to_string(),
+            ExprKind::Cast { expr, .. } => self.expr_to_string(expr),
+            ExprKind::MemberAccess(obj, member) => {
                 format!("{}.{}", self.expr_to_string(obj), member.as_str())
             }
             _ => "[expr]".to_string(),
         }
     }
     fn dispatch_file_method(&mut self, method_name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        self.sandbox.check_call(&format!("file.{}", method_name.to_lowercase()), args)?;
         match method_name.to_lowercase().as_str() {
             "exists" => {
                  let path = args.get(0).ok_or(RuntimeError::Custom("Missing path argument".to_string()))?.as_string();
@@ -15902,6 +18572,7 @@ impl Interpreter {
     }
 
     fn dispatch_directory_method(&mut self, method_name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        self.sandbox.check_call(&format!("directory.{}", method_name.to_lowercase()), args)?;
         match method_name.to_lowercase().as_str() {
             "exists" => {
                 let path = args.get(0).ok_or(RuntimeError::Custom("Missing path argument".to_string()))?.as_string();
@@ -16029,6 +18700,7 @@ impl Interpreter {
             }
             "gettempfilename" => {
                  let tmp = std::env::temp_dir();
+                 self.sandbox.check_path(&tmp.to_string_lossy())?;
                  let name = format!("tmp{:x}.tmp", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
                  let path = tmp.join(name);
                  // Create the file like .NET does
@@ -16066,7 +18738,7 @@ impl Interpreter {
                 } else {
                     msg
                 };
-                self.send_console_output(final_msg);
+                self.send_console_output(final_msg)?;
                 Ok(Value::Nothing)
             }
             "readline" | "readkey" => {
@@ -16229,8 +18901,11 @@ impl Interpreter {
             "round" => crate::builtins::math_fns::round_fn(args),
             "floor" => crate::builtins::math_fns::floor_fn(args),
             "truncate" => {
-                let val = args.get(0).ok_or(RuntimeError::Custom("Math.Truncate requires an argument".to_string()))?.as_double()?;
-                Ok(Value::Double(val.trunc()))
+                let val = args.get(0).ok_or(RuntimeError::Custom("Math.Truncate requires an argument".to_string()))?;
+                if let Value::Decimal(d) = val {
+                    return Ok(Value::Decimal(d.round(0, crate::decimal::Rounding::ToZero)));
+                }
+                Ok(Value::Double(val.as_double()?.trunc()))
             }
             "ceiling" => crate::builtins::math_fns::ceiling_fn(args),
             "pow" => crate::builtins::math_fns::pow_fn(args),
@@ -16293,6 +18968,10 @@ impl Interpreter {
     }
 
     fn call_lambda(&mut self, lambda_val: Value, args: &[Value]) -> Result<Value, RuntimeError> {
+        self.in_call_frame("Lambda".to_string(), |interp| interp.run_lambda(lambda_val, args))
+    }
+
+    fn run_lambda(&mut self, lambda_val: Value, args: &[Value]) -> Result<Value, RuntimeError> {
         if let Value::Lambda { params, body, env } = lambda_val {
             if args.len() != params.len() {
                 return Err(RuntimeError::Custom(format!("Lambda expects {} arguments, got {}", params.len(), args.len())));
@@ -16313,6 +18992,8 @@ impl Interpreter {
                 self.env.define(param.name.as_str(), arg.clone());
             }
             
+            // A lambda's `Return` isn't the enclosing Function's
+            let prev_return = self.return_type.take();
             let result = match &*body {
                 vybe_parser::ast::expr::LambdaBody::Expression(expr) => {
                     self.evaluate_expr(expr)
@@ -16326,8 +19007,8 @@ impl Interpreter {
                 }
                 vybe_parser::ast::expr::LambdaBody::Block(stmts) => {
                     let mut final_res = Ok(Value::Nothing);
-                    for stmt in stmts {
-                        match self.execute(stmt) {
+                    for (stmt, span) in stmts.iter_spanned() {
+                        match self.execute_at(stmt, span) {
                             Ok(_) => {},
                             Err(RuntimeError::Return(val)) => {
                                 final_res = Ok(val.unwrap_or(Value::Nothing));
@@ -16357,6 +19038,7 @@ impl Interpreter {
             
             // Restore environment
             self.env = prev_env;
+            self.return_type = prev_return;
             result
         } else {
             Err(RuntimeError::Custom("Not a lambda".to_string()))
@@ -16371,6 +19053,11 @@ impl Default for Interpreter {
 }
 
 /// Compare two Values for ordering. Numbers compare numerically, strings lexically.
+/// Query rows as `Into` elements: each row's variables and the value it stands for.
+fn query_elements(rows: Vec<query::Row>) -> Vec<query::Element> {
+    rows.into_iter().map(|row| (row.clone(), query::row_value(row))).collect()
+}
+
 fn compare_values_ordering(a: &Value, b: &Value) -> std::cmp::Ordering {
     // Try to extract numeric values for comparison
     let a_num = match a {
@@ -16426,15 +19113,167 @@ fn set_multi_dim_element(arr: &mut Value, indices: &[usize], val: Value) -> Resu
     Err(RuntimeError::Custom("Not an array".to_string()))
 }
 
+/// The CLR name of a VB type: `Int32` for Integer, ``Box`1`` for `Box(Of T)`.
+fn clr_type_name(vb_name: &str) -> String {
+    if let Some((base, args)) = crate::generics::split_type_arguments(vb_name) {
+        return format!("{}`{}", base, args.len());
+    }
+    match vb_name.to_lowercase().as_str() {
+        "integer" => "Int32".to_string(),
+        "long" => "Int64".to_string(),
+        "short" => "Int16".to_string(),
+        "date" => "DateTime".to_string(),
+        _ => vb_name.rsplit('.').next().unwrap_or(vb_name).to_string(),
+    }
+}
+
+/// A `Type` object for `GetType(T)`.
+fn type_object(vb_name: &str) -> Value {
+    let name = clr_type_name(vb_name);
+    let mut fields = std::collections::HashMap::new();
+    fields.insert("fullname".to_string(), Value::String(format!("System.{}", name)));
+    fields.insert("name".to_string(), Value::String(name));
+    fields.insert("namespace".to_string(), Value::String("System".to_string()));
+    fields.insert("__type".to_string(), Value::String("Type".to_string()));
+    Value::Object(Rc::new(RefCell::new(crate::value::ObjectData { drawing_commands: Vec::new(), class_name: "Type".to_string(), fields })))
+}
+
+/// Bind each type parameter to the argument in the same position.
+fn bind_type_args(bindings: &mut TypeArgs, type_params: &[TypeParameter], args: &[String]) {
+    for (tp, arg) in type_params.iter().zip(args) {
+        bindings.insert(tp.name.as_str().to_lowercase(), arg.clone());
+    }
+}
+
+/// The type a `CInt`/`CDbl`/... conversion function converts to.
+fn conversion_function_target(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "cstr" => "String",
+        "cint" => "Integer",
+        "clng" => "Long",
+        "csbyte" => "SByte",
+        "cbyte" => "Byte",
+        "cshort" => "Short",
+        "cushort" => "UShort",
+        "cuint" => "UInteger",
+        "culng" => "ULong",
+        "csng" => "Single",
+        "cdbl" => "Double",
+        "cdec" => "Decimal",
+        "cbool" => "Boolean",
+        "cchar" => "Char",
+        "cdate" => "Date",
+        _ => return None,
+    })
+}
+
+/// An operator declaration as the Shared Function that implements it.
+fn operator_function(op: &vybe_parser::ast::decl::OperatorDecl) -> FunctionDecl {
+    FunctionDecl {
+        visibility: op.visibility,
+        name: Identifier::new(format!("Operator {}", op.symbol)),
+        type_parameters: Vec::new(),
+        parameters: op.parameters.clone(),
+        return_type: op.return_type.clone(),
+        body: op.body.clone(),
+        handles: None,
+        is_async: false,
+        is_iterator: false,
+        is_extension: false,
+        is_overridable: false,
+        is_overrides: false,
+        is_must_override: false,
+        is_shared: true,
+        is_not_overridable: false,
+        attributes: Vec::new(),
+        span: op.span,
+        source_file: None,
+    }
+}
+
+/// `Math.Round(x, MidpointRounding.AwayFromZero)` passes the mode where the
+/// decimal places would go; move it to the third argument `round_fn` expects.
+fn midpoint_rounding_args(args: &[Expression], mut values: Vec<Value>) -> Vec<Value> {
+    let is_mode = |e: &Expression| matches!(&e.kind, ExprKind::MemberAccess(obj, _)
+        if matches!(&obj.kind, ExprKind::Variable(n) if n.as_str().eq_ignore_ascii_case("MidpointRounding")));
+    if args.len() == 2 && is_mode(&args[1]) {
+        values.insert(1, Value::Integer(0));
+    }
+    values
+}
+
+/// Decimal or one of the integral types, whose variables convert what is
+/// assigned to them.
+fn is_exact_numeric_type(ty: &vybe_parser::VBType) -> bool {
+    *ty == vybe_parser::VBType::Decimal || crate::integral::IntType::from_vb_type(ty).is_some()
+}
+
+/// `value` converted to a variable's declared Decimal or integral type.
+fn coerce_to_declared(var_type: Option<&vybe_parser::VBType>, value: Value) -> Result<Value, RuntimeError> {
+    match var_type {
+        Some(vybe_parser::VBType::Decimal) => Ok(Value::Decimal(crate::decimal::to_decimal(&value)?)),
+        // Whole numbers widen to a floating-point variable
+        Some(vybe_parser::VBType::Double) if crate::integral::IntType::of(&value).is_some() => {
+            Ok(Value::Double(value.as_double()?))
+        }
+        Some(vybe_parser::VBType::Single) if crate::integral::IntType::of(&value).is_some() => {
+            Ok(Value::Single(value.as_double()? as f32))
+        }
+        Some(vybe_parser::VBType::Custom(text)) => match crate::tuples::parse_type(text) {
+            Some(elements) => crate::tuples::convert(value, &elements, |ty, v| coerce_to_declared(Some(ty), v)),
+            None => Ok(crate::structures::copy_value(value)),
+        },
+        Some(ty) => match crate::integral::IntType::from_vb_type(ty) {
+            Some(int_type) => crate::integral::coerce(&value, int_type),
+            None => Ok(crate::structures::copy_value(value)),
+        },
+        None => Ok(crate::structures::copy_value(value)),
+    }
+}
+
+/// Both kinds of `CType` operator, for an explicit conversion.
+const ALL_CONVERSIONS: &[ConversionKind] = &[ConversionKind::Widening, ConversionKind::Narrowing];
+
+/// The type `ty` names when `value` may need a user-defined conversion to be
+/// stored as one: a class or structure the value may not be, or a primitive
+/// type for an object.
+fn conversion_target(ty: Option<&vybe_parser::VBType>, value: &Value) -> Option<String> {
+    match (ty?, value) {
+        (_, Value::Nothing) | (vybe_parser::VBType::Object, _) => None,
+        (vybe_parser::VBType::Custom(name), Value::Object(obj)) if obj.borrow().class_name.eq_ignore_ascii_case(name) => None,
+        (ty @ vybe_parser::VBType::Custom(name), _) if !is_tuple_type(ty) => Some(name.clone()),
+        (vybe_parser::VBType::Custom(_), _) => None,
+        (ty, Value::Object(_)) => Some(ty.to_string()),
+        _ => None,
+    }
+}
+
+/// A tuple type such as `(Min As Integer, Max As Integer)`.
+fn is_tuple_type(ty: &vybe_parser::VBType) -> bool {
+    matches!(ty, vybe_parser::VBType::Custom(text) if crate::tuples::parse_type(text).is_some())
+}
+
 fn default_value_for_type(_name: &str, var_type: &Option<vybe_parser::VBType>) -> Value {
     match var_type {
+        Some(vybe_parser::VBType::SByte) => Value::SByte(0),
+        Some(vybe_parser::VBType::Byte) => Value::Byte(0),
+        Some(vybe_parser::VBType::Short) => Value::Short(0),
+        Some(vybe_parser::VBType::UShort) => Value::UShort(0),
         Some(vybe_parser::VBType::Integer) => Value::Integer(0),
+        Some(vybe_parser::VBType::UInteger) => Value::UInteger(0),
         Some(vybe_parser::VBType::Long) => Value::Long(0),
+        Some(vybe_parser::VBType::ULong) => Value::ULong(0),
         Some(vybe_parser::VBType::Single) => Value::Single(0.0),
         Some(vybe_parser::VBType::Double) => Value::Double(0.0),
+        Some(vybe_parser::VBType::Decimal) => Value::Decimal(crate::decimal::Decimal::ZERO),
         Some(vybe_parser::VBType::String) => Value::String(String::new()),
+        Some(vybe_parser::VBType::FixedString(len)) => Value::String(" ".repeat(*len)),
         Some(vybe_parser::VBType::Boolean) => Value::Boolean(false),
         Some(vybe_parser::VBType::Variant) => Value::Nothing,
+        Some(vybe_parser::VBType::Custom(text)) => match crate::tuples::parse_type(text) {
+            Some(elements) => crate::tuples::default_value(&elements, |ty| default_value_for_type("", &Some(ty.clone()))),
+            None => Value::Nothing,
+        },
         _ => Value::Nothing,
     }
 }
@@ -16592,13 +19431,13 @@ fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
 
 /// VB.NET Like operator pattern matching
 /// Supports: * (any chars), ? (single char), # (single digit), [charlist], [!charlist]
-fn vb_like_match(text: &str, pattern: &str) -> bool {
+fn vb_like_match(text: &str, pattern: &str, ignore_case: bool) -> bool {
     let text_chars: Vec<char> = text.chars().collect();
     let pattern_chars: Vec<char> = pattern.chars().collect();
-    vb_like_match_inner(&text_chars, &pattern_chars)
+    vb_like_match_inner(&text_chars, &pattern_chars, ignore_case)
 }
 
-fn vb_like_match_inner(text: &[char], pattern: &[char]) -> bool {
+fn vb_like_match_inner(text: &[char], pattern: &[char], ignore_case: bool) -> bool {
     if pattern.is_empty() {
         return text.is_empty();
     }
@@ -16606,7 +19445,7 @@ fn vb_like_match_inner(text: &[char], pattern: &[char]) -> bool {
         '*' => {
             // Try matching rest of pattern at every position
             for i in 0..=text.len() {
-                if vb_like_match_inner(&text[i..], &pattern[1..]) {
+                if vb_like_match_inner(&text[i..], &pattern[1..], ignore_case) {
                     return true;
                 }
             }
@@ -16614,11 +19453,11 @@ fn vb_like_match_inner(text: &[char], pattern: &[char]) -> bool {
         }
         '?' => {
             if text.is_empty() { return false; }
-            vb_like_match_inner(&text[1..], &pattern[1..])
+            vb_like_match_inner(&text[1..], &pattern[1..], ignore_case)
         }
         '#' => {
             if text.is_empty() || !text[0].is_ascii_digit() { return false; }
-            vb_like_match_inner(&text[1..], &pattern[1..])
+            vb_like_match_inner(&text[1..], &pattern[1..], ignore_case)
         }
         '[' => {
             if text.is_empty() { return false; }
@@ -16646,176 +19485,1133 @@ fn vb_like_match_inner(text: &[char], pattern: &[char]) -> bool {
                         i += 1;
                     }
                 }
-                if negate { matches = !matches; }
-                if matches {
-                    vb_like_match_inner(&text[1..], &pattern[close + 1..])
-                } else {
-                    false
+                if negate { matches = !matches; }
+                if matches {
+                    vb_like_match_inner(&text[1..], &pattern[close + 1..], ignore_case)
+                } else {
+                    false
+                }
+            } else {
+                // No closing bracket — treat [ as literal
+                if text.is_empty() || text[0] != '[' { return false; }
+                vb_like_match_inner(&text[1..], &pattern[1..], ignore_case)
+            }
+        }
+        c => {
+            if text.is_empty() { return false; }
+            if text[0] == c || (ignore_case && text[0].eq_ignore_ascii_case(&c)) {
+                vb_like_match_inner(&text[1..], &pattern[1..], ignore_case)
+            } else {
+                false
+            }
+        }
+    }
+}
+
+/// `Me.Child.Grid` as the expression it names.
+fn dotted_expression(path: &str) -> Expression {
+    let mut parts = path.split('.');
+    let first = parts.next().unwrap_or_default();
+    let root = if first.eq_ignore_ascii_case("me") {
+        ExprKind::Me.into()
+    } else {
+        ExprKind::Variable(Identifier::new(first)).into()
+    };
+    parts.fold(root, |expr, part| ExprKind::MemberAccess(Box::new(expr), Identifier::new(part)).into())
+}
+
+/// Check whether a method body contains a `MyBase.New(...)` call.
+/// Used to decide if automatic base-class constructor chaining is needed.
+fn body_contains_mybase_new(body: &[vybe_parser::ast::Statement]) -> bool {
+    for stmt in body {
+        if expr_in_stmt_matches(stmt, &|e| {
+            matches!(&e.kind, ExprKind::MethodCall(obj, method, _)
+                if matches!(&obj.kind, ExprKind::MyBase)
+                   && method.as_str().eq_ignore_ascii_case("new"))
+        }) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Recursively check if any expression inside a statement satisfies a predicate.
+fn expr_in_stmt_matches(stmt: &vybe_parser::ast::Statement, pred: &dyn Fn(&vybe_parser::ast::Expression) -> bool) -> bool {
+    use vybe_parser::ast::Statement;
+    match stmt {
+        Statement::ExpressionStatement(e) => expr_matches(e, pred),
+        Statement::Assignment { value, .. } => expr_matches(value, pred),
+        Statement::MemberAssignment { object, value, .. } => expr_matches(object, pred) || expr_matches(value, pred),
+        Statement::IndexedAssignment { object, indices, value, .. } => {
+            expr_matches(object, pred) || indices.iter().any(|i| expr_matches(i, pred)) || expr_matches(value, pred)
+        }
+        Statement::If { condition, then_branch, elseif_branches, else_branch, .. } => {
+            if expr_matches(condition, pred) { return true; }
+            for s in then_branch { if expr_in_stmt_matches(s, pred) { return true; } }
+            for (c, b) in elseif_branches {
+                if expr_matches(c, pred) { return true; }
+                for s in b { if expr_in_stmt_matches(s, pred) { return true; } }
+            }
+            if let Some(eb) = else_branch {
+                for s in eb { if expr_in_stmt_matches(s, pred) { return true; } }
+            }
+            false
+        }
+        _ => false, // For constructor chaining detection, other statement types are unlikely
+    }
+}
+
+fn expr_matches(expr: &vybe_parser::ast::Expression, pred: &dyn Fn(&vybe_parser::ast::Expression) -> bool) -> bool {
+    if pred(expr) { return true; }
+    match &expr.kind {
+        ExprKind::MethodCall(obj, _, args) => {
+            if expr_matches(obj, pred) { return true; }
+            args.iter().any(|a| expr_matches(a, pred))
+        }
+        ExprKind::MemberAccess(obj, _) => expr_matches(obj, pred),
+        ExprKind::Call(_, args) => args.iter().any(|a| expr_matches(a, pred)),
+        _ => false,
+    }
+}
+// ── Option Statements ──
+
+impl Interpreter {
+    /// Is `name` a variable, constant, field or property in scope?
+    fn is_declared(&self, name: &str) -> bool {
+        if self.env.is_defined(name) {
+            return true;
+        }
+        let Some(obj) = &self.current_object else { return false };
+        let obj = obj.borrow();
+        obj.fields.contains_key(&*Symbol::intern(name).name()) || self.find_property(&obj.class_name, name).is_some()
+    }
+
+    /// `Option Explicit` and `Option Strict` rules for `name = expr`.
+    fn check_assignment(&self, name: &str, expr: &Expression, value: &Value) -> Result<(), RuntimeError> {
+        if options::explicit(&self.active_options) && !self.is_declared(name) {
+            return Err(RuntimeError::UndefinedVariable(name.to_string()));
+        }
+        if !options::strict(&self.active_options) {
+            return Ok(());
+        }
+        match self.env.declared_type(name) {
+            Some(ty) => self.check_strict_conversion(ty, expr, value),
+            None => Ok(()),
+        }
+    }
+
+    /// `=` under the active `Option Compare`: Binary matches strings exactly,
+    /// Text ignores case.
+    fn equal_values(&self, l: &Value, r: &Value) -> bool {
+        match (options::ignore_case(&self.active_options), l, r) {
+            (Some(false), Value::String(a), Value::String(b)) => a == b,
+            (Some(true), Value::String(a), Value::String(b)) => a.to_lowercase() == b.to_lowercase(),
+            _ => values_equal(l, r),
+        }
+    }
+
+    /// Under `Option Compare Text` strings are compared by their lowercase form.
+    fn fold_case(&self, value: Value) -> Value {
+        match value {
+            Value::String(s) if options::ignore_case(&self.active_options) == Some(true) => Value::String(s.to_lowercase()),
+            other => other,
+        }
+    }
+
+    fn fold_case_all(&self, values: Vec<Value>) -> Vec<Value> {
+        values.into_iter().map(|v| self.fold_case(v)).collect()
+    }
+
+    /// A `For` variable without `As` must already exist under Option Explicit,
+    /// unless Option Infer declares it.
+    fn check_loop_variable(&self, variable: &Identifier, var_type: &Option<vybe_parser::VBType>) -> Result<(), RuntimeError> {
+        let opts = &self.active_options;
+        if var_type.is_none() && options::explicit(opts) && !options::infer(opts) && !self.is_declared(variable.as_str()) {
+            return Err(RuntimeError::UndefinedVariable(variable.as_str().to_string()));
+        }
+        Ok(())
+    }
+
+    /// Option Strict On rejects storing `value`, computed by `expr`, in a
+    /// variable declared `As ty` when that narrows.
+    fn check_strict_conversion(&self, ty: &vybe_parser::VBType, expr: &Expression, value: &Value) -> Result<(), RuntimeError> {
+        // Dim doesn't convert its initializer (`Dim d As Double = 1` holds an
+        // Integer), so a typed variable is judged by its declaration.
+        let declared = match &expr.kind {
+            ExprKind::Variable(name) => self.env.declared_type(name.as_str()),
+            _ => None,
+        };
+        match declared {
+            Some(from) if options::is_narrowing_type(from, ty) => Err(options::narrowing_error(&from.to_string(), ty)),
+            Some(_) => Ok(()),
+            None if options::is_narrowing(ty, value, options::is_constant(expr)) => {
+                Err(options::narrowing_error(options::value_type_name(value), ty))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Option Strict On rejects `Return expr` when the value narrows to the
+    /// executing Function's return type.
+    fn check_return(&self, expr: &Expression, value: &Value) -> Result<(), RuntimeError> {
+        match &self.return_type {
+            Some(ty) if options::strict(&self.active_options) => self.check_strict_conversion(ty, expr, value),
+            _ => Ok(()),
+        }
+    }
+
+    /// Option Strict On rejects member access through a variable declared `As Object`.
+    fn check_late_binding(&self, object: &Expression, member: &str) -> Result<(), RuntimeError> {
+        if !options::strict(&self.active_options) || options::is_object_member(member) {
+            return Ok(());
+        }
+        let ExprKind::Variable(name) = &object.kind else { return Ok(()) };
+        match self.env.declared_type(name.as_str()) {
+            Some(vybe_parser::VBType::Object) | Some(vybe_parser::VBType::Variant) => Err(options::late_binding_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// ── Iterator Functions ──
+
+impl Interpreter {
+    /// The `IEnumerable` returned by calling an Iterator Function. The body and
+    /// the bound arguments travel in a parameterless lambda; each enumeration
+    /// starts the body over with a copy of the arguments.
+    fn iterator_object(&self, func: &FunctionDecl, args: Scope, me: Option<Rc<RefCell<ObjectData>>>) -> Value {
+        let mut env = Environment::new();
+        env.push_scope_with(args);
+        let body = Value::Lambda {
+            params: Vec::new(),
+            body: Box::new(vybe_parser::ast::expr::LambdaBody::Block(func.body.clone())),
+            env: Rc::new(RefCell::new(env)),
+        };
+        let me = me.map(Value::Object).unwrap_or(Value::Nothing);
+        iterators::enumerable(
+            "iterator",
+            vec![
+                ("__body", body),
+                ("__me", me),
+                ("__procedure", Value::String(func.name.as_str().to_string())),
+            ],
+        )
+    }
+
+    /// Starts enumerating `source`: a lazy sequence, or any value `For Each` accepts.
+    fn open_cursor(&mut self, source: &Value) -> Result<Cursor, RuntimeError> {
+        if !iterators::is_enumerable(source) {
+            let items = match self.default_property_items(source)? {
+                Some(items) => items,
+                None => source.to_iterable()?,
+            };
+            return Ok(Cursor::Items { items, index: 0 });
+        }
+        let kind = iterators::field(source, iterators::KIND_FIELD).as_string();
+        let count = || -> Result<usize, RuntimeError> {
+            Ok(iterators::field(source, "__count").as_integer()?.max(0) as usize)
+        };
+        let cursor = match kind.as_str() {
+            "iterator" => {
+                let (locals, body) = match iterators::field(source, "__body") {
+                    Value::Lambda { body, env, .. } => match *body {
+                        vybe_parser::ast::expr::LambdaBody::Block(block) => (Scope::from_variables(env.borrow().all_variables()), block),
+                        _ => (Scope::default(), Block::new()),
+                    },
+                    _ => (Scope::default(), Block::new()),
+                };
+                let me = match iterators::field(source, "__me") {
+                    Value::Object(obj) => Some(obj),
+                    _ => None,
+                };
+                Cursor::Body(Box::new(BodyCursor {
+                    locals,
+                    frames: vec![Frame::Block { block: Rc::new(body), pc: 0 }],
+                    me,
+                    with_object: None,
+                    procedure: iterators::field(source, "__procedure").as_string(),
+                    finished: false,
+                }))
+            }
+            _ => {
+                let inner = Box::new(self.open_cursor(&iterators::field(source, "__source"))?);
+                let func = iterators::field(source, "__fn");
+                match kind.as_str() {
+                    "select" => Cursor::Select { source: inner, selector: func },
+                    "where" => Cursor::Where { source: inner, predicate: func },
+                    "take" => Cursor::Take { source: inner, remaining: count()? },
+                    "skip" => Cursor::Skip { source: inner, remaining: count()? },
+                    "takewhile" => Cursor::TakeWhile { source: inner, predicate: func, done: false },
+                    "skipwhile" => Cursor::SkipWhile { source: inner, predicate: func, skipping: true },
+                    other => return Err(RuntimeError::Custom(format!("Unknown sequence operator '{}'", other))),
+                }
+            }
+        };
+        Ok(cursor)
+    }
+
+    /// The next value of an enumeration, None once it's exhausted.
+    fn cursor_next(&mut self, cursor: &mut Cursor) -> Result<Option<Value>, RuntimeError> {
+        match cursor {
+            Cursor::Items { items, index } => {
+                let item = items.get(*index).cloned();
+                *index += 1;
+                Ok(item)
+            }
+            Cursor::Body(body) => self.resume_iterator(body),
+            Cursor::Select { source, selector } => match self.cursor_next(source)? {
+                Some(item) => self.call_lambda(selector.clone(), &[item]).map(Some),
+                None => Ok(None),
+            },
+            Cursor::Where { source, predicate } => {
+                while let Some(item) = self.cursor_next(source)? {
+                    if self.call_lambda(predicate.clone(), std::slice::from_ref(&item))?.is_truthy() {
+                        return Ok(Some(item));
+                    }
+                }
+                Ok(None)
+            }
+            Cursor::Take { source, remaining } => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+                *remaining -= 1;
+                self.cursor_next(source)
+            }
+            Cursor::Skip { source, remaining } => {
+                while *remaining > 0 {
+                    *remaining -= 1;
+                    if self.cursor_next(source)?.is_none() {
+                        return Ok(None);
+                    }
+                }
+                self.cursor_next(source)
+            }
+            Cursor::TakeWhile { source, predicate, done } => {
+                if *done {
+                    return Ok(None);
+                }
+                let item = self.cursor_next(source)?;
+                let keep = match &item {
+                    Some(item) => self.call_lambda(predicate.clone(), std::slice::from_ref(item))?.is_truthy(),
+                    None => false,
+                };
+                if keep {
+                    return Ok(item);
+                }
+                *done = true;
+                Ok(None)
+            }
+            Cursor::SkipWhile { source, predicate, skipping } => {
+                while let Some(item) = self.cursor_next(source)? {
+                    if *skipping && self.call_lambda(predicate.clone(), std::slice::from_ref(&item))?.is_truthy() {
+                        continue;
+                    }
+                    *skipping = false;
+                    return Ok(Some(item));
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Stops an enumeration before its end, like disposing the enumerator:
+    /// an iterator suspended inside `Try` blocks runs their `Finally` blocks.
+    fn close_cursor(&mut self, cursor: Cursor) -> Result<(), RuntimeError> {
+        match cursor {
+            Cursor::Items { .. } => Ok(()),
+            Cursor::Body(mut body) => {
+                if body.finished {
+                    return Ok(());
+                }
+                body.finished = true;
+                let frames = std::mem::take(&mut body.frames);
+                self.env.push_scope_with(std::mem::take(&mut body.locals));
+                let prev_object = std::mem::replace(&mut self.current_object, body.me.clone());
+                let prev_with = std::mem::replace(&mut self.with_object, body.with_object.take());
+                let prev_procedure = self.current_procedure.replace(body.procedure.clone());
+                let mut result = Ok(());
+                for frame in frames.into_iter().rev() {
+                    let closed = match frame {
+                        Frame::Try { finally: Some(finally), .. } => self.execute_block(&finally),
+                        Frame::ForEach { items, .. } => self.close_cursor(*items),
+                        Frame::With { previous } => {
+                            self.with_object = previous;
+                            Ok(())
+                        }
+                        Frame::Using { variable, resource } => {
+                            self.end_using(&variable, &resource);
+                            Ok(())
+                        }
+                        _ => Ok(()),
+                    };
+                    if result.is_ok() {
+                        result = closed;
+                    }
+                }
+                self.env.pop_scope();
+                self.current_object = prev_object;
+                self.with_object = prev_with;
+                self.current_procedure = prev_procedure;
+                result
+            }
+            Cursor::Select { source, .. }
+            | Cursor::Where { source, .. }
+            | Cursor::Take { source, .. }
+            | Cursor::Skip { source, .. }
+            | Cursor::TakeWhile { source, .. }
+            | Cursor::SkipWhile { source, .. } => self.close_cursor(*source),
+        }
+    }
+
+    /// Enumerates `source` to the end.
+    fn sequence_items(&mut self, source: &Value) -> Result<Vec<Value>, RuntimeError> {
+        if !iterators::is_enumerable(source) {
+            return match self.default_property_items(source)? {
+                Some(items) => Ok(items),
+                None => source.to_iterable(),
+            };
+        }
+        let mut cursor = self.open_cursor(source)?;
+        let mut items = Vec::new();
+        while let Some(item) = self.cursor_next(&mut cursor)? {
+            self.budget.check_len(items.len() + 1)?;
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    /// Runs an Iterator Function's body from where it last stopped up to its
+    /// next `Yield`. None when the body finishes.
+    fn resume_iterator(&mut self, cursor: &mut BodyCursor) -> Result<Option<Value>, RuntimeError> {
+        if cursor.finished {
+            return Ok(None);
+        }
+        self.env.push_scope_with(std::mem::take(&mut cursor.locals));
+        let prev_object = std::mem::replace(&mut self.current_object, cursor.me.clone());
+        let prev_with = std::mem::replace(&mut self.with_object, cursor.with_object.take());
+        let prev_procedure = self.current_procedure.replace(cursor.procedure.clone());
+        let result = self.run_frames(&mut cursor.frames);
+        cursor.locals = self.env.take_scope();
+        cursor.with_object = std::mem::replace(&mut self.with_object, prev_with);
+        self.current_object = prev_object;
+        self.current_procedure = prev_procedure;
+        match result {
+            Ok(Some(value)) => Ok(Some(value)),
+            Ok(None) | Err(RuntimeError::Exit(ExitType::Function)) | Err(RuntimeError::Return(_)) => {
+                cursor.finished = true;
+                cursor.frames.clear();
+                Ok(None)
+            }
+            Err(e) => {
+                cursor.finished = true;
+                cursor.frames.clear();
+                Err(e)
+            }
+        }
+    }
+
+    fn run_frames(&mut self, frames: &mut Vec<Frame>) -> Result<Option<Value>, RuntimeError> {
+        loop {
+            let step = match frames.last_mut() {
+                Some(frame) => self.step_frame(frame),
+                None => return Ok(None),
+            };
+            match step {
+                Ok(Step::Yield(value)) => return Ok(Some(value)),
+                Ok(Step::Enter(entered)) => frames.extend(entered),
+                Ok(Step::Leave) => {
+                    let left = match frames.pop() {
+                        Some(Frame::Try { finally: Some(finally), .. }) => self.execute_block(&finally),
+                        Some(frame) => {
+                            self.leave_frame(frame);
+                            Ok(())
+                        }
+                        None => Ok(()),
+                    };
+                    if let Err(e) = left {
+                        self.unwind_frames(frames, e)?;
+                    }
+                }
+                Ok(Step::Next) => {}
+                Err(e) => self.unwind_frames(frames, e)?,
+            }
+        }
+    }
+
+    fn step_frame(&mut self, frame: &mut Frame) -> Result<Step, RuntimeError> {
+        if matches!(frame, Frame::For { .. } | Frame::ForEach { .. } | Frame::While { .. } | Frame::Do { .. }) {
+            self.budget.step()?;
+        }
+        match frame {
+            Frame::Block { block, pc } => {
+                if *pc >= block.len() {
+                    return Ok(Step::Leave);
+                }
+                let block = block.clone();
+                let index = *pc;
+                *pc += 1;
+                let span = block.span_at(index);
+                match &block[index] {
+                    Statement::Yield(expr) => {
+                        self.evaluate_expr(expr).map(Step::Yield).map_err(|e| self.locate_error(e, span))
+                    }
+                    stmt if iterators::contains_yield(stmt) => {
+                        self.enter_statement(stmt).map(Step::Enter).map_err(|e| self.locate_error(e, span))
+                    }
+                    stmt => self.execute_at(stmt, span).map(|_| Step::Next),
+                }
+            }
+            Frame::For { variable, var_type, next, end, step, body } => {
+                if (*step > 0 && *next > *end) || (*step < 0 && *next < *end) {
+                    return Ok(Step::Leave);
                 }
-            } else {
-                // No closing bracket — treat [ as literal
-                if text.is_empty() || text[0] != '[' { return false; }
-                vb_like_match_inner(&text[1..], &pattern[1..])
+                self.env.set(variable.as_str(), coerce_to_declared(var_type.as_ref(), Value::Integer(*next))?)?;
+                *next += *step;
+                Ok(Step::Enter(vec![Frame::Block { block: body.clone(), pc: 0 }]))
             }
-        }
-        c => {
-            if text.is_empty() { return false; }
-            if text[0].to_ascii_lowercase() == c.to_ascii_lowercase() {
-                vb_like_match_inner(&text[1..], &pattern[1..])
-            } else {
-                false
+            Frame::ForEach { variable, items, body } => match self.cursor_next(items)? {
+                Some(item) => {
+                    self.env.set(variable.as_str(), crate::structures::copy_value(item))?;
+                    Ok(Step::Enter(vec![Frame::Block { block: body.clone(), pc: 0 }]))
+                }
+                None => Ok(Step::Leave),
+            },
+            Frame::While { condition, body } => {
+                let cond_val = self.evaluate_expr(condition)?;
+                if !self.condition_is_true(&cond_val)? {
+                    return Ok(Step::Leave);
+                }
+                Ok(Step::Enter(vec![Frame::Block { block: body.clone(), pc: 0 }]))
+            }
+            Frame::Do { pre_condition, post_condition, body, entered } => {
+                let conditions = [post_condition.as_ref().filter(|_| *entered), pre_condition.as_ref()];
+                for (cond_type, expr) in conditions.into_iter().flatten() {
+                    let val = self.evaluate_expr(expr)?;
+                    let holds = self.condition_is_true(&val)?;
+                    let stop = match cond_type {
+                        LoopConditionType::While => !holds,
+                        LoopConditionType::Until => holds,
+                    };
+                    if stop {
+                        return Ok(Step::Leave);
+                    }
+                }
+                *entered = true;
+                Ok(Step::Enter(vec![Frame::Block { block: body.clone(), pc: 0 }]))
             }
+            Frame::Select | Frame::Try { .. } | Frame::With { .. } | Frame::Using { .. } => Ok(Step::Leave),
         }
     }
-}
 
-/// Check whether a method body contains a `MyBase.New(...)` call.
-/// Used to decide if automatic base-class constructor chaining is needed.
-fn body_contains_mybase_new(body: &[vybe_parser::ast::Statement]) -> bool {
-    use vybe_parser::ast::Expression;
-    for stmt in body {
-        if expr_in_stmt_matches(stmt, &|e| {
-            matches!(e, Expression::MethodCall(obj, method, _)
-                if matches!(obj.as_ref(), Expression::MyBase)
-                   && method.as_str().eq_ignore_ascii_case("new"))
-        }) {
-            return true;
+    /// Leave a `Using` block: dispose of `resource` if it has a Dispose
+    /// method (best effort), then clear its variable.
+    fn end_using(&mut self, variable: &str, resource: &Value) {
+        if let Value::Object(obj_ref) = resource {
+            let class_name = obj_ref.borrow().class_name.clone();
+            if let Some(vybe_parser::ast::decl::MethodDecl::Sub(s)) = self.find_method(&class_name, "Dispose") {
+                let _ = self.call_user_sub(&s, &[], Some(obj_ref.clone()));
+            }
         }
+        let _ = self.env.set(variable, Value::Nothing);
     }
-    false
-}
 
-/// Recursively check if any expression inside a statement satisfies a predicate.
-fn expr_in_stmt_matches(stmt: &vybe_parser::ast::Statement, pred: &dyn Fn(&vybe_parser::ast::Expression) -> bool) -> bool {
-    use vybe_parser::ast::Statement;
-    match stmt {
-        Statement::ExpressionStatement(e) => expr_matches(e, pred),
-        Statement::Assignment { value, .. } => expr_matches(value, pred),
-        Statement::MemberAssignment { object, value, .. } => expr_matches(object, pred) || expr_matches(value, pred),
-        Statement::If { condition, then_branch, elseif_branches, else_branch, .. } => {
-            if expr_matches(condition, pred) { return true; }
-            for s in then_branch { if expr_in_stmt_matches(s, pred) { return true; } }
-            for (c, b) in elseif_branches {
-                if expr_matches(c, pred) { return true; }
-                for s in b { if expr_in_stmt_matches(s, pred) { return true; } }
+    /// Frames for a statement with a `Yield` somewhere inside it.
+    fn enter_statement(&mut self, stmt: &Statement) -> Result<Vec<Frame>, RuntimeError> {
+        let block_frame = |block: &Block| Frame::Block { block: Rc::new(block.clone()), pc: 0 };
+        match stmt {
+            Statement::If { condition, then_branch, elseif_branches, else_branch } => {
+                let branch = self.if_branch(condition, then_branch, elseif_branches, else_branch)?;
+                Ok(branch.map(block_frame).into_iter().collect())
             }
-            if let Some(eb) = else_branch {
-                for s in eb { if expr_in_stmt_matches(s, pred) { return true; } }
+            Statement::Select { test_expr, cases, else_block } => {
+                match self.select_case_body(test_expr, cases, else_block)? {
+                    Some(body) => Ok(vec![Frame::Select, block_frame(body)]),
+                    None => Ok(Vec::new()),
+                }
             }
-            false
+            Statement::For { variable, var_type, start, end, step, body } => {
+                self.check_loop_variable(variable, var_type)?;
+                let start_val = self.evaluate_expr(start)?.as_integer()?;
+                let end_val = self.evaluate_expr(end)?.as_integer()?;
+                let step_val = match step {
+                    Some(s) => self.evaluate_expr(s)?.as_integer()?,
+                    None => 1,
+                };
+                Ok(vec![Frame::For {
+                    variable: variable.as_str().to_string(),
+                    var_type: var_type.clone(),
+                    next: start_val,
+                    end: end_val,
+                    step: step_val,
+                    body: Rc::new(body.clone()),
+                }])
+            }
+            Statement::ForEach { variable, var_type, collection, body } => {
+                self.check_loop_variable(variable, var_type)?;
+                let coll_val = self.evaluate_expr(collection)?;
+                let items = Box::new(self.open_cursor(&coll_val)?);
+                self.env.define(variable.as_str(), Value::Nothing);
+                Ok(vec![Frame::ForEach { variable: variable.as_str().to_string(), items, body: Rc::new(body.clone()) }])
+            }
+            Statement::While { condition, body } => {
+                Ok(vec![Frame::While { condition: condition.clone(), body: Rc::new(body.clone()) }])
+            }
+            Statement::DoLoop { pre_condition, body, post_condition } => Ok(vec![Frame::Do {
+                pre_condition: pre_condition.clone(),
+                post_condition: post_condition.clone(),
+                body: Rc::new(body.clone()),
+                entered: false,
+            }]),
+            Statement::Try { body, catches, finally } => Ok(vec![
+                Frame::Try { catches: catches.clone(), finally: finally.clone() },
+                block_frame(body),
+            ]),
+            Statement::With { object, body } => {
+                let obj_val = self.evaluate_expr(object)?;
+                let previous = self.with_object.replace(obj_val);
+                Ok(vec![Frame::With { previous }, block_frame(body)])
+            }
+            Statement::Using { variable, resource, body } => {
+                let resource = self.evaluate_expr(resource)?;
+                self.env.define(variable.as_str(), resource.clone());
+                Ok(vec![Frame::Using { variable: variable.as_str().to_string(), resource }, block_frame(body)])
+            }
+            Statement::SyncLock { lock_object, body } => {
+                self.evaluate_expr(lock_object)?;
+                Ok(vec![block_frame(body)])
+            }
+            _ => Ok(Vec::new()),
         }
-        _ => false, // For constructor chaining detection, other statement types are unlikely
     }
-}
 
-fn expr_matches(expr: &vybe_parser::ast::Expression, pred: &dyn Fn(&vybe_parser::ast::Expression) -> bool) -> bool {
-    use vybe_parser::ast::Expression;
-    if pred(expr) { return true; }
-    match expr {
-        Expression::MethodCall(obj, _, args) => {
-            if expr_matches(obj, pred) { return true; }
-            args.iter().any(|a| expr_matches(a, pred))
+    /// Undo what entering `frame` set up, when it's left without running a
+    /// `Finally` block.
+    fn leave_frame(&mut self, frame: Frame) {
+        match frame {
+            Frame::With { previous } => self.with_object = previous,
+            Frame::Using { variable, resource } => self.end_using(&variable, &resource),
+            _ => {}
         }
-        Expression::MemberAccess(obj, _) => expr_matches(obj, pred),
-        Expression::Call(_, args) => args.iter().any(|a| expr_matches(a, pred)),
-        _ => false,
+    }
+
+    /// Pops frames until one handles `error`: the loop an `Exit` or `Continue`
+    /// refers to, or a `Try` with a matching `Catch`. `Finally` blocks of the
+    /// `Try` frames passed on the way run as they're popped. Err if nothing in
+    /// the iterator handles it.
+    fn unwind_frames(&mut self, frames: &mut Vec<Frame>, mut error: RuntimeError) -> Result<(), RuntimeError> {
+        use vybe_parser::ast::stmt::ContinueType;
+        while let Some(frame) = frames.pop() {
+            match (&frame, &error) {
+                (Frame::For { .. } | Frame::ForEach { .. }, RuntimeError::Exit(ExitType::For))
+                | (Frame::While { .. }, RuntimeError::Exit(ExitType::While | ExitType::Do))
+                | (Frame::Do { .. }, RuntimeError::Exit(ExitType::Do))
+                | (Frame::Select, RuntimeError::Exit(ExitType::Select)) => {
+                    if let Frame::ForEach { items, .. } = frame {
+                        self.close_cursor(*items)?;
+                    }
+                    return Ok(());
+                }
+                (Frame::For { .. } | Frame::ForEach { .. }, RuntimeError::Continue(ContinueType::For))
+                | (Frame::While { .. }, RuntimeError::Continue(ContinueType::While))
+                | (Frame::Do { .. }, RuntimeError::Continue(ContinueType::Do)) => {
+                    frames.push(frame);
+                    return Ok(());
+                }
+                (Frame::Try { catches, finally }, _) => {
+                    let outcome = match error {
+                        RuntimeError::Exit(ExitType::Try) => Ok(()),
+                        e => self.run_catch(&e, catches).unwrap_or(Err(e)),
+                    };
+                    let outcome = match finally {
+                        Some(finally) => self.execute_block(finally).and(outcome),
+                        None => outcome,
+                    };
+                    match outcome {
+                        Ok(()) => return Ok(()),
+                        Err(e) => error = e,
+                    }
+                }
+                (Frame::ForEach { .. }, _) => {
+                    if let Frame::ForEach { items, .. } = frame {
+                        self.close_cursor(*items)?;
+                    }
+                }
+                _ => self.leave_frame(frame),
+            }
+        }
+        Err(error)
+    }
+
+    /// LINQ operators a lazy sequence handles without enumerating all of it.
+    /// `Where`, `Select`, `Take`, `Skip`, `TakeWhile` and `SkipWhile` return
+    /// another lazy sequence; `First`, `FirstOrDefault`, `ElementAt`, `Any` and
+    /// `Contains` stop pulling values once they have their answer. None for
+    /// anything else.
+    fn enumerable_method(&mut self, seq: &Value, method: &str, args: &[Expression]) -> Result<Option<Value>, RuntimeError> {
+        let lazy = matches!(method, "where" | "select" | "take" | "skip" | "takewhile" | "skipwhile");
+        let pulling = matches!(method, "first" | "firstordefault" | "elementat" | "elementatordefault" | "any" | "contains");
+        if !lazy && !pulling {
+            return Ok(None);
+        }
+        let arg_values: Vec<Value> = args.iter().map(|a| self.evaluate_expr(a)).collect::<Result<_, _>>()?;
+        let arg = arg_values.first().cloned();
+        if lazy {
+            let field = if matches!(method, "take" | "skip") { "__count" } else { "__fn" };
+            return Ok(Some(iterators::enumerable(
+                method,
+                vec![("__source", seq.clone()), (field, arg.unwrap_or(Value::Nothing))],
+            )));
+        }
+
+        let mut cursor = self.open_cursor(seq)?;
+        let mut index = 0;
+        let found = loop {
+            let Some(item) = self.cursor_next(&mut cursor)? else {
+                break None;
+            };
+            let matched = match (method, &arg) {
+                ("elementat" | "elementatordefault", Some(position)) => {
+                    index += 1;
+                    position.as_integer()? == index - 1
+                }
+                ("contains", Some(target)) => values_equal(&item, target),
+                (_, Some(predicate)) => self.call_lambda(predicate.clone(), std::slice::from_ref(&item))?.is_truthy(),
+                (_, None) => true,
+            };
+            if matched {
+                break Some(item);
+            }
+        };
+        self.close_cursor(cursor)?;
+
+        let result = match method {
+            "any" | "contains" => Value::Boolean(found.is_some()),
+            "first" => found.ok_or_else(|| RuntimeError::Custom("Sequence contains no elements".to_string()))?,
+            "elementat" => found.ok_or_else(|| {
+                RuntimeError::Custom(format!("Index {} out of range", arg.map(|a| a.as_string()).unwrap_or_default()))
+            })?,
+            _ => found.unwrap_or(Value::Nothing),
+        };
+        Ok(Some(result))
     }
 }
+
 // ── Runtime Extensions ──
 
 impl Interpreter {
     fn execute_query(&mut self, query: &vybe_parser::ast::query::QueryExpression) -> Result<Value, RuntimeError> {
-        use vybe_parser::ast::query::*;
-        
-        let range = query.from_clause.ranges.first().ok_or(RuntimeError::Custom("Query must have range variable".to_string()))?;
-        let collection_val = self.evaluate_expr(&range.collection)?;
-        
-        // Ensure we're working with a collection
-        let items: Vec<Value> = match collection_val {
-            Value::Array(a) => a.clone(),
-            Value::Collection(c) => c.borrow().items.clone(),
-            _ => return Err(RuntimeError::Custom("Query source must be array or collection".to_string()))
-        };
-        
         self.env.push_scope();
-        
-        let mut filtered_items = Vec::new();
-        for item in items {
-            self.env.define(&range.name, item.clone());
-            let mut include = true;
-            
-            for clause in &query.body.clauses {
-                match clause {
-                    QueryClause::Where(expr) => {
-                         if !self.evaluate_expr(expr)?.as_bool().unwrap_or(false) {
-                             include = false;
-                             break;
-                         }
+        let result = self.run_query(query);
+        self.env.pop_scope();
+        result
+    }
+
+    fn run_query(&mut self, query: &vybe_parser::ast::query::QueryExpression) -> Result<Value, RuntimeError> {
+        use vybe_parser::ast::query::QueryClause;
+
+        let (mut rows, clauses) = self.query_stream(query)?;
+        for clause in clauses {
+            if let QueryClause::Into(into) = clause {
+                let elements = query_elements(rows);
+                let aggregates = self.query_into(&elements, into)?;
+                return Ok(query::row_value(aggregates));
+            }
+            rows = self.apply_query_clause(rows, clause)?;
+        }
+        Ok(Value::Array(rows.into_iter().map(query::row_value).collect()))
+    }
+
+    /// Define a row's range variables in the query scope.
+    fn bind_query_row(&mut self, row: &query::Row) {
+        for (name, value) in row {
+            if !name.is_empty() {
+                self.env.define(name, value.clone());
+            }
+        }
+    }
+
+    /// The rows of a query's first `From`. A single range is streamed through
+    /// the `Where`, `Let`, `Select`, `Skip` and `Take` clauses after it (see
+    /// the `query` module docs); returns the clauses still to apply.
+    fn query_stream<'q>(
+        &mut self,
+        query: &'q vybe_parser::ast::query::QueryExpression,
+    ) -> Result<(Vec<query::Row>, &'q [vybe_parser::ast::query::QueryClause]), RuntimeError> {
+        use vybe_parser::ast::query::QueryClause;
+
+        let clauses = query.body.clauses.as_slice();
+        let [range] = query.from_clause.ranges.as_slice() else {
+            return Ok((self.query_cross_join(vec![Vec::new()], &query.from_clause.ranges)?, clauses));
+        };
+        let mut cursor = self.query_cursor(&range.collection)?;
+        let mut stages = Vec::new();
+        for clause in clauses {
+            stages.push(match clause {
+                QueryClause::Where(_) | QueryClause::Let { .. } | QueryClause::Select(_) => query::Stage::Row,
+                QueryClause::Skip(count) => query::Stage::Skip(self.evaluate_expr(count)?.as_integer()?.max(0) as usize),
+                QueryClause::Take(count) => query::Stage::Take(self.evaluate_expr(count)?.as_integer()?.max(0) as usize),
+                QueryClause::SkipWhile(_) => query::Stage::SkipWhile(true),
+                QueryClause::TakeWhile(_) => query::Stage::TakeWhile,
+                _ => break,
+            });
+        }
+
+        let mut rows = Vec::new();
+        'rows: while !stages.iter().any(|stage| matches!(stage, query::Stage::Take(0))) {
+            let Some(item) = self.cursor_next(&mut cursor)? else {
+                break;
+            };
+            let mut row = vec![(range.name.clone(), item)];
+            for (clause, stage) in clauses.iter().zip(&mut stages) {
+                match (clause, stage) {
+                    (_, query::Stage::Skip(remaining)) if *remaining > 0 => {
+                        *remaining -= 1;
+                        continue 'rows;
+                    }
+                    (_, query::Stage::Take(remaining)) => *remaining -= 1,
+                    (QueryClause::SkipWhile(condition), query::Stage::SkipWhile(skipping)) if *skipping => {
+                        self.bind_query_row(&row);
+                        *skipping = self.evaluate_expr(condition)?.is_truthy();
+                        if *skipping {
+                            continue 'rows;
+                        }
+                    }
+                    (QueryClause::TakeWhile(condition), query::Stage::TakeWhile) => {
+                        self.bind_query_row(&row);
+                        if !self.evaluate_expr(condition)?.is_truthy() {
+                            break 'rows;
+                        }
+                    }
+                    (_, query::Stage::Row) => match self.apply_query_clause(vec![row], clause)?.pop() {
+                        Some(next) => row = next,
+                        None => continue 'rows,
+                    },
+                    _ => {}
+                }
+            }
+            self.budget.check_len(rows.len() + 1)?;
+            rows.push(row);
+        }
+        self.close_cursor(cursor)?;
+        Ok((rows, &clauses[stages.len()..]))
+    }
+
+    /// Opens the collection of a query range. Only a value that can't be
+    /// enumerated at all is reported as not being a collection.
+    fn query_cursor(&mut self, collection: &Expression) -> Result<Cursor, RuntimeError> {
+        let source = self.evaluate_expr(collection)?;
+        if iterators::is_enumerable(&source) {
+            return self.open_cursor(&source);
+        }
+        let items = match self.default_property_items(&source)? {
+            Some(items) => items,
+            None => source.to_iterable().map_err(|_| {
+                RuntimeError::Custom(format!("Query source '{}' is not a collection", self.expr_to_string(collection)))
+            })?,
+        };
+        Ok(Cursor::Items { items, index: 0 })
+    }
+
+    fn query_source(&mut self, collection: &Expression) -> Result<Vec<Value>, RuntimeError> {
+        let mut cursor = self.query_cursor(collection)?;
+        let mut items = Vec::new();
+        while let Some(item) = self.cursor_next(&mut cursor)? {
+            self.budget.check_len(items.len() + 1)?;
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    /// Every row extended with every element of each range; later ranges
+    /// may use the earlier ones (`From o In orders, i In o.Items`).
+    fn query_cross_join(&mut self, mut rows: Vec<query::Row>, ranges: &[vybe_parser::ast::query::RangeVariable]) -> Result<Vec<query::Row>, RuntimeError> {
+        for range in ranges {
+            let mut joined = Vec::new();
+            for row in rows {
+                self.bind_query_row(&row);
+                for item in self.query_source(&range.collection)? {
+                    self.budget.check_len(joined.len() + 1)?;
+                    let mut next = row.clone();
+                    next.push((range.name.clone(), item));
+                    joined.push(next);
+                }
+            }
+            rows = joined;
+        }
+        Ok(rows)
+    }
+
+    fn apply_query_clause(&mut self, rows: Vec<query::Row>, clause: &vybe_parser::ast::query::QueryClause) -> Result<Vec<query::Row>, RuntimeError> {
+        use vybe_parser::ast::query::*;
+
+        match clause {
+            QueryClause::From(ranges) => self.query_cross_join(rows, ranges),
+            QueryClause::Where(condition) => {
+                let mut kept = Vec::new();
+                for row in rows {
+                    self.bind_query_row(&row);
+                    if self.evaluate_expr(condition)?.is_truthy() {
+                        kept.push(row);
                     }
-                    QueryClause::Let { name, value } => {
-                        let v = self.evaluate_expr(value)?;
-                        self.env.define(name, v);
+                }
+                Ok(kept)
+            }
+            QueryClause::Let { name, value } => {
+                let mut result = Vec::with_capacity(rows.len());
+                for mut row in rows {
+                    self.bind_query_row(&row);
+                    let v = self.evaluate_expr(value)?;
+                    query::set_binding(&mut row, name, v);
+                    result.push(row);
+                }
+                Ok(result)
+            }
+            QueryClause::OrderBy(orderings) => {
+                let mut keyed = Vec::with_capacity(rows.len());
+                for row in rows {
+                    self.bind_query_row(&row);
+                    let keys = orderings.iter().map(|o| self.evaluate_expr(&o.expression)).collect::<Result<Vec<_>, _>>()?;
+                    keyed.push((keys, row));
+                }
+                keyed.sort_by(|(a, _), (b, _)| {
+                    orderings.iter().zip(a.iter().zip(b)).map(|(o, (x, y))| {
+                        let ord = compare_values_ordering(x, y);
+                        if o.direction == OrderDirection::Descending { ord.reverse() } else { ord }
+                    }).find(|ord| ord.is_ne()).unwrap_or(std::cmp::Ordering::Equal)
+                });
+                Ok(keyed.into_iter().map(|(_, row)| row).collect())
+            }
+            QueryClause::Select(fields) => {
+                if fields.len() > 1 && fields.iter().any(|f| f.name.is_none()) {
+                    return Err(RuntimeError::Custom(
+                        "Range variable name can be inferred only from a simple or qualified name with no arguments.".to_string()));
+                }
+                let mut result = Vec::with_capacity(rows.len());
+                for row in rows {
+                    result.push(self.query_fields(&row, fields)?);
+                }
+                Ok(result)
+            }
+            QueryClause::Distinct => {
+                let mut unique: Vec<query::Row> = Vec::new();
+                for row in rows {
+                    if !unique.iter().any(|u| query::rows_equal(u, &row)) {
+                        unique.push(row);
                     }
-                    _ => {} // OrderBy handled later
                 }
+                Ok(unique)
             }
-            
-            if include {
-                filtered_items.push(item); 
+            QueryClause::Skip(count) => {
+                let n = self.evaluate_expr(count)?.as_integer()?.max(0) as usize;
+                Ok(rows.into_iter().skip(n).collect())
             }
-        }
-        
-        // TODO: Sort (OrderBy)
-        
-        // Project (Select)
-        let mut results = Vec::new();
-        for item in filtered_items {
-            self.env.define(&range.name, item.clone());
-            // Re-run Let for context
-            for clause in &query.body.clauses {
-                if let QueryClause::Let { name, value } = clause {
-                     let v = self.evaluate_expr(value)?;
-                     self.env.define(name, v);
+            QueryClause::Take(count) => {
+                let n = self.evaluate_expr(count)?.as_integer()?.max(0) as usize;
+                Ok(rows.into_iter().take(n).collect())
+            }
+            QueryClause::SkipWhile(condition) => {
+                let mut result = Vec::new();
+                let mut skipping = true;
+                for row in rows {
+                    if skipping {
+                        self.bind_query_row(&row);
+                        skipping = self.evaluate_expr(condition)?.is_truthy();
+                    }
+                    if !skipping {
+                        result.push(row);
+                    }
                 }
+                Ok(result)
             }
-            
-            match &query.body.select_or_group {
-                SelectOrGroupClause::Select(exprs) => {
-                    if exprs.len() == 1 {
-                        results.push(self.evaluate_expr(&exprs[0])?);
-                    } else {
-                        return Err(RuntimeError::Custom("Multiple select fields not yet supported in runtime".to_string()));
+            QueryClause::TakeWhile(condition) => {
+                let mut result = Vec::new();
+                for row in rows {
+                    self.bind_query_row(&row);
+                    if !self.evaluate_expr(condition)?.is_truthy() {
+                        break;
+                    }
+                    result.push(row);
+                }
+                Ok(result)
+            }
+            QueryClause::Join(join) => {
+                let items = self.query_source(&join.range.collection)?;
+                let mut result = Vec::new();
+                for row in rows {
+                    for item in self.query_join_matches(&row, &join.range.name, &items, &join.conditions)? {
+                        self.budget.check_len(result.len() + 1)?;
+                        let mut next = row.clone();
+                        next.push((join.range.name.clone(), item));
+                        result.push(next);
                     }
                 }
-                SelectOrGroupClause::Group(_g) => {
-                     return Err(RuntimeError::Custom("Group By not yet supported".to_string()));
+                Ok(result)
+            }
+            QueryClause::GroupJoin(join) => {
+                let items = self.query_source(&join.range.collection)?;
+                let mut result = Vec::new();
+                for mut row in rows {
+                    let matches = self.query_join_matches(&row, &join.range.name, &items, &join.conditions)?;
+                    let elements: Vec<query::Element> = matches.into_iter()
+                        .map(|item| (vec![(join.range.name.clone(), item.clone())], item))
+                        .collect();
+                    self.bind_query_row(&row);
+                    row.extend(self.query_into(&elements, &join.into)?);
+                    result.push(row);
+                }
+                Ok(result)
+            }
+            QueryClause::GroupBy(group) => {
+                let mut groups: Vec<(Vec<Value>, Vec<query::Element>)> = Vec::new();
+                for row in rows {
+                    self.bind_query_row(&row);
+                    let keys = group.keys.iter().map(|k| self.evaluate_expr(&k.expression)).collect::<Result<Vec<_>, _>>()?;
+                    let element_row = if group.items.is_empty() { row } else { self.query_fields(&row, &group.items)? };
+                    let element = (element_row.clone(), query::row_value(element_row));
+                    match groups.iter_mut().find(|(k, _)| k.iter().zip(&keys).all(|(a, b)| values_equal(a, b))) {
+                        Some((_, elements)) => elements.push(element),
+                        None => groups.push((keys, vec![element])),
+                    }
+                }
+                let mut result = Vec::with_capacity(groups.len());
+                for (keys, elements) in groups {
+                    let mut row: query::Row = group.keys.iter().zip(keys)
+                        .map(|(field, key)| (field.name.clone().unwrap_or_else(|| "Key".to_string()), key))
+                        .collect();
+                    row.extend(self.query_into(&elements, &group.into)?);
+                    result.push(row);
+                }
+                Ok(result)
+            }
+            QueryClause::Aggregate(aggregate) => {
+                let mut result = Vec::with_capacity(rows.len());
+                for mut row in rows {
+                    // The inner rows hold only the inner ranges; the outer row stays bound
+                    self.bind_query_row(&row);
+                    let mut inner = self.query_cross_join(vec![Vec::new()], &aggregate.ranges)?;
+                    for clause in &aggregate.clauses {
+                        inner = self.apply_query_clause(inner, clause)?;
+                    }
+                    self.bind_query_row(&row);
+                    row.extend(self.query_into(&query_elements(inner), &aggregate.into)?);
+                    result.push(row);
                 }
+                Ok(result)
             }
+            QueryClause::Into(_) => Err(RuntimeError::Custom("'Into' is only valid at the end of an Aggregate query".to_string())),
         }
-        
-        self.env.pop_scope();
-        Ok(Value::Array(results))
     }
 
+    /// Evaluate `Select`-style fields against a row, giving the new row.
+    fn query_fields(&mut self, row: &query::Row, fields: &[vybe_parser::ast::query::QueryField]) -> Result<query::Row, RuntimeError> {
+        self.bind_query_row(row);
+        let mut result = Vec::with_capacity(fields.len());
+        for field in fields {
+            let value = self.evaluate_expr(&field.expression)?;
+            result.push((field.name.clone().unwrap_or_default(), value));
+        }
+        Ok(result)
+    }
+
+    /// The elements of `items` whose keys equal the row's under every `On` condition.
+    fn query_join_matches(&mut self, row: &query::Row, name: &str, items: &[Value], conditions: &[vybe_parser::ast::query::JoinCondition]) -> Result<Vec<Value>, RuntimeError> {
+        self.bind_query_row(row);
+        let left = conditions.iter().map(|c| self.evaluate_expr(&c.left)).collect::<Result<Vec<_>, _>>()?;
+        let mut matches = Vec::new();
+        for item in items {
+            self.env.define(name, item.clone());
+            let mut matched = true;
+            for (condition, key) in conditions.iter().zip(&left) {
+                if !values_equal(key, &self.evaluate_expr(&condition.right)?) {
+                    matched = false;
+                    break;
+                }
+            }
+            if matched {
+                matches.push(item.clone());
+            }
+        }
+        Ok(matches)
+    }
+
+    /// The `Into` aggregates over a group's elements, as new range variables.
+    /// Each element is its range variables (for the aggregate arguments) and its value.
+    fn query_into(&mut self, elements: &[query::Element], into: &[vybe_parser::ast::query::Aggregation]) -> Result<query::Row, RuntimeError> {
+        let mut result = Vec::with_capacity(into.len());
+        for aggregation in into {
+            let values = match &aggregation.argument {
+                Some(argument) => {
+                    let mut values = Vec::with_capacity(elements.len());
+                    for (row, _) in elements {
+                        self.bind_query_row(row);
+                        values.push(self.evaluate_expr(argument)?);
+                    }
+                    values
+                }
+                None => elements.iter().map(|(_, v)| v.clone()).collect(),
+            };
+            let function = aggregation.function.to_lowercase();
+            let value = match function.as_str() {
+                "group" => Value::Array(values),
+                "count" | "longcount" => {
+                    let n = match aggregation.argument {
+                        Some(_) => values.iter().filter(|v| v.is_truthy()).count(),
+                        None => values.len(),
+                    };
+                    if function == "count" { Value::Integer(n as i32) } else { Value::Long(n as i64) }
+                }
+                "any" => Value::Boolean(match aggregation.argument {
+                    Some(_) => values.iter().any(|v| v.is_truthy()),
+                    None => !values.is_empty(),
+                }),
+                "all" => Value::Boolean(values.iter().all(|v| v.is_truthy())),
+                "sum" => query::sum_values(&values)?,
+                "average" if values.is_empty() => return Err(RuntimeError::Custom("Sequence contains no elements".to_string())),
+                "average" => Value::Double(query::sum_values(&values)?.as_double()? / values.len() as f64),
+                "min" | "max" => {
+                    let want = if function == "min" { std::cmp::Ordering::Less } else { std::cmp::Ordering::Greater };
+                    values.into_iter()
+                        .reduce(|best, v| if compare_values_ordering(&v, &best) == want { v } else { best })
+                        .ok_or_else(|| RuntimeError::Custom("Sequence contains no elements".to_string()))?
+                }
+                _ => return Err(RuntimeError::Custom(format!("'{}' is not an aggregate function", aggregation.function))),
+            };
+            result.push((aggregation.name.clone().unwrap_or_else(|| aggregation.function.clone()), value));
+        }
+        Ok(result)
+    }
+
+    /// Build the value of an XML literal. Nested elements and the literals
+    /// inside embedded expressions recurse as deep as the source nests them,
+    /// so the native stack is extended as they go.
     fn construct_xml(&mut self, node: &vybe_parser::ast::xml::XmlNode) -> Result<Value, RuntimeError> {
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || self.construct_xml_node(node))
+    }
+
+    fn construct_xml_node(&mut self, node: &vybe_parser::ast::xml::XmlNode) -> Result<Value, RuntimeError> {
         use vybe_parser::ast::xml::*;
         match node {
             XmlNode::Element(el) => {
-                let name = if let Some(prefix) = &el.name.prefix {
-                    format!("{}:{}", prefix, el.name.local)
-                } else {
-                    el.name.local.clone()
-                };
+                let name = xml_name(&el.name);
                 
                 let mut content = Vec::new();
                 
                 // Attributes creation
                 // Note: builtins::xml::create_xattribute takes &[Value] usually [name, value]
                 for attr in &el.attributes {
-                    let attr_name = if let Some(prefix) = &attr.name.prefix {
-                        format!("{}:{}", prefix, attr.name.local)
-                    } else {
-                        attr.name.local.clone()
+                    let attr_name = match &attr.name_expression {
+                        // <item <%= attr %>/>: the expression is the attribute itself
+                        Some(expr) if attr.value.is_empty() => {
+                            match self.evaluate_expr(expr)? {
+                                Value::Array(items) => content.extend(items),
+                                Value::Nothing => {}
+                                other => content.push(other),
+                            }
+                            continue;
+                        }
+                        Some(expr) => self.evaluate_expr(expr)?.as_string(),
+                        None => xml_name(&attr.name),
                     };
                     
                     let mut attr_val_str = String::new();
@@ -16840,13 +20636,26 @@ impl Interpreter {
                     content.push(attr_obj);
                 }
                 
-                // Children
+                // Children. Text pieces and embedded values join into the
+                // element's one text value
+                let mut text = String::new();
                 for child in &el.children {
                     match child {
-                        XmlNode::Text(s) => content.push(Value::String(s.clone())),
+                        XmlNode::Text(s) => text.push_str(s),
                         XmlNode::EmbeddedExpression(expr) => {
-                             let v = self.evaluate_expr(expr)?;
-                             content.push(v);
+                            let v = self.evaluate_expr(expr)?;
+                            // A sequence, such as a query's result, adds each item
+                            let items = match v {
+                                Value::Array(items) => items,
+                                other => vec![other],
+                            };
+                            for item in items {
+                                if crate::builtins::xml::is_xml_object(&item) {
+                                    content.push(item);
+                                } else {
+                                    text.push_str(&item.as_string());
+                                }
+                            }
                         }
                         XmlNode::Element(_) => {
                             content.push(self.construct_xml(child)?);
@@ -16857,6 +20666,9 @@ impl Interpreter {
                         _ => {}
                     }
                 }
+                if !text.is_empty() {
+                    content.push(Value::String(text));
+                }
                 
                 let mut args = vec![Value::String(name)];
                 args.extend(content);
@@ -16871,3 +20683,135 @@ impl Interpreter {
         }
     }
 }
+
+/// Index of the `Label` statement named `label` in `body`.
+fn find_label(body: &Block, label: &str) -> Result<usize, RuntimeError> {
+    body.iter()
+        .position(|s| matches!(s, Statement::Label(l) if l.eq_ignore_ascii_case(label)))
+        .ok_or_else(|| RuntimeError::Custom(format!("Label '{}' not found", label)))
+}
+
+/// How a call of procedure `name` shows in a stack trace: with its class
+/// when it runs on an object.
+fn frame_name(name: &str, context: &Option<Rc<RefCell<ObjectData>>>) -> String {
+    match context {
+        Some(obj) => format!("{}.{}", obj.borrow().class_name, name),
+        None => name.to_string(),
+    }
+}
+
+/// The binary operator a compound assignment applies: `+` for `+=`.
+fn compound_symbol(operator: &vybe_parser::ast::stmt::CompoundOp) -> &'static str {
+    use vybe_parser::ast::stmt::CompoundOp;
+    match operator {
+        CompoundOp::AddAssign => "+",
+        CompoundOp::SubtractAssign => "-",
+        CompoundOp::MultiplyAssign => "*",
+        CompoundOp::DivideAssign => "/",
+        CompoundOp::IntDivideAssign => "\\",
+        CompoundOp::ConcatAssign => "&",
+        CompoundOp::ExponentAssign => "^",
+        CompoundOp::ShiftLeftAssign => "<<",
+        CompoundOp::ShiftRightAssign => ">>",
+    }
+}
+
+/// The new value of `current op= rhs`.
+fn compound_value(operator: &vybe_parser::ast::stmt::CompoundOp, current: &Value, rhs: &Value) -> Result<Value, RuntimeError> {
+    use vybe_parser::ast::stmt::CompoundOp;
+    let symbol = match operator {
+        CompoundOp::AddAssign
+        | CompoundOp::SubtractAssign
+        | CompoundOp::MultiplyAssign
+        | CompoundOp::DivideAssign
+        | CompoundOp::IntDivideAssign => Some(compound_symbol(operator)),
+        _ => None,
+    };
+    // Decimal and integral operands keep their exact, checked type
+    let exact = match symbol {
+        Some(sym) => match crate::decimal::binary_operation(sym, current, rhs)? {
+            Some(result) => Some(result),
+            None => crate::integral::binary_operation(sym, current, rhs)?,
+        },
+        None => None,
+    };
+    if let Some(result) = exact {
+        return Ok(result);
+    }
+    Ok(match operator {
+        CompoundOp::AddAssign => {
+            match (current, rhs) {
+                (Value::String(a), _) => Value::String(format!("{}{}", a, rhs.as_string())),
+                _ => Value::Double(current.as_double()? + rhs.as_double()?),
+            }
+        }
+        CompoundOp::SubtractAssign => {
+            Value::Double(current.as_double()? - rhs.as_double()?)
+        }
+        CompoundOp::MultiplyAssign => {
+            Value::Double(current.as_double()? * rhs.as_double()?)
+        }
+        CompoundOp::DivideAssign => {
+            let a = current.as_double()?;
+            let b = rhs.as_double()?;
+            if b == 0.0 { return Err(RuntimeError::DivisionByZero); }
+            Value::Double(a / b)
+        }
+        CompoundOp::IntDivideAssign => {
+            let a = current.as_integer()?;
+            let b = rhs.as_integer()?;
+            if b == 0 { return Err(RuntimeError::DivisionByZero); }
+            Value::Integer(a / b)
+        }
+        CompoundOp::ConcatAssign => {
+            Value::String(format!("{}{}", current.as_string(), rhs.as_string()))
+        }
+        CompoundOp::ExponentAssign => {
+            Value::Double(current.as_double()?.powf(rhs.as_double()?))
+        }
+        CompoundOp::ShiftLeftAssign | CompoundOp::ShiftRightAssign => {
+            crate::integral::shift(compound_symbol(operator), current, rhs)?
+        }
+    })
+}
+
+/// Pop the VM stack; the compiler keeps it balanced.
+fn pop(stack: &mut Vec<Value>) -> Value {
+    stack.pop().unwrap_or(Value::Nothing)
+}
+
+/// The counter, end and step of the innermost `For`, on top of the VM stack.
+fn for_counter(stack: &[Value]) -> (i32, i32, i32) {
+    let int = |i: usize| match stack.get(stack.len().wrapping_sub(i)) {
+        Some(Value::Integer(n)) => *n,
+        _ => 0,
+    };
+    (int(3), int(2), int(1))
+}
+
+/// Drop the VM stack to the depth inside `chunk.loops[id]`; returns where
+/// the loop goes on (`next`) or ends.
+fn leave_loop(chunk: &Chunk, id: usize, stack: &mut Vec<Value>, next: bool) -> usize {
+    let target = &chunk.loops[id];
+    stack.truncate(target.depth);
+    if next { target.continue_at } else { target.exit_at }
+}
+
+/// How `AndAlso`/`OrElse` goes on after evaluating its left operand.
+enum ShortCircuit {
+    /// The left operand decided the result.
+    Done(Value),
+    /// The result is the right operand's truth value.
+    Right,
+    /// The left operand's class declares `IsTrue`/`IsFalse`: the result is
+    /// its `And`/`Or` operator applied to both operands.
+    Operator,
+}
+
+/// An XML name as written, with its prefix: `xml:lang`.
+fn xml_name(name: &vybe_parser::ast::xml::XmlName) -> String {
+    match &name.prefix {
+        Some(prefix) => format!("{}:{}", prefix, name.local),
+        None => name.local.clone(),
+    }
+}
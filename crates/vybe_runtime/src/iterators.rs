@@ -0,0 +1,152 @@
+//! Runtime support for `Iterator Function`s and lazy sequences.
+//!
+//! Calling an Iterator Function doesn't run its body: it returns an
+//! `IEnumerable` object holding the body and the bound arguments. Each
+//! enumeration (`For Each`, a LINQ operator) opens a fresh [`Cursor`] and
+//! pulls values from it one at a time. A body cursor keeps the iterator's
+//! locals and a stack of [`Frame`]s — the blocks and loops it is inside of —
+//! so execution can stop at a `Yield` and resume there on the next pull.
+//!
+//! LINQ operators applied to an `IEnumerable` (`Where`, `Select`, `Take`, ...)
+//! return another `IEnumerable` wrapping their source, so a pipeline over an
+//! infinite sequence only computes the values that are consumed.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use vybe_parser::ast::stmt::{CatchBlock, LoopConditionType};
+use vybe_parser::{Block, Expression, Statement, VBType};
+
+use crate::environment::Scope;
+use crate::value::{ObjectData, Value};
+
+/// Class name of the lazy sequence objects.
+pub const ENUMERABLE_CLASS: &str = "IEnumerable";
+
+/// Which operation an `IEnumerable` object performs, stored in its `__kind` field.
+pub const KIND_FIELD: &str = "__kind";
+
+/// A source of values being enumerated.
+pub enum Cursor {
+    /// A concrete collection, already materialized.
+    Items { items: Vec<Value>, index: usize },
+    /// A suspended Iterator Function body.
+    Body(Box<BodyCursor>),
+    Select { source: Box<Cursor>, selector: Value },
+    Where { source: Box<Cursor>, predicate: Value },
+    Take { source: Box<Cursor>, remaining: usize },
+    Skip { source: Box<Cursor>, remaining: usize },
+    TakeWhile { source: Box<Cursor>, predicate: Value, done: bool },
+    SkipWhile { source: Box<Cursor>, predicate: Value, skipping: bool },
+}
+
+/// The state of an Iterator Function between two `Yield`s.
+pub struct BodyCursor {
+    /// The iterator's parameters and locals.
+    pub locals: Scope,
+    /// Innermost last; empty once the body has finished.
+    pub frames: Vec<Frame>,
+    /// `Me` for an iterator method.
+    pub me: Option<Rc<RefCell<ObjectData>>>,
+    /// The object of the innermost `With` block the iterator is inside of.
+    pub with_object: Option<Value>,
+    pub procedure: String,
+    pub finished: bool,
+}
+
+/// A construct the iterator is executing inside of. A loop frame is
+/// revisited each time its body block finishes, to start the next iteration.
+/// `Yield` isn't allowed in `Catch` and `Finally` blocks, so those always run
+/// in one go and a `Try` frame only covers the `Try` block itself.
+pub enum Frame {
+    Block { block: Rc<Block>, pc: usize },
+    For { variable: String, var_type: Option<VBType>, next: i32, end: i32, step: i32, body: Rc<Block> },
+    ForEach { variable: String, items: Box<Cursor>, body: Rc<Block> },
+    While { condition: Expression, body: Rc<Block> },
+    Do {
+        pre_condition: Option<(LoopConditionType, Expression)>,
+        post_condition: Option<(LoopConditionType, Expression)>,
+        body: Rc<Block>,
+        entered: bool,
+    },
+    /// The chosen `Case` of a `Select Case`, so `Exit Select` knows where to stop.
+    Select,
+    Try { catches: Vec<CatchBlock>, finally: Option<Block> },
+    /// A `With` block, holding the object of the one around it.
+    With { previous: Option<Value> },
+    /// A `Using` block; its resource is disposed however the block is left.
+    Using { variable: String, resource: Value },
+}
+
+/// Is `value` a lazy sequence produced by an Iterator Function or a LINQ operator over one?
+pub fn is_enumerable(value: &Value) -> bool {
+    match value {
+        Value::Object(obj) => {
+            let obj = obj.borrow();
+            obj.class_name == ENUMERABLE_CLASS && obj.fields.contains_key(KIND_FIELD)
+        }
+        _ => false,
+    }
+}
+
+/// An `IEnumerable` object of the given kind with its operation's fields.
+pub fn enumerable(kind: &str, fields: Vec<(&str, Value)>) -> Value {
+    let mut map: HashMap<String, Value> = fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+    map.insert(KIND_FIELD.to_string(), Value::String(kind.to_string()));
+    map.insert("__type".to_string(), Value::String(ENUMERABLE_CLASS.to_string()));
+    Value::Object(Rc::new(RefCell::new(ObjectData {
+        class_name: ENUMERABLE_CLASS.to_string(),
+        fields: map,
+        drawing_commands: Vec::new(),
+    })))
+}
+
+/// A field of an `IEnumerable` object, Nothing if it's missing.
+pub fn field(value: &Value, name: &str) -> Value {
+    match value {
+        Value::Object(obj) => obj.borrow().fields.get(name).cloned().unwrap_or(Value::Nothing),
+        _ => Value::Nothing,
+    }
+}
+
+/// Does `stmt` contain a `Yield`, so the iterator has to step into it
+/// instead of running it in one go?
+pub fn contains_yield(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Yield(_) => true,
+        Statement::If { then_branch, elseif_branches, else_branch, .. } => {
+            block_contains_yield(then_branch)
+                || elseif_branches.iter().any(|(_, b)| block_contains_yield(b))
+                || else_branch.as_ref().is_some_and(block_contains_yield)
+        }
+        Statement::Select { cases, else_block, .. } => {
+            cases.iter().any(|c| block_contains_yield(&c.body)) || else_block.as_ref().is_some_and(block_contains_yield)
+        }
+        Statement::Try { body, .. } => block_contains_yield(body),
+        Statement::For { body, .. }
+        | Statement::ForEach { body, .. }
+        | Statement::While { body, .. }
+        | Statement::DoLoop { body, .. }
+        | Statement::With { body, .. }
+        | Statement::Using { body, .. }
+        | Statement::SyncLock { body, .. } => block_contains_yield(body),
+        _ => false,
+    }
+}
+
+pub fn block_contains_yield(block: &Block) -> bool {
+    block.iter().any(contains_yield)
+}
+
+/// What one step of the innermost [`Frame`] did.
+pub enum Step {
+    /// Produced a value; the iterator suspends here.
+    Yield(Value),
+    /// Entered constructs that have to be stepped through.
+    Enter(Vec<Frame>),
+    /// The frame is done.
+    Leave,
+    /// Ran a statement; keep going.
+    Next,
+}
@@ -8,6 +8,23 @@ pub mod file_io;
 pub mod std_lib;
 pub mod collections;
 pub mod data_access;
+pub mod overloads;
+pub mod generics;
+pub mod iterators;
+pub mod options;
+pub mod query;
+pub mod decimal;
+pub mod integral;
+pub mod tuples;
+pub mod arguments;
+pub mod structures;
+pub mod delegates;
+pub mod reflection;
+pub mod records;
+pub mod bytecode;
+pub mod symbol;
+pub mod limits;
+pub mod sandbox;
 
 /// A resource entry passed from the project layer into the runtime.
 /// Carries type info so the runtime can distinguish strings from file resources.
@@ -125,3 +142,6 @@ pub use evaluator::*;
 pub use environment::*;
 pub use value::*;
 pub use event_system::*;
+pub use bytecode::Engine;
+pub use limits::{CancelHandle, ExecutionLimits, Limit};
+pub use sandbox::{Capability, FileAccess, SandboxPolicy};
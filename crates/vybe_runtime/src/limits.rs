@@ -0,0 +1,216 @@
+//! Limits a host puts on the scripts it runs, and cancelling them.
+//!
+//! A service running user-supplied code sets [`ExecutionLimits`] with
+//! [`Interpreter::set_limits`](crate::Interpreter::set_limits) and keeps the
+//! interpreter's [`CancelHandle`] to stop a script from another thread. The
+//! interpreter checks both at every loop iteration and procedure call, under
+//! either engine, and while the script sleeps or waits for a task. Sizes are
+//! checked wherever an array, collection or string grows: collection methods
+//! and indexers, `Dim`/`ReDim`, concatenation, query rows and the values
+//! builtins return. Running past a limit fails with
+//! [`RuntimeError::LimitExceeded`] and cancelling with
+//! [`RuntimeError::Cancelled`]; neither can be caught by the script's own
+//! `Try`/`Catch` or `On Error`.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::value::{RuntimeError, Value};
+
+/// What a script may use. `None` leaves that resource unlimited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecutionLimits {
+    /// Loop iterations and procedure calls the script may make.
+    pub max_steps: Option<u64>,
+    /// Wall-clock time the script may run for.
+    pub timeout: Option<Duration>,
+    /// Elements any one array or collection may hold.
+    pub max_collection_len: Option<usize>,
+    /// Characters any one string may hold.
+    pub max_string_len: Option<usize>,
+    /// Bytes the script may write with `Console` and `Debug`.
+    pub max_output_bytes: Option<usize>,
+}
+
+/// The limit a script ran past, with its setting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    Steps(u64),
+    Timeout(Duration),
+    CollectionLen(usize),
+    StringLen(usize),
+    OutputBytes(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Steps(max) => write!(f, "more than {} steps", max),
+            Limit::Timeout(timeout) => write!(f, "ran longer than {:?}", timeout),
+            Limit::CollectionLen(max) => write!(f, "a collection of more than {} elements", max),
+            Limit::StringLen(max) => write!(f, "a string of more than {} characters", max),
+            Limit::OutputBytes(max) => write!(f, "more than {} bytes of output", max),
+        }
+    }
+}
+
+/// Stops a running script from any thread. Clones share one flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    /// Stop the script at its next loop iteration or procedure call.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Let the interpreter run again after a cancel.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Steps between looks at the clock; reading it costs more than a step.
+const CLOCK_INTERVAL: u64 = 16;
+
+/// The longest a sleeping script goes without looking at its cancel handle
+/// and deadline.
+const SLEEP_SLICE: Duration = Duration::from_millis(10);
+
+/// How much of its [`ExecutionLimits`] a run has used.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    limits: ExecutionLimits,
+    deadline: Option<Instant>,
+    steps: u64,
+    output_bytes: usize,
+    cancel: CancelHandle,
+    /// A run is in progress, so calls into the interpreter share its budget.
+    running: bool,
+}
+
+impl Budget {
+    /// A budget of `limits`, spent from the start of each run.
+    pub fn new(limits: ExecutionLimits, cancel: CancelHandle) -> Self {
+        Budget { limits, cancel, ..Default::default() }
+    }
+
+    /// Start a run unless one is in progress: the clock for the timeout
+    /// starts now, and steps and output are counted from zero. Returns
+    /// whether it started one, which the caller then [`end`](Self::end)s.
+    pub fn begin(&mut self) -> bool {
+        if self.running {
+            return false;
+        }
+        self.running = true;
+        self.deadline = self.limits.timeout.map(|timeout| Instant::now() + timeout);
+        self.steps = 0;
+        self.output_bytes = 0;
+        true
+    }
+
+    pub fn end(&mut self) {
+        self.running = false;
+    }
+
+    pub fn limits(&self) -> &ExecutionLimits {
+        &self.limits
+    }
+
+    pub fn cancel_handle(&self) -> &CancelHandle {
+        &self.cancel
+    }
+
+    /// The budget of a thread the script starts: the same limits, deadline
+    /// and cancel handle, with its own count of steps and output.
+    pub fn for_thread(&self) -> Self {
+        Budget { steps: 0, output_bytes: 0, ..self.clone() }
+    }
+
+    /// Count one loop iteration or procedure call.
+    pub fn step(&mut self) -> Result<(), RuntimeError> {
+        if self.cancel.is_cancelled() {
+            return Err(RuntimeError::Cancelled);
+        }
+        self.steps += 1;
+        if let Some(max) = self.limits.max_steps && self.steps > max {
+            return Err(RuntimeError::LimitExceeded(Limit::Steps(max)));
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout)
+            && self.steps.is_multiple_of(CLOCK_INTERVAL)
+            && Instant::now() >= deadline
+        {
+            return Err(RuntimeError::LimitExceeded(Limit::Timeout(timeout)));
+        }
+        Ok(())
+    }
+
+    /// Check that an array or collection may hold `len` elements.
+    pub fn check_len(&self, len: usize) -> Result<(), RuntimeError> {
+        match self.limits.max_collection_len {
+            Some(max) if len > max => Err(RuntimeError::LimitExceeded(Limit::CollectionLen(max))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that a string may hold `text`.
+    pub fn check_string(&self, text: &str) -> Result<(), RuntimeError> {
+        match self.limits.max_string_len {
+            // A string has no more characters than bytes
+            Some(max) if text.len() > max && text.chars().count() > max => {
+                Err(RuntimeError::LimitExceeded(Limit::StringLen(max)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the size of a value a builtin or operator just made.
+    pub fn check_value(&self, value: &Value) -> Result<(), RuntimeError> {
+        match value {
+            Value::String(text) => self.check_string(text),
+            Value::Array(items) => self.check_len(items.len()),
+            Value::Collection(items) => self.check_len(items.borrow().items.len()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sleep for `duration`, waking early to fail once the run is cancelled
+    /// or past its deadline.
+    pub fn sleep(&self, duration: Duration) -> Result<(), RuntimeError> {
+        let end = Instant::now() + duration;
+        loop {
+            if self.cancel.is_cancelled() {
+                return Err(RuntimeError::Cancelled);
+            }
+            let now = Instant::now();
+            if let (Some(deadline), Some(timeout)) = (self.deadline, self.limits.timeout) && now >= deadline {
+                return Err(RuntimeError::LimitExceeded(Limit::Timeout(timeout)));
+            }
+            if now >= end {
+                return Ok(());
+            }
+            std::thread::sleep(SLEEP_SLICE.min(end - now));
+        }
+    }
+
+    /// Check that an array with these dimension lengths may be allocated.
+    pub fn check_dims(&self, dims: &[usize]) -> Result<(), RuntimeError> {
+        self.check_len(dims.iter().fold(1, |len: usize, &dim| len.saturating_mul(dim)))
+    }
+
+    /// Count `bytes` of output, failing instead once they would pass the limit.
+    pub fn output(&mut self, bytes: usize) -> Result<(), RuntimeError> {
+        let total = self.output_bytes + bytes;
+        if let Some(max) = self.limits.max_output_bytes && total > max {
+            return Err(RuntimeError::LimitExceeded(Limit::OutputBytes(max)));
+        }
+        self.output_bytes = total;
+        Ok(())
+    }
+}
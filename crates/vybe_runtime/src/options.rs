@@ -0,0 +1,155 @@
+//! `Option Explicit`, `Option Strict`, `Option Compare` and `Option Infer`.
+//!
+//! A file's `Option` statements apply to the code in that file and fall back
+//! to the project defaults (`<OptionStrict>` and friends in the .vbproj).
+//! Settings neither states keep the interpreter's historical late-bound
+//! behaviour: variables spring into existence on assignment, any value
+//! converts implicitly, and `=` and `Like` ignore case while `<`, `>` and
+//! `InStr` don't.
+
+use vybe_parser::ast::{ExprKind, Expression};
+use vybe_parser::{CompareMode, OptionSettings, VBType};
+
+use crate::overloads::{is_primitive, numeric_rank, score_argument, ClassMatch, NARROWING};
+use crate::value::{RuntimeError, Value};
+
+pub fn explicit(options: &OptionSettings) -> bool {
+    options.explicit.unwrap_or(false)
+}
+
+pub fn strict(options: &OptionSettings) -> bool {
+    options.strict.unwrap_or(false)
+}
+
+pub fn infer(options: &OptionSettings) -> bool {
+    options.infer.unwrap_or(true)
+}
+
+/// `Some(true)` under `Option Compare Text`, `Some(false)` under Binary.
+pub fn ignore_case(options: &OptionSettings) -> Option<bool> {
+    options.compare.map(|mode| mode == CompareMode::Text)
+}
+
+/// Lowercased `ty` without any `System.` qualifier, if it is a primitive type.
+fn primitive_name(ty: &VBType) -> Option<String> {
+    let name = ty.to_string().to_lowercase();
+    let name = name.rsplit('.').next().unwrap_or(&name).to_string();
+    let primitive = numeric_rank(&name).is_some()
+        || matches!(name.as_str(), "string" | "char" | "boolean" | "date" | "datetime");
+    primitive.then_some(name)
+}
+
+/// Is `expr` a literal, whose value may narrow to any type that can hold it?
+pub fn is_constant(expr: &Expression) -> bool {
+    match &expr.kind {
+        ExprKind::IntegerLiteral(_) | ExprKind::DoubleLiteral(_) | ExprKind::LongLiteral(_)
+        | ExprKind::ShortLiteral(_) | ExprKind::UShortLiteral(_) | ExprKind::UIntegerLiteral(_)
+        | ExprKind::ULongLiteral(_) | ExprKind::SingleLiteral(_) | ExprKind::DecimalLiteral(_) => true,
+        ExprKind::Negate(inner) => is_constant(inner),
+        _ => false,
+    }
+}
+
+/// Does the constant `value` fit in the numeric type `name` without loss?
+fn constant_fits(name: &str, value: &Value) -> bool {
+    let whole = match value {
+        // Double constants convert to Single; VB rounds them silently.
+        Value::Double(_) => return name == "single",
+        // Decimal constants convert to Single and Double the same way.
+        Value::Decimal(_) => return matches!(name, "single" | "double"),
+        Value::Boolean(_) => return false,
+        other => match crate::integral::int_value(other) {
+            Some(whole) => whole,
+            None => return false,
+        },
+    };
+    match crate::integral::IntType::from_name(name) {
+        Some(ty) => ty.make(whole).is_ok(),
+        None => numeric_rank(name).is_some(),
+    }
+}
+
+/// Would storing `value` in a variable declared `As ty` be an implicit
+/// narrowing conversion? Only conversions between primitive types are
+/// checked; objects are the late binder's business.
+pub fn is_narrowing(ty: &VBType, value: &Value, constant: bool) -> bool {
+    let Some(name) = primitive_name(ty) else { return false };
+    if !is_primitive(value) {
+        return false;
+    }
+    let score = score_argument(Some(ty), value, &|_, _| ClassMatch::Unknown);
+    score == Some(NARROWING) && !(constant && constant_fits(&name, value))
+}
+
+/// Would converting a variable declared `As from` to `to` be narrowing?
+pub fn is_narrowing_type(from: &VBType, to: &VBType) -> bool {
+    let sample = match primitive_name(from).as_deref() {
+        Some("char") => Value::Char('\0'),
+        Some("single") => Value::Single(0.0),
+        Some("double") => Value::Double(0.0),
+        Some("decimal") => Value::Decimal(crate::decimal::Decimal::ZERO),
+        Some("string") => Value::String(String::new()),
+        Some("boolean") => Value::Boolean(false),
+        Some("date") | Some("datetime") => Value::Date(0.0),
+        Some(name) => match crate::integral::IntType::from_name(name) {
+            Some(ty) => ty.zero(),
+            None => return false,
+        },
+        None => return false,
+    };
+    is_narrowing(to, &sample, false)
+}
+
+/// Members every value has, so calling them on an `Object` isn't late bound.
+pub fn is_object_member(name: &str) -> bool {
+    ["tostring", "gettype", "equals", "gethashcode"].iter().any(|m| name.eq_ignore_ascii_case(m))
+}
+
+/// The type `Option Infer` gives a variable declared without `As`.
+pub fn inferred_type(value: &Value) -> VBType {
+    match value {
+        Value::SByte(_) => VBType::SByte,
+        Value::Byte(_) => VBType::Byte,
+        Value::Short(_) => VBType::Short,
+        Value::UShort(_) => VBType::UShort,
+        Value::Integer(_) => VBType::Integer,
+        Value::UInteger(_) => VBType::UInteger,
+        Value::Long(_) => VBType::Long,
+        Value::ULong(_) => VBType::ULong,
+        Value::Single(_) => VBType::Single,
+        Value::Double(_) => VBType::Double,
+        Value::Decimal(_) => VBType::Decimal,
+        Value::String(_) => VBType::String,
+        Value::Boolean(_) => VBType::Boolean,
+        Value::Date(_) => VBType::Date,
+        Value::Char(_) => VBType::Custom("Char".to_string()),
+        Value::Object(obj) => VBType::Custom(obj.borrow().class_name.clone()),
+        _ => VBType::Object,
+    }
+}
+
+/// Name of `value`'s type as it appears in conversion errors.
+pub fn value_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Char(_) => "Char",
+        Value::Single(_) => "Single",
+        Value::Double(_) => "Double",
+        Value::Decimal(_) => "Decimal",
+        Value::Date(_) => "Date",
+        Value::String(_) => "String",
+        Value::Boolean(_) => "Boolean",
+        other => crate::integral::IntType::of(other).map_or("Object", |ty| ty.name()),
+    }
+}
+
+pub fn narrowing_error(from: &str, to: &VBType) -> RuntimeError {
+    RuntimeError::Custom(format!("Option Strict On disallows implicit conversions from '{}' to '{}'.", from, to))
+}
+
+pub fn late_binding_error() -> RuntimeError {
+    RuntimeError::Custom("Option Strict On disallows late binding.".to_string())
+}
+
+pub fn as_clause_error() -> RuntimeError {
+    RuntimeError::Custom("Option Strict On requires all variable declarations to have an 'As' clause.".to_string())
+}
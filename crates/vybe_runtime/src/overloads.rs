@@ -0,0 +1,385 @@
+//! Overload resolution for user-defined Subs, Functions and methods.
+//!
+//! Procedures are registered by lowercased key (`module.name`, `class.name`)
+//! in the `subs` / `functions` maps, which hold every signature declared
+//! under a key. The [`OverloadTable`] records which keys declare each name,
+//! and the scoring helpers below pick the best overload for a call,
+//! following the VB.NET rules closely enough for late-bound code: argument
+//! count (with Optional and ParamArray), then the most specific parameter
+//! types.
+
+use std::collections::HashMap;
+
+use vybe_parser::ast::decl::{MethodDecl, Parameter};
+use vybe_parser::{Span, VBType};
+
+use crate::integral::IntType;
+use crate::value::Value;
+
+/// Argument matches the parameter type exactly.
+pub const EXACT: u8 = 100;
+/// Widening conversion; closer types score higher (see [`score_argument`]).
+pub const WIDENING: u8 = 90;
+/// Parameter is `Object` (or untyped, or a type the runtime can't check).
+pub const OBJECT: u8 = 50;
+/// Narrowing conversion, allowed because the runtime is late bound.
+pub const NARROWING: u8 = 10;
+
+/// The overload picked for a call, plus the argument values when they had
+/// to be evaluated to pick it.
+pub type ResolvedOverload = (MethodDecl, Option<Vec<Value>>);
+
+/// The `subs` / `functions` keys a lowercased procedure name is declared
+/// under, and whether it has more than one signature among them.
+#[derive(Debug, Clone, Default)]
+struct DeclaredName {
+    keys: Vec<String>,
+    overloaded: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OverloadTable {
+    names: HashMap<String, DeclaredName>,
+    /// Whether any name is overloaded; until one is, calls skip resolution
+    /// without looking the name up.
+    any_overloaded: bool,
+}
+
+impl OverloadTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The keys `name` (lowercased) has been registered under.
+    pub fn keys(&self, name: &str) -> &[String] {
+        self.names.get(name).map(|n| n.keys.as_slice()).unwrap_or(&[])
+    }
+
+    /// Record a procedure called `name` (lowercased) being registered under
+    /// `key`; `overloaded` says whether its signature differs from one
+    /// already registered under the name, including across a class hierarchy.
+    pub fn register(&mut self, name: String, key: &str, overloaded: bool) {
+        let declared = self.names.entry(name).or_default();
+        if !declared.keys.iter().any(|k| k == key) {
+            declared.keys.push(key.to_string());
+        }
+        declared.overloaded |= overloaded;
+        self.any_overloaded |= overloaded;
+    }
+
+    /// Whether any procedure called `name` has more than one signature.
+    pub fn is_overloaded(&self, name: &str) -> bool {
+        self.any_overloaded && self.names.get(&name.to_lowercase()).is_some_and(|n| n.overloaded)
+    }
+}
+
+pub fn method_name(method: &MethodDecl) -> &str {
+    match method {
+        MethodDecl::Sub(s) => s.name.as_str(),
+        MethodDecl::Function(f) => f.name.as_str(),
+    }
+}
+
+/// Where a procedure was declared: its span and source file.
+pub type Origin<'a> = (Span, Option<&'a str>);
+
+/// Whether `method` is the declaration of `name(params)` at `origin`. A
+/// class and a module can each declare a procedure with the same name and
+/// signature; where they were written tells them apart.
+pub fn is_declaration(method: &MethodDecl, name: &str, params: &[Parameter], origin: Origin) -> bool {
+    let declared = match method {
+        MethodDecl::Sub(s) => (s.span, s.source_file.as_deref()),
+        MethodDecl::Function(f) => (f.span, f.source_file.as_deref()),
+    };
+    declared == origin && method_name(method).eq_ignore_ascii_case(name) && method_parameters(method) == params
+}
+
+pub fn method_parameters(method: &MethodDecl) -> &[Parameter] {
+    match method {
+        MethodDecl::Sub(s) => &s.parameters,
+        MethodDecl::Function(f) => &f.parameters,
+    }
+}
+
+/// Two parameter lists with the same count, types and ParamArray-ness.
+/// Names, ByVal/ByRef and Optional defaults don't distinguish overloads.
+pub fn same_signature(a: &[Parameter], b: &[Parameter]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(x, y)| {
+            x.is_param_array == y.is_param_array && type_key(x.param_type.as_ref()) == type_key(y.param_type.as_ref())
+        })
+}
+
+fn type_key(ty: Option<&VBType>) -> String {
+    match ty {
+        None | Some(VBType::Object) | Some(VBType::Variant) => "object".to_string(),
+        Some(t) => t.to_string().to_lowercase(),
+    }
+}
+
+/// Can a call with `count` arguments bind to `params`?
+pub fn accepts_arg_count(params: &[Parameter], count: usize) -> bool {
+    let has_param_array = params.last().is_some_and(|p| p.is_param_array);
+    let required = params.iter().filter(|p| !p.is_optional && !p.is_param_array).count();
+    count >= required && (has_param_array || count <= params.len())
+}
+
+/// `Format(x As Integer, Optional y As String)`, for error messages.
+pub fn signature_display(method: &MethodDecl) -> String {
+    let params: Vec<String> = method_parameters(method)
+        .iter()
+        .map(|p| {
+            let mut s = String::new();
+            if p.is_param_array {
+                s.push_str("ParamArray ");
+            } else if p.is_optional {
+                s.push_str("Optional ");
+            }
+            s.push_str(p.name.as_str());
+            if let Some(ty) = &p.param_type {
+                s.push_str(&format!(" As {}", ty));
+            }
+            s
+        })
+        .collect();
+    format!("{}({})", method_name(method), params.join(", "))
+}
+
+/// How an object's class relates to a parameter type, as answered by the
+/// interpreter (which knows the class hierarchy). The class is `None` when
+/// the argument isn't an object at all.
+pub enum ClassMatch {
+    /// The class is the type (0) or derives from it `n` levels down.
+    Distance(u8),
+    /// The type is a user class the object's class doesn't derive from.
+    Unrelated,
+    /// The type isn't a user class (a framework type, interface, generic...).
+    Unknown,
+}
+
+/// Widening order of the numeric types; a value converts without loss to
+/// any type ranked above it.
+pub fn numeric_rank(type_name: &str) -> Option<u8> {
+    Some(match type_name {
+        "byte" | "sbyte" => 0,
+        "short" | "int16" | "ushort" | "uint16" => 1,
+        "integer" | "int32" | "uinteger" | "uint32" => 2,
+        "long" | "int64" | "ulong" | "uint64" => 3,
+        "decimal" => 4,
+        "single" => 5,
+        "double" => 6,
+        _ => return None,
+    })
+}
+
+fn value_numeric_rank(value: &Value) -> Option<u8> {
+    match value {
+        Value::Byte(_) | Value::SByte(_) => Some(0),
+        Value::Short(_) | Value::UShort(_) => Some(1),
+        Value::Integer(_) | Value::UInteger(_) => Some(2),
+        Value::Long(_) | Value::ULong(_) => Some(3),
+        Value::Decimal(_) => Some(4),
+        Value::Single(_) => Some(5),
+        Value::Double(_) => Some(6),
+        _ => None,
+    }
+}
+
+pub fn is_primitive(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Byte(_)
+            | Value::SByte(_)
+            | Value::Char(_)
+            | Value::Short(_)
+            | Value::UShort(_)
+            | Value::Integer(_)
+            | Value::UInteger(_)
+            | Value::Long(_)
+            | Value::ULong(_)
+            | Value::Single(_)
+            | Value::Double(_)
+            | Value::Decimal(_)
+            | Value::Date(_)
+            | Value::String(_)
+            | Value::Boolean(_)
+    )
+}
+
+/// How well `value` fits a parameter of type `ty`; `None` if it can't be
+/// passed at all. Higher is better.
+pub fn score_argument(
+    ty: Option<&VBType>,
+    value: &Value,
+    class_match: &dyn Fn(Option<&str>, &str) -> ClassMatch,
+) -> Option<u8> {
+    let type_name = match ty {
+        None | Some(VBType::Object) | Some(VBType::Variant) => return Some(OBJECT),
+        Some(t) => t.to_string().to_lowercase(),
+    };
+    // `System.String` -> `string`; generic arguments are left alone.
+    let type_name = match type_name.split_once('(') {
+        None => type_name.rsplit('.').next().unwrap_or(&type_name).to_string(),
+        Some(_) => type_name,
+    };
+
+    if let Some(element) = type_name.strip_suffix("()") {
+        return match value {
+            Value::Array(items) => {
+                let element_ty = VBType::from_str(element);
+                match items.first() {
+                    Some(first) if score_argument(Some(&element_ty), first, class_match)? < WIDENING => {
+                        Some(NARROWING)
+                    }
+                    _ => Some(EXACT),
+                }
+            }
+            Value::Nothing => Some(OBJECT),
+            _ => None,
+        };
+    }
+
+    if let Some(param_rank) = numeric_rank(&type_name) {
+        // Integral types of the same size differ in range, so `Short` to
+        // `UShort` is narrowing even though they rank alike
+        if let (Some(from), Some(to)) = (IntType::of(value), IntType::from_name(&type_name)) {
+            let rank = value_numeric_rank(value).unwrap_or(param_rank);
+            return Some(if from == to {
+                EXACT
+            } else if from.widens_to(to) {
+                WIDENING - param_rank.saturating_sub(rank).max(1)
+            } else {
+                NARROWING
+            });
+        }
+        return match value_numeric_rank(value) {
+            Some(rank) if rank == param_rank => Some(EXACT),
+            Some(rank) if rank < param_rank => Some(WIDENING - (param_rank - rank)),
+            Some(_) => Some(NARROWING),
+            None => match value {
+                Value::Boolean(_) | Value::String(_) | Value::Nothing => Some(NARROWING),
+                _ => None,
+            },
+        };
+    }
+
+    match type_name.as_str() {
+        "string" => match value {
+            Value::String(_) => Some(EXACT),
+            Value::Char(_) => Some(WIDENING),
+            Value::Nothing => Some(OBJECT + 10),
+            v if is_primitive(v) => Some(NARROWING),
+            _ => None,
+        },
+        "char" => match value {
+            Value::Char(_) => Some(EXACT),
+            Value::String(_) | Value::Nothing => Some(NARROWING),
+            _ => None,
+        },
+        "boolean" => match value {
+            Value::Boolean(_) => Some(EXACT),
+            Value::String(_) | Value::Nothing => Some(NARROWING),
+            v if value_numeric_rank(v).is_some() => Some(NARROWING),
+            _ => None,
+        },
+        "date" | "datetime" => match value {
+            Value::Date(_) => Some(EXACT),
+            Value::String(_) | Value::Nothing => Some(NARROWING),
+            _ => None,
+        },
+        _ => match value {
+            Value::Object(obj) => match class_match(Some(&obj.borrow().class_name), &type_name) {
+                ClassMatch::Distance(0) => Some(EXACT),
+                ClassMatch::Distance(d) => Some(WIDENING.saturating_sub(d)),
+                ClassMatch::Unrelated => None,
+                ClassMatch::Unknown => Some(OBJECT),
+            },
+            Value::Nothing => Some(OBJECT + 10),
+            // A user class can't hold a primitive; anything else is unchecked.
+            v if is_primitive(v) => match class_match(None, &type_name) {
+                ClassMatch::Unknown => Some(NARROWING),
+                _ => None,
+            },
+            _ => Some(OBJECT),
+        },
+    }
+}
+
+/// Per-candidate data for [`select_best`].
+#[derive(Debug, Clone)]
+pub struct CandidateScore {
+    /// One score per argument.
+    pub args: Vec<u8>,
+    /// The call spreads its trailing arguments into a ParamArray.
+    pub expands_param_array: bool,
+    /// Optional parameters left to their defaults.
+    pub omitted_optionals: usize,
+}
+
+/// Score `values` against `params`; `None` if some argument can't be passed.
+pub fn score_candidate(
+    params: &[Parameter],
+    values: &[Value],
+    class_match: &dyn Fn(Option<&str>, &str) -> ClassMatch,
+) -> Option<CandidateScore> {
+    let param_array = params.last().filter(|p| p.is_param_array);
+    let fixed = if param_array.is_some() { params.len() - 1 } else { params.len() };
+    // Passing an array in the ParamArray position binds it directly.
+    let expands = param_array.is_some()
+        && !(values.len() == params.len() && matches!(values.last(), Some(Value::Array(_)) | Some(Value::Nothing)));
+
+    let mut args = Vec::with_capacity(values.len());
+    for (i, value) in values.iter().enumerate() {
+        let score = if i < fixed || !expands {
+            // More arguments than parameters and no ParamArray to take them
+            score_argument(params.get(i)?.param_type.as_ref(), value, class_match)?
+        } else {
+            let element = param_array
+                .and_then(|p| p.param_type.as_ref())
+                .map(|t| {
+                    let name = t.to_string();
+                    VBType::from_str(name.strip_suffix("()").unwrap_or(&name))
+                });
+            score_argument(element.as_ref(), value, class_match)?
+        };
+        args.push(score);
+    }
+
+    Some(CandidateScore {
+        args,
+        expands_param_array: expands && values.len() >= fixed,
+        omitted_optionals: fixed.saturating_sub(values.len()),
+    })
+}
+
+/// Index of the single best candidate, or every candidate tied for best
+/// (empty if none were applicable).
+pub fn select_best(candidates: &[Option<CandidateScore>]) -> Result<usize, Vec<usize>> {
+    let applicable: Vec<usize> = (0..candidates.len()).filter(|&i| candidates[i].is_some()).collect();
+    let score = |i: usize| candidates[i].as_ref().expect("filtered to applicable candidates");
+
+    // A candidate is better than another when every argument fits at least as
+    // well and at least one fits strictly better.
+    let dominates = |a: &CandidateScore, b: &CandidateScore| {
+        a.args.iter().zip(&b.args).all(|(x, y)| x >= y) && a.args.iter().zip(&b.args).any(|(x, y)| x > y)
+    };
+    let mut best: Vec<usize> = applicable
+        .iter()
+        .copied()
+        .filter(|&i| !applicable.iter().any(|&j| j != i && dominates(score(j), score(i))))
+        .collect();
+
+    // Tie-breakers: prefer not expanding a ParamArray, then fewer omitted Optionals.
+    if best.len() > 1 && best.iter().any(|&i| !score(i).expands_param_array) {
+        best.retain(|&i| !score(i).expands_param_array);
+    }
+    if best.len() > 1 {
+        let fewest = best.iter().map(|&i| score(i).omitted_optionals).min().unwrap_or(0);
+        best.retain(|&i| score(i).omitted_optionals == fewest);
+    }
+
+    match best.as_slice() {
+        [only] => Ok(*only),
+        _ => Err(best),
+    }
+}
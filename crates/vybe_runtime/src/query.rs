@@ -0,0 +1,141 @@
+//! Support for LINQ query expressions and anonymous types.
+//!
+//! A query runs as a pipeline over rows. A row holds the range variables in
+//! scope at that point of the query — `From o In orders, c In customers`
+//! gives rows of `(o, c)`; `Select`, `Group By` and `Into` replace them with
+//! the names they introduce. Each clause maps the list of rows to a new one,
+//! and at the end a row with a single variable yields that value while a
+//! wider row yields an anonymous type. The exception is the start of a query
+//! over a single range: its elements are pulled one at a time through the
+//! `Where`, `Let`, `Select`, `Skip` and `Take` clauses that follow, so
+//! `From n In Naturals() Take 3` stops after three elements of an endless
+//! Iterator Function.
+//!
+//! Anonymous types (`New With {...}`, multi-field `Select`) are objects of
+//! class [`ANONYMOUS_CLASS`]. Their member names, in declaration order, are
+//! kept in the `__fields` field for `ToString`, and two of them are equal
+//! when their members are, so `Distinct`, `Group By` and `Join` can use them
+//! as keys.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::evaluator::values_equal;
+use crate::integral::IntType;
+use crate::value::{ObjectData, RuntimeError, Value};
+
+/// Class name of anonymous type instances.
+pub const ANONYMOUS_CLASS: &str = "AnonymousType";
+
+/// Field holding the member names of an anonymous type, in order.
+const FIELDS_FIELD: &str = "__fields";
+
+/// The range variables in scope for one element of a query.
+pub type Row = Vec<(String, Value)>;
+
+/// A row together with the value it stands for, as aggregates see it.
+pub type Element = (Row, Value);
+
+/// How far a streamed clause has got; see the module docs.
+pub enum Stage {
+    /// `Where`, `Let` and `Select` look at each row on its own.
+    Row,
+    /// Rows still to drop.
+    Skip(usize),
+    /// Rows still to let through.
+    Take(usize),
+    /// Whether `Skip While` is still dropping rows.
+    SkipWhile(bool),
+    TakeWhile,
+}
+
+/// An anonymous type instance with `fields` in order.
+pub fn anonymous_object(fields: Row) -> Value {
+    let mut map = HashMap::new();
+    let names = fields.iter().map(|(name, _)| Value::String(name.clone())).collect();
+    for (name, value) in fields {
+        map.insert(name.to_lowercase(), value);
+    }
+    map.insert(FIELDS_FIELD.to_string(), Value::Array(names));
+    Value::Object(Rc::new(RefCell::new(ObjectData {
+        class_name: ANONYMOUS_CLASS.to_string(),
+        fields: map,
+        drawing_commands: Vec::new(),
+    })))
+}
+
+/// The members of an anonymous type, in declaration order.
+fn anonymous_members(obj: &ObjectData) -> Vec<(String, Value)> {
+    let Some(Value::Array(names)) = obj.fields.get(FIELDS_FIELD) else {
+        return Vec::new();
+    };
+    names.iter().map(|name| {
+        let name = name.as_string();
+        let value = obj.fields.get(&name.to_lowercase()).cloned().unwrap_or(Value::Nothing);
+        (name, value)
+    }).collect()
+}
+
+/// `{ Name = Ann, Age = 3 }`, as VB prints an anonymous type.
+pub fn anonymous_to_string(obj: &ObjectData) -> String {
+    let members: Vec<String> = anonymous_members(obj).iter()
+        .map(|(name, value)| format!("{} = {}", name, value.as_string()))
+        .collect();
+    format!("{{ {} }}", members.join(", "))
+}
+
+/// Two anonymous type instances with equal members.
+pub fn anonymous_equal(a: &ObjectData, b: &ObjectData) -> bool {
+    if a.class_name != ANONYMOUS_CLASS || b.class_name != ANONYMOUS_CLASS {
+        return false;
+    }
+    let (a, b) = (anonymous_members(a), anonymous_members(b));
+    a.len() == b.len() && a.iter().zip(&b).all(|((na, va), (nb, vb))| na.eq_ignore_ascii_case(nb) && values_equal(va, vb))
+}
+
+/// The value a row stands for once the query ends.
+pub fn row_value(row: Row) -> Value {
+    if row.len() == 1 {
+        row.into_iter().next().map(|(_, v)| v).unwrap_or(Value::Nothing)
+    } else {
+        anonymous_object(row)
+    }
+}
+
+/// Rows with equal values, for `Distinct`.
+pub fn rows_equal(a: &Row, b: &Row) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|((_, va), (_, vb))| values_equal(va, vb))
+}
+
+/// Set `name` in `row`, replacing a variable of the same name.
+pub fn set_binding(row: &mut Row, name: &str, value: Value) {
+    match row.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+        Some(entry) => entry.1 = value,
+        None => row.push((name.to_string(), value)),
+    }
+}
+
+/// `Sum` over `values`: Integer while every value is an Integer and the total
+/// fits, Long for Longs and other integral types, Double otherwise.
+pub fn sum_values(values: &[Value]) -> Result<Value, RuntimeError> {
+    let integral = values.iter().all(|v| IntType::of(v).is_some() || matches!(v, Value::Nothing));
+    if integral {
+        let mut total: i64 = 0;
+        for v in values {
+            let n = crate::integral::int_value(v).unwrap_or(0);
+            let n = i64::try_from(n).map_err(|_| crate::integral::overflow())?;
+            total = total.checked_add(n).ok_or_else(crate::integral::overflow)?;
+        }
+        let all_integer = values.iter().all(|v| matches!(v, Value::Integer(_) | Value::Short(_) | Value::UShort(_) | Value::Byte(_) | Value::SByte(_) | Value::Nothing));
+        return Ok(match i32::try_from(total) {
+            Ok(i) if all_integer => Value::Integer(i),
+            _ => Value::Long(total),
+        });
+    }
+    let mut total = 0.0;
+    for v in values {
+        total += v.as_double()?;
+    }
+    Ok(Value::Double(total))
+}
@@ -0,0 +1,279 @@
+//! Records of `Random` and `Binary` files, for the `Get` and `Put` statements.
+//!
+//! A value is written field by field with nothing between fields, all numbers
+//! little-endian. `Get` and `Put` are kept for migrated VB6 code, so the
+//! widths are VB6's rather than .NET's: `Byte` 1 byte, `Integer`, `Short` and
+//! `Boolean` 2, `Long`, `UInteger` and `Single` 4, `ULong`, `Double` and
+//! `Date` 8, `Decimal` 16. An `Integer` outside -32768 to 32767 or a `Long`
+//! outside the 32-bit range can't be written, as it couldn't have been in
+//! VB6: `Put` raises an `OverflowException`. A `String * n`
+//! field takes exactly n characters; a variable-length string a 2-byte length
+//! and then its characters. Characters are one byte each (Latin-1), so a
+//! record written by `Put` has the same size as `Len(record)`.
+
+use vybe_parser::VBType;
+
+use crate::integral::{self, IntType};
+use crate::value::{RuntimeError, Value};
+
+/// How a value is laid out in a record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layout {
+    Int(IntType),
+    Boolean,
+    Single,
+    Double,
+    Decimal,
+    Date,
+    Char,
+    /// `String * n`: exactly n characters, padded with spaces.
+    FixedString(usize),
+    /// A variable-length string: a 2-byte length, then the characters.
+    String,
+    /// An array field with a fixed number of elements.
+    Array(Box<Layout>, usize),
+    /// A user-defined type: its fields in declaration order, by lower-case
+    /// name.
+    Record(Vec<(String, Layout)>),
+}
+
+impl Layout {
+    /// The layout of a field declared `As ty`, for the types that don't need
+    /// the class table. `None` for structures and types that can't be stored
+    /// in a record.
+    pub fn of_type(ty: &VBType) -> Option<Layout> {
+        Some(match ty {
+            VBType::SByte => Layout::Int(IntType::SByte),
+            VBType::Byte => Layout::Int(IntType::Byte),
+            VBType::Short => Layout::Int(IntType::Short),
+            VBType::UShort => Layout::Int(IntType::UShort),
+            VBType::Integer => Layout::Int(IntType::Integer),
+            VBType::UInteger => Layout::Int(IntType::UInteger),
+            VBType::Long => Layout::Int(IntType::Long),
+            VBType::ULong => Layout::Int(IntType::ULong),
+            VBType::Single => Layout::Single,
+            VBType::Double => Layout::Double,
+            VBType::Decimal => Layout::Decimal,
+            VBType::String => Layout::String,
+            VBType::FixedString(len) => Layout::FixedString(*len),
+            VBType::Boolean => Layout::Boolean,
+            VBType::Date => Layout::Date,
+            VBType::Custom(name) if name.eq_ignore_ascii_case("char") => Layout::Char,
+            _ => return None,
+        })
+    }
+
+    /// The layout of a value that isn't a structure instance.
+    pub fn of_value(value: &Value) -> Option<Layout> {
+        Some(match value {
+            Value::Boolean(_) => Layout::Boolean,
+            Value::Single(_) => Layout::Single,
+            Value::Double(_) => Layout::Double,
+            Value::Decimal(_) => Layout::Decimal,
+            Value::Date(_) => Layout::Date,
+            Value::Char(_) => Layout::Char,
+            Value::String(_) => Layout::String,
+            Value::Array(items) => Layout::Array(Box::new(Layout::of_value(items.first()?)?), items.len()),
+            other => Layout::Int(IntType::of(other)?),
+        })
+    }
+
+    /// Bytes taken by a value of this layout; for a variable-length string,
+    /// the bytes of an empty one.
+    pub fn size(&self) -> usize {
+        match self {
+            Layout::Int(ty) => match stored(*ty) {
+                IntType::SByte | IntType::Byte => 1,
+                IntType::Short | IntType::UShort => 2,
+                IntType::Integer | IntType::UInteger => 4,
+                IntType::Long | IntType::ULong => 8,
+            },
+            Layout::Char => 1,
+            Layout::Boolean | Layout::String => 2,
+            Layout::Single => 4,
+            Layout::Double | Layout::Date => 8,
+            Layout::Decimal => 16,
+            Layout::FixedString(len) => *len,
+            Layout::Array(element, count) => element.size() * count,
+            Layout::Record(fields) => fields.iter().map(|(_, layout)| layout.size()).sum(),
+        }
+    }
+}
+
+/// The type an integral field is stored as: VB6's 16-bit `Integer` and
+/// 32-bit `Long`.
+fn stored(ty: IntType) -> IntType {
+    match ty {
+        IntType::Integer => IntType::Short,
+        IntType::Long => IntType::Integer,
+        other => other,
+    }
+}
+
+/// `text` cut or padded with spaces to exactly `len` characters, as a
+/// `String * len` variable holds it.
+pub fn fit(text: &str, len: usize) -> String {
+    let mut fitted: String = text.chars().take(len).collect();
+    let missing = len - fitted.chars().count();
+    fitted.extend(std::iter::repeat_n(' ', missing));
+    fitted
+}
+
+/// `text` right-aligned in `len` characters, as `RSet` stores it.
+pub fn fit_right(text: &str, len: usize) -> String {
+    let count = text.chars().count();
+    if count >= len {
+        return text.chars().take(len).collect();
+    }
+    format!("{}{}", " ".repeat(len - count), text)
+}
+
+fn put_chars(text: &str, out: &mut Vec<u8>) {
+    out.extend(text.chars().map(|c| u8::try_from(u32::from(c)).unwrap_or(b'?')));
+}
+
+fn take<'a>(bytes: &mut &'a [u8], count: usize) -> &'a [u8] {
+    let (taken, rest) = bytes.split_at(count.min(bytes.len()));
+    *bytes = rest;
+    taken
+}
+
+/// The next `N` bytes, zero-filled past the end of the data as VB reads a
+/// record beyond the end of the file.
+fn take_array<const N: usize>(bytes: &mut &[u8]) -> [u8; N] {
+    let mut array = [0u8; N];
+    let taken = take(bytes, N);
+    array[..taken.len()].copy_from_slice(taken);
+    array
+}
+
+fn take_chars(bytes: &mut &[u8], count: usize) -> String {
+    let mut text: String = take(bytes, count).iter().map(|&b| char::from(b)).collect();
+    // Characters past the end of the data read as spaces.
+    text.extend(std::iter::repeat_n(' ', count - text.chars().count()));
+    text
+}
+
+/// Append `value`, laid out as `layout`, to `out`.
+pub fn encode(value: &Value, layout: &Layout, out: &mut Vec<u8>) -> Result<(), RuntimeError> {
+    match layout {
+        Layout::Int(ty) => {
+            let n = integral::int_value(&integral::convert(value, stored(*ty))?).unwrap_or(0);
+            let bytes = n.to_le_bytes();
+            out.extend_from_slice(&bytes[..layout.size()]);
+        }
+        // VB's True is -1
+        Layout::Boolean => out.extend_from_slice(&(if value.as_bool()? { -1i16 } else { 0 }).to_le_bytes()),
+        Layout::Single => out.extend_from_slice(&(value.as_double()? as f32).to_le_bytes()),
+        Layout::Double => out.extend_from_slice(&value.as_double()?.to_le_bytes()),
+        Layout::Date => {
+            let oa = match value {
+                Value::Date(d) => *d,
+                other => other.as_double()?,
+            };
+            out.extend_from_slice(&oa.to_le_bytes());
+        }
+        Layout::Decimal => {
+            let d = crate::decimal::to_decimal(value)?;
+            let digits: String = d.to_fixed(d.scale()).chars().filter(char::is_ascii_digit).collect();
+            let mantissa: u128 = digits.parse().unwrap_or(0);
+            out.extend_from_slice(&mantissa.to_le_bytes()[..12]);
+            let flags = (d.scale() << 16) | if d.is_negative() { 1 << 31 } else { 0 };
+            out.extend_from_slice(&flags.to_le_bytes());
+        }
+        Layout::Char => put_chars(&value.as_char()?.to_string(), out),
+        Layout::FixedString(len) => put_chars(&fit(&value.as_string(), *len), out),
+        Layout::String => {
+            let text = value.as_string();
+            let len = u16::try_from(text.chars().count())
+                .map_err(|_| RuntimeError::Custom("Bad record length".to_string()))?;
+            out.extend_from_slice(&len.to_le_bytes());
+            put_chars(&text, out);
+        }
+        Layout::Array(element, count) => {
+            let items = match value {
+                Value::Array(items) => items.as_slice(),
+                _ => &[],
+            };
+            for i in 0..*count {
+                encode(items.get(i).unwrap_or(&Value::Nothing), element, out)?;
+            }
+        }
+        Layout::Record(fields) => {
+            let Value::Object(obj) = value else {
+                return Err(RuntimeError::Custom("Expected a user-defined type".to_string()));
+            };
+            for (name, field_layout) in fields {
+                let field = obj.borrow().fields.get(name).cloned().unwrap_or(Value::Nothing);
+                encode(&field, field_layout, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read a value laid out as `layout` from the front of `bytes`. A record is
+/// read into a copy of `template`, the variable's current value, so its
+/// fields not in the layout are kept.
+pub fn decode(bytes: &mut &[u8], layout: &Layout, template: &Value) -> Result<Value, RuntimeError> {
+    Ok(match layout {
+        Layout::Int(ty) => {
+            let taken = take(bytes, layout.size());
+            let mut wide = [0u8; 8];
+            wide[..taken.len()].copy_from_slice(taken);
+            let bits = u64::from_le_bytes(wide);
+            ty.make(match stored(*ty) {
+                IntType::SByte => i128::from(bits as u8 as i8),
+                IntType::Short => i128::from(bits as u16 as i16),
+                IntType::Integer => i128::from(bits as u32 as i32),
+                IntType::Long => i128::from(bits as i64),
+                _ => i128::from(bits),
+            })?
+        }
+        Layout::Boolean => Value::Boolean(i16::from_le_bytes(take_array(bytes)) != 0),
+        Layout::Single => Value::Single(f32::from_le_bytes(take_array(bytes))),
+        Layout::Double => Value::Double(f64::from_le_bytes(take_array(bytes))),
+        Layout::Date => Value::Date(f64::from_le_bytes(take_array(bytes))),
+        Layout::Decimal => {
+            let raw: [u8; 16] = take_array(bytes);
+            let mut mantissa = [0u8; 16];
+            mantissa[..12].copy_from_slice(&raw[..12]);
+            let flags = u32::from_le_bytes([raw[12], raw[13], raw[14], raw[15]]);
+            let scale = ((flags >> 16) & 0xFF) as usize;
+            let digits = format!("{:0>width$}", u128::from_le_bytes(mantissa), width = scale + 1);
+            let (whole, fraction) = digits.split_at(digits.len() - scale);
+            let sign = if flags >> 31 == 1 { "-" } else { "" };
+            let text = if scale == 0 { format!("{}{}", sign, whole) } else { format!("{}{}.{}", sign, whole, fraction) };
+            Value::Decimal(crate::decimal::Decimal::parse(&text).unwrap_or(crate::decimal::Decimal::ZERO))
+        }
+        Layout::Char => Value::Char(char::from(take_array::<1>(bytes)[0])),
+        Layout::FixedString(len) => Value::String(take_chars(bytes, *len)),
+        Layout::String => {
+            let len = u16::from_le_bytes(take_array(bytes));
+            Value::String(take(bytes, len as usize).iter().map(|&b| char::from(b)).collect())
+        }
+        Layout::Array(element, count) => {
+            let items = match template {
+                Value::Array(items) => items.clone(),
+                _ => Vec::new(),
+            };
+            let mut decoded = Vec::with_capacity(*count);
+            for i in 0..*count {
+                decoded.push(decode(bytes, element, items.get(i).unwrap_or(&Value::Nothing))?);
+            }
+            Value::Array(decoded)
+        }
+        Layout::Record(fields) => {
+            let record = crate::structures::copy_value(template.clone());
+            let Value::Object(obj) = &record else {
+                return Err(RuntimeError::Custom("Expected a user-defined type".to_string()));
+            };
+            for (name, field_layout) in fields {
+                let current = obj.borrow().fields.get(name).cloned().unwrap_or(Value::Nothing);
+                let value = decode(bytes, field_layout, &current)?;
+                obj.borrow_mut().fields.insert(name.clone(), value);
+            }
+            record
+        }
+    })
+}
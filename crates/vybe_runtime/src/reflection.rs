@@ -0,0 +1,94 @@
+//! Attributes at runtime, read through the reflection objects of a type.
+//!
+//! `GetType(Customer)` is a `Type` object. `GetProperty`, `GetMethod` and
+//! `GetField` on it give a member object of class `PropertyInfo`,
+//! `MethodInfo` or `FieldInfo` that names the class ([`OWNER_FIELD`]) and
+//! the member. `GetCustomAttributes` and `IsDefined` on either kind read the
+//! attributes written on the declaration: instances of the program's own
+//! attribute classes, or for framework attributes such as `<Obsolete>` an
+//! object of class `ObsoleteAttribute` carrying its arguments as properties.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::value::{ObjectData, Value};
+
+/// Class name of `GetType(...)` results.
+pub const TYPE_CLASS: &str = "Type";
+
+/// Class names of the member objects, in `GetProperty`, `GetMethod`,
+/// `GetField` order.
+pub const MEMBER_CLASSES: [&str; 3] = ["PropertyInfo", "MethodInfo", "FieldInfo"];
+
+/// Field of a member object naming the class declaring the member.
+pub const OWNER_FIELD: &str = "__owner";
+
+/// Whether `obj` is a `Type` or member object.
+pub fn is_reflection_object(obj: &ObjectData) -> bool {
+    obj.class_name == TYPE_CLASS || MEMBER_CLASSES.contains(&obj.class_name.as_str())
+}
+
+/// The member `name` of `owner`, as an object of `class` (one of
+/// [`MEMBER_CLASSES`]).
+pub fn member_object(class: &str, owner: &str, name: &str) -> Value {
+    let mut fields = HashMap::new();
+    fields.insert("name".to_string(), Value::String(name.to_string()));
+    fields.insert(OWNER_FIELD.to_string(), Value::String(owner.to_string()));
+    Value::Object(Rc::new(RefCell::new(ObjectData {
+        class_name: class.to_string(),
+        fields,
+        drawing_commands: Vec::new(),
+    })))
+}
+
+/// An attribute type name without namespace or `Attribute` suffix, in lower
+/// case: `<Obsolete>` and `GetType(System.ObsoleteAttribute)` both give
+/// "obsolete".
+pub fn attribute_key(name: &str) -> String {
+    let last = name.rsplit('.').next().unwrap_or(name).to_lowercase();
+    match last.strip_suffix("attribute") {
+        Some(short) if !short.is_empty() => short.to_string(),
+        _ => last,
+    }
+}
+
+/// Properties set by the positional arguments of the framework attributes
+/// the interpreter knows.
+fn positional_properties(key: &str) -> &'static [&'static str] {
+    match key {
+        "obsolete" => &["message", "iserror"],
+        "defaultvalue" => &["value"],
+        "category" => &["category"],
+        "description" => &["description"],
+        "displayname" => &["displayname"],
+        "browsable" => &["browsable"],
+        "dllimport" => &["value"],
+        "testcategory" => &["testcategories"],
+        _ => &[],
+    }
+}
+
+/// A stand-in for the framework attribute `short_name` (as written, without
+/// the `Attribute` suffix). Named arguments become properties of the same
+/// name; positional arguments the properties the real attribute's
+/// constructor sets.
+pub fn framework_attribute(short_name: &str, positional: Vec<Value>, named: Vec<(String, Value)>) -> Value {
+    let key = attribute_key(short_name);
+    let mut fields = HashMap::new();
+    if key == "obsolete" {
+        fields.insert("message".to_string(), Value::Nothing);
+        fields.insert("iserror".to_string(), Value::Boolean(false));
+    }
+    for (property, value) in positional_properties(&key).iter().zip(positional) {
+        fields.insert(property.to_string(), value);
+    }
+    for (name, value) in named {
+        fields.insert(name.to_lowercase(), value);
+    }
+    Value::Object(Rc::new(RefCell::new(ObjectData {
+        class_name: format!("{}Attribute", short_name),
+        fields,
+        drawing_commands: Vec::new(),
+    })))
+}
@@ -0,0 +1,280 @@
+//! What a script may reach outside the interpreter.
+//!
+//! A host running untrusted code gives the interpreter a [`SandboxPolicy`]
+//! with [`Interpreter::set_sandbox`](crate::Interpreter::set_sandbox). Each
+//! builtin that reads or writes files, opens sockets, makes HTTP requests,
+//! starts processes, reads environment variables, connects to a database or
+//! shows a dialog checks the policy first; a denied call throws
+//! `UnauthorizedAccessException`, which the script can catch like any other.
+//! File access can be limited to a set of directory roots.
+
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use crate::value::{RuntimeError, Value};
+
+/// Something outside the interpreter a script can reach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Files,
+    Sockets,
+    Http,
+    Processes,
+    Environment,
+    Databases,
+    Dialogs,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Capability::Files => "the file system",
+            Capability::Sockets => "network sockets",
+            Capability::Http => "HTTP",
+            Capability::Processes => "starting processes",
+            Capability::Environment => "environment variables",
+            Capability::Databases => "database connections",
+            Capability::Dialogs => "dialogs",
+        })
+    }
+}
+
+/// Which files a script may touch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum FileAccess {
+    #[default]
+    Any,
+    Denied,
+    /// Only paths inside these directories, held as absolute paths.
+    Within(Vec<PathBuf>),
+}
+
+/// The capabilities a script is given. The default allows everything.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxPolicy {
+    pub files: FileAccess,
+    pub sockets: bool,
+    pub http: bool,
+    pub processes: bool,
+    pub environment: bool,
+    pub databases: bool,
+    pub dialogs: bool,
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        SandboxPolicy {
+            files: FileAccess::Any,
+            sockets: true,
+            http: true,
+            processes: true,
+            environment: true,
+            databases: true,
+            dialogs: true,
+        }
+    }
+}
+
+impl SandboxPolicy {
+    /// A policy that allows nothing; grant capabilities with [`allow`](Self::allow).
+    pub fn deny_all() -> Self {
+        SandboxPolicy {
+            files: FileAccess::Denied,
+            sockets: false,
+            http: false,
+            processes: false,
+            environment: false,
+            databases: false,
+            dialogs: false,
+        }
+    }
+
+    /// Allow or deny `capability`. Allowing files lifts any directory roots.
+    pub fn allow(&mut self, capability: Capability, allowed: bool) -> &mut Self {
+        match capability {
+            Capability::Files => self.files = if allowed { FileAccess::Any } else { FileAccess::Denied },
+            Capability::Sockets => self.sockets = allowed,
+            Capability::Http => self.http = allowed,
+            Capability::Processes => self.processes = allowed,
+            Capability::Environment => self.environment = allowed,
+            Capability::Databases => self.databases = allowed,
+            Capability::Dialogs => self.dialogs = allowed,
+        }
+        self
+    }
+
+    /// Allow files inside `root` (resolved against the current directory),
+    /// and no others beyond the roots already allowed.
+    pub fn allow_files_within(&mut self, root: impl AsRef<Path>) -> &mut Self {
+        let root = resolve(root.as_ref());
+        match &mut self.files {
+            FileAccess::Within(roots) => roots.push(root),
+            files => *files = FileAccess::Within(vec![root]),
+        }
+        self
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Files => self.files != FileAccess::Denied,
+            Capability::Sockets => self.sockets,
+            Capability::Http => self.http,
+            Capability::Processes => self.processes,
+            Capability::Environment => self.environment,
+            Capability::Databases => self.databases,
+            Capability::Dialogs => self.dialogs,
+        }
+    }
+
+    pub fn check(&self, capability: Capability) -> Result<(), RuntimeError> {
+        if self.allows(capability) {
+            Ok(())
+        } else {
+            Err(denied(format!("Access to {} is denied by the sandbox policy.", capability)))
+        }
+    }
+
+    /// Check that the script may touch the file or directory at `path`.
+    pub fn check_path(&self, path: &str) -> Result<(), RuntimeError> {
+        let allowed = match &self.files {
+            FileAccess::Any => true,
+            FileAccess::Denied => false,
+            FileAccess::Within(roots) => {
+                let path = resolve(Path::new(path));
+                roots.iter().any(|root| path.starts_with(root))
+            }
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(denied(format!("Access to the path '{}' is denied.", path)))
+        }
+    }
+
+    /// Check the string arguments at `indices` as paths.
+    pub fn check_paths(&self, args: &[Value], indices: &[usize]) -> Result<(), RuntimeError> {
+        for &i in indices {
+            if let Some(Value::String(path)) = args.get(i) {
+                self.check_path(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a call to the builtin `name` (lowercase, possibly qualified,
+    /// like `kill` or `system.io.file.readalltext`).
+    pub fn check_call(&self, name: &str, args: &[Value]) -> Result<(), RuntimeError> {
+        match builtin_access(name) {
+            Some(Access::Paths(indices)) => {
+                self.check_paths(args, indices)?;
+                self.check(Capability::Files)
+            }
+            Some(Access::To(capability)) => self.check(capability),
+            None => Ok(()),
+        }
+    }
+
+    /// Check creating a built-in object of class `class_name` (lowercase).
+    pub fn check_new(&self, class_name: &str) -> Result<(), RuntimeError> {
+        match class_capability(class_name) {
+            Some(capability) => self.check(capability),
+            None => Ok(()),
+        }
+    }
+}
+
+fn denied(message: String) -> RuntimeError {
+    RuntimeError::Exception("UnauthorizedAccessException".to_string(), message, None)
+}
+
+/// What a builtin call needs.
+enum Access {
+    To(Capability),
+    /// File access, with paths in the arguments at these positions.
+    Paths(&'static [usize]),
+}
+
+/// Namespaces a builtin may be called through; longer prefixes come first.
+const NAMESPACES: &[&str] = &[
+    "system.io.compression.",
+    "system.io.",
+    "system.diagnostics.",
+    "system.net.",
+    "system.windows.forms.",
+    "system.xml.linq.",
+    "microsoft.visualbasic.",
+    "interaction.",
+    "filesystem.",
+];
+
+fn builtin_access(name: &str) -> Option<Access> {
+    let mut name = name;
+    for namespace in NAMESPACES {
+        if let Some(rest) = name.strip_prefix(namespace) {
+            name = rest;
+        }
+    }
+    let access = match name {
+        "dir" | "dir$" | "kill" | "getattr" | "setattr" | "filedatetime" | "filelen" | "chdir" | "mkdir"
+        | "rmdir" | "fileexists" | "open" | "loadpicture" | "xdocument.load" | "xml.load" | "xelement.load"
+        | "zipfile.open" | "zipfile.openread" => Access::Paths(&[0]),
+        "filecopy" | "name" | "zipfile.createfromdirectory" | "zipfile.extracttodirectory" => Access::Paths(&[0, 1]),
+        "savepicture" => Access::Paths(&[1]),
+        "curdir" | "curdir$" => Access::Paths(&[]),
+        "shell" | "process.start" => Access::To(Capability::Processes),
+        "environ" | "environ$" | "environment.getenvironmentvariable" | "environment.setenvironmentvariable"
+        | "environment.getenvironmentvariables" | "environment.expandenvironmentvariables" => {
+            Access::To(Capability::Environment)
+        }
+        "webrequest.create" | "httpwebrequest.create" => Access::To(Capability::Http),
+        "msgbox" | "inputbox" | "messagebox.show" => Access::To(Capability::Dialogs),
+        _ => {
+            let (class, method) = name.split_once('.')?;
+            match (class, method) {
+                ("file" | "directory", "copy" | "move") => Access::Paths(&[0, 1]),
+                ("file", "replace") => Access::Paths(&[0, 1, 2]),
+                ("file" | "directory", _) => Access::Paths(&[0]),
+                ("dns", _) => Access::To(Capability::Sockets),
+                _ => return None,
+            }
+        }
+    };
+    Some(access)
+}
+
+fn class_capability(class_name: &str) -> Option<Capability> {
+    let class = class_name.rsplit('.').next().unwrap_or(class_name);
+    match class {
+        "openfiledialog" | "savefiledialog" | "colordialog" | "fontdialog" | "folderbrowserdialog" | "printdialog"
+        | "printpreviewdialog" | "pagesetupdialog" => Some(Capability::Dialogs),
+        "tcpclient" | "tcplistener" | "udpclient" | "smtpclient" => Some(Capability::Sockets),
+        "webclient" | "httpclient" => Some(Capability::Http),
+        _ => None,
+    }
+}
+
+/// `path` made absolute and free of `.` and `..`, the way the OS would
+/// open it: each component that exists is canonicalized before the next is
+/// applied, so a `..` after a symlink climbs out of the link's target, not
+/// out of the link.
+fn resolve(path: &Path) -> PathBuf {
+    let absolute = std::env::current_dir().map(|cwd| cwd.join(path)).unwrap_or_else(|_| path.to_path_buf());
+    let mut resolved = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => {
+                resolved.push(name);
+                // Past the part that exists, components are kept as written
+                if let Ok(real) = resolved.canonicalize() {
+                    resolved = real;
+                }
+            }
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
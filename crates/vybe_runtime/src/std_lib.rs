@@ -47,18 +47,8 @@ pub fn call_builtin(name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
         }
         
         // Math Functions
-        "abs" => {
-            let val = args.get(0).ok_or(RuntimeError::Custom("Argument required".to_string()))?;
-            match val {
-                Value::Integer(i) => Ok(Value::Integer(i.abs())),
-                Value::Double(d) => Ok(Value::Double(d.abs())),
-                _ => Ok(Value::Double(val.as_double()?.abs()))
-            }
-        }
-        "round" => {
-            let d = args.get(0).ok_or(RuntimeError::Custom("Argument required".to_string()))?.as_double()?;
-            Ok(Value::Double(d.round()))
-        }
+        "abs" => crate::builtins::abs_fn(args),
+        "round" => crate::builtins::round_fn(args),
         
         // Type checking functions
         "isnull" => {
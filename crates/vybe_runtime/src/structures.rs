@@ -0,0 +1,49 @@
+//! Value-type semantics for `Structure`.
+//!
+//! A structure instance is an object like a class instance, marked with the
+//! [`VALUE_TYPE_FIELD`] field. Instances are copied where VB copies a value
+//! type: when stored in a variable, field, array element or collection
+//! (`Add`, `Insert`, `Enqueue`, `Push`, an indexer), when passed ByVal and
+//! when bound to a `For Each` variable. Reading an array element
+//! or field does not copy, so `points(0).X = 5` changes the element in
+//! place. Fields of structure type are copied along with the instance;
+//! arrays and class instances held in fields stay shared, as references do.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::value::{ObjectData, Value};
+
+/// Field marking an object as a structure instance.
+pub const VALUE_TYPE_FIELD: &str = "__valuetype";
+
+pub fn is_structure(obj: &ObjectData) -> bool {
+    obj.fields.contains_key(VALUE_TYPE_FIELD)
+}
+
+/// `value` as stored into a new location: a fresh copy of a structure or
+/// tuple, anything else unchanged.
+pub fn copy_value(value: Value) -> Value {
+    match &value {
+        Value::Object(obj) if is_structure(&obj.borrow()) => {
+            let obj = obj.borrow();
+            let fields = obj.fields.iter().map(|(name, v)| (name.clone(), copy_value(v.clone()))).collect();
+            Value::Object(Rc::new(RefCell::new(ObjectData {
+                class_name: obj.class_name.clone(),
+                fields,
+                drawing_commands: obj.drawing_commands.clone(),
+            })))
+        }
+        Value::Object(obj) if crate::tuples::is_tuple(&obj.borrow()) => crate::tuples::copy(value),
+        _ => value,
+    }
+}
+
+/// An array with every structure element a separate copy, for arrays built
+/// by repeating one default element.
+pub fn copy_elements(value: Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(copy_elements).collect()),
+        other => copy_value(other),
+    }
+}
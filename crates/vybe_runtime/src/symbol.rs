@@ -0,0 +1,127 @@
+//! Interned, case-insensitive names.
+//!
+//! VB names ignore case, so every variable lookup used to lowercase the name
+//! into a fresh `String` and hash it. A [`Symbol`] is a small number standing
+//! for the lowercase form of a name: interning `Total` and `TOTAL` gives the
+//! same symbol, and after the first time a name is seen interning it again
+//! doesn't allocate. Symbols belong to the thread that interned them, like
+//! the interpreter's values.
+//!
+//! Only locals and parameters go further than that. Both engines resolve a
+//! variable to a [`Slot`](crate::environment::Slot) the first time it is used and
+//! keep it until the environment's epoch changes: the bytecode VM per variable
+//! it compiled, the tree walker per place a name is written (see
+//! [`Resolutions`](crate::environment::Resolutions)). Object fields, members and
+//! procedures are still keyed by their lowercase name, and are looked up with
+//! the name a symbol shares rather than resolved ahead of time; giving fields
+//! slots too needs `ObjectData` laid out per class, which is a step of its own.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// A name interned by [`Symbol::intern`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol {
+    id: u32,
+    // Ids are numbered per thread.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Symbol {
+    /// The symbol for `name`, in any case.
+    pub fn intern(name: &str) -> Symbol {
+        INTERNER.with(|interner| interner.borrow_mut().intern(name))
+    }
+
+    /// The lowercase form of the name.
+    pub fn name(self) -> Rc<str> {
+        INTERNER.with(|interner| interner.borrow().names[self.id as usize].clone())
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// A quick hasher for symbols, interned names and source spans; the default
+/// SipHash is most of the cost of a lookup keyed by a small number.
+#[derive(Default)]
+pub struct NameHasher(u64);
+
+impl Hasher for NameHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        // FNV-1a
+        let mut hash = if self.0 == 0 { 0xcbf2_9ce4_8422_2325 } else { self.0 };
+        for &b in bytes {
+            hash = (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+        }
+        self.0 = hash;
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        // Spread sequential ids over the high bits, which the table uses too
+        self.0 = (self.0 ^ u64::from(n)).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        // Source positions, hashed field by field
+        self.0 = (self.0.rotate_left(5) ^ n as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// A map keyed by symbols.
+pub type SymbolMap<V> = HashMap<Symbol, V, BuildHasherDefault<NameHasher>>;
+
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<Rc<str>, u32, BuildHasherDefault<NameHasher>>,
+    /// Each name once, indexed by id and shared with `ids`.
+    names: Vec<Rc<str>>,
+    /// Reused for lowercasing a name before looking it up.
+    scratch: String,
+}
+
+impl Interner {
+    fn intern(&mut self, name: &str) -> Symbol {
+        let lower = if !name.is_ascii() {
+            // Unicode lowercasing depends on context; keep `to_lowercase`'s rules
+            name.to_lowercase()
+        } else if name.bytes().any(|b| b.is_ascii_uppercase()) {
+            self.scratch.clear();
+            self.scratch.extend(name.chars().map(|c| c.to_ascii_lowercase()));
+            std::mem::take(&mut self.scratch)
+        } else {
+            return self.lookup(name);
+        };
+        let symbol = self.lookup(&lower);
+        self.scratch = lower;
+        symbol
+    }
+
+    fn lookup(&mut self, lower: &str) -> Symbol {
+        let id = match self.ids.get(lower) {
+            Some(&id) => id,
+            None => {
+                let name: Rc<str> = Rc::from(lower);
+                let id = u32::try_from(self.names.len()).expect("too many names");
+                self.names.push(name.clone());
+                self.ids.insert(name, id);
+                id
+            }
+        };
+        Symbol { id, _thread: PhantomData }
+    }
+}
+
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::new(Interner::default());
+}
@@ -0,0 +1,175 @@
+//! VB tuples: `(Name:="Ann", Age:=30)` literals and `(Min As Integer, Max As
+//! Integer)` types.
+//!
+//! A tuple is an object of class [`TUPLE_CLASS`]. Every element is stored as
+//! `item1`, `item2`, …, and a named element is also stored under its name;
+//! `__names` keeps the name of each position (empty when unnamed) so that an
+//! assignment through either key updates both. Names are not part of a
+//! tuple's identity: two tuples are equal when their elements are, which is
+//! what lets them serve as Dictionary keys.
+//!
+//! Tuples are values. Declaring or assigning a tuple variable stores a copy,
+//! renamed and converted to the declared tuple type if there is one.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use vybe_parser::VBType;
+
+use crate::evaluator::values_equal;
+use crate::value::{ObjectData, RuntimeError, Value};
+
+/// Class name of tuple instances.
+pub const TUPLE_CLASS: &str = "ValueTuple";
+
+/// Field holding the element names of a tuple, by position.
+const NAMES_FIELD: &str = "__names";
+
+/// A tuple of `elements`, each with an optional name.
+pub fn tuple_object(elements: Vec<(Option<String>, Value)>) -> Value {
+    let mut fields = HashMap::new();
+    let mut names = Vec::with_capacity(elements.len());
+    for (i, (name, value)) in elements.into_iter().enumerate() {
+        if let Some(name) = &name {
+            fields.insert(name.to_lowercase(), value.clone());
+        }
+        fields.insert(format!("item{}", i + 1), value);
+        names.push(Value::String(name.unwrap_or_default()));
+    }
+    fields.insert(NAMES_FIELD.to_string(), Value::Array(names));
+    Value::Object(Rc::new(RefCell::new(ObjectData {
+        class_name: TUPLE_CLASS.to_string(),
+        fields,
+        drawing_commands: Vec::new(),
+    })))
+}
+
+pub fn is_tuple(obj: &ObjectData) -> bool {
+    obj.class_name == TUPLE_CLASS
+}
+
+/// The element names of a tuple, by position; unnamed elements are empty.
+fn names(obj: &ObjectData) -> Vec<String> {
+    match obj.fields.get(NAMES_FIELD) {
+        Some(Value::Array(names)) => names.iter().map(|n| n.as_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The elements of a tuple, in order.
+pub fn elements(obj: &ObjectData) -> Vec<Value> {
+    (1..=names(obj).len())
+        .map(|i| obj.fields.get(&format!("item{}", i)).cloned().unwrap_or(Value::Nothing))
+        .collect()
+}
+
+/// `(Ann, 30)`, as VB prints a tuple.
+pub fn tuple_to_string(obj: &ObjectData) -> String {
+    let parts: Vec<String> = elements(obj).iter().map(|v| v.as_string()).collect();
+    format!("({})", parts.join(", "))
+}
+
+/// Two tuples with the same number of equal elements, whatever their names.
+pub fn tuple_equal(a: &ObjectData, b: &ObjectData) -> bool {
+    if !is_tuple(a) || !is_tuple(b) {
+        return false;
+    }
+    let (a, b) = (elements(a), elements(b));
+    a.len() == b.len() && a.iter().zip(&b).all(|(x, y)| values_equal(x, y))
+}
+
+/// Assign the element called `member` — a name or `ItemN` — keeping its
+/// other key in step. Returns false when the tuple has no such element.
+pub fn set_element(obj: &mut ObjectData, member: &str, value: Value) -> bool {
+    let names = names(obj);
+    let member = member.to_lowercase();
+    let position = match member.strip_prefix("item").and_then(|n| n.parse::<usize>().ok()) {
+        Some(n) if (1..=names.len()).contains(&n) => n - 1,
+        _ => match names.iter().position(|n| !n.is_empty() && n.eq_ignore_ascii_case(&member)) {
+            Some(i) => i,
+            None => return false,
+        },
+    };
+    if !names[position].is_empty() {
+        obj.fields.insert(names[position].to_lowercase(), value.clone());
+    }
+    obj.fields.insert(format!("item{}", position + 1), value);
+    true
+}
+
+/// A fresh copy of a tuple value, nested tuples included; other values as is.
+pub fn copy(value: Value) -> Value {
+    match &value {
+        Value::Object(obj) if is_tuple(&obj.borrow()) => {
+            let obj = obj.borrow();
+            let elements = names(&obj).into_iter().zip(elements(&obj))
+                .map(|(name, v)| ((!name.is_empty()).then_some(name), crate::structures::copy_value(v)))
+                .collect();
+            tuple_object(elements)
+        }
+        _ => value,
+    }
+}
+
+/// The elements of a tuple type such as `(Min As Integer, Max As Integer)`
+/// or `(String, Integer)`, or None if `text` is not a tuple type.
+pub fn parse_type(text: &str) -> Option<Vec<(Option<String>, VBType)>> {
+    let inner = text.trim().strip_prefix('(')?.strip_suffix(')')?;
+    let elements: Vec<_> = split_top_level(inner).into_iter().map(|part| {
+        // `Name As Type`, unless the As belongs to a nested tuple type
+        let lower = part.to_lowercase();
+        match lower.find(" as ") {
+            Some(at) if !part[..at].contains('(') => {
+                (Some(part[..at].trim().to_string()), VBType::from_str(&part[at + 4..]))
+            }
+            _ => (None, VBType::from_str(part)),
+        }
+    }).collect();
+    (elements.len() > 1).then_some(elements)
+}
+
+/// Split `text` at the commas outside parentheses.
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+/// A copy of `value` as the tuple type `elements`: the declared names
+/// replace the tuple's own, and each element goes through `convert` for its
+/// declared type. Non-tuples and tuples of another arity pass through.
+pub fn convert(
+    value: Value,
+    elements: &[(Option<String>, VBType)],
+    convert: impl Fn(&VBType, Value) -> Result<Value, RuntimeError>,
+) -> Result<Value, RuntimeError> {
+    let values = match &value {
+        Value::Object(obj) if is_tuple(&obj.borrow()) => self::elements(&obj.borrow()),
+        _ => return Ok(value),
+    };
+    if values.len() != elements.len() {
+        return Ok(copy(value));
+    }
+    let converted = elements.iter().zip(values)
+        .map(|((name, ty), v)| Ok((name.clone(), convert(ty, crate::structures::copy_value(v))?)))
+        .collect::<Result<Vec<_>, RuntimeError>>()?;
+    Ok(tuple_object(converted))
+}
+
+/// The default value of the tuple type `elements`: each element's default.
+pub fn default_value(elements: &[(Option<String>, VBType)], default_of: impl Fn(&VBType) -> Value) -> Value {
+    tuple_object(elements.iter().map(|(name, ty)| (name.clone(), default_of(ty))).collect())
+}
@@ -19,6 +19,18 @@ pub struct ObjectData {
     pub drawing_commands: Vec<DrawingCommand>,
 }
 
+impl ObjectData {
+    /// Set the field `name` (lowercase), allocating a key only for a new field.
+    pub fn set_field(&mut self, name: &str, value: Value) {
+        match self.fields.get_mut(name) {
+            Some(field) => *field = value,
+            None => {
+                self.fields.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SharedObjectData {
     pub class_name: String,
@@ -35,11 +47,17 @@ pub struct SharedEnvironment {
 #[derive(Debug, Clone)]
 pub enum SharedValue {
     Byte(u8),
+    SByte(i8),
     Char(char),
+    Short(i16),
+    UShort(u16),
     Integer(i32),
+    UInteger(u32),
     Long(i64),
+    ULong(u64),
     Single(f32),
     Double(f64),
+    Decimal(crate::decimal::Decimal),
     Date(f64),
     String(String),
     Boolean(bool),
@@ -59,11 +77,17 @@ pub enum SharedValue {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Byte(u8),
+    SByte(i8),
     Char(char),
+    Short(i16),
+    UShort(u16),
     Integer(i32),
+    UInteger(u32),
     Long(i64),
+    ULong(u64),
     Single(f32),
     Double(f64),
+    Decimal(crate::decimal::Decimal),
     Date(f64),
     String(String),
     Boolean(bool),
@@ -92,7 +116,13 @@ impl Value {
             Value::Long(l) => Ok(*l as i32),
             Value::Single(f) => Ok(*f as i32),
             Value::Double(d) => Ok(*d as i32),
+            Value::Decimal(d) => Ok(d.to_i64().unwrap_or(0) as i32),
             Value::Byte(b) => Ok(*b as i32),
+            Value::SByte(n) => Ok(*n as i32),
+            Value::Short(n) => Ok(*n as i32),
+            Value::UShort(n) => Ok(*n as i32),
+            Value::UInteger(n) => Ok(*n as i32),
+            Value::ULong(n) => Ok(*n as i32),
             Value::Char(c) => Ok(*c as i32),
             Value::Date(d) => Ok(*d as i32),
             Value::String(s) => {
@@ -128,7 +158,13 @@ impl Value {
             Value::Long(l) => Ok(*l),
             Value::Single(f) => Ok(*f as i64),
             Value::Double(d) => Ok(*d as i64),
+            Value::Decimal(d) => d.to_i64().ok_or_else(crate::decimal::overflow),
             Value::Byte(b) => Ok(*b as i64),
+            Value::SByte(n) => Ok(*n as i64),
+            Value::Short(n) => Ok(*n as i64),
+            Value::UShort(n) => Ok(*n as i64),
+            Value::UInteger(n) => Ok(*n as i64),
+            Value::ULong(n) => i64::try_from(*n).map_err(|_| crate::integral::overflow()),
             Value::Char(c) => Ok(*c as i64),
             Value::Date(d) => Ok(*d as i64),
             Value::String(s) => {
@@ -162,8 +198,16 @@ impl Value {
         match self {
             Value::Integer(i) => Ok(*i as f64),
             Value::Long(l) => Ok(*l as f64),
+            Value::Byte(b) => Ok(*b as f64),
+            Value::SByte(n) => Ok(*n as f64),
+            Value::Short(n) => Ok(*n as f64),
+            Value::UShort(n) => Ok(*n as f64),
+            Value::UInteger(n) => Ok(*n as f64),
+            Value::ULong(n) => Ok(*n as f64),
+            Value::Boolean(b) => Ok(if *b { -1.0 } else { 0.0 }),
             Value::Single(f) => Ok(*f as f64),
             Value::Double(d) => Ok(*d),
+            Value::Decimal(d) => Ok(d.to_f64()),
             Value::Date(d) => Ok(*d),
             Value::String(s) => s.parse().map_err(|_| RuntimeError::TypeError {
                 expected: "Double".to_string(),
@@ -182,9 +226,15 @@ impl Value {
             Value::Integer(i) => i.to_string(),
             Value::Long(l) => l.to_string(),
             Value::Byte(b) => b.to_string(),
+            Value::SByte(n) => n.to_string(),
+            Value::Short(n) => n.to_string(),
+            Value::UShort(n) => n.to_string(),
+            Value::UInteger(n) => n.to_string(),
+            Value::ULong(n) => n.to_string(),
             Value::Char(c) => c.to_string(),
             Value::Single(f) => f.to_string(),
             Value::Double(d) => d.to_string(),
+            Value::Decimal(d) => d.to_string(),
             Value::Date(d) => {
                 // OLE Automation Date: Days since Dec 30 1899
                 let base_date = NaiveDate::from_ymd_opt(1899, 12, 30).unwrap().and_hms_opt(0, 0, 0).unwrap();
@@ -217,6 +267,12 @@ impl Value {
                 if b.class_name == "StringBuilder" {
                     return b.fields.get("__data").map(|v| v.as_string()).unwrap_or_default();
                 }
+                if b.class_name == crate::query::ANONYMOUS_CLASS {
+                    return crate::query::anonymous_to_string(&b);
+                }
+                if crate::tuples::is_tuple(&b) {
+                    return crate::tuples::tuple_to_string(&b);
+                }
                 format!("[Object {}]", b.class_name)
             }
             Value::Lambda { .. } => "[Lambda]".to_string(),
@@ -229,9 +285,15 @@ impl Value {
             Value::Integer(i) => Ok(*i != 0),
             Value::Long(l) => Ok(*l != 0),
             Value::Byte(b) => Ok(*b != 0),
+            Value::SByte(n) => Ok(*n != 0),
+            Value::Short(n) => Ok(*n != 0),
+            Value::UShort(n) => Ok(*n != 0),
+            Value::UInteger(n) => Ok(*n != 0),
+            Value::ULong(n) => Ok(*n != 0),
             Value::Char(_) => Err(RuntimeError::TypeError { expected: "Boolean".to_string(), got: "Char".to_string() }),
             Value::Single(f) => Ok(*f != 0.0),
             Value::Double(d) => Ok(*d != 0.0),
+            Value::Decimal(d) => Ok(!d.is_zero()),
             Value::Date(d) => Ok(*d != 0.0),
             Value::String(s) => {
                  let lower = s.to_lowercase();
@@ -258,10 +320,15 @@ impl Value {
     pub fn as_byte(&self) -> Result<u8, RuntimeError> {
         match self {
              Value::Byte(b) => Ok(*b),
+             Value::SByte(_) | Value::Short(_) | Value::UShort(_) | Value::UInteger(_) | Value::ULong(_) => {
+                 let i = self.as_long()?;
+                 u8::try_from(i).map_err(|_| RuntimeError::Custom(format!("Overflow: {} to Byte", i)))
+             },
              Value::Integer(i) => if *i >= 0 && *i <= 255 { Ok(*i as u8) } else { Err(RuntimeError::Custom(format!("Overflow: {} to Byte", i))) },
              Value::Long(l) => if *l >= 0 && *l <= 255 { Ok(*l as u8) } else { Err(RuntimeError::Custom(format!("Overflow: {} to Byte", l))) },
              Value::Single(f) => if *f >= 0.0 && *f <= 255.0 { Ok(*f as u8) } else { Err(RuntimeError::Custom(format!("Overflow: {} to Byte", f))) },
              Value::Double(d) => if *d >= 0.0 && *d <= 255.0 { Ok(*d as u8) } else { Err(RuntimeError::Custom(format!("Overflow: {} to Byte", d))) },
+             Value::Decimal(d) => match d.to_i64() { Some(i) if (0..=255).contains(&i) => Ok(i as u8), _ => Err(RuntimeError::Custom(format!("Overflow: {} to Byte", d))) },
              Value::String(_) => {
                  let i = self.as_integer()?; 
                  if i >= 0 && i <= 255 { Ok(i as u8) } else { Err(RuntimeError::Custom(format!("Overflow: {} to Byte", i))) }
@@ -297,8 +364,15 @@ impl Value {
             Value::Boolean(b) => *b,
             Value::Integer(i) => *i != 0,
             Value::Long(l) => *l != 0,
+            Value::Byte(b) => *b != 0,
+            Value::SByte(n) => *n != 0,
+            Value::Short(n) => *n != 0,
+            Value::UShort(n) => *n != 0,
+            Value::UInteger(n) => *n != 0,
+            Value::ULong(n) => *n != 0,
             Value::Single(f) => *f != 0.0,
             Value::Double(d) => *d != 0.0,
+            Value::Decimal(d) => !d.is_zero(),
             Value::Date(d) => *d != 0.0,
             Value::String(s) => !s.is_empty(),
             Value::Object(_) => true,
@@ -481,11 +555,17 @@ impl Value {
     pub fn to_shared(&self) -> SharedValue {
         match self {
             Value::Byte(b) => SharedValue::Byte(*b),
+            Value::SByte(n) => SharedValue::SByte(*n),
+            Value::Short(n) => SharedValue::Short(*n),
+            Value::UShort(n) => SharedValue::UShort(*n),
+            Value::UInteger(n) => SharedValue::UInteger(*n),
+            Value::ULong(n) => SharedValue::ULong(*n),
             Value::Char(c) => SharedValue::Char(*c),
             Value::Integer(i) => SharedValue::Integer(*i),
             Value::Long(l) => SharedValue::Long(*l),
             Value::Single(f) => SharedValue::Single(*f),
             Value::Double(d) => SharedValue::Double(*d),
+            Value::Decimal(d) => SharedValue::Decimal(*d),
             Value::Date(d) => SharedValue::Date(*d),
             Value::String(s) => SharedValue::String(s.clone()),
             Value::Boolean(b) => SharedValue::Boolean(*b),
@@ -538,11 +618,17 @@ impl SharedValue {
     pub fn to_value(&self) -> Value {
         match self {
             SharedValue::Byte(b) => Value::Byte(*b),
+            SharedValue::SByte(n) => Value::SByte(*n),
+            SharedValue::Short(n) => Value::Short(*n),
+            SharedValue::UShort(n) => Value::UShort(*n),
+            SharedValue::UInteger(n) => Value::UInteger(*n),
+            SharedValue::ULong(n) => Value::ULong(*n),
             SharedValue::Char(c) => Value::Char(*c),
             SharedValue::Integer(i) => Value::Integer(*i),
             SharedValue::Long(l) => Value::Long(*l),
             SharedValue::Single(f) => Value::Single(*f),
             SharedValue::Double(d) => Value::Double(*d),
+            SharedValue::Decimal(d) => Value::Decimal(*d),
             SharedValue::Date(d) => Value::Date(*d),
             SharedValue::String(s) => Value::String(s.clone()),
             SharedValue::Boolean(b) => Value::Boolean(*b),
@@ -614,6 +700,116 @@ pub enum RuntimeError {
     /// GoTo control flow: jump to the named label.
     #[error("GoTo {0}")]
     GoTo(String),
+
+    /// Procedure calls nested deeper than the interpreter's
+    /// `max_call_depth`: the procedures being run, innermost first.
+    #[error("{STACK_OVERFLOW_MESSAGE}\n{}", stack_trace(.0))]
+    StackOverflow(Vec<String>),
+
+    /// The script ran past one of the host's
+    /// [`ExecutionLimits`](crate::limits::ExecutionLimits).
+    #[error("Execution limit exceeded: {0}")]
+    LimitExceeded(crate::limits::Limit),
+
+    /// The host stopped the script through its
+    /// [`CancelHandle`](crate::limits::CancelHandle).
+    #[error("Execution was cancelled")]
+    Cancelled,
+
+    /// An error tagged with the statement it was raised from.
+    #[error("{location}: {error}")]
+    Located {
+        error: Box<RuntimeError>,
+        location: Box<SourceLocation>,
+    },
+}
+
+impl RuntimeError {
+    /// Source location of the statement that raised this error, if known.
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            RuntimeError::Located { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, without any location wrapper.
+    pub fn without_location(&self) -> &RuntimeError {
+        match self {
+            RuntimeError::Located { error, .. } => error.without_location(),
+            other => other,
+        }
+    }
+
+    /// Consume the error and return it without any location wrapper.
+    pub fn into_unlocated(self) -> RuntimeError {
+        match self {
+            RuntimeError::Located { error, .. } => error.into_unlocated(),
+            other => other,
+        }
+    }
+
+    /// Limits and cancellation end the whole run: no `Catch` or `On Error`
+    /// in the script handles them.
+    pub fn is_abort(&self) -> bool {
+        matches!(self.without_location(), RuntimeError::LimitExceeded(_) | RuntimeError::Cancelled)
+    }
+
+    /// Control-flow signals (Exit, Return, Continue, GoTo) travel as errors
+    /// but are never reported to the user.
+    pub fn is_control_flow(&self) -> bool {
+        matches!(
+            self,
+            RuntimeError::Exit(_) | RuntimeError::Return(_) | RuntimeError::Continue(_) | RuntimeError::GoTo(_)
+        )
+    }
+}
+
+/// The message of a `StackOverflowException`.
+pub const STACK_OVERFLOW_MESSAGE: &str = "Exception of type 'System.StackOverflowException' was thrown.";
+
+/// A call chain, innermost call first, as `Exception.StackTrace` shows it;
+/// a procedure calling itself over and over takes one line.
+pub fn stack_trace(chain: &[String]) -> String {
+    let mut lines = Vec::new();
+    let mut i = 0;
+    while i < chain.len() {
+        let repeats = chain[i..].iter().take_while(|name| **name == chain[i]).count();
+        lines.push(match repeats {
+            1 => format!("   at {}", chain[i]),
+            n => format!("   at {} ({} calls)", chain[i], n),
+        });
+        i += repeats;
+    }
+    lines.join("\n")
+}
+
+/// Where a runtime error was raised: `Form1.vb:412:9`, the start of the
+/// innermost expression or statement that failed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceLocation {
+    pub file: Option<String>,
+    pub span: vybe_parser::Span,
+    pub procedure: Option<String>,
+}
+
+impl SourceLocation {
+    pub fn line(&self) -> usize {
+        self.span.start_line
+    }
+
+    pub fn column(&self) -> usize {
+        self.span.start_col
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}", file, self.span),
+            None => write!(f, "{}", self.span),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -0,0 +1,101 @@
+//! Tests for attributes read at runtime: `GetCustomAttributes` and
+//! `IsDefined` on a `Type` and on the `PropertyInfo`, `MethodInfo` and
+//! `FieldInfo` objects it hands out, the program's own attribute classes
+//! with named arguments, and `Attribute.GetCustomAttribute`.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+const TYPES: &str = r#"
+<AttributeUsage(AttributeTargets.All)>
+Public Class AuthorAttribute
+    Inherits Attribute
+    Public Name As String
+    Public Property Version As Integer
+    Public Sub New(name As String)
+        Me.Name = name
+    End Sub
+End Class
+
+<Serializable>
+<Author("Ann", Version:=2)> _
+Public Class Widget
+    <Category("Layout"), DefaultValue(10)>
+    Public Property Width As Integer = 10
+
+    <Obsolete("Use Resize")>
+    Public Sub Grow(<Out> ByRef amount As Integer)
+    End Sub
+
+    <NonSerialized> Private cache As String
+End Class
+
+Public Class Gadget
+    Inherits Widget
+End Class
+"#;
+
+fn program(body: &str) -> String {
+    format!("{}\nModule M\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", TYPES, body)
+}
+
+#[test]
+fn test_class_attributes() {
+    let code = program(r#"
+        Dim t = GetType(Widget)
+        For Each a In t.GetCustomAttributes(True)
+            Console.WriteLine(a.GetType().Name)
+        Next
+        Dim authors = t.GetCustomAttributes(GetType(AuthorAttribute), False)
+        Dim author = authors(0)
+        Console.WriteLine(author.Name & " v" & author.Version)
+        Console.WriteLine(t.IsDefined(GetType(SerializableAttribute), False))
+        Console.WriteLine(GetType(Gadget).IsDefined(GetType(SerializableAttribute), True) & " " & GetType(Gadget).IsDefined(GetType(SerializableAttribute), False))
+        Dim w As New Widget()
+        Console.WriteLine(w.GetType().GetCustomAttributes(False).Length)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "SerializableAttribute", "AuthorAttribute", "Ann v2", "True", "True False", "2",
+    ]);
+}
+
+#[test]
+fn test_member_attributes() {
+    let code = program(r#"
+        Dim t = GetType(Widget)
+        Dim p = t.GetProperty("Width")
+        For Each a In p.GetCustomAttributes(True)
+            Console.WriteLine(p.Name & ": " & a.GetType().Name)
+        Next
+        Console.WriteLine(t.GetField("cache").IsDefined(GetType(NonSerializedAttribute), False))
+        Console.WriteLine(t.GetMethod("Grow").IsDefined(GetType(SerializableAttribute), False))
+        Console.WriteLine(t.GetProperty("Missing") Is Nothing)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "Width: CategoryAttribute", "Width: DefaultValueAttribute", "True", "False", "True",
+    ]);
+}
+
+#[test]
+fn test_framework_attribute_arguments() {
+    let code = program(r#"
+        Dim t = GetType(Widget)
+        Dim ob = CType(Attribute.GetCustomAttribute(t.GetMethod("Grow"), GetType(ObsoleteAttribute)), ObsoleteAttribute)
+        Console.WriteLine(ob.Message & " " & ob.IsError)
+        Dim cat = CType(Attribute.GetCustomAttribute(t.GetProperty("Width"), GetType(CategoryAttribute)), CategoryAttribute)
+        Console.WriteLine(cat.Category)
+        Console.WriteLine(Attribute.IsDefined(t, GetType(ObsoleteAttribute)))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["Use Resize False", "Layout", "False"]);
+}
@@ -0,0 +1,288 @@
+//! Tests for the bytecode engine: programs run under both engines must print
+//! the same thing and fail the same way, including compiled loops around
+//! statements left to the tree walker, `On Error Resume Next`, user-defined
+//! operators in short-circuit conditions and error locations.
+
+use vybe_runtime::bytecode::{compile, Op};
+use vybe_runtime::{Engine, Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::{parse_program, Declaration};
+
+fn run_with(engine: Engine, code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.engine = engine;
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+/// Run `code` under the bytecode engine, checking the tree walker agrees.
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let expected = run_with(Engine::TreeWalker, code);
+    let actual = run_with(Engine::Bytecode, code);
+    match (&expected, &actual) {
+        (Ok(a), Ok(b)) => assert_eq!(a, b),
+        (Err(a), Err(b)) => assert_eq!(a.to_string(), b.to_string()),
+        _ => panic!("engines disagree: {:?} vs {:?}", expected, actual),
+    }
+    actual
+}
+
+fn program(body: &str) -> String {
+    format!("Module M\n{}\nEnd Module\n", body)
+}
+
+#[test]
+fn test_loops_and_operators() {
+    let code = program(r#"
+    Function Fib(n As Integer) As Integer
+        If n < 2 Then Return n
+        Return Fib(n - 1) + Fib(n - 2)
+    End Function
+
+    Sub Main()
+        Dim total As Long = 0
+        For i = 1 To 1000
+            If i Mod 3 = 0 OrElse i Mod 5 = 0 Then
+                total += i
+            ElseIf i > 990 AndAlso i Mod 2 = 0 Then
+                total -= 1
+            End If
+        Next
+        Console.WriteLine(total & " " & Fib(15))
+        Dim s = ""
+        For a = 1 To 3
+            For b = 3 To 1 Step -1
+                If b = 2 Then Continue For
+                If a = 3 Then Exit For
+                s &= a & b & " "
+            Next
+        Next
+        Console.WriteLine(s)
+        Dim n As Byte = 250
+        Do
+            n += 1
+            If n = 253 Then Continue Do
+        Loop Until n >= 255
+        Dim k = 0
+        While True
+            k = k + 1
+            If k > 4 Then Exit While
+        End While
+        Console.WriteLine(n.GetType().Name & " " & n & " " & k & " " & (Not k) & " " & -k)
+        Dim price As Decimal = 0.1D
+        price = price * 3
+        Console.WriteLine(price)
+    End Sub"#);
+    assert_eq!(run(&code).unwrap(), vec!["234165 610", "13 11 23 21", "Byte 255 5 -6 -5", "0.3"]);
+}
+
+#[test]
+fn test_fallback_statements_inside_compiled_loops() {
+    let code = program(r#"
+    Function Find(items() As Integer, wanted As Integer) As Integer
+        Dim found = -1
+        For i = 0 To items.Length - 1
+            Select Case items(i)
+                Case wanted
+                    found = i
+                    Exit For
+                Case Else
+                    Try
+                        If items(i) < 0 Then Continue For
+                    Finally
+                        Console.WriteLine("checked " & i)
+                    End Try
+            End Select
+        Next
+        Return found
+    End Function
+
+    Sub Main()
+        Console.WriteLine(Find({3, -1, 7, 9}, 7))
+        Dim words As New List(Of String) From {"a", "bb", "ccc"}
+        Dim count = 0
+        Do While count < 10
+            For Each w In words
+                If w.Length = 2 Then Exit Do
+                count += 1
+            Next
+        Loop
+        Console.WriteLine(count)
+    End Sub"#);
+    assert_eq!(run(&code).unwrap(), vec!["checked 0", "checked 1", "2", "1"]);
+}
+
+#[test]
+fn test_user_operators_in_conditions() {
+    let code = format!("{}{}", r#"
+Public Structure Flag
+    Public On As Boolean
+    Public Sub New(v As Boolean)
+        On = v
+    End Sub
+    Public Shared Operator IsTrue(f As Flag) As Boolean
+        Return f.On
+    End Operator
+    Public Shared Operator IsFalse(f As Flag) As Boolean
+        Return Not f.On
+    End Operator
+    Public Shared Operator And(a As Flag, b As Flag) As Flag
+        Console.WriteLine("And")
+        Return New Flag(a.On And b.On)
+    End Operator
+End Structure
+"#, program(r#"
+    Sub Main()
+        Dim yes As New Flag(True)
+        Dim no As New Flag(False)
+        Dim both = yes AndAlso no
+        Console.WriteLine(both.On)
+        If no AndAlso yes Then Console.WriteLine("never")
+        Dim i = 0
+        While yes AndAlso i < 2
+            i += 1
+        End While
+        Console.WriteLine(i)
+    End Sub"#));
+    assert_eq!(run(&code).unwrap(), vec!["And", "False", "2"]);
+}
+
+#[test]
+fn test_errors_and_on_error() {
+    let code = program(r#"
+    Sub Risky()
+        On Error Resume Next
+        Dim x = 1
+        While True
+            x = x \ 0
+            Console.WriteLine("not here")
+        End While
+        Console.WriteLine("resumed " & x)
+    End Sub
+
+    Sub Main()
+        Risky()
+    End Sub"#);
+    assert_eq!(run(&code).unwrap(), vec!["resumed 1"]);
+
+    let code = program(r#"
+    Sub Main()
+        Dim total = 0
+        For i = 1 To 3
+            If i = 3 Then
+                total = total + CInt("x")
+            End If
+        Next
+    End Sub"#);
+    let err = run(&code).unwrap_err();
+    assert_eq!(err.to_string(), "7:33: Conversion from string \"x\" to type 'Integer' is not valid.");
+}
+
+#[test]
+fn test_bodies_sharing_a_name() {
+    // The same interpreter runs programs whose Main differs only in a literal
+    let mut interp = Interpreter::new();
+    interp.engine = Engine::Bytecode;
+    for n in [1, 2, 1] {
+        let program = parse_program(&program(&format!(r#"
+    Sub Show(x As Integer)
+        For i = 1 To 1
+            Console.WriteLine("Integer " & x)
+        Next
+    End Sub
+
+    Sub Show(x As String)
+        For i = 1 To 1
+            Console.WriteLine("String " & x)
+        Next
+    End Sub
+
+    Sub Main()
+        For i = 1 To 1
+            Show({n})
+            Show("{n}")
+        Next
+    End Sub"#))).expect("Parse error");
+        interp.run(&program).unwrap();
+        interp.call_procedure(&Identifier::new("Main"), &[]).unwrap();
+    }
+    let output: Vec<String> = interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect();
+    assert_eq!(output, vec!["Integer 1", "String 1", "Integer 2", "String 2", "Integer 1", "String 1"]);
+}
+
+#[test]
+fn test_compiled_shape() {
+    let program = parse_program(&program(r#"
+    Sub Loops()
+        For i = 1 To 10
+            x = x + i
+        Next
+        Console.WriteLine(x)
+    End Sub
+
+    Sub Jumps()
+        GoTo Done
+Done:
+    End Sub
+
+    Sub Straight()
+        x = x + 1
+        Console.WriteLine(x)
+    End Sub"#)).expect("Parse error");
+    let body = |name: &str| program.declarations.iter().find_map(|d| match d {
+        Declaration::Sub(sub) if sub.name.as_str() == name => Some(sub.body.clone()),
+        _ => None,
+    }).unwrap();
+
+    let chunk = compile(&body("Loops")).expect("compiled");
+    assert!(chunk.ops.iter().any(|op| matches!(op, Op::ForTest { .. })));
+    assert!(chunk.ops.contains(&Op::Binary(vybe_parser::ast::expr::BinaryOp::Add)));
+    // The call is left to the tree walker
+    assert_eq!(chunk.statements.len(), 1);
+    assert_eq!(chunk.statement_starts.len(), 2);
+
+    // Labels are jumped to by statement index, which only the tree walker has
+    assert!(compile(&body("Jumps")).is_none());
+    // Nothing repeats, so the tree walker runs it as fast
+    assert!(compile(&body("Straight")).is_none());
+}
+
+#[test]
+fn test_property_accessors_with_loops() {
+    // The Get and Set of a property are declared together but are two bodies
+    let code = format!("{}{}", r#"
+Public Class Person
+    Private nm As String
+    Private reads As Integer
+
+    Public Property Name As String
+        Get
+            For i = 1 To 2
+                reads += 1
+            Next
+            Return nm & " " & reads
+        End Get
+        Set(value As String)
+            nm = value
+            For i = 1 To 2
+                nm = nm & "!"
+            Next
+        End Set
+    End Property
+End Class
+"#, program(r#"
+    Sub Main()
+        Dim p As New Person()
+        p.Name = "nm"
+        Console.WriteLine(p.Name)
+        p.Name = "x"
+        Console.WriteLine(p.Name)
+    End Sub"#));
+    assert_eq!(run(&code).unwrap(), vec!["nm!! 2", "x!! 4"]);
+}
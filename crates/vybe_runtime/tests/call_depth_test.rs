@@ -0,0 +1,171 @@
+//! Tests for the call-depth limit: runaway recursion throws a catchable
+//! StackOverflowException carrying the VB call chain, and deep but finite
+//! recursion runs to the end without a larger thread stack, even on a host
+//! thread with a small one.
+
+use vybe_runtime::{Engine, Interpreter, RuntimeError, RuntimeSideEffect, DEFAULT_MAX_CALL_DEPTH};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run_with(engine: Engine, max_call_depth: usize, code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.engine = engine;
+    interp.max_call_depth = max_call_depth;
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+/// Run `code` under both engines, checking they agree.
+fn run(max_call_depth: usize, code: &str) -> Result<Vec<String>, RuntimeError> {
+    let expected = run_with(Engine::TreeWalker, max_call_depth, code);
+    let actual = run_with(Engine::Bytecode, max_call_depth, code);
+    match (&expected, &actual) {
+        (Ok(a), Ok(b)) => assert_eq!(a, b),
+        (Err(a), Err(b)) => assert_eq!(a.to_string(), b.to_string()),
+        _ => panic!("engines disagree: {:?} vs {:?}", expected, actual),
+    }
+    actual
+}
+
+#[test]
+fn test_runaway_recursion_is_catchable() {
+    let code = r#"
+Module M
+    Function Forever(n As Integer) As Integer
+        Return Forever(n + 1)
+    End Function
+
+    Sub Main()
+        Try
+            Forever(0)
+        Catch ex As StackOverflowException
+            Console.WriteLine(ex.GetType().Name)
+            Console.WriteLine(ex.Message)
+            Console.WriteLine(ex.StackTrace)
+        End Try
+        Console.WriteLine("still running")
+    End Sub
+End Module
+"#;
+    let output = run(50, code).unwrap();
+    assert_eq!(output, vec![
+        "StackOverflowException",
+        "Exception of type 'System.StackOverflowException' was thrown.",
+        "   at Forever (50 calls)\n   at Main",
+        "still running",
+    ]);
+}
+
+#[test]
+fn test_uncaught_overflow_shows_call_chain() {
+    let code = r#"
+Class Node
+    Public Function Visit(depth As Integer) As Integer
+        Return Ping(depth)
+    End Function
+
+    Private Function Ping(depth As Integer) As Integer
+        Return Visit(depth + 1)
+    End Function
+End Class
+
+Module M
+    Sub Main()
+        Dim n As New Node()
+        Dim total = n.Visit(0)
+    End Sub
+End Module
+"#;
+    let err = run(6, code).unwrap_err();
+    let RuntimeError::StackOverflow(chain) = err.without_location() else {
+        panic!("expected a stack overflow, got {:?}", err);
+    };
+    assert_eq!(chain, &["Node.Ping", "Node.Visit", "Node.Ping", "Node.Visit", "Node.Ping", "Node.Visit", "Main"]);
+    assert!(err.to_string().contains(
+        "Exception of type 'System.StackOverflowException' was thrown.\n   at Node.Ping\n   at Node.Visit\n"
+    ));
+
+    // A handler further up the chain sees the same exception
+    let code = code.replace(
+        "        Dim total = n.Visit(0)\n",
+        "        Try\n            n.Visit(0)\n        Catch ex As Exception\n            Console.WriteLine(TypeName(ex) & \": \" & ex.StackTrace.Split(vbLf).Length)\n        End Try\n",
+    );
+    assert_eq!(run(6, &code).unwrap(), vec!["StackOverflowException: 7"]);
+}
+
+#[test]
+fn test_deep_recursion_within_the_limit() {
+    let code = r#"
+Module M
+    Function Sum(n As Integer) As Long
+        If n = 0 Then Return 0
+        Return n + Sum(n - 1)
+    End Function
+
+    Sub Main()
+        Console.WriteLine(Sum(1000))
+        Dim square = Function(x As Integer) x * x
+        Console.WriteLine(square(Sum(10)))
+    End Sub
+End Module
+"#;
+    // Far deeper than a test thread's stack allows without growing it
+    assert_eq!(run(DEFAULT_MAX_CALL_DEPTH, code).unwrap(), vec!["500500", "3025"]);
+
+    let err = run(1000, code).unwrap_err();
+    assert!(matches!(err.without_location(), RuntimeError::StackOverflow(chain) if chain.len() == 1001));
+}
+
+#[test]
+fn test_small_host_thread_stack() {
+    // Top-level statements evaluated before any call, and runaway recursion,
+    // on a host thread with less stack than a few interpreter frames take
+    let nested = format!("{}1{}", "(1 + ".repeat(15), ")".repeat(15));
+    let top_level = parse_program(&format!(r#"
+Dim x = {nested}
+If x > 0 Then
+    For i = 1 To 2
+        Select Case i
+            Case 2
+                x = x * 2
+        End Select
+    Next
+End If
+Console.WriteLine(x)
+"#)).expect("Parse error");
+    let recursion = parse_program(r#"
+Module M
+    Function Forever(n As Integer) As Integer
+        Return Forever(n + 1)
+    End Function
+
+    Sub Main()
+        Try
+            Forever(0)
+        Catch ex As StackOverflowException
+            Console.WriteLine(ex.GetType().Name)
+        End Try
+    End Sub
+End Module
+"#).expect("Parse error");
+
+    let host = std::thread::Builder::new().stack_size(256 * 1024).spawn(move || {
+        let mut output = Vec::new();
+        for engine in [Engine::TreeWalker, Engine::Bytecode] {
+            let mut interp = Interpreter::new();
+            interp.engine = engine;
+            interp.run(&top_level).unwrap();
+            interp.run(&recursion).unwrap();
+            interp.call_procedure(&Identifier::new("Main"), &[]).unwrap();
+            output.extend(interp.side_effects.iter().filter_map(|e| {
+                if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+            }));
+        }
+        output
+    }).unwrap();
+    assert_eq!(host.join().unwrap(), vec!["32", "StackOverflowException", "32", "StackOverflowException"]);
+}
@@ -0,0 +1,93 @@
+//! Tests for conditional compilation with `#If`, `#Const` and project constants.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::{parse_program_named_with, ConditionalConstants, ConstValue};
+
+fn run(code: &str, constants: &ConditionalConstants) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program_named_with(code, "Module1.vb", constants).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+#[test]
+fn test_project_constants_select_code() {
+    let code = r#"
+Module M
+    Sub Main()
+#If DEBUG Then
+        Console.WriteLine("debug build")
+#Else
+        Console.WriteLine("release build")
+#End If
+#If TRACE AndAlso Edition = "Pro" Then
+        Console.WriteLine("traced pro")
+#End If
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code, &ConditionalConstants::new()).unwrap(), vec!["release build"]);
+
+    let mut constants = ConditionalConstants::project_defaults();
+    constants.define_list("Edition=\"Pro\"");
+    assert_eq!(run(code, &constants).unwrap(), vec!["debug build", "traced pro"]);
+}
+
+#[test]
+fn test_file_constants_and_regions() {
+    let code = r#"
+#Const Platform = "Web"
+#Const Verbose = Platform = "Web"
+
+#Region "Entry point"
+Module M
+#If Platform = "Desktop" Then
+    Function Target() As String
+        Return "desktop"
+    End Function
+#ElseIf Platform = "Web" Then
+    Function Target() As String
+        Return "web"
+    End Function
+#End If
+
+    Sub Main()
+        Console.WriteLine(Target())
+#If Verbose Then
+        Console.WriteLine("verbose")
+#End If
+    End Sub
+End Module
+#End Region
+"#;
+    assert_eq!(run(code, &ConditionalConstants::new()).unwrap(), vec!["web", "verbose"]);
+
+    // File constants override project constants of the same name
+    let mut constants = ConditionalConstants::new();
+    constants.define("Platform", ConstValue::String("Desktop".to_string()));
+    assert_eq!(run(code, &constants).unwrap(), vec!["web", "verbose"]);
+}
+
+#[test]
+fn test_error_lines_count_stripped_regions() {
+    let code = r#"
+Module M
+    Sub Main()
+#If DEBUG Then
+        Console.WriteLine("one")
+        Console.WriteLine("two")
+#End If
+        Throw New Exception("boom")
+    End Sub
+End Module
+"#;
+    for constants in [ConditionalConstants::new(), ConditionalConstants::project_defaults()] {
+        let err = run(code, &constants).unwrap_err();
+        let loc = err.location().expect("error should carry a location");
+        assert_eq!((loc.line(), loc.column()), (8, 9));
+    }
+}
@@ -0,0 +1,172 @@
+//! Tests for events declared by classes: per-instance handlers added with
+//! `AddHandler` and `WithEvents`/`Handles`, delegates that carry their target
+//! object, and `Custom Event` accessor blocks.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+const COUNTER: &str = r#"
+Public Class Counter
+    Public Event Changed(sender As Object, value As Integer)
+    Public Name As String
+    Private _n As Integer
+    Public Sub New(name As String)
+        Me.Name = name
+    End Sub
+    Public Sub Increment()
+        _n += 1
+        RaiseEvent Changed(Me, _n)
+    End Sub
+End Class
+
+Public Class Observer
+    Public Tag As String
+    Public Sub New(tag As String)
+        Me.Tag = tag
+    End Sub
+    Public Sub OnChanged(sender As Object, value As Integer)
+        Console.WriteLine(Tag & " saw " & sender.Name & "=" & value)
+    End Sub
+    Public Sub Watch(c As Counter)
+        AddHandler c.Changed, AddressOf OnChanged
+    End Sub
+    Public Sub Unwatch(c As Counter)
+        RemoveHandler c.Changed, AddressOf Me.OnChanged
+    End Sub
+End Class
+"#;
+
+fn program(types: &str, members: &str, body: &str) -> String {
+    format!("{}\nModule M\n{}\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", types, members, body)
+}
+
+#[test]
+fn test_handlers_per_instance() {
+    let code = program(COUNTER, r#"
+    Sub Logged(sender As Object, value As Integer)
+        Console.WriteLine("module saw " & value)
+    End Sub
+"#, r#"
+        Dim a As New Counter("a")
+        Dim b As New Counter("b")
+        Dim o1 As New Observer("o1")
+        Dim o2 As New Observer("o2")
+        o1.Watch(a)
+        o2.Watch(a)
+        o2.Watch(b)
+        AddHandler b.Changed, AddressOf Logged
+        a.Increment()
+        b.Increment()
+        o1.Unwatch(a)
+        RemoveHandler b.Changed, AddressOf Logged
+        a.Increment()
+        b.Increment()
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "o1 saw a=1",
+        "o2 saw a=1",
+        "o2 saw b=1",
+        "module saw 1",
+        "o2 saw a=2",
+        "o2 saw b=2",
+    ]);
+}
+
+#[test]
+fn test_withevents_handles() {
+    let code = program(COUNTER, r#"
+    Class Display
+        Private WithEvents source As Counter
+        Public Sub Attach(c As Counter)
+            source = c
+        End Sub
+        Private Sub source_Changed(sender As Object, value As Integer) Handles source.Changed
+            Console.WriteLine("display " & value)
+        End Sub
+    End Class
+"#, r#"
+        Dim a As New Counter("a")
+        Dim b As New Counter("b")
+        Dim d As New Display()
+        d.Attach(a)
+        a.Increment()
+        d.Attach(b)
+        a.Increment()
+        b.Increment()
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["display 1", "display 1"]);
+}
+
+#[test]
+fn test_custom_event_accessors() {
+    let code = program(r#"
+Public Class Relay
+    Private handlers As New List(Of EventHandler)
+    Public Custom Event Fired As EventHandler
+        AddHandler(ByVal value As EventHandler)
+            If handlers.Count < 2 Then handlers.Add(value)
+        End AddHandler
+        RemoveHandler(ByVal value As EventHandler)
+            handlers.Remove(value)
+        End RemoveHandler
+        RaiseEvent(ByVal sender As Object, ByVal e As EventArgs)
+            Console.WriteLine("raising to " & handlers.Count)
+            For Each h As EventHandler In handlers
+                h.Invoke(sender, e)
+            Next
+        End RaiseEvent
+    End Event
+    Public Sub Fire()
+        RaiseEvent Fired(Me, EventArgs.Empty)
+    End Sub
+End Class
+"#, r#"
+    Sub Ping(sender As Object, e As EventArgs)
+        Console.WriteLine("ping")
+    End Sub
+    Sub Pong()
+        Console.WriteLine("pong")
+    End Sub
+"#, r#"
+        Dim r As New Relay()
+        AddHandler r.Fired, AddressOf Ping
+        AddHandler r.Fired, AddressOf Pong
+        AddHandler r.Fired, AddressOf Ping
+        r.Fire()
+        RemoveHandler r.Fired, AddressOf Ping
+        r.Fire()
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["raising to 2", "ping", "pong", "raising to 1", "pong"]);
+}
+
+#[test]
+fn test_delegate_values() {
+    let code = program(COUNTER, r#"
+    Sub Show(sender As Object, value As Integer)
+        Console.WriteLine("show " & value)
+    End Sub
+"#, r#"
+        Dim o1 As New Observer("o1")
+        Dim o2 As New Observer("o2")
+        Dim a As New Counter("a")
+        Dim d1 = AddressOf o1.OnChanged
+        Dim d2 = AddressOf o2.OnChanged
+        d1.Invoke(a, 1)
+        d2(a, 2)
+        Dim s = AddressOf Show
+        s(a, 3)
+        Console.WriteLine((d1 = AddressOf o1.OnChanged) & " " & (d1 = d2))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["o1 saw a=1", "o2 saw a=2", "show 3", "True False"]);
+}
@@ -0,0 +1,165 @@
+//! Tests for the Decimal type: exact arithmetic, scale, banker's rounding,
+//! overflow, formatting and database round-trips.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+fn main_with(body: &str) -> String {
+    format!("Module M\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", body)
+}
+
+#[test]
+fn test_exact_arithmetic() {
+    let code = main_with(r#"
+        Console.WriteLine(0.1D + 0.2D)
+        Console.WriteLine(0.1D + 0.2D = 0.3D)
+        Dim total As Decimal = 0
+        For i = 1 To 10
+            total += 0.1D
+        Next
+        Console.WriteLine(total = 1)
+        Console.WriteLine(19.99D * 3 - 0.97D)
+        Console.WriteLine(-7.5D Mod 2)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["0.3", "True", "True", "59.00", "-1.5"]);
+}
+
+#[test]
+fn test_scale_is_kept() {
+    let code = main_with(r#"
+        Console.WriteLine(1.10D)
+        Console.WriteLine(1.5D * 2)
+        Console.WriteLine(1.00D / 2)
+        Console.WriteLine(CDec("123.4500"))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["1.10", "3.0", "0.50", "123.4500"]);
+}
+
+#[test]
+fn test_division() {
+    let code = main_with(r#"
+        Console.WriteLine(1D / 3D)
+        Console.WriteLine(2D / 3D)
+        Console.WriteLine(10D / 4)
+        Console.WriteLine(1D / 3D * 3D)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "0.3333333333333333333333333333",
+        "0.6666666666666666666666666667",
+        "2.5",
+        "0.9999999999999999999999999999",
+    ]);
+}
+
+#[test]
+fn test_widening_and_types() {
+    let code = main_with(r#"
+        Dim price As Decimal = 19.99
+        Dim n As Integer = 3
+        Dim sum = price * n
+        Console.WriteLine(TypeName(sum) & " " & sum.GetType().FullName & " " & VarType(sum))
+        Console.WriteLine(TypeOf CObj(sum) Is Decimal)
+        Dim mixed = price + 0.5
+        Console.WriteLine(TypeName(mixed))
+        Dim fromDouble As Decimal = 0.1 + 0.2
+        Console.WriteLine(fromDouble)
+        price = price / 2
+        Console.WriteLine(TypeName(price) & " " & price)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "Decimal System.Decimal 14",
+        "True",
+        "Double",
+        "0.3",
+        "Decimal 9.995",
+    ]);
+}
+
+#[test]
+fn test_bankers_rounding() {
+    let code = main_with(r#"
+        Console.WriteLine(Math.Round(2.5D) & " " & Math.Round(3.5D) & " " & Math.Round(-2.5D))
+        Console.WriteLine(Math.Round(2.345D, 2) & " " & Math.Round(2.355D, 2))
+        Console.WriteLine(Math.Round(2.5D, MidpointRounding.AwayFromZero))
+        Console.WriteLine(Math.Round(2.345D, 2, MidpointRounding.AwayFromZero))
+        Console.WriteLine(Math.Round(2.5) & " " & Round(0.5))
+        Console.WriteLine(Math.Floor(-1.5D) & " " & Math.Ceiling(1.2D) & " " & Math.Truncate(-1.7D) & " " & Math.Abs(-1.25D))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["2 4 -2", "2.34 2.36", "3", "2.35", "2 0", "-2 2 -1 1.25"]);
+}
+
+#[test]
+fn test_overflow_is_catchable() {
+    let code = main_with(r#"
+        Console.WriteLine(Decimal.MaxValue)
+        Try
+            Dim big As Decimal = Decimal.MaxValue
+            big = big + 1
+        Catch ex As OverflowException
+            Console.WriteLine(ex.Message)
+        End Try
+        Try
+            Console.WriteLine(1D / 0)
+        Catch ex As DivideByZeroException
+            Console.WriteLine("divide by zero")
+        End Try
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "79228162514264337593543950335",
+        "Value was either too large or too small for a Decimal.",
+        "divide by zero",
+    ]);
+}
+
+#[test]
+fn test_formatting_and_parsing() {
+    let code = main_with(r#"
+        Dim amount = 1234567.891D
+        Console.WriteLine(amount.ToString("N2") & " " & amount.ToString("F1") & " " & Format(amount, "C"))
+        Console.WriteLine(FormatNumber(2.345D, 2) & " " & FormatCurrency(0.125D) & " " & Format(0.125D, "P1"))
+        Console.WriteLine(Decimal.Parse("1,234.50") + 1)
+        Console.WriteLine(Decimal.TryParse("abc") & " " & Convert.ToDecimal("0.10"))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "1,234,567.89 1234567.9 $1,234,567.89",
+        "2.35 $0.13 12.5%",
+        "1235.50",
+        "False 0.10",
+    ]);
+}
+
+#[test]
+fn test_database_round_trip() {
+    let code = main_with(r#"
+        Dim conn As New System.Data.SqlClient.SqlConnection()
+        conn.ConnectionString = "Data Source=:memory:"
+        conn.Open()
+        Dim create As Object = conn.CreateCommand()
+        create.CommandText = "CREATE TABLE Items (Id INTEGER PRIMARY KEY, Price DECIMAL(10,2))"
+        create.ExecuteNonQuery()
+        Dim insert As Object = conn.CreateCommand()
+        insert.CommandText = "INSERT INTO Items (Price) VALUES (@price)"
+        insert.Parameters.AddWithValue("@price", 0.1D + 0.2D)
+        insert.ExecuteNonQuery()
+        Dim query As Object = conn.CreateCommand()
+        query.CommandText = "SELECT Price FROM Items"
+        Dim reader As Object = query.ExecuteReader()
+        While reader.Read()
+            Dim price = reader.GetDecimal(0)
+            Console.WriteLine(TypeName(price) & " " & price & " " & (price = 0.3D))
+        End While
+        conn.Close()
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["Decimal 0.3 True"]);
+}
@@ -0,0 +1,128 @@
+//! Tests for `Default` properties and parameterized properties on user
+//! classes: `obj(3)` and `table("key")` reading and writing `Item`, `For Each`
+//! over a class with a Default property and `Count`, and `With` blocks.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+const TYPES: &str = r#"
+Public Class Bag
+    Private items As New List(Of String)
+    Public Sub Add(s As String)
+        items.Add(s)
+    End Sub
+    Public ReadOnly Property Count As Integer
+        Get
+            Return items.Count
+        End Get
+    End Property
+    Default Public Property Item(index As Integer) As String
+        Get
+            Return items(index)
+        End Get
+        Set(value As String)
+            items(index) = value
+        End Set
+    End Property
+    Public Sub Shout()
+        Item(0) = Item(0).ToUpper()
+        Me.Item(1) = Me.Item(1) & "!"
+    End Sub
+End Class
+
+Public Class Table
+    Private data As New Dictionary(Of String, Integer)
+    Default Public Property Item(key As String) As Integer
+        Get
+            If data.ContainsKey(key) Then Return data(key)
+            Return -1
+        End Get
+        Set(value As Integer)
+            data(key) = value
+        End Set
+    End Property
+End Class
+
+Public Class Holder
+    Public Inner As New Bag()
+End Class
+"#;
+
+fn program(body: &str) -> String {
+    format!("{}\nModule M\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", TYPES, body)
+}
+
+#[test]
+fn test_default_property_reads_and_writes() {
+    let code = program(r#"
+        Dim b As New Bag()
+        b.Add("x")
+        b.Add("y")
+        Console.WriteLine(b.Count & " " & b(1) & " " & b.Item(0))
+        b(0) = "z"
+        b.Item(1) = "w"
+        Console.WriteLine(b(0) & b(1))
+        b.Shout()
+        Console.WriteLine(b(0) & b(1))
+        Dim t As New Table()
+        t("a") = 5
+        t("a") = t("a") + 1
+        Console.WriteLine(t("a") & " " & t("b"))
+        Dim h As New Holder()
+        h.Inner.Add("n")
+        h.Inner(0) = "m"
+        Console.WriteLine(h.Inner(0))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["2 y x", "zw", "Zw!", "6 -1", "m"]);
+}
+
+#[test]
+fn test_for_each_and_with() {
+    let code = program(r#"
+        Dim b As New Bag()
+        b.Add("p")
+        b.Add("q")
+        For Each s In b
+            Console.WriteLine("each " & s)
+        Next
+        With b
+            .Item(0) = "r"
+            Console.WriteLine(.Item(0) & " " & .Count)
+        End With
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["each p", "each q", "r 2"]);
+}
+
+#[test]
+fn test_read_only_default_property() {
+    let code = r#"
+Public Class Squares
+    Default Public ReadOnly Property Item(n As Integer) As Integer
+        Get
+            Return n * n
+        End Get
+    End Property
+End Class
+
+Module M
+    Sub Main()
+        Dim s As New Squares()
+        Console.WriteLine(s(7))
+        s(2) = 5
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert_eq!(err.without_location().to_string(), "Property 'Item' is 'ReadOnly'.");
+}
@@ -0,0 +1,92 @@
+//! Tests that runtime errors carry the source location of the failing code.
+
+use vybe_runtime::{Interpreter, RuntimeError};
+use vybe_parser::ast::Identifier;
+use vybe_parser::{parse_program, parse_program_named};
+
+fn run_main(program: &vybe_parser::Program) -> Result<(), RuntimeError> {
+    let mut interp = Interpreter::new();
+    interp.run(program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[]).map(|_| ())
+}
+
+#[test]
+fn test_error_reports_file_line_and_column() {
+    let code = r#"
+Sub Main()
+    Dim x As Integer = 1
+    Fail(x)
+End Sub
+
+Sub Fail(n As Integer)
+    Dim y As Integer = n
+    Throw New Exception("boom")
+End Sub
+"#;
+    let program = parse_program_named(code, "Module1.vb").expect("Parse error");
+    let err = run_main(&program).expect_err("expected a runtime error");
+
+    // The innermost statement wins: the Throw inside Fail, not the call in Main.
+    let loc = err.location().expect("error should carry a location");
+    assert_eq!(loc.file.as_deref(), Some("Module1.vb"));
+    assert_eq!((loc.line(), loc.column()), (9, 5));
+    assert_eq!(loc.procedure.as_deref(), Some("Fail"));
+    assert_eq!(err.to_string(), "Module1.vb:9:5: boom");
+    assert!(matches!(err.without_location(), RuntimeError::Exception(t, m, _) if t == "Exception" && m == "boom"));
+}
+
+#[test]
+fn test_error_location_without_file_name() {
+    let code = r#"
+Sub Main()
+    Dim a As Integer = 10
+    Dim b As Integer = 0
+    Console.WriteLine(a \ b)
+End Sub
+"#;
+    let program = parse_program(code).expect("Parse error");
+    let err = run_main(&program).expect_err("expected a runtime error");
+    let loc = err.location().expect("error should carry a location");
+    assert_eq!(loc.file, None);
+    assert_eq!(loc.to_string(), "5:23");
+}
+
+#[test]
+fn test_error_inside_expression_reports_its_column() {
+    let code = r#"
+Sub Main()
+    Dim zero As Integer = 0
+    Dim total As Integer = 1 + _
+        10 \ zero
+End Sub
+"#;
+    let program = parse_program_named(code, "Module1.vb").expect("Parse error");
+    let err = run_main(&program).expect_err("expected a runtime error");
+
+    // The failing division on the continuation line, not the Dim above it.
+    let loc = err.location().expect("error should carry a location");
+    assert_eq!((loc.line(), loc.column()), (5, 9));
+    assert_eq!(loc.procedure.as_deref(), Some("Main"));
+}
+
+#[test]
+fn test_caught_exception_unwraps_location() {
+    let code = r#"
+Sub Main()
+    Try
+        Throw New InvalidOperationException("bad state")
+    Catch ex As InvalidOperationException
+        Console.WriteLine(ex.Message)
+        Console.WriteLine(ex.StackTrace)
+    End Try
+End Sub
+"#;
+    let program = parse_program_named(code, "Program.vb").expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program).expect("Runtime error");
+    interp.call_procedure(&Identifier::new("Main"), &[]).expect("Failed to call Main");
+    let output: Vec<String> = interp.side_effects.iter().filter_map(|e| {
+        if let vybe_runtime::RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect();
+    assert_eq!(output, vec!["bad state", "   at Main in Program.vb:4:9"]);
+}
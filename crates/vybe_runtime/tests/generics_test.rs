@@ -0,0 +1,176 @@
+//! Tests for user-defined generic classes, structures and methods.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+fn exception_message(err: RuntimeError) -> (String, String) {
+    match err.without_location() {
+        RuntimeError::Exception(t, m, _) => (t.clone(), m.clone()),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_generic_class_remembers_type_arguments() {
+    let code = r#"
+Public Class Box(Of T)
+    Private _value As T
+
+    Public Sub New(v As T)
+        _value = v
+    End Sub
+
+    Public Function GetValue() As T
+        Return _value
+    End Function
+
+    Public Function Describe() As String
+        Return "Box of " & GetType(T).Name
+    End Function
+End Class
+
+Sub Main()
+    Dim b As New Box(Of Integer)(5)
+    Console.WriteLine(b.GetValue())
+    Console.WriteLine(b.Describe())
+    Console.WriteLine(TypeName(b))
+    Console.WriteLine(b.GetType().Name)
+    Console.WriteLine(TypeOf b Is Box(Of Integer))
+    Console.WriteLine(TypeOf b Is Box(Of String))
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["5", "Box of Int32", "Box(Of Integer)", "Box`1", "True", "False"]);
+}
+
+#[test]
+fn test_generic_method_explicit_and_inferred() {
+    let code = r#"
+Class Widget
+    Public Name As String = "widget"
+End Class
+
+Function Echo(Of T)(x As T) As String
+    Dim d As T
+    Return GetType(T).Name & " default=" & d & " value=" & x
+End Function
+
+Function Make(Of T As New)() As T
+    Return New T()
+End Function
+
+Sub Main()
+    Console.WriteLine(Echo(Of Integer)(3))
+    Console.WriteLine(Echo("s"))
+    Console.WriteLine(Make(Of Widget)().Name)
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["Int32 default=0 value=3", "String default= value=s", "widget"]);
+}
+
+#[test]
+fn test_inherited_generic_base_and_structure() {
+    let code = r#"
+Public Class Box(Of T)
+    Public Value As T
+End Class
+
+Public Class IntBox
+    Inherits Box(Of Integer)
+End Class
+
+Public Structure Pair(Of TKey, TValue)
+    Public Key As TKey
+    Public Value As TValue
+End Structure
+
+Function Unwrap(Of T)(b As Box(Of T)) As String
+    Return GetType(T).Name
+End Function
+
+Sub Main()
+    Dim ib As New IntBox()
+    Console.WriteLine(ib.Value)
+    Console.WriteLine(TypeOf ib Is Box(Of Integer))
+    Console.WriteLine(Unwrap(ib))
+    Dim p As New Pair(Of String, Integer)()
+    Console.WriteLine(p.Value)
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["0", "True", "Int32", "0"]);
+}
+
+#[test]
+fn test_casts_check_type_arguments() {
+    let code = r#"
+Public Class Box(Of T)
+End Class
+
+Sub Main()
+    Dim o As Object = New Box(Of String)()
+    Console.WriteLine(TryCast(o, Box(Of Integer)) Is Nothing)
+    Console.WriteLine(TryCast(o, Box(Of String)) Is Nothing)
+    Dim b = CType(o, Box(Of Integer))
+End Sub
+"#;
+    let err = run(code).expect_err("expected an invalid cast");
+    let (kind, message) = exception_message(err);
+    assert_eq!(kind, "InvalidCastException");
+    assert_eq!(message, "Unable to cast object of type 'Box(Of String)' to type 'Box(Of Integer)'.");
+}
+
+#[test]
+fn test_constraint_violations_are_reported() {
+    let code = r#"
+Public Interface IShape
+End Interface
+
+Public Class Circle
+    Implements IShape
+End Class
+
+Public Class Repository(Of T As {Class, New})
+End Class
+
+Function Count(Of T As IShape)(x As T) As Integer
+    Return 1
+End Function
+
+Sub Main()
+    Console.WriteLine(Count(New Circle()))
+    Dim r As New Repository(Of Integer)()
+End Sub
+"#;
+    let err = run(code).expect_err("expected a constraint violation");
+    let (kind, message) = exception_message(err);
+    assert_eq!(kind, "ArgumentException");
+    assert_eq!(message, "Type argument 'Integer' does not satisfy the 'Class' constraint for type parameter 'T' of 'Repository'");
+
+    let code = r#"
+Public Interface IShape
+End Interface
+
+Public Class Plain
+End Class
+
+Function Count(Of T As IShape)(x As T) As Integer
+    Return 1
+End Function
+
+Sub Main()
+    Count(Of Plain)(New Plain())
+End Sub
+"#;
+    let (_, message) = exception_message(run(code).expect_err("expected a constraint violation"));
+    assert_eq!(message, "Type argument 'Plain' does not satisfy the 'IShape' constraint for type parameter 'T' of 'Count'");
+}
@@ -0,0 +1,201 @@
+//! Tests for the integral types: SByte, Short, UShort, UInteger and ULong
+//! values, the binary-operator result-type table, shifts, checked arithmetic
+//! and the CShort/CUInt/CULng family of conversions.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+fn main_with(body: &str) -> String {
+    format!("Module M\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", body)
+}
+
+#[test]
+fn test_declared_types_and_limits() {
+    let code = main_with(r#"
+        Dim s As Short = 100
+        Dim us As UShort = 65535US
+        Dim ui As UInteger = 4000000000UI
+        Dim ul As ULong = 18446744073709551615UL
+        Dim sb As SByte
+        Console.WriteLine(TypeName(s) & " " & TypeName(us) & " " & TypeName(ui) & " " & TypeName(ul) & " " & TypeName(sb))
+        Console.WriteLine(Short.MinValue & " " & UShort.MaxValue & " " & UInteger.MaxValue & " " & ULong.MaxValue & " " & SByte.MinValue)
+        Console.WriteLine(us.GetType().Name & " " & ul.GetType().FullName & " " & (TypeOf CObj(ui) Is UInteger))
+        Dim n As Short
+        n = 3.5
+        Console.WriteLine(n & " " & TypeName(n))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "Short UShort UInteger ULong SByte",
+        "-32768 65535 4294967295 18446744073709551615 -128",
+        "UInt16 System.UInt64 True",
+        "4 Short",
+    ]);
+}
+
+#[test]
+fn test_result_type_table() {
+    let code = main_with(r#"
+        Dim b As Byte = 1
+        Dim sb As SByte = 1
+        Dim s As Short = 1
+        Dim us As UShort = 1
+        Dim ui As UInteger = 1
+        Dim ul As ULong = 1
+        Console.WriteLine(TypeName(b + b) & " " & TypeName(b + sb) & " " & TypeName(s + us) & " " & TypeName(us + us))
+        Console.WriteLine(TypeName(ui + 1) & " " & TypeName(ui + 1L) & " " & TypeName(ul + ul) & " " & TypeName(ul + 1L))
+        Console.WriteLine(TypeName(True + True) & " " & TypeName(b * 2) & " " & TypeName(s / s) & " " & TypeName(us \ us))
+        Console.WriteLine(TypeName(-us) & " " & TypeName(-ui) & " " & TypeName(-ul) & " " & TypeName(5S And 3S))
+        Console.WriteLine(ul * 2UL + ui & " " & (Not 0US) & " " & (ul > 0UL) & " " & (18446744073709551615UL = ULong.MaxValue))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "Byte Short Integer UShort",
+        "Long Long ULong Decimal",
+        "Short Integer Double UShort",
+        "Integer Long Decimal Short",
+        "3 65535 True True",
+    ]);
+}
+
+#[test]
+fn test_shifts() {
+    let code = main_with(r#"
+        Dim i As Integer = 5
+        Dim b As Byte = 200
+        Dim sh As Short = -1
+        Dim ui As UInteger = 4294967295UI
+        Console.WriteLine((1 << 31) & " " & TypeName(1 << 31) & " " & (i << 33) & " " & TypeName(i << 33) & " " & (-8 >> 1))
+        Console.WriteLine((b << 1) & " " & (b << 9) & " " & TypeName(b << 1) & " " & (sh >> 20) & " " & TypeName(sh >> 20))
+        Console.WriteLine((1L << 63) & " " & (1L << 64) & " " & (ui >> 28) & " " & TypeName(ui >> 28))
+        Console.WriteLine((2.6 << 1) & " " & TypeName(2.6 << 1) & " " & TypeName(True << 1))
+        i <<= 33
+        Console.WriteLine(i & " " & TypeName(i))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "-2147483648 Integer 10 Integer -4",
+        "144 144 Byte -1 Short",
+        "-9223372036854775808 1 15 UInteger",
+        "6 Long Short",
+        "10 Integer",
+    ]);
+}
+
+#[test]
+fn test_overflow_is_catchable() {
+    let code = main_with(r#"
+        Dim i As Integer = 2147483647
+        Try
+            i += 1
+        Catch ex As System.OverflowException
+            Console.WriteLine(ex.Message)
+        End Try
+        Console.WriteLine(i)
+        Dim b As Byte = 255
+        Try
+            b = b + 1
+        Catch ex As OverflowException
+            Console.WriteLine("Byte")
+        End Try
+        Try
+            Dim l As Long = Long.MaxValue
+            l *= 2
+        Catch ex As OverflowException
+            Console.WriteLine("Long")
+        End Try
+        Try
+            Dim m As Integer = Integer.MinValue
+            m = -m
+        Catch ex As OverflowException
+            Console.WriteLine("Negate")
+        End Try
+        Try
+            Dim u As UInteger = 0
+            u -= 1UI
+        Catch ex As OverflowException
+            Console.WriteLine("UInteger")
+        End Try
+        Try
+            Console.WriteLine(Math.Abs(Short.MinValue))
+        Catch ex As OverflowException
+            Console.WriteLine("Abs")
+        End Try
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "Arithmetic operation resulted in an overflow.",
+        "2147483647",
+        "Byte",
+        "Long",
+        "Negate",
+        "UInteger",
+        "Abs",
+    ]);
+}
+
+#[test]
+fn test_conversions() {
+    let code = main_with(r#"
+        Console.WriteLine(CShort("123") & " " & CUInt(3.5) & " " & CULng(2.5) & " " & CInt(-2.5) & " " & CByte("&HFF"))
+        Console.WriteLine(TypeName(CShort(1)) & " " & TypeName(CUShort(1)) & " " & TypeName(CUInt(1)) & " " & TypeName(CULng(1)) & " " & TypeName(CSByte(1)))
+        Console.WriteLine(Convert.ToUInt16(7) + Convert.ToInt16("FFFF", 16))
+        Try
+            Console.WriteLine(CShort(40000))
+        Catch ex As OverflowException
+            Console.WriteLine("CShort")
+        End Try
+        Try
+            Console.WriteLine(CUInt(-1))
+        Catch ex As OverflowException
+            Console.WriteLine("CUInt")
+        End Try
+        Try
+            Console.WriteLine(CULng("abc"))
+        Catch ex As InvalidCastException
+            Console.WriteLine(ex.Message)
+        End Try
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "123 4 2 -2 255",
+        "Short UShort UInteger ULong SByte",
+        "6",
+        "CShort",
+        "CUInt",
+        "Conversion from string \"abc\" to type 'ULong' is not valid.",
+    ]);
+}
+
+#[test]
+fn test_loops_and_overloads() {
+    let code = r#"
+Module M
+    Function Pick(x As Short) As String
+        Return "Short"
+    End Function
+    Function Pick(x As Integer) As String
+        Return "Integer"
+    End Function
+    Function Pick(x As Long) As String
+        Return "Long"
+    End Function
+
+    Sub Main()
+        For i As Short = 1 To 2
+            Console.WriteLine(TypeName(i) & " " & i)
+        Next
+        Dim b As Byte = 10
+        Console.WriteLine(Pick(1S) & " " & Pick(b) & " " & Pick(5UI) & " " & Pick(3))
+        Console.WriteLine(7.5 Mod 2 & " " & Math.Max(3US, 7US) & " " & TypeName(Math.Abs(-5S)))
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["Short 1", "Short 2", "Short Short Long Integer", "1.5 7 Short"]);
+}
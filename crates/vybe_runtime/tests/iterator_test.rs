@@ -0,0 +1,313 @@
+//! Tests for Iterator Functions with `Yield` and lazy enumeration.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+#[test]
+fn test_for_each_over_iterator_function() {
+    let code = r#"
+Module M
+    Iterator Function Evens(max As Integer) As IEnumerable(Of Integer)
+        For i As Integer = 0 To max Step 2
+            Yield i
+        Next
+    End Function
+
+    Sub Main()
+        For Each n In Evens(6)
+            Console.WriteLine(n)
+        Next
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["0", "2", "4", "6"]);
+}
+
+#[test]
+fn test_body_runs_lazily_between_yields() {
+    let code = r#"
+Module M
+    Iterator Function Letters() As IEnumerable(Of String)
+        Console.WriteLine("start")
+        Yield "a"
+        Console.WriteLine("after a")
+        Yield "b"
+        Console.WriteLine("done")
+    End Function
+
+    Sub Main()
+        Dim seq = Letters()
+        Console.WriteLine("called")
+        For Each s In seq
+            Console.WriteLine(s)
+        Next
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["called", "start", "a", "after a", "b", "done"]);
+}
+
+#[test]
+fn test_infinite_sequence_with_linq_operators() {
+    let code = r#"
+Module M
+    Iterator Function Naturals() As IEnumerable(Of Integer)
+        Dim n As Integer = 1
+        Do
+            Yield n
+            n += 1
+        Loop
+    End Function
+
+    Sub Main()
+        Dim threes = Naturals().Where(Function(v) v Mod 3 = 0).Take(3)
+        For Each x In threes
+            Console.WriteLine(x)
+        Next
+        Dim fifth = Naturals().Skip(4).First()
+        Console.WriteLine(fifth)
+        Dim small = Naturals().TakeWhile(Function(v) v < 5).Count()
+        Console.WriteLine(small)
+        Dim found = Naturals().Any(Function(v) v > 1000)
+        Console.WriteLine(found)
+        Dim squares = Naturals().Select(Function(v) v * v).Take(3)
+        Console.WriteLine(String.Join(",", squares))
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["3", "6", "9", "5", "4", "True", "1,4,9"]);
+}
+
+#[test]
+fn test_exit_for_runs_pending_finally() {
+    let code = r#"
+Module M
+    Iterator Function Guarded() As IEnumerable(Of String)
+        Try
+            Yield "first"
+            Yield "second"
+        Finally
+            Console.WriteLine("cleanup")
+        End Try
+    End Function
+
+    Sub Main()
+        For Each s In Guarded()
+            Console.WriteLine(s)
+            Exit For
+        Next
+        Console.WriteLine("after loop")
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["first", "cleanup", "after loop"]);
+}
+
+#[test]
+fn test_exceptions_inside_iterator_are_caught() {
+    let code = r#"
+Module M
+    Iterator Function Values() As IEnumerable(Of Integer)
+        Try
+            Yield 1
+            Throw New InvalidOperationException("boom")
+        Catch ex As InvalidOperationException
+            Console.WriteLine("caught " & ex.Message)
+        End Try
+        Yield 2
+    End Function
+
+    Iterator Function Broken() As IEnumerable(Of Integer)
+        Yield 1
+        Throw New ArgumentException("bad")
+    End Function
+
+    Sub Main()
+        For Each v In Values()
+            Console.WriteLine(v)
+        Next
+        Try
+            For Each v In Broken()
+                Console.WriteLine(v)
+            Next
+        Catch ex As ArgumentException
+            Console.WriteLine("outer " & ex.Message)
+        End Try
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["1", "caught boom", "2", "1", "outer bad"]);
+}
+
+#[test]
+fn test_nested_loops_and_iterator_methods() {
+    let code = r#"
+Class Bag
+    Private items() As String = {"x", "y", "z"}
+
+    Public Iterator Function Visible() As IEnumerable(Of String)
+        For Each s In Me.items
+            If s = "y" Then Continue For
+            Yield s.ToUpper()
+        Next
+    End Function
+End Class
+
+Module M
+    Iterator Function Doubled(src As IEnumerable(Of String)) As IEnumerable(Of String)
+        For Each v In src
+            Select Case v
+                Case "Z"
+                    Exit For
+                Case Else
+                    Yield v & v
+            End Select
+        Next
+        Dim k As Integer = 0
+        While k < 2
+            k += 1
+            Yield "w" & k
+        End While
+    End Function
+
+    Sub Main()
+        Dim b As New Bag()
+        For Each s In Doubled(b.Visible())
+            Console.WriteLine(s)
+        Next
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["XX", "w1", "w2"]);
+}
+
+#[test]
+fn test_query_and_reenumeration() {
+    let code = r#"
+Module M
+    Iterator Function Evens(max As Integer) As IEnumerable(Of Integer)
+        For i As Integer = 0 To max Step 2
+            Yield i
+        Next
+    End Function
+
+    Sub Main()
+        Dim big = From v In Evens(10) Where v > 4 Select v * 10
+        For Each v In big
+            Console.WriteLine(v)
+        Next
+        Dim seq = Evens(2)
+        For Each a In seq
+            Console.WriteLine("first " & a)
+        Next
+        Console.WriteLine(String.Join(",", seq))
+        Console.WriteLine(seq.ToList().Count)
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["60", "80", "100", "first 0", "first 2", "0,2", "2"]);
+}
+
+#[test]
+fn test_yield_outside_iterator_is_an_error() {
+    let code = r#"
+Module M
+    Sub Main()
+        Yield 1
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert!(matches!(err.without_location(), RuntimeError::Custom(m) if m.contains("Iterator Function")));
+}
+
+#[test]
+fn test_yield_inside_with_using_and_synclock() {
+    let code = r#"
+Class Resource
+    Public Name As String
+
+    Public Sub New(n As String)
+        Name = n
+    End Sub
+
+    Public Sub Dispose()
+        Console.WriteLine("dispose " & Name)
+    End Sub
+End Class
+
+Class Person
+    Public Name As String
+End Class
+
+Module M
+    Iterator Function Items(p As Person) As IEnumerable(Of String)
+        With p
+            Yield .Name
+            Using r As New Resource("a")
+                Yield r.Name
+                SyncLock r
+                    Yield "locked"
+                End SyncLock
+            End Using
+            Yield .Name & "!"
+        End With
+    End Function
+
+    Sub Main()
+        Dim p As New Person()
+        p.Name = "iter"
+        Dim caller As New Person()
+        caller.Name = "caller"
+        With caller
+            For Each s In Items(p)
+                Console.WriteLine(s & " " & .Name)
+            Next
+            ' Leaving early still disposes the resource
+            For Each s In Items(p)
+                If s = "a" Then Exit For
+            Next
+            Console.WriteLine(.Name)
+        End With
+    End Sub
+End Module
+"#;
+    assert_eq!(
+        run(code).unwrap(),
+        vec!["iter caller", "a caller", "locked caller", "dispose a", "iter! caller", "dispose a", "caller"]
+    );
+}
+
+#[test]
+fn test_for_loop_variable_keeps_its_declared_type() {
+    let code = r#"
+Module M
+    Iterator Function Values() As IEnumerable(Of String)
+        For i As Long = 1 To 2
+            Yield TypeName(i)
+        Next
+        For d As Double = 1 To 1
+            Yield TypeName(d)
+        Next
+    End Function
+
+    Sub Main()
+        For Each s In Values()
+            Console.WriteLine(s)
+        Next
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["Long", "Long", "Double"]);
+}
@@ -0,0 +1,173 @@
+//! Tests for the statements kept from VB6: `GoSub`/`Return` and
+//! `On ... GoTo`/`GoSub`, `Mid` assignment, `LSet`/`RSet`, `Erase`, and
+//! `Type ... End Type` records with `String * n` fields read and written
+//! with `Get` and `Put` on `Random` and `Binary` files.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+const TYPES: &str = r#"
+Type Person
+    Name As String * 10
+    Age As Integer
+    Scores(2) As Short
+End Type
+
+Type Widths
+    I As Integer
+    L As Long
+End Type
+"#;
+
+fn program(body: &str) -> String {
+    format!("{}\nModule M\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", TYPES, body)
+}
+
+/// A path in the temp directory for a test's data file, removed first.
+fn temp_file(name: &str) -> String {
+    let path = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_file(&path);
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_gosub_and_on_goto() {
+    let code = program(r#"
+        Dim total As Integer = 0
+        For i = 1 To 3
+            On i GoSub AddOne, AddTen
+        Next
+        Console.WriteLine("total " & total)
+        GoSub AddTen
+        Console.WriteLine("total " & total)
+        Dim k As Double = 2.5
+        On k GoTo First, Second
+        Console.WriteLine("not reached")
+First:
+        Console.WriteLine("first")
+Second:
+        Console.WriteLine("second")
+        On 9 GoTo First
+        Exit Sub
+AddOne:
+        total += 1
+        Return
+AddTen:
+        total += 10
+        Return
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["total 11", "total 21", "second"]);
+
+    let err = run(&program("        On -1 GoTo Here\nHere:")).unwrap_err();
+    assert_eq!(err.without_location().to_string(), "Procedure call or argument is not valid.");
+}
+
+#[test]
+fn test_mid_lset_rset_and_erase() {
+    let code = program(r#"
+        Dim s As String = "abcdef"
+        Mid(s, 3, 2) = "XYZ"
+        Console.WriteLine(s)
+        Mid$(s, 5) = "12345"
+        Console.WriteLine(s)
+        Dim f As String = "12345678"
+        LSet f = "ab"
+        Console.WriteLine("[" & f & "]")
+        RSet f = "ab"
+        Console.WriteLine("[" & f & "]")
+        Dim p As Person
+        p.Name = "Alexander the Great"
+        RSet p.Name = "Al"
+        Console.WriteLine("[" & p.Name & "]")
+        Dim arr() As Integer = {1, 2}
+        Erase arr
+        Console.WriteLine(arr Is Nothing)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["abXYef", "abXY12", "[ab      ]", "[      ab]", "[        Al]", "True"]);
+
+    let err = run(&program("        Dim s As String = \"abc\"\n        Mid(s, 0) = \"x\"")).unwrap_err();
+    assert_eq!(err.without_location().to_string(), "Argument 'Start' must be greater than zero.");
+}
+
+#[test]
+fn test_type_records_in_random_file() {
+    let path = temp_file("vybe_legacy_random.dat");
+    let code = program(&format!(r#"
+        Dim p As Person
+        p.Name = "Alexander the Great"
+        p.Age = 33
+        p.Scores(1) = 7
+        Console.WriteLine("[" & p.Name & "] " & Len(p))
+        Open "{}" For Random As #1 Len = Len(p)
+        Put #1, 1, p
+        p.Name = "Bob"
+        p.Age = 40
+        Put #1, , p
+        Console.WriteLine(LOF(1) \ Len(p))
+        Dim q As Person
+        Get #1, 1, q
+        Console.WriteLine("[" & q.Name & "] " & q.Age & " " & q.Scores(1))
+        Get #1, 2, q
+        Console.WriteLine(Trim(q.Name) & " " & q.Age & " " & EOF(1))
+        Close #1
+    "#, path));
+    assert_eq!(run(&code).unwrap(), vec!["[Alexander ] 18", "2", "[Alexander ] 33 7", "Bob 40 True"]);
+}
+
+#[test]
+fn test_binary_file_get_and_put() {
+    let path = temp_file("vybe_legacy_binary.dat");
+    let code = program(&format!(r#"
+        Open "{}" For Binary As #2
+        Put #2, , "hello"
+        Dim x As Integer = 300
+        Put #2, , x
+        Console.WriteLine(LOF(2))
+        Dim s As String = Space(5)
+        Get #2, 1, s
+        Dim y As Integer
+        Get #2, , y
+        Console.WriteLine(s & " " & y & " " & Loc(2))
+        Close #2
+    "#, path));
+    assert_eq!(run(&code).unwrap(), vec!["7", "hello 300 7"]);
+}
+
+#[test]
+fn test_record_fields_use_vb6_widths() {
+    let path = temp_file("vybe_legacy_widths.dat");
+    let code = program(&format!(r#"
+        Dim w As Widths
+        w.I = -2
+        w.L = 100000
+        Open "{}" For Binary As #3
+        Put #3, , w
+        Console.WriteLine(Len(w) & " " & LOF(3))
+        Dim low As Byte, high As Byte
+        Get #3, 1, low
+        Get #3, 3, high
+        Console.WriteLine(low & " " & high)
+        Dim back As Widths
+        Get #3, 1, back
+        Console.WriteLine(back.I & " " & back.L & " " & TypeName(back.L))
+        w.L = 3000000000
+        Try
+            Put #3, 1, w
+        Catch ex As OverflowException
+            Console.WriteLine("overflow")
+        End Try
+        Close #3
+    "#, path));
+    assert_eq!(run(&code).unwrap(), vec!["6 6", "254 160", "-2 100000 Long", "overflow"]);
+}
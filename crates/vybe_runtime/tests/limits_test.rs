@@ -0,0 +1,298 @@
+//! Tests for execution limits and cancellation: a script that runs too long,
+//! too far, grows a collection too large or prints too much stops with an
+//! error its own handlers can't catch, under either engine.
+
+use std::time::{Duration, Instant};
+
+use vybe_runtime::{Engine, ExecutionLimits, Interpreter, Limit, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run_with(engine: Engine, limits: &ExecutionLimits, code: &str) -> (Result<(), RuntimeError>, Vec<String>) {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.engine = engine;
+    interp.set_limits(limits.clone());
+    let result = interp.run(&program).and_then(|_| interp.call_procedure(&Identifier::new("Main"), &[]).map(|_| ()));
+    let output = interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect();
+    (result, output)
+}
+
+/// The limit `code` runs past under each engine; both must stop at the same
+/// point.
+fn limit_hit(limits: ExecutionLimits, code: &str) -> (Limit, Vec<String>) {
+    let (expected, expected_output) = run_with(Engine::TreeWalker, &limits, code);
+    let (actual, output) = run_with(Engine::Bytecode, &limits, code);
+    assert_eq!(expected_output, output);
+    let limit = |result: Result<(), RuntimeError>| match result {
+        Err(e) => match e.without_location() {
+            RuntimeError::LimitExceeded(limit) => *limit,
+            other => panic!("expected a limit, got {:?}", other),
+        },
+        Ok(()) => panic!("expected a limit, but the script finished"),
+    };
+    let limit = (limit(expected), limit(actual));
+    assert_eq!(limit.0, limit.1);
+    (limit.1, output)
+}
+
+#[test]
+fn test_step_limit_stops_endless_loops() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim i = 0
+        Try
+            While True
+                i += 1
+                If i = 10 Then Console.WriteLine("ten")
+            End While
+        Catch ex As Exception
+            Console.WriteLine("caught " & ex.Message)
+        Finally
+            Console.WriteLine("finally " & i)
+        End Try
+    End Sub
+End Module
+"#;
+    let limits = ExecutionLimits { max_steps: Some(100), ..Default::default() };
+    let (limit, output) = limit_hit(limits.clone(), code);
+    assert_eq!(limit, Limit::Steps(100));
+    assert_eq!(output, vec!["ten", "finally 100"]);
+
+    // On Error Resume Next doesn't skip past it either
+    let code = r#"
+Module M
+    Sub Tick(n As Integer)
+        If n Mod 10 = 0 Then Console.WriteLine(n)
+    End Sub
+
+    Sub Main()
+        On Error Resume Next
+        Dim n = 0
+        Do
+            n += 1
+            Tick(n)
+        Loop
+        Console.WriteLine("after")
+    End Sub
+End Module
+"#;
+    let (limit, output) = limit_hit(limits, code);
+    assert_eq!(limit, Limit::Steps(100));
+    assert_eq!(output, vec!["10", "20", "30", "40", "50"]);
+    assert_eq!(Limit::Steps(100).to_string(), "more than 100 steps");
+}
+
+#[test]
+fn test_timeout() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim n As Long = 0
+        Do
+            n += 1
+        Loop
+    End Sub
+End Module
+"#;
+    let timeout = Duration::from_millis(50);
+    let start = Instant::now();
+    let (limit, _) = limit_hit(ExecutionLimits { timeout: Some(timeout), ..Default::default() }, code);
+    assert_eq!(limit, Limit::Timeout(timeout));
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_each_run_gets_the_whole_timeout() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim n As Long = 0
+        For i = 1 To 100
+            n += i
+        Next
+        Console.WriteLine(n)
+    End Sub
+End Module
+"#;
+    let program = parse_program(code).expect("Parse error");
+    for engine in [Engine::TreeWalker, Engine::Bytecode] {
+        let mut interp = Interpreter::new();
+        interp.engine = engine;
+        interp.set_limits(ExecutionLimits { timeout: Some(Duration::from_millis(200)), max_steps: Some(150), ..Default::default() });
+        // Configured well before the script runs, and run more than once
+        std::thread::sleep(Duration::from_millis(300));
+        interp.run(&program).unwrap();
+        for _ in 0..3 {
+            interp.call_procedure(&Identifier::new("Main"), &[]).unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(interp.side_effects.len(), 3);
+    }
+}
+
+#[test]
+fn test_cancel_from_another_thread() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim items As New List(Of Integer)
+        For Each item In items
+        Next
+        Do While True
+            Try
+                items.Add(1)
+                items.Clear()
+            Catch ex As Exception
+            End Try
+        Loop
+    End Sub
+End Module
+"#;
+    let program = parse_program(code).expect("Parse error");
+    for engine in [Engine::TreeWalker, Engine::Bytecode] {
+        let mut interp = Interpreter::new();
+        interp.engine = engine;
+        let handle = interp.cancel_handle();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            handle.cancel();
+        });
+        interp.run(&program).unwrap();
+        let err = interp.call_procedure(&Identifier::new("Main"), &[]).unwrap_err();
+        canceller.join().unwrap();
+        assert!(matches!(err.without_location(), RuntimeError::Cancelled));
+        assert!(err.is_abort());
+
+        // Still cancelled until the handle is reset
+        assert!(interp.call_procedure(&Identifier::new("Main"), &[]).unwrap_err().is_abort());
+        interp.cancel_handle().reset();
+        interp.set_limits(ExecutionLimits { max_steps: Some(10), ..Default::default() });
+        let err = interp.call_procedure(&Identifier::new("Main"), &[]).unwrap_err();
+        assert!(matches!(err.without_location(), RuntimeError::LimitExceeded(Limit::Steps(10))));
+    }
+}
+
+#[test]
+fn test_collection_and_output_limits() {
+    let limits = ExecutionLimits { max_collection_len: Some(1000), max_output_bytes: Some(40), ..Default::default() };
+    let code = r#"
+Module M
+    Sub Main()
+        Dim fits(999) As Integer
+        Dim names As New Dictionary(Of String, Integer)
+        names.Add("a", 1)
+        Dim items As New List(Of Integer)
+        For i = 1 To 5000
+            items.Add(i)
+        Next
+    End Sub
+End Module
+"#;
+    assert_eq!(limit_hit(limits.clone(), code).0, Limit::CollectionLen(1000));
+    let arrays = [
+        "        Dim big(1000) As Integer\n",
+        "        Dim grid(99, 10) As Integer\n",
+        "        Dim grown(10) As Integer\n        ReDim Preserve grown(2000)\n",
+        "        Dim grown(10, 10) As Integer\n        ReDim grown(10, 2000)\n",
+        // Dimensions whose product overflows
+        "        Dim huge(65536, 65536, 65536, 65536) As Integer\n",
+        "        Dim grown(10) As Integer\n        ReDim grown(65536, 65536, 65536, 65536)\n",
+        "        Dim q As New Queue(Of Integer)\n        Do\n            q.Enqueue(1)\n        Loop\n",
+    ];
+    for array in arrays {
+        let code = code.replace("        Dim items As New List(Of Integer)\n", array);
+        assert_eq!(limit_hit(limits.clone(), &code).0, Limit::CollectionLen(1000), "{}", array);
+    }
+
+    let code = r#"
+Module M
+    Sub Main()
+        For i = 1 To 100
+            Console.WriteLine("line " & i)
+        Next
+    End Sub
+End Module
+"#;
+    let (limit, output) = limit_hit(limits, code);
+    assert_eq!(limit, Limit::OutputBytes(40));
+    assert_eq!(output, vec!["line 1", "line 2", "line 3", "line 4", "line 5"]);
+
+    // No limits by default
+    let (result, output) = run_with(Engine::Bytecode, &ExecutionLimits::default(), code);
+    assert!(result.is_ok());
+    assert_eq!(output.len(), 100);
+}
+
+#[test]
+fn test_collections_grown_outside_add() {
+    let limits = ExecutionLimits { max_collection_len: Some(1000), ..Default::default() };
+    let bodies = [
+        // Each new key grows a dictionary, not just Add
+        "        Dim d As New Dictionary(Of Integer, Integer)\n        For i = 1 To 5000\n            d(i) = i\n        Next\n",
+        "        Dim s = StrDup(100000, \",\")\n        Dim parts = Split(s, \",\")\n",
+        "        Dim s = StrDup(100000, \",\")\n        Dim parts = s.Split(\",\")\n",
+        "        Dim xs(999) As Integer\n        Dim pairs = From a In xs, b In xs Select a + b\n",
+        "        Dim xs(999) As Integer\n        Dim pairs = From a In xs Join b In xs On a Equals b Select a\n",
+    ];
+    for body in bodies {
+        let code = format!("Module M\n    Sub Main()\n{}    End Sub\nEnd Module\n", body);
+        assert_eq!(limit_hit(limits.clone(), &code).0, Limit::CollectionLen(1000), "{}", body);
+    }
+    // Updating an existing key doesn't grow the dictionary
+    let code = "Module M\n    Sub Main()\n        Dim d As New Dictionary(Of Integer, Integer)\n        For i = 1 To 5000\n            d(i Mod 10) = i\n        Next\n    End Sub\nEnd Module\n";
+    assert!(run_with(Engine::TreeWalker, &limits, code).0.is_ok());
+}
+
+#[test]
+fn test_string_limit() {
+    let limits = ExecutionLimits { max_string_len: Some(10_000), ..Default::default() };
+    let bodies = [
+        "        Dim s = \"x\"\n        Do\n            s = s & s\n        Loop\n",
+        "        Dim s = \"x\"\n        Do\n            s &= s\n        Loop\n",
+        "        Dim s = StrDup(20000, \"x\")\n",
+        "        Dim s = Space(5000)\n        Dim t = s.Replace(\" \", \"ab\") & \"!\"\n",
+    ];
+    for body in bodies {
+        let code = format!("Module M\n    Sub Main()\n{}    End Sub\nEnd Module\n", body);
+        assert_eq!(limit_hit(limits.clone(), &code).0, Limit::StringLen(10_000), "{}", body);
+    }
+    assert_eq!(Limit::StringLen(10).to_string(), "a string of more than 10 characters");
+}
+
+#[test]
+fn test_sleep_keeps_to_the_deadline() {
+    let code = r#"
+Module M
+    Sub Main()
+        Console.WriteLine("start")
+        Thread.Sleep(6000)
+        Console.WriteLine("woke")
+    End Sub
+End Module
+"#;
+    let timeout = Duration::from_millis(200);
+    let start = Instant::now();
+    let (limit, output) = limit_hit(ExecutionLimits { timeout: Some(timeout), ..Default::default() }, code);
+    assert_eq!(limit, Limit::Timeout(timeout));
+    assert_eq!(output, vec!["start"]);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // And wakes up when cancelled
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    let handle = interp.cancel_handle();
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+    });
+    let start = Instant::now();
+    interp.run(&program).unwrap();
+    let err = interp.call_procedure(&Identifier::new("Main"), &[]).unwrap_err();
+    canceller.join().unwrap();
+    assert!(matches!(err.without_location(), RuntimeError::Cancelled));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,166 @@
+//! Tests for named arguments: `OpenFile(path, readOnly:=True)` against user
+//! procedures, constructors, methods and builtins, with omitted Optional
+//! parameters taking their defaults and errors for bad names.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+fn module_with(members: &str, body: &str) -> String {
+    format!("Module M\n{}\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", members, body)
+}
+
+const OPEN_FILE: &str = r#"
+    Function OpenFile(path As String, Optional readOnly As Boolean = False, Optional mode As String = "text") As String
+        Return path & " " & readOnly & " " & mode
+    End Function
+    Sub Greet(name As String, Optional greeting As String = "Hello")
+        Console.WriteLine(greeting & ", " & name)
+    End Sub
+"#;
+
+#[test]
+fn test_user_procedures() {
+    let code = module_with(OPEN_FILE, r#"
+        Console.WriteLine(OpenFile("a.txt", readOnly:=True))
+        Console.WriteLine(OpenFile("a.txt", mode:="bin"))
+        Console.WriteLine(OpenFile(mode:="x", path:="b"))
+        Console.WriteLine(OpenFile("p", readOnly:=True, "m"))
+        Greet(greeting:="Hi", name:="Ann")
+        Greet "Bo", Greeting:="Yo"
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "a.txt True text",
+        "a.txt False bin",
+        "b False x",
+        "p True m",
+        "Hi, Ann",
+        "Yo, Bo",
+    ]);
+}
+
+#[test]
+fn test_constructors_and_methods() {
+    let code = module_with(r#"
+    Class Box
+        Public W As Integer
+        Sub New(Optional width As Integer = 1, Optional height As Integer = 2)
+            W = width * 10 + height
+        End Sub
+        Function Area(Optional scale As Integer = 1, Optional extra As Integer = 0) As Integer
+            Return W * scale + extra
+        End Function
+        Function Twice() As Integer
+            Return Me.Area(extra:=1) + Area(scale:=2)
+        End Function
+    End Class
+"#, r#"
+        Dim a As New Box(height:=5)
+        Dim b = New Box(width:=3)
+        Console.WriteLine(a.W & " " & b.W)
+        Console.WriteLine(a.Area(extra:=3) & " " & b.Twice())
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["15 32", "18 97"]);
+}
+
+#[test]
+fn test_builtins() {
+    let code = module_with("", r#"
+        Console.WriteLine(Mid("abcdef", Start:=2, Length:=3) & " " & Replace("a-b-c", "-", "+", Count:=1))
+        Console.WriteLine(InStr(String1:="hello", String2:="l") & " " & Split("a b", Delimiter:=" ").Length)
+        Console.WriteLine(Format(Style:="0.00", Expression:=2.5) & " " & (Pmt(0.01, 12, PV:=1000, Due:=0) < 0))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["bcd a+b-c", "3 2", "2.50 True"]);
+}
+
+#[test]
+fn test_argument_errors() {
+    let code = module_with(OPEN_FILE, r#"
+        Try
+            OpenFile("x", readOnly:=True, ReadOnly:=False)
+        Catch ex As Exception
+            Console.WriteLine(ex.Message)
+        End Try
+        Try
+            OpenFile("x", bogus:=1)
+        Catch ex As Exception
+            Console.WriteLine(ex.Message)
+        End Try
+        Try
+            OpenFile(mode:="x")
+        Catch ex As Exception
+            Console.WriteLine(ex.Message)
+        End Try
+        Try
+            OpenFile(mode:="x", "y")
+        Catch ex As Exception
+            Console.WriteLine(ex.Message)
+        End Try
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "Parameter 'readOnly' already has a matching argument.",
+        "'bogus' is not a parameter of 'OpenFile'.",
+        "Argument not specified for parameter 'path' of 'OpenFile'.",
+        "Named argument expected.",
+    ]);
+
+    let err = run(&module_with("", "        Console.WriteLine(LCase(Str:=\"X\"))")).unwrap_err();
+    assert_eq!(err.without_location().to_string(), "'LCase' does not accept named arguments.");
+}
+
+#[test]
+fn test_overloads_matched_by_name() {
+    let code = module_with(r#"
+    Class Item
+        Sub New(name As String)
+            Console.WriteLine("name " & name)
+        End Sub
+        Sub New(id As Integer)
+            Console.WriteLine("id " & id)
+        End Sub
+        Function Find(name As String) As String
+            Return "find name " & name
+        End Function
+        Function Find(id As Integer) As String
+            Return "find id " & id
+        End Function
+    End Class
+    Sub F(a As String, b As Integer)
+        Console.WriteLine("SI " & a & b)
+    End Sub
+    Sub F(a As Integer, b As String)
+        Console.WriteLine("IS " & a & b)
+    End Sub
+    Sub Bump(ByRef n As Integer, Optional label As String = "int")
+        n += 1
+        Console.WriteLine(label)
+    End Sub
+    Sub Bump(ByRef s As String, Optional times As Integer = 1)
+        s = s & "!" & times
+    End Sub
+"#, r#"
+        F(b:=1, a:="x")
+        F(b:="y", a:=2)
+        Dim count As Integer = 1
+        Dim text As String = "t"
+        Bump(label:="one", n:=count)
+        Bump(times:=2, s:=text)
+        Console.WriteLine(count & " " & text)
+        Dim i As New Item(id:=7)
+        Dim j As New Item(name:="pen")
+        Console.WriteLine(i.Find(name:="cup") & ", " & j.Find(id:=3))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "SI x1", "IS 2y", "one", "2 t!2", "id 7", "name pen", "find name cup, find id 3",
+    ]);
+}
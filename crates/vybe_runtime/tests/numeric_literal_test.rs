@@ -0,0 +1,68 @@
+//! Tests for numeric literals: hex, octal and binary prefixes, digit
+//! separators and type characters.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+#[test]
+fn test_prefixed_literals_and_separators() {
+    let code = r#"
+Module M
+    Sub Main()
+        Console.WriteLine(&HFF & " " & &o17 & " " & &B1010 & " " & &HFFFFFFFF)
+        Console.WriteLine(1_000_000 & " " & &HFF_FF & " " & &B_1111_0000)
+        Console.WriteLine(&HFFFFS & " " & &HFFFFUS & " " & &HFFFFFFFFUI)
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["255 15 10 -1", "1000000 65535 240", "-1 65535 4294967295"]);
+}
+
+#[test]
+fn test_literal_types() {
+    let code = r#"
+Module M
+    Sub Main()
+        Console.WriteLine(TypeName(2147483647) & " " & TypeName(2147483648) & " " & TypeName(&H100000000))
+        Console.WriteLine(TypeName(5L) & " " & TypeName(5&) & " " & TypeName(5%) & " " & TypeName(5I))
+        Console.WriteLine(TypeName(2.5F) & " " & TypeName(2.5!) & " " & TypeName(7R) & " " & TypeName(7#) & " " & TypeName(1.5E3))
+        Console.WriteLine(1.5E3 & " " & .5 & " " & 2E-2)
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec![
+        "Integer Long Long",
+        "Long Long Integer Integer",
+        "Single Single Double Double Double",
+        "1500 0.5 0.02",
+    ]);
+}
+
+#[test]
+fn test_long_bit_manipulation() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim mask As Long = &HFFFFFFFF00L
+        Console.WriteLine(mask)
+        Console.WriteLine((mask And &HFF00L) \ &H100L)
+        Dim big = 3000000000
+        Console.WriteLine(big * 2)
+        Dim s = "a" & 1 & "b"
+        Console.WriteLine(s)
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["1099511627520", "255", "6000000000", "a1b"]);
+}
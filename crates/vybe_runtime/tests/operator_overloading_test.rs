@@ -0,0 +1,269 @@
+//! Tests for user-defined operators and CType conversions on classes and structures.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+const MONEY: &str = r#"
+Public Structure Money
+    Public Amount As Double
+
+    Public Sub New(a As Double)
+        Amount = a
+    End Sub
+
+    Public Shared Operator +(a As Money, b As Money) As Money
+        Return New Money(a.Amount + b.Amount)
+    End Operator
+
+    Public Shared Operator -(a As Money) As Money
+        Return New Money(-a.Amount)
+    End Operator
+
+    Public Shared Operator =(a As Money, b As Money) As Boolean
+        Return a.Amount = b.Amount
+    End Operator
+
+    Public Shared Operator <>(a As Money, b As Money) As Boolean
+        Return a.Amount <> b.Amount
+    End Operator
+
+    Public Shared Widening Operator CType(m As Money) As Double
+        Return m.Amount
+    End Operator
+
+    Public Shared Narrowing Operator CType(d As Double) As Money
+        Return New Money(d)
+    End Operator
+
+    Public Shared Operator IsTrue(m As Money) As Boolean
+        Return m.Amount <> 0
+    End Operator
+
+    Public Shared Operator IsFalse(m As Money) As Boolean
+        Return m.Amount = 0
+    End Operator
+End Structure
+"#;
+
+#[test]
+fn test_structure_arithmetic_and_equality_operators() {
+    let code = format!("{}{}", MONEY, r#"
+Sub Main()
+    Dim a As New Money(2.5)
+    Dim b As New Money(4)
+    Console.WriteLine((a + b).Amount)
+    Console.WriteLine((-a).Amount)
+    Console.WriteLine(a = New Money(2.5))
+    Console.WriteLine(a <> New Money(2.5))
+    Console.WriteLine(a <> b)
+End Sub
+"#);
+    assert_eq!(run(&code).unwrap(), vec!["6.5", "-2.5", "True", "False", "True"]);
+}
+
+#[test]
+fn test_widening_and_narrowing_ctype() {
+    let code = format!("{}{}", MONEY, r#"
+Sub Main()
+    Dim m As New Money(3.5)
+    Console.WriteLine(CType(m, Double))
+    Console.WriteLine(CDbl(m) * 2)
+    Dim back As Money = CType(7.25, Money)
+    Console.WriteLine(back.Amount)
+End Sub
+"#);
+    assert_eq!(run(&code).unwrap(), vec!["3.5", "7", "7.25"]);
+}
+
+#[test]
+fn test_is_true_drives_conditions() {
+    let code = format!("{}{}", MONEY, r#"
+Sub Main()
+    Dim zero As New Money(0)
+    Dim some As New Money(1)
+    If some Then Console.WriteLine("some")
+    If zero Then
+        Console.WriteLine("wrong")
+    Else
+        Console.WriteLine("zero")
+    End If
+    Console.WriteLine((zero AndAlso some).Amount)
+    Console.WriteLine((some OrElse zero).Amount)
+End Sub
+"#);
+    assert_eq!(run(&code).unwrap(), vec!["some", "zero", "0", "1"]);
+}
+
+#[test]
+fn test_class_operators_overload_on_operand_types() {
+    let code = r#"
+Public Class Vector2
+    Public X As Double
+    Public Y As Double
+
+    Public Sub New(x As Double, y As Double)
+        Me.X = x
+        Me.Y = y
+    End Sub
+
+    Public Shared Operator +(a As Vector2, b As Vector2) As Vector2
+        Return New Vector2(a.X + b.X, a.Y + b.Y)
+    End Operator
+
+    Public Shared Operator *(v As Vector2, k As Double) As Vector2
+        Return New Vector2(v.X * k, v.Y * k)
+    End Operator
+
+    Public Shared Operator *(k As Double, v As Vector2) As Vector2
+        Return New Vector2(k * v.X, k * v.Y)
+    End Operator
+End Class
+
+Public Class Point3
+    Inherits Vector2
+
+    Public Sub New()
+        MyBase.New(1, 1)
+    End Sub
+End Class
+
+Sub Main()
+    Dim v = New Vector2(1, 2) + New Vector2(3, 4)
+    Dim half = v * 0.5
+    Dim twice = 2 * v
+    Dim p = New Point3() + New Point3()
+    Console.WriteLine(v.X & " " & v.Y)
+    Console.WriteLine(half.X & " " & half.Y)
+    Console.WriteLine(twice.X & " " & twice.Y)
+    Console.WriteLine(p.X & " " & p.Y)
+    Console.WriteLine(New Vector2(1, 1) Is New Vector2(1, 1))
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["4 6", "2 3", "8 12", "2 2", "False"]);
+}
+
+#[test]
+fn test_compound_assignment_uses_user_operators() {
+    let code = r#"
+Public Structure Money
+    Public Amount As Double
+
+    Public Sub New(a As Double)
+        Amount = a
+    End Sub
+
+    Public Shared Operator +(a As Money, b As Money) As Money
+        Return New Money(a.Amount + b.Amount)
+    End Operator
+
+    Public Shared Operator -(a As Money, b As Money) As Money
+        Return New Money(a.Amount - b.Amount)
+    End Operator
+
+    Public Shared Operator *(a As Money, k As Integer) As Money
+        Return New Money(a.Amount * k)
+    End Operator
+End Structure
+
+Public Class Tag
+    Public Text As String
+
+    Public Sub New(t As String)
+        Text = t
+    End Sub
+
+    Public Shared Operator &(a As Tag, b As String) As Tag
+        Return New Tag(a.Text & "<" & b & ">")
+    End Operator
+End Class
+
+Public Class Wallet
+    Public Cash As Money
+
+    Public Sub New()
+        Cash = New Money(1)
+    End Sub
+End Class
+
+Sub Main()
+    Dim total As New Money(2)
+    For i As Integer = 1 To 3
+        total += New Money(i)
+    Next
+    total -= New Money(0.5)
+    total *= 2
+    Console.WriteLine(total.Amount)
+
+    Dim t As New Tag("a")
+    t &= "b"
+    Console.WriteLine(t.Text)
+
+    Dim w As New Wallet()
+    w.Cash += New Money(4)
+    Console.WriteLine(w.Cash.Amount)
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["15", "a<b>", "5"]);
+}
+
+#[test]
+fn test_implicit_user_conversions() {
+    let code = r#"
+Public Structure Cents
+    Public Value As Long
+
+    Public Shared Widening Operator CType(v As Long) As Cents
+        Dim c As Cents
+        c.Value = v * 100
+        Return c
+    End Operator
+
+    Public Shared Widening Operator CType(c As Cents) As Double
+        Return c.Value / 100
+    End Operator
+
+    Public Shared Narrowing Operator CType(s As String) As Cents
+        Dim c As Cents
+        c.Value = CLng(s)
+        Return c
+    End Operator
+End Structure
+
+Sub Pay(c As Cents)
+    Console.WriteLine("pay " & c.Value)
+End Sub
+
+Function Price() As Cents
+    Return 9L
+End Function
+
+Sub Main()
+    Dim d As Cents = 700L
+    Console.WriteLine(d.Value)
+    d = 3L
+    Console.WriteLine(d.Value)
+    Pay(5L)
+    Console.WriteLine(Price().Value)
+    Dim x As Double = d
+    Console.WriteLine(x)
+    Dim s As Cents = "42"
+    Console.WriteLine(s.Value)
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["70000", "300", "pay 500", "900", "3", "42"]);
+
+    // Option Strict On only applies the widening ones implicitly
+    let err = run(&format!("Option Strict On\n{}", code)).unwrap_err();
+    assert_eq!(err.without_location().to_string(), "Option Strict On disallows implicit conversions from 'String' to 'Cents'.");
+}
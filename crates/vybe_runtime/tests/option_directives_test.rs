@@ -0,0 +1,267 @@
+//! Tests for `Option Explicit`, `Option Strict`, `Option Compare` and `Option Infer`.
+
+use vybe_runtime::{Engine, Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::{parse_program, CompareMode, OptionSettings};
+
+fn run_with(code: &str, defaults: OptionSettings) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.options = defaults;
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    run_with(code, OptionSettings::default())
+}
+
+fn custom_message(err: RuntimeError) -> String {
+    match err.without_location() {
+        RuntimeError::Custom(m) => m.clone(),
+        other => panic!("expected a custom error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_option_explicit_rejects_undeclared_variables() {
+    let code = r#"
+Option Explicit On
+
+Module M
+    Dim total As Integer
+
+    Sub Main()
+        total = 1
+        Dim count As Integer
+        count = total + 1
+        Console.WriteLine(count)
+        countt = 5
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert!(matches!(err.without_location(), RuntimeError::UndefinedVariable(n) if n == "countt"));
+
+    // Without the directive the typo quietly creates a new variable
+    let loose = code.replace("Option Explicit On", "");
+    assert_eq!(run(&loose).unwrap(), vec!["2"]);
+}
+
+#[test]
+fn test_option_explicit_allows_fields_and_inferred_loop_variables() {
+    let code = r#"
+Option Explicit On
+
+Class Counter
+    Private count As Integer
+    Public Property Label As String
+
+    Public Sub Bump()
+        count = count + 1
+        Label = "count " & count
+    End Sub
+End Class
+
+Module M
+    Sub Main()
+        Dim c As New Counter()
+        c.Bump()
+        Console.WriteLine(c.Label)
+        For i = 1 To 2
+            Console.WriteLine(i)
+        Next
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["count 1", "1", "2"]);
+
+    let no_infer = code.replace("Option Explicit On", "Option Explicit On\nOption Infer Off");
+    let err = run(&no_infer).unwrap_err();
+    assert!(matches!(err.without_location(), RuntimeError::UndefinedVariable(n) if n == "i"));
+}
+
+#[test]
+fn test_option_strict_rejects_implicit_narrowing() {
+    let code = r#"
+Option Strict On
+
+Module M
+    Sub Main()
+        Dim small As Byte = 200
+        Dim n As Long = 5
+        Dim ratio As Single = 0.5
+        Dim d As Double = n
+        Console.WriteLine(d + small + ratio)
+        Dim i As Integer = CInt(d)
+        i = d
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert_eq!(custom_message(err), "Option Strict On disallows implicit conversions from 'Double' to 'Integer'.");
+
+    let text = r#"
+Option Strict On
+
+Module M
+    Sub Main()
+        Dim s As String = 42
+    End Sub
+End Module
+"#;
+    let err = run(text).unwrap_err();
+    assert_eq!(custom_message(err), "Option Strict On disallows implicit conversions from 'Integer' to 'String'.");
+}
+
+#[test]
+fn test_option_strict_checks_compound_assignment_arguments_and_returns() {
+    let code = r#"
+Option Strict On
+
+Module M
+    Function Half(n As Integer) As Integer
+        ' A lambda's Return isn't checked against the Function's type
+        Dim f = Function(x As Integer)
+                    Return x / 2
+                End Function
+        Console.WriteLine(f(n))
+        Return n \ 2
+    End Function
+
+    Function Total(count As Integer) As Integer
+        Dim sum As Integer = 0
+        For k = 1 To count
+            sum += k
+        Next
+        Return sum
+    End Function
+
+    Function Tiny() As Byte
+        Return 1
+    End Function
+
+    Sub Main()
+        Dim i As Integer = 1
+        Dim d As Double = 2.5
+        i += 2
+        Console.WriteLine(Half(i) & " " & Total(4) & " " & Tiny())
+        BODY
+    End Sub
+End Module
+"#;
+    let bodies = [
+        "i += d",
+        "Console.WriteLine(Half(d))",
+        "Console.WriteLine(Half(i * 1.5))",
+    ];
+    for body in bodies {
+        let err = run(&code.replace("BODY", body)).unwrap_err();
+        assert_eq!(custom_message(err), "Option Strict On disallows implicit conversions from 'Double' to 'Integer'.", "{}", body);
+    }
+    assert_eq!(run(&code.replace("BODY", "")).unwrap(), vec!["1.5", "1 10 1"]);
+
+    // Return is checked under either engine
+    let returns = code.replace("Return sum", "Return sum * 1.5").replace("BODY", "");
+    for engine in [Engine::TreeWalker, Engine::Bytecode] {
+        let program = parse_program(&returns).expect("Parse error");
+        let mut interp = Interpreter::new();
+        interp.engine = engine;
+        interp.run(&program).unwrap();
+        let err = interp.call_procedure(&Identifier::new("Main"), &[]).unwrap_err();
+        assert_eq!(custom_message(err), "Option Strict On disallows implicit conversions from 'Double' to 'Integer'.");
+    }
+
+    // Without the directive the values are converted
+    let loose = code.replace("Option Strict On", "").replace("BODY", "i += d\n        Console.WriteLine(i)");
+    assert_eq!(run(&loose).unwrap(), vec!["1.5", "1 10 1", "6"]);
+}
+
+#[test]
+fn test_option_strict_rejects_late_binding() {
+    let code = r#"
+Option Strict On
+
+Module M
+    Sub Main()
+        Dim o As Object = "text"
+        Console.WriteLine(o.ToString())
+        Dim s As String = CStr(o)
+        Console.WriteLine(s.Length)
+        Console.WriteLine(o.Length)
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert_eq!(custom_message(err), "Option Strict On disallows late binding.");
+}
+
+#[test]
+fn test_option_strict_with_infer_off_requires_as_clause() {
+    let code = r#"
+Option Strict On
+Option Infer Off
+
+Module M
+    Sub Main()
+        Dim x = 5
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert!(custom_message(err).contains("'As' clause"));
+
+    // With Option Infer On the type comes from the initializer
+    let inferred = code.replace("Option Infer Off", "Option Infer On").replace("Dim x = 5", "Dim x = 5\n        Console.WriteLine(x)");
+    assert_eq!(run(&inferred).unwrap(), vec!["5"]);
+}
+
+#[test]
+fn test_option_compare_text_and_binary() {
+    let code = r#"
+Option Compare Text
+
+Module M
+    Sub Main()
+        Console.WriteLine("apple" = "APPLE")
+        Console.WriteLine("Apple" < "banana")
+        Console.WriteLine("README.TXT" Like "*.txt")
+        Console.WriteLine(InStr("Hello World", "WORLD"))
+        Select Case "YES"
+            Case "yes"
+                Console.WriteLine("matched")
+        End Select
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["True", "True", "True", "7", "matched"]);
+
+    let binary = code.replace("Option Compare Text", "Option Compare Binary");
+    assert_eq!(run(&binary).unwrap(), vec!["False", "True", "False", "0"]);
+}
+
+#[test]
+fn test_project_defaults_apply_unless_file_overrides() {
+    let code = r#"
+Module M
+    Sub Main()
+        Console.WriteLine("a" = "A")
+        Dim d As Double = 1.5
+        Dim i As Integer = d
+    End Sub
+End Module
+"#;
+    let defaults = OptionSettings {
+        strict: Some(true),
+        compare: Some(CompareMode::Binary),
+        ..OptionSettings::default()
+    };
+    let err = run_with(code, defaults).unwrap_err();
+    assert!(custom_message(err).contains("'Double' to 'Integer'"));
+
+    let overridden = format!("Option Strict Off\nOption Compare Text\n{}", code);
+    assert_eq!(run_with(&overridden, defaults).unwrap(), vec!["True"]);
+}
@@ -0,0 +1,276 @@
+//! Tests for overload resolution of user Subs, Functions, methods and constructors.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+#[test]
+fn test_overloads_by_count_and_type() {
+    let code = r#"
+Function Describe(x As Integer) As String
+    Return "Integer " & x
+End Function
+
+Function Describe(s As String) As String
+    Return "String " & s
+End Function
+
+Function Describe(d As Double) As String
+    Return "Double " & d
+End Function
+
+Function Describe(a As Integer, b As Integer) As String
+    Return "Pair " & (a + b)
+End Function
+
+Sub Main()
+    Console.WriteLine(Describe(42))
+    Console.WriteLine(Describe("hi"))
+    Console.WriteLine(Describe(2.5))
+    Console.WriteLine(Describe(1, 2))
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["Integer 42", "String hi", "Double 2.5", "Pair 3"]);
+}
+
+#[test]
+fn test_optional_and_param_array_overloads() {
+    let code = r#"
+Sub Show(a As Integer, Optional b As Integer = 7)
+    Console.WriteLine("Optional " & a & " " & b)
+End Sub
+
+Sub Show(ParamArray items() As String)
+    Console.WriteLine("ParamArray " & items.Length)
+End Sub
+
+Sub Main()
+    Show(1)
+    Show(1, 2)
+    Show("a", "b", "c")
+    Show()
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["Optional 1 7", "Optional 1 2", "ParamArray 3", "ParamArray 0"]);
+}
+
+#[test]
+fn test_class_method_and_constructor_overloads() {
+    let code = r#"
+Class Greeter
+    Public Name As String
+
+    Public Sub New()
+        Name = "world"
+    End Sub
+
+    Public Sub New(n As String)
+        Name = n
+    End Sub
+
+    Public Function Greet() As String
+        Return "Hello, " & Name
+    End Function
+
+    Public Function Greet(times As Integer) As String
+        Dim s As String = ""
+        For i As Integer = 1 To times
+            s = s & Greet() & "! "
+        Next
+        Return s.Trim()
+    End Function
+End Class
+
+Sub Main()
+    Dim a As New Greeter()
+    Dim b As New Greeter("VB")
+    Console.WriteLine(a.Greet())
+    Console.WriteLine(b.Greet(2))
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["Hello, world", "Hello, VB! Hello, VB!"]);
+}
+
+#[test]
+fn test_derived_class_argument_prefers_closest_type() {
+    let code = r#"
+Class Animal
+End Class
+
+Class Dog
+    Inherits Animal
+End Class
+
+Function Kind(a As Animal) As String
+    Return "animal"
+End Function
+
+Function Kind(d As Dog) As String
+    Return "dog"
+End Function
+
+Sub Main()
+    Console.WriteLine(Kind(New Dog()))
+    Console.WriteLine(Kind(New Animal()))
+End Sub
+"#;
+    assert_eq!(run(code).unwrap(), vec!["dog", "animal"]);
+}
+
+#[test]
+fn test_no_overload_accepts_argument_count() {
+    let code = r#"
+Sub Log(a As Integer)
+End Sub
+
+Sub Log(a As Integer, b As Integer)
+End Sub
+
+Sub Main()
+    Log(1, 2, 3)
+End Sub
+"#;
+    let err = run(code).expect_err("expected a resolution error");
+    match err.without_location() {
+        RuntimeError::Exception(t, m, _) => {
+            assert_eq!(t, "MissingMemberException");
+            assert_eq!(m, "No overload of 'Log' accepts 3 argument(s)");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_ambiguous_call_is_reported() {
+    let code = r#"
+Sub Pick(a As Integer, b As Double)
+End Sub
+
+Sub Pick(a As Double, b As Integer)
+End Sub
+
+Sub Main()
+    Pick(1, 2)
+End Sub
+"#;
+    let err = run(code).expect_err("expected an ambiguity error");
+    match err.without_location() {
+        RuntimeError::Exception(t, m, _) => {
+            assert_eq!(t, "AmbiguousMatchException");
+            assert!(m.contains("Pick(a As Integer, b As Double)"), "{}", m);
+            assert!(m.contains("Pick(a As Double, b As Integer)"), "{}", m);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_same_signatures_in_class_and_module() {
+    let code = r#"
+Class Printer
+    Public Sub Show(x As Integer)
+        Console.WriteLine("class Integer " & x)
+    End Sub
+    Public Sub Show(s As String)
+        Console.WriteLine("class String " & s)
+    End Sub
+    Public Sub Run()
+        Show(1)
+        Report("a")
+    End Sub
+End Class
+
+Module Helpers
+    Sub Report(s As String)
+        Show(s)
+    End Sub
+End Module
+
+Module M
+    Sub Show(x As Integer)
+        Console.WriteLine("module Integer " & x)
+    End Sub
+    Sub Show(s As String)
+        Console.WriteLine("module String " & s)
+    End Sub
+
+    Sub Main()
+        Show(2)
+        Show("b")
+        Dim p As New Printer()
+        p.Run()
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec![
+        "module Integer 2", "module String b", "class Integer 1", "module String a",
+    ]);
+}
+
+#[test]
+fn test_extra_arguments_rule_out_a_candidate() {
+    use vybe_parser::ast::decl::{Parameter, ParameterPassType};
+    use vybe_parser::VBType;
+    use vybe_runtime::overloads::{score_candidate, ClassMatch};
+    use vybe_runtime::Value;
+
+    let class_match = |_: Option<&str>, _: &str| ClassMatch::Unknown;
+    let one = vec![Parameter {
+        pass_type: ParameterPassType::ByVal,
+        name: Identifier::new("x"),
+        param_type: Some(VBType::Integer),
+        is_optional: false,
+        default_value: None,
+        is_nullable: false,
+        is_param_array: false,
+        attributes: Vec::new(),
+    }];
+    let values = [Value::Integer(1), Value::Integer(2)];
+    assert!(score_candidate(&[], &values, &class_match).is_none());
+    assert!(score_candidate(&one, &values, &class_match).is_none());
+    assert!(score_candidate(&one, &values[..1], &class_match).is_some());
+}
+
+#[test]
+fn test_procedure_maps_hold_every_overload() {
+    let code = r#"
+Module M
+    Sub Show(x As Integer)
+        Console.WriteLine("first " & x)
+    End Sub
+
+    Function Show(s As String) As String
+        Return s
+    End Function
+
+    Sub Show(x As Integer)
+        Console.WriteLine("again " & x)
+    End Sub
+
+    Sub Main()
+        Show(1)
+        Console.WriteLine(Show("two"))
+    End Sub
+End Module
+"#;
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program).unwrap();
+    assert_eq!(interp.subs["show"].len(), 1);
+    assert_eq!(interp.functions["show"].len(), 1);
+    interp.call_procedure(&Identifier::new("Main"), &[]).unwrap();
+    let output: Vec<String> = interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect();
+    assert_eq!(output, vec!["again 1", "two"]);
+}
@@ -0,0 +1,263 @@
+//! Tests for LINQ query syntax: joins, grouping, aggregates, partitioning
+//! and anonymous types.
+
+use vybe_runtime::{ExecutionLimits, Interpreter, Limit, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+/// Customers and orders shared by the tests; `body` becomes Sub Main.
+fn with_data(body: &str) -> String {
+    format!(r#"
+Class Customer
+    Public Property Id As Integer
+    Public Property Name As String
+    Public Property City As String
+End Class
+
+Class Order
+    Public Property CustomerId As Integer
+    Public Property Region As String
+    Public Property Total As Integer
+End Class
+
+Module M
+    Function Customers() As List(Of Customer)
+        Dim list As New List(Of Customer)
+        list.Add(New Customer With {{.Id = 1, .Name = "Ann", .City = "Oslo"}})
+        list.Add(New Customer With {{.Id = 2, .Name = "Bob", .City = "Rome"}})
+        list.Add(New Customer With {{.Id = 3, .Name = "Cy", .City = "Oslo"}})
+        Return list
+    End Function
+
+    Function Orders() As List(Of Order)
+        Dim list As New List(Of Order)
+        list.Add(New Order With {{.CustomerId = 1, .Region = "N", .Total = 10}})
+        list.Add(New Order With {{.CustomerId = 2, .Region = "S", .Total = 20}})
+        list.Add(New Order With {{.CustomerId = 1, .Region = "S", .Total = 5}})
+        Return list
+    End Function
+
+    Sub Main()
+        Dim customers = Customers()
+        Dim orders = Orders()
+{}
+    End Sub
+End Module
+"#, body)
+}
+
+#[test]
+fn test_anonymous_types() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim name = "Ann"
+        Dim p = New With {name, Key .Age = 3}
+        Console.WriteLine(p.Name & " " & p.Age)
+        Console.WriteLine(p)
+        Dim q = New With {.Name = "Ann", .Age = 3}
+        Dim r = New With {.Name = "Ann", .Age = 4}
+        Console.WriteLine(p.Equals(q) & " " & p.Equals(r))
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["Ann 3", "{ name = Ann, Age = 3 }", "True False"]);
+}
+
+#[test]
+fn test_join_and_select_fields() {
+    let code = with_data(r#"
+        Dim rows = From o In orders
+                   Join c In customers On o.CustomerId Equals c.Id
+                   Order By c.Name, o.Total Descending
+                   Select c.Name, Amount = o.Total
+        For Each r In rows
+            Console.WriteLine(r.Name & " " & r.Amount)
+        Next
+        Dim first = From o In orders Join c In customers On o.CustomerId Equals c.Id And o.Region Equals "S" Select c.Name
+        Console.WriteLine(String.Join(",", first))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["Ann 10", "Ann 5", "Bob 20", "Bob,Ann"]);
+}
+
+#[test]
+fn test_group_join() {
+    let code = with_data(r#"
+        Dim spending = From c In customers
+                       Group Join o In orders On c.Id Equals o.CustomerId Into Spent = Sum(o.Total), Count()
+                       Select c.Name, Spent, Count
+        For Each s In spending
+            Console.WriteLine(s)
+        Next
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "{ Name = Ann, Spent = 15, Count = 2 }",
+        "{ Name = Bob, Spent = 20, Count = 1 }",
+        "{ Name = Cy, Spent = 0, Count = 0 }",
+    ]);
+}
+
+#[test]
+fn test_group_by_into() {
+    let code = with_data(r#"
+        Dim byCity = From c In customers Group By c.City Into People = Group, N = Count()
+        For Each g In byCity
+            Console.WriteLine(g.City & " " & g.N & " " & g.People(0).Name)
+        Next
+        Dim totals = From o In orders
+                     Group o.Total By o.Region Into Sum(Total), Max(Total)
+                     Order By Region
+        For Each t In totals
+            Console.WriteLine(t)
+        Next
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "Oslo 2 Ann",
+        "Rome 1 Bob",
+        "{ Region = N, Sum = 10, Max = 10 }",
+        "{ Region = S, Sum = 25, Max = 20 }",
+    ]);
+}
+
+#[test]
+fn test_aggregate_queries() {
+    let code = with_data(r#"
+        Dim total = Aggregate o In orders Into Sum(o.Total)
+        Console.WriteLine(total)
+        Dim stats = Aggregate o In orders Where o.Total > 6 Into Biggest = Max(o.Total), Average(o.Total), Cnt = Count()
+        Console.WriteLine(stats)
+        Dim labels = From c In customers
+                     Aggregate o In orders Where o.CustomerId = c.Id Into Spent = Sum(o.Total)
+                     Let Label = c.Name & ":" & Spent
+                     Select Label
+        Console.WriteLine(String.Join(",", labels))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["35", "{ Biggest = 20, Average = 15, Cnt = 2 }", "Ann:15,Bob:20,Cy:0"]);
+}
+
+#[test]
+fn test_distinct_and_partitioning() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim xs = {3, 1, 2, 3, 5, 4, 1}
+        Console.WriteLine(String.Join(",", From x In xs Distinct))
+        Console.WriteLine(String.Join(",", From x In xs Order By x Skip 2 Take 3))
+        Console.WriteLine(String.Join(",", From x In xs Skip While x > 2 Take While x < 5))
+        Dim pairs = From a In {1, 2}, b In {"x", "y"} Select a, b Distinct
+        Console.WriteLine(pairs.Count())
+        Dim squares = From x In xs Let sq = x * x, big = sq > 8 Where big Select sq Distinct
+        Console.WriteLine(String.Join(",", squares))
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["3,1,2,5,4", "2,3,3", "1,2,3", "4", "9,25,16"]);
+}
+
+#[test]
+fn test_select_new_with_anonymous_type() {
+    let code = with_data(r#"
+        Dim rows = From c In customers
+                   Where c.City = "Oslo"
+                   Select New With {.Who = c.Name.ToUpper(), .Len = c.Name.Length}
+        For Each r In rows
+            Console.WriteLine(r.Who & r.Len)
+        Next
+        Dim keys = From o In orders Select New With {o.Region} Distinct
+        Console.WriteLine(keys.Count())
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["ANN3", "CY2", "2"]);
+}
+
+#[test]
+fn test_unknown_aggregate_function() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim x = Aggregate n In {1, 2} Into Median(n)
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert!(matches!(err.without_location(), RuntimeError::Custom(m) if m.contains("'Median' is not an aggregate function")));
+}
+
+#[test]
+fn test_query_over_endless_iterator_stops_at_take() {
+    let code = r#"
+Module M
+    Iterator Function Naturals() As IEnumerable(Of Integer)
+        Dim n As Integer = 0
+        Do
+            n += 1
+            Console.WriteLine("yield " & n)
+            Yield n
+        Loop
+    End Function
+
+    Sub Main()
+        Dim evens = From n In Naturals() Where n Mod 2 = 0 Select n * 10 Skip 1 Take 2
+        Console.WriteLine(String.Join(",", evens))
+        Dim small = From n In Naturals() Take While n < 3
+        Console.WriteLine(small.Count())
+    End Sub
+End Module
+"#;
+    assert_eq!(
+        run(code).unwrap(),
+        vec!["yield 1", "yield 2", "yield 3", "yield 4", "yield 5", "yield 6", "40,60",
+             "yield 1", "yield 2", "yield 3", "2"]
+    );
+}
+
+#[test]
+fn test_query_source_keeps_limit_errors() {
+    let code = r#"
+Module M
+    Iterator Function Slow() As IEnumerable(Of Integer)
+        For i As Integer = 1 To 1000
+            Yield i
+        Next
+    End Function
+
+    Sub Main()
+        Try
+            Dim q = From n In Slow() Order By n Descending
+            Console.WriteLine(q.Count())
+        Catch ex As Exception
+            Console.WriteLine("caught")
+        End Try
+    End Sub
+End Module
+"#;
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.set_limits(ExecutionLimits { max_steps: Some(200), ..Default::default() });
+    interp.run(&program).unwrap();
+    let err = interp.call_procedure(&Identifier::new("Main"), &[]).unwrap_err();
+    assert!(matches!(err.without_location(), RuntimeError::LimitExceeded(Limit::Steps(200))));
+}
+
+#[test]
+fn test_query_source_that_is_not_a_collection() {
+    let code = r#"
+Module M
+    Sub Main()
+        Dim x As Integer = 5
+        Dim q = From n In x Select n
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert!(matches!(err.without_location(), RuntimeError::Custom(m) if m.contains("Query source 'x' is not a collection")));
+}
@@ -0,0 +1,274 @@
+//! Tests for the sandbox policy: denied file, process, environment, network,
+//! database and dialog access throws a catchable UnauthorizedAccessException,
+//! and file access can be kept inside directory roots.
+
+use std::path::PathBuf;
+
+use vybe_runtime::{Capability, Engine, Interpreter, RuntimeError, RuntimeSideEffect, SandboxPolicy};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run_with(engine: Engine, policy: &SandboxPolicy, code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.engine = engine;
+    interp.set_sandbox(policy.clone());
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+/// Run `code` under both engines, checking they agree.
+fn run(policy: &SandboxPolicy, code: &str) -> Vec<String> {
+    let expected = run_with(Engine::TreeWalker, policy, code).unwrap();
+    let actual = run_with(Engine::Bytecode, policy, code).unwrap();
+    assert_eq!(expected, actual);
+    actual
+}
+
+/// Run `statement` inside a handler that prints what it caught.
+fn attempt(policy: &SandboxPolicy, statement: &str) -> String {
+    let code = format!(r#"
+Module M
+    Sub Main()
+        Try
+            {}
+            Console.WriteLine("allowed")
+        Catch ex As UnauthorizedAccessException
+            Console.WriteLine("denied: " & ex.Message)
+        Catch ex As Exception
+            Console.WriteLine("allowed, then " & TypeName(ex))
+        End Try
+    End Sub
+End Module
+"#, statement);
+    run(policy, &code).join("\n")
+}
+
+/// A fresh directory holding `data/inside.txt` and `secret.txt`.
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vybe_sandbox_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("data")).unwrap();
+    std::fs::write(dir.join("data").join("inside.txt"), "inside").unwrap();
+    std::fs::write(dir.join("secret.txt"), "secret").unwrap();
+    dir
+}
+
+#[test]
+fn test_denied_files_throw_unauthorized_access() {
+    let dir = scratch_dir("denied");
+    let secret = dir.join("secret.txt").display().to_string();
+    let policy = SandboxPolicy::deny_all();
+    let statements = [
+        format!("Console.WriteLine(File.ReadAllText(\"{}\"))", secret),
+        format!("File.Delete(\"{}\")", secret),
+        format!("Kill \"{}\"", secret),
+        format!("Open \"{}\" For Input As #1", secret),
+        format!("Dim reader As New StreamReader(\"{}\")", secret),
+        format!("Dim exists = Directory.Exists(\"{}\")", dir.display()),
+        "ChDir \"..\"".to_string(),
+        "Dim tmp = System.IO.Path.GetTempFileName()".to_string(),
+        format!("ZipFile.CreateFromDirectory(\"{}\", \"{}.zip\")", dir.display(), dir.display()),
+    ];
+    for statement in &statements {
+        assert!(attempt(&policy, statement).starts_with("denied: Access to "), "{}", statement);
+    }
+    assert_eq!(
+        attempt(&policy, &statements[0]),
+        format!("denied: Access to the path '{}' is denied.", secret)
+    );
+    assert!(dir.join("secret.txt").exists());
+
+    // Uncaught, it stops the script with the same exception
+    let code = format!("Module M\n    Sub Main()\n        {}\n    End Sub\nEnd Module\n", statements[1]);
+    let err = run_with(Engine::TreeWalker, &policy, &code).unwrap_err();
+    assert!(matches!(err.without_location(), RuntimeError::Exception(name, _, _) if name == "UnauthorizedAccessException"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_file_access_within_roots() {
+    let dir = scratch_dir("roots");
+    let data = dir.join("data");
+    let mut policy = SandboxPolicy::deny_all();
+    policy.allow_files_within(&data);
+
+    let inside = data.join("inside.txt").display().to_string();
+    let written = data.join("written.txt").display().to_string();
+    let code = format!(r#"
+Module M
+    Sub Main()
+        Console.WriteLine(File.ReadAllText("{inside}"))
+        Dim writer As New StreamWriter("{written}")
+        writer.Write("written")
+        writer.Close()
+        Console.WriteLine(File.ReadAllText("{written}"))
+    End Sub
+End Module
+"#);
+    assert_eq!(run(&policy, &code), vec!["inside", "written"]);
+
+    // Climbing out with .. is caught, whether or not the target exists
+    let escapes = [
+        format!("{}/../secret.txt", data.display()),
+        format!("{}/missing/../../secret.txt", data.display()),
+        format!("{}", dir.display()),
+        format!("{}_other/inside.txt", data.display()),
+    ];
+    for path in &escapes {
+        let statement = format!("Console.WriteLine(File.ReadAllText(\"{}\"))", path);
+        assert!(attempt(&policy, &statement).starts_with("denied: "), "{}", path);
+        let statement = format!("File.Copy(\"{}\", \"{}\")", inside, path);
+        assert!(attempt(&policy, &statement).starts_with("denied: "), "{}", path);
+    }
+    assert!(!dir.join("data_other").exists());
+    // The temporary directory holds the root but isn't inside it
+    assert!(attempt(&policy, "Dim tmp = System.IO.Path.GetTempFileName()").starts_with("denied: Access to the path "));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_cannot_climb_out_of_root() {
+    let dir = scratch_dir("symlink");
+    let data = dir.join("data");
+    std::fs::create_dir_all(dir.join("outside").join("deep")).unwrap();
+    std::fs::write(dir.join("outside").join("secret.txt"), "outside").unwrap();
+    std::os::unix::fs::symlink(dir.join("outside").join("deep"), data.join("link")).unwrap();
+    let mut policy = SandboxPolicy::deny_all();
+    policy.allow_files_within(&data);
+
+    // The OS follows `link` before `..`, landing in outside/, not data/
+    let escapes = [
+        format!("{}/link/../secret.txt", data.display()),
+        format!("{}/link/missing/../../secret.txt", data.display()),
+        format!("{}/link", data.display()),
+    ];
+    for path in &escapes {
+        let statement = format!("Console.WriteLine(File.ReadAllText(\"{}\"))", path);
+        assert!(attempt(&policy, &statement).starts_with("denied: Access to the path "), "{}", path);
+    }
+    // Climbing back into the root through the link is allowed
+    let statement = format!("Console.WriteLine(File.ReadAllText(\"{}/link/../../data/inside.txt\"))", data.display());
+    assert_eq!(attempt(&policy, &statement), "inside\nallowed");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_stream_closed_after_access_is_denied() {
+    let dir = scratch_dir("stream");
+    let written = dir.join("data").join("stream.bin");
+    let code = format!(r#"
+Module M
+    Dim fs As FileStream
+
+    Sub OpenStream()
+        fs = New FileStream("{}", 2)
+        fs.WriteByte(65)
+    End Sub
+
+    Sub Main()
+        Try
+            fs.Close()
+            Console.WriteLine("allowed")
+        Catch ex As UnauthorizedAccessException
+            Console.WriteLine("denied")
+        End Try
+    End Sub
+End Module
+"#, written.display());
+    let program = parse_program(&code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program).unwrap();
+    interp.call_procedure(&Identifier::new("OpenStream"), &[]).unwrap();
+    // Writing the buffered bytes out checks the policy in force then
+    interp.set_sandbox(SandboxPolicy::deny_all());
+    interp.call_procedure(&Identifier::new("Main"), &[]).unwrap();
+    assert!(interp.side_effects.iter().any(|e| matches!(e, RuntimeSideEffect::ConsoleOutput(msg) if msg.trim_end() == "denied")));
+    assert!(!written.exists());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Reads through a BindingSource bound to a DataAdapter that isn't filled yet.
+const FILL_ADAPTER: &str = concat!(
+    "Dim da As New SqlDataAdapter(\"SELECT 1 AS n\")\n",
+    "            da.ConnectionString = \"Data Source=:memory:\"\n",
+    "            Dim bs As New BindingSource()\n",
+    "            bs.DataSource = da\n",
+    "            Console.WriteLine(bs.Count)",
+);
+
+#[test]
+fn test_denied_capabilities() {
+    let cases = [
+        (Capability::Processes, "Shell(\"echo hi\")"),
+        (Capability::Processes, "Process.Start(\"echo\", \"hi\")"),
+        // Zip archives are made and unpacked by the system zip tools
+        (Capability::Processes, "ZipFile.CreateFromDirectory(\"data\", \"data.zip\")"),
+        (Capability::Processes, "ZipFile.ExtractToDirectory(\"data.zip\", \"data\")"),
+        (Capability::Environment, "Console.WriteLine(Environ(\"PATH\"))"),
+        (Capability::Environment, "Console.WriteLine(Environment.GetEnvironmentVariable(\"PATH\"))"),
+        (Capability::Sockets, "Dim client As New TcpClient(\"localhost\", 1)"),
+        (Capability::Sockets, "Dim listener As New System.Net.Sockets.TcpListener(0)"),
+        (Capability::Http, "Dim web As New WebClient()"),
+        (Capability::Http, "Dim http As New HttpClient()"),
+        (Capability::Databases, "Dim conn As New SqlConnection(\"Data Source=:memory:\")\n            conn.Open()"),
+        // The adapter connects when the BindingSource first reads from it
+        (Capability::Databases, FILL_ADAPTER),
+        (Capability::Dialogs, "MsgBox(\"hello\")"),
+        (Capability::Dialogs, "Dim dlg As New OpenFileDialog()"),
+    ];
+    for (capability, statement) in cases {
+        let mut policy = SandboxPolicy::default();
+        policy.allow(capability, false);
+        assert_eq!(
+            attempt(&policy, statement),
+            format!("denied: Access to {} is denied by the sandbox policy.", capability),
+            "{}",
+            statement
+        );
+    }
+
+    // Denying one capability leaves the others alone
+    let mut policy = SandboxPolicy::default();
+    policy.allow(Capability::Processes, false).allow(Capability::Sockets, false);
+    assert_eq!(attempt(&policy, "Dim path = Environment.GetEnvironmentVariable(\"PATH\")"), "allowed");
+    assert_eq!(attempt(&policy, "Dim found = File.Exists(\"no-such-file.txt\")"), "allowed");
+    assert_eq!(attempt(&policy, FILL_ADAPTER), "1\nallowed");
+    assert!(policy.allows(Capability::Http));
+    assert!(!policy.allows(Capability::Processes));
+}
+
+#[test]
+fn test_default_policy_allows_everything() {
+    let policy = SandboxPolicy::default();
+    for capability in [
+        Capability::Files,
+        Capability::Sockets,
+        Capability::Http,
+        Capability::Processes,
+        Capability::Environment,
+        Capability::Databases,
+        Capability::Dialogs,
+    ] {
+        assert!(policy.allows(capability));
+        assert!(!SandboxPolicy::deny_all().allows(capability));
+    }
+    assert_eq!(Interpreter::new().sandbox(), &policy);
+
+    let dir = scratch_dir("default");
+    let secret = dir.join("secret.txt").display().to_string();
+    let code = format!(r#"
+Module M
+    Sub Main()
+        Console.WriteLine(File.ReadAllText("{secret}"))
+        Console.WriteLine(Environ("VYBE_SANDBOX_UNSET") = "")
+    End Sub
+End Module
+"#);
+    assert_eq!(run(&policy, &code), vec!["secret", "True"]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,137 @@
+//! Tests for Structure value semantics: copies on assignment, ByVal passing
+//! and `For Each`, zeroed instances without `New`, and arrays of structures
+//! updated in place.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+const TYPES: &str = r#"
+Structure Point
+    Public X As Integer
+    Public Y As Integer
+    Public Sub Move(dx As Integer)
+        X += dx
+    End Sub
+End Structure
+
+Structure Segment
+    Public A As Point
+    Public B As Point
+End Structure
+
+Class Shape
+    Public Origin As Point
+End Class
+"#;
+
+fn program(body: &str) -> String {
+    format!("{}\nModule M\n    Sub Bump(ByVal p As Point)\n        p.X = 100\n    End Sub\n    Sub BumpRef(ByRef p As Point)\n        p.X = 200\n    End Sub\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", TYPES, body)
+}
+
+#[test]
+fn test_assignment_and_passing_copy() {
+    let code = program(r#"
+        Dim p As Point
+        p.X = 1
+        Dim q = p
+        q.X = 2
+        Console.WriteLine(p.X & " " & q.X)
+        Bump(p)
+        Console.WriteLine(p.X)
+        BumpRef(p)
+        Console.WriteLine(p.X)
+        p.Move(5)
+        Console.WriteLine(p.X)
+        Dim n = New Point With {.X = 4}
+        Dim m = n
+        m.Y = 9
+        Console.WriteLine(n.X & " " & n.Y & " " & m.Y)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["1 2", "1", "200", "205", "4 0 9"]);
+}
+
+#[test]
+fn test_nested_and_field_structures() {
+    let code = program(r#"
+        Dim s As Segment
+        s.B.X = 3
+        Dim s2 = s
+        s2.B.X = 4
+        Console.WriteLine(s.B.X & " " & s2.B.X)
+        Dim sh As New Shape()
+        sh.Origin.X = 8
+        Dim o = sh.Origin
+        o.X = 9
+        Console.WriteLine(sh.Origin.X & " " & o.X)
+        Dim p As Point
+        sh.Origin = p
+        p.X = 1
+        Console.WriteLine(sh.Origin.X)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["3 4", "8 9", "0"]);
+}
+
+#[test]
+fn test_arrays_of_structures() {
+    let code = program(r#"
+        Dim pts(2) As Point
+        pts(0).X = 5
+        pts(1).Y = 7
+        Console.WriteLine(pts(0).X & " " & pts(1).X & " " & pts(1).Y & " " & pts(2).X)
+        Dim r = pts(0)
+        r.X = 99
+        Console.WriteLine(pts(0).X)
+        For Each e In pts
+            e.X = 42
+        Next
+        Console.WriteLine(pts(0).X)
+        Dim p As Point
+        pts(1) = p
+        p.Y = 1000
+        Console.WriteLine(pts(1).Y)
+        Dim grid(1, 1) As Point
+        grid(0, 1).X = 1
+        Console.WriteLine(grid(0, 0).X & " " & grid(0, 1).X & " " & grid(1, 1).X)
+        ReDim Preserve pts(3)
+        pts(3).X = 6
+        Console.WriteLine(pts(0).X & " " & pts(3).X)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["5 0 7 0", "5", "5", "0", "0 1 0", "5 6"]);
+}
+
+#[test]
+fn test_collections_store_copies() {
+    let code = program(r#"
+        Dim a As Point
+        a.X = 1
+        Dim l As New List(Of Point)
+        l.Add(a)
+        l.Insert(0, a)
+        a.X = 4
+        Console.WriteLine(l(0).X & " " & l(1).X)
+        l(1) = a
+        a.X = 5
+        Console.WriteLine(l(1).X)
+        Dim q As New Queue(Of Point)
+        q.Enqueue(a)
+        Dim st As New Stack(Of Point)
+        st.Push(a)
+        Dim d As New Dictionary(Of String, Point)
+        d.Add("a", a)
+        d("b") = a
+        a.X = 6
+        Console.WriteLine(q.Peek().X & " " & st.Peek().X & " " & d("a").X & " " & d("b").X)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["1 1", "4", "5 5 5 5"]);
+}
@@ -0,0 +1,153 @@
+//! Tests for interned names and variable slots: symbols ignore case, slots
+//! stay valid until a scope or variable comes or goes, names are resolved by
+//! where they are written, and programs mixing the case of their names behave
+//! the same under both engines.
+
+use vybe_runtime::environment::{Environment, Resolutions};
+use vybe_runtime::symbol::Symbol;
+use vybe_runtime::{Engine, Interpreter, RuntimeError, RuntimeSideEffect, Value};
+use vybe_parser::ast::Identifier;
+use vybe_parser::{parse_program, Span};
+
+fn run_with(engine: Engine, code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.engine = engine;
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+/// Run `code` under both engines, checking they agree.
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let expected = run_with(Engine::TreeWalker, code);
+    let actual = run_with(Engine::Bytecode, code);
+    match (&expected, &actual) {
+        (Ok(a), Ok(b)) => assert_eq!(a, b),
+        (Err(a), Err(b)) => assert_eq!(a.to_string(), b.to_string()),
+        _ => panic!("engines disagree: {:?} vs {:?}", expected, actual),
+    }
+    actual
+}
+
+#[test]
+fn test_symbols_ignore_case() {
+    let total = Symbol::intern("Total");
+    assert_eq!(total, Symbol::intern("TOTAL"));
+    assert_eq!(total, Symbol::intern("total"));
+    assert_ne!(total, Symbol::intern("Totals"));
+    assert_eq!(&*total.name(), "total");
+    assert_eq!(Symbol::intern("ÄRGER"), Symbol::intern("ärger"));
+    assert_eq!(Symbol::intern("ÄRGER").to_string(), "ärger");
+}
+
+#[test]
+fn test_slots_follow_scopes() {
+    let mut env = Environment::new();
+    let x = Symbol::intern("x");
+    env.define("X", Value::Integer(1));
+    assert_eq!(env.resolve_local(x), None);
+
+    env.push_scope();
+    env.define("x", Value::Integer(2));
+    let slot = env.resolve_local(x).expect("local x");
+    assert_eq!(env.resolve(x), Some(slot));
+    let epoch = env.epoch();
+
+    // Assigning keeps the slots; defining a new variable doesn't
+    env.set("X", Value::Integer(3)).unwrap();
+    env.define("x", Value::Integer(4));
+    assert_eq!(env.epoch(), epoch);
+    assert_eq!(env.slot(slot), &Value::Integer(4));
+    env.define("y", Value::Integer(5));
+    assert_ne!(env.epoch(), epoch);
+    assert_eq!(env.resolve_local(x), Some(slot));
+
+    env.store(slot, Value::Integer(6));
+    assert_eq!(env.get("x").unwrap(), Value::Integer(6));
+    let locals = env.take_scope();
+    assert_eq!(locals.value("X"), Some(&Value::Integer(6)));
+    assert_eq!(env.get("x").unwrap(), Value::Integer(1));
+
+    env.push_scope_with(locals);
+    assert_eq!(env.get("Y").unwrap(), Value::Integer(5));
+    env.define_const("Limit", Value::Integer(10));
+    assert!(env.is_const(Symbol::intern("LIMIT")));
+    assert_eq!(env.set("limit", Value::Integer(0)).unwrap_err().to_string(), "Cannot assign to constant 'limit'");
+}
+
+#[test]
+fn test_resolutions_by_span() {
+    let mut env = Environment::new();
+    let mut resolutions = Resolutions::default();
+    let at = Span { start_line: 3, start_col: 9, end_line: 3, end_col: 14 };
+    env.define("total", Value::Integer(1));
+    assert_eq!(resolutions.local(at, "Total", &env), (Symbol::intern("total"), None));
+
+    // A local declared since is found on the next run of the same line
+    env.push_scope();
+    env.define("TOTAL", Value::Integer(2));
+    let (symbol, slot) = resolutions.local(at, "Total", &env);
+    assert_eq!(symbol, Symbol::intern("total"));
+    assert_eq!(env.slot(slot.expect("local total")), &Value::Integer(2));
+
+    // Another name at the same place, as in a different file
+    env.define("count", Value::Integer(3));
+    let (symbol, slot) = resolutions.local(at, "Count", &env);
+    assert_eq!(symbol, Symbol::intern("count"));
+    assert_eq!(env.slot(slot.expect("local count")), &Value::Integer(3));
+
+    env.pop_scope();
+    assert_eq!(resolutions.local(at, "count", &env).1, None);
+    assert_eq!(resolutions.local(Span::default(), "Total", &env), (Symbol::intern("total"), None));
+}
+
+#[test]
+fn test_names_in_any_case() {
+    let code = r#"
+Class Account
+    Private Balance As Decimal
+    Private count As Integer
+
+    Public Sub Deposit(Amount As Decimal)
+        balance = BALANCE + amount
+        COUNT += 1
+    End Sub
+
+    Public Function Summary() As String
+        Return Count & ": " & Balance
+    End Function
+End Class
+
+Module M
+    Function Depth(N As Integer) As Integer
+        Dim here = n
+        If N > 0 Then here = HERE + Depth(n - 1)
+        Return Here
+    End Function
+
+    Sub Main()
+        Dim acct As New Account()
+        For I = 1 To 3
+            Acct.Deposit(i * 1.5D)
+        Next
+        Console.WriteLine(ACCT.Summary())
+        Console.WriteLine(depth(4))
+        Dim Small As Byte = 250
+        Do While small < 255
+            SMALL += 1
+        Loop
+        Console.WriteLine(Small.GetType().Name & " " & small)
+        Const Top As Integer = 3
+        top = 4
+    End Sub
+End Module
+"#;
+    let err = run(code).unwrap_err();
+    assert_eq!(err.without_location().to_string(), "Cannot assign to constant 'top'");
+
+    let code = code.replace("        Const Top As Integer = 3\n        top = 4\n", "");
+    assert_eq!(run(&code).unwrap(), vec!["3: 9.0", "10", "Byte 255"]);
+}
@@ -0,0 +1,115 @@
+//! Tests for tuples: `(Name:="Ann", Age:=30)` literals, tuple types on
+//! variables, parameters and return values, element names, tuple equality
+//! and tuples as Dictionary keys.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+fn main_with(body: &str) -> String {
+    format!("Module M\n    Sub Main()\n{}\n    End Sub\nEnd Module\n", body)
+}
+
+#[test]
+fn test_literals_and_element_names() {
+    let code = main_with(r#"
+        Dim p = (Name:="Ann", Age:=30)
+        Console.WriteLine(p.Name & " " & p.Age & " " & p.Item1 & " " & p.Item2)
+        Console.WriteLine(p)
+        Dim city = "Oslo"
+        Dim c = (city, p.Name, 5)
+        Console.WriteLine(c.City & " " & c.Name & " " & c.Item3)
+        Dim n = (1, (X:=2, Y:=3))
+        Console.WriteLine(n.Item2.Y & " " & n.ToString())
+        p.Age = 31
+        Console.WriteLine(p.Item2)
+        p.Item1 = "Bo"
+        Console.WriteLine(p.Name)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "Ann 30 Ann 30",
+        "(Ann, 30)",
+        "Oslo Ann 5",
+        "3 (1, (2, 3))",
+        "31",
+        "Bo",
+    ]);
+}
+
+#[test]
+fn test_tuple_types() {
+    let code = r#"
+Module M
+    Function MinMax(xs As Integer()) As (Min As Integer, Max As Integer)
+        Dim lo = xs(0), hi = xs(0)
+        For Each x In xs
+            If x < lo Then lo = x
+            If x > hi Then hi = x
+        Next
+        Return (lo, hi)
+    End Function
+
+    Sub Show(p As (Name As String, Age As Integer))
+        Console.WriteLine(p.Name & " is " & p.Age)
+    End Sub
+
+    Sub Main()
+        Dim r = MinMax({3, 9, 1})
+        Console.WriteLine(r.Min & " " & r.Max)
+        Show(("Bo", 4))
+        Dim t As (X As Integer, Y As Short)
+        Console.WriteLine(t.X & " " & TypeName(t.Y))
+        t = (5, 6)
+        Console.WriteLine(t.X + t.Y & " " & TypeName(t.Y))
+        Dim pairs As New List(Of (String, Integer))
+        pairs.Add(("x", 1))
+        Console.WriteLine(pairs(0).Item1)
+    End Sub
+End Module
+"#;
+    assert_eq!(run(code).unwrap(), vec!["1 9", "Bo is 4", "0 Short", "11 Short", "x"]);
+}
+
+#[test]
+fn test_no_deconstruction() {
+    // VB.NET has no deconstruction syntax; elements are read by name instead
+    let code = main_with("        Dim (lo, hi) = (1, 2)");
+    assert!(parse_program(&code).is_err());
+}
+
+#[test]
+fn test_tuples_are_values() {
+    let code = main_with(r#"
+        Dim p = (Name:="Ann", Age:=30)
+        Dim q = p
+        q.Age = 31
+        Console.WriteLine(p.Age & " " & q.Age)
+        Console.WriteLine(((1, "a") = (1, "a")) & " " & ((1, 2) <> (1, 3)) & " " & ((A:=1, B:=2) = (1, 2)))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["30 31", "True True True"]);
+}
+
+#[test]
+fn test_dictionary_keys() {
+    let code = main_with(r#"
+        Dim grid As New Dictionary(Of (Integer, Integer), String)
+        grid.Add((1, 2), "a")
+        grid((3, 4)) = "b"
+        grid((1, 2)) = "c"
+        Console.WriteLine(grid((1, 2)) & grid((3, 4)) & " " & grid.ContainsKey((3, 4)) & " " & grid.Count)
+        Dim names As New Dictionary(Of String, Integer)
+        names("x") = 1
+        Console.WriteLine(names("x"))
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["cb True 2", "1"]);
+}
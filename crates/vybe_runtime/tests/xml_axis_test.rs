@@ -0,0 +1,90 @@
+//! Tests for XML axis properties: `doc.<root>.<item>`, `doc...<price>`,
+//! `el.@id` reads and assignments, indexing a sequence, and embedded
+//! expressions in XML literals for attribute names, whole attributes and
+//! query results.
+
+use vybe_runtime::{Interpreter, RuntimeError, RuntimeSideEffect};
+use vybe_parser::ast::Identifier;
+use vybe_parser::parse_program;
+
+fn run(code: &str) -> Result<Vec<String>, RuntimeError> {
+    let program = parse_program(code).expect("Parse error");
+    let mut interp = Interpreter::new();
+    interp.run(&program)?;
+    interp.call_procedure(&Identifier::new("Main"), &[])?;
+    Ok(interp.side_effects.iter().filter_map(|e| {
+        if let RuntimeSideEffect::ConsoleOutput(msg) = e { Some(msg.trim_end().to_string()) } else { None }
+    }).collect())
+}
+
+fn program(body: &str) -> String {
+    let doc = r#"XDocument.Parse("<root><item id=""1""><price>3</price></item><item id=""2""><price>5</price><box><price>9</price></box></item></root>")"#;
+    format!("Module M\n    Sub Main()\n        Dim doc = {}\n{}\n    End Sub\nEnd Module\n", doc, body)
+}
+
+#[test]
+fn test_element_and_descendant_axes() {
+    let code = program(r#"
+        Console.WriteLine(doc.<root>.<item>.Count())
+        For Each it In doc.<root>.<item>
+            Console.WriteLine(it.@id & ": " & it.<price>.Value)
+        Next
+        Console.WriteLine(doc...<price>.Count() & " " & doc.Root.<item>.Count())
+        Console.WriteLine(doc...<item>(1).@id & " " & (doc...<item>(5) Is Nothing))
+        Console.WriteLine(doc.<root>.<item>.@id)
+        Dim total = 0
+        For Each p In doc...<price>
+            total += CInt(p.Value)
+        Next
+        Console.WriteLine(total)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["2", "1: 3", "2: 5", "3 2", "2 True", "1", "17"]);
+}
+
+#[test]
+fn test_attribute_axis_assignment() {
+    let code = program(r#"
+        Dim el = doc...<item>(0)
+        el.@id = "10"
+        el.@extra = "x"
+        Console.WriteLine(el.@id & el.@extra & " " & (el.@missing Is Nothing))
+        el.@extra = Nothing
+        If el.@id = "10" Then doc.<root>.<item>.@id = 11
+        Console.WriteLine(el.ToString())
+    "#);
+    assert_eq!(run(&code).unwrap(), vec!["10x True", "<item id=\"11\">\n  <price>3</price>\n</item>"]);
+}
+
+#[test]
+fn test_embedded_expressions_in_literals() {
+    let code = program(r#"
+        Dim n = 7
+        Dim key = "code"
+        Dim made = <order no=<%= n %> <%= key %>="A1" <%= New XAttribute("rush", "yes") %>><line>Qty <%= n * 2 %></line></order>
+        Console.WriteLine(made.ToString())
+        Dim names = {"a", "b"}
+        Dim list = <list><%= From s In names Select <it name=<%= s %>/> %></list>
+        Console.WriteLine(list.<it>.Count() & " " & list.<it>(1).@name)
+    "#);
+    assert_eq!(run(&code).unwrap(), vec![
+        "<order no=\"7\" code=\"A1\" rush=\"yes\">\n  <line>Qty 14</line>\n</order>", "2 b",
+    ]);
+}
+
+#[test]
+fn test_deeply_nested_literals() {
+    // Each level of embedding evaluates the literal inside it before building
+    // its own element, on the test thread's default stack
+    let mut embedded = "1".to_string();
+    for _ in 0..32 {
+        embedded = format!("<a><%= {} %></a>", embedded);
+    }
+    let elements = format!("{}1{}", "<b>".repeat(300), "</b>".repeat(300));
+    let code = program(&format!(r#"
+        Dim embedded = {embedded}
+        Dim elements = {elements}
+        Console.WriteLine(embedded.Value & " " & embedded...<a>.Count())
+        Console.WriteLine(elements.Value & " " & elements...<b>.Count())
+    "#));
+    assert_eq!(run(&code).unwrap(), vec!["1 31", "1 299"]);
+}
@@ -3,4 +3,4 @@ mod runner;
 
 pub use runtime_panel::FormRunner;
 pub use runtime_panel::RuntimeProject;
-pub use runner::run;
+pub use runner::{check, run};
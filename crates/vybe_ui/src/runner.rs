@@ -4,9 +4,9 @@ use std::path::{Path, PathBuf};
 use dioxus::prelude::*;
 use dioxus::desktop::{Config, WindowBuilder};
 
-use vybe_parser::parse_program;
-use vybe_project::Project;
-use vybe_runtime::{Interpreter, ResourceEntry, RuntimeSideEffect};
+use vybe_parser::{check_syntax_with, parse_program_named, parse_program_named_with, ConditionalConstants, ParseError, Program};
+use vybe_project::{FormModule, Project};
+use vybe_runtime::{Interpreter, ResourceEntry, RuntimeSideEffect, SandboxPolicy};
 
 use crate::runtime_panel::RuntimeProject;
 use crate::FormRunner;
@@ -18,6 +18,9 @@ use crate::FormRunner;
 thread_local! {
     pub static LAUNCH_PROJECT: std::cell::RefCell<Option<Project>> = std::cell::RefCell::new(None);
     pub static LAUNCH_TITLE: std::cell::RefCell<String> = std::cell::RefCell::new(String::new());
+    /// Sandbox for the interpreters the FormRunner creates; the editor leaves
+    /// it at the default, which allows everything.
+    pub static LAUNCH_SANDBOX: std::cell::RefCell<SandboxPolicy> = std::cell::RefCell::new(SandboxPolicy::default());
 }
 
 // ---------------------------------------------------------------------------
@@ -32,15 +35,16 @@ thread_local! {
 ///
 /// `extra_args` are the command-line arguments passed *after* the project file,
 /// available to the VB program via `Command()` or `Environment.GetCommandLineArgs()`.
-pub fn run(path: &Path, extra_args: &[String]) {
+/// `sandbox` limits what the program may reach outside the interpreter.
+pub fn run(path: &Path, extra_args: &[String], sandbox: &SandboxPolicy) {
     let ext = path
         .extension()
         .map(|e| e.to_string_lossy().to_lowercase())
         .unwrap_or_default();
 
     match ext.as_str() {
-        "vb" => run_vb_file(path, extra_args),
-        "vbp" | "vbproj" => run_project(path, extra_args),
+        "vb" => run_vb_file(path, extra_args, sandbox),
+        "vbp" | "vbproj" => run_project(path, extra_args, sandbox),
         _ => {
             eprintln!(
                 "Error: unsupported file type '.{}'. Expected .vb, .vbp, or .vbproj",
@@ -51,12 +55,88 @@ pub fn run(path: &Path, extra_args: &[String]) {
     }
 }
 
+/// Check a file or project for syntax errors without running it.
+///
+/// Every diagnostic is printed to stderr as `file:line:col: error[code]: message`.
+/// Returns `true` when everything parses cleanly.
+pub fn check(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let mut errors = 0;
+    match ext.as_str() {
+        "vb" => {
+            let code = match fs::read_to_string(path) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("Error reading file: {e}");
+                    return false;
+                }
+            };
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            errors += report_syntax_errors(&file_name, &code, &ConditionalConstants::new());
+        }
+        "vbp" | "vbproj" => {
+            let project = match vybe_project::load_project_auto(path) {
+                Ok(p) => p,
+                Err(e) => {
+                    eprintln!("Error loading project: {e}");
+                    return false;
+                }
+            };
+            for form_module in &project.forms {
+                let name = &form_module.form.name;
+                if form_module.is_vbnet() {
+                    errors += report_syntax_errors(&format!("{name}.Designer.vb"), form_module.get_designer_code(), &project.define_constants);
+                    errors += report_syntax_errors(&format!("{name}.vb"), form_module.get_user_code(), &project.define_constants);
+                } else {
+                    errors += report_syntax_errors(&format!("{name}.frm"), form_module.get_user_code(), &project.define_constants);
+                }
+            }
+            for code_file in &project.code_files {
+                errors += report_syntax_errors(&code_file_source_name(&code_file.name), &code_file.code, &project.define_constants);
+            }
+        }
+        _ => {
+            eprintln!(
+                "Error: unsupported file type '.{}'. Expected .vb, .vbp, or .vbproj",
+                ext
+            );
+            return false;
+        }
+    }
+
+    if errors > 0 {
+        eprintln!("{errors} syntax error(s)");
+    }
+    errors == 0
+}
+
 // ---------------------------------------------------------------------------
 // Internals
 // ---------------------------------------------------------------------------
 
+/// Print every syntax error in `code`, prefixed with `file_name`.
+/// Returns the number of errors found.
+fn report_syntax_errors(file_name: &str, code: &str, constants: &ConditionalConstants) -> usize {
+    let diagnostics = check_syntax_with(code, constants);
+    for d in &diagnostics {
+        if d.span.is_unknown() {
+            eprintln!("{file_name}: {d}");
+        } else {
+            eprintln!("{file_name}:{d}");
+        }
+    }
+    diagnostics.len()
+}
+
 /// Run a standalone .vb file as a console program.
-fn run_vb_file(path: &Path, extra_args: &[String]) {
+fn run_vb_file(path: &Path, extra_args: &[String], sandbox: &SandboxPolicy) {
     let code = match fs::read_to_string(path) {
         Ok(c) => c,
         Err(e) => {
@@ -65,10 +145,16 @@ fn run_vb_file(path: &Path, extra_args: &[String]) {
         }
     };
 
-    let program = match parse_program(&code) {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let program = match parse_program_named(&code, &file_name) {
         Ok(p) => p,
         Err(e) => {
-            eprintln!("Parse error: {:?}", e);
+            if report_syntax_errors(&file_name, &code, &ConditionalConstants::new()) == 0 {
+                eprintln!("Parse error in '{}': {}", file_name, e);
+            }
             std::process::exit(1);
         }
     };
@@ -76,9 +162,10 @@ fn run_vb_file(path: &Path, extra_args: &[String]) {
     let mut interp = Interpreter::new();
     interp.direct_console = true;
     interp.set_command_line_args(extra_args.to_vec());
+    interp.set_sandbox(sandbox.clone());
 
     if let Err(e) = interp.run(&program) {
-        eprintln!("Runtime error: {:?}", e);
+        eprintln!("Runtime error: {}", e);
         std::process::exit(1);
     }
 
@@ -90,7 +177,7 @@ fn run_vb_file(path: &Path, extra_args: &[String]) {
         Err(vybe_runtime::RuntimeError::UndefinedFunction(_)) => {} // no Main sub found
         Err(e) => {
             drain_console_effects(&mut interp);
-            eprintln!("Runtime error: {:?}", e);
+            eprintln!("Runtime error: {}", e);
             std::process::exit(1);
         }
     }
@@ -98,7 +185,7 @@ fn run_vb_file(path: &Path, extra_args: &[String]) {
 }
 
 /// Run a .vbp / .vbproj project.
-fn run_project(path: &Path, extra_args: &[String]) {
+fn run_project(path: &Path, extra_args: &[String], sandbox: &SandboxPolicy) {
     let project = match vybe_project::load_project_auto(path) {
         Ok(p) => p,
         Err(e) => {
@@ -122,10 +209,10 @@ fn run_project(path: &Path, extra_args: &[String]) {
 
     if has_forms {
         // Has forms → launch the GUI (handles Sub Main inside FormRunner too)
-        run_form_project(project);
+        run_form_project(project, sandbox);
     } else if starts_with_main {
         // Pure console project
-        run_console_project(&project, extra_args);
+        run_console_project(&project, extra_args, sandbox);
     } else {
         eprintln!("Error: project has no forms and no Sub Main entry point");
         std::process::exit(1);
@@ -133,23 +220,28 @@ fn run_project(path: &Path, extra_args: &[String]) {
 }
 
 /// Run a console-only project (Sub Main, no forms).
-fn run_console_project(project: &Project, extra_args: &[String]) {
+fn run_console_project(project: &Project, extra_args: &[String], sandbox: &SandboxPolicy) {
     let mut interp = Interpreter::new();
+    interp.options = project.options;
     interp.direct_console = true;
     interp.set_command_line_args(extra_args.to_vec());
+    interp.set_sandbox(sandbox.clone());
 
     let entries = collect_resource_entries(project);
     interp.register_resource_entries(entries);
 
     for code_file in &project.code_files {
-        match parse_program(&code_file.code) {
+        match parse_program_named_with(&code_file.code, &code_file_source_name(&code_file.name), &project.define_constants) {
             Ok(program) => {
                 if let Err(e) = interp.load_code_file(&program) {
-                    eprintln!("Runtime error loading '{}': {:?}", code_file.name, e);
+                    eprintln!("Runtime error loading '{}': {}", code_file.name, e);
                 }
             }
             Err(e) => {
-                eprintln!("Parse error in '{}': {:?}", code_file.name, e);
+                let file_name = code_file_source_name(&code_file.name);
+                if report_syntax_errors(&file_name, &code_file.code, &project.define_constants) == 0 {
+                    eprintln!("Parse error in '{}': {}", code_file.name, e);
+                }
             }
         }
     }
@@ -157,7 +249,7 @@ fn run_console_project(project: &Project, extra_args: &[String]) {
     match interp.call_procedure(&vybe_parser::ast::Identifier::new("main"), &[]) {
         Ok(_) => {}
         Err(e) => {
-            eprintln!("Sub Main error: {:?}", e);
+            eprintln!("Sub Main error: {}", e);
             std::process::exit(1);
         }
     }
@@ -167,7 +259,7 @@ fn run_console_project(project: &Project, extra_args: &[String]) {
 
 /// Launch a Dioxus desktop window showing the form runtime.
 /// Uses the shared FormRunner – the exact same renderer the editor uses.
-fn run_form_project(project: Project) {
+fn run_form_project(project: Project, sandbox: &SandboxPolicy) {
     let title = project
         .get_startup_form()
         .map(|f| {
@@ -181,6 +273,7 @@ fn run_form_project(project: Project) {
 
     LAUNCH_PROJECT.with(|cell| *cell.borrow_mut() = Some(project));
     LAUNCH_TITLE.with(|cell| *cell.borrow_mut() = title.clone());
+    LAUNCH_SANDBOX.with(|cell| *cell.borrow_mut() = sandbox.clone());
 
     let config = Config::new()
         .with_resource_directory(PathBuf::from("."))
@@ -235,6 +328,32 @@ fn drain_console_effects(interp: &mut Interpreter) {
     }
 }
 
+/// File name used in error locations for a project code file
+/// (`CodeFile::name` is stored without the `.vb` extension).
+pub fn code_file_source_name(name: &str) -> String {
+    format!("{name}.vb")
+}
+
+/// Parse a form's code for the runtime.
+///
+/// VB.NET forms are parsed per file (designer, then user code) so that error
+/// locations name `Form1.Designer.vb` or `Form1.vb` with the right line
+/// numbers; the two `Partial Class` halves are merged when the module loads.
+/// `constants` are the project's conditional compilation constants.
+pub fn parse_form_code(form_module: &FormModule, constants: &ConditionalConstants) -> Result<Program, ParseError> {
+    let name = &form_module.form.name;
+    if !form_module.is_vbnet() {
+        return parse_program_named_with(form_module.get_user_code(), &format!("{name}.frm"), constants);
+    }
+    let mut program = parse_program_named_with(form_module.get_designer_code(), &format!("{name}.Designer.vb"), constants)?;
+    let user = parse_program_named_with(form_module.get_user_code(), &format!("{name}.vb"), constants)?;
+    program.declarations.extend(user.declarations);
+    program.statements.extend_block(user.statements);
+    program.source_file = user.source_file;
+    program.options = user.options;
+    Ok(program)
+}
+
 /// Collect all resource entries from the project (resource_files + form-level resources)
 /// into a flat Vec of ResourceEntry for the runtime.
 pub fn collect_resource_entries(project: &Project) -> Vec<ResourceEntry> {
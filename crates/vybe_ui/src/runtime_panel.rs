@@ -8,8 +8,8 @@ use std::collections::HashMap;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::mpsc;
-use vybe_parser::parse_program;
-use crate::runner::LAUNCH_PROJECT;
+use vybe_parser::{parse_program, parse_program_named_with};
+use crate::runner::{LAUNCH_PROJECT, LAUNCH_SANDBOX};
 
 // ---------------------------------------------------------------------------
 // Console color helpers
@@ -1809,15 +1809,10 @@ fn process_side_effects(
                             if property.eq_ignore_ascii_case("Visible")
                                 && (value.as_bool().unwrap_or(false) || value.as_string() == "True")
                             {
-                                let switch_code = if other_form_module.is_vbnet() {
-                                    format!("{}\n{}", other_form_module.get_designer_code(), other_form_module.get_user_code())
-                                } else {
-                                    other_form_module.get_user_code().to_string()
-                                };
-                                match parse_program(&switch_code) {
+                                match crate::runner::parse_form_code(other_form_module, &proj.define_constants) {
                                     Ok(prog) => {
                                         if let Err(e) = interp.load_module(&other_form_module.form.name, &prog) {
-                                            println!("Error loading new form code: {:?}", e);
+                                            println!("Error loading new form code: {}", e);
                                         } else {
                                             let load_args = interp.make_event_handler_args(&other_form_module.form.name, "Load");
                             let _ = interp.call_event_handler(&format!("{}_Load", other_form_module.form.name), &load_args);
@@ -1934,6 +1929,8 @@ fn process_side_effects(
                                             let ival = match &value {
                                                 vybe_runtime::Value::Integer(i) => *i,
                                                 vybe_runtime::Value::Double(d) => *d as i32,
+                                                vybe_runtime::Value::Decimal(d) => d.to_i64().unwrap_or(0) as i32,
+                                                v if vybe_runtime::integral::IntType::of(v).is_some() => v.as_integer().unwrap_or(0),
                                                 vybe_runtime::Value::String(s) => s.parse().unwrap_or(0),
                                                 _ => 0,
                                             };
@@ -2040,6 +2037,8 @@ fn process_side_effects(
                                                 vybe_runtime::Value::String(s) => Some(vybe_forms::PropertyValue::String(s.clone())),
                                                 vybe_runtime::Value::Boolean(b) => Some(vybe_forms::PropertyValue::Boolean(*b)),
                                                 vybe_runtime::Value::Double(d) => Some(vybe_forms::PropertyValue::Double(*d)),
+                                                vybe_runtime::Value::Decimal(d) => Some(vybe_forms::PropertyValue::Double(d.to_f64())),
+                                                v if vybe_runtime::integral::IntType::of(v).is_some() => v.as_integer().ok().map(vybe_forms::PropertyValue::Integer),
                                                 // For Object values, extract the "name" field as a string identifier
                                                 // (e.g. BindingSource property set to a BindingSource object → store its name)
                                                 vybe_runtime::Value::Object(obj_ref) => {
@@ -2165,12 +2164,7 @@ fn process_side_effects(
                 let project_read = rp.project.read();
                 if let Some(proj) = project_read.as_ref() {
                     if let Some(form_module) = proj.forms.iter().find(|f| f.form.name.eq_ignore_ascii_case(&form_name)) {
-                        let switch_code = if form_module.is_vbnet() {
-                            format!("{}\n{}", form_module.get_designer_code(), form_module.get_user_code())
-                        } else {
-                            form_module.get_user_code().to_string()
-                        };
-                        if let Ok(prog) = parse_program(&switch_code) {
+                        if let Ok(prog) = crate::runner::parse_form_code(form_module, &proj.define_constants) {
                             let _ = interp.load_module(&form_module.form.name, &prog);
                             let load_args = interp.make_event_handler_args(&form_module.form.name, "Load");
                             let _ = interp.call_event_handler(&format!("{}_Load", form_module.form.name), &load_args);
@@ -2437,17 +2431,15 @@ pub fn FormRunner() -> Element {
                     // Collect all code + resources as owned Strings so we can
                     // move them to the background thread.
                     let resource_entries = crate::runner::collect_resource_entries(proj);
-                    let code_files: Vec<String> = proj.code_files.iter()
-                        .map(|cf| cf.code.clone())
+                    let options = proj.options;
+                    let sandbox = LAUNCH_SANDBOX.with(|cell| cell.borrow().clone());
+                    let constants = proj.define_constants.clone();
+                    let code_files: Vec<(String, String)> = proj.code_files.iter()
+                        .map(|cf| (crate::runner::code_file_source_name(&cf.name), cf.code.clone()))
                         .collect();
-                    let form_sources: Vec<(String, String)> = proj.forms.iter()
-                        .map(|fm| {
-                            let code = if fm.is_vbnet() {
-                                format!("{}\n{}", fm.get_designer_code(), fm.get_user_code())
-                            } else {
-                                fm.get_user_code().to_string()
-                            };
-                            (fm.form.name.clone(), code)
+                    let form_programs: Vec<(String, vybe_parser::Program)> = proj.forms.iter()
+                        .filter_map(|fm| {
+                            crate::runner::parse_form_code(fm, &proj.define_constants).ok().map(|prog| (fm.form.name.clone(), prog))
                         })
                         .collect();
                     drop(project_read);
@@ -2464,27 +2456,27 @@ pub fn FormRunner() -> Element {
                     // Spawn the interpreter on a background thread
                     std::thread::spawn(move || {
                         let mut interp = Interpreter::new();
+                        interp.options = options;
+                        interp.set_sandbox(sandbox);
                         interp.console_tx = Some(msg_tx.clone());
                         interp.console_input_rx = Some(input_rx);
                         interp.register_resource_entries(resource_entries);
 
                         // Load all code files
-                        for code in &code_files {
-                            if let Ok(program) = parse_program(code) {
+                        for (file_name, code) in &code_files {
+                            if let Ok(program) = parse_program_named_with(code, file_name, &constants) {
                                 let _ = interp.load_code_file(&program);
                             }
                         }
                         // Load form modules
-                        for (name, code) in &form_sources {
-                            if let Ok(program) = parse_program(code) {
-                                let _ = interp.load_module(name, &program);
-                            }
+                        for (name, program) in &form_programs {
+                            let _ = interp.load_module(name, program);
                         }
 
                         // Run Sub Main
                         match interp.call_procedure(&vybe_parser::ast::Identifier::new("main"), &[]) {
                             Ok(_) => { let _ = msg_tx.send(ConsoleMessage::Finished); }
-                            Err(e) => { let _ = msg_tx.send(ConsoleMessage::Error(format!("{:?}", e))); }
+                            Err(e) => { let _ = msg_tx.send(ConsoleMessage::Error(e.to_string())); }
                         }
                     });
 
@@ -2494,28 +2486,27 @@ pub fn FormRunner() -> Element {
                 // ── Form mode ───────────────────────────────────────────
                 if let Some(startup_form_module) = proj.get_startup_form() {
                     let form = startup_form_module.form.clone();
-                    let form_code = if startup_form_module.is_vbnet() {
-                        format!("{}\n{}", startup_form_module.get_designer_code(), startup_form_module.get_user_code())
-                    } else {
-                        startup_form_module.get_user_code().to_string()
-                    };
+                    let form_program = crate::runner::parse_form_code(startup_form_module, &proj.define_constants);
                     drop(project_read);
 
                     runtime_form.set(Some(form.clone()));
 
                     let mut interp = Interpreter::new();
+                    interp.set_sandbox(LAUNCH_SANDBOX.with(|cell| cell.borrow().clone()));
 
                     // Register resources from all project resource files + form resources
                     if let Some(proj) = rp.project.read().as_ref() {
                         let entries = crate::runner::collect_resource_entries(proj);
                         interp.register_resource_entries(entries);
+                        interp.options = proj.options;
                     }
 
                     // Load all code files (global scope + class definitions)
                     let project_read = rp.project.read();
                     if let Some(proj) = project_read.as_ref() {
                         for code_file in &proj.code_files {
-                            if let Ok(program) = parse_program(&code_file.code) {
+                            let file_name = crate::runner::code_file_source_name(&code_file.name);
+                            if let Ok(program) = parse_program_named_with(&code_file.code, &file_name, &proj.define_constants) {
                                 let _ = interp.run(&program);
                             }
                         }
@@ -2523,11 +2514,11 @@ pub fn FormRunner() -> Element {
                     drop(project_read);
 
                     // Now load the startup form code (registers the class)
-                    match parse_program(&form_code) {
+                    match form_program {
                         Ok(program) => {
                             parse_error.set(None);
                             if let Err(e) = interp.run(&program) {
-                                parse_error.set(Some(format!("Runtime Load Error: {:?}", e)));
+                                parse_error.set(Some(format!("Runtime Load Error: {}", e)));
                             } else {
                                 // ── Clean .NET-style form initialization ─────
                                 // 1. Create the form instance via the interpreter.
@@ -2585,7 +2576,7 @@ pub fn FormRunner() -> Element {
                                         }
                                     }
                                     Err(e) => {
-                                        eprintln!("Form instance creation error: {:?}", e);
+                                        eprintln!("Form instance creation error: {}", e);
                                         // Fallback: still try Form_Load as a free sub
                                         let load_args = interp.make_event_handler_args("Form", "Load");
                                         let _ = interp.call_event_handler("Form_Load", &load_args);
@@ -2685,7 +2676,7 @@ pub fn FormRunner() -> Element {
                                             .collect()
                                     } else { Vec::new() }
                                 };
-                                for ctrl_obj in bs_objects {
+                                'sources: for ctrl_obj in bs_objects {
                                     let (bs_name, position) = {
                                         let borrow = ctrl_obj.borrow();
                                         let name = borrow.fields.get("name").map(|v| v.as_string()).unwrap_or_default();
@@ -2695,7 +2686,13 @@ pub fn FormRunner() -> Element {
                                         (name, pos)
                                     };
                                     if bs_name.is_empty() { continue; }
-                                    let count = interp.binding_source_row_count_filtered(&Value::Object(ctrl_obj.clone()));
+                                    let count = match interp.binding_source_row_count_filtered(&Value::Object(ctrl_obj.clone())) {
+                                        Ok(count) => count,
+                                        Err(e) => {
+                                            parse_error.set(Some(format!("Runtime Error: {}", e)));
+                                            break;
+                                        }
+                                    };
                                     if count > 0 {
                                         let ds = ctrl_obj.borrow().fields.get("__datasource").cloned().unwrap_or(Value::Nothing);
                                         interp.side_effects.push_back(RuntimeSideEffect::BindingPositionChanged {
@@ -2704,7 +2701,10 @@ pub fn FormRunner() -> Element {
                                             count,
                                         });
                                         // Refresh DataBindings.Add bound controls (TextBox, Label, CheckBox, etc.)
-                                        interp.refresh_bindings_filtered(&ctrl_obj, &ds, position);
+                                        if let Err(e) = interp.refresh_bindings_filtered(&ctrl_obj, &ds, position) {
+                                            parse_error.set(Some(format!("Runtime Error: {}", e)));
+                                            break;
+                                        }
                                         // Emit DataSourceChanged for controls bound via DataSource = bs
                                         // (DataGridView, ComboBox, ListBox, etc.)
                                         let bound_controls: Vec<String> = ctrl_obj.borrow()
@@ -2715,7 +2715,13 @@ pub fn FormRunner() -> Element {
                                             .unwrap_or_default();
                                         let bs_val = Value::Object(ctrl_obj.clone());
                                         for bound_ctrl_name in bound_controls {
-                                            let (columns, rows) = interp.get_datasource_table_data_filtered(&bs_val);
+                                            let (columns, rows) = match interp.get_datasource_table_data_filtered(&bs_val) {
+                                                Ok(data) => data,
+                                                Err(e) => {
+                                                    parse_error.set(Some(format!("Runtime Error: {}", e)));
+                                                    break 'sources;
+                                                }
+                                            };
                                             interp.side_effects.push_back(RuntimeSideEffect::DataSourceChanged {
                                                 control_name: bound_ctrl_name,
                                                 columns,
@@ -2730,7 +2736,7 @@ pub fn FormRunner() -> Element {
                             }
                         }
                         Err(e) => {
-                            parse_error.set(Some(format!("Parse Error: {:?}", e)));
+                            parse_error.set(Some(format!("Parse Error: {}", e)));
                         }
                     }
                     interpreter.set(Some(interp));